@@ -0,0 +1,193 @@
+//! Handling of reports received as datagrams, each holding either a
+//! whole report or a fragment of one, as the UDP and unix socket
+//! collectors receive them.
+
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Utc};
+
+use modality_probe::{wire::ReportWireError, ProbeId};
+
+use crate::{
+    metrics::{ReceivedReport, ReportTracker},
+    reassembly::{self, Reassembler},
+    Report, SequenceNumber, SerializationError,
+};
+
+/// What came of a datagram
+#[derive(Debug, Default)]
+pub struct HandledDatagram {
+    /// The probe id and sequence number of the report the datagram held
+    /// or completed, if any
+    pub report: Option<(ProbeId, SequenceNumber)>,
+    /// The reports which are now ready to be written
+    pub released: Vec<ReceivedReport>,
+}
+
+/// Count a datagram received at `receive_time`, reassemble it if it's a
+/// report fragment, and hand the report it holds or completes to the
+/// tracker. Datagrams which can't be parsed are counted as discarded,
+/// with a warning.
+pub fn handle_datagram(
+    bytes: &[u8],
+    receive_time: DateTime<Utc>,
+    reassembler: &mut Reassembler,
+    tracker: &mut ReportTracker,
+) -> HandledDatagram {
+    tracker.metrics.bytes_accumulated = tracker
+        .metrics
+        .bytes_accumulated
+        .saturating_add(bytes.len() as u64);
+
+    let reassembled;
+    let report_bytes = if reassembly::is_fragment(bytes) {
+        tracker.metrics.fragments_received = tracker.metrics.fragments_received.saturating_add(1);
+        let fragment = reassembler.push(bytes);
+        for incomplete in reassembler.take_incomplete() {
+            eprintln!("{}", incomplete);
+            tracker.metrics.flag_incomplete();
+        }
+        match fragment {
+            Ok(Some(bytes)) => {
+                reassembled = bytes;
+                &reassembled[..]
+            }
+            Ok(None) => return HandledDatagram::default(),
+            Err(e) => {
+                eprintln!(
+                    "Error parsing a message as a report fragment ({}), throwing away {} bytes",
+                    e,
+                    bytes.len()
+                );
+                tracker.metrics.bytes_discarded = tracker
+                    .metrics
+                    .bytes_discarded
+                    .saturating_add(bytes.len() as u64);
+                return HandledDatagram::default();
+            }
+        }
+    } else {
+        bytes
+    };
+
+    match Report::try_from(report_bytes) {
+        Ok(report) => {
+            tracker.metrics.flag_parsed(report_bytes);
+            HandledDatagram {
+                report: Some((report.probe_id, report.seq_num)),
+                released: tracker.push(report, receive_time),
+            }
+        }
+        Err(e) => {
+            if let SerializationError::ReportWireError(ReportWireError::ChecksumMismatch) = e {
+                eprintln!(
+                    "Report failed its checksum, throwing away {} bytes",
+                    report_bytes.len()
+                );
+            } else {
+                eprintln!(
+                    "Error parsing a message as a report, throwing away {} bytes",
+                    report_bytes.len()
+                );
+            }
+            tracker.metrics.flag_discarded(report_bytes.len(), &e);
+            HandledDatagram::default()
+        }
+    }
+}
+
+/// Flag the reports still missing fragments as incomplete, then release
+/// every report the tracker holds, e.g. when a collector shuts down
+pub fn finish_datagrams(
+    reassembler: &mut Reassembler,
+    tracker: &mut ReportTracker,
+) -> Vec<ReceivedReport> {
+    for incomplete in reassembler.finish() {
+        eprintln!("{}", incomplete);
+        tracker.metrics.flag_incomplete();
+    }
+    tracker.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem::MaybeUninit;
+
+    use modality_probe::{
+        wire::WireFragment, EventId, ModalityProbe, NanosecondResolution, Probe,
+        RestartCounterProvider, WallClockId,
+    };
+
+    use super::*;
+
+    #[test]
+    fn whole_and_fragmented_reports_are_tracked() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.record_event(EventId::new(1).unwrap());
+        let mut report = [0u8; 512];
+        let n_bytes = probe.report(&mut report).unwrap().unwrap().get();
+        let whole = report[..n_bytes].to_vec();
+        for i in 1..=20 {
+            probe.record_event(EventId::new(i).unwrap());
+        }
+        probe.set_checksums(true);
+        let mut fragment = [0u8; 64];
+        let mut fragments = Vec::new();
+        probe
+            .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))
+            .unwrap()
+            .unwrap();
+        assert!(fragments.len() > 1);
+        assert!(WireFragment::new(&fragments[0][..]).is_ok());
+
+        let mut reassembler = Reassembler::new();
+        let mut tracker = ReportTracker::new(0);
+        let now = Utc::now();
+        let handled = handle_datagram(&whole, now, &mut reassembler, &mut tracker);
+        assert_eq!(
+            handled.report,
+            Some((ProbeId::new(1).unwrap(), SequenceNumber(0)))
+        );
+        assert_eq!(handled.released.len(), 1);
+
+        let (last, rest) = fragments.split_last().unwrap();
+        for f in rest {
+            let handled = handle_datagram(f, now, &mut reassembler, &mut tracker);
+            assert_eq!(handled.report, None);
+            assert!(handled.released.is_empty());
+        }
+        let handled = handle_datagram(last, now, &mut reassembler, &mut tracker);
+        assert_eq!(
+            handled.report,
+            Some((ProbeId::new(1).unwrap(), SequenceNumber(1)))
+        );
+        assert_eq!(handled.released.len(), 1);
+
+        let handled = handle_datagram(&[1, 2, 3], now, &mut reassembler, &mut tracker);
+        assert_eq!(handled.report, None);
+
+        // A report which never completes is flagged at shutdown
+        let handled = handle_datagram(&fragments[0], now, &mut reassembler, &mut tracker);
+        assert_eq!(handled.report, None);
+        assert!(finish_datagrams(&mut reassembler, &mut tracker).is_empty());
+
+        let metrics = &tracker.metrics;
+        let total_len =
+            whole.len() + fragments.iter().map(Vec::len).sum::<usize>() + 3 + fragments[0].len();
+        assert_eq!(metrics.bytes_accumulated, total_len as u64);
+        assert_eq!(metrics.fragments_received, fragments.len() as u64 + 1);
+        assert_eq!(metrics.incomplete_reports, 1);
+        assert_eq!(metrics.reports_discarded, 1);
+        assert_eq!(metrics.bytes_discarded, 3);
+        assert_eq!(metrics.checksums_verified, 1);
+        assert_eq!(metrics.num_reports(), 2);
+    }
+}
@@ -0,0 +1,370 @@
+//! Decoders for captures where the binary reports are wrapped in a framing
+//! or text encoding, such as the output of a serial console
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+use log::{debug, warn};
+
+/// How the reports in the input are framed
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum Framing {
+    /// Reports are written back to back, without any framing
+    #[default]
+    Raw,
+    /// Consistent Overhead Byte Stuffing, with frames delimited by zero bytes
+    Cobs,
+    /// SLIP (RFC 1055), with frames delimited by `0xC0` bytes
+    Slip,
+    /// Each frame is preceded by its length, as a 4 byte little endian integer
+    LenPrefix,
+    /// Lines of hex digits; other lines are skipped
+    HexLines,
+    /// Lines of standard base64; other lines are skipped
+    Base64Lines,
+}
+
+impl Framing {
+    /// Names of each framing, as accepted by `from_str`
+    pub const NAMES: &'static [&'static str] = &[
+        "raw",
+        "cobs",
+        "slip",
+        "len-prefix",
+        "hex-lines",
+        "base64-lines",
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Framing::Raw => "raw",
+            Framing::Cobs => "cobs",
+            Framing::Slip => "slip",
+            Framing::LenPrefix => "len-prefix",
+            Framing::HexLines => "hex-lines",
+            Framing::Base64Lines => "base64-lines",
+        }
+    }
+}
+
+impl fmt::Display for Framing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Framing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Framing::Raw),
+            "cobs" => Ok(Framing::Cobs),
+            "slip" => Ok(Framing::Slip),
+            "len-prefix" => Ok(Framing::LenPrefix),
+            "hex-lines" => Ok(Framing::HexLines),
+            "base64-lines" => Ok(Framing::Base64Lines),
+            _ => Err(format!("Unknown framing '{}'", s)),
+        }
+    }
+}
+
+/// Wrap `reader` so that reading from it produces the decoded contents of
+/// each frame, back to back. Frames which can't be decoded are skipped.
+pub fn framed_reader<'a, R: Read + 'a>(framing: Framing, reader: R) -> Box<dyn Read + 'a> {
+    match framing {
+        Framing::Raw => Box::new(reader),
+        _ => Box::new(FrameDecoder {
+            framing,
+            reader: BufReader::new(reader),
+            frame: Vec::new(),
+            payload: Vec::new(),
+            payload_pos: 0,
+        }),
+    }
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+struct FrameDecoder<R: Read> {
+    framing: Framing,
+    reader: BufReader<R>,
+    frame: Vec<u8>,
+    payload: Vec<u8>,
+    payload_pos: usize,
+}
+
+impl<R: Read> FrameDecoder<R> {
+    /// Read the next frame and decode it into the payload buffer, returning
+    /// false once the input is exhausted
+    fn next_frame(&mut self) -> io::Result<bool> {
+        self.frame.clear();
+        self.payload.clear();
+        self.payload_pos = 0;
+
+        let delimiter = match self.framing {
+            Framing::Cobs => 0,
+            Framing::Slip => SLIP_END,
+            Framing::HexLines | Framing::Base64Lines => b'\n',
+            Framing::LenPrefix => return self.next_len_prefixed_frame(),
+            Framing::Raw => unreachable!("Raw input isn't decoded"),
+        };
+        if self.reader.read_until(delimiter, &mut self.frame)? == 0 {
+            return Ok(false);
+        }
+        if self.frame.last() == Some(&delimiter) {
+            self.frame.pop();
+        }
+
+        let decoded = match self.framing {
+            Framing::Cobs => cobs_decode(&self.frame, &mut self.payload),
+            Framing::Slip => slip_decode(&self.frame, &mut self.payload),
+            Framing::HexLines => {
+                line_decode(&self.frame, &mut self.payload, |l| hex::decode(l).ok())
+            }
+            Framing::Base64Lines => {
+                line_decode(&self.frame, &mut self.payload, |l| base64::decode(l).ok())
+            }
+            Framing::LenPrefix | Framing::Raw => unreachable!(),
+        };
+        if let Err(e) = decoded {
+            warn!(
+                "Throwing away {} byte {} frame, {}",
+                self.frame.len(),
+                self.framing,
+                e
+            );
+            self.payload.clear();
+        }
+        Ok(true)
+    }
+
+    fn next_len_prefixed_frame(&mut self) -> io::Result<bool> {
+        let mut len_bytes = [0_u8; 4];
+        let prefix_len = read_up_to(&mut self.reader, &mut len_bytes)?;
+        if prefix_len == 0 {
+            return Ok(false);
+        }
+        if prefix_len < len_bytes.len() {
+            warn!("Throwing away {} byte truncated length prefix", prefix_len);
+            return Ok(false);
+        }
+        let len = u32::from_le_bytes(len_bytes);
+        (&mut self.reader)
+            .take(u64::from(len))
+            .read_to_end(&mut self.payload)?;
+        if self.payload.len() < len as usize {
+            warn!(
+                "Throwing away truncated frame, {} of {} bytes",
+                self.payload.len(),
+                len
+            );
+            self.payload.clear();
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for FrameDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.payload_pos == self.payload.len() {
+            if !self.next_frame()? {
+                return Ok(0);
+            }
+        }
+        let remaining = &self.payload[self.payload_pos..];
+        let n = remaining.len().min(buf.len());
+        buf[..n].copy_from_slice(&remaining[..n]);
+        self.payload_pos += n;
+        Ok(n)
+    }
+}
+
+/// Fill as much of `buf` as the reader has left, returning how much was read
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Decode a COBS frame without its zero delimiter
+fn cobs_decode(frame: &[u8], payload: &mut Vec<u8>) -> Result<(), &'static str> {
+    let mut rest = frame;
+    while let Some((&code, tail)) = rest.split_first() {
+        let len = usize::from(code).checked_sub(1).ok_or("zero code byte")?;
+        if len > tail.len() {
+            return Err("code byte points past the end of the frame");
+        }
+        payload.extend_from_slice(&tail[..len]);
+        rest = &tail[len..];
+        // The longest blocks aren't followed by a zero
+        if code != 0xFF && !rest.is_empty() {
+            payload.push(0);
+        }
+    }
+    Ok(())
+}
+
+/// Decode a SLIP frame without its end delimiter
+fn slip_decode(frame: &[u8], payload: &mut Vec<u8>) -> Result<(), &'static str> {
+    let mut bytes = frame.iter();
+    while let Some(&b) = bytes.next() {
+        if b != SLIP_ESC {
+            payload.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(&SLIP_ESC_END) => payload.push(SLIP_END),
+            Some(&SLIP_ESC_ESC) => payload.push(SLIP_ESC),
+            _ => return Err("invalid escape sequence"),
+        }
+    }
+    Ok(())
+}
+
+/// Decode a line of text, skipping it if it isn't encoded data
+fn line_decode<F>(line: &[u8], payload: &mut Vec<u8>, decode: F) -> Result<(), &'static str>
+where
+    F: FnOnce(&[u8]) -> Option<Vec<u8>>,
+{
+    let line = trim_ascii_whitespace(line);
+    if line.is_empty() {
+        return Ok(());
+    }
+    match decode(line) {
+        Some(decoded) => payload.extend_from_slice(&decoded),
+        None => debug!("Skipping line which isn't encoded data"),
+    }
+    Ok(())
+}
+
+fn trim_ascii_whitespace(mut bytes: &[u8]) -> &[u8] {
+    while let Some((first, rest)) = bytes.split_first() {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let Some((last, rest)) = bytes.split_last() {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(framing: Framing, input: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        framed_reader(framing, input).read_to_end(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn cobs() {
+        assert_eq!(
+            decode_all(
+                Framing::Cobs,
+                &[0x03, 0x11, 0x22, 0x02, 0x33, 0x00, 0x01, 0x01, 0x00]
+            ),
+            vec![0x11, 0x22, 0x00, 0x33, 0x00]
+        );
+        // 254 non-zero bytes need no trailing zero
+        let mut frame = vec![0xFF];
+        frame.extend(1..=254_u8);
+        frame.push(0x00);
+        assert_eq!(
+            decode_all(Framing::Cobs, &frame),
+            (1..=254_u8).collect::<Vec<_>>()
+        );
+        // A code byte past the end of the frame discards it
+        assert_eq!(
+            decode_all(Framing::Cobs, &[0x05, 0x11, 0x00, 0x02, 0x22, 0x00]),
+            vec![0x22]
+        );
+    }
+
+    #[test]
+    fn slip() {
+        assert_eq!(
+            decode_all(
+                Framing::Slip,
+                &[
+                    SLIP_END,
+                    0x11,
+                    SLIP_ESC,
+                    SLIP_ESC_END,
+                    SLIP_ESC,
+                    SLIP_ESC_ESC,
+                    SLIP_END,
+                    0x22
+                ]
+            ),
+            vec![0x11, SLIP_END, SLIP_ESC, 0x22]
+        );
+        // An invalid escape discards the frame
+        assert_eq!(
+            decode_all(Framing::Slip, &[SLIP_ESC, 0x11, SLIP_END, 0x22, SLIP_END]),
+            vec![0x22]
+        );
+    }
+
+    #[test]
+    fn len_prefix() {
+        assert_eq!(
+            decode_all(
+                Framing::LenPrefix,
+                &[2, 0, 0, 0, 0x11, 0x22, 0, 0, 0, 0, 1, 0, 0, 0, 0x33]
+            ),
+            vec![0x11, 0x22, 0x33]
+        );
+        // A truncated frame is discarded
+        assert_eq!(
+            decode_all(Framing::LenPrefix, &[1, 0, 0, 0, 0x11, 4, 0, 0, 0, 0x22]),
+            vec![0x11]
+        );
+    }
+
+    #[test]
+    fn hex_lines() {
+        assert_eq!(
+            decode_all(
+                Framing::HexLines,
+                b"boot ok\r\n0011aA\r\n\r\n  ff \nsensor=12\n22"
+            ),
+            vec![0x00, 0x11, 0xAA, 0xFF, 0x22]
+        );
+    }
+
+    #[test]
+    fn base64_lines() {
+        assert_eq!(
+            decode_all(Framing::Base64Lines, b"boot ok\nABEi\r\n\n/w==\nx=1\n"),
+            vec![0x00, 0x11, 0x22, 0xFF]
+        );
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for name in Framing::NAMES {
+            assert_eq!(&name.parse::<Framing>().unwrap().to_string(), name);
+        }
+        assert!("crlf".parse::<Framing>().is_err());
+    }
+}
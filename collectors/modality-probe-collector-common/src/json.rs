@@ -1,6 +1,8 @@
+use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
 
-use super::{Error, ReportLogEntry};
+use super::{rotation, rotation::Compression, Error, ReportLogEntry};
 
 pub fn write_log_entries<'a, W: Write, E: IntoIterator<Item = &'a ReportLogEntry>>(
     w: &mut W,
@@ -24,8 +26,36 @@ impl From<&serde_json::error::Error> for Error {
     }
 }
 
+/// Read the log entries written by `write_log_entries`, decompressing them
+/// first if they're gzip or zstd compressed
 pub fn read_log_entries<R: Read>(r: &mut R) -> Result<Vec<ReportLogEntry>, Error> {
-    let br = BufReader::new(r);
+    let mut br = BufReader::new(r);
+    let start = br
+        .fill_buf()
+        .map_err(|e| Error::Serialization(format!("unable to read log: {}", e)))?;
+    match Compression::detect(start) {
+        Compression::None => read_log_lines(br),
+        Compression::Gzip => {
+            read_log_lines(BufReader::new(flate2::bufread::MultiGzDecoder::new(br)))
+        }
+        Compression::Zstd => read_log_lines(BufReader::new(zstd::Decoder::with_buffer(br)?)),
+    }
+}
+
+/// Read the log entries in the file at `path`, along with any segments
+/// that have been rotated out of it, in the order they were written. See
+/// the `rotation` module.
+pub fn read_log_entries_from_path<P: AsRef<Path>>(path: P) -> Result<Vec<ReportLogEntry>, Error> {
+    let mut entries = Vec::new();
+    for segment in rotation::segment_paths(path)? {
+        let mut f =
+            File::open(&segment).map_err(|e| Error::Io(format!("{}: {}", segment.display(), e)))?;
+        entries.extend(read_log_entries(&mut f)?);
+    }
+    Ok(entries)
+}
+
+fn read_log_lines<R: BufRead>(br: R) -> Result<Vec<ReportLogEntry>, Error> {
     let entries: Result<Vec<ReportLogEntry>, _> = br
         .lines()
         .map(|line| match line {
@@ -34,11 +64,12 @@ pub fn read_log_entries<R: Read>(r: &mut R) -> Result<Vec<ReportLogEntry>, Error
             Err(e) => Err(Error::Serialization(format!("unable to read log: {}", e))),
         })
         .collect();
-    Ok(entries?)
+    entries
 }
 
 #[cfg(test)]
 mod test {
+    use crate::rotation::{Compression, RotatingWriter, RotationPolicy};
     use proptest::prelude::*;
 
     proptest! {
@@ -59,5 +90,36 @@ mod test {
                 Ok(es) => prop_assert_eq!(entries, es),
             }
         }
+
+        #[test]
+        fn round_trip_rotated_segments(
+            entries in proptest::collection::vec(
+                crate::test::arb_log_entry(),
+                0..15
+            ),
+            compression in prop_oneof![
+                Just(Compression::None),
+                Just(Compression::Gzip),
+                Just(Compression::Zstd),
+            ],
+        ) {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("log.jsonl");
+            let policy = RotationPolicy {
+                max_bytes: Some(256),
+                compression,
+                ..Default::default()
+            };
+            let mut w = RotatingWriter::open(&path, policy).unwrap();
+            prop_assert!(super::write_log_entries(&mut w, &entries).is_ok());
+            drop(w);
+
+            let read_back = super::read_log_entries_from_path(&path);
+
+            match read_back {
+                Err(e) => prop_assert!(false, "read_back error: {:?}", e),
+                Ok(es) => prop_assert_eq!(entries, es),
+            }
+        }
     }
 }
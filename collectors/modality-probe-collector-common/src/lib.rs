@@ -1,3 +1,6 @@
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
+
 use std::{convert::TryFrom, io, iter::Peekable, mem};
 
 use chrono::prelude::*;
@@ -15,7 +18,14 @@ use modality_probe::{
     EventId, LogicalClock, ProbeEpoch, ProbeId, ProbeTicks,
 };
 
+pub mod datagram;
+pub mod framing;
 pub mod json;
+pub mod metrics;
+pub mod reassembly;
+pub mod rotation;
+#[cfg(unix)]
+pub mod shm;
 
 assert_eq_size!(LogEntry, u32);
 
@@ -30,9 +40,9 @@ macro_rules! newtype {
             }
         }
 
-        impl Into<$t> for &$name {
-            fn into(self) -> $t {
-                self.0
+        impl From<&$name> for $t {
+            fn from(val: &$name) -> $t {
+                val.0
             }
         }
     };
@@ -100,13 +110,17 @@ pub struct Report {
     pub event_log: Vec<EventLogEntry>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum EventLogEntry {
     Event(EventId),
-    EventWithPayload(EventId, u32),
+    EventWithPayload(EventId, u64),
+    EventWithBlob(EventId, Vec<u8>),
+    /// The event was recorded this many more times right after the
+    /// preceding entry
+    EventRepeated(EventId, u32),
     TraceClock(LogicalClock),
     EventWithTime(Nanoseconds, EventId),
-    EventWithPayloadWithTime(Nanoseconds, EventId, u32),
+    EventWithPayloadWithTime(Nanoseconds, EventId, u64),
     TraceClockWithTime(Nanoseconds, LogicalClock),
     WallClockTime(Nanoseconds),
 }
@@ -120,6 +134,59 @@ impl EventLogEntry {
     }
 }
 
+/// Record an event repeated entry as `count` more occurrences of the
+/// event preceding it. The count is kept as is rather than expanded,
+/// since it comes straight off the wire. Returns false, leaving the
+/// log untouched, if the preceding entry is not a plain event, e.g.
+/// because it was reported separately.
+fn push_repeated_event(event_log: &mut Vec<EventLogEntry>, count: u32) -> bool {
+    match event_log.last() {
+        Some(EventLogEntry::Event(id)) => {
+            let id = *id;
+            event_log.push(EventLogEntry::EventRepeated(id, count));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Combine the high bits from a preceding wide payload high bits
+/// entry, if any, with the low bits from an event with payload entry
+fn widen_payload(high_bits: Option<u32>, low_bits: u32) -> u64 {
+    (u64::from(high_bits.unwrap_or(0)) << 32) | u64::from(low_bits)
+}
+
+/// Add the bytes from a blob data entry to the blob being collected
+fn append_blob_data(blob: &mut Option<Vec<u8>>, data: u32) {
+    blob.get_or_insert_with(Vec::new)
+        .extend_from_slice(&data.to_le_bytes());
+}
+
+/// Drop the padding from a collected blob given the length from
+/// its event with payload entry
+fn finish_blob(mut blob: Vec<u8>, len: u32) -> Vec<u8> {
+    blob.truncate(len as usize);
+    blob
+}
+
+/// The number of log entries needed to carry a blob, every 4 bytes
+/// of which takes up a blob data entry. Even an empty blob has a
+/// blob data entry to tell it apart from an event with payload.
+fn blob_entries(blob: &[u8]) -> usize {
+    let n_chunks = blob.len().div_ceil(mem::size_of::<u32>());
+    2 * usize::max(1, n_chunks) + 2
+}
+
+/// The number of log entries needed to carry an event's payload,
+/// a wide payload needs an extra high bits entry
+fn payload_entries(payload: u64) -> usize {
+    if payload > u64::from(u32::MAX) {
+        4
+    } else {
+        2
+    }
+}
+
 pub mod serde_ns {
     use super::Nanoseconds;
     use serde::{de, Deserialize, Serialize};
@@ -202,7 +269,8 @@ pub struct ReportLogEntry {
     pub wall_clock_id: WallClockId,
 
     /// This entry's data; a frontier
-    /// clock, event, event with payload, or a trace clock
+    /// clock, event, event with payload, or a trace clock, or a
+    /// marker added by the collector
     pub data: LogEntryData,
 
     /// The time this entry was received by the collector
@@ -214,6 +282,11 @@ pub struct ReportLogEntry {
 }
 
 impl ReportLogEntry {
+    /// Sequence index of a `ProbeRestarted` marker. No report holds this
+    /// many entries, so the marker's coordinate never collides with that of
+    /// the first entry in the report it precedes.
+    pub const PROBE_RESTART_SEQUENCE_INDEX: u32 = u32::MAX;
+
     pub fn is_frontier_clock(&self) -> bool {
         matches!(self.data, LogEntryData::FrontierClock(_))
     }
@@ -223,10 +296,16 @@ impl ReportLogEntry {
             LogEntryData::EventWithPayload(id, _) => id.is_internal(),
             LogEntryData::EventWithTime(_, id) => id.is_internal(),
             LogEntryData::EventWithPayloadWithTime(_, id, _) => id.is_internal(),
+            LogEntryData::EventWithBlob(id, _) => id.is_internal(),
+            LogEntryData::EventRepeated(id, _) => id.is_internal(),
             _ => false,
         }
     }
 
+    pub fn is_probe_restart(&self) -> bool {
+        self.data == LogEntryData::ProbeRestarted
+    }
+
     pub fn coordinate(&self) -> String {
         format!(
             "{}:{}:{}:{}:{}",
@@ -243,12 +322,21 @@ impl ReportLogEntry {
 pub enum LogEntryData {
     FrontierClock(LogicalClock),
     Event(EventId),
-    EventWithPayload(EventId, u32),
+    EventWithPayload(EventId, u64),
     TraceClock(LogicalClock),
     EventWithTime(#[serde(with = "NanosecondsDef")] Nanoseconds, EventId),
-    EventWithPayloadWithTime(#[serde(with = "NanosecondsDef")] Nanoseconds, EventId, u32),
+    EventWithPayloadWithTime(#[serde(with = "NanosecondsDef")] Nanoseconds, EventId, u64),
     TraceClockWithTime(#[serde(with = "NanosecondsDef")] Nanoseconds, LogicalClock),
     WallClockTime(#[serde(with = "NanosecondsDef")] Nanoseconds),
+    EventWithBlob(EventId, Vec<u8>),
+    /// The event was recorded this many more times right after the
+    /// preceding entry
+    EventRepeated(EventId, u32),
+    /// Added by the collector where it noticed that the probe restarted.
+    /// Entries from before and after the marker come from different boots
+    /// of the probe, and their sequence numbers and clocks can't be
+    /// compared.
+    ProbeRestarted,
 }
 
 impl LogEntryData {
@@ -273,6 +361,8 @@ impl From<EventLogEntry> for LogEntryData {
             }
             EventLogEntry::TraceClockWithTime(t, lc) => LogEntryData::TraceClockWithTime(t, lc),
             EventLogEntry::WallClockTime(t) => LogEntryData::WallClockTime(t),
+            EventLogEntry::EventWithBlob(id, b) => LogEntryData::EventWithBlob(id, b),
+            EventLogEntry::EventRepeated(id, n) => LogEntryData::EventRepeated(id, n),
         }
     }
 }
@@ -299,10 +389,11 @@ where
 {
     type Item = Report;
     fn next(&mut self) -> Option<Report> {
-        let next = match self.report_items.peek() {
-            Some(e) => e,
-            None => return None,
-        };
+        // Restart markers separate reports, but aren't part of any
+        while self.report_items.peek()?.is_probe_restart() {
+            self.report_items.next();
+        }
+        let next = self.report_items.peek()?;
         let probe_id = next.probe_id;
 
         let seq_num = next.sequence_number;
@@ -326,7 +417,10 @@ where
                 Some(e) => e,
                 None => return Some(report),
             };
-            if next.probe_id != probe_id || next.sequence_number != seq_num {
+            if next.probe_id != probe_id
+                || next.sequence_number != seq_num
+                || next.is_probe_restart()
+            {
                 return Some(report);
             }
 
@@ -373,6 +467,13 @@ where
                 LogEntryData::WallClockTime(t) => {
                     report.event_log.push(EventLogEntry::WallClockTime(t));
                 }
+                LogEntryData::EventWithBlob(e, b) => {
+                    report.event_log.push(EventLogEntry::EventWithBlob(e, b));
+                }
+                LogEntryData::EventRepeated(e, n) => {
+                    report.event_log.push(EventLogEntry::EventRepeated(e, n));
+                }
+                LogEntryData::ProbeRestarted => (),
             }
         }
     }
@@ -459,7 +560,7 @@ pub fn add_log_report_to_entries(
             wall_clock_id,
             probe_id,
             persistent_epoch_counting: log_report.persistent_epoch_counting,
-            data: LogEntryData::from(*event),
+            data: LogEntryData::from(event.clone()),
             clock: probe_clock,
             receive_time,
         });
@@ -468,6 +569,29 @@ pub fn add_log_report_to_entries(
     Ok(())
 }
 
+/// Add a `ProbeRestarted` marker to the log entries, for a restart noticed
+/// before the given report. The marker shares the report's sequence number
+/// but has the sequence index `ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX`.
+pub fn add_probe_restart_to_entries(
+    log_report: &Report,
+    session_id: SessionId,
+    receive_time: DateTime<Utc>,
+    log_entries_buffer: &mut Vec<ReportLogEntry>,
+) {
+    log_entries_buffer.push(ReportLogEntry {
+        session_id,
+        sequence_number: log_report.seq_num,
+        sequence_index: ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX,
+        time_resolution: log_report.time_resolution,
+        wall_clock_id: log_report.wall_clock_id,
+        probe_id: log_report.probe_id,
+        persistent_epoch_counting: log_report.persistent_epoch_counting,
+        data: LogEntryData::ProbeRestarted,
+        clock: log_report.probe_clock,
+        receive_time,
+    });
+}
+
 impl TryFrom<&[u8]> for Report {
     type Error = SerializationError;
     fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
@@ -511,6 +635,8 @@ impl TryFrom<&[u8]> for Report {
         }
 
         let mut paired_wall_clock_time = None;
+        let mut wide_payload_high_bits = None;
+        let mut blob = None;
         let mut interpret_next_as = Next::DontKnow;
         for u32_bytes in payload[clocks_len..].chunks_exact(mem::size_of::<LogEntry>()) {
             let raw = le_bytes::read_u32(u32_bytes);
@@ -583,14 +709,26 @@ impl TryFrom<&[u8]> for Report {
                             raw
                         );
                     }
-                    if let Some(pwct) = paired_wall_clock_time.take() {
+                    if id == EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS {
+                        wide_payload_high_bits = Some(raw);
+                    } else if id == EventId::EVENT_BLOB_DATA {
+                        append_blob_data(&mut blob, raw);
+                    } else if let Some(b) = blob.take() {
                         owned_report
                             .event_log
-                            .push(EventLogEntry::EventWithPayloadWithTime(pwct, id, raw));
-                    } else {
+                            .push(EventLogEntry::EventWithBlob(id, finish_blob(b, raw)));
+                    } else if let Some(pwct) = paired_wall_clock_time.take() {
+                        let payload = widen_payload(wide_payload_high_bits.take(), raw);
                         owned_report
                             .event_log
-                            .push(EventLogEntry::EventWithPayload(id, raw));
+                            .push(EventLogEntry::EventWithPayloadWithTime(pwct, id, payload));
+                    } else if id != EventId::EVENT_PREVIOUS_EVENT_REPEATED
+                        || !push_repeated_event(&mut owned_report.event_log, raw)
+                    {
+                        let payload = widen_payload(wide_payload_high_bits.take(), raw);
+                        owned_report
+                            .event_log
+                            .push(EventLogEntry::EventWithPayload(id, payload));
                     }
                     interpret_next_as = Next::DontKnow;
                 }
@@ -642,6 +780,8 @@ impl Report {
             wall_clock_id: WallClockId::default(),
         };
         let mut paired_wall_clock_time = None;
+        let mut wide_payload_high_bits = None;
+        let mut blob = None;
 
         for entry in log {
             match entry {
@@ -650,19 +790,19 @@ impl Report {
                         owned_report.event_log.push(EventLogEntry::EventWithTime(
                             pwct,
                             ev.interpret_as_event_id()
-                                .ok_or_else(|| SerializationError::InvalidEventId(*ev))?,
+                                .ok_or(SerializationError::InvalidEventId(*ev))?,
                         ));
                     } else {
                         owned_report.event_log.push(EventLogEntry::Event(
                             ev.interpret_as_event_id()
-                                .ok_or_else(|| SerializationError::InvalidEventId(*ev))?,
+                                .ok_or(SerializationError::InvalidEventId(*ev))?,
                         ));
                     }
                 }
                 WholeEntry::Double(first, second) => {
                     if first.has_clock_bit_set() {
                         let id = ProbeId::new(first.interpret_as_logical_clock_probe_id())
-                            .ok_or_else(|| SerializationError::InvalidProbeId(*first))?;
+                            .ok_or(SerializationError::InvalidProbeId(*first))?;
                         let (epoch, ticks) = modality_probe::unpack_clock_word(second.raw());
                         if let Some(pwct) = paired_wall_clock_time.take() {
                             owned_report
@@ -693,13 +833,25 @@ impl Report {
                         debug_assert!(first.has_event_with_payload_bit_set());
                         let ev = first
                             .interpret_as_event_id()
-                            .ok_or_else(|| SerializationError::InvalidEventId(*first))?;
-                        let payload = second.raw();
-                        if let Some(pwct) = paired_wall_clock_time.take() {
+                            .ok_or(SerializationError::InvalidEventId(*first))?;
+                        let raw = second.raw();
+                        if ev == EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS {
+                            wide_payload_high_bits = Some(raw);
+                        } else if ev == EventId::EVENT_BLOB_DATA {
+                            append_blob_data(&mut blob, raw);
+                        } else if let Some(b) = blob.take() {
+                            owned_report
+                                .event_log
+                                .push(EventLogEntry::EventWithBlob(ev, finish_blob(b, raw)));
+                        } else if let Some(pwct) = paired_wall_clock_time.take() {
+                            let payload = widen_payload(wide_payload_high_bits.take(), raw);
                             owned_report
                                 .event_log
                                 .push(EventLogEntry::EventWithPayloadWithTime(pwct, ev, payload));
-                        } else {
+                        } else if ev != EventId::EVENT_PREVIOUS_EVENT_REPEATED
+                            || !push_repeated_event(&mut owned_report.event_log, raw)
+                        {
+                            let payload = widen_payload(wide_payload_high_bits.take(), raw);
                             owned_report
                                 .event_log
                                 .push(EventLogEntry::EventWithPayload(ev, payload));
@@ -712,7 +864,7 @@ impl Report {
     }
 
     pub fn write_into_le_bytes(&self, bytes: &mut [u8]) -> Result<usize, SerializationError> {
-        if self.frontier_clocks.len() > std::u16::MAX as usize {
+        if self.frontier_clocks.len() > u16::MAX as usize {
             return Err(SerializationError::TooManyFrontierClocks(
                 self.frontier_clocks.len(),
             ));
@@ -739,19 +891,23 @@ impl Report {
             .iter()
             .map(|e| match e {
                 EventLogEntry::Event(_) => 1,
-                EventLogEntry::EventWithPayload(_, _) => 2,
+                EventLogEntry::EventWithPayload(_, p) => payload_entries(*p),
                 EventLogEntry::TraceClock(_) => entries_per_logical_clock,
 
                 EventLogEntry::EventWithTime(_, _) => 1 + entries_per_time,
-                EventLogEntry::EventWithPayloadWithTime(_, _, _) => 2 + entries_per_time,
+                EventLogEntry::EventWithPayloadWithTime(_, _, p) => {
+                    payload_entries(*p) + entries_per_time
+                }
                 EventLogEntry::TraceClockWithTime(_, _) => {
                     entries_per_logical_clock + entries_per_time
                 }
                 EventLogEntry::WallClockTime(_) => entries_per_time,
+                EventLogEntry::EventWithBlob(_, b) => blob_entries(b),
+                EventLogEntry::EventRepeated(_, _) => 2,
             })
             .sum();
 
-        if num_u32_entries > std::u32::MAX as usize {
+        if num_u32_entries > u32::MAX as usize {
             return Err(SerializationError::TooManyLogEntries(num_u32_entries));
         }
 
@@ -780,6 +936,29 @@ impl Report {
             bc
         };
 
+        let write_event_with_payload = |id: &EventId, p: &u64, buffer: &mut [u8]| -> usize {
+            let mut bc = 0;
+            let (entry_a, entry_b) = LogEntry::event_with_payload(*id, *p as u32);
+            le_bytes::write_u32(&mut buffer[bc..], entry_a.raw());
+            bc += mem::size_of::<u32>();
+            le_bytes::write_u32(&mut buffer[bc..], entry_b.raw());
+            bc += mem::size_of::<u32>();
+            bc
+        };
+
+        let write_wide_payload_high_bits = |p: &u64, buffer: &mut [u8]| -> usize {
+            if payload_entries(*p) == 2 {
+                return 0;
+            }
+            let mut bc = 0;
+            let (entry_a, entry_b) = LogEntry::wide_payload_high_bits(*p);
+            le_bytes::write_u32(&mut buffer[bc..], entry_a.raw());
+            bc += mem::size_of::<u32>();
+            le_bytes::write_u32(&mut buffer[bc..], entry_b.raw());
+            bc += mem::size_of::<u32>();
+            bc
+        };
+
         let mut byte_cursor = n_clock_bytes;
         for src_entry in self.event_log.iter() {
             match src_entry {
@@ -789,11 +968,8 @@ impl Report {
                     byte_cursor += mem::size_of::<u32>();
                 }
                 EventLogEntry::EventWithPayload(id, p) => {
-                    let (entry_a, entry_b) = LogEntry::event_with_payload(*id, *p);
-                    le_bytes::write_u32(&mut payload[byte_cursor..], entry_a.raw());
-                    byte_cursor += mem::size_of::<u32>();
-                    le_bytes::write_u32(&mut payload[byte_cursor..], entry_b.raw());
-                    byte_cursor += mem::size_of::<u32>();
+                    byte_cursor += write_wide_payload_high_bits(p, &mut payload[byte_cursor..]);
+                    byte_cursor += write_event_with_payload(id, p, &mut payload[byte_cursor..]);
                 }
                 EventLogEntry::TraceClock(lc) => {
                     let (entry_a, entry_b) = LogEntry::clock(*lc);
@@ -809,12 +985,9 @@ impl Report {
                     byte_cursor += mem::size_of::<u32>();
                 }
                 EventLogEntry::EventWithPayloadWithTime(t, id, p) => {
+                    byte_cursor += write_wide_payload_high_bits(p, &mut payload[byte_cursor..]);
                     byte_cursor += write_paired_time(t, &mut payload[byte_cursor..]);
-                    let (entry_a, entry_b) = LogEntry::event_with_payload(*id, *p);
-                    le_bytes::write_u32(&mut payload[byte_cursor..], entry_a.raw());
-                    byte_cursor += mem::size_of::<u32>();
-                    le_bytes::write_u32(&mut payload[byte_cursor..], entry_b.raw());
-                    byte_cursor += mem::size_of::<u32>();
+                    byte_cursor += write_event_with_payload(id, p, &mut payload[byte_cursor..]);
                 }
                 EventLogEntry::TraceClockWithTime(t, lc) => {
                     byte_cursor += write_paired_time(t, &mut payload[byte_cursor..]);
@@ -831,6 +1004,27 @@ impl Report {
                     le_bytes::write_u32(&mut payload[byte_cursor..], entry_b.raw());
                     byte_cursor += mem::size_of::<u32>();
                 }
+                EventLogEntry::EventWithBlob(id, b) => {
+                    let n_chunks = blob_entries(b) / 2 - 1;
+                    for i in 0..n_chunks {
+                        let start = usize::min(i * mem::size_of::<u32>(), b.len());
+                        let end = usize::min(start + mem::size_of::<u32>(), b.len());
+                        let (entry_a, entry_b) = LogEntry::blob_data(&b[start..end]);
+                        le_bytes::write_u32(&mut payload[byte_cursor..], entry_a.raw());
+                        byte_cursor += mem::size_of::<u32>();
+                        le_bytes::write_u32(&mut payload[byte_cursor..], entry_b.raw());
+                        byte_cursor += mem::size_of::<u32>();
+                    }
+                    let len = b.len() as u64;
+                    byte_cursor += write_event_with_payload(id, &len, &mut payload[byte_cursor..]);
+                }
+                EventLogEntry::EventRepeated(_, n) => {
+                    byte_cursor += write_event_with_payload(
+                        &EventId::EVENT_PREVIOUS_EVENT_REPEATED,
+                        &u64::from(*n),
+                        &mut payload[byte_cursor..],
+                    );
+                }
             }
         }
 
@@ -884,6 +1078,15 @@ pub(crate) mod test {
         ]
     }
 
+    /// Event repeated, wide payload high bits and blob data entries
+    /// are folded into the entries around them when a report is
+    /// decoded, so they can't be generated as event ids of their own
+    pub fn is_folded_internal_id(id: EventId) -> bool {
+        id == EventId::EVENT_PREVIOUS_EVENT_REPEATED
+            || id == EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS
+            || id == EventId::EVENT_BLOB_DATA
+    }
+
     prop_compose! {
         pub fn arb_probe_id()(raw_id in 1..=ProbeId::MAX_ID) -> ProbeId {
             ProbeId::new(raw_id).unwrap()
@@ -936,29 +1139,34 @@ pub(crate) mod test {
 
     pub fn arb_log_entry_data() -> impl Strategy<Value = LogEntryData> {
         let fc = arb_logical_clock()
-            .prop_map(|lc| LogEntryData::FrontierClock(lc))
+            .prop_map(LogEntryData::FrontierClock)
             .boxed();
-        let eid = arb_event_id()
-            .prop_map(|id| LogEntryData::Event(id))
-            .boxed();
-        let eid_wp = (arb_event_id(), any::<u32>())
+        let eid = arb_event_id().prop_map(LogEntryData::Event).boxed();
+        let eid_wp = (arb_event_id(), any::<u64>())
             .prop_map(|(id, p)| LogEntryData::EventWithPayload(id, p))
             .boxed();
         let tc = arb_logical_clock()
-            .prop_map(|lc| LogEntryData::TraceClock(lc))
+            .prop_map(LogEntryData::TraceClock)
             .boxed();
         let eid_wt = (gen_wall_clock_time(), arb_event_id())
             .prop_map(|(t, id)| LogEntryData::EventWithTime(t, id))
             .boxed();
-        let eid_wp_wt = (gen_wall_clock_time(), arb_event_id(), any::<u32>())
+        let eid_wp_wt = (gen_wall_clock_time(), arb_event_id(), any::<u64>())
             .prop_map(|(t, id, p)| LogEntryData::EventWithPayloadWithTime(t, id, p))
             .boxed();
         let tc_wt = (gen_wall_clock_time(), arb_logical_clock())
             .prop_map(|(t, lc)| LogEntryData::TraceClockWithTime(t, lc))
             .boxed();
         let wct = gen_wall_clock_time()
-            .prop_map(|t| LogEntryData::WallClockTime(t))
+            .prop_map(LogEntryData::WallClockTime)
+            .boxed();
+        let eid_wb = (arb_event_id(), arb_blob())
+            .prop_filter("folded internal event", |(id, _)| {
+                !is_folded_internal_id(*id)
+            })
+            .prop_map(|(id, b)| LogEntryData::EventWithBlob(id, b))
             .boxed();
+        let restart = Just(LogEntryData::ProbeRestarted).boxed();
         fc.prop_union(eid)
             .or(eid_wt)
             .or(eid_wp)
@@ -966,16 +1174,19 @@ pub(crate) mod test {
             .or(tc)
             .or(tc_wt)
             .or(wct)
+            .or(eid_wb)
+            .or(restart)
     }
 
     pub fn arb_event_log_entry() -> impl Strategy<Value = EventLogEntry> {
         let tc = arb_logical_clock()
-            .prop_map(|lc| EventLogEntry::TraceClock(lc))
-            .boxed();
-        let eid = arb_event_id()
-            .prop_map(|id| EventLogEntry::Event(id))
+            .prop_map(EventLogEntry::TraceClock)
             .boxed();
-        let eid_wp = (arb_event_id(), any::<u32>())
+        let eid = arb_event_id().prop_map(EventLogEntry::Event).boxed();
+        let eid_wp = (arb_event_id(), any::<u64>())
+            .prop_filter("folded internal event", |(id, _)| {
+                !is_folded_internal_id(*id)
+            })
             .prop_map(|(id, p)| EventLogEntry::EventWithPayload(id, p))
             .boxed();
         let tc_wt = (gen_wall_clock_time(), arb_logical_clock())
@@ -984,11 +1195,20 @@ pub(crate) mod test {
         let eid_wt = (gen_wall_clock_time(), arb_event_id())
             .prop_map(|(t, id)| EventLogEntry::EventWithTime(t, id))
             .boxed();
-        let eid_wp_wt = (gen_wall_clock_time(), arb_event_id(), any::<u32>())
+        let eid_wp_wt = (gen_wall_clock_time(), arb_event_id(), any::<u64>())
+            .prop_filter("folded internal event", |(_, id, _)| {
+                !is_folded_internal_id(*id)
+            })
             .prop_map(|(t, id, p)| EventLogEntry::EventWithPayloadWithTime(t, id, p))
             .boxed();
         let wct = gen_wall_clock_time()
-            .prop_map(|t| EventLogEntry::WallClockTime(t))
+            .prop_map(EventLogEntry::WallClockTime)
+            .boxed();
+        let eid_wb = (arb_event_id(), arb_blob())
+            .prop_filter("folded internal event", |(id, _)| {
+                !is_folded_internal_id(*id)
+            })
+            .prop_map(|(id, b)| EventLogEntry::EventWithBlob(id, b))
             .boxed();
         tc.prop_union(tc_wt)
             .or(eid)
@@ -996,6 +1216,11 @@ pub(crate) mod test {
             .or(eid_wp)
             .or(eid_wp_wt)
             .or(wct)
+            .or(eid_wb)
+    }
+
+    pub fn arb_blob() -> impl Strategy<Value = Vec<u8>> {
+        proptest::collection::vec(any::<u8>(), 0..=modality_probe::ModalityProbe::MAX_BLOB_LEN)
     }
 
     prop_compose! {
@@ -1075,6 +1300,173 @@ pub(crate) mod test {
             }
     }
 
+    #[test]
+    fn reports_with_checksums_are_verified() {
+        let mut storage = vec![MaybeUninit::new(0); 1024];
+        let mut probe = modality_probe::ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.set_checksums(true);
+
+        let a = EventId::new(1).unwrap();
+        probe.record_event(a);
+        let mut report_dest = vec![0; 512];
+        let n_bytes = probe.report(&mut report_dest).unwrap().unwrap().get();
+        let report = Report::try_from(&report_dest[..n_bytes]).unwrap();
+        assert_eq!(report.event_log.last(), Some(&EventLogEntry::Event(a)));
+
+        report_dest[n_bytes - 8] ^= 0x80;
+        match Report::try_from(&report_dest[..n_bytes]) {
+            Err(SerializationError::ReportWireError(ReportWireError::ChecksumMismatch)) => (),
+            r => panic!("Expected a checksum mismatch, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn repeated_events_keep_their_count() {
+        let mut storage = vec![MaybeUninit::new(0); 1024];
+        let mut probe = modality_probe::ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.set_repeated_event_compression(true);
+
+        let a = EventId::new(1).unwrap();
+        let b = EventId::new(2).unwrap();
+        for _ in 0..4 {
+            probe.record_event(a);
+        }
+        probe.record_event(b);
+        let mut report_dest = vec![0; 512];
+        let n_bytes = probe.report(&mut report_dest).unwrap().unwrap();
+        let report = Report::try_from(&report_dest[..n_bytes.get()]).unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::TraceClock(LogicalClock {
+                    id: ProbeId::new(1).unwrap(),
+                    epoch: ProbeEpoch(0),
+                    ticks: ProbeTicks(0)
+                }),
+                EventLogEntry::Event(EventId::EVENT_PROBE_INITIALIZED),
+                EventLogEntry::Event(a),
+                EventLogEntry::EventRepeated(a, 3),
+                EventLogEntry::Event(b),
+            ]
+        );
+
+        let mut round_trip_dest = vec![0; 512];
+        let n_bytes = report.write_into_le_bytes(&mut round_trip_dest).unwrap();
+        assert_eq!(
+            Report::try_from(&round_trip_dest[..n_bytes]).unwrap(),
+            report
+        );
+
+        let (first, second) = LogEntry::event_repeated(2);
+        let (huge_first, huge_second) = LogEntry::event_repeated(u32::MAX);
+        let log = vec![
+            WholeEntry::Double(first, second),
+            WholeEntry::Single(LogEntry::event(b)),
+            WholeEntry::Double(huge_first, huge_second),
+        ];
+        let report =
+            Report::try_from_log(report.probe_clock, 1, report.frontier_clocks, &log).unwrap();
+        // A repeat of an event reported separately can't be attributed,
+        // and a large count is carried as is rather than expanded
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::EventWithPayload(EventId::EVENT_PREVIOUS_EVENT_REPEATED, 2),
+                EventLogEntry::Event(b),
+                EventLogEntry::EventRepeated(b, u32::MAX),
+            ]
+        );
+    }
+
+    #[test]
+    fn wide_payloads_are_combined() {
+        let mut storage = vec![MaybeUninit::new(0); 1024];
+        let mut probe = modality_probe::ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+
+        let a = EventId::new(1).unwrap();
+        let t = Nanoseconds::new(2).unwrap();
+        probe.record_event_with_wide_payload(a, u64::MAX);
+        probe.record_event_with_wide_payload_with_time(a, 1 << 32, t);
+        probe.record_event_with_wide_payload(a, 3);
+        let mut report_dest = vec![0; 512];
+        let n_bytes = probe.report(&mut report_dest).unwrap().unwrap();
+        let report = Report::try_from(&report_dest[..n_bytes.get()]).unwrap();
+        assert_eq!(
+            &report.event_log[2..],
+            [
+                EventLogEntry::EventWithPayload(a, u64::MAX),
+                EventLogEntry::EventWithPayloadWithTime(t, a, 1 << 32),
+                EventLogEntry::EventWithPayload(a, 3),
+            ]
+        );
+
+        // Payloads which fit in 32 bits are written without the high bits entry
+        let mut round_trip_dest = vec![0; 512];
+        let n_round_trip_bytes = report.write_into_le_bytes(&mut round_trip_dest).unwrap();
+        assert_eq!(
+            n_round_trip_bytes,
+            n_bytes.get() - 2 * mem::size_of::<LogEntry>()
+        );
+        let round_trip = Report::try_from(&round_trip_dest[..n_round_trip_bytes]).unwrap();
+        assert_eq!(round_trip, report);
+    }
+
+    #[test]
+    fn blobs_are_collected() {
+        let mut storage = vec![MaybeUninit::new(0); 1024];
+        let mut probe = modality_probe::ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+
+        let a = EventId::new(1).unwrap();
+        probe.record_event_with_blob(a, b"hello, world");
+        probe.record_event_with_blob(a, &[]);
+        probe.record_event_with_blob(a, &[0xff; 5]);
+        let mut report_dest = vec![0; 512];
+        let n_bytes = probe.report(&mut report_dest).unwrap().unwrap();
+        let report = Report::try_from(&report_dest[..n_bytes.get()]).unwrap();
+        assert_eq!(
+            &report.event_log[2..],
+            [
+                EventLogEntry::EventWithBlob(a, b"hello, world".to_vec()),
+                EventLogEntry::EventWithBlob(a, Vec::new()),
+                EventLogEntry::EventWithBlob(a, vec![0xff; 5]),
+            ]
+        );
+
+        let mut round_trip_dest = vec![0; 512];
+        let n_round_trip_bytes = report.write_into_le_bytes(&mut round_trip_dest).unwrap();
+        assert_eq!(n_round_trip_bytes, n_bytes.get());
+        let round_trip = Report::try_from(&round_trip_dest[..n_round_trip_bytes]).unwrap();
+        assert_eq!(round_trip, report);
+    }
+
     #[test]
     fn report_e2e() {
         let mut storage1 = vec![MaybeUninit::new(0); 1024];
@@ -0,0 +1,952 @@
+//! Metrics about the reports a collector receives, and the per-probe
+//! sequencing that drops duplicate reports, writes reports which arrive
+//! out of order in sequence number order, and notices when a probe
+//! restarts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+use modality_probe::{
+    wire::{ReportWireError, WireReport},
+    EventId, LogicalClock, OrdClock, ProbeId,
+};
+
+use crate::{
+    add_log_report_to_entries, add_probe_restart_to_entries, json, Error, EventLogEntry, Report,
+    ReportLogEntry, SequenceNumber, SerializationError, SessionId,
+};
+
+/// How many reports from a probe are held back waiting for a missing
+/// earlier report, unless configured otherwise
+pub const DEFAULT_REORDER_WINDOW: usize = 16;
+
+/// How long a report is held back waiting for a missing earlier report
+/// before it's written anyway
+pub const DEFAULT_MAX_REORDER_DELAY: Duration = Duration::from_secs(1);
+
+/// How many sequence numbers already written are remembered per probe to
+/// recognize duplicates
+const RECENT_SEQ_NUMS_LEN: usize = 256;
+
+#[derive(Clone, Debug, Default)]
+pub struct ReportMetrics {
+    pub bytes_accumulated: u64,
+    pub bytes_discarded: u64,
+    pub reports_discarded: u64,
+    /// Reports whose CRC-32 trailer was verified
+    pub checksums_verified: u64,
+    /// Reports discarded because their CRC-32 trailer didn't match
+    pub checksum_mismatches: u64,
+    /// Report fragments found, see `ModalityProbe::report_fragmented`
+    pub fragments_received: u64,
+    /// Fragmented reports discarded because some fragments never arrived
+    pub incomplete_reports: u64,
+    /// Reports discarded because one with the same probe id and sequence
+    /// number was already received
+    pub duplicate_reports: u64,
+    /// Reports received after a later report from the same probe
+    pub out_of_order_reports: u64,
+    pub probe_report_metrics: HashMap<ProbeId, ProbeReportMetrics>,
+}
+
+impl ReportMetrics {
+    /// Count a fragmented report which never completed
+    pub fn flag_incomplete(&mut self) {
+        self.incomplete_reports = self.incomplete_reports.saturating_add(1);
+    }
+
+    /// Count a report parsed from `bytes`, whose checksum was verified
+    /// if it has one
+    pub fn flag_parsed(&mut self, bytes: &[u8]) {
+        if WireReport::new_unchecked(bytes).has_checksum() {
+            self.checksums_verified = self.checksums_verified.saturating_add(1);
+        }
+    }
+
+    /// Count a report of `len` bytes which couldn't be parsed
+    pub fn flag_discarded(&mut self, len: usize, error: &SerializationError) {
+        if let SerializationError::ReportWireError(ReportWireError::ChecksumMismatch) = error {
+            self.checksum_mismatches = self.checksum_mismatches.saturating_add(1);
+        }
+        self.reports_discarded = self.reports_discarded.saturating_add(1);
+        self.bytes_discarded = self.bytes_discarded.saturating_add(len as u64);
+    }
+
+    /// Add the counters of metrics gathered separately, e.g. on another
+    /// thread, to these. Per-probe metrics are not merged, since they
+    /// depend on the order reports are written in.
+    pub fn add_counters(&mut self, other: &ReportMetrics) {
+        self.bytes_accumulated = self
+            .bytes_accumulated
+            .saturating_add(other.bytes_accumulated);
+        self.bytes_discarded = self.bytes_discarded.saturating_add(other.bytes_discarded);
+        self.reports_discarded = self
+            .reports_discarded
+            .saturating_add(other.reports_discarded);
+        self.checksums_verified = self
+            .checksums_verified
+            .saturating_add(other.checksums_verified);
+        self.checksum_mismatches = self
+            .checksum_mismatches
+            .saturating_add(other.checksum_mismatches);
+        self.fragments_received = self
+            .fragments_received
+            .saturating_add(other.fragments_received);
+        self.incomplete_reports = self
+            .incomplete_reports
+            .saturating_add(other.incomplete_reports);
+        self.duplicate_reports = self
+            .duplicate_reports
+            .saturating_add(other.duplicate_reports);
+        self.out_of_order_reports = self
+            .out_of_order_reports
+            .saturating_add(other.out_of_order_reports);
+    }
+
+    /// Total number of reports written, across all probes
+    pub fn num_reports(&self) -> u64 {
+        self.probe_report_metrics
+            .values()
+            .map(|m| m.num_reports)
+            .sum()
+    }
+}
+
+/// A multi-line summary, suitable for printing periodically and when a
+/// collector shuts down
+impl fmt::Display for ReportMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Collected {} reports from {} probes, {} reports were discarded",
+            self.num_reports(),
+            self.probe_report_metrics.len(),
+            self.reports_discarded
+        )?;
+        write!(
+            f,
+            "\nProcessed {} bytes, {} bytes were discarded",
+            self.bytes_accumulated, self.bytes_discarded
+        )?;
+        if self.checksums_verified != 0 || self.checksum_mismatches != 0 {
+            write!(
+                f,
+                "\nVerified {} report checksums, {} reports failed their checksum",
+                self.checksums_verified, self.checksum_mismatches
+            )?;
+        }
+        if self.fragments_received != 0 {
+            write!(
+                f,
+                "\nReassembled reports from {} fragments, {} reports were incomplete",
+                self.fragments_received, self.incomplete_reports
+            )?;
+        }
+        if self.duplicate_reports != 0 || self.out_of_order_reports != 0 {
+            write!(
+                f,
+                "\nDiscarded {} duplicate reports, {} reports arrived out of order",
+                self.duplicate_reports, self.out_of_order_reports
+            )?;
+        }
+        let mut probe_ids: Vec<&ProbeId> = self.probe_report_metrics.keys().collect();
+        probe_ids.sort_by_key(|id| id.get_raw());
+        for probe_id in probe_ids {
+            let m = &self.probe_report_metrics[probe_id];
+            write!(
+                f,
+                "\n{} reports from ProbeId {}, {} missed reports",
+                m.num_reports,
+                probe_id.get_raw(),
+                m.missed_seq_nums
+            )?;
+            if m.restarts != 0 {
+                write!(f, ", {} restarts", m.restarts)?;
+            }
+            if m.epoch_rollovers != 0 {
+                write!(f, ", {} epoch rollovers", m.epoch_rollovers)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ProbeReportMetrics {
+    pub num_reports: u64,
+    /// Sequence numbers skipped between the reports written
+    pub missed_seq_nums: u64,
+    /// The highest sequence number written
+    pub last_seq_num: SequenceNumber,
+    /// Times the probe was seen to restart
+    pub restarts: u64,
+    /// Times the probe's clock epoch was seen to wrap around
+    pub epoch_rollovers: u64,
+}
+
+impl Default for ProbeReportMetrics {
+    fn default() -> Self {
+        ProbeReportMetrics {
+            num_reports: 0,
+            missed_seq_nums: 0,
+            last_seq_num: SequenceNumber(0),
+            restarts: 0,
+            epoch_rollovers: 0,
+        }
+    }
+}
+
+impl ProbeReportMetrics {
+    /// Account for a report being written
+    pub fn update(&mut self, report: &Report) {
+        if self.num_reports == 0 {
+            self.last_seq_num = report.seq_num;
+        } else if report.seq_num > self.last_seq_num {
+            let skipped = report.seq_num.0 - self.last_seq_num.0 - 1;
+            self.missed_seq_nums = self.missed_seq_nums.saturating_add(skipped);
+            self.last_seq_num = report.seq_num;
+        } else {
+            // A late report, which was counted as missed when a later one
+            // was written
+            self.missed_seq_nums = self.missed_seq_nums.saturating_sub(1);
+        }
+        self.num_reports = self.num_reports.saturating_add(1);
+    }
+
+    /// Account for the first report written after the probe restarted,
+    /// whose sequence number isn't comparable with the earlier ones
+    pub fn update_after_restart(&mut self, report: &Report) {
+        self.restarts = self.restarts.saturating_add(1);
+        self.last_seq_num = report.seq_num;
+        self.num_reports = self.num_reports.saturating_add(1);
+    }
+}
+
+/// A report, along with when it was received
+#[derive(Debug, PartialEq)]
+pub struct ReceivedReport {
+    pub report: Report,
+    pub receive_time: DateTime<Utc>,
+    /// Whether the probe restarted after the report released before this
+    /// one
+    pub probe_restarted: bool,
+}
+
+impl ReceivedReport {
+    /// Add the report's log entries, preceded by a `ProbeRestarted`
+    /// marker if the probe restarted before it
+    pub fn add_to_entries(
+        &self,
+        session_id: SessionId,
+        log_entries_buffer: &mut Vec<ReportLogEntry>,
+    ) -> Result<(), Error> {
+        if self.probe_restarted {
+            add_probe_restart_to_entries(
+                &self.report,
+                session_id,
+                self.receive_time,
+                log_entries_buffer,
+            );
+        }
+        add_log_report_to_entries(
+            &self.report,
+            session_id,
+            self.receive_time,
+            log_entries_buffer,
+        )
+    }
+}
+
+/// Write the log entries of reports released by a `ReportTracker`, using
+/// `log_entries_buffer` as scratch space. A malformed report is left out of
+/// the trace with a warning, as are entries which fail to be written; the
+/// result is that of flushing the output.
+pub fn write_reports<W: Write>(
+    released: &[ReceivedReport],
+    session_id: SessionId,
+    log_entries_buffer: &mut Vec<ReportLogEntry>,
+    log_output_writer: &mut W,
+) -> io::Result<()> {
+    if released.is_empty() {
+        return Ok(());
+    }
+    log_entries_buffer.clear();
+    for r in released {
+        if let Err(e) = r.add_to_entries(session_id, log_entries_buffer) {
+            eprintln!(
+                "Encountered a malformed report from probe {}, not adding it to the trace: {}",
+                r.report.probe_id.get_raw(),
+                e
+            );
+        }
+    }
+    if let Err(e) = json::write_log_entries(log_output_writer, log_entries_buffer.iter()) {
+        eprintln!("Error writing log entries: {}", e);
+    }
+    log_output_writer.flush()
+}
+
+/// Sequencing state for a single probe
+#[derive(Debug, Default)]
+struct ProbeSequence {
+    /// Sequence number of the last report released in order
+    last_released: Option<u64>,
+    /// Highest sequence number received
+    highest_received: u64,
+    /// Probe clock of the report with the highest sequence number
+    latest_clock: Option<LogicalClock>,
+    /// Sequence number and probe clock of the report which logged the
+    /// probe's initialization or recovery
+    boot_report: Option<(u64, LogicalClock)>,
+    /// Reports waiting for a missing earlier report, and when they were
+    /// received
+    held: BTreeMap<u64, (ReceivedReport, Instant)>,
+    /// Sequence numbers recently released, with their probe clocks
+    recent: BTreeMap<u64, LogicalClock>,
+}
+
+/// Tracks the reports received from each probe, dropping duplicates and
+/// holding back reports which arrive ahead of a missing earlier report, so
+/// that they can be written in sequence number order.
+///
+/// Reports are held until the missing report arrives, more than
+/// `reorder_window` reports are held for the probe, or the oldest one has
+/// been held for longer than the maximum reorder delay. A report which
+/// arrives after a later one was released is released immediately.
+///
+/// A probe is taken to have restarted when a report logs its
+/// initialization or recovery, when a report reuses a sequence number
+/// already seen with a different clock, when a report's sequence number
+/// goes back while its clock goes forward or its epoch changes, or when
+/// the sequence number goes back further than a late report could.
+/// Sequencing then starts over for the
+/// probe, and the first report released afterwards is flagged with
+/// `probe_restarted`. An epoch which wraps around while the sequence
+/// numbers keep going is counted as a rollover, not a restart.
+#[derive(Debug)]
+pub struct ReportTracker {
+    reorder_window: usize,
+    max_reorder_delay: Duration,
+    summary_interval: Option<Duration>,
+    last_summary: Instant,
+    probes: HashMap<ProbeId, ProbeSequence>,
+    pub metrics: ReportMetrics,
+}
+
+// Options for how a collector sequences the reports it receives and
+// summarizes its metrics, shared by the collectors' command lines. Not a doc
+// comment, which structopt would use as the about text of every command
+// that flattens this in.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct TrackerOpts {
+    /// How many reports from a probe to hold back while waiting for a
+    /// missing earlier report, so that reports are written in sequence
+    /// order. 0 writes reports in the order they arrive. Defaults to 16.
+    #[cfg_attr(feature = "cli", structopt(short = "w", long = "reorder-window"))]
+    pub reorder_window: Option<usize>,
+
+    /// Print a summary of the collected reports every this many seconds,
+    /// in addition to at shutdown.
+    #[cfg_attr(feature = "cli", structopt(long = "metrics-interval"))]
+    pub metrics_interval: Option<u64>,
+}
+
+impl TrackerOpts {
+    pub fn reorder_window(&self) -> usize {
+        self.reorder_window.unwrap_or(DEFAULT_REORDER_WINDOW)
+    }
+
+    pub fn metrics_interval(&self) -> Option<Duration> {
+        self.metrics_interval.map(Duration::from_secs)
+    }
+
+    /// A tracker configured by these options
+    pub fn tracker(&self) -> ReportTracker {
+        ReportTracker::new(self.reorder_window()).with_summary_interval(self.metrics_interval())
+    }
+}
+
+impl Default for ReportTracker {
+    fn default() -> Self {
+        ReportTracker::new(DEFAULT_REORDER_WINDOW)
+    }
+}
+
+impl ReportTracker {
+    /// Hold back at most `reorder_window` reports per probe. With a window
+    /// of 0, reports are released in the order they arrive, but
+    /// duplicates are still dropped.
+    pub fn new(reorder_window: usize) -> Self {
+        ReportTracker {
+            reorder_window,
+            max_reorder_delay: DEFAULT_MAX_REORDER_DELAY,
+            summary_interval: None,
+            last_summary: Instant::now(),
+            probes: HashMap::new(),
+            metrics: ReportMetrics::default(),
+        }
+    }
+
+    /// Hold reports back for at most the given time
+    pub fn with_max_reorder_delay(mut self, max_reorder_delay: Duration) -> Self {
+        self.max_reorder_delay = max_reorder_delay;
+        self
+    }
+
+    /// Have `summary_due` report that a summary is due every `interval`
+    pub fn with_summary_interval(mut self, interval: Option<Duration>) -> Self {
+        self.summary_interval = interval;
+        self
+    }
+
+    /// Add a received report, returning the reports which are now ready to
+    /// be written, in the order they should be written
+    pub fn push(&mut self, report: Report, receive_time: DateTime<Utc>) -> Vec<ReceivedReport> {
+        let mut released = Vec::new();
+        let reorder_window = self.reorder_window;
+        let probe_id = report.probe_id;
+        let seq_num = report.seq_num.0;
+
+        let probe_restarted = self
+            .probes
+            .get(&probe_id)
+            .is_some_and(|probe| probe.is_restart(&report, reorder_window));
+        if probe_restarted {
+            // Nothing from the previous boot will fill the gaps, so there's
+            // no point in holding its reports back any longer
+            if let Some(mut previous) = self.probes.remove(&probe_id) {
+                while !previous.held.is_empty() {
+                    previous.release_first_held(&mut released);
+                }
+            }
+        }
+        let probe = self.probes.entry(probe_id).or_default();
+
+        if probe.held.contains_key(&seq_num)
+            || probe.recent.contains_key(&seq_num)
+            || probe.last_released == Some(seq_num)
+        {
+            self.metrics.duplicate_reports = self.metrics.duplicate_reports.saturating_add(1);
+            return released;
+        }
+        if probe.last_released.is_some() && seq_num < probe.highest_received {
+            self.metrics.out_of_order_reports = self.metrics.out_of_order_reports.saturating_add(1);
+        }
+        if probe.latest_clock.is_none() || seq_num > probe.highest_received {
+            if probe
+                .latest_clock
+                .is_some_and(|latest| epoch_rolled_over(latest, report.probe_clock))
+            {
+                let m = self
+                    .metrics
+                    .probe_report_metrics
+                    .entry(probe_id)
+                    .or_default();
+                m.epoch_rollovers = m.epoch_rollovers.saturating_add(1);
+            }
+            probe.highest_received = seq_num;
+            probe.latest_clock = Some(report.probe_clock);
+        }
+        if has_boot_event(&report) {
+            probe.boot_report = Some((seq_num, report.probe_clock));
+        }
+
+        let received = ReceivedReport {
+            report,
+            receive_time,
+            probe_restarted,
+        };
+        match probe.last_released {
+            Some(last) if seq_num > last.saturating_add(1) && reorder_window != 0 => {
+                probe.held.insert(seq_num, (received, Instant::now()));
+                while probe.held.len() > reorder_window {
+                    probe.release_first_held(&mut released);
+                }
+            }
+            Some(last) if seq_num < last => probe.release_late(received, &mut released),
+            _ => probe.release(received, &mut released),
+        }
+        self.count_released(&released);
+        released
+    }
+
+    /// Release reports which have been held back for longer than the
+    /// maximum reorder delay
+    pub fn release_overdue(&mut self) -> Vec<ReceivedReport> {
+        let mut released = Vec::new();
+        let max_reorder_delay = self.max_reorder_delay;
+        for probe in self.probes.values_mut() {
+            while probe
+                .held
+                .values()
+                .next()
+                .is_some_and(|(_, held_since)| held_since.elapsed() >= max_reorder_delay)
+            {
+                probe.release_first_held(&mut released);
+            }
+        }
+        self.count_released(&released);
+        released
+    }
+
+    /// Release every report still held back, e.g. when shutting down
+    pub fn finish(&mut self) -> Vec<ReceivedReport> {
+        let mut released = Vec::new();
+        for probe in self.probes.values_mut() {
+            while !probe.held.is_empty() {
+                probe.release_first_held(&mut released);
+            }
+        }
+        self.count_released(&released);
+        released
+    }
+
+    /// Whether the summary interval has elapsed since a summary was last
+    /// due, for collectors which print their metrics periodically
+    pub fn summary_due(&mut self) -> bool {
+        match self.summary_interval {
+            Some(interval) if self.last_summary.elapsed() >= interval => {
+                self.last_summary = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn count_released(&mut self, released: &[ReceivedReport]) {
+        for r in released {
+            let m = self
+                .metrics
+                .probe_report_metrics
+                .entry(r.report.probe_id)
+                .or_default();
+            if r.probe_restarted {
+                m.update_after_restart(&r.report);
+            } else {
+                m.update(&r.report);
+            }
+        }
+    }
+}
+
+impl ProbeSequence {
+    /// Whether a report comes from a later boot of the probe than the
+    /// reports received so far
+    fn is_restart(&self, report: &Report, reorder_window: usize) -> bool {
+        let seq_num = report.seq_num.0;
+        let clock = report.probe_clock;
+        if has_boot_event(report) {
+            // Unless it's the boot report already seen, resent
+            return self.boot_report != Some((seq_num, clock));
+        }
+        if let Some(seen) = self.seen_clock(seq_num) {
+            // A resent report carries the same clock as the original
+            return seen != clock;
+        }
+        match self.latest_clock {
+            Some(latest) if seq_num < self.highest_received => {
+                // Within a boot, sequence numbers and clocks only go
+                // forwards together, and a late report is at most an
+                // epoch behind
+                OrdClock(clock.epoch, clock.ticks) > OrdClock(latest.epoch, latest.ticks)
+                    || (clock.epoch != latest.epoch
+                        && clock.epoch.0 != latest.epoch.0.wrapping_sub(1))
+                    || self.highest_received - seq_num
+                        > RECENT_SEQ_NUMS_LEN.max(reorder_window) as u64
+            }
+            _ => false,
+        }
+    }
+
+    /// The probe clock of the report with the given sequence number, if
+    /// it's held back or was recently released
+    fn seen_clock(&self, seq_num: u64) -> Option<LogicalClock> {
+        self.held
+            .get(&seq_num)
+            .map(|(received, _)| received.report.probe_clock)
+            .or_else(|| self.recent.get(&seq_num).copied())
+    }
+
+    /// Release a report which follows the last one released, along with
+    /// any held reports which follow it
+    fn release(&mut self, received: ReceivedReport, released: &mut Vec<ReceivedReport>) {
+        let mut next = received.report.seq_num.0;
+        self.remember(&received.report);
+        self.last_released = Some(next);
+        released.push(received);
+        while let Some((held, _)) = next.checked_add(1).and_then(|n| self.held.remove(&n)) {
+            next += 1;
+            self.remember(&held.report);
+            self.last_released = Some(next);
+            released.push(held);
+        }
+    }
+
+    /// Release a report which arrived after a later one was released
+    fn release_late(&mut self, received: ReceivedReport, released: &mut Vec<ReceivedReport>) {
+        self.remember(&received.report);
+        released.push(received);
+    }
+
+    /// Give up waiting for whatever's missing before the earliest held
+    /// report, and release it
+    fn release_first_held(&mut self, released: &mut Vec<ReceivedReport>) {
+        let first = self.held.keys().next().copied();
+        if let Some((received, _)) = first.and_then(|seq_num| self.held.remove(&seq_num)) {
+            self.release(received, released);
+        }
+    }
+
+    fn remember(&mut self, report: &Report) {
+        self.recent.insert(report.seq_num.0, report.probe_clock);
+        while self.recent.len() > RECENT_SEQ_NUMS_LEN {
+            let oldest = *self.recent.keys().next().unwrap();
+            self.recent.remove(&oldest);
+        }
+    }
+}
+
+/// Whether the report logged the probe's initialization or recovery
+fn has_boot_event(report: &Report) -> bool {
+    report.event_log.iter().any(|e| match e {
+        EventLogEntry::Event(id) | EventLogEntry::EventWithTime(_, id) => {
+            *id == EventId::EVENT_PROBE_INITIALIZED || *id == EventId::EVENT_PROBE_RECOVERED
+        }
+        _ => false,
+    })
+}
+
+/// Whether the clock's epoch wrapped around since the latest clock
+fn epoch_rolled_over(latest: LogicalClock, clock: LogicalClock) -> bool {
+    clock.epoch < latest.epoch
+        && OrdClock(clock.epoch, clock.ticks) > OrdClock(latest.epoch, latest.ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogEntryData;
+    use modality_probe::{
+        EventId, LogicalClock, NanosecondResolution, ProbeEpoch, ProbeTicks, WallClockId,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn report(raw_probe_id: u32, seq_num: u64) -> Report {
+        let probe_id = ProbeId::new(raw_probe_id).unwrap();
+        Report {
+            probe_id,
+            probe_clock: LogicalClock {
+                id: probe_id,
+                epoch: ProbeEpoch(0),
+                ticks: ProbeTicks(seq_num as u16),
+            },
+            seq_num: seq_num.into(),
+            persistent_epoch_counting: false,
+            time_resolution: NanosecondResolution::UNSPECIFIED,
+            wall_clock_id: WallClockId::default(),
+            frontier_clocks: vec![],
+            event_log: vec![EventLogEntry::Event(EventId::new(1).unwrap())],
+        }
+    }
+
+    fn boot_report(raw_probe_id: u32, seq_num: u64, epoch: u16) -> Report {
+        let mut r = report(raw_probe_id, seq_num);
+        r.probe_clock.epoch = ProbeEpoch(epoch);
+        r.event_log = vec![EventLogEntry::Event(EventId::EVENT_PROBE_INITIALIZED)];
+        r
+    }
+
+    fn push(tracker: &mut ReportTracker, raw_probe_id: u32, seq_num: u64) -> Vec<u64> {
+        seq_nums(tracker.push(report(raw_probe_id, seq_num), Utc::now()))
+    }
+
+    fn seq_nums(released: Vec<ReceivedReport>) -> Vec<u64> {
+        released.iter().map(|r| r.report.seq_num.0).collect()
+    }
+
+    fn probe_metrics(tracker: &ReportTracker, raw_probe_id: u32) -> ProbeReportMetrics {
+        tracker.metrics.probe_report_metrics[&ProbeId::new(raw_probe_id).unwrap()]
+    }
+
+    #[test]
+    fn in_order_reports_are_released_immediately() {
+        let mut tracker = ReportTracker::new(4);
+        for seq_num in 3..6 {
+            assert_eq!(push(&mut tracker, 1, seq_num), vec![seq_num]);
+        }
+        assert_eq!(
+            probe_metrics(&tracker, 1),
+            ProbeReportMetrics {
+                num_reports: 3,
+                missed_seq_nums: 0,
+                last_seq_num: 5.into(),
+                restarts: 0,
+                epoch_rollovers: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn duplicates_are_dropped() {
+        let mut tracker = ReportTracker::new(4);
+        assert_eq!(push(&mut tracker, 1, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 0), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 2), Vec::<u64>::new());
+        // Held reports are recognized too
+        assert_eq!(push(&mut tracker, 1, 2), Vec::<u64>::new());
+        // The same sequence number from another probe is not a duplicate
+        assert_eq!(push(&mut tracker, 2, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 1), vec![1, 2]);
+        assert_eq!(push(&mut tracker, 1, 1), Vec::<u64>::new());
+        assert_eq!(tracker.metrics.duplicate_reports, 3);
+        assert_eq!(probe_metrics(&tracker, 1).num_reports, 3);
+    }
+
+    #[test]
+    fn reordered_reports_are_released_in_sequence() {
+        let mut tracker = ReportTracker::new(4);
+        assert_eq!(push(&mut tracker, 1, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 3), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 2), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 1), vec![1, 2, 3]);
+        assert_eq!(tracker.metrics.out_of_order_reports, 2);
+        assert_eq!(
+            probe_metrics(&tracker, 1),
+            ProbeReportMetrics {
+                num_reports: 4,
+                missed_seq_nums: 0,
+                last_seq_num: 3.into(),
+                restarts: 0,
+                epoch_rollovers: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn full_window_gives_up_on_missing_reports() {
+        let mut tracker = ReportTracker::new(2);
+        assert_eq!(push(&mut tracker, 1, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 3), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 4), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 6), vec![3, 4]);
+        assert_eq!(probe_metrics(&tracker, 1).missed_seq_nums, 2);
+
+        // A missing report which turns up late is still written, and no
+        // longer counted as missed
+        assert_eq!(push(&mut tracker, 1, 1), vec![1]);
+        assert_eq!(probe_metrics(&tracker, 1).missed_seq_nums, 1);
+
+        assert_eq!(seq_nums(tracker.finish()), vec![6]);
+        assert_eq!(
+            probe_metrics(&tracker, 1),
+            ProbeReportMetrics {
+                num_reports: 5,
+                missed_seq_nums: 2,
+                last_seq_num: 6.into(),
+                restarts: 0,
+                epoch_rollovers: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn overdue_reports_are_released() {
+        let mut tracker = ReportTracker::new(8).with_max_reorder_delay(Duration::from_millis(10));
+        assert_eq!(push(&mut tracker, 1, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 2), Vec::<u64>::new());
+        assert_eq!(push(&mut tracker, 1, 3), Vec::<u64>::new());
+        assert_eq!(seq_nums(tracker.release_overdue()), Vec::<u64>::new());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(seq_nums(tracker.release_overdue()), vec![2, 3]);
+    }
+
+    #[test]
+    fn no_window_releases_in_arrival_order() {
+        let mut tracker = ReportTracker::new(0);
+        assert_eq!(push(&mut tracker, 1, 0), vec![0]);
+        assert_eq!(push(&mut tracker, 1, 2), vec![2]);
+        assert_eq!(push(&mut tracker, 1, 1), vec![1]);
+        assert_eq!(push(&mut tracker, 1, 2), Vec::<u64>::new());
+        assert_eq!(probe_metrics(&tracker, 1).missed_seq_nums, 0);
+    }
+
+    #[test]
+    fn summary_lists_each_probe() {
+        let mut tracker = ReportTracker::new(0);
+        push(&mut tracker, 2, 0);
+        push(&mut tracker, 1, 0);
+        push(&mut tracker, 1, 2);
+        push(&mut tracker, 1, 2);
+        let summary = tracker.metrics.to_string();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Collected 3 reports from 2 probes, 0 reports were discarded",
+                "Processed 0 bytes, 0 bytes were discarded",
+                "Discarded 1 duplicate reports, 0 reports arrived out of order",
+                "2 reports from ProbeId 1, 1 missed reports",
+                "1 reports from ProbeId 2, 0 missed reports",
+            ]
+        );
+    }
+
+    #[test]
+    fn boot_events_mark_restarts() {
+        let mut tracker = ReportTracker::new(4);
+        let released = tracker.push(boot_report(1, 0, 0), Utc::now());
+        assert_eq!(released.len(), 1);
+        assert!(!released[0].probe_restarted);
+        assert_eq!(push(&mut tracker, 1, 1), vec![1]);
+        assert_eq!(push(&mut tracker, 1, 3), Vec::<u64>::new());
+
+        // The boot report resent is still a duplicate
+        assert!(tracker.push(boot_report(1, 0, 0), Utc::now()).is_empty());
+        assert_eq!(tracker.metrics.duplicate_reports, 1);
+
+        // A new boot releases what was held back from the previous one,
+        // and starts its sequence numbers over
+        let released = tracker.push(boot_report(1, 0, 1), Utc::now());
+        assert_eq!(
+            released
+                .iter()
+                .map(|r| (r.report.seq_num.0, r.probe_restarted))
+                .collect::<Vec<_>>(),
+            vec![(3, false), (0, true)]
+        );
+        assert_eq!(push(&mut tracker, 1, 1), vec![1]);
+        assert_eq!(
+            probe_metrics(&tracker, 1),
+            ProbeReportMetrics {
+                num_reports: 5,
+                missed_seq_nums: 1,
+                last_seq_num: 1.into(),
+                restarts: 1,
+                epoch_rollovers: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn sequence_numbers_going_back_mark_restarts() {
+        let mut tracker = ReportTracker::new(4);
+        for seq_num in 0..4 {
+            push(&mut tracker, 1, seq_num);
+        }
+        // A later clock with an earlier sequence number is a new boot
+        let mut r = report(1, 1);
+        r.probe_clock.epoch = ProbeEpoch(2);
+        let released = tracker.push(r, Utc::now());
+        assert!(released[0].probe_restarted);
+
+        // As is going back further than a late report could
+        for seq_num in 2..300 {
+            push(&mut tracker, 1, seq_num);
+        }
+        let released = tracker.push(report(1, 2), Utc::now());
+        assert!(released[0].probe_restarted);
+        assert_eq!(probe_metrics(&tracker, 1).restarts, 2);
+        assert_eq!(tracker.metrics.duplicate_reports, 0);
+    }
+
+    #[test]
+    fn restarts_are_marked_when_the_boot_report_is_lost() {
+        let mut tracker = ReportTracker::new(4);
+        tracker.push(boot_report(1, 0, 0), Utc::now());
+        for seq_num in 1..10 {
+            let mut r = report(1, seq_num);
+            r.probe_clock.ticks = ProbeTicks(seq_num as u16 * 10);
+            tracker.push(r, Utc::now());
+        }
+
+        // The new boot's boot report never arrives, and its clock is behind
+        // the previous boot's, but a sequence number seen with a different
+        // clock can only come from a new boot
+        let released = tracker.push(report(1, 1), Utc::now());
+        assert!(released[0].probe_restarted);
+        assert_eq!(push(&mut tracker, 1, 2), vec![2]);
+
+        // As can an earlier sequence number with a different epoch, even
+        // one never seen from the previous boot
+        let mut r = report(1, 6);
+        r.probe_clock.epoch = ProbeEpoch(3);
+        tracker.push(r, Utc::now());
+        let mut r = report(1, 4);
+        r.probe_clock.epoch = ProbeEpoch(0);
+        let released = tracker.push(r, Utc::now());
+        assert_eq!(
+            released
+                .iter()
+                .map(|r| (r.report.seq_num.0, r.probe_restarted))
+                .collect::<Vec<_>>(),
+            vec![(6, false), (4, true)]
+        );
+        assert_eq!(probe_metrics(&tracker, 1).restarts, 2);
+        assert_eq!(tracker.metrics.duplicate_reports, 0);
+    }
+
+    #[test]
+    fn epoch_rollovers_are_not_restarts() {
+        let mut tracker = ReportTracker::new(4);
+        let mut r = report(1, 0);
+        r.probe_clock.epoch = ProbeEpoch::MAX;
+        tracker.push(r, Utc::now());
+        let mut r = report(1, 1);
+        r.probe_clock.epoch = ProbeEpoch(1);
+        let released = tracker.push(r, Utc::now());
+        assert!(!released[0].probe_restarted);
+        let m = probe_metrics(&tracker, 1);
+        assert_eq!((m.restarts, m.epoch_rollovers), (0, 1));
+        assert!(tracker
+            .metrics
+            .to_string()
+            .ends_with("2 reports from ProbeId 1, 0 missed reports, 1 epoch rollovers"));
+    }
+
+    #[test]
+    fn restarts_are_marked_in_the_log() {
+        let mut tracker = ReportTracker::new(4);
+        let mut r = boot_report(1, 0, 0);
+        r.frontier_clocks = vec![r.probe_clock];
+        tracker.push(r, Utc::now());
+        let mut r = boot_report(1, 0, 1);
+        r.frontier_clocks = vec![r.probe_clock];
+        let released = tracker.push(r, Utc::now());
+        let mut entries = Vec::new();
+        released[0].add_to_entries(1.into(), &mut entries).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| &e.data).collect::<Vec<_>>(),
+            vec![
+                &LogEntryData::ProbeRestarted,
+                &LogEntryData::FrontierClock(released[0].report.probe_clock),
+                &LogEntryData::Event(EventId::EVENT_PROBE_INITIALIZED),
+            ]
+        );
+        // The marker can't be mistaken for the report's first entry
+        assert_ne!(entries[0].coordinate(), entries[1].coordinate());
+    }
+
+    #[test]
+    fn malformed_reports_are_left_out_of_the_trace() {
+        let mut tracker = ReportTracker::new(0);
+        // Without a frontier clock
+        let mut released = tracker.push(report(1, 0), Utc::now());
+        let mut r = report(1, 1);
+        r.frontier_clocks = vec![r.probe_clock];
+        released.extend(tracker.push(r, Utc::now()));
+        assert_eq!(seq_nums(tracker.finish()), Vec::<u64>::new());
+
+        let mut out = Vec::new();
+        write_reports(&released, 1.into(), &mut Vec::new(), &mut out).unwrap();
+        let entries = json::read_log_entries(&mut &out[..]).unwrap();
+        assert!(!entries.is_empty());
+        assert!(entries.iter().all(|e| e.sequence_number == 1.into()));
+    }
+}
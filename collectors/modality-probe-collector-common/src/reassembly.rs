@@ -0,0 +1,252 @@
+//! Reassembly of reports split into fragments by probes reporting
+//! over MTU-limited transports.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use modality_probe::{
+    wire::{FragmentWireError, WireFragment},
+    ProbeId,
+};
+
+/// Whether `bytes` start with a report fragment, rather than a whole report.
+pub fn is_fragment(bytes: &[u8]) -> bool {
+    let f = WireFragment::new_unchecked(bytes);
+    f.check_len().is_ok() && f.check_fingerprint().is_ok()
+}
+
+/// A report for which some fragments never arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncompleteReport {
+    pub probe_id: ProbeId,
+    /// The low 16 bits of the report's sequence number
+    pub seq_num: u16,
+    pub received_fragments: usize,
+    pub expected_fragments: usize,
+}
+
+impl fmt::Display for IncompleteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Report {} from probe {} is incomplete, received {} of {} fragments",
+            self.seq_num,
+            self.probe_id.get_raw(),
+            self.received_fragments,
+            self.expected_fragments
+        )
+    }
+}
+
+#[derive(Debug)]
+struct PartialReport {
+    seq_num: u16,
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+impl PartialReport {
+    fn incomplete(&self, probe_id: ProbeId) -> IncompleteReport {
+        IncompleteReport {
+            probe_id,
+            seq_num: self.seq_num,
+            received_fragments: self.received,
+            expected_fragments: self.fragments.len(),
+        }
+    }
+}
+
+/// Rebuilds whole reports from their fragments.
+///
+/// Each probe has at most one report in progress: a fragment of a
+/// different report from the same probe abandons the one in progress,
+/// which is then flagged as incomplete. Fragments of a report may
+/// arrive in any order, and duplicates are ignored.
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    partial: HashMap<ProbeId, PartialReport>,
+    incomplete: Vec<IncompleteReport>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a fragment, returning the bytes of the whole report once
+    /// all of its fragments have arrived.
+    pub fn push(&mut self, fragment: &[u8]) -> Result<Option<Vec<u8>>, FragmentWireError> {
+        let f = WireFragment::new(fragment)?;
+        let probe_id = f.probe_id()?;
+        let count = f.count() as usize;
+
+        let starts_new_report = match self.partial.get(&probe_id) {
+            Some(p) => p.seq_num != f.seq_num() || p.fragments.len() != count,
+            None => true,
+        };
+        if starts_new_report {
+            let prev = self.partial.insert(
+                probe_id,
+                PartialReport {
+                    seq_num: f.seq_num(),
+                    fragments: vec![None; count],
+                    received: 0,
+                },
+            );
+            if let Some(prev) = prev {
+                self.incomplete.push(prev.incomplete(probe_id));
+            }
+        }
+
+        let partial = self
+            .partial
+            .get_mut(&probe_id)
+            .expect("partial report was just inserted");
+        let slot = &mut partial.fragments[f.index() as usize];
+        if slot.is_none() {
+            *slot = Some(f.payload().to_vec());
+            partial.received += 1;
+        }
+        if partial.received < count {
+            return Ok(None);
+        }
+
+        let partial = self
+            .partial
+            .remove(&probe_id)
+            .expect("partial report is present");
+        Ok(Some(
+            partial.fragments.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Take the reports abandoned so far because a newer report from
+    /// the same probe started before they were complete.
+    pub fn take_incomplete(&mut self) -> Vec<IncompleteReport> {
+        std::mem::take(&mut self.incomplete)
+    }
+
+    /// Abandon every report still in progress, e.g. at the end of
+    /// the input, returning all incomplete reports not yet taken.
+    pub fn finish(&mut self) -> Vec<IncompleteReport> {
+        let mut incomplete = self.take_incomplete();
+        let mut in_progress: Vec<IncompleteReport> = self
+            .partial
+            .drain()
+            .map(|(probe_id, p)| p.incomplete(probe_id))
+            .collect();
+        in_progress.sort_by_key(|i| (i.probe_id, i.seq_num));
+        incomplete.extend(in_progress);
+        incomplete
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::TryFrom, mem::MaybeUninit};
+
+    use modality_probe::{
+        EventId, ModalityProbe, NanosecondResolution, Probe, RestartCounterProvider, WallClockId,
+    };
+
+    use super::*;
+    use crate::Report;
+
+    fn fragments(probe_id: u32, n_events: u32) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(probe_id).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        for i in 1..=n_events {
+            probe.record_event(EventId::new(i).unwrap());
+        }
+        let mut report = [0u8; 512];
+        let mut fragment = [0u8; 64];
+        let mut fragments = Vec::new();
+        probe
+            .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))
+            .unwrap()
+            .unwrap();
+
+        let report = fragments
+            .iter()
+            .flat_map(|f| WireFragment::new(&f[..]).unwrap().payload().to_vec())
+            .collect();
+        (report, fragments)
+    }
+
+    #[test]
+    fn out_of_order_fragments_are_reassembled() {
+        let (report, mut fragments) = fragments(1, 20);
+        assert!(fragments.len() > 2);
+        assert!(fragments.iter().all(|f| is_fragment(f)));
+        assert!(!is_fragment(&report));
+        fragments.swap(0, 1);
+        // Duplicates are ignored
+        fragments.insert(1, fragments[0].clone());
+
+        let mut reassembler = Reassembler::new();
+        let (last, rest) = fragments.split_last().unwrap();
+        for f in rest {
+            assert_eq!(reassembler.push(f).unwrap(), None);
+        }
+        let bytes = reassembler.push(last).unwrap().unwrap();
+        assert_eq!(bytes, report);
+        assert!(Report::try_from(&bytes[..]).is_ok());
+        assert!(reassembler.finish().is_empty());
+    }
+
+    #[test]
+    fn missing_fragments_are_flagged() {
+        let (_, fragments_a) = fragments(1, 20);
+        let (report_b, fragments_b) = fragments(2, 20);
+        let expected_fragments = fragments_a.len();
+
+        let mut reassembler = Reassembler::new();
+        assert_eq!(reassembler.push(&fragments_a[0]).unwrap(), None);
+        let mut complete = None;
+        for f in fragments_b.iter() {
+            complete = reassembler.push(f).unwrap();
+        }
+        assert_eq!(complete, Some(report_b));
+
+        // The rest of the report from probe 1 was lost
+        assert!(reassembler.take_incomplete().is_empty());
+        assert_eq!(
+            reassembler.finish(),
+            vec![IncompleteReport {
+                probe_id: ProbeId::new(1).unwrap(),
+                seq_num: 0,
+                received_fragments: 1,
+                expected_fragments,
+            }]
+        );
+
+        assert_eq!(
+            reassembler.push(&fragments_a[0][..10]),
+            Err(FragmentWireError::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn newer_report_abandons_incomplete_one() {
+        let (_, fragments) = fragments(1, 20);
+        let mut newer = fragments[0].clone();
+        // Bump the low bits of the report sequence number
+        newer[8] += 1;
+
+        let mut reassembler = Reassembler::new();
+        assert_eq!(reassembler.push(&fragments[0]).unwrap(), None);
+        assert_eq!(reassembler.push(&newer).unwrap(), None);
+        let incomplete = reassembler.take_incomplete();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].seq_num, 0);
+        assert_eq!(incomplete[0].received_fragments, 1);
+        assert_eq!(reassembler.finish()[0].seq_num, 1);
+    }
+}
@@ -0,0 +1,556 @@
+//! Log entry output that's rotated into numbered segments once it grows too
+//! large or too old, so that long running collectors don't write a single
+//! unbounded file.
+//!
+//! The file being written to keeps the configured output path. When it's
+//! rotated, it's renamed to `<path>.<N>`, where `N` counts up from 1, and
+//! a new file is started at the output path. Finished segments can
+//! optionally be compressed in the background, becoming `<path>.<N>.gz` or
+//! `<path>.<N>.zst`. `segment_paths` lists the files of such a set in the
+//! order they were written, so readers can treat them as a single log.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::warn;
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// How finished segments are compressed
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum Compression {
+    /// Segments are left as they were written
+    #[default]
+    None,
+    /// gzip, with the `.gz` extension
+    Gzip,
+    /// Zstandard, with the `.zst` extension
+    Zstd,
+}
+
+impl Compression {
+    /// Names of each compression, as accepted by `from_str`
+    pub const NAMES: &'static [&'static str] = &["none", "gzip", "zstd"];
+
+    fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Extension added to the name of a segment compressed this way
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+
+    /// The compression of data starting with the given bytes, judging by
+    /// its magic number
+    pub fn detect(start: &[u8]) -> Compression {
+        if start.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if start.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Compress everything read from `reader` into `writer`
+    fn compress<R: Read, W: Write>(self, reader: &mut R, writer: W) -> io::Result<W> {
+        match self {
+            Compression::None => {
+                let mut writer = writer;
+                io::copy(reader, &mut writer)?;
+                Ok(writer)
+            }
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                io::copy(reader, &mut encoder)?;
+                encoder.finish()
+            }
+            Compression::Zstd => {
+                let mut encoder = zstd::Encoder::new(writer, 0)?;
+                io::copy(reader, &mut encoder)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(format!("Unknown compression '{}'", s)),
+        }
+    }
+}
+
+/// When the output is rotated, and what happens to the finished segments
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct RotationPolicy {
+    /// Rotate once the file being written to reaches this many bytes
+    pub max_bytes: Option<u64>,
+    /// Rotate once the file being written to has been written to for this
+    /// long
+    pub max_age: Option<Duration>,
+    /// How to compress finished segments
+    pub compression: Compression,
+}
+
+impl RotationPolicy {
+    /// Whether the output is rotated at all
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_age.is_some()
+    }
+}
+
+// Options for rotating a collector's output file, shared by the collectors'
+// command lines. Not a doc comment, which structopt would use as the about
+// text of every command that flattens this in.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct RotationOpts {
+    /// Rotate the output file once it reaches this size, in bytes or with
+    /// a K, M or G suffix. Finished segments are kept alongside it, with
+    /// .1, .2 and so on appended to its name.
+    #[cfg_attr(
+        feature = "cli",
+        structopt(long = "rotate-size", parse(try_from_str = parse_size))
+    )]
+    pub rotate_size: Option<u64>,
+
+    /// Rotate the output file once it's been written to for this many
+    /// seconds.
+    #[cfg_attr(feature = "cli", structopt(long = "rotate-interval"))]
+    pub rotate_interval: Option<u64>,
+
+    /// Compress finished segments of the output file. Defaults to none.
+    #[cfg_attr(feature = "cli", structopt(long, possible_values = Compression::NAMES))]
+    pub compress: Option<Compression>,
+}
+
+impl RotationOpts {
+    /// The rotation policy given by these options
+    pub fn policy(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes: self.rotate_size,
+            max_age: self.rotate_interval.map(Duration::from_secs),
+            compression: self.compress.unwrap_or_default(),
+        }
+    }
+}
+
+/// Parse a size in bytes, optionally followed by a `K`, `M` or `G` suffix
+/// for multiples of 1024
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, multiplier) = match s.char_indices().last() {
+        Some((i, 'k')) | Some((i, 'K')) => (&s[..i], 1 << 10),
+        Some((i, 'm')) | Some((i, 'M')) => (&s[..i], 1 << 20),
+        Some((i, 'g')) | Some((i, 'G')) => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("Invalid size '{}'", s))
+}
+
+/// Writes to the output path, rotating it according to a `RotationPolicy`.
+///
+/// The output is only rotated between lines, at the first line boundary
+/// after it's due, so each segment holds whole log entries. The policy is
+/// checked as data is written, so a collector that isn't receiving
+/// anything won't rotate its output until it does.
+#[derive(Debug)]
+pub struct RotatingWriter {
+    path: PathBuf,
+    policy: RotationPolicy,
+    /// Only `None` while the file is being rotated
+    file: Option<File>,
+    /// Bytes in the file being written to
+    len: u64,
+    opened_at: Instant,
+    at_line_start: bool,
+    next_segment: u64,
+    compressing: Vec<JoinHandle<()>>,
+}
+
+impl RotatingWriter {
+    /// Open the output path for appending, continuing the numbering of any
+    /// segments already rotated out of it
+    pub fn open<P: Into<PathBuf>>(path: P, policy: RotationPolicy) -> io::Result<Self> {
+        let path = path.into();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let len = file.metadata()?.len();
+        let at_line_start = if len == 0 {
+            true
+        } else {
+            let mut last = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            last[0] == b'\n'
+        };
+        let next_segment = segments(&path)?.last().map(|(n, _)| n + 1).unwrap_or(1);
+        Ok(RotatingWriter {
+            path,
+            policy,
+            file: Some(file),
+            len,
+            opened_at: Instant::now(),
+            at_line_start,
+            next_segment,
+            compressing: Vec::new(),
+        })
+    }
+
+    fn rotation_due(&self) -> bool {
+        self.at_line_start
+            && self.len > 0
+            && (self.policy.max_bytes.is_some_and(|max| self.len >= max)
+                || self
+                    .policy
+                    .max_age
+                    .is_some_and(|max| self.opened_at.elapsed() >= max))
+    }
+
+    /// Rename the file being written to as the next segment, and start a
+    /// new one at the output path
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        let segment = segment_path(&self.path, self.next_segment);
+        fs::rename(&self.path, &segment)?;
+        self.next_segment += 1;
+        self.file = Some(
+            OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(&self.path)?,
+        );
+        self.len = 0;
+        self.opened_at = Instant::now();
+
+        self.compressing.retain(|handle| !handle.is_finished());
+        let compression = self.policy.compression;
+        if compression != Compression::None {
+            self.compressing.push(thread::spawn(move || {
+                if let Err(e) = compress_segment(&segment, compression) {
+                    warn!(
+                        "Failed to compress log segment {}: {}",
+                        segment.display(),
+                        e
+                    );
+                }
+            }));
+        }
+        Ok(())
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        match self.file.as_mut() {
+            Some(f) => Ok(f),
+            None => Err(io::Error::other("The output file could not be reopened")),
+        }
+    }
+}
+
+impl Write for RotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.is_none() || self.rotation_due() {
+            self.rotate()?;
+        }
+        let n = self.file()?.write(buf)?;
+        if n > 0 {
+            self.len += n as u64;
+            self.at_line_start = buf[n - 1] == b'\n';
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+impl Drop for RotatingWriter {
+    /// Wait for finished segments to be compressed, so none are left
+    /// half way through when the collector exits
+    fn drop(&mut self) {
+        for handle in self.compressing.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Compress a finished segment alongside it, replacing it once the
+/// compressed copy is complete
+fn compress_segment(segment: &Path, compression: Compression) -> io::Result<PathBuf> {
+    let compressed = append_extension(segment, compression.extension().unwrap_or_default());
+    let tmp = append_extension(&compressed, "tmp");
+    let mut reader = File::open(segment)?;
+    let file = compression.compress(&mut reader, File::create(&tmp)?)?;
+    file.sync_all()?;
+    fs::rename(&tmp, &compressed)?;
+    fs::remove_file(segment)?;
+    Ok(compressed)
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(".");
+    s.push(extension);
+    PathBuf::from(s)
+}
+
+fn segment_path(path: &Path, n: u64) -> PathBuf {
+    append_extension(path, &n.to_string())
+}
+
+/// Segments rotated out of `path`, by their number. When a segment is
+/// found both compressed and not, because the collector stopped before it
+/// could remove the original, the original is used.
+fn segments(path: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(n) => format!("{}.", n),
+        None => return Ok(Vec::new()),
+    };
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut segments: Vec<(u64, bool, PathBuf)> = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let suffix = match file_name.to_str().and_then(|f| f.strip_prefix(&name)) {
+            Some(s) => s,
+            None => continue,
+        };
+        let (number, compressed) = match suffix.split_once('.') {
+            None => (suffix, false),
+            Some((number, "gz")) | Some((number, "zst")) => (number, true),
+            // Including compressed copies still being written
+            Some(_) => continue,
+        };
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(n) = number.parse::<u64>() {
+            segments.push((n, compressed, entry.path()));
+        }
+    }
+    segments.sort();
+    segments.dedup_by_key(|(n, _, _)| *n);
+    Ok(segments.into_iter().map(|(n, _, path)| (n, path)).collect())
+}
+
+/// The files of the log at `path`, in the order they were written: any
+/// segments rotated out of it, followed by `path` itself if it exists
+pub fn segment_paths<P: AsRef<Path>>(path: P) -> io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let mut paths: Vec<PathBuf> = segments(path)?.into_iter().map(|(_, p)| p).collect();
+    if path.exists() || paths.is_empty() {
+        paths.push(path.to_path_buf());
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn read_to_string(path: &Path) -> String {
+        let mut data = Vec::new();
+        File::open(path).unwrap().read_to_end(&mut data).unwrap();
+        let mut s = String::new();
+        match Compression::detect(&data) {
+            Compression::None => s = String::from_utf8(data).unwrap(),
+            Compression::Gzip => {
+                flate2::read::MultiGzDecoder::new(&data[..])
+                    .read_to_string(&mut s)
+                    .unwrap();
+            }
+            Compression::Zstd => {
+                zstd::Decoder::new(&data[..])
+                    .unwrap()
+                    .read_to_string(&mut s)
+                    .unwrap();
+            }
+        }
+        s
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("100"), Ok(100));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert_eq!(parse_size("2M"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_size("1G"), Ok(1024 * 1024 * 1024));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("12T").is_err());
+    }
+
+    #[test]
+    fn rotates_at_line_boundaries_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        let policy = RotationPolicy {
+            max_bytes: Some(10),
+            ..Default::default()
+        };
+        let mut w = RotatingWriter::open(&path, policy).unwrap();
+        // A line is never split across segments, however it's written
+        w.write_all(b"0123456789").unwrap();
+        w.write_all(b"abc\n").unwrap();
+        w.write_all(b"second\n").unwrap();
+        w.write_all(b"third line\n").unwrap();
+        w.write_all(b"4\n").unwrap();
+        drop(w);
+
+        let paths = segment_paths(&path).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                dir.path().join("log.jsonl.1"),
+                dir.path().join("log.jsonl.2"),
+                dir.path().join("log.jsonl"),
+            ]
+        );
+        assert_eq!(read_to_string(&paths[0]), "0123456789abc\n");
+        assert_eq!(read_to_string(&paths[1]), "second\nthird line\n");
+        assert_eq!(read_to_string(&paths[2]), "4\n");
+
+        // Reopening appends to the file, and continues the numbering
+        let mut w = RotatingWriter::open(&path, policy).unwrap();
+        w.write_all(b"56789abcd\n").unwrap();
+        w.write_all(b"e\n").unwrap();
+        drop(w);
+        let paths = segment_paths(&path).unwrap();
+        assert_eq!(paths[2], dir.path().join("log.jsonl.3"));
+        assert_eq!(read_to_string(&paths[2]), "4\n56789abcd\n");
+        assert_eq!(read_to_string(&path), "e\n");
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        let policy = RotationPolicy {
+            max_age: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        let mut w = RotatingWriter::open(&path, policy).unwrap();
+        w.write_all(b"first\n").unwrap();
+        w.write_all(b"still first\n").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        w.write_all(b"second\n").unwrap();
+        drop(w);
+
+        let paths = segment_paths(&path).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(read_to_string(&paths[0]), "first\nstill first\n");
+        assert_eq!(read_to_string(&paths[1]), "second\n");
+    }
+
+    #[test]
+    fn compresses_finished_segments() {
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("log.jsonl");
+            let policy = RotationPolicy {
+                max_bytes: Some(1),
+                compression,
+                ..Default::default()
+            };
+            let mut w = RotatingWriter::open(&path, policy).unwrap();
+            w.write_all(b"first\nsecond\n").unwrap();
+            w.write_all(b"third\n").unwrap();
+            w.write_all(b"fourth\n").unwrap();
+            drop(w);
+
+            let ext = compression.extension().unwrap();
+            let paths = segment_paths(&path).unwrap();
+            assert_eq!(
+                paths,
+                vec![
+                    dir.path().join(format!("log.jsonl.1.{}", ext)),
+                    dir.path().join(format!("log.jsonl.2.{}", ext)),
+                    dir.path().join("log.jsonl"),
+                ]
+            );
+            let contents: Vec<String> = paths.iter().map(|p| read_to_string(p)).collect();
+            assert_eq!(contents, vec!["first\nsecond\n", "third\n", "fourth\n"]);
+        }
+    }
+
+    #[test]
+    fn lists_segments_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        for name in &[
+            "log.jsonl.10.zst",
+            "log.jsonl.2",
+            "log.jsonl.2.gz",
+            "log.jsonl.3.gz.tmp",
+            "log.jsonl.1.gz",
+            "log.jsonl.old",
+            "other.jsonl.4",
+        ] {
+            File::create(dir.path().join(name)).unwrap();
+        }
+        assert_eq!(
+            segment_paths(&path).unwrap(),
+            vec![
+                dir.path().join("log.jsonl.1.gz"),
+                dir.path().join("log.jsonl.2"),
+                dir.path().join("log.jsonl.10.zst"),
+            ]
+        );
+
+        // A path without any segments is read on its own
+        let single = dir.path().join("other.jsonl.4");
+        assert_eq!(segment_paths(&single).unwrap(), vec![single]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Named shared memory regions that processes on the same host keep
+//! their probes in, so that a collector can read the probes directly
+//! instead of having reports sent to it.
+//!
+//! A region starts with a header, followed by the storage the process
+//! initializes its probe(s) in:
+//!
+//! | Offset | Size | Contents                                                |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 4    | `REGION_FINGERPRINT`                                    |
+//! | 4      | 4    | Reserved, zero                                          |
+//! | 8      | 8    | Address the region is mapped at in the creating process |
+//!
+//! Values are in the host's byte order. The probes' pointers hold
+//! addresses in the creating process, so collectors use the address in
+//! the header to find what they point to in their own mapping.
+
+use std::ffi::CString;
+use std::io;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+/// Marks the start of a region created by `SharedMemoryRegion::create`
+pub const REGION_FINGERPRINT: u32 = 0x4d50_5348;
+
+/// Size of the header at the start of a region
+pub const REGION_HEADER_LEN: usize = 16;
+
+/// A named shared memory region, mapped into this process
+#[derive(Debug)]
+pub struct SharedMemoryRegion {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// The mapping is only accessed through raw pointers, and stays valid
+// until the region is dropped
+unsafe impl Send for SharedMemoryRegion {}
+
+impl SharedMemoryRegion {
+    /// Create a region with room for `storage_len` bytes of probe storage,
+    /// failing if one with the same name already exists
+    pub fn create(name: &str, storage_len: usize) -> io::Result<Self> {
+        let len = REGION_HEADER_LEN + storage_len;
+        let c_name = shm_name(name)?;
+        // Safety: c_name is a valid C string for the duration of the call
+        let fd = unsafe {
+            libc::shm_open(
+                c_name.as_ptr(),
+                libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+                0o600,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Safety: fd is open until it's closed at the end of the block
+        let mapped = unsafe {
+            if libc::ftruncate(fd, len as libc::off_t) != 0 {
+                let e = io::Error::last_os_error();
+                libc::close(fd);
+                libc::shm_unlink(c_name.as_ptr());
+                return Err(e);
+            }
+            let mapped = map(fd, len);
+            libc::close(fd);
+            mapped
+        };
+        let region = match mapped {
+            Ok(ptr) => SharedMemoryRegion { ptr, len },
+            Err(e) => {
+                // Safety: c_name is a valid C string for the duration of the call
+                unsafe { libc::shm_unlink(c_name.as_ptr()) };
+                return Err(e);
+            }
+        };
+        // Safety: the header is within the mapping, which is page aligned
+        unsafe {
+            ptr::write_volatile(region.ptr.add(8) as *mut u64, region.ptr as u64);
+            ptr::write_volatile(region.ptr.add(4) as *mut u32, 0);
+            ptr::write_volatile(region.ptr as *mut u32, REGION_FINGERPRINT);
+        }
+        Ok(region)
+    }
+
+    /// Map an existing region, created by another process
+    pub fn open(name: &str) -> io::Result<Self> {
+        let c_name = shm_name(name)?;
+        // Safety: c_name is a valid C string for the duration of the call
+        let fd = unsafe { libc::shm_open(c_name.as_ptr(), libc::O_RDWR, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Safety: fd is open until it's closed at the end of the block, and
+        // stat is only read after fstat fills it in
+        let mapped = unsafe {
+            let mut stat: libc::stat = std::mem::zeroed();
+            let mapped = if libc::fstat(fd, &mut stat) != 0 {
+                Err(io::Error::last_os_error())
+            } else if (stat.st_size as usize) < REGION_HEADER_LEN {
+                Err(not_a_probe_region(name))
+            } else {
+                map(fd, stat.st_size as usize).map(|ptr| (ptr, stat.st_size as usize))
+            };
+            libc::close(fd);
+            mapped
+        };
+        let (ptr, len) = mapped?;
+        let region = SharedMemoryRegion { ptr, len };
+        if region.read_u32(0) != REGION_FINGERPRINT {
+            return Err(not_a_probe_region(name));
+        }
+        Ok(region)
+    }
+
+    /// Remove the region's name, so it's destroyed once every process has
+    /// unmapped it
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let c_name = shm_name(name)?;
+        // Safety: c_name is a valid C string for the duration of the call
+        if unsafe { libc::shm_unlink(c_name.as_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Storage to initialize probes in, following the header
+    pub fn storage(&mut self) -> &mut [MaybeUninit<u8>] {
+        // Safety: the storage is within the mapping, which outlives the borrow
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.ptr.add(REGION_HEADER_LEN) as *mut MaybeUninit<u8>,
+                self.len - REGION_HEADER_LEN,
+            )
+        }
+    }
+
+    /// Address the region is mapped at in the process which created it
+    pub fn creator_base_addr(&self) -> u64 {
+        // Safety: the header is within the mapping, which is page aligned
+        unsafe { ptr::read_volatile(self.ptr.add(8) as *const u64) }
+    }
+
+    /// Size of the region, including the header
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the region is empty, which it never is since it has a header
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read a 32 bit value at the given offset into the region, in a single
+    /// access if it's aligned. Panics if it's not within the region.
+    pub fn read_u32(&self, offset: usize) -> u32 {
+        assert!(offset + 4 <= self.len, "Read past the end of the region");
+        // Safety: just checked that the value is within the mapping
+        unsafe {
+            let src = self.ptr.add(offset);
+            if offset.is_multiple_of(4) {
+                ptr::read_volatile(src as *const u32)
+            } else {
+                ptr::read_unaligned(src as *const u32)
+            }
+        }
+    }
+
+    /// Write a 32 bit value at the given offset into the region, in a single
+    /// access if it's aligned. Panics if it's not within the region.
+    pub fn write_u32(&mut self, offset: usize, value: u32) {
+        assert!(offset + 4 <= self.len, "Write past the end of the region");
+        // Safety: just checked that the value is within the mapping
+        unsafe {
+            let dst = self.ptr.add(offset);
+            if offset.is_multiple_of(4) {
+                ptr::write_volatile(dst as *mut u32, value)
+            } else {
+                ptr::write_unaligned(dst as *mut u32, value)
+            }
+        }
+    }
+
+    /// Copy bytes starting at the given offset into the region. Panics if
+    /// they're not all within the region.
+    pub fn read_bytes(&self, offset: usize, buf: &mut [u8]) {
+        assert!(
+            offset + buf.len() <= self.len,
+            "Read past the end of the region"
+        );
+        for (i, b) in buf.iter_mut().enumerate() {
+            // Safety: just checked that the bytes are within the mapping
+            *b = unsafe { ptr::read_volatile(self.ptr.add(offset + i)) };
+        }
+    }
+}
+
+impl Drop for SharedMemoryRegion {
+    fn drop(&mut self) {
+        // Safety: the mapping is no longer used
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+/// Shared memory object names start with a slash
+fn shm_name(name: &str) -> io::Result<CString> {
+    let name = if name.starts_with('/') {
+        name.to_string()
+    } else {
+        format!("/{}", name)
+    };
+    CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Map `len` bytes of the given shared memory object for reading and writing
+unsafe fn map(fd: libc::c_int, len: usize) -> io::Result<*mut u8> {
+    let ptr = libc::mmap(
+        ptr::null_mut(),
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+        fd,
+        0,
+    );
+    if ptr == libc::MAP_FAILED {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ptr as *mut u8)
+    }
+}
+
+fn not_a_probe_region(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Shared memory region {} does not hold probes", name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_name(test: &str) -> String {
+        format!("modality-probe-test-{}-{}", test, std::process::id())
+    }
+
+    #[test]
+    fn open_created_region() {
+        let name = region_name("open");
+        let mut created = SharedMemoryRegion::create(&name, 64).unwrap();
+        assert_eq!(created.storage().len(), 64);
+        assert!(SharedMemoryRegion::create(&name, 64).is_err());
+
+        let mut opened = SharedMemoryRegion::open(&name).unwrap();
+        SharedMemoryRegion::unlink(&name).unwrap();
+        assert_eq!(opened.len(), REGION_HEADER_LEN + 64);
+        assert_eq!(opened.creator_base_addr(), created.ptr as u64);
+
+        created.write_u32(REGION_HEADER_LEN, 0x1234_5678);
+        assert_eq!(opened.read_u32(REGION_HEADER_LEN), 0x1234_5678);
+        opened.write_u32(REGION_HEADER_LEN + 5, 0xaabb_ccdd);
+        let mut buf = [0u8; 4];
+        created.read_bytes(REGION_HEADER_LEN + 5, &mut buf);
+        assert_eq!(u32::from_ne_bytes(buf), 0xaabb_ccdd);
+    }
+
+    #[test]
+    fn open_missing_region() {
+        assert!(SharedMemoryRegion::open(&region_name("missing")).is_err());
+    }
+}
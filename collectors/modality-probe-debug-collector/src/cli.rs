@@ -3,17 +3,32 @@ use std::convert::TryFrom;
 use std::fs::File;
 use std::io::prelude::*;
 use std::net::SocketAddrV4;
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 
-use goblin::elf::Elf;
+use goblin::elf::{
+    section_header::{SHF_ALLOC, SHF_WRITE},
+    Elf,
+};
 
-use modality_probe_debug_collector::{Config, ProbeAddr, TargetConfig, Word};
+use serde::Deserialize;
+
+use modality_probe_collector_common::{metrics::TrackerOpts, rotation::RotationOpts};
+use modality_probe_debug_collector::{
+    load_bias, process_executable, Config, MultiTargetConfig, ProbeAddr, ProbeSetup, TargetConfig,
+    TargetSetup, Word,
+};
 
 #[derive(Debug, Error)]
 pub enum CliError {
-    #[error(display = "Must provide at least one probe symbol/address")]
+    #[error(
+        display = "Must provide at least one probe symbol/address, or where to discover probes"
+    )]
     NoSymbolsGiven,
+    #[error(display = "Scan range is not valid \"{}\"", _0)]
+    ScanRangeNotValid(String),
     #[error(display = "Address is not valid or too large \"{}\"", _0)]
     AddressNotValid(String),
     #[error(display = "Error opening ELF file")]
@@ -24,12 +39,18 @@ pub enum CliError {
     InvalidDuration(String),
     #[error(display = "Symbol not found in given ELF file: \"{}\"", _0)]
     SymbolNotFound(String),
+    #[error(display = "Could not find where process {} loaded its executable", _0)]
+    LoadAddressUnknown(u32),
+    #[error(display = "Error reading config file: {}", _0)]
+    ConfigFileError(String),
+    #[error(display = "Target \"{}\" is not valid: {}", _0, _1)]
+    InvalidTarget(String, String),
 }
 
 #[derive(Debug, Default, StructOpt)]
 #[structopt(
     name = "modality-probe-debug-collector",
-    about = "Periodically collects logs from microcontrollers over debug interfaces; outputs them to a file."
+    about = "Periodically collects logs from microcontrollers over debug interfaces or from local processes and shared memory, or once from memory dumps; outputs them to a file."
 )]
 pub struct Opts {
     /// Session id to associate with the collected trace data
@@ -52,29 +73,100 @@ pub struct Opts {
     #[structopt(
         short = "a",
         long = "attach",
-        conflicts_with = "gdb-addr",
-        required_unless = "gdb-addr"
+        conflicts_with_all = &["gdb-addr", "dump-path"],
+        required_unless_one = &["gdb-addr", "dump-path", "pid", "shm-name", "config-path"]
     )]
     chip_type: Option<String>,
 
+    /// Index of the debug probe to attach through, when several are connected
+    #[structopt(long = "probe-index", default_value = "0")]
+    probe_index: usize,
+
     /// Address of gdb server attached to chip
-    #[structopt(short = "g", long = "gdb-addr", required_unless = "chip-type")]
+    #[structopt(
+        short = "g",
+        long = "gdb-addr",
+        conflicts_with = "dump-path",
+        required_unless_one = &["chip-type", "dump-path", "pid", "shm-name", "config-path"]
+    )]
     gdb_addr: Option<SocketAddrV4>,
 
+    /// Path of a TOML config file describing several targets to collect from concurrently,
+    /// instead of giving a single target and its probes on the command line. `--interval` is
+    /// used for targets which don't give their own.
+    #[structopt(
+        short = "c",
+        long = "config",
+        parse(from_os_str),
+        conflicts_with_all = &[
+            "chip-type", "gdb-addr", "pid", "shm-name", "dump-path", "elf-path", "word-size-32",
+            "word-size-64", "init-timeout", "discover", "scan-ranges", "probe-syms"
+        ]
+    )]
+    config_path: Option<PathBuf>,
+
+    /// Pid of a process on this host to collect from. Unless `--elf` is given, its executable is
+    /// used for symbol resolution and architecture detection.
+    #[structopt(
+        short = "p",
+        long = "pid",
+        conflicts_with_all = &["chip-type", "gdb-addr", "dump-path", "init-timeout"]
+    )]
+    pid: Option<u32>,
+
+    /// Name of a shared memory region on this host to collect from, which a process created to
+    /// keep its probes in. Probes are discovered anywhere in the region, so none need to be given.
+    #[structopt(
+        long = "shm",
+        conflicts_with_all = &["chip-type", "gdb-addr", "dump-path", "pid", "init-timeout"]
+    )]
+    shm_name: Option<String>,
+
+    /// Path of a memory dump taken from the target device to collect the final report of
+    /// each probe from, instead of attaching to it. Without `--dump-base`, the dump must be an
+    /// ELF core file.
+    #[structopt(short = "d", long = "dump", parse(from_os_str))]
+    dump_path: Option<PathBuf>,
+
+    /// Address on the target device that the memory dump starts at, for raw memory images.
+    /// Should be in hex format, prefixed with '0x' or '0X'
+    #[structopt(long = "dump-base", requires = "dump-path")]
+    dump_base_addr: Option<String>,
+
     /// Interval between collection rounds Ex: "2 min 15 sec 500 milli 250 micro"
-    #[structopt(short = "i", long = "interval")]
-    interval_duration: String,
+    #[structopt(
+        short = "i",
+        long = "interval",
+        required_unless_one = &["dump-path", "config-path"]
+    )]
+    interval_duration: Option<String>,
 
     /// Output file path
     #[structopt(short = "o", long = "output", parse(from_os_str))]
     output_path: PathBuf,
 
+    #[structopt(flatten)]
+    rotation: RotationOpts,
+
+    #[structopt(flatten)]
+    tracking: TrackerOpts,
+
     /// Reset the execution of the target device upon starting the collector, then wait
     /// `init-timeout` before attempting to read from probe state. If the initialization timeout is not long enough,
     /// the collector may error when attempting to read uninitialized probe state.
-    #[structopt(short = "r", long = "reset")]
+    #[structopt(short = "r", long = "reset", conflicts_with = "dump-path")]
     init_timeout: Option<String>,
 
+    /// Discover probes stored in the writable sections of the ELF file, in addition to the
+    /// given probe symbols/addresses
+    #[structopt(long = "discover")]
+    discover: bool,
+
+    /// Memory range to discover probes in, in addition to the given probe symbols/addresses. May
+    /// be given more than once. Ex: 0x20000000..0x20010000
+    #[structopt(long = "scan", number_of_values = 1)]
+    scan_ranges: Vec<String>,
+
     /// Symbols and/or raw addresses of probes or probe pointers.
     /// Raw addresses should be in hex format, prefixed with '0x' or '0X'
     /// Probe pointer addresses and symbols should be prefixed with `*`.
@@ -82,14 +174,277 @@ pub struct Opts {
     probe_syms: Vec<String>,
 }
 
+impl Opts {
+    /// Whether the targets to collect from are described in a config file
+    pub(crate) fn uses_config_file(&self) -> bool {
+        self.config_path.is_some()
+    }
+}
+
 /// Turn CLI options into configuration for the collector
 pub(crate) fn config_from_options(options: Opts) -> Result<Config, CliError> {
-    if options.probe_syms.is_empty() {
+    if options.probe_syms.is_empty()
+        && !options.discover
+        && options.scan_ranges.is_empty()
+        && options.shm_name.is_none()
+    {
         return Err(CliError::NoSymbolsGiven);
     }
     let mut elf_buf = Vec::new();
-    let (use_64_bit, elf_file_opt) = if let Some(elf_path) = options.elf_path.as_ref() {
-        let elf_file = open_elf(&elf_path, &mut elf_buf)?;
+    let resolver = ProbeResolver::new(&options, &mut elf_buf)?;
+
+    // Raw addresses come before symbols
+    let (addresses, symbols): (Vec<&String>, Vec<&String>) = options
+        .probe_syms
+        .iter()
+        .partition(|input| is_probe_address(input));
+    let probe_addrs = addresses
+        .into_iter()
+        .chain(symbols)
+        .map(|input| resolver.probe_addr(input))
+        .collect::<Result<Vec<_>, _>>()?;
+    let scan_ranges = resolver.scan_ranges(&options)?;
+
+    // Memory dumps are only collected from once
+    let interval = parse_optional_duration(options.interval_duration.as_ref())?.unwrap_or_default();
+    let init_timeout = parse_optional_duration(options.init_timeout.as_ref())?;
+    let target = target_config(&options)?;
+
+    Ok(modality_probe_debug_collector::Config {
+        init_timeout,
+        session_id: options.session_id.into(),
+        target,
+        interval,
+        output_path: options.output_path,
+        rotation: options.rotation.policy(),
+        tracking: options.tracking,
+        probe_addrs,
+        scan_ranges,
+    })
+}
+
+/// Turn CLI options and the config file they name into configuration for
+/// collecting from several targets. The interval given on the command line
+/// is used for targets which don't specify their own.
+pub(crate) fn multi_target_config_from_options(
+    options: Opts,
+) -> Result<MultiTargetConfig, CliError> {
+    let path = options
+        .config_path
+        .as_ref()
+        .expect("Config file path is required for multi-target configuration");
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| CliError::ConfigFileError(e.to_string()))?;
+    let file: ConfigFile =
+        toml::from_str(&contents).map_err(|e| CliError::ConfigFileError(e.to_string()))?;
+    if file.targets.is_empty() {
+        return Err(CliError::ConfigFileError(
+            "No targets were given".to_string(),
+        ));
+    }
+
+    let default_interval = parse_optional_duration(options.interval_duration.as_ref())?;
+    let retry_interval =
+        parse_optional_duration(file.retry_interval.as_ref())?.unwrap_or(DEFAULT_RETRY_INTERVAL);
+    let mut targets: Vec<TargetSetup> = Vec::new();
+    for entry in file.targets {
+        if targets.iter().any(|t| t.name == entry.name) {
+            return Err(CliError::InvalidTarget(
+                entry.name,
+                "the name is used by more than one target".to_string(),
+            ));
+        }
+        targets.push(entry.into_setup(default_interval)?);
+    }
+
+    Ok(MultiTargetConfig {
+        session_id: options.session_id.into(),
+        output_path: options.output_path,
+        rotation: options.rotation.policy(),
+        tracking: options.tracking,
+        retry_interval,
+        targets,
+    })
+}
+
+/// Time to wait before reconnecting to a target after an error, unless the
+/// config file says otherwise
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Config file describing several targets to collect from
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ConfigFile {
+    retry_interval: Option<String>,
+    #[serde(default, rename = "target")]
+    targets: Vec<TargetEntry>,
+}
+
+/// A target in the config file. Fields mirror the CLI options.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TargetEntry {
+    name: String,
+    attach: Option<String>,
+    #[serde(default)]
+    probe_index: usize,
+    gdb_addr: Option<SocketAddrV4>,
+    pid: Option<u32>,
+    shm: Option<String>,
+    dump: Option<PathBuf>,
+    dump_base: Option<String>,
+    elf: Option<PathBuf>,
+    word_size: Option<u8>,
+    interval: Option<String>,
+    reset: Option<String>,
+    #[serde(default)]
+    discover: bool,
+    #[serde(default)]
+    scan: Vec<String>,
+    #[serde(default, rename = "probe")]
+    probes: Vec<ProbeEntry>,
+}
+
+/// A probe on a target in the config file
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ProbeEntry {
+    /// Symbol or raw address of the probe or probe pointer, as on the command line
+    probe: String,
+    #[serde(default)]
+    core: usize,
+    interval: Option<String>,
+}
+
+impl TargetEntry {
+    /// Check the target's settings and resolve its probes
+    fn into_setup(self, default_interval: Option<Duration>) -> Result<TargetSetup, CliError> {
+        let name = self.name;
+        let invalid = |reason: &str| CliError::InvalidTarget(name.clone(), reason.to_string());
+        let backends = [
+            self.attach.is_some(),
+            self.gdb_addr.is_some(),
+            self.pid.is_some(),
+            self.shm.is_some(),
+            self.dump.is_some(),
+        ]
+        .iter()
+        .filter(|&&given| given)
+        .count();
+        if backends != 1 {
+            return Err(invalid(
+                "exactly one of attach, gdb-addr, pid, shm or dump must be given",
+            ));
+        }
+        if self.dump_base.is_some() && self.dump.is_none() {
+            return Err(invalid("dump-base requires dump"));
+        }
+        if self.reset.is_some() && (self.dump.is_some() || self.pid.is_some() || self.shm.is_some())
+        {
+            return Err(invalid("only devices can be reset"));
+        }
+        if self.probes.is_empty() && !self.discover && self.scan.is_empty() && self.shm.is_none() {
+            return Err(invalid(
+                "at least one probe, or where to discover probes, must be given",
+            ));
+        }
+        let (word_size_32, word_size_64) = match self.word_size {
+            None => (false, false),
+            Some(32) => (true, false),
+            Some(64) => (false, true),
+            Some(_) => return Err(invalid("word-size must be 32 or 64")),
+        };
+
+        let options = Opts {
+            word_size_32,
+            word_size_64,
+            elf_path: self.elf,
+            chip_type: self.attach,
+            probe_index: self.probe_index,
+            gdb_addr: self.gdb_addr,
+            pid: self.pid,
+            shm_name: self.shm,
+            dump_path: self.dump,
+            dump_base_addr: self.dump_base,
+            interval_duration: self.interval,
+            init_timeout: self.reset,
+            discover: self.discover,
+            scan_ranges: self.scan,
+            ..Default::default()
+        };
+        let target = target_config(&options)?;
+        let interval = match parse_optional_duration(options.interval_duration.as_ref())? {
+            Some(interval) => interval,
+            None => match default_interval {
+                Some(interval) => interval,
+                // Memory dumps are only collected from once
+                None if target.is_dump() => Duration::default(),
+                None => {
+                    return Err(invalid(
+                        "no interval was given for it or on the command line",
+                    ))
+                }
+            },
+        };
+
+        let mut elf_buf = Vec::new();
+        let resolver = ProbeResolver::new(&options, &mut elf_buf)?;
+        let probes = self
+            .probes
+            .iter()
+            .map(|probe| {
+                Ok(ProbeSetup {
+                    addr: resolver.probe_addr(&probe.probe)?,
+                    core: probe.core,
+                    interval: parse_optional_duration(probe.interval.as_ref())?,
+                })
+            })
+            .collect::<Result<Vec<_>, CliError>>()?;
+        let scan_ranges = resolver.scan_ranges(&options)?;
+
+        Ok(TargetSetup {
+            name,
+            target,
+            interval,
+            init_timeout: parse_optional_duration(options.init_timeout.as_ref())?,
+            probes,
+            scan_ranges,
+        })
+    }
+}
+
+/// Resolves the probe symbols and addresses of a target
+struct ProbeResolver<'a> {
+    use_64_bit: bool,
+    elf_file: Option<Elf<'a>>,
+    /// Offset that a position independent executable is loaded at
+    bias: u64,
+}
+
+impl<'a> ProbeResolver<'a> {
+    /// Use the target's ELF file, if there is one, and its word size options
+    fn new(options: &Opts, elf_buf: &'a mut Vec<u8>) -> Result<Self, CliError> {
+        let elf_path = options
+            .elf_path
+            .clone()
+            .or_else(|| options.pid.map(process_executable));
+        let elf_path = if let Some(elf_path) = elf_path {
+            elf_path
+        } else {
+            // Shared memory regions are created by processes on this host
+            let host_64_bit = options.shm_name.is_some() && cfg!(target_pointer_width = "64");
+            // Use 32 bit unless otherwise specified
+            if !options.word_size_32 && !options.word_size_64 && options.shm_name.is_none() {
+                println!("Warning: Pointer width not specified; using 32 bit");
+            }
+            return Ok(ProbeResolver {
+                use_64_bit: options.word_size_64 || (host_64_bit && !options.word_size_32),
+                elf_file: None,
+                bias: 0,
+            });
+        };
+
+        let elf_file = open_elf(&elf_path, elf_buf)?;
         let use_64_bit = if !options.word_size_32 && !options.word_size_64 {
             const HEADER_SIZE_32: u16 = 52;
             const HEADER_SIZE_64: u16 = 64;
@@ -99,74 +454,108 @@ pub(crate) fn config_from_options(options: Opts) -> Result<Config, CliError> {
         } else {
             options.word_size_64
         };
-        (use_64_bit, Some(elf_file))
-    } else {
-        // Use 32 bit unless otherwise specified
-        if !options.word_size_32 && !options.word_size_64 {
-            println!("Warning: Pointer width not specified; using 32 bit");
-        }
-        (options.word_size_64, None)
-    };
+        // Position independent executables are loaded at an offset
+        let bias = if let Some(pid) = options.pid {
+            load_bias(pid, &elf_file).map_err(|_e| CliError::LoadAddressUnknown(pid))?
+        } else {
+            0
+        };
+        Ok(ProbeResolver {
+            use_64_bit,
+            elf_file: Some(elf_file),
+            bias,
+        })
+    }
 
-    let mut probe_addrs = Vec::new();
-    let mut symbols = Vec::new();
-    for addr_str in options.probe_syms.iter() {
-        match parse_probe_address(addr_str, use_64_bit)? {
-            None => symbols.push(addr_str),
-            Some(probe_addr) => probe_addrs.push(probe_addr),
+    /// Resolve a probe symbol or raw address, prefixed with `*` for probe pointers
+    fn probe_addr(&self, input: &str) -> Result<ProbeAddr, CliError> {
+        if let Some(probe_addr) = parse_probe_address(input, self.use_64_bit)? {
+            return Ok(probe_addr);
+        }
+        let elf_file = self
+            .elf_file
+            .as_ref()
+            .ok_or(CliError::MissingElfFileError)?;
+        let sym_val = parse_symbol_info(elf_file, input.trim_start_matches('*'), self.use_64_bit)?
+            + self.bias;
+        if input.starts_with('*') {
+            Ok(ProbeAddr::PtrAddr(sym_val))
+        } else {
+            Ok(ProbeAddr::Addr(sym_val))
         }
     }
 
-    if let Some(elf_file) = elf_file_opt {
-        for sym in symbols {
-            let sym_val = parse_symbol_info(&elf_file, sym.trim_start_matches('*'), use_64_bit)?;
-            if sym.starts_with('*') {
-                probe_addrs.push(ProbeAddr::PtrAddr(sym_val));
-            } else {
-                probe_addrs.push(ProbeAddr::Addr(sym_val));
-            }
+    /// Memory ranges to discover probes in, from the given ranges and, if
+    /// discovery was asked for, the ELF file's writable sections
+    fn scan_ranges(&self, options: &Opts) -> Result<Vec<Range<Word>>, CliError> {
+        let mut scan_ranges = options
+            .scan_ranges
+            .iter()
+            .map(|range| parse_scan_range(range, self.use_64_bit))
+            .collect::<Result<Vec<_>, _>>()?;
+        if options.discover {
+            let elf_file = self
+                .elf_file
+                .as_ref()
+                .ok_or(CliError::MissingElfFileError)?;
+            scan_ranges.extend(writable_section_ranges(
+                elf_file,
+                self.bias,
+                self.use_64_bit,
+            )?);
         }
-    } else if !symbols.is_empty() {
-        return Err(CliError::MissingElfFileError);
+        Ok(scan_ranges)
     }
+}
 
-    let interval = parse_duration::parse(&options.interval_duration)
-        .map_err(|_e| CliError::InvalidDuration(options.interval_duration.to_string()))?;
-
-    let init_timeout = if let Some(timeout) = options.init_timeout.as_ref() {
-        Some(
-            parse_duration::parse(timeout)
-                .map_err(|_e| CliError::InvalidDuration(timeout.to_string()))?,
-        )
-    } else {
-        None
-    };
-
-    let target = if let Some(probe_rs_target) = options.chip_type {
-        TargetConfig::ProbeRsTarget(probe_rs_target)
+/// Target to collect from, as given by the CLI options
+fn target_config(options: &Opts) -> Result<TargetConfig, CliError> {
+    if let Some(dump_path) = options.dump_path.clone() {
+        if let Some(base_addr) = options.dump_base_addr.as_ref() {
+            Ok(TargetConfig::RawDump(
+                dump_path,
+                parse_hex_address(base_addr)?,
+            ))
+        } else {
+            Ok(TargetConfig::CoreDump(dump_path))
+        }
+    } else if let Some(pid) = options.pid {
+        Ok(TargetConfig::Process(pid))
+    } else if let Some(name) = options.shm_name.clone() {
+        Ok(TargetConfig::SharedMemory(name))
+    } else if let Some(probe_rs_target) = options.chip_type.clone() {
+        Ok(TargetConfig::ProbeRsTarget(
+            probe_rs_target,
+            options.probe_index,
+        ))
     } else if let Some(gdb_addr) = options.gdb_addr {
-        TargetConfig::GdbAddr(gdb_addr)
+        Ok(TargetConfig::GdbAddr(gdb_addr))
     } else {
-        // StructOpt will exit if neither are provided
+        // StructOpt will exit if none are provided
         unreachable!()
-    };
+    }
+}
 
-    Ok(modality_probe_debug_collector::Config {
-        init_timeout,
-        session_id: options.session_id.into(),
-        target,
-        interval,
-        output_path: options.output_path,
-        probe_addrs,
-    })
+/// Parse a duration, if one was given. Ex: "2 min 15 sec"
+fn parse_optional_duration(input: Option<&String>) -> Result<Option<Duration>, CliError> {
+    input
+        .map(|duration| {
+            parse_duration::parse(duration)
+                .map_err(|_e| CliError::InvalidDuration(duration.to_string()))
+        })
+        .transpose()
+}
+
+/// Whether a probe argument is a raw address rather than a symbol
+fn is_probe_address(input: &str) -> bool {
+    ["0x", "0X", "*0x", "*0X"]
+        .iter()
+        .any(|prefix| input.starts_with(prefix))
 }
 
 /// Parse a probe address from a given argument, or return none in case of a symbol
 fn parse_probe_address(input: &str, use_64_bit: bool) -> Result<Option<ProbeAddr>, CliError> {
-    let is_address = ["0x", "0X", "*0x", "*0X"]
-        .iter()
-        .any(|prefix| input.starts_with(prefix));
-    if !is_address {
+    if !is_probe_address(input) {
         // Input is a symbol
         return Ok(None);
     }
@@ -192,6 +581,64 @@ fn parse_probe_address(input: &str, use_64_bit: bool) -> Result<Option<ProbeAddr
     }
 }
 
+/// Parse a memory range to discover probes in, given as `<start>..<end>` in hex
+fn parse_scan_range(input: &str, use_64_bit: bool) -> Result<Range<Word>, CliError> {
+    let mut bounds = input.splitn(2, "..");
+    let (start, end) = match (bounds.next(), bounds.next()) {
+        (Some(start), Some(end)) => (
+            parse_hex_address(start)
+                .map_err(|_e| CliError::ScanRangeNotValid(input.to_string()))?,
+            parse_hex_address(end).map_err(|_e| CliError::ScanRangeNotValid(input.to_string()))?,
+        ),
+        _ => return Err(CliError::ScanRangeNotValid(input.to_string())),
+    };
+    if start >= end {
+        return Err(CliError::ScanRangeNotValid(input.to_string()));
+    }
+    Ok(address_word(start, input, use_64_bit)?..address_word(end, input, use_64_bit)?)
+}
+
+/// Memory ranges of the ELF file's writable sections, where probes in
+/// statics are stored
+fn writable_section_ranges(
+    elf_file: &Elf,
+    bias: u64,
+    use_64_bit: bool,
+) -> Result<Vec<Range<Word>>, CliError> {
+    let flags = u64::from(SHF_ALLOC | SHF_WRITE);
+    elf_file
+        .section_headers
+        .iter()
+        .filter(|sh| sh.sh_flags & flags == flags && sh.sh_size != 0)
+        .map(|sh| {
+            let name = elf_file.shdr_strtab.get_unsafe(sh.sh_name).unwrap_or("");
+            let start = sh.sh_addr + bias;
+            Ok(address_word(start, name, use_64_bit)?
+                ..address_word(start + sh.sh_size, name, use_64_bit)?)
+        })
+        .collect()
+}
+
+/// Turn an address into a word of the target's size
+fn address_word(addr: u64, input: &str, use_64_bit: bool) -> Result<Word, CliError> {
+    if use_64_bit {
+        Ok(Word::U64(addr))
+    } else {
+        let addr_32 =
+            u32::try_from(addr).map_err(|_e| CliError::AddressNotValid(input.to_string()))?;
+        Ok(Word::U32(addr_32))
+    }
+}
+
+/// Parse a hex address prefixed with '0x' or '0X'
+fn parse_hex_address(input: &str) -> Result<u64, CliError> {
+    let trimmed = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .ok_or_else(|| CliError::AddressNotValid(input.to_string()))?;
+    u64::from_str_radix(trimmed, 16).map_err(|_e| CliError::AddressNotValid(input.to_string()))
+}
+
 /// Open elf file for parsing
 fn open_elf<'a>(path: &PathBuf, elf_buf: &'a mut Vec<u8>) -> Result<Elf<'a>, CliError> {
     let mut file = File::open(path).map_err(|_e| CliError::ElfFileError)?;
@@ -230,12 +677,12 @@ fn parse_symbol_info(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use modality_probe_collector_common::rotation::{Compression, RotationPolicy};
     use std::format;
     use std::fs::canonicalize;
     use std::process::Command;
     use std::str::from_utf8;
     use std::str::FromStr;
-    use std::time::Duration;
 
     const SYMBOLS_32_BIN_PATH: &str =
         "./tests/symbols-example/target/thumbv7em-none-eabihf/debug/symbols-example";
@@ -288,10 +735,41 @@ mod tests {
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
-                probe_addrs: vec![ProbeAddr::Addr(Word::U32(0x100))]
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![ProbeAddr::Addr(Word::U32(0x100))],
+                scan_ranges: vec![]
+            }
+        )
+    }
+
+    /// Rotate the output file
+    #[test]
+    fn specify_rotation() {
+        assert_eq!(
+            config_from_options(
+                options_from_str(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --attach stm32 \
+                --interval 1s \
+                --output ./out \
+                --rotate-size 10M \
+                --rotate-interval 60 \
+                --compress gzip \
+                0x100"
+                )
+                .unwrap()
+            )
+            .unwrap()
+            .rotation,
+            RotationPolicy {
+                max_bytes: Some(10 * 1024 * 1024),
+                max_age: Some(Duration::from_secs(60)),
+                compression: Compression::Gzip,
             }
         )
     }
@@ -318,9 +796,299 @@ mod tests {
                 target: TargetConfig::GdbAddr(SocketAddrV4::from_str("127.0.0.1:3000").unwrap()),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
-                probe_addrs: vec![ProbeAddr::Addr(Word::U32(0x100))]
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![ProbeAddr::Addr(Word::U32(0x100))],
+                scan_ranges: vec![]
+            }
+        )
+    }
+
+    /// Collect from a raw memory image instead of attaching
+    #[test]
+    fn specify_raw_dump() {
+        assert_eq!(
+            config_from_options(
+                options_from_str(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --dump ./ram.bin \
+                --dump-base 0x20000000 \
+                --output ./out \
+                0x20000100"
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Config {
+                init_timeout: None,
+                session_id: 0.into(),
+                target: TargetConfig::RawDump("./ram.bin".into(), 0x20000000),
+                interval: Duration::default(),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![ProbeAddr::Addr(Word::U32(0x20000100))],
+                scan_ranges: vec![]
+            }
+        )
+    }
+
+    /// Collect from an ELF core file instead of attaching
+    #[test]
+    fn specify_core_dump() {
+        assert_eq!(
+            config_from_options(
+                options_from_str(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --dump ./core \
+                --64-bit \
+                --output ./out \
+                *0x100"
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Config {
+                init_timeout: None,
+                session_id: 0.into(),
+                target: TargetConfig::CoreDump("./core".into()),
+                interval: Duration::default(),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![ProbeAddr::PtrAddr(Word::U64(0x100))],
+                scan_ranges: vec![]
+            }
+        )
+    }
+
+    #[cfg(all(target_pointer_width = "64", target_os = "linux"))]
+    #[no_mangle]
+    static MODALITY_CLI_TEST_PROBE: u32 = 0;
+
+    /// Collect from a process, resolving symbols with its executable
+    #[cfg(all(target_pointer_width = "64", target_os = "linux"))]
+    #[test]
+    fn specify_process() {
+        let pid = std::process::id();
+        assert_eq!(
+            config_from_options(
+                options_from_str(&format!(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --pid {} \
+                --interval 1s \
+                --output ./out \
+                MODALITY_CLI_TEST_PROBE 0x100",
+                    pid
+                ))
+                .unwrap()
+            )
+            .unwrap(),
+            Config {
+                init_timeout: None,
+                session_id: 0.into(),
+                target: TargetConfig::Process(pid),
+                interval: Duration::from_millis(1000),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![
+                    ProbeAddr::Addr(Word::U64(0x100)),
+                    ProbeAddr::Addr(Word::U64(&MODALITY_CLI_TEST_PROBE as *const u32 as u64)),
+                ],
+                scan_ranges: vec![]
+            }
+        );
+        assert!(options_from_str(&format!(
+            "modality-probe-debug-collector \
+            --session-id 0 \
+            --pid {} \
+            --reset 1s \
+            --interval 1s \
+            --output ./out \
+            0x100",
+            pid
+        ))
+        .is_err());
+    }
+
+    /// Collect from a shared memory region, whose probes needn't be given
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn specify_shared_memory() {
+        assert_eq!(
+            config_from_options(
+                options_from_str(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --shm probes \
+                --interval 1s \
+                --output ./out"
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Config {
+                init_timeout: None,
+                session_id: 0.into(),
+                target: TargetConfig::SharedMemory("probes".to_string()),
+                interval: Duration::from_millis(1000),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![],
+                scan_ranges: vec![]
             }
+        );
+        assert!(options_from_str(
+            "modality-probe-debug-collector \
+            --session-id 0 \
+            --shm probes \
+            --reset 1s \
+            --interval 1s \
+            --output ./out"
         )
+        .is_err());
+    }
+
+    /// Discover probes in the given ranges, without any probe addresses
+    #[test]
+    fn specify_scan_ranges() {
+        assert_eq!(
+            config_from_options(
+                options_from_str(
+                    "modality-probe-debug-collector \
+                --session-id 0 \
+                --attach stm32 \
+                --interval 1s \
+                --output ./out \
+                --scan 0x20000000..0x20001000 \
+                --scan 0X20008000..0X20009000"
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Config {
+                init_timeout: None,
+                session_id: 0.into(),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
+                interval: Duration::from_millis(1000),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![],
+                scan_ranges: vec![
+                    Word::U32(0x20000000)..Word::U32(0x20001000),
+                    Word::U32(0x20008000)..Word::U32(0x20009000),
+                ]
+            }
+        );
+        for range in &[
+            "0x2000..0x1000",
+            "0x1000",
+            "0x1000..",
+            "0x1000..0x100000000",
+        ] {
+            assert!(config_from_options(
+                options_from_str(&format!(
+                    "modality-probe-debug-collector \
+                    --session-id 0 \
+                    --attach stm32 \
+                    --interval 1s \
+                    --output ./out \
+                    --32-bit \
+                    --scan {}",
+                    range
+                ))
+                .unwrap()
+            )
+            .is_err());
+        }
+    }
+
+    /// Discovery in the ELF file's sections needs an ELF file
+    #[test]
+    fn error_discover_without_elf() {
+        assert!(config_from_options(
+            options_from_str(
+                "modality-probe-debug-collector \
+            --session-id 0 \
+            --attach stm32 \
+            --interval 1s \
+            --output ./out \
+            --discover",
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    /// Discover probes in the writable sections of a process's executable
+    #[cfg(all(target_pointer_width = "64", target_os = "linux"))]
+    #[test]
+    fn discover_in_process_sections() {
+        let config = config_from_options(
+            options_from_str(&format!(
+                "modality-probe-debug-collector \
+                --session-id 0 \
+                --pid {} \
+                --interval 1s \
+                --output ./out \
+                --discover",
+                std::process::id()
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(config.probe_addrs.is_empty());
+        let scanned = |addr: u64| {
+            config
+                .scan_ranges
+                .iter()
+                .any(|r| u64::from(r.start) <= addr && addr < u64::from(r.end))
+        };
+        // MODALITY_CLI_TEST_PROBE is in a read-only section
+        assert!(!scanned(&MODALITY_CLI_TEST_PROBE as *const u32 as u64));
+        static mut WRITABLE: u32 = 0;
+        assert!(scanned(std::ptr::addr_of!(WRITABLE) as u64));
+    }
+
+    /// Should error if a dump is given along with a device to attach to or reset
+    #[test]
+    fn error_on_dump_and_device() {
+        assert!(options_from_str(
+            "modality-probe-debug-collector \
+            --session-id 0 \
+            --dump ./core \
+            --attach stm32 \
+            --output ./out \
+            0x100",
+        )
+        .is_err());
+        assert!(options_from_str(
+            "modality-probe-debug-collector \
+            --session-id 0 \
+            --dump ./core \
+            --reset 1s \
+            --output ./out \
+            0x100",
+        )
+        .is_err());
+        assert!(config_from_options(
+            options_from_str(
+                "modality-probe-debug-collector \
+            --session-id 0 \
+            --dump ./ram.bin \
+            --dump-base 20000000 \
+            --output ./out \
+            0x100",
+            )
+            .unwrap()
+        )
+        .is_err());
     }
 
     /// Should error if no probe symbols/addresses supplied
@@ -425,14 +1193,17 @@ mod tests {
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
                 probe_addrs: vec![
                     ProbeAddr::Addr(Word::U32(0x20000000)),
                     ProbeAddr::Addr(Word::U32(0x20000004)),
                     ProbeAddr::Addr(Word::U32(0x20000008))
-                ]
+                ],
+                scan_ranges: vec![]
             }
         )
     }
@@ -460,9 +1231,11 @@ mod tests {
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
                 probe_addrs: vec![
                     ProbeAddr::Addr(Word::U32(0x1)),
                     ProbeAddr::Addr(Word::U32(0x10)),
@@ -470,7 +1243,8 @@ mod tests {
                     ProbeAddr::Addr(Word::U32(0x20000000)),
                     ProbeAddr::Addr(Word::U32(0x20000004)),
                     ProbeAddr::Addr(Word::U32(0x20000008))
-                ]
+                ],
+                scan_ranges: vec![]
             }
         )
     }
@@ -514,10 +1288,13 @@ mod tests {
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
-                probe_addrs: vec![ProbeAddr::Addr(Word::U64(0x1))]
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                probe_addrs: vec![ProbeAddr::Addr(Word::U64(0x1))],
+                scan_ranges: vec![]
             }
         )
     }
@@ -544,9 +1321,11 @@ mod tests {
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
                 probe_addrs: vec![
                     ProbeAddr::PtrAddr(Word::U32(0x1)),
                     ProbeAddr::PtrAddr(Word::U32(0x10)),
@@ -554,7 +1333,8 @@ mod tests {
                     ProbeAddr::Addr(Word::U32(0x20000000)),
                     ProbeAddr::PtrAddr(Word::U32(0x20000004)),
                     ProbeAddr::PtrAddr(Word::U32(0x20000008))
-                ]
+                ],
+                scan_ranges: vec![]
             }
         )
     }
@@ -565,7 +1345,7 @@ mod tests {
         compile_symbol_example();
         assert_eq!(
             config_from_options(
-                options_from_str(&format!(
+                options_from_str(
                     "modality-probe-debug-collector \
                     --session-id 0 \
                     --attach stm32 \
@@ -573,22 +1353,173 @@ mod tests {
                     --output ./out \
                     --64-bit \
                     *0X1 0x10 *0x100"
-                ))
+                )
                 .unwrap()
             )
             .unwrap(),
             Config {
                 init_timeout: None,
                 session_id: 0.into(),
-                target: TargetConfig::ProbeRsTarget("stm32".to_string()),
+                target: TargetConfig::ProbeRsTarget("stm32".to_string(), 0),
                 interval: Duration::from_millis(1000),
                 output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
                 probe_addrs: vec![
                     ProbeAddr::PtrAddr(Word::U64(0x1)),
                     ProbeAddr::Addr(Word::U64(0x10)),
                     ProbeAddr::PtrAddr(Word::U64(0x100)),
+                ],
+                scan_ranges: vec![]
+            }
+        )
+    }
+
+    /// Write a config file and parse the options that name it
+    fn multi_target_config_from_file(
+        contents: &str,
+        args: &str,
+    ) -> Result<MultiTargetConfig, CliError> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        multi_target_config_from_options(
+            options_from_str(&format!(
+                "modality-probe-debug-collector --config {} {}",
+                file.path().display(),
+                args
+            ))
+            .unwrap(),
+        )
+    }
+
+    /// Describe several targets in a config file
+    #[test]
+    fn specify_config_file() {
+        let config = multi_target_config_from_file(
+            r#"
+retry-interval = "5s"
+
+[[target]]
+name = "soc"
+attach = "stm32"
+probe-index = 1
+word-size = 32
+
+[[target.probe]]
+probe = "0x20000000"
+
+[[target.probe]]
+probe = "*0x10000000"
+core = 1
+interval = "100ms"
+
+[[target]]
+name = "board"
+gdb-addr = "127.0.0.1:3000"
+interval = "2s"
+reset = "1s"
+scan = ["0x20000000..0x20001000"]
+
+[[target]]
+name = "crashed"
+dump = "./core"
+word-size = 64
+
+[[target.probe]]
+probe = "0x100"
+"#,
+            "--session-id 3 --interval 1s --output ./out",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            MultiTargetConfig {
+                session_id: 3.into(),
+                output_path: "./out".into(),
+                rotation: RotationPolicy::default(),
+                tracking: TrackerOpts::default(),
+                retry_interval: Duration::from_secs(5),
+                targets: vec![
+                    TargetSetup {
+                        name: "soc".to_string(),
+                        target: TargetConfig::ProbeRsTarget("stm32".to_string(), 1),
+                        interval: Duration::from_secs(1),
+                        init_timeout: None,
+                        probes: vec![
+                            ProbeSetup {
+                                addr: ProbeAddr::Addr(Word::U32(0x20000000)),
+                                core: 0,
+                                interval: None,
+                            },
+                            ProbeSetup {
+                                addr: ProbeAddr::PtrAddr(Word::U32(0x10000000)),
+                                core: 1,
+                                interval: Some(Duration::from_millis(100)),
+                            },
+                        ],
+                        scan_ranges: vec![],
+                    },
+                    TargetSetup {
+                        name: "board".to_string(),
+                        target: TargetConfig::GdbAddr(
+                            SocketAddrV4::from_str("127.0.0.1:3000").unwrap()
+                        ),
+                        interval: Duration::from_secs(2),
+                        init_timeout: Some(Duration::from_secs(1)),
+                        probes: vec![],
+                        scan_ranges: vec![Word::U32(0x20000000)..Word::U32(0x20001000)],
+                    },
+                    TargetSetup {
+                        name: "crashed".to_string(),
+                        target: TargetConfig::CoreDump("./core".into()),
+                        interval: Duration::from_secs(1),
+                        init_timeout: None,
+                        probes: vec![ProbeSetup {
+                            addr: ProbeAddr::Addr(Word::U64(0x100)),
+                            core: 0,
+                            interval: None,
+                        }],
+                        scan_ranges: vec![],
+                    },
                 ]
             }
+        );
+    }
+
+    /// Should error on config files with invalid targets
+    #[test]
+    fn error_invalid_config_file() {
+        for contents in &[
+            // No targets
+            "retry-interval = \"1s\"",
+            // Unknown field
+            "[[target]]\nname = \"a\"\nattach = \"stm32\"\nscan = [\"0x0..0x10\"]\nspeed = 1",
+            // More than one backend
+            "[[target]]\nname = \"a\"\nattach = \"stm32\"\npid = 1\nscan = [\"0x0..0x10\"]",
+            // No probes
+            "[[target]]\nname = \"a\"\nattach = \"stm32\"",
+            // Duplicate name
+            "[[target]]\nname = \"a\"\nattach = \"stm32\"\nscan = [\"0x0..0x10\"]\n\
+             [[target]]\nname = \"a\"\nattach = \"stm32\"\nscan = [\"0x0..0x10\"]",
+        ] {
+            assert!(
+                multi_target_config_from_file(contents, "--interval 1s --output ./out").is_err()
+            );
+        }
+        // No interval for a live target
+        assert!(multi_target_config_from_file(
+            "[[target]]\nname = \"a\"\nattach = \"stm32\"\nscan = [\"0x0..0x10\"]",
+            "--output ./out"
         )
+        .is_err());
+        // Targets can't also be given on the command line
+        assert!(options_from_str(
+            "modality-probe-debug-collector \
+            --config ./targets.toml \
+            --attach stm32 \
+            --interval 1s \
+            --output ./out"
+        )
+        .is_err());
     }
 }
@@ -1,3 +1,5 @@
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
 #![allow(dead_code)]
 
 use structopt::{clap::Shell, StructOpt};
@@ -0,0 +1,463 @@
+//! Target that accesses device memory through a gdb server, using the
+//! GDB remote serial protocol over TCP
+use std::io::{BufReader, Read, Write};
+use std::net::{SocketAddrV4, TcpStream};
+use std::time::Duration;
+
+use crate::{Target, TargetError, Word};
+
+/// How long to wait for the gdb server to reply to a packet
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many times a packet is resent when the gdb server asks for it
+const MAX_RETRANSMISSIONS: usize = 3;
+
+/// Most bytes of memory read with a single packet, which keeps replies
+/// within the packet size of common gdb servers
+const MAX_READ_LEN: usize = 512;
+
+/// Target that reads and writes device memory with the `m` and `M`
+/// packets of a gdb server, like OpenOCD, QEMU's gdbstub or a J-Link
+/// GDB server. Multi-byte values are read as little endian.
+pub struct GdbTarget {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// Whether the gdb server agreed to stop acknowledging packets
+    no_ack_mode: bool,
+}
+
+impl GdbTarget {
+    /// Connect to the gdb server, switching to no-ack mode if it supports it
+    pub fn connect(addr: SocketAddrV4) -> Result<Self, TargetError> {
+        let stream = TcpStream::connect(addr).map_err(TargetError::GdbIoError)?;
+        stream
+            .set_read_timeout(Some(REPLY_TIMEOUT))
+            .map_err(TargetError::GdbIoError)?;
+        stream.set_nodelay(true).map_err(TargetError::GdbIoError)?;
+        let writer = stream.try_clone().map_err(TargetError::GdbIoError)?;
+        let mut target = GdbTarget {
+            reader: BufReader::new(stream),
+            writer,
+            no_ack_mode: false,
+        };
+
+        let supported = target.command("qSupported")?;
+        if supported
+            .split(|b| *b == b';')
+            .any(|feature| feature == b"QStartNoAckMode+")
+        {
+            target.expect_ok("QStartNoAckMode")?;
+            target.no_ack_mode = true;
+        }
+        Ok(target)
+    }
+
+    /// Read `buf.len()` bytes of device memory starting at `addr`
+    pub fn read_memory(&mut self, addr: u64, buf: &mut [u8]) -> Result<(), TargetError> {
+        let reply = self.command(&format!("m{:x},{:x}", addr, buf.len()))?;
+        check_error_reply(&reply)?;
+        if reply.len() != buf.len() * 2 {
+            return Err(unexpected_reply(&reply));
+        }
+        for (b, hex) in buf.iter_mut().zip(reply.chunks(2)) {
+            *b = parse_hex_byte(hex).ok_or_else(|| unexpected_reply(&reply))?;
+        }
+        Ok(())
+    }
+
+    /// Write `data` to device memory starting at `addr`
+    pub fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), TargetError> {
+        let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+        self.expect_ok(&format!("M{:x},{:x}:{}", addr, data.len(), hex))
+    }
+
+    /// Send a command packet and wait for the reply to it
+    fn command(&mut self, packet: &str) -> Result<Vec<u8>, TargetError> {
+        self.send_packet(packet.as_bytes())?;
+        self.recv_packet()
+    }
+
+    fn expect_ok(&mut self, packet: &str) -> Result<(), TargetError> {
+        let reply = self.command(packet)?;
+        check_error_reply(&reply)?;
+        if reply == b"OK" {
+            Ok(())
+        } else {
+            Err(unexpected_reply(&reply))
+        }
+    }
+
+    fn send_packet(&mut self, data: &[u8]) -> Result<(), TargetError> {
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        packet.extend_from_slice(data);
+        packet.extend_from_slice(format!("#{:02x}", checksum(data)).as_bytes());
+
+        for _ in 0..=MAX_RETRANSMISSIONS {
+            self.writer
+                .write_all(&packet)
+                .map_err(TargetError::GdbIoError)?;
+            if self.no_ack_mode {
+                return Ok(());
+            }
+            match self.read_byte()? {
+                b'+' => return Ok(()),
+                b'-' => continue,
+                b => return Err(unexpected_reply(&[b])),
+            }
+        }
+        Err(TargetError::GdbProtocolError(
+            "gdb server kept rejecting a packet".to_string(),
+        ))
+    }
+
+    fn recv_packet(&mut self) -> Result<Vec<u8>, TargetError> {
+        for _ in 0..=MAX_RETRANSMISSIONS {
+            // Skip acknowledgements and anything else up to the start of the packet
+            while self.read_byte()? != b'$' {}
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    b'#' => break,
+                    b => data.push(b),
+                }
+            }
+            let checksum_hex = [self.read_byte()?, self.read_byte()?];
+            let valid = parse_hex_byte(&checksum_hex) == Some(checksum(&data));
+            if self.no_ack_mode {
+                return if valid {
+                    decode_packet_data(&data)
+                } else {
+                    Err(TargetError::GdbProtocolError(
+                        "reply checksum mismatch".to_string(),
+                    ))
+                };
+            }
+            self.writer
+                .write_all(if valid { b"+" } else { b"-" })
+                .map_err(TargetError::GdbIoError)?;
+            if valid {
+                return decode_packet_data(&data);
+            }
+        }
+        Err(TargetError::GdbProtocolError(
+            "gdb server kept sending corrupted replies".to_string(),
+        ))
+    }
+
+    fn read_byte(&mut self) -> Result<u8, TargetError> {
+        let mut b = [0u8];
+        self.reader
+            .read_exact(&mut b)
+            .map_err(TargetError::GdbIoError)?;
+        Ok(b[0])
+    }
+}
+
+impl Target for GdbTarget {
+    /// Reset through the gdb server's `reset` monitor command
+    fn reset(&mut self) -> Result<(), TargetError> {
+        let hex: String = b"reset".iter().map(|b| format!("{:02x}", b)).collect();
+        self.send_packet(format!("qRcmd,{}", hex).as_bytes())?;
+        loop {
+            let reply = self.recv_packet()?;
+            // Console output from the monitor command precedes the result
+            if reply.len() > 1 && reply[0] == b'O' && reply != b"OK" {
+                continue;
+            }
+            check_error_reply(&reply)?;
+            return if reply == b"OK" {
+                Ok(())
+            } else {
+                Err(unexpected_reply(&reply))
+            };
+        }
+    }
+
+    fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
+        match addr {
+            Word::U32(_) => self.read_32(addr).map(Word::U32),
+            Word::U64(addr_raw) => {
+                let mut buf = [0u8; 8];
+                self.read_memory(addr_raw, &mut buf)?;
+                Ok(Word::U64(u64::from_le_bytes(buf)))
+            }
+        }
+    }
+
+    fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
+        let mut buf = [0u8; 4];
+        self.read_memory(addr.into(), &mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
+        let mut buf = [0u8];
+        self.read_memory(addr.into(), &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError> {
+        self.write_memory(addr.into(), &data.to_le_bytes())
+    }
+
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        let addr = u64::from(addr);
+        for (i, chunk) in buf.chunks_mut(MAX_READ_LEN).enumerate() {
+            self.read_memory(addr + (i * MAX_READ_LEN) as u64, chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+fn parse_hex_byte(hex: &[u8]) -> Option<u8> {
+    let s = std::str::from_utf8(hex).ok()?;
+    u8::from_str_radix(s, 16).ok()
+}
+
+/// Undo the escaping and run-length encoding of a packet's data
+fn decode_packet_data(data: &[u8]) -> Result<Vec<u8>, TargetError> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'}' => {
+                let escaped = bytes.next().ok_or_else(|| unexpected_reply(data))?;
+                decoded.push(escaped ^ 0x20);
+            }
+            b'*' => {
+                let count = bytes
+                    .next()
+                    .and_then(|n| n.checked_sub(29))
+                    .ok_or_else(|| unexpected_reply(data))?;
+                let repeated = *decoded.last().ok_or_else(|| unexpected_reply(data))?;
+                decoded.extend(std::iter::repeat_n(repeated, count as usize));
+            }
+            _ => decoded.push(b),
+        }
+    }
+    Ok(decoded)
+}
+
+fn check_error_reply(reply: &[u8]) -> Result<(), TargetError> {
+    if reply.len() == 3 && reply[0] == b'E' && parse_hex_byte(&reply[1..]).is_some() {
+        Err(TargetError::GdbErrorReply(
+            String::from_utf8_lossy(reply).into_owned(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn unexpected_reply(reply: &[u8]) -> TargetError {
+    TargetError::GdbProtocolError(format!(
+        "unexpected reply \"{}\"",
+        String::from_utf8_lossy(reply)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dumped_probe, dumped_probe_report};
+    use crate::{Collector, ProbeAddr};
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+    use std::mem::MaybeUninit;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::rc::Rc;
+    use std::thread;
+
+    /// Minimal gdb server serving a memory image, which run-length encodes
+    /// its replies
+    struct FakeGdbServer {
+        stream: TcpStream,
+        base_addr: u64,
+        memory: Vec<u8>,
+        supports_no_ack_mode: bool,
+        no_ack_mode: bool,
+    }
+
+    impl FakeGdbServer {
+        /// Serve a single connection in the background
+        fn spawn(base_addr: u64, memory: Vec<u8>, supports_no_ack_mode: bool) -> SocketAddrV4 {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            let addr = match listener.local_addr().unwrap() {
+                std::net::SocketAddr::V4(addr) => addr,
+                _ => unreachable!(),
+            };
+            thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                stream.set_nodelay(true).unwrap();
+                FakeGdbServer {
+                    stream,
+                    base_addr,
+                    memory,
+                    supports_no_ack_mode,
+                    no_ack_mode: false,
+                }
+                .serve();
+            });
+            addr
+        }
+
+        fn serve(&mut self) {
+            let mut reader = BufReader::new(self.stream.try_clone().unwrap());
+            loop {
+                let mut b = [0u8];
+                // Client hung up
+                if reader.read_exact(&mut b).is_err() {
+                    return;
+                }
+                if b[0] != b'$' {
+                    continue;
+                }
+                let mut packet = Vec::new();
+                let mut checksum_hex = [0u8; 2];
+                loop {
+                    reader.read_exact(&mut b).unwrap();
+                    if b[0] == b'#' {
+                        break;
+                    }
+                    packet.push(b[0]);
+                }
+                reader.read_exact(&mut checksum_hex).unwrap();
+                assert_eq!(parse_hex_byte(&checksum_hex), Some(checksum(&packet)));
+                if !self.no_ack_mode {
+                    self.stream.write_all(b"+").unwrap();
+                }
+                let packet = String::from_utf8(packet).unwrap();
+                let reply = self.reply(&packet);
+                self.send(&reply);
+                if !self.no_ack_mode {
+                    reader.read_exact(&mut b).unwrap();
+                    assert_eq!(b[0], b'+');
+                }
+                if packet == "QStartNoAckMode" {
+                    self.no_ack_mode = true;
+                }
+            }
+        }
+
+        fn reply(&mut self, packet: &str) -> String {
+            if packet == "qSupported" {
+                if self.supports_no_ack_mode {
+                    "PacketSize=4000;QStartNoAckMode+".to_string()
+                } else {
+                    "PacketSize=4000".to_string()
+                }
+            } else if packet == "QStartNoAckMode" && self.supports_no_ack_mode {
+                "OK".to_string()
+            } else if let Some(args) = packet.strip_prefix('m') {
+                let (addr, len) = Self::parse_range(args);
+                match self.range(addr, len) {
+                    Some(r) => r.iter().map(|b| format!("{:02x}", b)).collect(),
+                    None => "E01".to_string(),
+                }
+            } else if let Some(args) = packet.strip_prefix('M') {
+                let mut parts = args.splitn(2, ':');
+                let (addr, len) = Self::parse_range(parts.next().unwrap());
+                let data = parts.next().unwrap().as_bytes();
+                match self.range(addr, len) {
+                    Some(r) => {
+                        for (b, hex) in r.iter_mut().zip(data.chunks(2)) {
+                            *b = parse_hex_byte(hex).unwrap();
+                        }
+                        "OK".to_string()
+                    }
+                    None => "E01".to_string(),
+                }
+            } else {
+                // Unsupported
+                String::new()
+            }
+        }
+
+        fn parse_range(args: &str) -> (u64, usize) {
+            let mut parts = args.splitn(2, ',');
+            let addr = u64::from_str_radix(parts.next().unwrap(), 16).unwrap();
+            let len = usize::from_str_radix(parts.next().unwrap(), 16).unwrap();
+            (addr, len)
+        }
+
+        fn range(&mut self, addr: u64, len: usize) -> Option<&mut [u8]> {
+            let start = addr.checked_sub(self.base_addr)? as usize;
+            self.memory.get_mut(start..start + len)
+        }
+
+        fn send(&mut self, reply: &str) {
+            let mut data = Vec::new();
+            let reply = reply.as_bytes();
+            let mut i = 0;
+            while i < reply.len() {
+                let run = reply[i..].iter().take_while(|b| **b == reply[i]).count();
+                // Repeat counts which would encode as '#' or '$' aren't allowed
+                let repeats = match run - 1 {
+                    n if n < 3 => 0,
+                    6 | 7 => 5,
+                    n => n.min(97),
+                };
+                data.push(reply[i]);
+                if repeats > 0 {
+                    data.push(b'*');
+                    data.push(repeats as u8 + 29);
+                }
+                i += 1 + repeats;
+            }
+            let mut packet = vec![b'$'];
+            packet.extend_from_slice(&data);
+            packet.extend_from_slice(format!("#{:02x}", checksum(&data)).as_bytes());
+            self.stream.write_all(&packet).unwrap();
+        }
+    }
+
+    #[test]
+    fn decode_escaped_and_run_length_encoded_data() {
+        assert_eq!(decode_packet_data(b"0* 1}\x03").unwrap(), b"00001#");
+        assert!(decode_packet_data(b"*").is_err());
+    }
+
+    fn collect_over_gdb(supports_no_ack_mode: bool) {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (_probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) }.to_vec();
+        let server_addr = FakeGdbServer::spawn(addr.into(), image, supports_no_ack_mode);
+
+        let target = Rc::new(RefCell::new(GdbTarget::connect(server_addr).unwrap()));
+        assert_eq!(target.borrow().no_ack_mode, supports_no_ack_mode);
+        let mut collector = Collector::initialize(&ProbeAddr::Addr(addr), target.clone()).unwrap();
+        assert_eq!(
+            collector.collect_report().unwrap().unwrap(),
+            dumped_probe_report()
+        );
+        assert_eq!(collector.collect_report().unwrap(), None);
+
+        let mut target = target.borrow_mut();
+        target.write_32(addr + 4, 0xDEAD_BEEF).unwrap();
+        assert_eq!(target.read_32(addr + 4).unwrap(), 0xDEAD_BEEF);
+        assert!(matches!(
+            target.read_32(addr + 1024),
+            Err(TargetError::GdbErrorReply(_))
+        ));
+        // No reset monitor command
+        assert!(matches!(
+            target.reset(),
+            Err(TargetError::GdbProtocolError(_))
+        ));
+    }
+
+    #[test]
+    fn collect_over_gdb_in_no_ack_mode() {
+        collect_over_gdb(true);
+    }
+
+    #[test]
+    fn collect_over_gdb_with_acks() {
+        collect_over_gdb(false);
+    }
+}
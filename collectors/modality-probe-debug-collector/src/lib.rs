@@ -1,19 +1,23 @@
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
+
 use chrono::Utc;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::fs::File;
-use std::io;
+use std::io::{self, Write};
 use std::mem::{align_of, size_of};
 use std::net::SocketAddrV4;
-use std::ops::Add;
+use std::ops::{Add, Range};
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::mpsc::Receiver;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
 use err_derive::Error;
+use goblin::elf::{header::ET_CORE, program_header::PT_LOAD, Elf};
 use probe_rs::{MemoryInterface, Session};
 
 use fenced_ring_buffer::async_reader::{FencedReader, Snapper};
@@ -21,13 +25,23 @@ use fenced_ring_buffer::WholeEntry;
 use modality_probe::field_offsets::*;
 use modality_probe::{
     log::LogEntry, EventId, LogicalClock, ModalityProbe, NanosecondResolution, OrdClock,
-    ProbeEpoch, ProbeId, ProbeTicks, WallClockId,
+    ProbeEpoch, ProbeId, ProbeTicks, WallClockId, EVENT_FILTER_LEN,
 };
 use modality_probe_collector_common::{
-    add_log_report_to_entries, json::write_log_entries, Report, ReportLogEntry, SerializationError,
-    SessionId,
+    metrics::{write_reports, ReceivedReport, ReportTracker, TrackerOpts},
+    rotation::{RotatingWriter, RotationPolicy},
+    Report, ReportLogEntry, SerializationError, SessionId,
 };
 
+mod gdb;
+mod process;
+#[cfg(unix)]
+mod shm;
+pub use gdb::GdbTarget;
+pub use process::{load_bias, process_executable, ProcessTarget};
+#[cfg(unix)]
+pub use shm::SharedMemoryTarget;
+
 /// Either a u32 or u64, depending on the target architecture
 #[derive(Debug, PartialEq, Copy, Clone, Eq, Hash)]
 pub enum Word {
@@ -52,20 +66,20 @@ impl Word {
     }
 }
 
-impl Into<usize> for Word {
-    fn into(self) -> usize {
-        match self {
-            Self::U32(n) => n as usize,
-            Self::U64(n) => n as usize,
+impl From<Word> for usize {
+    fn from(val: Word) -> Self {
+        match val {
+            Word::U32(n) => n as usize,
+            Word::U64(n) => n as usize,
         }
     }
 }
 
-impl Into<u64> for Word {
-    fn into(self) -> u64 {
-        match self {
-            Self::U32(n) => n as u64,
-            Self::U64(n) => n,
+impl From<Word> for u64 {
+    fn from(val: Word) -> Self {
+        match val {
+            Word::U32(n) => n as u64,
+            Word::U64(n) => n,
         }
     }
 }
@@ -89,20 +103,82 @@ pub struct Config {
     pub target: TargetConfig,
     pub interval: Duration,
     pub output_path: PathBuf,
+    /// When to rotate the output file
+    pub rotation: RotationPolicy,
+    /// How to sequence the collected reports and summarize the metrics
+    pub tracking: TrackerOpts,
     pub init_timeout: Option<Duration>,
     pub probe_addrs: Vec<ProbeAddr>,
+    /// Memory ranges to discover more probes in
+    pub scan_ranges: Vec<Range<Word>>,
 }
 
-/// Target device, either directly through probe-rs or by proxy through a gdb server
+/// Configuration for collecting from several targets at once, into the
+/// same session and output file
 #[derive(Debug, PartialEq)]
+pub struct MultiTargetConfig {
+    pub session_id: SessionId,
+    pub output_path: PathBuf,
+    /// When to rotate the output file
+    pub rotation: RotationPolicy,
+    /// How to sequence the collected reports and summarize the metrics
+    pub tracking: TrackerOpts,
+    /// Time to wait before reconnecting to a target after an error
+    pub retry_interval: Duration,
+    pub targets: Vec<TargetSetup>,
+}
+
+/// A target to collect from as part of a multi-target configuration
+#[derive(Debug, PartialEq, Clone)]
+pub struct TargetSetup {
+    /// Name used to tell targets apart in messages
+    pub name: String,
+    pub target: TargetConfig,
+    /// Interval between collections from probes which don't specify their own
+    pub interval: Duration,
+    pub init_timeout: Option<Duration>,
+    pub probes: Vec<ProbeSetup>,
+    /// Memory ranges to discover more probes in, through the first core
+    pub scan_ranges: Vec<Range<Word>>,
+}
+
+/// A probe on a target in a multi-target configuration
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProbeSetup {
+    pub addr: ProbeAddr,
+    /// Core whose view of memory the probe is accessed through
+    pub core: usize,
+    /// Interval between collections, if different from the target's
+    pub interval: Option<Duration>,
+}
+
+/// Target device, either directly through probe-rs, by proxy through a gdb server,
+/// or a memory dump taken from it, or else a process or shared memory region on this host
+#[derive(Debug, PartialEq, Clone)]
 pub enum TargetConfig {
-    ProbeRsTarget(String),
+    /// Chip type, along with the index of the debug probe attached to it
+    ProbeRsTarget(String, usize),
     GdbAddr(SocketAddrV4),
+    /// Raw memory image, along with the address it starts at on the device
+    RawDump(PathBuf, u64),
+    /// ELF core file
+    CoreDump(PathBuf),
+    /// Process on this host, by pid
+    Process(u32),
+    /// Shared memory region on this host, by name
+    SharedMemory(String),
+}
+
+impl TargetConfig {
+    /// Whether the target is a memory dump rather than a live device
+    pub fn is_dump(&self) -> bool {
+        matches!(self, TargetConfig::RawDump(..) | TargetConfig::CoreDump(_))
+    }
 }
 
 /// Struct representing a probe address, either the address of the probe itself or of
 /// a pointer to the probe
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ProbeAddr {
     Addr(Word),
     PtrAddr(Word),
@@ -129,16 +205,51 @@ pub enum Error {
     OutputWritingError(modality_probe_collector_common::Error),
     #[error(display = "Error opening output file: {}", _0)]
     FileError(#[error(from)] io::Error),
+    #[error(
+        display = "The probe's event filter holds at most {} disabled event ranges",
+        _0
+    )]
+    TooManyDisabledEventRanges(usize),
 }
 
 #[derive(Debug, Error)]
 pub enum TargetError {
     #[error(display = "No probes found to attach to; check connection to chip")]
     NoProbesConnected,
+    #[error(display = "There is no debug probe with index {}", _0)]
+    NoSuchProbe(usize),
+    #[error(display = "Target does not have core {}", _0)]
+    NoSuchCore(usize),
     #[error(display = "{}", _0)]
     ProbeRsError(probe_rs::Error),
     #[error(display = "Cannot directly attach to 64 bit chips")]
     MustBe32Bit,
+    #[error(display = "Error reading memory dump: {}", _0)]
+    DumpFileError(io::Error),
+    #[error(display = "Invalid ELF core file: {}", _0)]
+    InvalidCoreFile(String),
+    #[error(display = "Address {:X} is not in the memory dump", _0)]
+    AddressNotInDump(u64),
+    #[error(display = "Cannot reset a memory dump")]
+    CannotResetDump,
+    #[error(display = "Error communicating with gdb server: {}", _0)]
+    GdbIoError(io::Error),
+    #[error(display = "Error in gdb remote protocol: {}", _0)]
+    GdbProtocolError(String),
+    #[error(display = "gdb server replied with error {}", _0)]
+    GdbErrorReply(String),
+    #[error(display = "Error accessing process memory: {}", _0)]
+    ProcessMemoryError(io::Error),
+    #[error(display = "Cannot reset a process")]
+    CannotResetProcess,
+    #[error(display = "Executable of process {} is not mapped into its memory", _0)]
+    ExecutableNotMapped(u32),
+    #[error(display = "Error accessing shared memory region: {}", _0)]
+    SharedMemoryError(io::Error),
+    #[error(display = "Address {:X} is not in the shared memory region", _0)]
+    AddressNotInSharedMemory(u64),
+    #[error(display = "Cannot reset a shared memory region")]
+    CannotResetSharedMemory,
 }
 
 /// Trait used to specify backend used to access device memory
@@ -148,14 +259,34 @@ pub trait Target {
     fn read_32(&mut self, addr: Word) -> Result<u32, TargetError>;
     fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError>;
     fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError>;
+
+    /// Read `buf.len()` bytes starting at `addr`. Targets which can read
+    /// a block of memory at once should override this.
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = self.read_byte(addr + i as u64)?;
+        }
+        Ok(())
+    }
+
+    /// Memory ranges that probes are always discovered in on this target,
+    /// in addition to the configured ones
+    fn implicit_scan_ranges(&self) -> Vec<Range<Word>> {
+        Vec::new()
+    }
 }
 
-/// Target that uses probe-rs to access device memory
-struct ProbeRsTarget(Session);
+/// Target that uses probe-rs to access device memory through one of its
+/// cores. The targets of each core of a device share the same session.
+struct ProbeRsTarget {
+    session: Rc<RefCell<Session>>,
+    core: usize,
+}
 
 impl Target for ProbeRsTarget {
     fn reset(&mut self) -> Result<(), TargetError> {
-        let mut core = self.0.core(0).map_err(TargetError::ProbeRsError)?;
+        let mut session = self.session.borrow_mut();
+        let mut core = session.core(self.core).map_err(TargetError::ProbeRsError)?;
         core.reset().map_err(TargetError::ProbeRsError)
     }
 
@@ -165,7 +296,8 @@ impl Target for ProbeRsTarget {
 
     fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
         if let Word::U32(addr_raw) = addr {
-            let mut core = self.0.core(0).map_err(TargetError::ProbeRsError)?;
+            let mut session = self.session.borrow_mut();
+            let mut core = session.core(self.core).map_err(TargetError::ProbeRsError)?;
             core.read_word_32(addr_raw)
                 .map_err(TargetError::ProbeRsError)
         } else {
@@ -176,7 +308,8 @@ impl Target for ProbeRsTarget {
 
     fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
         if let Word::U32(addr_raw) = addr {
-            let mut core = self.0.core(0).map_err(TargetError::ProbeRsError)?;
+            let mut session = self.session.borrow_mut();
+            let mut core = session.core(self.core).map_err(TargetError::ProbeRsError)?;
             let mut res = [0u8];
             core.read_8(addr_raw, &mut res)
                 .map_err(TargetError::ProbeRsError)?;
@@ -189,7 +322,8 @@ impl Target for ProbeRsTarget {
 
     fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError> {
         if let Word::U32(addr_raw) = addr {
-            let mut core = self.0.core(0).map_err(TargetError::ProbeRsError)?;
+            let mut session = self.session.borrow_mut();
+            let mut core = session.core(self.core).map_err(TargetError::ProbeRsError)?;
             core.write_word_32(addr_raw, data)
                 .map_err(TargetError::ProbeRsError)
         } else {
@@ -197,6 +331,117 @@ impl Target for ProbeRsTarget {
             Err(TargetError::MustBe32Bit)
         }
     }
+
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        if let Word::U32(addr_raw) = addr {
+            let mut session = self.session.borrow_mut();
+            let mut core = session.core(self.core).map_err(TargetError::ProbeRsError)?;
+            core.read_8(addr_raw, buf)
+                .map_err(TargetError::ProbeRsError)
+        } else {
+            // ProbeRs does not support 64 bit targets
+            Err(TargetError::MustBe32Bit)
+        }
+    }
+}
+
+/// Target backed by a memory dump of the device, either a raw memory image
+/// or the loadable segments of an ELF core file. Multi-byte values are
+/// read as little endian.
+pub struct DumpTarget {
+    /// Start address and contents of each dumped memory region
+    segments: Vec<(u64, Vec<u8>)>,
+}
+
+impl DumpTarget {
+    /// Use a raw memory image which starts at `base_addr` on the device
+    pub fn from_raw(image: Vec<u8>, base_addr: u64) -> Self {
+        DumpTarget {
+            segments: vec![(base_addr, image)],
+        }
+    }
+
+    /// Use the `PT_LOAD` segments of an ELF core file
+    pub fn from_core(core: &[u8]) -> Result<Self, TargetError> {
+        let elf = Elf::parse(core).map_err(|e| TargetError::InvalidCoreFile(e.to_string()))?;
+        if elf.header.e_type != ET_CORE {
+            return Err(TargetError::InvalidCoreFile("not a core file".to_string()));
+        }
+        let mut segments = Vec::new();
+        // Segments that weren't dumped have no contents in the file
+        for ph in elf
+            .program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD && ph.p_filesz != 0)
+        {
+            let contents = usize::try_from(ph.p_offset)
+                .ok()
+                .zip(usize::try_from(ph.p_filesz).ok())
+                .and_then(|(offset, len)| core.get(offset..offset.checked_add(len)?))
+                .ok_or_else(|| {
+                    TargetError::InvalidCoreFile(format!(
+                        "segment at {:X} is truncated",
+                        ph.p_vaddr
+                    ))
+                })?;
+            segments.push((ph.p_vaddr, contents.to_vec()));
+        }
+        Ok(DumpTarget { segments })
+    }
+
+    fn byte_mut(&mut self, addr: u64) -> Result<&mut u8, TargetError> {
+        self.segments
+            .iter_mut()
+            .find_map(|(start, contents)| {
+                let offset = usize::try_from(addr.checked_sub(*start)?).ok()?;
+                contents.get_mut(offset)
+            })
+            .ok_or(TargetError::AddressNotInDump(addr))
+    }
+}
+
+impl Target for DumpTarget {
+    fn reset(&mut self) -> Result<(), TargetError> {
+        Err(TargetError::CannotResetDump)
+    }
+
+    fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
+        match addr {
+            Word::U32(_) => self.read_32(addr).map(Word::U32),
+            Word::U64(_) => {
+                let mut buf = [0u8; 8];
+                self.read_bytes(addr, &mut buf)?;
+                Ok(Word::U64(u64::from_le_bytes(buf)))
+            }
+        }
+    }
+
+    fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
+        let mut buf = [0u8; 4];
+        self.read_bytes(addr, &mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
+        self.byte_mut(addr.into()).map(|b| *b)
+    }
+
+    // Only changes the collector's copy of the dump
+    fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError> {
+        let addr = u64::from(addr);
+        for (i, b) in data.to_le_bytes().iter().enumerate() {
+            *self.byte_mut(addr + i as u64)? = *b;
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        let addr = u64::from(addr);
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = *self.byte_mut(addr + i as u64)?;
+        }
+        Ok(())
+    }
 }
 
 /// Struct used to take snapshots of FencedRingBuffer on device
@@ -268,20 +513,58 @@ impl PriorityWriter {
     }
 }
 
+/// Used to write to probe's "event_filter" field
+struct EventFilterWriter {
+    /// Memory accessor used to write to device memory
+    mem_accessor: Rc<RefCell<dyn Target>>,
+    /// Address of the probe's DynamicHistory, which holds the event filter
+    hist_addr: Word,
+}
+
+impl EventFilterWriter {
+    fn write(&mut self, disabled: &[(EventId, EventId)]) -> Result<(), Error> {
+        if disabled.len() > EVENT_FILTER_LEN {
+            return Err(Error::TooManyDisabledEventRanges(EVENT_FILTER_LEN));
+        }
+        let mut mem = self.mem_accessor.borrow_mut();
+        for i in 0..EVENT_FILTER_LEN {
+            let first_addr = self.hist_addr + event_filter_range_offset(i);
+            let last_addr = first_addr + size_of::<u32>() as u64;
+            // Mark the range unused while it's being changed, so the probe
+            // never sees a new first id with a stale last id
+            mem.write_32(first_addr, 0)?;
+            if let Some((first, last)) = disabled.get(i) {
+                let (first, last) = (first.get_raw(), last.get_raw());
+                mem.write_32(last_addr, first.max(last))?;
+                mem.write_32(first_addr, first.min(last))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Log collector for a single probe
 pub struct Collector {
+    /// Id of the probe
+    probe_id: ProbeId,
     /// Sequence number of next report
     seq_num: u64,
     /// Reader used to read the probe's FencedRingBuffer
     reader: FencedReader<LogEntry, MemorySnapper>,
-    /// Temporary storage for trailing paired wall clock time entry
-    prev_paired_wall_clock_time: Option<WholeEntry<LogEntry>>,
+    /// Temporary storage for trailing paired wall clock time, wide
+    /// payload high bits and blob data entries
+    prev_buddy_entries: Vec<WholeEntry<LogEntry>>,
+    /// Trailing event entry of the previous report, which an
+    /// event repeated entry at the start of the next read refers to
+    prev_event: Option<WholeEntry<LogEntry>>,
     /// Allocated buffer for reading the log into
     rbuf: Vec<WholeEntry<LogEntry>>,
     /// Processed clocks backing storage
     clocks: Vec<LogicalClock>,
     /// Used to write to the probe's "overwrite_priority" field
     priority_writer: PriorityWriter,
+    /// Used to write to the probe's "event_filter" field
+    event_filter_writer: EventFilterWriter,
     /// Time resolution extracted from the probe
     time_resolution: NanosecondResolution,
     /// Wall clock id extracted from the probe
@@ -330,6 +613,7 @@ impl Collector {
         let priority_field_addr = hist_addr + overwrite_priority_offset();
 
         let priority_mem_accessor = mem_accessor.clone();
+        let event_filter_mem_accessor = mem_accessor.clone();
         let mut clocks = Vec::new();
         // Merge self clock set to 0
         Self::merge_clock(
@@ -341,6 +625,7 @@ impl Collector {
             },
         );
         Ok(Self {
+            probe_id: id,
             seq_num: 0,
             reader: FencedReader::new(
                 MemorySnapper {
@@ -353,13 +638,18 @@ impl Collector {
                 },
                 buf_cap,
             ),
-            prev_paired_wall_clock_time: None,
+            prev_buddy_entries: Vec::new(),
+            prev_event: None,
             rbuf: Vec::new(),
             clocks,
             priority_writer: PriorityWriter {
                 mem_accessor: priority_mem_accessor,
                 priority_field_addr,
             },
+            event_filter_writer: EventFilterWriter {
+                mem_accessor: event_filter_mem_accessor,
+                hist_addr,
+            },
             time_resolution: time_res.into(),
             wall_clock_id: wall_clock_id.into(),
             persistent_epoch_counting: persistent_epoch_counting != 0,
@@ -422,6 +712,11 @@ impl Collector {
         Ok(None)
     }
 
+    /// Id of the probe this collector collects from
+    pub fn probe_id(&self) -> ProbeId {
+        self.probe_id
+    }
+
     /// Collect all new logs, return a report
     pub fn collect_report(&mut self) -> Result<Option<Report>, Error> {
         self.rbuf.clear();
@@ -435,12 +730,29 @@ impl Collector {
                 LogEntry::event_with_payload(EventId::EVENT_LOG_ITEMS_MISSED, num_missed_rounded);
             self.rbuf.insert(0, WholeEntry::Double(ev, payload));
 
-            if let Some(e) = self.prev_paired_wall_clock_time.take() {
-                eprintln!("Warning: dropping paired wall clock time entry {:?} from previous report because items were missed and the associated entry was lost", e);
+            if !self.prev_buddy_entries.is_empty() {
+                eprintln!("Warning: dropping entries {:?} from previous report because items were missed and the associated entry was lost", self.prev_buddy_entries);
+                self.prev_buddy_entries.clear();
+            }
+            self.prev_event = None;
+        } else if !self.prev_buddy_entries.is_empty() {
+            // Insert paired wall clock time, wide payload high bits and blob
+            // data entries from previous report if needed
+            self.rbuf.splice(0..0, self.prev_buddy_entries.drain(..));
+        } else if let Some(WholeEntry::Double(first, second)) = self.rbuf.first().copied() {
+            // Attribute repeats of the previous report's trailing event, which
+            // the probe wrote to the log after that report was collected, by
+            // reporting one of them as the event itself
+            if let (true, Some(e)) = (first.is_event_repeated(), self.prev_event) {
+                match second.raw().saturating_sub(1) {
+                    0 => self.rbuf[0] = e,
+                    n => {
+                        let (first, second) = LogEntry::event_repeated(n);
+                        self.rbuf
+                            .splice(0..1, [e, WholeEntry::Double(first, second)].iter().copied());
+                    }
+                }
             }
-        } else if let Some(e) = self.prev_paired_wall_clock_time.take() {
-            // Insert paired wall clock time entry from previous report if needed
-            self.rbuf.insert(0, e);
         }
 
         if self.rbuf.is_empty() {
@@ -448,16 +760,21 @@ impl Collector {
             return Ok(None);
         }
 
-        // If the last entry is a paired wall clock time entry, then
-        // save it for the next report so its not fragmented away from
-        // its associated entry
-        if let Some(e) = self.rbuf.last() {
-            if e.is_double() && e.first_entry().has_wall_clock_time_paired_bit_set() {
-                self.prev_paired_wall_clock_time =
-                    Some(self.rbuf.pop().expect("Just checked last entry"));
+        // If the last entries are paired wall clock time or wide payload
+        // high bits entries, then save them for the next report so they're
+        // not fragmented away from their associated entry
+        while let Some(e) = self.rbuf.last() {
+            if !precedes_buddy_entry(e) {
+                break;
             }
+            let e = self.rbuf.pop().expect("Just checked last entry");
+            self.prev_buddy_entries.insert(0, e);
         }
 
+        // If the last entry is an event, then remember it in case
+        // the probe follows it up with an event repeated entry
+        self.prev_event = self.rbuf.last().copied().filter(|e| !e.is_double());
+
         // Add report produced event
         self.rbuf.push(WholeEntry::Single(LogEntry::event(
             EventId::EVENT_PRODUCED_EXTERNAL_REPORT,
@@ -511,25 +828,143 @@ impl Collector {
     pub fn set_overwrite_priority(&mut self, level: u32) -> Result<(), TargetError> {
         self.priority_writer.write(level)
     }
+
+    /// Replace the probe's event filter, so that events with ids in
+    /// any of the given inclusive ranges are no longer recorded
+    pub fn set_event_filter(&mut self, disabled: &[(EventId, EventId)]) -> Result<(), Error> {
+        self.event_filter_writer.write(disabled)
+    }
 }
 
-/// Open memory accessor based on config
-fn open_mem_accessor(c: &Config) -> Result<Rc<RefCell<dyn Target>>, TargetError> {
-    match &c.target {
-        TargetConfig::ProbeRsTarget(target) => {
-            let probes = probe_rs::Probe::list_all();
-            if probes.is_empty() {
-                return Err(TargetError::NoProbesConnected);
+/// Search the given memory ranges for probes, returning a collector for each
+/// probe found. Only addresses aligned to 4 bytes are considered. Memory
+/// which can't be read is skipped with a warning.
+pub fn discover_probes(
+    ranges: &[Range<Word>],
+    mem_accessor: Rc<RefCell<dyn Target>>,
+) -> Result<Vec<Collector>, Error> {
+    // Read this much memory at a time
+    const CHUNK_LEN: u64 = 4096;
+    let fingerprint = ModalityProbe::STRUCT_FINGERPRINT.to_le_bytes();
+    let mut collectors = Vec::new();
+    let mut buf = Vec::new();
+    for range in ranges {
+        let end = u64::from(range.end);
+        let mut addr = range.start + u64::from(range.start).wrapping_neg() % 4;
+        while u64::from(addr) < end {
+            let len = u64::min(CHUNK_LEN, end - u64::from(addr)) & !3;
+            if len == 0 {
+                break;
+            }
+            buf.resize(len as usize, 0);
+            if let Err(e) = mem_accessor.borrow_mut().read_bytes(addr, &mut buf) {
+                eprintln!(
+                    "Warning: skipping {} bytes at {:X} while searching for probes: {}",
+                    len,
+                    u64::from(addr),
+                    e
+                );
+                addr = addr + len;
+                continue;
+            }
+            for (i, word) in buf.chunks_exact(4).enumerate() {
+                if word != fingerprint {
+                    continue;
+                }
+                // The fingerprint value may also turn up outside of a probe
+                let probe_addr = ProbeAddr::Addr(addr + (i * 4) as u64);
+                if let Ok(collector) = Collector::initialize(&probe_addr, mem_accessor.clone()) {
+                    collectors.push(collector);
+                }
             }
-            let probe = probes[0]
-                .open()
-                .map_err(|e| TargetError::ProbeRsError(e.into()))?;
-            let session = probe.attach(target).map_err(TargetError::ProbeRsError)?;
-            Ok(Rc::new(RefCell::new(ProbeRsTarget(session))))
+            addr = addr + len;
         }
-        // No probe rs target implies use of gdb, which is not implemented yet
-        TargetConfig::GdbAddr(_) => unimplemented!(),
     }
+    Ok(collectors)
+}
+
+/// Determine if this entry must be kept together with the entry that follows it
+fn precedes_buddy_entry(entry: &WholeEntry<LogEntry>) -> bool {
+    match entry {
+        WholeEntry::Double(first, _) => {
+            first.has_wall_clock_time_paired_bit_set()
+                || first.is_wide_payload_high_bits()
+                || first.is_blob_data()
+        }
+        WholeEntry::Single(_) => false,
+    }
+}
+
+/// Attach to a chip through the debug probe with the given index
+fn attach_probe_rs(chip: &str, probe_index: usize) -> Result<Session, TargetError> {
+    let probes = probe_rs::Probe::list_all();
+    if probes.is_empty() {
+        return Err(TargetError::NoProbesConnected);
+    }
+    let probe = probes
+        .get(probe_index)
+        .ok_or(TargetError::NoSuchProbe(probe_index))?
+        .open()
+        .map_err(|e| TargetError::ProbeRsError(e.into()))?;
+    probe.attach(chip).map_err(TargetError::ProbeRsError)
+}
+
+/// Open memory accessor based on target config
+fn open_mem_accessor(target: &TargetConfig) -> Result<Rc<RefCell<dyn Target>>, TargetError> {
+    match target {
+        TargetConfig::ProbeRsTarget(chip, probe_index) => {
+            Ok(Rc::new(RefCell::new(ProbeRsTarget {
+                session: Rc::new(RefCell::new(attach_probe_rs(chip, *probe_index)?)),
+                core: 0,
+            })))
+        }
+        TargetConfig::GdbAddr(addr) => Ok(Rc::new(RefCell::new(GdbTarget::connect(*addr)?))),
+        TargetConfig::RawDump(path, base_addr) => {
+            let image = std::fs::read(path).map_err(TargetError::DumpFileError)?;
+            Ok(Rc::new(RefCell::new(DumpTarget::from_raw(
+                image, *base_addr,
+            ))))
+        }
+        TargetConfig::Process(pid) => Ok(Rc::new(RefCell::new(ProcessTarget::attach(*pid)?))),
+        #[cfg(unix)]
+        TargetConfig::SharedMemory(name) => {
+            Ok(Rc::new(RefCell::new(SharedMemoryTarget::open(name)?)))
+        }
+        #[cfg(not(unix))]
+        TargetConfig::SharedMemory(_) => Err(TargetError::SharedMemoryError(io::Error::new(
+            io::ErrorKind::Other,
+            "Shared memory regions are only supported on unix platforms",
+        ))),
+        TargetConfig::CoreDump(path) => {
+            let core = std::fs::read(path).map_err(TargetError::DumpFileError)?;
+            Ok(Rc::new(RefCell::new(DumpTarget::from_core(&core)?)))
+        }
+    }
+}
+
+/// Open a memory accessor for each of the given cores of the target. Only
+/// probe-rs targets have cores other than 0.
+fn open_mem_accessors(
+    target: &TargetConfig,
+    cores: &[usize],
+) -> Result<Vec<Rc<RefCell<dyn Target>>>, TargetError> {
+    if let TargetConfig::ProbeRsTarget(chip, probe_index) = target {
+        let session = Rc::new(RefCell::new(attach_probe_rs(chip, *probe_index)?));
+        return Ok(cores
+            .iter()
+            .map(|&core| -> Rc<RefCell<dyn Target>> {
+                Rc::new(RefCell::new(ProbeRsTarget {
+                    session: session.clone(),
+                    core,
+                }))
+            })
+            .collect());
+    }
+    if let Some(core) = cores.iter().find(|&&core| core != 0) {
+        return Err(TargetError::NoSuchCore(*core));
+    }
+    let mem_accessor = open_mem_accessor(target)?;
+    Ok(cores.iter().map(|_| mem_accessor.clone()).collect())
 }
 
 /// Initialize collectors of each probe based on config
@@ -541,16 +976,96 @@ fn initialize_collectors(
     for probe_addr in c.probe_addrs.iter() {
         collectors.push(Collector::initialize(probe_addr, mem_accessor.clone())?);
     }
+    let known_ids: Vec<ProbeId> = collectors.iter().map(Collector::probe_id).collect();
+    collectors.extend(discover_new_probes(
+        &c.scan_ranges,
+        mem_accessor,
+        &known_ids,
+    )?);
+    Ok(collectors)
+}
+
+/// Discover probes in the given memory ranges, skipping those with the given
+/// ids, which were also given by address
+fn discover_new_probes(
+    ranges: &[Range<Word>],
+    mem_accessor: Rc<RefCell<dyn Target>>,
+    known_ids: &[ProbeId],
+) -> Result<Vec<Collector>, Error> {
+    let mut collectors: Vec<Collector> = Vec::new();
+    let implicit_ranges = mem_accessor.borrow().implicit_scan_ranges();
+    let ranges: Vec<Range<Word>> = ranges.iter().cloned().chain(implicit_ranges).collect();
+    for collector in discover_probes(&ranges, mem_accessor)? {
+        let probe_id = collector.probe_id();
+        if known_ids.contains(&probe_id) || collectors.iter().any(|c| c.probe_id() == probe_id) {
+            continue;
+        }
+        println!("Discovered probe {}", probe_id.get_raw());
+        collectors.push(collector);
+    }
     Ok(collectors)
 }
 
-/// Write report to given file
-fn report_to_file(out: &mut File, report: Report, session_id: SessionId) -> Result<(), Error> {
-    let mut entries: Vec<ReportLogEntry> = Vec::new();
+/// How often reports held back for reordering are released while
+/// collecting from several targets
+const RELEASE_INTERVAL: Duration = Duration::from_millis(100);
 
-    add_log_report_to_entries(&report, session_id, Utc::now(), &mut entries)
-        .map_err(Error::OutputWritingError)?;
-    write_log_entries(out, &entries).map_err(Error::OutputWritingError)
+/// Sequences the collected reports and writes them to the output
+struct ReportWriter<W: Write> {
+    session_id: SessionId,
+    tracker: ReportTracker,
+    log_entries_buffer: Vec<ReportLogEntry>,
+    out: W,
+}
+
+impl<W: Write> ReportWriter<W> {
+    fn new(session_id: SessionId, tracker: ReportTracker, out: W) -> Self {
+        ReportWriter {
+            session_id,
+            tracker,
+            log_entries_buffer: Vec::new(),
+            out,
+        }
+    }
+
+    /// Hand a collected report to the tracker, writing the reports it
+    /// releases
+    fn push(&mut self, report: Report) -> Result<(), Error> {
+        let released = self.tracker.push(report, Utc::now());
+        self.write(&released)
+    }
+
+    /// Write the reports held back for too long, and print a summary of
+    /// the metrics if one is due
+    fn release_overdue(&mut self) -> Result<(), Error> {
+        let released = self.tracker.release_overdue();
+        self.write(&released)?;
+        if self.tracker.summary_due() {
+            println!("{}", self.tracker.metrics);
+        }
+        Ok(())
+    }
+
+    /// Write every report still held back, then print a summary of the
+    /// metrics
+    fn finish(mut self) -> Result<(), Error> {
+        let released = self.tracker.finish();
+        self.write(&released)?;
+        println!("{}", self.tracker.metrics);
+        Ok(())
+    }
+
+    fn write(&mut self, released: &[ReceivedReport]) -> Result<(), Error> {
+        write_reports(
+            released,
+            self.session_id,
+            &mut self.log_entries_buffer,
+            &mut self.out,
+        )
+        .map_err(|e| {
+            Error::OutputWritingError(modality_probe_collector_common::Error::Io(e.to_string()))
+        })
+    }
 }
 
 /// Run debug collector with given config
@@ -562,7 +1077,7 @@ pub fn run(c: &Config, shutdown_receiver: Receiver<()>) -> Result<(), Error> {
         shutdown_sender_crossbeam.send(()).unwrap();
     });
 
-    let mem_accessor = open_mem_accessor(c)?;
+    let mem_accessor = open_mem_accessor(&c.target)?;
     if let Some(timeout) = c.init_timeout {
         mem_accessor.borrow_mut().reset()?;
         channel::select! {
@@ -571,22 +1086,173 @@ pub fn run(c: &Config, shutdown_receiver: Receiver<()>) -> Result<(), Error> {
         }
     }
     let mut collectors = initialize_collectors(c, mem_accessor)?;
-    let mut out = std::fs::OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(&c.output_path)?;
+    let out = RotatingWriter::open(&c.output_path, c.rotation)?;
+    let mut writer = ReportWriter::new(c.session_id, c.tracking.tracker(), out);
     loop {
         for collector in &mut collectors {
             if let Some(report) = collector.collect_report()? {
-                report_to_file(&mut out, report, c.session_id)?;
+                writer.push(report)?;
             }
         }
+        writer.release_overdue()?;
+
+        // A memory dump doesn't change, so the first round collected everything
+        if c.target.is_dump() {
+            break;
+        }
 
         channel::select! {
-            recv(shutdown_receiver_crossbeam) -> _ => return Ok(()),
+            recv(shutdown_receiver_crossbeam) -> _ => break,
             default(c.interval) => (),
         }
     }
+    writer.finish()
+}
+
+/// Run debug collector on several targets at once, with a thread for each
+/// target. An error on one target is reported and the target reconnected to
+/// after the retry interval, while collection from the others goes on.
+pub fn run_multi(c: &MultiTargetConfig, shutdown_receiver: Receiver<()>) -> Result<(), Error> {
+    // Dropping the crossbeam sender shuts down every target thread
+    let (shutdown_sender_crossbeam, shutdown_receiver_crossbeam) = channel::unbounded::<()>();
+    thread::spawn(move || {
+        let _ = shutdown_receiver.recv();
+        drop(shutdown_sender_crossbeam);
+    });
+
+    let out = RotatingWriter::open(&c.output_path, c.rotation)?;
+    let mut writer = ReportWriter::new(c.session_id, c.tracking.tracker(), out);
+    let (report_sender, report_receiver) = channel::unbounded();
+    for setup in &c.targets {
+        let setup = setup.clone();
+        let report_sender = report_sender.clone();
+        let shutdown_receiver = shutdown_receiver_crossbeam.clone();
+        let retry_interval = c.retry_interval;
+        thread::spawn(move || {
+            collect_from_target(&setup, retry_interval, &report_sender, &shutdown_receiver)
+        });
+    }
+    drop(report_sender);
+
+    // Every target thread has finished once the channel disconnects
+    loop {
+        match report_receiver.recv_timeout(RELEASE_INTERVAL) {
+            Ok(report) => writer.push(report)?,
+            Err(channel::RecvTimeoutError::Timeout) => (),
+            Err(channel::RecvTimeoutError::Disconnected) => break,
+        }
+        writer.release_overdue()?;
+    }
+    writer.finish()
+}
+
+/// Collect from a target until shutdown, reconnecting after errors
+fn collect_from_target(
+    setup: &TargetSetup,
+    retry_interval: Duration,
+    report_sender: &channel::Sender<Report>,
+    shutdown_receiver: &channel::Receiver<()>,
+) {
+    let mut needs_reset = setup.init_timeout.is_some();
+    // Carried across reconnections so each probe's reports keep counting up
+    let mut next_seq_nums = HashMap::new();
+    loop {
+        match poll_target(
+            setup,
+            &mut needs_reset,
+            &mut next_seq_nums,
+            report_sender,
+            shutdown_receiver,
+        ) {
+            Ok(()) => return,
+            Err(err) => eprintln!("Error collecting from target {}: {}", setup.name, err),
+        }
+        // Errors reading a memory dump won't go away by retrying
+        if setup.target.is_dump() {
+            return;
+        }
+        channel::select! {
+            recv(shutdown_receiver) -> _ => return,
+            default(retry_interval) => (),
+        }
+    }
+}
+
+/// Connect to a target and poll each of its probes at its interval until
+/// shutdown or an error. Each probe's reports are numbered on from the
+/// sequence number in `next_seq_nums`, which is kept up to date.
+fn poll_target(
+    setup: &TargetSetup,
+    needs_reset: &mut bool,
+    next_seq_nums: &mut HashMap<ProbeId, u64>,
+    report_sender: &channel::Sender<Report>,
+    shutdown_receiver: &channel::Receiver<()>,
+) -> Result<(), Error> {
+    let mut cores: Vec<usize> = setup.probes.iter().map(|p| p.core).collect();
+    // Reset and discovery go through the first core
+    cores.push(0);
+    let mut mem_accessors = open_mem_accessors(&setup.target, &cores)?;
+    let first_core_accessor = mem_accessors.pop().unwrap();
+
+    if *needs_reset {
+        if let Some(timeout) = setup.init_timeout {
+            first_core_accessor.borrow_mut().reset()?;
+            // Only reset once, rather than on each reconnection
+            *needs_reset = false;
+            channel::select! {
+                recv(shutdown_receiver) -> _ => return Ok(()),
+                default(timeout) => (),
+            }
+        }
+    }
+
+    let mut collectors = Vec::new();
+    for (probe, mem_accessor) in setup.probes.iter().zip(mem_accessors) {
+        let collector = Collector::initialize(&probe.addr, mem_accessor)?;
+        collectors.push((collector, probe.interval.unwrap_or(setup.interval)));
+    }
+    let known_ids: Vec<ProbeId> = collectors.iter().map(|(c, _)| c.probe_id()).collect();
+    for collector in discover_new_probes(&setup.scan_ranges, first_core_accessor, &known_ids)? {
+        collectors.push((collector, setup.interval));
+    }
+    for (collector, _) in collectors.iter_mut() {
+        if let Some(seq_num) = next_seq_nums.get(&collector.probe_id) {
+            collector.seq_num = *seq_num;
+        }
+    }
+
+    let mut next_collections = vec![Instant::now(); collectors.len()];
+    loop {
+        for ((collector, interval), next) in collectors.iter_mut().zip(next_collections.iter_mut())
+        {
+            if Instant::now() < *next {
+                continue;
+            }
+            if let Some(report) = collector.collect_report()? {
+                next_seq_nums.insert(collector.probe_id, collector.seq_num);
+                if report_sender.send(report).is_err() {
+                    // The output has stopped
+                    return Ok(());
+                }
+            }
+            *next = Instant::now() + *interval;
+        }
+
+        // A memory dump doesn't change, so the first round collected everything
+        if setup.target.is_dump() {
+            return Ok(());
+        }
+
+        let wait = next_collections
+            .iter()
+            .min()
+            .map(|next| next.saturating_duration_since(Instant::now()))
+            .unwrap_or(setup.interval);
+        channel::select! {
+            recv(shutdown_receiver) -> _ => return Ok(()),
+            default(wait) => (),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -594,15 +1260,17 @@ pub mod tests {
     use super::*;
     use maplit::hashmap;
     use pretty_assertions::assert_eq;
-    use std::collections::HashMap;
     use std::convert::TryInto;
+    use std::fs::File;
     use std::ptr;
 
     use modality_probe::{
         time::{NanosecondResolution, Nanoseconds, WallClockId},
         EventId, ModalityProbe, Probe, RestartCounterProvider,
     };
-    use modality_probe_collector_common::{EventLogEntry, SequenceNumber};
+    use modality_probe_collector_common::{
+        add_log_report_to_entries, EventLogEntry, SequenceNumber,
+    };
     use std::mem::MaybeUninit;
 
     fn lc(probe_id: u32, epoch: u16, ticks: u16) -> LogicalClock {
@@ -617,6 +1285,8 @@ pub mod tests {
         EventId::new(id).unwrap()
     }
 
+    /// Reads the test process's own memory. Accesses are unaligned since
+    /// the invalid pointer tests follow arbitrary addresses.
     struct DirectMemAccessor;
 
     impl Target for DirectMemAccessor {
@@ -636,7 +1306,7 @@ pub mod tests {
                     addr_raw as usize as *mut u32
                 }
             };
-            unsafe { *ptr = data };
+            unsafe { ptr.write_unaligned(data) };
             Ok(())
         }
 
@@ -651,7 +1321,7 @@ pub mod tests {
                     addr_raw as usize as *const u32
                 }
             };
-            Ok(unsafe { *ptr })
+            Ok(unsafe { ptr.read_unaligned() })
         }
 
         fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
@@ -665,7 +1335,7 @@ pub mod tests {
                     addr_raw as usize as *const u8
                 }
             };
-            Ok(unsafe { *ptr })
+            Ok(unsafe { ptr.read_unaligned() })
         }
 
         fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
@@ -673,12 +1343,12 @@ pub mod tests {
                 Word::U32(addr_raw) => {
                     assert!(size_of::<usize>() == size_of::<u32>());
                     let ptr = addr_raw as usize as *const u32;
-                    Ok(unsafe { Word::U32(*ptr) })
+                    Ok(unsafe { Word::U32(ptr.read_unaligned()) })
                 }
                 Word::U64(addr_raw) => {
                     assert!(size_of::<usize>() == size_of::<u64>());
                     let ptr = addr_raw as usize as *const u64;
-                    Ok(unsafe { Word::U64(*ptr) })
+                    Ok(unsafe { Word::U64(ptr.read_unaligned()) })
                 }
             }
         }
@@ -751,7 +1421,7 @@ pub mod tests {
         let padding_len = (align_of::<ModalityProbe>() - 1) as isize;
         for i in 0..padding_len {
             assert_eq!(
-                unsafe { (*(storage_unaligned_ptr.offset(i as isize))).assume_init() },
+                unsafe { (*(storage_unaligned_ptr.offset(i))).assume_init() },
                 ModalityProbe::PADDING_GUARD_BYTE
             );
         }
@@ -958,7 +1628,7 @@ pub mod tests {
         assert_eq!(
             third_report,
             Report {
-                probe_id: probe_id,
+                probe_id,
                 probe_clock: lc(pid_raw, 0, 2),
                 seq_num: SequenceNumber(0),
                 frontier_clocks: vec![lc(pid_raw, 0, 0)],
@@ -983,7 +1653,7 @@ pub mod tests {
         assert_eq!(
             fourth_report,
             Report {
-                probe_id: probe_id,
+                probe_id,
                 probe_clock: lc(pid_raw, 0, 2),
                 seq_num: SequenceNumber(1),
                 frontier_clocks: vec![lc(pid_raw, 0, 2), lc(pid_raw_2, 0, 1)],
@@ -1025,6 +1695,18 @@ pub mod tests {
 
         collector.set_overwrite_priority(1).unwrap();
         assert_eq!(probe.get_overwrite_priority_level(), 1);
+
+        collector.set_event_filter(&[(ev(10), ev(20))]).unwrap();
+        assert!(probe.is_event_enabled(ev(9)));
+        assert!(!probe.is_event_enabled(ev(10)));
+        assert!(!probe.is_event_enabled(ev(20)));
+        assert!(probe.is_event_enabled(ev(21)));
+
+        collector.set_event_filter(&[]).unwrap();
+        assert!(probe.is_event_enabled(ev(10)));
+
+        let too_many = vec![(ev(1), ev(1)); EVENT_FILTER_LEN + 1];
+        assert!(collector.set_event_filter(&too_many).is_err());
     }
 
     struct HashMapMemAccessor(HashMap<Word, u32>);
@@ -1039,7 +1721,7 @@ pub mod tests {
             probe_id: ProbeId,
             write_seqn: u32,
             overwrite_seqn: u32,
-            buf_contents: &Vec<LogEntry>,
+            buf_contents: &[LogEntry],
         ) -> Self {
             let map = hashmap! {
                 Self::PROBE_PTR_ADDR => Self::PROBE_ADDR.unwrap_32(),
@@ -1057,11 +1739,11 @@ pub mod tests {
                 Self::HIST_ADDR + overwrite_seqn_low_offset() => overwrite_seqn,
             };
             let mut reader = HashMapMemAccessor(map);
-            reader.overwrite_buffer(&buf_contents);
+            reader.overwrite_buffer(buf_contents);
             reader
         }
 
-        fn overwrite_buffer(&mut self, buf_contents: &Vec<LogEntry>) {
+        fn overwrite_buffer(&mut self, buf_contents: &[LogEntry]) {
             for (index, entry) in buf_contents.iter().enumerate() {
                 self.0
                     .insert(Self::STORAGE_ADDR + 4 * index as u64, entry.raw());
@@ -1087,7 +1769,7 @@ pub mod tests {
         }
 
         fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
-            self.read_32(addr).map(|res| Word::U32(res))
+            self.read_32(addr).map(Word::U32)
         }
 
         fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
@@ -1113,7 +1795,7 @@ pub mod tests {
             probe_id,
             4,
             0,
-            &mut vec![
+            &[
                 LogEntry::clock(lc(pid_raw, 0, 1)).0,
                 LogEntry::clock(lc(pid_raw, 0, 1)).1,
                 LogEntry::event(ev(3)),
@@ -1157,7 +1839,7 @@ pub mod tests {
             probe_id,
             4,
             0,
-            &mut vec![
+            &[
                 LogEntry::event(ev(1)),
                 LogEntry::event(ev(2)),
                 LogEntry::event(ev(3)),
@@ -1201,7 +1883,7 @@ pub mod tests {
             probe_id,
             6,
             2,
-            &mut vec![
+            &[
                 LogEntry::event(ev(5)),
                 LogEntry::event(ev(6)),
                 LogEntry::event(ev(3)),
@@ -1248,7 +1930,7 @@ pub mod tests {
             probe_id,
             8,
             0,
-            &mut vec![
+            &[
                 LogEntry::clock(lc(pid_raw, 0, 1)).0,
                 LogEntry::clock(lc(pid_raw, 0, 1)).1,
                 LogEntry::clock(lc(other_id_raw, 0, 1)).0,
@@ -1288,7 +1970,7 @@ pub mod tests {
             }
         );
 
-        mem_accessor.borrow_mut().overwrite_buffer(&vec![
+        mem_accessor.borrow_mut().overwrite_buffer(&[
             LogEntry::event(ev(5)),
             LogEntry::event(ev(6)),
             LogEntry::event(ev(7)),
@@ -1336,7 +2018,7 @@ pub mod tests {
             probe_id,
             0,
             0,
-            &mut vec![
+            &[
                 LogEntry::event(ev(1)),
                 LogEntry::event(ev(1)),
                 LogEntry::event(ev(1)),
@@ -1362,7 +2044,7 @@ pub mod tests {
             probe_id,
             4,
             0,
-            &mut vec![
+            &[
                 LogEntry::event(ev(1)),
                 LogEntry::event(ev(2)),
                 LogEntry::event(ev(3)),
@@ -1395,7 +2077,7 @@ pub mod tests {
             }
         );
 
-        mem_accessor.borrow_mut().overwrite_buffer(&vec![
+        mem_accessor.borrow_mut().overwrite_buffer(&[
             LogEntry::clock(lc(pid_raw, 0, 1)).1,
             LogEntry::event(ev(4)),
             LogEntry::event(ev(5)),
@@ -1435,7 +2117,7 @@ pub mod tests {
             probe_id,
             4,
             0,
-            &mut vec![
+            &[
                 LogEntry::event(ev(1)),
                 LogEntry::event(ev(2)),
                 LogEntry::event(ev(3)),
@@ -1468,7 +2150,7 @@ pub mod tests {
             }
         );
 
-        mem_accessor.borrow_mut().overwrite_buffer(&vec![
+        mem_accessor.borrow_mut().overwrite_buffer(&[
             LogEntry::event_with_payload(ev(4), 5).1,
             LogEntry::event(ev(6)),
             LogEntry::event(ev(7)),
@@ -1508,7 +2190,7 @@ pub mod tests {
             probe_id,
             3,
             0,
-            &mut vec![LogEntry::event(ev(10)), a, b],
+            &[LogEntry::event(ev(10)), a, b],
         )));
 
         let mut collector = Collector::initialize(
@@ -1537,7 +2219,7 @@ pub mod tests {
 
         mem_accessor
             .borrow_mut()
-            .overwrite_buffer(&vec![LogEntry::event(ev(11)), LogEntry::event(ev(12))]);
+            .overwrite_buffer(&[LogEntry::event(ev(11)), LogEntry::event(ev(12))]);
         mem_accessor.borrow_mut().set_write_seqn(5);
         mem_accessor.borrow_mut().set_overwrite_seqn(2);
 
@@ -1561,6 +2243,125 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn fragmented_wide_payload_entries_are_carried_over() {
+        let pid_raw = 1;
+        let probe_id = ProbeId::new(pid_raw).unwrap();
+        let payload = 0x1_0000_0002;
+        let time = Nanoseconds::new(1).unwrap();
+        let (hi_a, hi_b) = LogEntry::wide_payload_high_bits(payload);
+        let (t_a, t_b) = LogEntry::paired_wall_clock_time(time);
+        let (pl_a, pl_b) = LogEntry::event_with_payload(ev(11), payload as u32);
+        let mem_accessor = Rc::new(RefCell::new(HashMapMemAccessor::new(
+            probe_id,
+            5,
+            0,
+            &[LogEntry::event(ev(10)), hi_a, hi_b, t_a, t_b],
+        )));
+
+        let mut collector = Collector::initialize(
+            &ProbeAddr::Addr(HashMapMemAccessor::PROBE_ADDR),
+            mem_accessor.clone() as Rc<RefCell<dyn Target>>,
+        )
+        .unwrap();
+
+        let report = collector.collect_report().unwrap().unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::Event(ev(10)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
+            ]
+        );
+
+        mem_accessor
+            .borrow_mut()
+            .overwrite_buffer(&[pl_a, pl_b, LogEntry::event(ev(12))]);
+        mem_accessor.borrow_mut().set_write_seqn(8);
+        mem_accessor.borrow_mut().set_overwrite_seqn(3);
+
+        let report = collector.collect_report().unwrap().unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::EventWithPayloadWithTime(time, ev(11), payload),
+                EventLogEntry::Event(ev(12)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
+            ]
+        );
+    }
+
+    #[test]
+    fn repeats_of_previously_reported_event_are_attributed() {
+        let pid_raw = 1;
+        let probe_id = ProbeId::new(pid_raw).unwrap();
+        let mem_accessor = Rc::new(RefCell::new(HashMapMemAccessor::new(
+            probe_id,
+            1,
+            0,
+            &[
+                LogEntry::event(ev(10)),
+                LogEntry::event(ev(1)),
+                LogEntry::event(ev(1)),
+                LogEntry::event(ev(1)),
+            ],
+        )));
+
+        let mut collector = Collector::initialize(
+            &ProbeAddr::Addr(HashMapMemAccessor::PROBE_ADDR),
+            mem_accessor.clone() as Rc<RefCell<dyn Target>>,
+        )
+        .unwrap();
+
+        let report = collector.collect_report().unwrap().unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::Event(ev(10)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
+            ]
+        );
+
+        let (a, b) = LogEntry::event_repeated(2);
+        mem_accessor.borrow_mut().overwrite_buffer(&[
+            LogEntry::event(ev(10)),
+            a,
+            b,
+            LogEntry::event(ev(11)),
+        ]);
+        mem_accessor.borrow_mut().set_write_seqn(4);
+
+        let report = collector.collect_report().unwrap().unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::Event(ev(10)),
+                EventLogEntry::EventRepeated(ev(10), 1),
+                EventLogEntry::Event(ev(11)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
+            ]
+        );
+
+        let (a, b) = LogEntry::event_repeated(1);
+        mem_accessor.borrow_mut().overwrite_buffer(&[
+            a,
+            b,
+            LogEntry::event(ev(12)),
+            LogEntry::event(ev(12)),
+        ]);
+        mem_accessor.borrow_mut().set_write_seqn(7);
+
+        let report = collector.collect_report().unwrap().unwrap();
+        assert_eq!(
+            report.event_log,
+            vec![
+                EventLogEntry::Event(ev(11)),
+                EventLogEntry::Event(ev(12)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
+            ]
+        );
+    }
+
     #[test]
     fn fragmented_paired_wall_clock_time_entries_are_dropped_when_items_are_missed() {
         let pid_raw = 1;
@@ -1570,7 +2371,7 @@ pub mod tests {
             probe_id,
             3,
             0,
-            &mut vec![LogEntry::event(ev(10)), a, b],
+            &[LogEntry::event(ev(10)), a, b],
         )));
 
         let mut collector = Collector::initialize(
@@ -1599,7 +2400,7 @@ pub mod tests {
 
         mem_accessor
             .borrow_mut()
-            .overwrite_buffer(&vec![LogEntry::event(ev(11)), LogEntry::event(ev(12))]);
+            .overwrite_buffer(&[LogEntry::event(ev(11)), LogEntry::event(ev(12))]);
         mem_accessor.borrow_mut().set_write_seqn(5);
         mem_accessor.borrow_mut().set_overwrite_seqn(4);
 
@@ -1673,7 +2474,7 @@ pub mod tests {
 
     #[test]
     fn on_device_probe_missed_entries_are_detectable() {
-        const STORAGE_CAP: usize = 512;
+        const STORAGE_CAP: usize = 584;
         const LOG_CAP: usize = 78;
         let mut storage = [MaybeUninit::new(0u8); STORAGE_CAP];
         let storage_addr = storage.as_ptr() as *const _ as u64;
@@ -1808,10 +2609,12 @@ pub mod tests {
             // NOTE: debug-collector bug, see issue #288
             // Wall clock time portion will be dropped
             if i == 0 {
-                expected_event_log.push(EventLogEntry::EventWithPayload(event_c, payload));
+                expected_event_log.push(EventLogEntry::EventWithPayload(event_c, payload.into()));
             } else {
                 expected_event_log.push(EventLogEntry::EventWithPayloadWithTime(
-                    time, event_c, payload,
+                    time,
+                    event_c,
+                    payload.into(),
                 ));
             }
         }
@@ -1847,4 +2650,309 @@ pub mod tests {
             }
         );
     }
+
+    /// Initialize a probe at the start of `storage` and record a couple
+    /// of events, returning the probe and the address of `storage`
+    pub(crate) fn dumped_probe(storage: &mut [MaybeUninit<u8>]) -> (&mut ModalityProbe<'_>, Word) {
+        let addr_raw = storage.as_ptr() as usize;
+        #[cfg(target_pointer_width = "32")]
+        let addr = Word::U32(addr_raw as u32);
+        #[cfg(target_pointer_width = "64")]
+        let addr = Word::U64(addr_raw as u64);
+        let probe = ModalityProbe::initialize_at(
+            storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.record_event(ev(1));
+        probe.record_event(ev(2));
+        (probe, addr)
+    }
+
+    pub(crate) fn dumped_probe_report() -> Report {
+        let probe_id = ProbeId::new(1).unwrap();
+        Report {
+            probe_id,
+            probe_clock: lc(1, 0, 0),
+            seq_num: SequenceNumber(0),
+            frontier_clocks: vec![lc(1, 0, 0)],
+            event_log: vec![
+                EventLogEntry::TraceClock(lc(1, 0, 0)),
+                EventLogEntry::Event(EventId::EVENT_PROBE_INITIALIZED),
+                EventLogEntry::Event(ev(1)),
+                EventLogEntry::Event(ev(2)),
+                EventLogEntry::Event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT),
+            ],
+            persistent_epoch_counting: false,
+            time_resolution: NanosecondResolution::UNSPECIFIED,
+            wall_clock_id: WallClockId::default(),
+        }
+    }
+
+    /// Build a little endian ELF64 core file with a PT_LOAD segment for
+    /// each of the given regions; empty regions weren't dumped
+    fn core_file(regions: &[(u64, &[u8])]) -> Vec<u8> {
+        const EHDR_SIZE: u16 = 64;
+        const PHDR_SIZE: u16 = 56;
+        let mut core = vec![0x7F, b'E', b'L', b'F', 2, 1, 1];
+        core.resize(16, 0);
+        core.extend_from_slice(&4u16.to_le_bytes()); // e_type: ET_CORE
+        core.extend_from_slice(&62u16.to_le_bytes()); // e_machine: x86_64
+        core.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        core.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+        core.extend_from_slice(&u64::from(EHDR_SIZE).to_le_bytes()); // e_phoff
+        core.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+        core.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        core.extend_from_slice(&EHDR_SIZE.to_le_bytes());
+        core.extend_from_slice(&PHDR_SIZE.to_le_bytes());
+        core.extend_from_slice(&(regions.len() as u16).to_le_bytes());
+        core.extend_from_slice(&64u16.to_le_bytes()); // e_shentsize
+        core.extend_from_slice(&0u16.to_le_bytes()); // e_shnum
+        core.extend_from_slice(&0u16.to_le_bytes()); // e_shstrndx
+
+        let mut offset = u64::from(EHDR_SIZE) + u64::from(PHDR_SIZE) * regions.len() as u64;
+        for (vaddr, contents) in regions {
+            let len = contents.len() as u64;
+            core.extend_from_slice(&1u32.to_le_bytes()); // p_type: PT_LOAD
+            core.extend_from_slice(&6u32.to_le_bytes()); // p_flags: RW
+            core.extend_from_slice(&offset.to_le_bytes());
+            core.extend_from_slice(&vaddr.to_le_bytes());
+            core.extend_from_slice(&0u64.to_le_bytes()); // p_paddr
+            core.extend_from_slice(&len.to_le_bytes()); // p_filesz
+            core.extend_from_slice(&len.max(4096).to_le_bytes()); // p_memsz
+            core.extend_from_slice(&4096u64.to_le_bytes()); // p_align
+            offset += len;
+        }
+        for (_, contents) in regions {
+            core.extend_from_slice(contents);
+        }
+        core
+    }
+
+    /// Find the probes in a memory image, skipping a stray fingerprint
+    #[test]
+    fn discover_probes_in_memory() {
+        let mut storage = [MaybeUninit::new(0u8); 3 * 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (first, rest) = storage.split_at_mut(1024);
+        let (second, stray) = rest.split_at_mut(1024);
+        let (_probe, addr) = dumped_probe(first);
+        let _probe_2 = ModalityProbe::initialize_at(
+            second,
+            ProbeId::new(2).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let offset = 64 + stray.as_ptr().align_offset(4);
+        for (i, b) in ModalityProbe::STRUCT_FINGERPRINT
+            .to_le_bytes()
+            .iter()
+            .enumerate()
+        {
+            stray[offset + i] = MaybeUninit::new(*b);
+        }
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 3 * 1024) }.to_vec();
+
+        let dump = Rc::new(RefCell::new(DumpTarget::from_raw(image, addr.into())));
+        let collectors = discover_probes(&[addr..addr + 3 * 1024], dump).unwrap();
+        let ids: Vec<u32> = collectors.iter().map(|c| c.probe_id().get_raw()).collect();
+        assert_eq!(ids, vec![1, 2]);
+        let mut collector = collectors.into_iter().next().unwrap();
+        assert_eq!(
+            collector.collect_report().unwrap().unwrap(),
+            dumped_probe_report()
+        );
+    }
+
+    /// Keep searching for probes past memory that can't be read
+    #[test]
+    fn discover_probes_skips_unreadable_memory() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (_probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) }.to_vec();
+
+        let dump = Rc::new(RefCell::new(DumpTarget::from_raw(image, addr.into())));
+        let unmapped = addr + 4096..addr + 3 * 4096;
+        let collectors = discover_probes(&[unmapped, addr..addr + 1024], dump).unwrap();
+        let ids: Vec<u32> = collectors.iter().map(|c| c.probe_id().get_raw()).collect();
+        assert_eq!(ids, vec![1]);
+    }
+
+    /// Collect the final report from a raw image of the probe's memory
+    #[test]
+    fn raw_memory_dump() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) }.to_vec();
+        // Not in the dump
+        probe.record_event(ev(3));
+
+        let dump = DumpTarget::from_raw(image, addr.into());
+        let mut collector =
+            Collector::initialize(&ProbeAddr::Addr(addr), Rc::new(RefCell::new(dump))).unwrap();
+        assert_eq!(
+            collector.collect_report().unwrap().unwrap(),
+            dumped_probe_report()
+        );
+        assert_eq!(collector.collect_report().unwrap(), None);
+    }
+
+    /// Collect the final report from the segments of a core file
+    #[test]
+    fn core_file_dump() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (_probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) };
+        let base_addr = u64::from(addr);
+        let core = core_file(&[
+            (base_addr + 512, &image[512..]),
+            (0x1000, &[]),
+            (base_addr, &image[..512]),
+        ]);
+
+        let dump = DumpTarget::from_core(&core).unwrap();
+        let mut collector =
+            Collector::initialize(&ProbeAddr::Addr(addr), Rc::new(RefCell::new(dump))).unwrap();
+        assert_eq!(
+            collector.collect_report().unwrap().unwrap(),
+            dumped_probe_report()
+        );
+
+        let mut dump = DumpTarget::from_core(&core).unwrap();
+        assert!(matches!(
+            dump.read_32(Word::U64(0x1000)),
+            Err(TargetError::AddressNotInDump(0x1000))
+        ));
+        assert!(matches!(
+            DumpTarget::from_core(&core[..core.len() - 1]),
+            Err(TargetError::InvalidCoreFile(_))
+        ));
+        assert!(matches!(
+            DumpTarget::from_core(image),
+            Err(TargetError::InvalidCoreFile(_))
+        ));
+    }
+
+    /// Collect from several targets into one output, where one of the
+    /// targets can't be opened
+    #[test]
+    fn multiple_targets_with_failure() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (_probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) };
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("ram.bin");
+        std::fs::write(&image_path, image).unwrap();
+        let output_path = dir.path().join("out.jsonl");
+
+        let board = TargetSetup {
+            name: "board".to_string(),
+            target: TargetConfig::RawDump(image_path, addr.into()),
+            interval: Duration::default(),
+            init_timeout: None,
+            probes: vec![ProbeSetup {
+                addr: ProbeAddr::Addr(addr),
+                core: 0,
+                interval: None,
+            }],
+            scan_ranges: vec![],
+        };
+        let missing = TargetSetup {
+            name: "missing".to_string(),
+            target: TargetConfig::RawDump(dir.path().join("missing.bin"), addr.into()),
+            ..board.clone()
+        };
+        let config = MultiTargetConfig {
+            session_id: 1.into(),
+            output_path: output_path.clone(),
+            rotation: RotationPolicy::default(),
+            tracking: TrackerOpts::default(),
+            retry_interval: Duration::from_millis(10),
+            targets: vec![missing, board],
+        };
+        let (_shutdown_sender, shutdown_receiver) = std::sync::mpsc::channel();
+        run_multi(&config, shutdown_receiver).unwrap();
+
+        let mut expected = Vec::new();
+        add_log_report_to_entries(&dumped_probe_report(), 1.into(), Utc::now(), &mut expected)
+            .unwrap();
+        let entries = modality_probe_collector_common::json::read_log_entries(
+            &mut File::open(output_path).unwrap(),
+        )
+        .unwrap();
+        let without_time = |entries: Vec<ReportLogEntry>| -> Vec<ReportLogEntry> {
+            entries
+                .into_iter()
+                .map(|e| ReportLogEntry {
+                    receive_time: expected[0].receive_time,
+                    ..e
+                })
+                .collect()
+        };
+        assert_eq!(without_time(entries), without_time(expected.clone()));
+    }
+
+    /// Reports collected after reconnecting to a target carry on from the
+    /// sequence numbers reached before
+    #[test]
+    fn reconnection_keeps_sequence_numbers() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let storage_ptr = storage.as_ptr() as *const u8;
+        let (_probe, addr) = dumped_probe(&mut storage);
+        let image = unsafe { std::slice::from_raw_parts(storage_ptr, 1024) };
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("ram.bin");
+        std::fs::write(&image_path, image).unwrap();
+
+        let board = TargetSetup {
+            name: "board".to_string(),
+            target: TargetConfig::RawDump(image_path, addr.into()),
+            interval: Duration::default(),
+            init_timeout: None,
+            probes: vec![ProbeSetup {
+                addr: ProbeAddr::Addr(addr),
+                core: 0,
+                interval: None,
+            }],
+            scan_ranges: vec![],
+        };
+        let (report_sender, report_receiver) = channel::unbounded();
+        let (_shutdown_sender, shutdown_receiver) = channel::unbounded();
+        let mut next_seq_nums = HashMap::new();
+        // Each poll of the dump connects to it anew
+        for _ in 0..2 {
+            poll_target(
+                &board,
+                &mut false,
+                &mut next_seq_nums,
+                &report_sender,
+                &shutdown_receiver,
+            )
+            .unwrap();
+        }
+        drop(report_sender);
+
+        let seq_nums: Vec<SequenceNumber> = report_receiver.iter().map(|r| r.seq_num).collect();
+        assert_eq!(seq_nums, vec![SequenceNumber(0), SequenceNumber(1)]);
+        assert_eq!(next_seq_nums[&ProbeId::new(1).unwrap()], 2);
+    }
+
+    /// Only probe-rs targets have cores other than the first
+    #[test]
+    fn no_other_cores() {
+        let target = TargetConfig::CoreDump("./nonexistent".into());
+        assert!(matches!(
+            open_mem_accessors(&target, &[0, 1]),
+            Err(TargetError::NoSuchCore(1))
+        ));
+    }
 }
@@ -1,27 +1,47 @@
-use std::sync::mpsc::channel;
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
+
+use std::sync::mpsc::{channel, Receiver};
 use structopt::StructOpt;
 
 mod cli;
-use cli::{config_from_options, Opts};
+use cli::{config_from_options, multi_target_config_from_options, Opts};
 
-use modality_probe_debug_collector::run;
+use modality_probe_debug_collector::{run, run_multi};
 
 fn main() {
     let opts = Opts::from_args();
-    let config = match config_from_options(opts) {
-        Ok(cfg) => cfg,
-        Err(err) => {
-            println!("{}", err);
-            return;
-        }
+    let result = if opts.uses_config_file() {
+        let config = match multi_target_config_from_options(opts) {
+            Ok(cfg) => cfg,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        };
+        run_multi(&config, shutdown_receiver())
+    } else {
+        let config = match config_from_options(opts) {
+            Ok(cfg) => cfg,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        };
+        run(&config, shutdown_receiver())
     };
+    if let Err(err) = result {
+        println!("{}", err);
+    }
+}
+
+/// Receive a message when the user presses Ctrl-C
+fn shutdown_receiver() -> Receiver<()> {
     let (shutdown_sender, shutdown_receiver) = channel();
     ctrlc::set_handler(move || {
         println!();
         shutdown_sender.send(()).unwrap();
     })
     .expect("Could not set the Ctrl-C handler");
-    if let Err(err) = run(&config, shutdown_receiver) {
-        println!("{}", err);
-    }
+    shutdown_receiver
 }
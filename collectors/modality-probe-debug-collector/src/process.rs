@@ -0,0 +1,202 @@
+//! Target that accesses the memory of another process on the same
+//! Linux host through procfs
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use goblin::elf::{header::ET_DYN, program_header::PT_LOAD, Elf};
+
+use crate::{Target, TargetError, Word};
+
+/// Target that reads and writes the memory of a running process via
+/// `/proc/<pid>/mem`. Multi-byte values are read in the host's byte order.
+pub struct ProcessTarget {
+    mem: File,
+}
+
+impl ProcessTarget {
+    /// Open the memory of the process with the given pid. Writing to it
+    /// requires the same permissions as attaching to it with ptrace.
+    pub fn attach(pid: u32) -> Result<Self, TargetError> {
+        let path = format!("/proc/{}/mem", pid);
+        // Fall back to read-only access, which is all collection needs
+        let mem = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .or_else(|_| File::open(&path))
+            .map_err(TargetError::ProcessMemoryError)?;
+        Ok(ProcessTarget { mem })
+    }
+}
+
+impl Target for ProcessTarget {
+    fn reset(&mut self) -> Result<(), TargetError> {
+        Err(TargetError::CannotResetProcess)
+    }
+
+    fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
+        match addr {
+            Word::U32(_) => self.read_32(addr).map(Word::U32),
+            Word::U64(_) => {
+                let mut buf = [0u8; 8];
+                self.read_bytes(addr, &mut buf)?;
+                Ok(Word::U64(u64::from_ne_bytes(buf)))
+            }
+        }
+    }
+
+    fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
+        let mut buf = [0u8; 4];
+        self.read_bytes(addr, &mut buf)?;
+        Ok(u32::from_ne_bytes(buf))
+    }
+
+    fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
+        let mut buf = [0u8];
+        self.read_bytes(addr, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError> {
+        self.mem
+            .seek(SeekFrom::Start(addr.into()))
+            .and_then(|_| self.mem.write_all(&data.to_ne_bytes()))
+            .map_err(TargetError::ProcessMemoryError)
+    }
+
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        self.mem
+            .seek(SeekFrom::Start(addr.into()))
+            .and_then(|_| self.mem.read_exact(buf))
+            .map_err(TargetError::ProcessMemoryError)
+    }
+}
+
+/// Path of the executable of the process with the given pid
+pub fn process_executable(pid: u32) -> PathBuf {
+    PathBuf::from(format!("/proc/{}/exe", pid))
+}
+
+/// Find the offset between the addresses in the given executable and
+/// where they are in the memory of the process running it, which is
+/// non-zero for position independent executables
+pub fn load_bias(pid: u32, elf: &Elf) -> Result<u64, TargetError> {
+    if elf.header.e_type != ET_DYN {
+        return Ok(0);
+    }
+    let first_segment = elf
+        .program_headers
+        .iter()
+        .find(|ph| ph.p_type == PT_LOAD)
+        .ok_or(TargetError::ExecutableNotMapped(pid))?;
+    let exe = fs::read_link(process_executable(pid)).map_err(TargetError::ProcessMemoryError)?;
+    let maps = fs::read_to_string(format!("/proc/{}/maps", pid))
+        .map_err(TargetError::ProcessMemoryError)?;
+    let mapped_start = executable_mapping_start(&maps, &exe.to_string_lossy())
+        .ok_or(TargetError::ExecutableNotMapped(pid))?;
+    // The first segment is mapped starting from the start of the file
+    let linked_start = first_segment.p_vaddr.wrapping_sub(first_segment.p_offset);
+    Ok(mapped_start.wrapping_sub(linked_start))
+}
+
+/// Lowest address that the start of the executable is mapped at, given
+/// the contents of `/proc/<pid>/maps`
+fn executable_mapping_start(maps: &str, exe: &str) -> Option<u64> {
+    maps.lines()
+        .filter_map(|line| {
+            // address perms offset dev inode pathname
+            let mut fields = line.splitn(6, ' ');
+            let range = fields.next()?;
+            let offset = fields.nth(1)?;
+            let path = fields.nth(2)?.trim_start();
+            if path != exe || u64::from_str_radix(offset, 16).ok()? != 0 {
+                return None;
+            }
+            u64::from_str_radix(range.split('-').next()?, 16).ok()
+        })
+        .min()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::tests::{dumped_probe, dumped_probe_report};
+    use crate::{Collector, ProbeAddr};
+    use modality_probe::{EventId, Probe};
+    use modality_probe_collector_common::EventLogEntry;
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+    use std::mem::MaybeUninit;
+    use std::rc::Rc;
+
+    #[test]
+    fn find_executable_mapping() {
+        let maps = "\
+5581c2a00000-5581c2a10000 r--p 00010000 08:01 1234      /usr/bin/service
+5581c2a10000-5581c2a20000 r-xp 00000000 08:01 1234      /usr/bin/service
+5581c2a20000-5581c2a30000 r--p 00000000 08:01 5678      /usr/bin/service-helper
+7f0000000000-7f0000001000 rw-p 00000000 00:00 0
+7ffc00000000-7ffc00021000 rw-p 00000000 00:00 0         [stack]";
+        assert_eq!(
+            executable_mapping_start(maps, "/usr/bin/service"),
+            Some(0x5581c2a10000)
+        );
+        assert_eq!(executable_mapping_start(maps, "/usr/bin/other"), None);
+    }
+
+    #[test]
+    fn collect_from_own_process() {
+        let mut storage = [MaybeUninit::new(0u8); 1024];
+        let (probe, addr) = dumped_probe(&mut storage);
+        let target = Rc::new(RefCell::new(
+            ProcessTarget::attach(std::process::id()).unwrap(),
+        ));
+        let mut collector = Collector::initialize(&ProbeAddr::Addr(addr), target.clone()).unwrap();
+        assert_eq!(
+            collector.collect_report().unwrap().unwrap(),
+            dumped_probe_report()
+        );
+        assert_eq!(collector.collect_report().unwrap(), None);
+
+        // Keeps up with the live probe
+        probe.record_event(EventId::new(3).unwrap());
+        let report = collector.collect_report().unwrap().unwrap();
+        assert!(report
+            .event_log
+            .contains(&EventLogEntry::Event(EventId::new(3).unwrap())));
+
+        collector.set_overwrite_priority(2).unwrap();
+        assert!(matches!(
+            target.borrow_mut().reset(),
+            Err(TargetError::CannotResetProcess)
+        ));
+    }
+
+    #[no_mangle]
+    static MODALITY_PROCESS_TARGET_TEST_SYMBOL: u32 = 0x600D_F00D;
+
+    /// Resolve a symbol of this test executable, which is position
+    /// independent unless built otherwise
+    #[test]
+    fn resolve_own_symbol() {
+        let pid = std::process::id();
+        let exe = fs::read(process_executable(pid)).unwrap();
+        let elf = Elf::parse(&exe).unwrap();
+        let sym = elf
+            .syms
+            .iter()
+            .find(|sym| {
+                elf.strtab.get(sym.st_name).and_then(|n| n.ok())
+                    == Some("MODALITY_PROCESS_TARGET_TEST_SYMBOL")
+            })
+            .unwrap();
+        let addr = sym.st_value + load_bias(pid, &elf).unwrap();
+        assert_eq!(
+            addr,
+            &MODALITY_PROCESS_TARGET_TEST_SYMBOL as *const u32 as u64
+        );
+        let mut target = ProcessTarget::attach(pid).unwrap();
+        assert_eq!(target.read_32(Word::U64(addr)).unwrap(), 0x600D_F00D);
+    }
+}
@@ -0,0 +1,154 @@
+//! Target that accesses probes kept in a named shared memory region by a
+//! process on the same host
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use modality_probe_collector_common::shm::SharedMemoryRegion;
+
+use crate::{Target, TargetError, Word};
+
+/// Target that reads and writes a shared memory region, addressed as it is
+/// in the process which created it. Multi-byte values are read in the
+/// host's byte order.
+pub struct SharedMemoryTarget {
+    region: SharedMemoryRegion,
+    /// Address the region starts at in the process which created it
+    base_addr: u64,
+}
+
+impl SharedMemoryTarget {
+    /// Map the shared memory region with the given name
+    pub fn open(name: &str) -> Result<Self, TargetError> {
+        let region = SharedMemoryRegion::open(name).map_err(TargetError::SharedMemoryError)?;
+        let base_addr = region.creator_base_addr();
+        Ok(SharedMemoryTarget { region, base_addr })
+    }
+
+    /// Addresses the region spans in the process which created it
+    pub fn addr_range(&self) -> Range<u64> {
+        self.base_addr..self.base_addr + self.region.len() as u64
+    }
+
+    /// Offset into the region of the `len` bytes at `addr`
+    fn offset(&self, addr: Word, len: usize) -> Result<usize, TargetError> {
+        let addr = u64::from(addr);
+        addr.checked_sub(self.base_addr)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .filter(|offset| {
+                offset
+                    .checked_add(len)
+                    .is_some_and(|end| end <= self.region.len())
+            })
+            .ok_or(TargetError::AddressNotInSharedMemory(addr))
+    }
+}
+
+impl Target for SharedMemoryTarget {
+    fn reset(&mut self) -> Result<(), TargetError> {
+        Err(TargetError::CannotResetSharedMemory)
+    }
+
+    fn read_word(&mut self, addr: Word) -> Result<Word, TargetError> {
+        match addr {
+            Word::U32(_) => self.read_32(addr).map(Word::U32),
+            Word::U64(_) => {
+                let mut buf = [0u8; 8];
+                self.read_bytes(addr, &mut buf)?;
+                Ok(Word::U64(u64::from_ne_bytes(buf)))
+            }
+        }
+    }
+
+    fn read_32(&mut self, addr: Word) -> Result<u32, TargetError> {
+        let offset = self.offset(addr, 4)?;
+        Ok(self.region.read_u32(offset))
+    }
+
+    fn read_byte(&mut self, addr: Word) -> Result<u8, TargetError> {
+        let mut buf = [0u8];
+        self.read_bytes(addr, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_32(&mut self, addr: Word, data: u32) -> Result<(), TargetError> {
+        let offset = self.offset(addr, 4)?;
+        self.region.write_u32(offset, data);
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, addr: Word, buf: &mut [u8]) -> Result<(), TargetError> {
+        let offset = self.offset(addr, buf.len())?;
+        self.region.read_bytes(offset, buf);
+        Ok(())
+    }
+
+    /// Probes are discovered anywhere in the region, using the host's word size
+    fn implicit_scan_ranges(&self) -> Vec<Range<Word>> {
+        let range = self.addr_range();
+        if cfg!(target_pointer_width = "64") {
+            vec![Word::U64(range.start)..Word::U64(range.end)]
+        } else {
+            vec![Word::U32(range.start as u32)..Word::U32(range.end as u32)]
+        }
+    }
+}
+
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    use super::*;
+    use crate::{discover_new_probes, Collector};
+    use modality_probe::{
+        EventId, ModalityProbe, NanosecondResolution, Probe, ProbeId, RestartCounterProvider,
+        WallClockId,
+    };
+    use modality_probe_collector_common::EventLogEntry;
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn collect_from_shared_memory() {
+        let name = format!("modality-probe-debug-collector-test-{}", std::process::id());
+        let mut region = SharedMemoryRegion::create(&name, 1024).unwrap();
+        let probe = ModalityProbe::initialize_at(
+            region.storage(),
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.record_event(EventId::new(2).unwrap());
+
+        let target = SharedMemoryTarget::open(&name).unwrap();
+        SharedMemoryRegion::unlink(&name).unwrap();
+        let range = target.addr_range();
+        let target = Rc::new(RefCell::new(target));
+        // The whole region is scanned without being asked to
+        let mut collectors = discover_new_probes(&[], target.clone(), &[]).unwrap();
+        assert_eq!(collectors.len(), 1);
+        let collector: &mut Collector = &mut collectors[0];
+        assert_eq!(collector.probe_id(), ProbeId::new(1).unwrap());
+        let report = collector.collect_report().unwrap().unwrap();
+        assert!(report
+            .event_log
+            .contains(&EventLogEntry::Event(EventId::new(2).unwrap())));
+        assert_eq!(collector.collect_report().unwrap(), None);
+
+        // Keeps up with the live probe
+        probe.record_event(EventId::new(3).unwrap());
+        let report = collector.collect_report().unwrap().unwrap();
+        assert!(report
+            .event_log
+            .contains(&EventLogEntry::Event(EventId::new(3).unwrap())));
+
+        assert!(matches!(
+            target.borrow_mut().read_32(Word::U64(range.end)),
+            Err(TargetError::AddressNotInSharedMemory(_))
+        ));
+        assert!(matches!(
+            target.borrow_mut().reset(),
+            Err(TargetError::CannotResetSharedMemory)
+        ));
+    }
+}
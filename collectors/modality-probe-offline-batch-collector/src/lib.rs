@@ -1,18 +1,23 @@
 #![deny(warnings)]
 
-use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::io::{self, BufRead, Read, Write};
 use std::mem;
 use std::path::PathBuf;
 
 use buf_redux::BufReader;
-use chrono::Utc;
-use log::{debug, warn};
-use modality_probe::{wire::WireReport, ProbeId};
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use modality_probe::wire::{WireFragment, WireReport};
 use modality_probe_collector_common::{
-    self as common, json, Report, ReportLogEntry, SequenceNumber, SessionId,
+    framing::Framing,
+    metrics::{write_reports, ReceivedReport, ReportTracker, TrackerOpts, DEFAULT_REORDER_WINDOW},
+    reassembly::{IncompleteReport, Reassembler},
+    rotation::RotationOpts,
+    Report, ReportLogEntry, SessionId,
 };
+
+pub use modality_probe_collector_common::metrics::{ProbeReportMetrics, ReportMetrics};
 use structopt::StructOpt;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, StructOpt)]
@@ -32,49 +37,24 @@ pub struct Opts {
     /// The output file location, defaults to the current directory
     #[structopt(short = "o", long, parse(from_os_str))]
     pub output_file: Option<PathBuf>,
-}
 
-#[derive(Clone, Debug, Default)]
-pub struct ReportMetrics {
-    pub bytes_accumulated: u64,
-    pub bytes_discarded: u64,
-    pub reports_discarded: u64,
-    pub probe_report_metrics: HashMap<ProbeId, ProbeReportMetrics>,
-}
+    /// How the reports in the input are framed
+    #[structopt(short = "f", long, default_value = "raw", possible_values = Framing::NAMES)]
+    pub framing: Framing,
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
-pub struct ProbeReportMetrics {
-    pub num_reports: u64,
-    pub missed_seq_nums: u64,
-    pub last_seq_num: SequenceNumber,
-}
+    #[structopt(flatten)]
+    pub tracking: TrackerOpts,
 
-impl Default for ProbeReportMetrics {
-    fn default() -> Self {
-        ProbeReportMetrics {
-            num_reports: 0,
-            missed_seq_nums: 0,
-            last_seq_num: SequenceNumber(0),
-        }
-    }
-}
-
-impl ProbeReportMetrics {
-    fn update(&mut self, report: &Report) {
-        if self.num_reports != 0 && (report.seq_num.prev() != self.last_seq_num) {
-            self.missed_seq_nums = self.missed_seq_nums.saturating_add(1);
-        }
-        self.num_reports = self.num_reports.saturating_add(1);
-        self.last_seq_num = report.seq_num;
-    }
+    #[structopt(flatten)]
+    pub rotation: RotationOpts,
 }
 
 #[derive(Debug)]
 pub struct OfflineBatchCollector<'a, I: Read, O: Write + 'a> {
     fingerprint_len: usize,
-    header_len: usize,
     log_entries_buffer: Vec<ReportLogEntry>,
-    metrics: ReportMetrics,
+    tracker: ReportTracker,
+    reassembler: Reassembler,
     session_id: SessionId,
     eof_reached: bool,
     reader: BufReader<I>,
@@ -86,9 +66,9 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
         let fingerprint_len = mem::size_of_val(&WireReport::<&[u8]>::FINGERPRINT);
         OfflineBatchCollector {
             fingerprint_len,
-            header_len: WireReport::<&[u8]>::header_len(),
             log_entries_buffer: Vec::with_capacity(4096),
-            metrics: ReportMetrics::default(),
+            tracker: ReportTracker::new(DEFAULT_REORDER_WINDOW),
+            reassembler: Reassembler::new(),
             session_id,
             eof_reached: false,
             reader: BufReader::with_capacity_ringbuf(8192, reader),
@@ -96,6 +76,19 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
         }
     }
 
+    /// Hold back at most `reorder_window` reports per probe while waiting
+    /// for a missing earlier report, see `ReportTracker`
+    pub fn with_reorder_window(self, reorder_window: usize) -> Self {
+        self.with_tracker(ReportTracker::new(reorder_window))
+    }
+
+    /// Sequence reports and summarize the metrics with the given tracker,
+    /// logging a summary whenever `ReportTracker::summary_due` says so
+    pub fn with_tracker(mut self, tracker: ReportTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
     /// Run the collection loop, consuming until EOF or an error is encountered
     pub fn run(mut self) -> io::Result<ReportMetrics> {
         // Keep consuming until EOF or an error is encountered
@@ -129,18 +122,21 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
                 let slice = &bytes[idx..];
                 if slice.len() >= self.fingerprint_len {
                     fingerprint_offsets_checked += 1;
-                    let r = WireReport::new_unchecked(&slice[..]);
-                    if r.check_fingerprint().is_ok() {
+                    let r = WireReport::new_unchecked(slice);
+                    let f = WireFragment::new_unchecked(slice);
+                    if r.check_fingerprint().is_ok() || f.check_fingerprint().is_ok() {
                         fingerprint_offset = Some(idx);
                         debug!(
                             "Found fingerprint at offset {}",
-                            self.metrics.bytes_accumulated + idx as u64
+                            self.tracker.metrics.bytes_accumulated + idx as u64
                         );
                         if idx != 0 {
                             // Fingerprint not found at the start of the buffer
-                            let throwaway_start = self.metrics.bytes_accumulated;
-                            let throwaway_end = self.metrics.bytes_accumulated + (idx as u64 - 1);
-                            self.metrics.bytes_discarded = self
+                            let throwaway_start = self.tracker.metrics.bytes_accumulated;
+                            let throwaway_end =
+                                self.tracker.metrics.bytes_accumulated + (idx as u64 - 1);
+                            self.tracker.metrics.bytes_discarded = self
+                                .tracker
                                 .metrics
                                 .bytes_discarded
                                 .saturating_add(throwaway_end - throwaway_start + 1);
@@ -173,13 +169,15 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
                     } else {
                         fingerprint_offsets_checked
                     };
-                    let throwaway_start = self.metrics.bytes_accumulated;
-                    let throwaway_end = self.metrics.bytes_accumulated + bytes_thrown_away as u64;
+                    let throwaway_start = self.tracker.metrics.bytes_accumulated;
+                    let throwaway_end =
+                        self.tracker.metrics.bytes_accumulated + bytes_thrown_away as u64;
                     warn!(
                         "Throwing away bytes {}..={} (size {}), searching for fingerprint",
                         throwaway_start, throwaway_end, bytes_thrown_away,
                     );
-                    self.metrics.bytes_discarded = self
+                    self.tracker.metrics.bytes_discarded = self
+                        .tracker
                         .metrics
                         .bytes_discarded
                         .saturating_add(bytes_thrown_away as _);
@@ -188,48 +186,58 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
                 Some(fingerprint_offset) => {
                     bytes_consumed += fingerprint_offset;
                     let slice = &bytes[fingerprint_offset..];
-                    let r = WireReport::new_unchecked(&slice[..]);
-                    if r.check_len().is_ok() && r.check_payload_len().is_ok() {
-                        let payload_len = r.payload_len();
-                        let report_size = self.header_len + payload_len;
+                    let r = WireReport::new_unchecked(slice);
+                    let f = WireFragment::new_unchecked(slice);
+                    if r.check_fingerprint().is_ok()
+                        && r.check_len().is_ok()
+                        && r.check_payload_len().is_ok()
+                    {
+                        let report_size = r.total_len();
                         bytes_consumed += report_size;
                         debug!("Found report, size {} bytes", report_size);
                         let report_bytes = &r.into_inner()[..report_size];
-                        self.log_entries_buffer.clear();
-                        match Report::try_from(report_bytes) {
-                            Ok(log_report) => {
-                                let metrics = self
-                                    .metrics
-                                    .probe_report_metrics
-                                    .entry(log_report.probe_id)
-                                    .or_default();
-                                metrics.update(&log_report);
-                                if let Err(e) = common::add_log_report_to_entries(
-                                    &log_report,
+                        let released = track_report(report_bytes, recv_time, &mut self.tracker);
+                        write_reports(
+                            &released,
+                            self.session_id,
+                            &mut self.log_entries_buffer,
+                            &mut self.log_output_writer,
+                        )?;
+                    } else if f.check_fingerprint().is_ok()
+                        && f.check_len().is_ok()
+                        && f.check_payload_len().is_ok()
+                    {
+                        let fragment_size = f.total_len();
+                        bytes_consumed += fragment_size;
+                        debug!("Found report fragment, size {} bytes", fragment_size);
+                        self.tracker.metrics.fragments_received =
+                            self.tracker.metrics.fragments_received.saturating_add(1);
+                        let reassembled = self.reassembler.push(&slice[..fragment_size]);
+                        for incomplete in self.reassembler.take_incomplete() {
+                            flag_incomplete(&mut self.tracker.metrics, incomplete);
+                        }
+                        match reassembled {
+                            Ok(Some(report_bytes)) => {
+                                debug!("Reassembled report, size {} bytes", report_bytes.len());
+                                let released =
+                                    track_report(&report_bytes, recv_time, &mut self.tracker);
+                                write_reports(
+                                    &released,
                                     self.session_id,
-                                    recv_time,
                                     &mut self.log_entries_buffer,
-                                ) {
-                                    warn!("Encountered a malformed report, discarding: {}", e);
-                                }
+                                    &mut self.log_output_writer,
+                                )?;
                             }
+                            Ok(None) => (),
                             Err(e) => {
-                                self.metrics.reports_discarded =
-                                    self.metrics.reports_discarded.saturating_add(1);
-                                self.metrics.bytes_discarded = self
+                                self.tracker.metrics.bytes_discarded = self
+                                    .tracker
                                     .metrics
                                     .bytes_discarded
-                                    .saturating_add(report_size as _);
-                                warn!("{}, throwing away {} bytes", e, report_size);
+                                    .saturating_add(fragment_size as _);
+                                warn!("{}, throwing away {} bytes", e, fragment_size);
                             }
                         }
-                        if let Err(e) = json::write_log_entries(
-                            &mut self.log_output_writer,
-                            &self.log_entries_buffer,
-                        ) {
-                            warn!("Error writing log entries: {}", e);
-                        }
-                        self.log_output_writer.flush()?;
                     } else {
                         // Need more data to fullfill the report, check if any is available
                         // or if we're at the EOF
@@ -237,26 +245,47 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
                         if eof_expected {
                             // No more available, throw away the remaining
                             bytes_consumed += buffer_len;
-                            self.metrics.bytes_discarded =
-                                self.metrics.bytes_discarded.saturating_add(buffer_len as _);
+                            self.tracker.metrics.bytes_discarded = self
+                                .tracker
+                                .metrics
+                                .bytes_discarded
+                                .saturating_add(buffer_len as _);
                         }
                     }
                 }
             }
 
             self.reader.consume(bytes_consumed);
-            self.metrics.bytes_accumulated = self
+            self.tracker.metrics.bytes_accumulated = self
+                .tracker
                 .metrics
                 .bytes_accumulated
                 .saturating_add(bytes_consumed as u64);
 
             debug!(
                 "Consuming {} bytes from input buffer, total bytes accumulated {}",
-                bytes_consumed, self.metrics.bytes_accumulated
+                bytes_consumed, self.tracker.metrics.bytes_accumulated
             );
+
+            if self.tracker.summary_due() {
+                for line in self.tracker.metrics.to_string().lines() {
+                    info!("{}", line);
+                }
+            }
+        }
+
+        for incomplete in self.reassembler.finish() {
+            flag_incomplete(&mut self.tracker.metrics, incomplete);
         }
+        let released = self.tracker.finish();
+        write_reports(
+            &released,
+            self.session_id,
+            &mut self.log_entries_buffer,
+            &mut self.log_output_writer,
+        )?;
 
-        Ok(self.metrics)
+        Ok(self.tracker.metrics)
     }
 
     fn read_check_eof(&mut self) -> io::Result<bool> {
@@ -265,3 +294,28 @@ impl<'a, I: Read, O: Write> OfflineBatchCollector<'a, I, O> {
         Ok(self.eof_reached)
     }
 }
+
+fn flag_incomplete(metrics: &mut ReportMetrics, incomplete: IncompleteReport) {
+    warn!("{}", incomplete);
+    metrics.flag_incomplete();
+}
+
+/// Parse a report and hand it to the tracker, returning the reports which
+/// are ready to be written
+fn track_report(
+    report_bytes: &[u8],
+    recv_time: DateTime<Utc>,
+    tracker: &mut ReportTracker,
+) -> Vec<ReceivedReport> {
+    match Report::try_from(report_bytes) {
+        Ok(log_report) => {
+            tracker.metrics.flag_parsed(report_bytes);
+            tracker.push(log_report, recv_time)
+        }
+        Err(e) => {
+            tracker.metrics.flag_discarded(report_bytes.len(), &e);
+            warn!("{}, throwing away {} bytes", e, report_bytes.len());
+            Vec::new()
+        }
+    }
+}
@@ -1,7 +1,10 @@
 #![deny(warnings)]
 
 use modality_probe::*;
-use modality_probe_collector_common::SessionId;
+use modality_probe_collector_common::{
+    framing::{framed_reader, Framing},
+    json, SessionId,
+};
 use modality_probe_offline_batch_collector::{OfflineBatchCollector, ProbeReportMetrics};
 use proptest::prelude::*;
 use std::convert::TryInto;
@@ -167,27 +170,33 @@ proptest! {
         prop_assert!(metrics.probe_report_metrics.contains_key(&probe_id_c));
 
         prop_assert_eq!(
-            metrics.probe_report_metrics.get(&probe_id_a).unwrap().clone(),
+            *metrics.probe_report_metrics.get(&probe_id_a).unwrap(),
             ProbeReportMetrics {
-                num_reports: num_reports_per_probe as u64,
+                num_reports: num_reports_per_probe,
                 missed_seq_nums: 0,
                 last_seq_num: (num_reports_per_probe - 1).into(),
+                restarts: 0,
+                epoch_rollovers: 0,
             }
         );
         prop_assert_eq!(
-            metrics.probe_report_metrics.get(&probe_id_b).unwrap().clone(),
+            *metrics.probe_report_metrics.get(&probe_id_b).unwrap(),
             ProbeReportMetrics {
                 num_reports: num_reports_per_probe,
                 missed_seq_nums: 0,
                 last_seq_num: (num_reports_per_probe - 1).into(),
+                restarts: 0,
+                epoch_rollovers: 0,
             }
         );
         prop_assert_eq!(
-            metrics.probe_report_metrics.get(&probe_id_c).unwrap().clone(),
+            *metrics.probe_report_metrics.get(&probe_id_c).unwrap(),
             ProbeReportMetrics {
                 num_reports: num_reports_per_probe,
                 missed_seq_nums: 0,
                 last_seq_num: (num_reports_per_probe - 1).into(),
+                restarts: 0,
+                epoch_rollovers: 0,
             }
         );
 
@@ -267,6 +276,8 @@ fn missed_reports_are_detected() {
             num_reports: 2,
             missed_seq_nums: 1,
             last_seq_num: 2.into(),
+            restarts: 0,
+            epoch_rollovers: 0,
         }
     );
 
@@ -335,6 +346,8 @@ fn discarded_reports_are_detected() {
             num_reports: 1,
             missed_seq_nums: 0,
             last_seq_num: 0.into(),
+            restarts: 0,
+            epoch_rollovers: 0,
         }
     );
 
@@ -342,3 +355,319 @@ fn discarded_reports_are_detected() {
     assert_eq!(metrics.bytes_discarded, corrupted_bytes as u64);
     assert_eq!(metrics.reports_discarded, 1);
 }
+
+#[test]
+fn duplicate_and_reordered_reports_are_sequenced() {
+    init_logging();
+
+    let probe_id = 1.try_into().unwrap();
+    let mut storage = vec![MaybeUninit::new(0_u8); STORAGE_SIZE];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )
+    .unwrap();
+
+    let mut reports = Vec::new();
+    for _ in 0..4 {
+        fill_probe_log(probe);
+        let mut report = Vec::new();
+        write_report(probe, &mut report);
+        reports.push(report);
+    }
+    // Reports 1 and 2 are swapped, and report 1 is repeated
+    let input: Vec<u8> = [0, 2, 1, 1, 3]
+        .iter()
+        .flat_map(|i| reports[*i].iter().copied())
+        .collect();
+
+    let mut logs_out = Vec::new();
+    let collector = OfflineBatchCollector::new(SessionId(0), &input[..], &mut logs_out);
+    let metrics = collector.run().unwrap();
+
+    assert_eq!(
+        metrics.probe_report_metrics.get(&probe_id).unwrap().clone(),
+        ProbeReportMetrics {
+            num_reports: 4,
+            missed_seq_nums: 0,
+            last_seq_num: 3.into(),
+            restarts: 0,
+            epoch_rollovers: 0,
+        }
+    );
+    assert_eq!(metrics.duplicate_reports, 1);
+    assert_eq!(metrics.out_of_order_reports, 1);
+    assert_eq!(metrics.reports_discarded, 0);
+
+    let entries = json::read_log_entries(&mut &logs_out[..]).unwrap();
+    let mut seq_nums: Vec<u64> = entries.iter().map(|e| e.sequence_number.0).collect();
+    seq_nums.dedup();
+    assert_eq!(seq_nums, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn checksum_mismatches_are_detected() {
+    init_logging();
+
+    let probe_id = 1.try_into().unwrap();
+    let mut storage = vec![MaybeUninit::new(0_u8); STORAGE_SIZE];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )
+    .unwrap();
+    probe.set_checksums(true);
+
+    let root_dir = tempfile::tempdir().unwrap();
+    let root_path = root_dir.path().to_owned();
+    let reports_in_path = root_path.join("reports.bin");
+    let logs_out_path = root_path.join("reports.jsonl");
+
+    let (bytes_written, corrupted_bytes) = {
+        let mut report_file = File::create(&reports_in_path).unwrap();
+        let mut bytes_written = 0;
+
+        // First report is ok
+        fill_probe_log(probe);
+        bytes_written += write_report(probe, &mut report_file);
+
+        // Second has a single flipped bit
+        fill_probe_log(probe);
+        let mut buffer = vec![0_u8; 2 * STORAGE_SIZE];
+        let n_report_bytes = probe.report(&mut buffer[..]).unwrap().unwrap().get();
+        buffer[n_report_bytes / 2] ^= 0x01;
+        report_file.write_all(&buffer[..n_report_bytes]).unwrap();
+        bytes_written += n_report_bytes;
+
+        report_file.sync_all().unwrap();
+
+        (bytes_written, n_report_bytes)
+    };
+
+    let metrics = {
+        let mut reader = File::open(&reports_in_path).unwrap();
+        let mut logs_out_file = File::create(&logs_out_path).unwrap();
+
+        let session_id = SessionId(0);
+        let collector = OfflineBatchCollector::new(session_id, &mut reader, &mut logs_out_file);
+        let metrics = collector.run().unwrap();
+
+        logs_out_file.sync_all().unwrap();
+
+        metrics
+    };
+
+    assert_eq!(
+        metrics.probe_report_metrics.get(&probe_id).unwrap().clone(),
+        ProbeReportMetrics {
+            num_reports: 1,
+            missed_seq_nums: 0,
+            last_seq_num: 0.into(),
+            restarts: 0,
+            epoch_rollovers: 0,
+        }
+    );
+
+    assert_eq!(metrics.bytes_accumulated, bytes_written as u64);
+    assert_eq!(metrics.bytes_discarded, corrupted_bytes as u64);
+    assert_eq!(metrics.reports_discarded, 1);
+    assert_eq!(metrics.checksums_verified, 1);
+    assert_eq!(metrics.checksum_mismatches, 1);
+}
+
+#[test]
+fn fragmented_reports_are_reassembled() {
+    init_logging();
+
+    let probe_id = 1.try_into().unwrap();
+    let mut storage = vec![MaybeUninit::new(0_u8); STORAGE_SIZE];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )
+    .unwrap();
+
+    let root_dir = tempfile::tempdir().unwrap();
+    let root_path = root_dir.path().to_owned();
+    let reports_in_path = root_path.join("reports.bin");
+    let logs_out_path = root_path.join("reports.jsonl");
+
+    let (bytes_written, junk_bytes, n_fragments) = {
+        let mut report_file = File::create(&reports_in_path).unwrap();
+        let mut bytes_written = 0;
+        let mut junk_bytes = 0;
+        let mut n_fragments = 0;
+        let mut buffer = vec![0_u8; 2 * STORAGE_SIZE];
+        let mut fragment = [0_u8; 64];
+
+        for report_index in 0..3 {
+            fill_probe_log(probe);
+            let mut fragments = Vec::new();
+            probe
+                .report_fragmented(&mut buffer, &mut fragment, |f| fragments.push(f.to_vec()))
+                .unwrap()
+                .unwrap();
+            assert!(fragments.len() > 1);
+            // The second report loses its last fragment
+            if report_index == 1 {
+                fragments.pop();
+            }
+            for f in fragments.iter() {
+                report_file.write_all(f).unwrap();
+                bytes_written += f.len();
+                n_fragments += 1;
+                junk_bytes += write_junk_bytes(3, &mut report_file);
+            }
+        }
+        bytes_written += junk_bytes;
+
+        report_file.sync_all().unwrap();
+
+        (bytes_written, junk_bytes, n_fragments)
+    };
+
+    let metrics = {
+        let mut reader = File::open(&reports_in_path).unwrap();
+        let mut logs_out_file = File::create(&logs_out_path).unwrap();
+
+        let session_id = SessionId(0);
+        let collector = OfflineBatchCollector::new(session_id, &mut reader, &mut logs_out_file);
+        let metrics = collector.run().unwrap();
+
+        logs_out_file.sync_all().unwrap();
+
+        metrics
+    };
+
+    assert_eq!(
+        metrics.probe_report_metrics.get(&probe_id).unwrap().clone(),
+        ProbeReportMetrics {
+            num_reports: 2,
+            missed_seq_nums: 1,
+            last_seq_num: 2.into(),
+            restarts: 0,
+            epoch_rollovers: 0,
+        }
+    );
+
+    assert_eq!(metrics.bytes_accumulated, bytes_written as u64);
+    assert_eq!(metrics.bytes_discarded, junk_bytes as u64);
+    assert_eq!(metrics.fragments_received, n_fragments);
+    assert_eq!(metrics.incomplete_reports, 1);
+    assert_eq!(metrics.reports_discarded, 0);
+}
+
+type Encoder = fn(&[u8]) -> Vec<u8>;
+
+fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0];
+    let mut code_idx = 0;
+    for &b in data {
+        if b != 0 {
+            out.push(b);
+        }
+        if b == 0 || out.len() - code_idx == 0xFF {
+            out[code_idx] = (out.len() - code_idx) as u8;
+            code_idx = out.len();
+            out.push(0);
+        }
+    }
+    out[code_idx] = (out.len() - code_idx) as u8;
+    out.push(0);
+    out
+}
+
+fn slip_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0xC0];
+    for &b in data {
+        match b {
+            0xC0 => out.extend_from_slice(&[0xDB, 0xDC]),
+            0xDB => out.extend_from_slice(&[0xDB, 0xDD]),
+            _ => out.push(b),
+        }
+    }
+    out.push(0xC0);
+    out
+}
+
+fn len_prefix_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = (data.len() as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(data);
+    out
+}
+
+/// Encoded lines are interleaved with other console output
+fn hex_line_encode(data: &[u8]) -> Vec<u8> {
+    format!("[app] sending report\r\n{}\r\n", hex::encode(data)).into_bytes()
+}
+
+fn base64_line_encode(data: &[u8]) -> Vec<u8> {
+    format!("[app] sending report\n{}\n", base64::encode(data)).into_bytes()
+}
+
+#[test]
+fn framed_reports_are_decoded() {
+    init_logging();
+
+    let probe_id = 1.try_into().unwrap();
+    let mut storage = vec![MaybeUninit::new(0_u8); STORAGE_SIZE];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )
+    .unwrap();
+
+    let mut reports = Vec::new();
+    for _ in 0..3 {
+        fill_probe_log(probe);
+        let mut report = Vec::new();
+        write_report(probe, &mut report);
+        reports.push(report);
+    }
+    let reports_len: usize = reports.iter().map(Vec::len).sum();
+
+    let encodings: [(Framing, Encoder); 5] = [
+        (Framing::Cobs, cobs_encode),
+        (Framing::Slip, slip_encode),
+        (Framing::LenPrefix, len_prefix_encode),
+        (Framing::HexLines, hex_line_encode),
+        (Framing::Base64Lines, base64_line_encode),
+    ];
+    for (framing, encode) in encodings.iter() {
+        let input: Vec<u8> = reports.iter().flat_map(|r| encode(r)).collect();
+        let mut logs_out = Vec::new();
+        let collector = OfflineBatchCollector::new(
+            SessionId(0),
+            framed_reader(*framing, &input[..]),
+            &mut logs_out,
+        );
+        let metrics = collector.run().unwrap();
+
+        assert_eq!(
+            metrics.probe_report_metrics.get(&probe_id).unwrap().clone(),
+            ProbeReportMetrics {
+                num_reports: 3,
+                missed_seq_nums: 0,
+                last_seq_num: 2.into(),
+                restarts: 0,
+                epoch_rollovers: 0,
+            },
+            "{} framing",
+            framing
+        );
+        assert_eq!(metrics.bytes_accumulated, reports_len as u64);
+        assert_eq!(metrics.bytes_discarded, 0);
+    }
+}
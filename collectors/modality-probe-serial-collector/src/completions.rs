@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+
+use modality_probe_serial_collector::Opts;
+use structopt::{clap::Shell, StructOpt};
+
+fn main() {
+    // Generate `bash` completions in the current working directory
+    Opts::clap().gen_completions("modality-probe-serial-collector", Shell::Bash, "./");
+}
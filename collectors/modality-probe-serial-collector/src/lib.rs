@@ -0,0 +1,92 @@
+#![deny(warnings)]
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use modality_probe_collector_common::{
+    framing::{framed_reader, Framing},
+    metrics::{ReportTracker, TrackerOpts},
+    rotation::RotationOpts,
+    SessionId,
+};
+use modality_probe_offline_batch_collector::{OfflineBatchCollector, ReportMetrics};
+use structopt::StructOpt;
+
+mod tty;
+pub use tty::{SerialPort, READ_TIMEOUT_MS};
+
+#[derive(Clone, Eq, PartialEq, Debug, StructOpt)]
+#[structopt(
+    name = "modality-probe-serial-collector",
+    about = "Collects modality-probe reports from a serial device and logs them to file"
+)]
+pub struct Opts {
+    /// The serial device to read reports from, e.g. /dev/ttyUSB0
+    #[structopt(parse(from_os_str))]
+    pub device: PathBuf,
+
+    /// The baud rate of the serial device
+    #[structopt(short = "b", long, default_value = "115200")]
+    pub baud: u32,
+
+    /// How the reports sent over the serial device are framed
+    #[structopt(short = "f", long, default_value = "raw", possible_values = Framing::NAMES)]
+    pub framing: Framing,
+
+    /// The session id to associate with the collected trace data
+    #[structopt(short = "s", long, default_value = "0")]
+    pub session_id: u32,
+
+    /// The output file location, defaults to the current directory
+    #[structopt(short = "o", long, parse(from_os_str))]
+    pub output_file: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    pub tracking: TrackerOpts,
+
+    #[structopt(flatten)]
+    pub rotation: RotationOpts,
+}
+
+/// Collect reports from the serial device until `shutdown` is set or the
+/// device hangs up, sequencing them with `tracker` and writing their log
+/// entries to `log_output_writer`. Anything between reports, such as
+/// console output, is skipped.
+pub fn run<W: Write>(
+    port: SerialPort,
+    framing: Framing,
+    session_id: SessionId,
+    tracker: ReportTracker,
+    shutdown: Arc<AtomicBool>,
+    log_output_writer: &mut W,
+) -> io::Result<ReportMetrics> {
+    let reader = framed_reader(framing, UntilShutdown { port, shutdown });
+    OfflineBatchCollector::new(session_id, reader, log_output_writer)
+        .with_tracker(tracker)
+        .run()
+}
+
+/// Reads from a serial device, waiting out quiet periods until shutdown,
+/// when it reaches the end of input
+struct UntilShutdown {
+    port: SerialPort,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl Read for UntilShutdown {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.port.read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                    if self.shutdown.load(Ordering::SeqCst) {
+                        return Ok(0);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                res => return res,
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#![deny(warnings)]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{env, io};
+
+use log::info;
+use modality_probe_collector_common::{rotation::RotatingWriter, SessionId};
+use structopt::StructOpt;
+
+use modality_probe_serial_collector::{run, Opts, SerialPort};
+
+fn main() -> io::Result<()> {
+    env_logger::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let opts = Opts::from_args();
+    let session_id = SessionId::from(opts.session_id);
+    let output_file = opts.output_file.clone().unwrap_or_else(|| {
+        env::current_dir()
+            .expect("Could not retrieve current directory")
+            .join(format!("session_{}_log_entries.jsonl", session_id.0))
+    });
+
+    let mut log_output_writer = RotatingWriter::open(&output_file, opts.rotation.policy())?;
+
+    let port = SerialPort::open(&opts.device, opts.baud)?;
+    info!(
+        "Reading {} framed reports from {} at {} baud",
+        opts.framing,
+        opts.device.display(),
+        opts.baud
+    );
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_flag = shutdown.clone();
+    ctrlc::set_handler(move || {
+        shutdown_flag.store(true, Ordering::SeqCst);
+    })
+    .expect("Could not set the Ctrl-C handler");
+
+    let metrics = run(
+        port,
+        opts.framing,
+        session_id,
+        opts.tracking.tracker(),
+        shutdown,
+        &mut log_output_writer,
+    )?;
+
+    info!("Wrote log entries to {}", output_file.display());
+    for line in metrics.to_string().lines() {
+        info!("{}", line);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Raw access to serial devices through termios
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// How long a read waits for input before timing out
+pub const READ_TIMEOUT_MS: i32 = 100;
+
+/// A serial device configured for raw input.
+///
+/// Reads wait up to `READ_TIMEOUT_MS` for input, then fail with a
+/// `TimedOut` error. Once the device hangs up, such as when the other
+/// end of a pseudo-terminal is closed, reads return 0 bytes.
+#[derive(Debug)]
+pub struct SerialPort {
+    file: File,
+}
+
+#[cfg(unix)]
+impl SerialPort {
+    /// Open a serial device and configure it for raw 8N1 input at the given baud rate
+    pub fn open(path: &Path, baud: u32) -> io::Result<Self> {
+        use std::fs::OpenOptions;
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::os::unix::io::AsRawFd;
+
+        let speed = baud_rate(baud).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported baud rate {}", baud),
+            )
+        })?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(path)?;
+        let fd = file.as_raw_fd();
+        // Safety: the termios struct is only used by the calls below, on a
+        // file descriptor that stays open for their duration
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::cfmakeraw(&mut termios);
+            termios.c_cflag |= libc::CLOCAL | libc::CREAD;
+            // Waiting for input is done with poll
+            termios.c_cc[libc::VMIN] = 0;
+            termios.c_cc[libc::VTIME] = 0;
+            if libc::cfsetispeed(&mut termios, speed) != 0
+                || libc::cfsetospeed(&mut termios, speed) != 0
+                || libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(SerialPort { file })
+    }
+}
+
+#[cfg(not(unix))]
+impl SerialPort {
+    /// Serial devices are only supported on unix platforms
+    pub fn open(_path: &Path, _baud: u32) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Serial devices are only supported on unix platforms",
+        ))
+    }
+}
+
+impl Read for SerialPort {
+    #[cfg(unix)]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut poll_fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Safety: poll_fd outlives the call, which only reads one entry
+        match unsafe { libc::poll(&mut poll_fd, 1, READ_TIMEOUT_MS) } {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "No input from the serial device",
+            )),
+            // After a hang up, the read returns 0 bytes
+            _ => self.file.read(buf),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+/// Termios speed for the given baud rate, if it's supported
+#[cfg(unix)]
+fn baud_rate(baud: u32) -> Option<libc::speed_t> {
+    let speed = match baud {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115_200 => libc::B115200,
+        230_400 => libc::B230400,
+        #[cfg(target_os = "linux")]
+        460_800 => libc::B460800,
+        #[cfg(target_os = "linux")]
+        500_000 => libc::B500000,
+        #[cfg(target_os = "linux")]
+        576_000 => libc::B576000,
+        #[cfg(target_os = "linux")]
+        921_600 => libc::B921600,
+        #[cfg(target_os = "linux")]
+        1_000_000 => libc::B1000000,
+        #[cfg(target_os = "linux")]
+        1_152_000 => libc::B1152000,
+        #[cfg(target_os = "linux")]
+        1_500_000 => libc::B1500000,
+        #[cfg(target_os = "linux")]
+        2_000_000 => libc::B2000000,
+        #[cfg(target_os = "linux")]
+        2_500_000 => libc::B2500000,
+        #[cfg(target_os = "linux")]
+        3_000_000 => libc::B3000000,
+        #[cfg(target_os = "linux")]
+        3_500_000 => libc::B3500000,
+        #[cfg(target_os = "linux")]
+        4_000_000 => libc::B4000000,
+        _ => return None,
+    };
+    Some(speed)
+}
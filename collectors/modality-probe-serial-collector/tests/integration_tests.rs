@@ -0,0 +1,137 @@
+#![deny(warnings)]
+#![cfg(target_os = "linux")]
+
+use modality_probe::*;
+use modality_probe_collector_common::{framing::Framing, json, metrics::ReportTracker, SessionId};
+use modality_probe_serial_collector::{run, SerialPort};
+use std::convert::TryInto;
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::Write;
+use std::mem::MaybeUninit;
+use std::os::unix::io::FromRawFd;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const STORAGE_SIZE: usize = 512;
+
+/// Open a pseudo-terminal, returning its master side and the path of its
+/// slave device
+fn open_pty() -> (File, PathBuf) {
+    unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0);
+        assert_eq!(libc::grantpt(master), 0);
+        assert_eq!(libc::unlockpt(master), 0);
+        let mut name = [0 as libc::c_char; 128];
+        assert_eq!(libc::ptsname_r(master, name.as_mut_ptr(), name.len()), 0);
+        let path = CStr::from_ptr(name.as_ptr()).to_str().unwrap().into();
+        (File::from_raw_fd(master), path)
+    }
+}
+
+fn report(probe: &mut ModalityProbe<'_>) -> Vec<u8> {
+    for i in 0..STORAGE_SIZE / 32 {
+        probe.record_event(1.try_into().unwrap());
+        probe.record_event_with_payload(2.try_into().unwrap(), i as _);
+    }
+    let mut buffer = vec![0_u8; 2 * STORAGE_SIZE];
+    let len = probe.report(&mut buffer).unwrap().unwrap().get();
+    buffer.truncate(len);
+    buffer
+}
+
+fn slip_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0xC0];
+    for &b in data {
+        match b {
+            0xC0 => out.extend_from_slice(&[0xDB, 0xDC]),
+            0xDB => out.extend_from_slice(&[0xDB, 0xDD]),
+            _ => out.push(b),
+        }
+    }
+    out.push(0xC0);
+    out
+}
+
+#[test]
+fn collect_through_pseudo_terminal() {
+    let probe_id = 1.try_into().unwrap();
+    let mut storage = vec![MaybeUninit::new(0_u8); STORAGE_SIZE];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )
+    .unwrap();
+
+    let (mut master, slave_path) = open_pty();
+    let port = SerialPort::open(&slave_path, 115_200).unwrap();
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let collector_shutdown = shutdown.clone();
+    let collector = thread::spawn(move || {
+        let mut out = Vec::new();
+        let metrics = run(
+            port,
+            Framing::Slip,
+            SessionId(0),
+            ReportTracker::default(),
+            collector_shutdown,
+            &mut out,
+        )
+        .unwrap();
+        (metrics, out)
+    });
+
+    // Reports are interleaved with console output, and one never arrives
+    master.write_all(b"booting...\r\n").unwrap();
+    for seq_num in 0..4 {
+        let report = report(probe);
+        if seq_num != 1 {
+            master.write_all(&slip_encode(&report)).unwrap();
+        }
+        master.write_all(b"tick\r\n").unwrap();
+    }
+    master.flush().unwrap();
+    shutdown.store(true, Ordering::SeqCst);
+    let (metrics, out) = collector.join().unwrap();
+
+    let m = metrics.probe_report_metrics.get(&probe_id).unwrap();
+    assert_eq!(m.num_reports, 3);
+    assert_eq!(m.missed_seq_nums, 1);
+    assert_eq!(m.last_seq_num, 3.into());
+    assert_eq!(metrics.reports_discarded, 0);
+    let entries = json::read_log_entries(&mut &out[..]).unwrap();
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|e| e.probe_id == probe_id));
+}
+
+#[test]
+fn stop_when_device_hangs_up() {
+    let (master, slave_path) = open_pty();
+    let port = SerialPort::open(&slave_path, 9600).unwrap();
+    let collector = thread::spawn(move || {
+        run(
+            port,
+            Framing::Raw,
+            SessionId(0),
+            ReportTracker::default(),
+            Arc::new(AtomicBool::new(false)),
+            &mut Vec::new(),
+        )
+        .unwrap()
+    });
+    drop(master);
+    let metrics = collector.join().unwrap();
+    assert!(metrics.probe_report_metrics.is_empty());
+}
+
+#[test]
+fn unsupported_baud_rate() {
+    let (_master, slave_path) = open_pty();
+    assert!(SerialPort::open(&slave_path, 1234).is_err());
+}
@@ -0,0 +1,681 @@
+use std::convert::TryFrom;
+use std::{
+    collections::BTreeSet,
+    io::{Error as IoError, ErrorKind, Read, Write},
+    mem,
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use modality_probe::wire::ReportWireError;
+use modality_probe_collector_common::{
+    metrics::{write_reports, ReceivedReport, ReportMetrics, ReportTracker, TrackerOpts},
+    reassembly,
+    rotation::{RotatingWriter, RotationPolicy},
+    Report, ReportLogEntry, SerializationError, SessionId,
+};
+
+mod opts;
+
+pub use opts::*;
+
+/// Size of the little endian length that precedes each report, or
+/// report fragment, on a connection
+pub const FRAME_HEADER_LEN: usize = 4;
+
+/// Largest frame accepted before the connection is considered garbled
+/// and closed
+pub const MAX_FRAME_LEN: usize = 1024 * 1024;
+
+/// How often blocked threads check for a shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub addr: SocketAddr,
+    pub session_id: SessionId,
+    pub output_file: PathBuf,
+    /// How to sequence the reports received and summarize the metrics
+    pub tracking: TrackerOpts,
+    /// When to rotate the output file, and how to compress its finished
+    /// segments
+    pub rotation: RotationPolicy,
+}
+
+pub type ShutdownSignalReceiver = mpsc::Receiver<()>;
+
+pub fn start_receiving(
+    config: Config,
+    shutdown_signal_receiver: ShutdownSignalReceiver,
+) -> Result<ReportMetrics, IoError> {
+    let mut file = RotatingWriter::open(config.output_file, config.rotation)?;
+    let tracker = config.tracking.tracker();
+    start_receiving_at_addr(
+        config.addr,
+        config.session_id,
+        tracker,
+        &mut file,
+        shutdown_signal_receiver,
+    )
+}
+
+pub fn start_receiving_at_addr<W: Write>(
+    addr: SocketAddr,
+    session_id: SessionId,
+    tracker: ReportTracker,
+    log_output_writer: &mut W,
+    shutdown_signal_receiver: ShutdownSignalReceiver,
+) -> Result<ReportMetrics, IoError> {
+    start_receiving_from_listener(
+        TcpListener::bind(addr)?,
+        session_id,
+        tracker,
+        log_output_writer,
+        shutdown_signal_receiver,
+    )
+}
+
+/// What a connection thread hands over to the thread writing log entries
+enum Received {
+    Report(Report, DateTime<Utc>),
+    /// Counters gathered by a connection since it last handed any over
+    Metrics(ReportMetrics),
+}
+
+/// Accept connections on the given listener until a shutdown signal is
+/// received, then print a summary of the metrics and return them. Each
+/// connection is read on its own thread, while the reports of every
+/// connection are sequenced and written from the calling thread.
+pub fn start_receiving_from_listener<W: Write>(
+    listener: TcpListener,
+    session_id: SessionId,
+    mut tracker: ReportTracker,
+    log_output_writer: &mut W,
+    shutdown_signal_receiver: ShutdownSignalReceiver,
+) -> Result<ReportMetrics, IoError> {
+    listener.set_nonblocking(true)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let (received_sender, received_receiver) = mpsc::channel::<Received>();
+    let mut connections: Vec<JoinHandle<()>> = Vec::new();
+    let mut writer = EntryWriter {
+        session_id,
+        log_entries_buffer: Vec::with_capacity(4096),
+        log_output_writer,
+    };
+    loop {
+        if shutdown_signal_receiver.try_recv().is_ok() {
+            break;
+        }
+        loop {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let received_sender = received_sender.clone();
+                    let stop = stop.clone();
+                    connections.push(thread::spawn(move || {
+                        receive_from_connection(stream, peer, received_sender, &stop)
+                    }));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("Error accepting a connection : {}", e);
+                    break;
+                }
+            }
+        }
+        connections.retain(|c| !c.is_finished());
+
+        match received_receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(received) => writer.handle(received, &mut tracker),
+            Err(mpsc::RecvTimeoutError::Timeout) => (),
+            Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!("A sender is held above"),
+        }
+        for received in received_receiver.try_iter() {
+            writer.handle(received, &mut tracker);
+        }
+        writer.write_reports(&tracker.release_overdue());
+        if tracker.summary_due() {
+            println!("{}", tracker.metrics);
+        }
+    }
+
+    stop.store(true, Ordering::SeqCst);
+    drop(received_sender);
+    for connection in connections {
+        let _ = connection.join();
+    }
+    for received in received_receiver.try_iter() {
+        writer.handle(received, &mut tracker);
+    }
+    writer.write_reports(&tracker.finish());
+    println!("{}", tracker.metrics);
+    Ok(tracker.metrics)
+}
+
+/// Writes the log entries of the reports released by the tracker
+struct EntryWriter<'a, W: Write> {
+    session_id: SessionId,
+    log_entries_buffer: Vec<ReportLogEntry>,
+    log_output_writer: &'a mut W,
+}
+
+impl<'a, W: Write> EntryWriter<'a, W> {
+    fn handle(&mut self, received: Received, tracker: &mut ReportTracker) {
+        match received {
+            Received::Report(report, receive_time) => {
+                let released = tracker.push(report, receive_time);
+                self.write_reports(&released);
+            }
+            Received::Metrics(metrics) => tracker.metrics.add_counters(&metrics),
+        }
+    }
+
+    fn write_reports(&mut self, released: &[ReceivedReport]) {
+        let _ = write_reports(
+            released,
+            self.session_id,
+            &mut self.log_entries_buffer,
+            self.log_output_writer,
+        );
+    }
+}
+
+/// Read length-prefixed reports from a single connection until the peer
+/// disconnects, the stream becomes garbled, or the collector shuts down
+fn receive_from_connection(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    received_sender: mpsc::Sender<Received>,
+    stop: &AtomicBool,
+) {
+    // Accepted streams may inherit the listener's non-blocking mode
+    if let Err(e) = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(POLL_INTERVAL)))
+    {
+        eprintln!("Error configuring the connection from {} : {}", peer, e);
+        return;
+    }
+    println!("Accepted a connection from {}", peer);
+
+    let mut connection = Connection::new(peer, received_sender);
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut pending: Vec<u8> = Vec::new();
+    while !stop.load(Ordering::SeqCst) {
+        let bytes_read = match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e)
+                if e.kind() == ErrorKind::WouldBlock
+                    || e.kind() == ErrorKind::TimedOut
+                    || e.kind() == ErrorKind::Interrupted =>
+            {
+                continue
+            }
+            Err(e) => {
+                eprintln!("Error reading from {} : {}", peer, e);
+                break;
+            }
+        };
+        pending.extend_from_slice(&buf[..bytes_read]);
+        connection.metrics.bytes_accumulated = connection
+            .metrics
+            .bytes_accumulated
+            .saturating_add(bytes_read as u64);
+
+        let mut consumed = 0;
+        while let Some(frame) = next_frame(&pending[consumed..]) {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(frame_len) => {
+                    eprintln!(
+                        "Frame of {} bytes from {} exceeds the limit of {} bytes, closing the connection",
+                        frame_len, peer, MAX_FRAME_LEN
+                    );
+                    connection.finish(pending.len() - consumed);
+                    return;
+                }
+            };
+            consumed += FRAME_HEADER_LEN + frame.len();
+            connection.handle_frame(frame);
+        }
+        pending.drain(..consumed);
+        connection.send_metrics();
+    }
+    connection.finish(pending.len());
+}
+
+/// The next complete frame at the start of `bytes`, `None` if it hasn't
+/// been fully received yet, or the declared length if it's too long
+fn next_frame(bytes: &[u8]) -> Option<Result<&[u8], usize>> {
+    if bytes.len() < FRAME_HEADER_LEN {
+        return None;
+    }
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    header.copy_from_slice(&bytes[..FRAME_HEADER_LEN]);
+    let frame_len = u32::from_le_bytes(header) as usize;
+    if frame_len > MAX_FRAME_LEN {
+        return Some(Err(frame_len));
+    }
+    bytes[FRAME_HEADER_LEN..].get(..frame_len).map(Ok)
+}
+
+/// Per-connection state, kept to turn frames into reports and to
+/// attribute what was received to the peer for diagnostics
+struct Connection {
+    peer: SocketAddr,
+    received_sender: mpsc::Sender<Received>,
+    reassembler: reassembly::Reassembler,
+    /// Counters not yet handed over to the writing thread
+    metrics: ReportMetrics,
+    reports: u64,
+    probe_ids: BTreeSet<u32>,
+}
+
+impl Connection {
+    fn new(peer: SocketAddr, received_sender: mpsc::Sender<Received>) -> Self {
+        Connection {
+            peer,
+            received_sender,
+            reassembler: reassembly::Reassembler::new(),
+            metrics: ReportMetrics::default(),
+            reports: 0,
+            probe_ids: BTreeSet::new(),
+        }
+    }
+
+    fn handle_frame(&mut self, frame: &[u8]) {
+        let receive_time = Utc::now();
+        let reassembled;
+        let report_bytes = if reassembly::is_fragment(frame) {
+            self.metrics.fragments_received = self.metrics.fragments_received.saturating_add(1);
+            let fragment = self.reassembler.push(frame);
+            let incomplete = self.reassembler.take_incomplete();
+            self.report_incomplete(incomplete);
+            match fragment {
+                Ok(Some(bytes)) => {
+                    reassembled = bytes;
+                    &reassembled[..]
+                }
+                Ok(None) => return,
+                Err(e) => {
+                    eprintln!(
+                        "Error parsing a frame from {} as a report fragment ({}), throwing away {} bytes",
+                        self.peer,
+                        e,
+                        frame.len()
+                    );
+                    self.metrics.bytes_discarded = self
+                        .metrics
+                        .bytes_discarded
+                        .saturating_add(frame.len() as u64);
+                    return;
+                }
+            }
+        } else {
+            frame
+        };
+
+        match Report::try_from(report_bytes) {
+            Ok(log_report) => {
+                self.metrics.flag_parsed(report_bytes);
+                self.reports += 1;
+                self.probe_ids.insert(log_report.probe_id.get_raw());
+                // The receiver outlives every connection thread
+                let _ = self
+                    .received_sender
+                    .send(Received::Report(log_report, receive_time));
+            }
+            Err(e) => {
+                if let SerializationError::ReportWireError(ReportWireError::ChecksumMismatch) = e {
+                    eprintln!(
+                        "Report from {} failed its checksum, throwing away {} bytes",
+                        self.peer,
+                        report_bytes.len()
+                    );
+                } else {
+                    eprintln!(
+                        "Error parsing a frame from {} as a report, throwing away {} bytes",
+                        self.peer,
+                        report_bytes.len()
+                    );
+                }
+                self.metrics.flag_discarded(report_bytes.len(), &e);
+            }
+        }
+    }
+
+    fn report_incomplete(&mut self, incomplete: Vec<reassembly::IncompleteReport>) {
+        for incomplete in incomplete {
+            eprintln!(
+                "Report {} from probe {} via {} is incomplete, received {} of {} fragments",
+                incomplete.seq_num,
+                incomplete.probe_id.get_raw(),
+                self.peer,
+                incomplete.received_fragments,
+                incomplete.expected_fragments
+            );
+            self.metrics.flag_incomplete();
+        }
+    }
+
+    /// Hand the counters gathered so far over to the writing thread
+    fn send_metrics(&mut self) {
+        let metrics = mem::take(&mut self.metrics);
+        // The receiver outlives every connection thread
+        let _ = self.received_sender.send(Received::Metrics(metrics));
+    }
+
+    /// Report what's left over once the connection is closed, given the
+    /// number of bytes received of a frame that never completed
+    fn finish(mut self, partial_frame_bytes: usize) {
+        if partial_frame_bytes != 0 {
+            eprintln!(
+                "Connection from {} closed in the middle of a frame, throwing away {} bytes",
+                self.peer, partial_frame_bytes
+            );
+            self.metrics.bytes_discarded = self
+                .metrics
+                .bytes_discarded
+                .saturating_add(partial_frame_bytes as u64);
+        }
+        let incomplete = self.reassembler.finish();
+        self.report_incomplete(incomplete);
+        self.send_metrics();
+        let probe_ids: Vec<String> = self.probe_ids.iter().map(|id| id.to_string()).collect();
+        println!(
+            "Connection from {} closed after {} reports from probes [{}]",
+            self.peer,
+            self.reports,
+            probe_ids.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, mem::MaybeUninit, net::Shutdown, sync::Mutex, time::Instant};
+
+    use pretty_assertions::assert_eq;
+
+    use modality_probe::time::{NanosecondResolution, WallClockId};
+    use modality_probe::*;
+    use modality_probe_collector_common::{self as common, json, metrics::DEFAULT_REORDER_WINDOW};
+
+    use super::*;
+
+    const PROBE_STORAGE_BYTES_SIZE: usize = 544;
+    const LOG_REPORT_BYTES_SIZE: usize = 512;
+
+    /// How long to wait for the collector to write the expected entries
+    const COLLECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// An output which the test can read while the collector writes to it
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        /// The log entries written so far, leaving out a partly written line
+        fn entries(&self) -> Vec<ReportLogEntry> {
+            let output = self.0.lock().unwrap();
+            let complete = output
+                .iter()
+                .rposition(|b| *b == b'\n')
+                .map_or(0, |i| i + 1);
+            json::read_log_entries(&mut &output[..complete])
+                .expect("Could not read output as json log entries")
+        }
+    }
+
+    /// Run the collector on an OS-picked local port until `clients` returns
+    /// and it has written `expected_len` log entries, or a timeout passes,
+    /// giving back the log entries it wrote
+    fn collect<F: FnOnce(SocketAddr)>(
+        session_id: SessionId,
+        expected_len: usize,
+        clients: F,
+    ) -> Vec<ReportLogEntry> {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = mpsc::channel();
+        let output = SharedOutput::default();
+        let mut server_output = output.clone();
+        let h = thread::spawn(move || {
+            start_receiving_from_listener(
+                listener,
+                session_id,
+                ReportTracker::new(DEFAULT_REORDER_WINDOW),
+                &mut server_output,
+                shutdown_receiver,
+            )
+            .expect("Could not receive from listener");
+        });
+        clients(addr);
+        let deadline = Instant::now() + COLLECT_TIMEOUT;
+        while output.entries().len() < expected_len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        shutdown_sender.send(()).unwrap();
+        h.join().expect("Couldn't join server thread");
+        output.entries()
+    }
+
+    /// Close the sending side of a connection, and wait for the collector
+    /// to close it in turn once it has read everything sent
+    fn close_and_wait(mut stream: TcpStream) {
+        stream.shutdown(Shutdown::Write).unwrap();
+        stream.set_read_timeout(Some(COLLECT_TIMEOUT)).unwrap();
+        let mut buf = [0u8; 1];
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    fn probe_reports(raw_probe_id: u32, count: u32) -> Vec<Vec<u8>> {
+        let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(raw_probe_id).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+        (1..=count)
+            .map(|i| {
+                probe.record_event(EventId::new(i).unwrap());
+                let n = probe.report(&mut report).unwrap().unwrap();
+                report[..n.get()].to_vec()
+            })
+            .collect()
+    }
+
+    fn frame(bytes: &[u8]) -> Vec<u8> {
+        let mut framed = (bytes.len() as u32).to_le_bytes().to_vec();
+        framed.extend_from_slice(bytes);
+        framed
+    }
+
+    fn entries_of(reports: &[Vec<u8>], session_id: SessionId) -> Vec<ReportLogEntry> {
+        let mut entries = Vec::new();
+        for r in reports {
+            let report = Report::try_from(&r[..]).unwrap();
+            common::add_log_report_to_entries(&report, session_id, Utc::now(), &mut entries)
+                .unwrap();
+        }
+        entries
+    }
+
+    fn assert_same_entries(expected: &[ReportLogEntry], found: &[ReportLogEntry]) {
+        assert_eq!(expected.len(), found.len());
+        for (expected, found) in expected.iter().zip(found.iter()) {
+            assert_eq!(expected.session_id, found.session_id);
+            assert_eq!(expected.probe_id, found.probe_id);
+            assert_eq!(expected.sequence_number, found.sequence_number);
+            assert_eq!(expected.sequence_index, found.sequence_index);
+            assert_eq!(expected.data, found.data);
+        }
+    }
+
+    #[test]
+    fn concurrent_connections() {
+        let session_id = 7.into();
+        let reports: Vec<Vec<Vec<u8>>> = (1..=3).map(|id| probe_reports(id, 3)).collect();
+        let to_send = reports.clone();
+        let expected_len = reports
+            .iter()
+            .map(|r| entries_of(r, session_id).len())
+            .sum();
+        let found = collect(session_id, expected_len, move |addr| {
+            let clients: Vec<_> = to_send
+                .into_iter()
+                .map(|probe_reports| {
+                    thread::spawn(move || {
+                        let mut stream = TcpStream::connect(addr).unwrap();
+                        let bytes: Vec<u8> = probe_reports.iter().flat_map(|r| frame(r)).collect();
+                        // Frames are split up across writes
+                        for chunk in bytes.chunks(7) {
+                            stream.write_all(chunk).unwrap();
+                            stream.flush().unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for c in clients {
+                c.join().unwrap();
+            }
+        });
+
+        let found_probes: HashSet<_> = found.iter().map(|e| e.probe_id.get_raw()).collect();
+        assert_eq!(found_probes, (1..=3).collect());
+        for (raw_probe_id, probe_reports) in (1..=3).zip(reports.iter()) {
+            let found_for_probe: Vec<_> = found
+                .iter()
+                .filter(|e| e.probe_id.get_raw() == raw_probe_id)
+                .cloned()
+                .collect();
+            assert_same_entries(&entries_of(probe_reports, session_id), &found_for_probe);
+        }
+    }
+
+    #[test]
+    fn half_written_frame_on_disconnect() {
+        let session_id = 8.into();
+        let reports = probe_reports(1, 2);
+        let first = reports[0].clone();
+        let second = reports[1].clone();
+        let expected = entries_of(&reports, session_id);
+        let found = collect(session_id, expected.len(), move |addr| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(&frame(&first)).unwrap();
+            let partial = frame(&second);
+            stream.write_all(&partial[..partial.len() / 2]).unwrap();
+            close_and_wait(stream);
+
+            // The collector keeps accepting new connections
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(&frame(&second)).unwrap();
+        });
+        assert_same_entries(&expected, &found);
+    }
+
+    #[test]
+    fn reports_resent_on_reconnect_are_written_once() {
+        let session_id = 10.into();
+        let reports = probe_reports(1, 3);
+        let to_send = reports.clone();
+        let expected = entries_of(&reports, session_id);
+        let found = collect(session_id, expected.len(), move |addr| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(&frame(&to_send[0])).unwrap();
+            stream.write_all(&frame(&to_send[2])).unwrap();
+            close_and_wait(stream);
+
+            // A probe which reconnects sends everything it isn't sure
+            // was received again
+            let mut stream = TcpStream::connect(addr).unwrap();
+            for r in to_send.iter() {
+                stream.write_all(&frame(r)).unwrap();
+            }
+        });
+        assert_same_entries(&expected, &found);
+    }
+
+    #[test]
+    fn fragmented_reports_over_a_connection() {
+        let session_id = 9.into();
+        let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        for i in 1..=20 {
+            probe.record_event(EventId::new(i).unwrap());
+        }
+        let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+        let mut fragment = [0u8; 64];
+        let mut fragments = Vec::new();
+        probe
+            .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))
+            .unwrap()
+            .unwrap();
+        assert!(fragments.len() > 1);
+        let report_bytes: Vec<u8> = fragments
+            .iter()
+            .flat_map(|f| wire::WireFragment::new(&f[..]).unwrap().payload().to_vec())
+            .collect();
+
+        let expected = entries_of(&[report_bytes], session_id);
+        let to_send = fragments.clone();
+        let found = collect(session_id, expected.len(), move |addr| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            for f in to_send.iter() {
+                stream.write_all(&frame(f)).unwrap();
+            }
+        });
+        assert_same_entries(&expected, &found);
+    }
+
+    #[test]
+    fn oversized_frame_closes_connection() {
+        let session_id = 10.into();
+        let reports = probe_reports(1, 1);
+        let report = reports[0].clone();
+        let expected = entries_of(&reports, session_id);
+        let found = collect(session_id, expected.len(), move |addr| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(&((MAX_FRAME_LEN + 1) as u32).to_le_bytes())
+                .unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut buf = [0u8; 1];
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(&frame(&report)).unwrap();
+        });
+        assert_same_entries(&expected, &found);
+    }
+}
@@ -0,0 +1,36 @@
+use modality_probe_collector_common::rotation::Compression;
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+fn main() {
+    #[cfg(not(feature = "cli"))]
+    let opts = modality_probe_tcp_collector::Opts::default();
+    #[cfg(feature = "cli")]
+    let opts = modality_probe_tcp_collector::Opts::from_args();
+
+    let config: modality_probe_tcp_collector::Config = opts.into();
+    println!("Using the configuration:");
+    println!("    addr:\t\t{}", config.addr);
+    println!("    session id:\t\t{}", config.session_id.0);
+    println!("    output file:\t{}", config.output_file.display());
+    println!("    reorder window:\t{}", config.tracking.reorder_window());
+    if let Some(interval) = config.tracking.metrics_interval() {
+        println!("    metrics interval:\t{}s", interval.as_secs());
+    }
+    if let Some(size) = config.rotation.max_bytes {
+        println!("    rotate size:\t{} bytes", size);
+    }
+    if let Some(interval) = config.rotation.max_age {
+        println!("    rotate interval:\t{}s", interval.as_secs());
+    }
+    if config.rotation.compression != Compression::None {
+        println!("    compress:\t\t{}", config.rotation.compression);
+    }
+    let (shutdown_sender, shutdown_receiver) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = shutdown_sender.send(());
+    })
+    .expect("Could not set the Ctrl-C handler");
+    modality_probe_tcp_collector::start_receiving(config, shutdown_receiver)
+        .expect("Could not set up TCP listener");
+}
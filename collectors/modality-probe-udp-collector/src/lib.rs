@@ -1,23 +1,41 @@
-use std::convert::TryFrom;
 use std::{
-    io::{Error as IoError, Write},
+    io::{Error as IoError, ErrorKind, Write},
     net::{SocketAddr, UdpSocket},
     path::PathBuf,
+    time::Duration,
 };
 
 use chrono::Utc;
 
-use modality_probe_collector_common::{self as common, json, Report, ReportLogEntry, SessionId};
+use modality_probe::{wire::WireReportAck, ProbeId};
+use modality_probe_collector_common::{
+    datagram::{finish_datagrams, handle_datagram},
+    metrics::{write_reports, ReportMetrics, ReportTracker, TrackerOpts},
+    reassembly,
+    rotation::{RotatingWriter, RotationPolicy},
+    ReportLogEntry, SessionId,
+};
 
 mod opts;
 
 pub use opts::*;
 
+/// How often the receive loop releases reports held back for reordering
+/// and checks whether a metrics summary is due
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug, PartialEq)]
 pub struct Config {
     pub addr: SocketAddr,
     pub session_id: SessionId,
     pub output_file: PathBuf,
+    /// Whether to acknowledge each report back to the address it came from
+    pub acknowledge_reports: bool,
+    /// How to sequence the reports received and summarize the metrics
+    pub tracking: TrackerOpts,
+    /// When to rotate the output file, and how to compress its finished
+    /// segments
+    pub rotation: RotationPolicy,
 }
 
 pub struct ShutdownSignalSender {
@@ -57,14 +75,14 @@ impl ShutdownSignalSender {
 pub fn start_receiving(
     config: Config,
     shutdown_signal_receiver: ShutdownSignalReceiver,
-) -> Result<(), IoError> {
-    let mut file = std::fs::OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(config.output_file)?;
+) -> Result<ReportMetrics, IoError> {
+    let mut file = RotatingWriter::open(config.output_file, config.rotation)?;
+    let tracker = config.tracking.tracker();
     start_receiving_at_addr(
         config.addr,
         config.session_id,
+        config.acknowledge_reports,
+        tracker,
         &mut file,
         shutdown_signal_receiver,
     )
@@ -73,38 +91,62 @@ pub fn start_receiving(
 pub fn start_receiving_at_addr<W: Write>(
     addr: SocketAddr,
     session_id: SessionId,
+    acknowledge_reports: bool,
+    tracker: ReportTracker,
     log_output_writer: &mut W,
     shutdown_signal_receiver: ShutdownSignalReceiver,
-) -> Result<(), IoError> {
-    start_receiving_from_socket(
+) -> Result<ReportMetrics, IoError> {
+    Ok(start_receiving_from_socket(
         UdpSocket::bind(addr)?,
         session_id,
+        acknowledge_reports,
+        tracker,
         log_output_writer,
         shutdown_signal_receiver,
-    );
-    Ok(())
+    ))
 }
 
+/// Receive reports on the socket until a shutdown signal is received,
+/// then print a summary of the metrics and return them
 pub fn start_receiving_from_socket<W: Write>(
     socket: UdpSocket,
     session_id: SessionId,
+    acknowledge_reports: bool,
+    mut tracker: ReportTracker,
     log_output_writer: &mut W,
     shutdown_signal_receiver: ShutdownSignalReceiver,
-) {
+) -> ReportMetrics {
     let addr = socket.local_addr().map(|a| a.to_string());
+    if let Err(e) = socket.set_read_timeout(Some(POLL_INTERVAL)) {
+        eprintln!("Error setting the socket's read timeout : {}", e);
+    }
     let mut buf = vec![0u8; 1024 * 1024];
     let mut log_entries_buffer: Vec<ReportLogEntry> = Vec::with_capacity(4096);
+    let mut reassembler = reassembly::Reassembler::new();
     loop {
         if shutdown_signal_receiver.try_recv().is_ok() {
-            return;
+            break;
+        }
+        let released = tracker.release_overdue();
+        let _ = write_reports(
+            &released,
+            session_id,
+            &mut log_entries_buffer,
+            log_output_writer,
+        );
+        if tracker.summary_due() {
+            println!("{}", tracker.metrics);
         }
         // Be sure to zero out the first few bytes to ensure that the
         // magic fingerprint words are not stale.
         for b in buf[..8].iter_mut() {
             *b = 0;
         }
-        let (bytes_read, _src) = match socket.recv_from(&mut buf) {
+        let (bytes_read, src) = match socket.recv_from(&mut buf) {
             Ok(r) => r,
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                continue
+            }
             Err(e) => {
                 match addr.as_ref() {
                     Ok(a) => eprintln!("Error during recv_from on {} : {}", a, e),
@@ -127,36 +169,45 @@ pub fn start_receiving_from_socket<W: Write>(
         // N.B. To avoid copies and allocation, skip materializing a log report
         // and instead directly create log entries. Probably wise to wait until the
         // log format settles down some before doing this.
-        log_entries_buffer.clear();
-
-        match Report::try_from(&buf[..bytes_read]) {
-            Ok(log_report) => {
-                if let Err(e) = common::add_log_report_to_entries(
-                    &log_report,
-                    session_id,
-                    receive_time,
-                    &mut log_entries_buffer,
-                ) {
-                    eprintln!(
-                        "Encountered a malformed report, not adding it to the trace: {}",
-                        e
-                    )
-                }
-            }
-            Err(_) => {
-                eprintln!(
-                    "Error parsing a message as a report, throwing away {} bytes",
-                    bytes_read
-                );
 
-                continue;
-            }
+        let handled = handle_datagram(
+            &buf[..bytes_read],
+            receive_time,
+            &mut reassembler,
+            &mut tracker,
+        );
+        if let (true, Some((probe_id, seq_num))) = (acknowledge_reports, handled.report) {
+            // Unacknowledged reports are retransmitted as is, so a
+            // retransmission is dropped as a duplicate whose
+            // acknowledgement got lost, but is acknowledged again
+            send_report_ack(&socket, src, probe_id, seq_num.0);
         }
+        let _ = write_reports(
+            &handled.released,
+            session_id,
+            &mut log_entries_buffer,
+            log_output_writer,
+        );
+    }
 
-        if let Err(e) = json::write_log_entries(log_output_writer, &log_entries_buffer) {
-            eprintln!("Error writing log entries: {}", e);
-        }
-        let _ = log_output_writer.flush();
+    let released = finish_datagrams(&mut reassembler, &mut tracker);
+    let _ = write_reports(
+        &released,
+        session_id,
+        &mut log_entries_buffer,
+        log_output_writer,
+    );
+    println!("{}", tracker.metrics);
+    tracker.metrics
+}
+
+fn send_report_ack(socket: &UdpSocket, dest: SocketAddr, probe_id: ProbeId, seq_num: u64) {
+    let mut ack = WireReportAck::new_unchecked([0u8; 16]);
+    ack.set_fingerprint();
+    ack.set_probe_id(probe_id);
+    ack.set_seq_num(seq_num);
+    if let Err(e) = socket.send_to(ack.as_ref(), dest) {
+        eprintln!("Error acknowledging report to {} : {}", dest, e);
     }
 }
 
@@ -164,7 +215,7 @@ pub fn start_receiving_from_socket<W: Write>(
 mod tests {
     use std::{
         collections::{HashMap, HashSet},
-        convert::TryInto,
+        convert::{TryFrom, TryInto},
         net::{Ipv4Addr, SocketAddrV4, TcpListener},
         sync::{
             atomic::{AtomicU16, AtomicU32, Ordering},
@@ -179,7 +230,7 @@ mod tests {
 
     use modality_probe::time::{NanosecondResolution, WallClockId};
     use modality_probe::*;
-    use modality_probe_collector_common::*;
+    use modality_probe_collector_common::{self as common, metrics::DEFAULT_REORDER_WINDOW, *};
 
     use super::*;
     use std::mem::MaybeUninit;
@@ -374,6 +425,9 @@ mod tests {
             addr: server_addr,
             session_id,
             output_file: output_file_path.clone(),
+            acknowledge_reports: false,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
         };
         let h = std::thread::spawn(move || {
             let mut file = std::fs::OpenOptions::new()
@@ -385,7 +439,14 @@ mod tests {
             server_state_sender
                 .send(ServerState::Started)
                 .expect("Could not send status update");
-            start_receiving_from_socket(socket, config.session_id, &mut file, shutdown_receiver);
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
             let _ = server_state_sender.send(ServerState::Shutdown);
         });
         thread::yield_now();
@@ -442,8 +503,305 @@ mod tests {
         h.join().expect("Couldn't join server handler thread");
     }
 
+    #[test]
+    fn fragmented_round_trip() {
+        let addrs = find_usable_addrs(2);
+        let server_addr = *addrs.first().unwrap();
+        let (shutdown_sender, shutdown_receiver) = ShutdownSignalSender::new(server_addr);
+        let (server_state_sender, server_state_receiver) = crossbeam::unbounded();
+        let session_id = gen_session_id().into();
+        let f = tempfile::NamedTempFile::new().expect("Could not make temp file");
+        let output_file_path = PathBuf::from(f.path());
+        let config = Config {
+            addr: server_addr,
+            session_id,
+            output_file: output_file_path.clone(),
+            acknowledge_reports: false,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
+        };
+        let h = std::thread::spawn(move || {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(config.output_file)
+                .expect("Could not open file for writing");
+            let socket = UdpSocket::bind(config.addr).expect("Could not bind to server socket");
+            server_state_sender
+                .send(ServerState::Started)
+                .expect("Could not send status update");
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
+            let _ = server_state_sender.send(ServerState::Shutdown);
+        });
+        thread::yield_now();
+
+        let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        for i in 1..=20 {
+            probe.record_event(EventId::new(i).unwrap());
+        }
+        let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+        let mut fragment = [0u8; 64];
+        let mut fragments = Vec::new();
+        probe
+            .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))
+            .unwrap()
+            .unwrap();
+        assert!(fragments.len() > 1);
+        let report_bytes: Vec<u8> = fragments
+            .iter()
+            .flat_map(|f| wire::WireFragment::new(&f[..]).unwrap().payload().to_vec())
+            .collect();
+        let log_report = Report::try_from(&report_bytes[..]).unwrap();
+
+        // A report that never completes
+        probe.record_event(EventId::new(1).unwrap());
+        let mut lost_fragments = Vec::new();
+        probe
+            .report_fragmented(&mut report, &mut fragment[..20], |f| {
+                lost_fragments.push(f.to_vec())
+            })
+            .unwrap()
+            .unwrap();
+
+        if let ServerState::Started = server_state_receiver
+            .recv()
+            .expect("Could not get state update")
+        {
+            let client_addr = addrs[1];
+            let socket =
+                UdpSocket::bind(client_addr).expect("Could not bind to socket for sending");
+            for f in lost_fragments.iter().take(1).chain(fragments.iter().rev()) {
+                socket
+                    .send_to(f, server_addr)
+                    .expect("Could not send fragment bytes");
+            }
+            thread::sleep(std::time::Duration::from_millis(200));
+            shutdown_sender.shutdown();
+        } else {
+            panic!("Server did not start up");
+        }
+
+        let ss = server_state_receiver
+            .recv()
+            .expect("Could not get state update");
+        if ss != ServerState::Shutdown {
+            panic!("Expected the server to have shut down");
+        }
+        let mut file_reader =
+            std::fs::File::open(&output_file_path).expect("Could not open output file for reading");
+        let found_log_entries = json::read_log_entries(&mut file_reader)
+            .expect("Could not read output file as json log entries");
+
+        let mut expected_entries = Vec::new();
+        common::add_log_report_to_entries(
+            &log_report,
+            session_id,
+            Utc::now(),
+            &mut expected_entries,
+        )
+        .unwrap();
+        assert_eq!(expected_entries.len(), found_log_entries.len());
+        for (expected, found) in expected_entries.iter().zip(found_log_entries.iter()) {
+            assert_eq!(expected.sequence_index, found.sequence_index);
+            assert_eq!(expected.data, found.data);
+        }
+        h.join().expect("Couldn't join server handler thread");
+    }
+
+    #[test]
+    fn acknowledged_round_trip() {
+        let addrs = find_usable_addrs(2);
+        let server_addr = *addrs.first().unwrap();
+        let (shutdown_sender, shutdown_receiver) = ShutdownSignalSender::new(server_addr);
+        let (server_state_sender, server_state_receiver) = crossbeam::unbounded();
+        let session_id = gen_session_id().into();
+        let f = tempfile::NamedTempFile::new().expect("Could not make temp file");
+        let output_file_path = PathBuf::from(f.path());
+        let config = Config {
+            addr: server_addr,
+            session_id,
+            output_file: output_file_path.clone(),
+            acknowledge_reports: true,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
+        };
+        let h = std::thread::spawn(move || {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(config.output_file)
+                .expect("Could not open file for writing");
+            let socket = UdpSocket::bind(config.addr).expect("Could not bind to server socket");
+            server_state_sender
+                .send(ServerState::Started)
+                .expect("Could not send status update");
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
+            let _ = server_state_sender.send(ServerState::Shutdown);
+        });
+        thread::yield_now();
+
+        let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        probe.set_report_acknowledgements(true);
+        probe.record_event(EventId::new(1).unwrap());
+        let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+        let n_report_bytes = probe.report(&mut report).unwrap().unwrap().get();
+        let log_report = Report::try_from(&report[..n_report_bytes]).unwrap();
+
+        if let ServerState::Started = server_state_receiver
+            .recv()
+            .expect("Could not get state update")
+        {
+            let client_addr = addrs[1];
+            let socket =
+                UdpSocket::bind(client_addr).expect("Could not bind to socket for sending");
+            socket
+                .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                .expect("Could not set read timeout");
+            let mut ack = [0u8; 64];
+
+            // The first acknowledgement is lost, so the report is retransmitted
+            socket
+                .send_to(&report[..n_report_bytes], server_addr)
+                .expect("Could not send report bytes");
+            socket.recv(&mut ack).expect("Could not receive ack");
+            let retransmitted_bytes = probe.report(&mut report).unwrap().unwrap().get();
+            assert_eq!(n_report_bytes, retransmitted_bytes);
+            socket
+                .send_to(&report[..retransmitted_bytes], server_addr)
+                .expect("Could not send report bytes");
+            let ack_len = socket.recv(&mut ack).expect("Could not receive ack");
+            assert_eq!(probe.consume_report_ack(&ack[..ack_len]), Ok(true));
+            assert_eq!(probe.consume_report_ack(&ack[..ack_len]), Ok(false));
+
+            thread::sleep(std::time::Duration::from_millis(200));
+            shutdown_sender.shutdown();
+        } else {
+            panic!("Server did not start up");
+        }
+
+        let ss = server_state_receiver
+            .recv()
+            .expect("Could not get state update");
+        if ss != ServerState::Shutdown {
+            panic!("Expected the server to have shut down");
+        }
+        let mut file_reader =
+            std::fs::File::open(&output_file_path).expect("Could not open output file for reading");
+        let found_log_entries = json::read_log_entries(&mut file_reader)
+            .expect("Could not read output file as json log entries");
+
+        // The retransmission was not written again
+        let mut expected_entries = Vec::new();
+        common::add_log_report_to_entries(
+            &log_report,
+            session_id,
+            Utc::now(),
+            &mut expected_entries,
+        )
+        .unwrap();
+        assert_eq!(expected_entries.len(), found_log_entries.len());
+        for (expected, found) in expected_entries.iter().zip(found_log_entries.iter()) {
+            assert_eq!(expected.sequence_number, found.sequence_number);
+            assert_eq!(expected.data, found.data);
+        }
+        h.join().expect("Couldn't join server handler thread");
+    }
+
+    #[test]
+    fn duplicate_and_reordered_reports() {
+        let addrs = find_usable_addrs(2);
+        let server_addr = *addrs.first().unwrap();
+        let (shutdown_sender, shutdown_receiver) = ShutdownSignalSender::new(server_addr);
+        let session_id = gen_session_id().into();
+        let socket = UdpSocket::bind(server_addr).expect("Could not bind to server socket");
+        let h = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            let metrics = start_receiving_from_socket(
+                socket,
+                session_id,
+                false,
+                ReportTracker::new(DEFAULT_REORDER_WINDOW),
+                &mut output,
+                shutdown_receiver,
+            );
+            (metrics, output)
+        });
+
+        let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+        let probe = ModalityProbe::initialize_at(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let mut reports = Vec::new();
+        for i in 1..=4 {
+            probe.record_event(EventId::new(i).unwrap());
+            let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+            let n = probe.report(&mut report).unwrap().unwrap().get();
+            reports.push(report[..n].to_vec());
+        }
+
+        let client = UdpSocket::bind(addrs[1]).expect("Could not bind to socket for sending");
+        // Reports 1 and 2 swap places, and report 2 is repeated
+        for i in [0, 2, 1, 2, 3].iter() {
+            client
+                .send_to(&reports[*i], server_addr)
+                .expect("Could not send report bytes");
+        }
+        thread::sleep(std::time::Duration::from_millis(200));
+        shutdown_sender.shutdown();
+        let (metrics, output) = h.join().expect("Couldn't join server handler thread");
+
+        let found_log_entries = json::read_log_entries(&mut &output[..])
+            .expect("Could not read output as json log entries");
+        let mut seq_nums: Vec<u64> = found_log_entries
+            .iter()
+            .map(|e| e.sequence_number.0)
+            .collect();
+        seq_nums.dedup();
+        assert_eq!(seq_nums, vec![0, 1, 2, 3]);
+        assert_eq!(metrics.duplicate_reports, 1);
+        assert_eq!(metrics.out_of_order_reports, 1);
+        let probe_metrics = metrics.probe_report_metrics[&ProbeId::new(1).unwrap()];
+        assert_eq!(probe_metrics.num_reports, 4);
+        assert_eq!(probe_metrics.missed_seq_nums, 0);
+    }
+
     const SNAPSHOT_BYTES_SIZE: usize = 12;
-    const PROBE_STORAGE_BYTES_SIZE: usize = 512;
+    const PROBE_STORAGE_BYTES_SIZE: usize = 544;
     const LOG_REPORT_BYTES_SIZE: usize = 512;
 
     #[test]
@@ -459,6 +817,9 @@ mod tests {
             addr: server_addr,
             session_id,
             output_file: output_file_path.clone(),
+            acknowledge_reports: false,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
         };
         let h = thread::spawn(move || {
             let mut file = std::fs::OpenOptions::new()
@@ -470,7 +831,14 @@ mod tests {
             server_state_sender
                 .send(ServerState::Started)
                 .expect("Could not send status update");
-            start_receiving_from_socket(socket, config.session_id, &mut file, shutdown_receiver);
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
             let _ = server_state_sender.send(ServerState::Shutdown);
         });
         thread::yield_now();
@@ -493,13 +861,19 @@ mod tests {
                 probe_a_id,
                 NUM_MESSAGES_FROM_A,
                 server_addr,
-                Some(event_foo),
+                Some(event_foo.clone()),
             ),
         );
         net.add_process(
             "b",
             vec!["c"],
-            make_message_relay_proc("b", probe_b_id, NUM_MESSAGES_FROM_A, None, Some(event_bar)),
+            make_message_relay_proc(
+                "b",
+                probe_b_id,
+                NUM_MESSAGES_FROM_A,
+                None,
+                Some(event_bar.clone()),
+            ),
         );
         net.add_process(
             "c",
@@ -511,7 +885,7 @@ mod tests {
                     n_messages: 3,
                     collector_addr: server_addr,
                 },
-                Some(event_baz),
+                Some(event_baz.clone()),
                 network_done_sender,
             ),
         );
@@ -531,7 +905,7 @@ mod tests {
         let found_log_entries = json::read_log_entries(&mut file_reader)
             .expect("Could not read output file as json log entries");
 
-        assert!(found_log_entries.len() > 0);
+        assert!(!found_log_entries.is_empty());
         let expected_direct_probe_ids: HashSet<_> =
             [probe_a_id, probe_c_id].iter().copied().collect();
         let built_in_event_ids: HashSet<_> =
@@ -562,6 +936,8 @@ mod tests {
                     }
                 }
                 LogEntryData::EventWithPayload(_, _) => (),
+                LogEntryData::EventWithBlob(_, _) => (),
+                LogEntryData::EventRepeated(_, _) => (),
                 LogEntryData::FrontierClock(lc) => {
                     if e.probe_id == probe_a_id {
                         // Process A should only know about itself, since it doesn't receive history from anyone else
@@ -582,6 +958,7 @@ mod tests {
                 LogEntryData::EventWithTime(_, _) => (),
                 LogEntryData::EventWithPayloadWithTime(_, _, _) => (),
                 LogEntryData::TraceClockWithTime(_, _) => (),
+                LogEntryData::ProbeRestarted => panic!("No probe was restarted"),
             }
         }
     }
@@ -599,6 +976,9 @@ mod tests {
             addr: server_addr,
             session_id,
             output_file: output_file_path.clone(),
+            acknowledge_reports: false,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
         };
         let h = thread::spawn(move || {
             let mut file = std::fs::OpenOptions::new()
@@ -610,7 +990,14 @@ mod tests {
             server_state_sender
                 .send(ServerState::Started)
                 .expect("Could not send status update");
-            start_receiving_from_socket(socket, config.session_id, &mut file, shutdown_receiver);
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
             let _ = server_state_sender.send(ServerState::Shutdown);
         });
         thread::yield_now();
@@ -631,7 +1018,7 @@ mod tests {
                 probe_a_id,
                 NUM_MESSAGES_FROM_A,
                 server_addr,
-                Some(event_foo),
+                Some(event_foo.clone()),
             ),
         );
         net.add_process(
@@ -644,7 +1031,7 @@ mod tests {
                     n_messages: 3,
                     collector_addr: server_addr,
                 },
-                Some(event_bar),
+                Some(event_bar.clone()),
                 network_done_sender,
             ),
         );
@@ -664,7 +1051,7 @@ mod tests {
         let found_log_entries = json::read_log_entries(&mut file_reader)
             .expect("Could not read output file as json log entries");
 
-        assert!(found_log_entries.len() > 0);
+        assert!(!found_log_entries.is_empty());
         let expected_probe_ids: HashSet<_> = [probe_a_id, probe_b_id].iter().copied().collect();
         let built_in_event_ids: HashSet<_> = modality_probe::EventId::INTERNAL_EVENTS
             .iter()
@@ -692,6 +1079,8 @@ mod tests {
                     }
                 }
                 LogEntryData::EventWithPayload(_, _) => (),
+                LogEntryData::EventWithBlob(_, _) => (),
+                LogEntryData::EventRepeated(_, _) => (),
                 LogEntryData::FrontierClock(lc) => {
                     if e.probe_id == probe_a_id {
                         // Process A should only know about itself, since it doesn't receive history from anyone else
@@ -712,6 +1101,7 @@ mod tests {
                 LogEntryData::EventWithTime(_, _) => (),
                 LogEntryData::EventWithPayloadWithTime(_, _, _) => (),
                 LogEntryData::TraceClockWithTime(_, _) => (),
+                LogEntryData::ProbeRestarted => panic!("No probe was restarted"),
             }
         }
     }
@@ -729,6 +1119,9 @@ mod tests {
             addr: server_addr,
             session_id,
             output_file: output_file_path.clone(),
+            acknowledge_reports: false,
+            tracking: TrackerOpts::default(),
+            rotation: RotationPolicy::default(),
         };
         let h = thread::spawn(move || {
             let mut file = std::fs::OpenOptions::new()
@@ -740,7 +1133,14 @@ mod tests {
             server_state_sender
                 .send(ServerState::Started)
                 .expect("Could not send status update");
-            start_receiving_from_socket(socket, config.session_id, &mut file, shutdown_receiver);
+            start_receiving_from_socket(
+                socket,
+                config.session_id,
+                config.acknowledge_reports,
+                config.tracking.tracker(),
+                &mut file,
+                shutdown_receiver,
+            );
             let _ = server_state_sender.send(ServerState::Shutdown);
         });
         thread::yield_now();
@@ -766,7 +1166,7 @@ mod tests {
                 probe_a_id,
                 NUM_MESSAGES_FROM_A,
                 server_addr,
-                Some(event_foo),
+                Some(event_foo.clone()),
             ),
         );
         net.add_process(
@@ -779,7 +1179,7 @@ mod tests {
                     n_messages: 3,
                     collector_addr: server_addr,
                 },
-                Some(event_bar),
+                Some(event_bar.clone()),
                 network_done_sender,
             ),
         );
@@ -799,13 +1199,15 @@ mod tests {
         let found_log_entries = json::read_log_entries(&mut file_reader)
             .expect("Could not read output file as json log entries");
 
-        assert!(found_log_entries.len() > 0);
+        assert!(!found_log_entries.is_empty());
         let expected_probe_ids: HashSet<_> = [probe_a_id, probe_b_id].iter().copied().collect();
         for e in found_log_entries {
             assert_eq!(session_id, e.session_id);
             assert!(expected_probe_ids.contains(&e.probe_id));
             match e.data {
                 LogEntryData::Event(_) => (),
+                LogEntryData::EventWithBlob(_, _) => (),
+                LogEntryData::EventRepeated(_, _) => (),
                 LogEntryData::EventWithPayload(event, payload) => {
                     if event == foo_id {
                         assert_eq!(EventLogEntry::EventWithPayload(event, payload), event_foo);
@@ -838,21 +1240,21 @@ mod tests {
                 LogEntryData::EventWithTime(_, _) => (),
                 LogEntryData::EventWithPayloadWithTime(_, _, _) => (),
                 LogEntryData::TraceClockWithTime(_, _) => (),
+                LogEntryData::ProbeRestarted => panic!("No probe was restarted"),
             }
         }
     }
 
+    type ProcSenders = HashMap<String, std::sync::mpsc::Sender<(String, Vec<u8>)>>;
+    type ProcReceiver = std::sync::mpsc::Receiver<(String, Vec<u8>)>;
+
     fn make_message_broadcaster_proc(
         proc_name: &'static str,
         probe_id: modality_probe::ProbeId,
         n_messages: usize,
         collector_addr: SocketAddr,
         per_iteration_event: Option<EventLogEntry>,
-    ) -> impl Fn(
-        HashMap<String, std::sync::mpsc::Sender<(String, Vec<u8>)>>,
-        std::sync::mpsc::Receiver<(String, Vec<u8>)>,
-    ) + Send
-           + 'static {
+    ) -> impl Fn(ProcSenders, ProcReceiver) + Send + 'static {
         move |id_to_sender, _receiver| {
             let mut probe_storage = vec![MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
             let mut probe = modality_probe::ModalityProbe::new_with_storage(
@@ -868,7 +1270,7 @@ mod tests {
                 match per_iteration_event {
                     Some(EventLogEntry::Event(e)) => probe.record_event(e),
                     Some(EventLogEntry::EventWithPayload(e, payload)) => {
-                        probe.record_event_with_payload(e, payload)
+                        probe.record_event_with_wide_payload(e, payload)
                     }
                     _ => (),
                 }
@@ -913,11 +1315,7 @@ mod tests {
         stop_relaying_after_receiving_n_messages: usize,
         send_log_report_every_n_messages: Option<SendLogReportEveryFewMessages>,
         per_iteration_event: Option<EventLogEntry>,
-    ) -> impl Fn(
-        HashMap<String, std::sync::mpsc::Sender<(String, Vec<u8>)>>,
-        std::sync::mpsc::Receiver<(String, Vec<u8>)>,
-    ) + Send
-           + 'static {
+    ) -> impl Fn(ProcSenders, ProcReceiver) + Send + 'static {
         move |id_to_sender, receiver| {
             let mut probe_storage = vec![MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
             let mut probe = modality_probe::ModalityProbe::new_with_storage(
@@ -945,7 +1343,7 @@ mod tests {
                 match per_iteration_event {
                     Some(EventLogEntry::Event(e)) => probe.record_event(e),
                     Some(EventLogEntry::EventWithPayload(e, payload)) => {
-                        probe.record_event_with_payload(e, payload)
+                        probe.record_event_with_wide_payload(e, payload)
                     }
                     _ => (),
                 }
@@ -996,11 +1394,7 @@ mod tests {
         send_log_report_every_n_messages: SendLogReportEveryFewMessages,
         per_iteration_event: Option<EventLogEntry>,
         stopped_sender: crossbeam::Sender<()>,
-    ) -> impl Fn(
-        HashMap<String, std::sync::mpsc::Sender<(String, Vec<u8>)>>,
-        std::sync::mpsc::Receiver<(String, Vec<u8>)>,
-    ) + Send
-           + 'static {
+    ) -> impl Fn(ProcSenders, ProcReceiver) + Send + 'static {
         move |_id_to_sender, receiver| {
             let mut probe_storage = vec![MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
             let mut probe = modality_probe::ModalityProbe::new_with_storage(
@@ -1030,7 +1424,7 @@ mod tests {
                 match per_iteration_event {
                     Some(EventLogEntry::Event(e)) => probe.record_event(e),
                     Some(EventLogEntry::EventWithPayload(e, payload)) => {
-                        probe.record_event_with_payload(e, payload)
+                        probe.record_event_with_wide_payload(e, payload)
                     }
                     _ => (),
                 }
@@ -1,3 +1,4 @@
+use modality_probe_collector_common::rotation::Compression;
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
 
@@ -12,6 +13,20 @@ fn main() {
     println!("    addr:\t\t{}", config.addr);
     println!("    session id:\t\t{}", config.session_id.0);
     println!("    output file:\t{}", config.output_file.display());
+    println!("    ack reports:\t{}", config.acknowledge_reports);
+    println!("    reorder window:\t{}", config.tracking.reorder_window());
+    if let Some(interval) = config.tracking.metrics_interval() {
+        println!("    metrics interval:\t{}s", interval.as_secs());
+    }
+    if let Some(size) = config.rotation.max_bytes {
+        println!("    rotate size:\t{} bytes", size);
+    }
+    if let Some(interval) = config.rotation.max_age {
+        println!("    rotate interval:\t{}s", interval.as_secs());
+    }
+    if config.rotation.compression != Compression::None {
+        println!("    compress:\t\t{}", config.rotation.compression);
+    }
     let (shutdown_sender, shutdown_receiver) =
         modality_probe_udp_collector::ShutdownSignalSender::new(config.addr);
     ctrlc::set_handler(move || {
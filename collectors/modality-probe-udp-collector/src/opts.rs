@@ -1,4 +1,5 @@
 use crate::Config;
+use modality_probe_collector_common::{metrics::TrackerOpts, rotation::RotationOpts};
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::path::PathBuf;
 #[cfg(feature = "cli")]
@@ -30,6 +31,17 @@ pub struct Opts {
         structopt(short = "o", long = "output-file", parse(from_os_str))
     )]
     pub output_file: Option<PathBuf>,
+
+    /// Acknowledge each report back to the address it came from, for
+    /// probes with report acknowledgements enabled.
+    #[cfg_attr(feature = "cli", structopt(long = "ack-reports"))]
+    pub ack_reports: bool,
+
+    #[cfg_attr(feature = "cli", structopt(flatten))]
+    pub tracking: TrackerOpts,
+
+    #[cfg_attr(feature = "cli", structopt(flatten))]
+    pub rotation: RotationOpts,
 }
 
 impl From<Opts> for Config {
@@ -46,6 +58,9 @@ impl From<Opts> for Config {
                     .expect("Could not retrieve current directory")
                     .join(format!("session_{}_log_entries.jsonl", session_id))
             }),
+            acknowledge_reports: o.ack_reports,
+            tracking: o.tracking,
+            rotation: o.rotation.policy(),
         }
     }
 }
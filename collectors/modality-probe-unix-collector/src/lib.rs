@@ -0,0 +1,315 @@
+use std::{io::Error as IoError, path::PathBuf, sync::mpsc};
+
+use modality_probe_collector_common::{
+    metrics::{ReportMetrics, TrackerOpts},
+    rotation::{RotatingWriter, RotationPolicy},
+    SessionId,
+};
+
+mod opts;
+
+pub use opts::*;
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub socket_path: PathBuf,
+    pub session_id: SessionId,
+    pub output_file: PathBuf,
+    /// How to sequence the reports received and summarize the metrics
+    pub tracking: TrackerOpts,
+    /// When to rotate the output file, and how to compress its finished
+    /// segments
+    pub rotation: RotationPolicy,
+}
+
+pub type ShutdownSignalReceiver = mpsc::Receiver<()>;
+
+pub fn start_receiving(
+    config: Config,
+    shutdown_signal_receiver: ShutdownSignalReceiver,
+) -> Result<ReportMetrics, IoError> {
+    let mut file = RotatingWriter::open(config.output_file, config.rotation)?;
+    let tracker = config.tracking.tracker();
+    start_receiving_at_path(
+        &config.socket_path,
+        config.session_id,
+        tracker,
+        &mut file,
+        shutdown_signal_receiver,
+    )
+}
+
+#[cfg(not(unix))]
+pub fn start_receiving_at_path<W: std::io::Write>(
+    _socket_path: &std::path::Path,
+    _session_id: SessionId,
+    _tracker: modality_probe_collector_common::metrics::ReportTracker,
+    _log_output_writer: &mut W,
+    _shutdown_signal_receiver: ShutdownSignalReceiver,
+) -> Result<ReportMetrics, IoError> {
+    Err(IoError::new(
+        std::io::ErrorKind::Other,
+        "Unix domain sockets are only supported on unix platforms",
+    ))
+}
+
+#[cfg(unix)]
+pub use self::unix::*;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        io::{Error as IoError, ErrorKind, Write},
+        os::unix::{fs::FileTypeExt, net::UnixDatagram},
+        path::Path,
+        time::Duration,
+    };
+
+    use chrono::Utc;
+
+    use modality_probe_collector_common::{
+        datagram::{finish_datagrams, handle_datagram},
+        metrics::{write_reports, ReportMetrics, ReportTracker},
+        reassembly, ReportLogEntry, SessionId,
+    };
+
+    use super::ShutdownSignalReceiver;
+
+    /// How often the receive loop checks for a shutdown, releases reports
+    /// held back for reordering and checks whether a summary is due
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Create a socket at the given path and receive from it until a shutdown
+    /// signal is received, then remove the socket. A socket left behind at
+    /// the path by an earlier run is replaced.
+    pub fn start_receiving_at_path<W: Write>(
+        socket_path: &Path,
+        session_id: SessionId,
+        tracker: ReportTracker,
+        log_output_writer: &mut W,
+        shutdown_signal_receiver: ShutdownSignalReceiver,
+    ) -> Result<ReportMetrics, IoError> {
+        if let Ok(metadata) = std::fs::symlink_metadata(socket_path) {
+            if metadata.file_type().is_socket() {
+                std::fs::remove_file(socket_path)?;
+            }
+        }
+        let socket = UnixDatagram::bind(socket_path)?;
+        let result = start_receiving_from_socket(
+            socket,
+            session_id,
+            tracker,
+            log_output_writer,
+            shutdown_signal_receiver,
+        );
+        let _ = std::fs::remove_file(socket_path);
+        result
+    }
+
+    /// Receive reports on the socket until a shutdown signal is received,
+    /// then print a summary of the metrics and return them
+    pub fn start_receiving_from_socket<W: Write>(
+        socket: UnixDatagram,
+        session_id: SessionId,
+        mut tracker: ReportTracker,
+        log_output_writer: &mut W,
+        shutdown_signal_receiver: ShutdownSignalReceiver,
+    ) -> Result<ReportMetrics, IoError> {
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let mut buf = vec![0u8; 1024 * 1024];
+        let mut log_entries_buffer: Vec<ReportLogEntry> = Vec::with_capacity(4096);
+        let mut reassembler = reassembly::Reassembler::new();
+        loop {
+            if shutdown_signal_receiver.try_recv().is_ok() {
+                break;
+            }
+            let released = tracker.release_overdue();
+            let _ = write_reports(
+                &released,
+                session_id,
+                &mut log_entries_buffer,
+                log_output_writer,
+            );
+            if tracker.summary_due() {
+                println!("{}", tracker.metrics);
+            }
+            // Be sure to zero out the first few bytes to ensure that the
+            // magic fingerprint words are not stale.
+            for b in buf[..8].iter_mut() {
+                *b = 0;
+            }
+            let bytes_read = match socket.recv(&mut buf) {
+                Ok(r) => r,
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    continue
+                }
+                Err(e) => {
+                    eprintln!("Error during recv : {}", e);
+                    continue;
+                }
+            };
+            let handled = handle_datagram(
+                &buf[..bytes_read],
+                Utc::now(),
+                &mut reassembler,
+                &mut tracker,
+            );
+            let _ = write_reports(
+                &handled.released,
+                session_id,
+                &mut log_entries_buffer,
+                log_output_writer,
+            );
+        }
+
+        let released = finish_datagrams(&mut reassembler, &mut tracker);
+        let _ = write_reports(
+            &released,
+            session_id,
+            &mut log_entries_buffer,
+            log_output_writer,
+        );
+        println!("{}", tracker.metrics);
+        Ok(tracker.metrics)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{convert::TryFrom, mem::MaybeUninit, sync::mpsc, thread};
+
+        use pretty_assertions::assert_eq;
+
+        use modality_probe::time::{NanosecondResolution, WallClockId};
+        use modality_probe::*;
+        use modality_probe_collector_common::{
+            self as common, json, metrics::DEFAULT_REORDER_WINDOW, Report,
+        };
+
+        use super::*;
+
+        const PROBE_STORAGE_BYTES_SIZE: usize = 544;
+        const LOG_REPORT_BYTES_SIZE: usize = 512;
+
+        /// Run the collector on a socket in a temporary directory until
+        /// `clients` returns, giving back the log entries it wrote
+        fn collect<F: FnOnce(&Path)>(session_id: SessionId, clients: F) -> Vec<ReportLogEntry> {
+            let dir = tempfile::tempdir().expect("Could not make temp dir");
+            let socket_path = dir.path().join("collector.sock");
+            // A socket left behind by an earlier run
+            drop(UnixDatagram::bind(&socket_path).unwrap());
+
+            let (shutdown_sender, shutdown_receiver) = mpsc::channel();
+            let server_path = socket_path.clone();
+            let h = thread::spawn(move || {
+                let mut output = Vec::new();
+                start_receiving_at_path(
+                    &server_path,
+                    session_id,
+                    ReportTracker::new(DEFAULT_REORDER_WINDOW),
+                    &mut output,
+                    shutdown_receiver,
+                )
+                .expect("Could not receive from socket");
+                output
+            });
+            // Wait for the stale socket to be replaced
+            while UnixDatagram::unbound()
+                .unwrap()
+                .connect(&socket_path)
+                .is_err()
+            {
+                thread::sleep(Duration::from_millis(10));
+            }
+            clients(&socket_path);
+            thread::sleep(Duration::from_millis(300));
+            shutdown_sender.send(()).unwrap();
+            let output = h.join().expect("Couldn't join server thread");
+            assert!(!socket_path.exists(), "The socket was not removed");
+            json::read_log_entries(&mut &output[..])
+                .expect("Could not read output as json log entries")
+        }
+
+        fn initialize_probe(storage: &mut [MaybeUninit<u8>]) -> &mut ModalityProbe<'_> {
+            ModalityProbe::initialize_at(
+                storage,
+                ProbeId::new(1).unwrap(),
+                NanosecondResolution::UNSPECIFIED,
+                WallClockId::local_only(),
+                RestartCounterProvider::NoRestartTracking,
+            )
+            .unwrap()
+        }
+
+        fn entries_of(reports: &[&[u8]], session_id: SessionId) -> Vec<ReportLogEntry> {
+            let mut entries = Vec::new();
+            for r in reports {
+                let report = Report::try_from(*r).unwrap();
+                common::add_log_report_to_entries(&report, session_id, Utc::now(), &mut entries)
+                    .unwrap();
+            }
+            entries
+        }
+
+        fn assert_same_entries(expected: &[ReportLogEntry], found: &[ReportLogEntry]) {
+            assert_eq!(expected.len(), found.len());
+            for (expected, found) in expected.iter().zip(found.iter()) {
+                assert_eq!(expected.session_id, found.session_id);
+                assert_eq!(expected.probe_id, found.probe_id);
+                assert_eq!(expected.sequence_number, found.sequence_number);
+                assert_eq!(expected.sequence_index, found.sequence_index);
+                assert_eq!(expected.data, found.data);
+            }
+        }
+
+        #[test]
+        fn round_trip() {
+            let session_id = 11.into();
+            let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+            let probe = initialize_probe(&mut storage);
+            probe.record_event(EventId::new(1).unwrap());
+            let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+            let n = probe.report(&mut report).unwrap().unwrap().get();
+            let report = report[..n].to_vec();
+
+            let to_send = report.clone();
+            let found = collect(session_id, move |path| {
+                let socket = UnixDatagram::unbound().unwrap();
+                socket.send_to(&[1, 2, 3], path).unwrap();
+                socket.send_to(&to_send, path).unwrap();
+            });
+            assert_same_entries(&entries_of(&[&report], session_id), &found);
+        }
+
+        #[test]
+        fn fragmented_round_trip() {
+            let session_id = 12.into();
+            let mut storage = [MaybeUninit::new(0u8); PROBE_STORAGE_BYTES_SIZE];
+            let probe = initialize_probe(&mut storage);
+            for i in 1..=20 {
+                probe.record_event(EventId::new(i).unwrap());
+            }
+            let mut report = [0u8; LOG_REPORT_BYTES_SIZE];
+            let mut fragment = [0u8; 64];
+            let mut fragments = Vec::new();
+            probe
+                .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))
+                .unwrap()
+                .unwrap();
+            assert!(fragments.len() > 1);
+            let report_bytes: Vec<u8> = fragments
+                .iter()
+                .flat_map(|f| wire::WireFragment::new(&f[..]).unwrap().payload().to_vec())
+                .collect();
+
+            let found = collect(session_id, move |path| {
+                let dir = tempfile::tempdir().unwrap();
+                // Senders which bind their socket are named in diagnostics
+                let socket = UnixDatagram::bind(dir.path().join("probe.sock")).unwrap();
+                for f in fragments.iter().rev() {
+                    socket.send_to(f, path).unwrap();
+                }
+            });
+            assert_same_entries(&entries_of(&[&report_bytes], session_id), &found);
+        }
+    }
+}
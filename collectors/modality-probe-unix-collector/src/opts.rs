@@ -0,0 +1,62 @@
+use crate::Config;
+use modality_probe_collector_common::{metrics::TrackerOpts, rotation::RotationOpts};
+use std::path::PathBuf;
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+/// Name of the socket created in the temporary directory when no path is given
+pub const DEFAULT_SOCKET_NAME: &str = "modality-probe-collector.sock";
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+#[cfg_attr(
+    feature = "cli",
+    structopt(
+        name = "modality-probe-unix-collector",
+        about = "Server that receives modality-probe reports via a Unix datagram socket and logs to file"
+    )
+)]
+pub struct Opts {
+    /// The path of the socket this server creates to receive data on.
+    #[cfg_attr(
+        feature = "cli",
+        structopt(short = "p", long = "socket-path", parse(from_os_str))
+    )]
+    pub socket_path: Option<PathBuf>,
+
+    /// The session id to associate with the collected trace data.
+    #[cfg_attr(feature = "cli", structopt(short = "s", long = "session-id"))]
+    pub session_id: Option<u32>,
+
+    /// The output file location.
+    #[cfg_attr(
+        feature = "cli",
+        structopt(short = "o", long = "output-file", parse(from_os_str))
+    )]
+    pub output_file: Option<PathBuf>,
+
+    #[cfg_attr(feature = "cli", structopt(flatten))]
+    pub tracking: TrackerOpts,
+
+    #[cfg_attr(feature = "cli", structopt(flatten))]
+    pub rotation: RotationOpts,
+}
+
+impl From<Opts> for Config {
+    fn from(o: Opts) -> Self {
+        let session_id = o.session_id.unwrap_or(0);
+        Config {
+            socket_path: o
+                .socket_path
+                .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_SOCKET_NAME)),
+            session_id: session_id.into(),
+            output_file: o.output_file.unwrap_or_else(|| {
+                std::env::current_dir()
+                    .expect("Could not retrieve current directory")
+                    .join(format!("session_{}_log_entries.jsonl", session_id))
+            }),
+            tracking: o.tracking,
+            rotation: o.rotation.policy(),
+        }
+    }
+}
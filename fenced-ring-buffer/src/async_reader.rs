@@ -87,18 +87,16 @@ where
         let first_read_seqn = self.read_seqn;
         while self.read_seqn != pre_write_seqn {
             let storage_index = get_seqn_index(self.storage_cap, self.read_seqn, false);
-            let entry_read = self.snap_storage(storage_index).map_err(|e| {
+            let entry_read = self.snap_storage(storage_index).inspect_err(|_| {
                 self.read_seqn = reset_read_seqn;
-                e
             })?;
             self.buf_snapshot.push(entry_read);
             self.read_seqn += 1;
         }
 
         // Calculate number of entries in snapshot buffer that may have been overwritten
-        let post_overwrite_seqn = self.snap_overwrite_seqn().map_err(|e| {
+        let post_overwrite_seqn = self.snap_overwrite_seqn().inspect_err(|_| {
             self.read_seqn = reset_read_seqn;
-            e
         })?;
         let n_overwritten_in_snap = min(
             num_missed(first_read_seqn, post_overwrite_seqn),
@@ -212,7 +210,7 @@ mod tests {
     #[test]
     fn test_async_read() {
         const STORAGE_CAP: usize = 4;
-        let mut storage = [MaybeUninit::uninit(); STORAGE_CAP as usize];
+        let mut storage = [MaybeUninit::uninit(); STORAGE_CAP];
         let mut buf = FencedRingBuffer::new(&mut storage[..], false).unwrap();
         let buf_ptr = &buf as *const FencedRingBuffer<'_, OrderedEntry>;
         let snapper = PtrSnapper::new(buf_ptr);
@@ -3,7 +3,6 @@
 use crate::{get_seqn_index, num_missed, Entry, SeqNum, WholeEntry};
 use core::cmp::max;
 use core::fmt;
-use core::mem::size_of;
 use core::mem::MaybeUninit;
 use core::sync::atomic::fence;
 use core::sync::atomic::Ordering;
@@ -26,7 +25,7 @@ impl fmt::Debug for SizeError {
 #[inline]
 fn round_to_power_2(length: usize) -> usize {
     debug_assert!(length > 0);
-    let exp: usize = size_of::<usize>() * 8 - (length.leading_zeros() as usize) - 1;
+    let exp: usize = usize::BITS as usize - (length.leading_zeros() as usize) - 1;
     1 << exp
 }
 
@@ -214,6 +213,71 @@ where
         tail
     }
 
+    /// Sequence number of the tail: the next entry to be read, or the oldest
+    /// entry present in the buffer if the tail has already been overwritten
+    #[inline]
+    pub fn tail_seqn(&self) -> SeqNum {
+        max(self.read_seqn, self.overwrite_seqn)
+    }
+
+    /// Move the tail back to a sequence number previously returned by
+    /// `tail_seqn`, so the entries popped since are read again. Entries
+    /// which have since been overwritten are skipped.
+    #[inline]
+    pub fn rewind_tail(&mut self, seqn: SeqNum) {
+        debug_assert!(seqn <= self.tail_seqn());
+        self.read_seqn = seqn;
+    }
+
+    /// Restore the sequence numbers of `stale`, a buffer over the same storage
+    /// as this one whose memory survived a reset, e.g. in RAM which isn't
+    /// initialized at boot. They are only restored if they, and the entries
+    /// between them, are consistent. Returns whether they were restored.
+    ///
+    /// # Safety
+    ///
+    /// `stale` must be valid for reads, and its bytes as well as those of this
+    /// buffer's storage must be initialized. Its storage reference is not used.
+    pub unsafe fn restore_from(&mut self, stale: *const Self) -> bool {
+        let write_seqn = (*stale).write_seqn;
+        let overwrite_seqn = (*stale).overwrite_seqn;
+        let read_seqn = (*stale).read_seqn;
+
+        // A reset in the middle of an increment leaves the updating bit set
+        let is_settled = |seqn: SeqNum| seqn.high < SeqNum::UPDATING_HIGH_MASK;
+        if !is_settled(write_seqn)
+            || !is_settled(overwrite_seqn)
+            || !is_settled(read_seqn)
+            || overwrite_seqn > write_seqn
+            || read_seqn > write_seqn
+            || u64::from(write_seqn - overwrite_seqn) > self.capacity() as u64
+        {
+            return false;
+        }
+
+        // Double entries must be whole, and the tail must not point
+        // at the suffix of one
+        let tail_seqn = max(read_seqn, overwrite_seqn);
+        let mut is_tail_at_entry = tail_seqn == write_seqn;
+        let mut seqn = overwrite_seqn;
+        while seqn < write_seqn {
+            is_tail_at_entry |= seqn == tail_seqn;
+            seqn += if self.read_storage(seqn).is_prefix() {
+                2
+            } else {
+                1
+            };
+        }
+        if seqn != write_seqn || !is_tail_at_entry {
+            return false;
+        }
+
+        self.write_seqn = write_seqn;
+        self.overwrite_seqn = overwrite_seqn;
+        self.read_seqn = read_seqn;
+        true
+    }
+
     /// Create iterator over the entries currently present in the buffer without
     /// changing the tail
     #[inline]
@@ -392,7 +456,7 @@ mod tests {
             while n != 1 {
                 prop_assert_eq!(n % 2, 0);
                 prop_assert_eq!(n & 1, 0);
-                n = n / 2;
+                n /= 2;
             }
         }
     }
@@ -402,11 +466,98 @@ mod tests {
         let e = WholeEntry::Single(OrderedEntry::from_index(1));
         assert_eq!(e.size(), 1);
         assert_eq!(e.first_entry(), &OrderedEntry::from_index(1));
-        assert_eq!(e.is_double(), false);
+        assert!(!e.is_double());
         let e = WholeEntry::Double(OrderedEntry::from_index(2), OrderedEntry::from_index(3));
         assert_eq!(e.size(), 2);
         assert_eq!(e.first_entry(), &OrderedEntry::from_index(2));
-        assert_eq!(e.is_double(), true);
+        assert!(e.is_double());
+    }
+
+    #[test]
+    fn rewind_tail() {
+        let mut storage = [MaybeUninit::<OrderedEntry>::uninit(); 4];
+        let mut buf = FencedRingBuffer::new(&mut storage[..], false).unwrap();
+        for i in 0..3 {
+            buf.push(OrderedEntry::from_index(i));
+        }
+        let tail = buf.tail_seqn();
+        assert_eq!(
+            buf.pop(),
+            Some(WholeEntry::Single(OrderedEntry::from_index(0)))
+        );
+        assert_eq!(
+            buf.pop(),
+            Some(WholeEntry::Single(OrderedEntry::from_index(1)))
+        );
+        assert_eq!(buf.len(), 1);
+        buf.rewind_tail(tail);
+        assert_eq!(buf.len(), 3);
+        assert_eq!(
+            buf.peek(),
+            Some(WholeEntry::Single(OrderedEntry::from_index(0)))
+        );
+
+        // Entries overwritten after rewinding are skipped
+        buf.pop();
+        let tail = buf.tail_seqn();
+        buf.pop();
+        buf.rewind_tail(tail);
+        for i in 3..6 {
+            buf.push(OrderedEntry::from_index(i));
+        }
+        assert_eq!(
+            buf.peek(),
+            Some(WholeEntry::Single(OrderedEntry::from_index(2)))
+        );
+        assert_eq!(buf.tail_seqn(), 2.into());
+    }
+
+    #[test]
+    fn restore_from() {
+        let mut storage = [MaybeUninit::<OrderedEntry>::uninit(); 4];
+        let (storage_ptr, storage_len) = (storage.as_mut_ptr(), storage.len());
+        let new_buf = || {
+            let storage = unsafe { core::slice::from_raw_parts_mut(storage_ptr, storage_len) };
+            FencedRingBuffer::new(storage, false).unwrap()
+        };
+
+        let mut stale = new_buf();
+        stale.push(OrderedEntry::from_index(0));
+        stale.push_double(
+            OrderedEntry::from_index_prefix(1),
+            OrderedEntry::from_index_suffix(2),
+        );
+        stale.push(OrderedEntry::from_index(3));
+        // Overwrites entry 0
+        stale.push(OrderedEntry::from_index(4));
+
+        let mut buf = new_buf();
+        assert!(unsafe { buf.restore_from(&stale) });
+        assert_eq!(buf.num_missed(), 1);
+        assert_eq!(
+            buf.pop(),
+            Some(WholeEntry::Double(
+                OrderedEntry::from_index_prefix(1),
+                OrderedEntry::from_index_suffix(2)
+            ))
+        );
+        assert_eq!(buf.len(), 2);
+
+        // The tail points at the suffix of a double entry
+        stale.read_seqn = 2.into();
+        let mut buf = new_buf();
+        assert!(!unsafe { buf.restore_from(&stale) });
+        assert!(buf.is_empty());
+        stale.read_seqn = 0.into();
+
+        // More entries than fit in the storage
+        stale.overwrite_seqn = 0.into();
+        assert!(!unsafe { new_buf().restore_from(&stale) });
+        stale.overwrite_seqn = 1.into();
+
+        // Reset in the middle of an increment
+        stale.write_seqn.set_updating_high_bit();
+        assert!(!unsafe { new_buf().restore_from(&stale) });
     }
 
     /// Test backing storage size rounding and minimum size enforcement
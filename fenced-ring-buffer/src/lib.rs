@@ -1,5 +1,7 @@
 //! FencedRingBuffer, a single producer, single consumer, shared memory ring
 //! buffer which maintains consistency while operating under race conditions.
+//! MpscFencedRingBuffer is a lock-free variant of it that accepts entries from
+//! several producers at once.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(warnings)]
 #![deny(missing_docs)]
@@ -29,7 +31,7 @@ impl SeqNum {
         Self { high, low }
     }
 
-    #[cfg(feature = "std")]
+    #[cfg(any(feature = "std", target_has_atomic = "32"))]
     pub(crate) fn has_updating_high_bit_set(high: u32) -> bool {
         high & Self::UPDATING_HIGH_MASK != 0
     }
@@ -200,16 +202,20 @@ pub mod async_reader;
 pub mod buffer;
 pub use buffer::FencedRingBuffer;
 
+#[cfg(target_has_atomic = "32")]
+pub mod mpsc;
+#[cfg(target_has_atomic = "32")]
+pub use mpsc::MpscFencedRingBuffer;
+
 #[cfg(all(feature = "std", test))]
 mod test_support;
 
 #[cfg(all(feature = "std", test))]
-pub mod tests {
+mod tests {
     use super::*;
 
     use crate::buffer::MIN_STORAGE_CAP;
     use core::mem::MaybeUninit;
-    use crossbeam;
     use proptest::prelude::*;
     use rand::Rng;
     use std::sync::{Arc, Barrier};
@@ -329,7 +335,7 @@ pub mod tests {
         proptest!(
             ProptestConfig::default(),
             |(seqnum in gen_seq_num())| {
-                let mut a = seqnum.clone();
+                let mut a = seqnum;
                 a.set_updating_high_bit();
                 prop_assert_ne!(a, seqnum);
                 prop_assert!(SeqNum::has_updating_high_bit_set(a.high));
@@ -0,0 +1,647 @@
+//! A lock-free, multiple producer variant of the FencedRingBuffer. Producers
+//! reserve slots by atomically advancing a reservation sequence number, write
+//! their entries, and then publish them by advancing the write sequence number
+//! over every contiguous committed slot. Any producer can publish entries
+//! committed by another, so a producer that is interrupted between reserving
+//! and committing never blocks the others.
+//!
+//! The write sequence number, overwrite sequence number, and backing storage
+//! have the same layout as in FencedRingBuffer, so the buffer can be read
+//! asynchronously by the same FencedReader/Snapper implementations.
+use crate::buffer::{SizeError, MIN_STORAGE_CAP};
+use crate::{Entry, SeqNum, WholeEntry};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::slice;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Error returned when a push would overwrite entries that another producer
+/// is still writing, or when another producer is in the middle of carrying a
+/// sequence number into its high word
+pub struct ReservationError;
+
+impl fmt::Debug for ReservationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Entries to be overwritten are still being written by another producer"
+        )
+    }
+}
+
+/// Result of trying to advance an AtomicSeqNum
+enum Advance {
+    /// The sequence number was advanced
+    Done,
+    /// The sequence number was changed by someone else; reload and retry
+    Moved,
+    /// Another producer is carrying the sequence number into its high word
+    Contended,
+}
+
+/// Sequence number that can be advanced by several producers at once
+/// Note: This has the same layout as SeqNum, including the use of the
+/// "updating" bit of the high word, so it can be read by the asynchronous
+/// reader in the same way.
+#[derive(Debug)]
+#[repr(C)]
+pub struct AtomicSeqNum {
+    /// High 32 bits of sequence number
+    pub high: AtomicU32,
+    /// Low 32 bits of sequence number
+    pub low: AtomicU32,
+}
+
+impl AtomicSeqNum {
+    fn new() -> Self {
+        AtomicSeqNum {
+            high: AtomicU32::new(0),
+            low: AtomicU32::new(0),
+        }
+    }
+
+    /// Load the current value of the sequence number without waiting on a
+    /// producer that is carrying into the high word
+    pub fn load(&self) -> u64 {
+        loop {
+            let initial_high = self.high.load(Ordering::SeqCst);
+            let low = self.low.load(Ordering::SeqCst);
+            let final_high = self.high.load(Ordering::SeqCst);
+            if initial_high == final_high {
+                return Self::combine(final_high, low);
+            }
+        }
+    }
+
+    /// Combine the words of the sequence number. While the updating bit is
+    /// set, the low word is either just below the carry (high word still
+    /// valid) or just past it (high word is one behind).
+    fn combine(high: u32, low: u32) -> u64 {
+        if SeqNum::has_updating_high_bit_set(high) {
+            let high = high & !SeqNum::UPDATING_HIGH_MASK;
+            let carried = low < (1 << 31);
+            u64::from(SeqNum::new(high + carried as u32, low))
+        } else {
+            u64::from(SeqNum::new(high, low))
+        }
+    }
+
+    /// Advance the sequence number from `current` to `new`, unless it has
+    /// been changed since `current` was loaded
+    fn advance(&self, current: u64, new: u64) -> Advance {
+        let current = SeqNum::from(current);
+        let new = SeqNum::from(new);
+        if current.high == new.high {
+            match self.low.compare_exchange(
+                current.low,
+                new.low,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => Advance::Done,
+                Err(_) => Advance::Moved,
+            }
+        } else {
+            // Claim the updating bit before carrying, so readers retry and no
+            // other producer carries at the same time
+            if self
+                .high
+                .compare_exchange(
+                    current.high,
+                    current.high | SeqNum::UPDATING_HIGH_MASK,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                return if self.load() == u64::from(current) {
+                    Advance::Contended
+                } else {
+                    Advance::Moved
+                };
+            }
+            let carried = self
+                .low
+                .compare_exchange(current.low, new.low, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+            self.high.store(
+                if carried { new.high } else { current.high },
+                Ordering::SeqCst,
+            );
+            if carried {
+                Advance::Done
+            } else {
+                Advance::Moved
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+/// Struct used to write to buffer from several producers at once
+pub struct MpscFencedRingBuffer<'a, E>
+where
+    E: Entry,
+{
+    /// Sequence number of the next entry to be published to readers
+    pub write_seqn: AtomicSeqNum,
+    /// Sequence number of the next entry to be overwritten
+    pub overwrite_seqn: AtomicSeqNum,
+    /// Backing storage
+    pub storage: &'a [UnsafeCell<MaybeUninit<E>>],
+    /// Sequence number of the next slot to be reserved by a producer
+    reserve_seqn: AtomicSeqNum,
+    /// For each slot in backing storage, the low word of the sequence number
+    /// of the entry last committed to it, plus 1
+    commits: &'a [AtomicU32],
+}
+
+// Safe because all access to backing storage is coordinated through the
+// reservation, commit, and overwrite sequence numbers
+unsafe impl<E> Sync for MpscFencedRingBuffer<'_, E> where E: Entry + Send {}
+
+impl<'a, E> MpscFencedRingBuffer<'a, E>
+where
+    E: Entry,
+{
+    /// Create new MpscFencedRingBuffer. `commits` holds the per-slot commit
+    /// markers, and should be at least as long as `storage`; the buffer's
+    /// capacity is the shorter of the two. Returns error if the capacity is
+    /// smaller than the minimum size
+    pub fn new(
+        storage: &'a mut [MaybeUninit<E>],
+        commits: &'a mut [MaybeUninit<u32>],
+    ) -> Result<MpscFencedRingBuffer<'a, E>, SizeError> {
+        let cap = storage.len().min(commits.len());
+        if cap < MIN_STORAGE_CAP {
+            return Err(SizeError);
+        }
+        for commit in commits.iter_mut() {
+            *commit = MaybeUninit::new(0);
+        }
+        // Safe because UnsafeCell<T> and AtomicU32 have the same in-memory
+        // representation as T and u32, the slices are exclusively borrowed
+        // for 'a, and the commit markers were just initialized
+        let (storage, commits) = unsafe {
+            (
+                slice::from_raw_parts(storage.as_mut_ptr() as *const UnsafeCell<_>, cap),
+                slice::from_raw_parts(commits.as_mut_ptr() as *const AtomicU32, cap),
+            )
+        };
+        Ok(MpscFencedRingBuffer {
+            write_seqn: AtomicSeqNum::new(),
+            overwrite_seqn: AtomicSeqNum::new(),
+            storage,
+            reserve_seqn: AtomicSeqNum::new(),
+            commits,
+        })
+    }
+
+    /// Write single entry to buffer, passing any overwritten entries to
+    /// `on_overwrite`
+    pub fn push<F>(&self, entry: E, mut on_overwrite: F) -> Result<(), ReservationError>
+    where
+        F: FnMut(WholeEntry<E>),
+    {
+        let seqn = self.reserve(1, &mut on_overwrite)?;
+        self.write_to_storage(seqn, entry);
+        self.commit(seqn);
+        self.publish();
+        Ok(())
+    }
+
+    /// Write double entry to buffer, passing any overwritten entries to
+    /// `on_overwrite`. Both halves are reserved together, so they are
+    /// never separated by another producer's entries.
+    pub fn push_double<F>(
+        &self,
+        prefix: E,
+        suffix: E,
+        mut on_overwrite: F,
+    ) -> Result<(), ReservationError>
+    where
+        F: FnMut(WholeEntry<E>),
+    {
+        debug_assert!(prefix.is_prefix());
+        let seqn = self.reserve(2, &mut on_overwrite)?;
+        self.write_to_storage(seqn, prefix);
+        self.write_to_storage(seqn + 1, suffix);
+        // Commit the suffix first, so a committed prefix implies a committed
+        // suffix
+        self.commit(seqn + 1);
+        self.commit(seqn);
+        self.publish();
+        Ok(())
+    }
+
+    /// Return capacity of backing storage
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Return number of published entries that have not been overwritten
+    pub fn len(&self) -> usize {
+        let overwrite_seqn = self.overwrite_seqn.load();
+        (self.write_seqn.load().saturating_sub(overwrite_seqn)) as usize
+    }
+
+    /// Return true if no entries have been published or all have been
+    /// overwritten
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserve `n` consecutive slots, first marking the entries they hold as
+    /// overwritten. Returns the sequence number of the first reserved slot.
+    fn reserve<F>(&self, n: u64, on_overwrite: &mut F) -> Result<u64, ReservationError>
+    where
+        F: FnMut(WholeEntry<E>),
+    {
+        loop {
+            let reserve_seqn = self.reserve_seqn.load();
+            // Slots for [reserve_seqn, reserve_seqn + n) hold the entries at
+            // those sequence numbers minus the capacity, which readers have to
+            // be told about before the slots are reused
+            self.overwrite_until(
+                (reserve_seqn + n).saturating_sub(self.capacity() as u64),
+                on_overwrite,
+            )?;
+            match self.reserve_seqn.advance(reserve_seqn, reserve_seqn + n) {
+                Advance::Done => return Ok(reserve_seqn),
+                Advance::Moved => continue,
+                Advance::Contended => return Err(ReservationError),
+            }
+        }
+    }
+
+    /// Advance the overwrite sequence number until it is at least `target`
+    fn overwrite_until<F>(&self, target: u64, on_overwrite: &mut F) -> Result<(), ReservationError>
+    where
+        F: FnMut(WholeEntry<E>),
+    {
+        loop {
+            let overwrite_seqn = self.overwrite_seqn.load();
+            if overwrite_seqn >= target {
+                return Ok(());
+            }
+            if overwrite_seqn >= self.write_seqn.load() {
+                // The oldest entry may be committed but not yet published
+                self.publish();
+                if overwrite_seqn >= self.write_seqn.load() {
+                    return Err(ReservationError);
+                }
+            }
+            // The entry has been published, and its slot can't be reserved
+            // again until the overwrite sequence number moves past it
+            let overwritten = match self.read_at(overwrite_seqn, &self.overwrite_seqn) {
+                Some(overwritten) => overwritten,
+                None => continue,
+            };
+            match self
+                .overwrite_seqn
+                .advance(overwrite_seqn, overwrite_seqn + overwritten.size() as u64)
+            {
+                Advance::Done => on_overwrite(overwritten),
+                Advance::Moved => continue,
+                Advance::Contended => return Err(ReservationError),
+            }
+        }
+    }
+
+    /// Advance the write sequence number over every contiguous committed
+    /// entry, including those committed by other producers
+    fn publish(&self) {
+        loop {
+            let write_seqn = self.write_seqn.load();
+            if !self.is_committed(write_seqn) {
+                return;
+            }
+            // The entry's slot can't be reused until the write sequence number
+            // moves past it
+            let entry = match self.read_at(write_seqn, &self.write_seqn) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            match self
+                .write_seqn
+                .advance(write_seqn, write_seqn + entry.size() as u64)
+            {
+                Advance::Done | Advance::Moved => continue,
+                // The producer carrying the write sequence number publishes
+                // the rest once it's done
+                Advance::Contended => return,
+            }
+        }
+    }
+
+    #[inline]
+    fn index(&self, seqn: u64) -> usize {
+        (seqn % self.capacity() as u64) as usize
+    }
+
+    #[inline]
+    fn commit(&self, seqn: u64) {
+        self.commits[self.index(seqn)].store((seqn as u32).wrapping_add(1), Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_committed(&self, seqn: u64) -> bool {
+        self.commits[self.index(seqn)].load(Ordering::SeqCst) == (seqn as u32).wrapping_add(1)
+    }
+
+    /// Write to storage at index corresponding to given sequence number
+    #[inline]
+    fn write_to_storage(&self, seqn: u64, entry: E) {
+        // Safe because the slot is reserved by this producer
+        unsafe {
+            ptr::write_volatile(
+                self.storage[self.index(seqn)].get(),
+                MaybeUninit::new(entry),
+            )
+        }
+    }
+
+    /// Get value of backing storage at given index
+    #[inline]
+    pub(crate) unsafe fn read_storage(&self, index: usize) -> E {
+        ptr::read_volatile(self.storage[index].get()).assume_init()
+    }
+
+    /// Read the whole entry starting at given sequence number, which must be
+    /// committed and not a double-entry suffix. Returns None if `cursor` has
+    /// moved away from seqn, since the slot may have been reused while reading
+    fn read_at(&self, seqn: u64, cursor: &AtomicSeqNum) -> Option<WholeEntry<E>> {
+        // Safe because the slot was committed, and the read is discarded if it
+        // may have been reused since
+        let first_entry = unsafe { self.read_storage(self.index(seqn)) };
+        if cursor.load() != seqn {
+            return None;
+        }
+        if first_entry.is_prefix() {
+            // Safe because a suffix is committed before its prefix
+            let second_entry = unsafe { self.read_storage(self.index(seqn + 1)) };
+            Some(WholeEntry::Double(first_entry, second_entry))
+        } else {
+            Some(WholeEntry::Single(first_entry))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> crate::async_reader::Snapper<E> for &MpscFencedRingBuffer<'_, E>
+where
+    E: Entry,
+{
+    type Error = std::convert::Infallible;
+
+    fn snap_write_seqn_high(&self) -> Result<u32, Self::Error> {
+        Ok(self.write_seqn.high.load(Ordering::SeqCst))
+    }
+
+    fn snap_write_seqn_low(&self) -> Result<u32, Self::Error> {
+        Ok(self.write_seqn.low.load(Ordering::SeqCst))
+    }
+
+    fn snap_overwrite_seqn_high(&self) -> Result<u32, Self::Error> {
+        Ok(self.overwrite_seqn.high.load(Ordering::SeqCst))
+    }
+
+    fn snap_overwrite_seqn_low(&self) -> Result<u32, Self::Error> {
+        Ok(self.overwrite_seqn.low.load(Ordering::SeqCst))
+    }
+
+    fn snap_storage(&self, index: usize) -> Result<E, Self::Error> {
+        // Torn reads of slots being reused are discarded by the reader,
+        // since the overwrite sequence number is advanced before reuse
+        Ok(unsafe { self.read_storage(index) })
+    }
+}
+
+#[cfg(all(feature = "std", test))]
+mod tests {
+    use super::*;
+    use crate::async_reader::FencedReader;
+    use crate::test_support::{OrderedEntry, OutputOrderedEntry};
+    use crate::FencedRingBuffer;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicBool;
+
+    const PRODUCER_SHIFT: u32 = 20;
+
+    #[test]
+    fn layout_matches_fenced_ring_buffer() {
+        let mut storage = [MaybeUninit::<OrderedEntry>::uninit(); 4];
+        let mut commits = [MaybeUninit::uninit(); 4];
+        let mpsc_buf = MpscFencedRingBuffer::new(&mut storage[..], &mut commits[..]).unwrap();
+        let mpsc_addr = &mpsc_buf as *const _ as usize;
+        let mpsc_offsets = [
+            &mpsc_buf.write_seqn.high as *const _ as usize - mpsc_addr,
+            &mpsc_buf.write_seqn.low as *const _ as usize - mpsc_addr,
+            &mpsc_buf.overwrite_seqn.high as *const _ as usize - mpsc_addr,
+            &mpsc_buf.overwrite_seqn.low as *const _ as usize - mpsc_addr,
+            &mpsc_buf.storage as *const _ as usize - mpsc_addr,
+        ];
+
+        let mut storage = [MaybeUninit::<OrderedEntry>::uninit(); 4];
+        let buf = FencedRingBuffer::new(&mut storage[..], false).unwrap();
+        let addr = &buf as *const _ as usize;
+        let offsets = [
+            &buf.write_seqn.high as *const _ as usize - addr,
+            &buf.write_seqn.low as *const _ as usize - addr,
+            &buf.overwrite_seqn.high as *const _ as usize - addr,
+            &buf.overwrite_seqn.low as *const _ as usize - addr,
+            &buf.storage as *const _ as usize - addr,
+        ];
+        assert_eq!(mpsc_offsets, offsets);
+    }
+
+    #[test]
+    fn push_and_overwrite() {
+        let mut storage = [MaybeUninit::uninit(); 4];
+        let mut commits = [MaybeUninit::uninit(); 4];
+        let buf = MpscFencedRingBuffer::new(&mut storage[..], &mut commits[..]).unwrap();
+        let mut overwritten = Vec::new();
+        let mut out = Vec::new();
+        let mut reader = FencedReader::new(&buf, buf.capacity());
+
+        for i in 0..4 {
+            buf.push(OrderedEntry::from_index(i), |e| overwritten.push(e))
+                .unwrap();
+        }
+        assert!(overwritten.is_empty());
+        assert_eq!(buf.len(), 4);
+
+        buf.push_double(
+            OrderedEntry::from_index_prefix(4),
+            OrderedEntry::from_index_suffix(5),
+            |e| overwritten.push(e),
+        )
+        .unwrap();
+        assert_eq!(
+            overwritten,
+            vec![
+                WholeEntry::Single(OrderedEntry::from_index(0)),
+                WholeEntry::Single(OrderedEntry::from_index(1)),
+            ]
+        );
+
+        overwritten.clear();
+        buf.push(OrderedEntry::from_index(6), |e| overwritten.push(e))
+            .unwrap();
+        buf.push(OrderedEntry::from_index(7), |e| overwritten.push(e))
+            .unwrap();
+        assert_eq!(
+            overwritten,
+            vec![
+                WholeEntry::Single(OrderedEntry::from_index(2)),
+                WholeEntry::Single(OrderedEntry::from_index(3)),
+            ]
+        );
+
+        assert_eq!(4, reader.read(&mut out).unwrap());
+        assert_eq!(
+            out,
+            vec![
+                WholeEntry::Double(
+                    OrderedEntry::from_index_prefix(4),
+                    OrderedEntry::from_index_suffix(5)
+                ),
+                WholeEntry::Single(OrderedEntry::from_index(6)),
+                WholeEntry::Single(OrderedEntry::from_index(7)),
+            ]
+        );
+
+        // Overwriting the prefix of a double entry overwrites the whole thing
+        overwritten.clear();
+        buf.push(OrderedEntry::from_index(8), |e| overwritten.push(e))
+            .unwrap();
+        assert_eq!(
+            overwritten,
+            vec![WholeEntry::Double(
+                OrderedEntry::from_index_prefix(4),
+                OrderedEntry::from_index_suffix(5)
+            )]
+        );
+        assert_eq!(buf.len(), 3);
+    }
+
+    #[test]
+    fn seqns_carry_into_high_word() {
+        let mut storage = [MaybeUninit::uninit(); 4];
+        let mut commits = [MaybeUninit::uninit(); 4];
+        let buf = MpscFencedRingBuffer::new(&mut storage[..], &mut commits[..]).unwrap();
+        let start = u32::MAX as u64 - 2;
+        for seqn in &[&buf.write_seqn, &buf.overwrite_seqn, &buf.reserve_seqn] {
+            seqn.low.store(start as u32, Ordering::SeqCst);
+        }
+
+        let mut overwritten = Vec::new();
+        for i in 0..3 {
+            buf.push(OrderedEntry::from_index(i), |e| overwritten.push(e))
+                .unwrap();
+            buf.push_double(
+                OrderedEntry::from_index_prefix(i),
+                OrderedEntry::from_index_suffix(i),
+                |e| overwritten.push(e),
+            )
+            .unwrap();
+        }
+        assert_eq!(buf.write_seqn.load(), start + 9);
+        assert_eq!(buf.reserve_seqn.load(), start + 9);
+        assert_eq!(buf.write_seqn.high.load(Ordering::SeqCst), 1);
+        assert_eq!(buf.overwrite_seqn.high.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            overwritten.iter().map(|e| e.size() as u64).sum::<u64>(),
+            buf.overwrite_seqn.load() - start
+        );
+        assert_eq!(
+            buf.len() as u64,
+            buf.write_seqn.load() - buf.overwrite_seqn.load()
+        );
+    }
+
+    // Push single and double entries from several producers while reading
+    // concurrently. Check that double entries are never torn or interleaved
+    // with another producer's entries, each producer's entries stay in
+    // order, and every published entry is either read or counted as missed.
+    proptest! {
+        #[test]
+        fn concurrent_producers(
+            num_producers in 2_u32..=4_u32,
+            num_writes in 1_u32..=512_u32,
+            storage_cap in 4_usize..=64_usize,
+        ) {
+            let mut storage = vec![MaybeUninit::uninit(); storage_cap];
+            let mut commits = vec![MaybeUninit::uninit(); storage_cap];
+            let buf = MpscFencedRingBuffer::new(&mut storage[..], &mut commits[..]).unwrap();
+            let done = AtomicBool::new(false);
+            let (buf, done) = (&buf, &done);
+
+            let (n_missed, out) = crossbeam::thread::scope(|s| {
+                let producers: Vec<_> = (0..num_producers)
+                    .map(|producer| {
+                        s.spawn(move |_| {
+                            let base = producer << PRODUCER_SHIFT;
+                            let mut i = 0;
+                            while i < num_writes {
+                                let res = if i % 3 == 0 {
+                                    i += 2;
+                                    buf.push_double(
+                                        OrderedEntry::from_index_prefix(base + i - 2),
+                                        OrderedEntry::from_index_suffix(base + i - 1),
+                                        |_| (),
+                                    )
+                                } else {
+                                    i += 1;
+                                    buf.push(OrderedEntry::from_index(base + i - 1), |_| ())
+                                };
+                                // Entries that couldn't be reserved are dropped
+                                let _ = res;
+                            }
+                        })
+                    })
+                    .collect();
+                let reader = s.spawn(move |_| {
+                    let mut reader = FencedReader::new(buf, storage_cap);
+                    let mut out = Vec::new();
+                    let mut n_missed = 0;
+                    loop {
+                        let finished = done.load(Ordering::SeqCst);
+                        n_missed += reader.read(&mut out).unwrap();
+                        if finished {
+                            return (n_missed, out);
+                        }
+                    }
+                });
+                for producer in producers {
+                    producer.join().unwrap();
+                }
+                done.store(true, Ordering::SeqCst);
+                reader.join().unwrap()
+            })
+            .unwrap();
+
+            prop_assert_eq!(buf.write_seqn.load(), buf.reserve_seqn.load());
+            let n_read: u64 = out.iter().map(|e| e.size() as u64).sum();
+            prop_assert_eq!(n_missed + n_read, buf.write_seqn.load());
+
+            let output: Vec<_> = out.iter().map(|e| OutputOrderedEntry::Present(*e)).collect();
+            OutputOrderedEntry::check_double_entries_consistent(&output[..]);
+            let mut last_index = HashMap::new();
+            for entry in out {
+                let index = entry.first_entry().to_index();
+                if let WholeEntry::Double(_, suffix) = entry {
+                    prop_assert_eq!(suffix.to_index(), index + 1);
+                }
+                if let Some(last) = last_index.insert(index >> PRODUCER_SHIFT, index) {
+                    prop_assert!(index > last);
+                }
+            }
+        }
+    }
+}
@@ -18,7 +18,7 @@ impl OrderedEntry {
     const PREFIX_TAG: u32 = 0x80000000; // 2^31
     const SUFFIX_TAG: u32 = 0x40000000; // 2^30
 
-    pub(crate) fn to_index(&self) -> u32 {
+    pub(crate) fn to_index(self) -> u32 {
         if self.is_suffix() {
             self.0 - Self::SUFFIX_TAG
         } else if self.is_prefix_unchecked() {
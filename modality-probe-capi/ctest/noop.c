@@ -94,6 +94,12 @@ int main(void) {
     assert(err == MODALITY_PROBE_ERROR_OK);
     err = MODALITY_PROBE_RECORD_W_F32(g_probe, EVENT_A, 0.0f);
     assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_I64(g_probe, EVENT_A, 0);
+    assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_U64(g_probe, EVENT_A, 0);
+    assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_F64(g_probe, EVENT_A, 0.0);
+    assert(err == MODALITY_PROBE_ERROR_OK);
 
     err = MODALITY_PROBE_RECORD_W_I8_W_TIME(g_probe, EVENT_A, 0, 1);
     assert(err == MODALITY_PROBE_ERROR_OK);
@@ -109,6 +115,12 @@ int main(void) {
     assert(err == MODALITY_PROBE_ERROR_OK);
     err = MODALITY_PROBE_RECORD_W_F32_W_TIME(g_probe, EVENT_A, 0.0f, 1);
     assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_I64_W_TIME(g_probe, EVENT_A, 0, 1);
+    assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_U64_W_TIME(g_probe, EVENT_A, 0, 1);
+    assert(err == MODALITY_PROBE_ERROR_OK);
+    err = MODALITY_PROBE_RECORD_W_F64_W_TIME(g_probe, EVENT_A, 0.0, 1);
+    assert(err == MODALITY_PROBE_ERROR_OK);
 
     err = MODALITY_PROBE_EXPECT(g_probe, EVENT_A, 1 == 0);
     assert(err == MODALITY_PROBE_ERROR_OK);
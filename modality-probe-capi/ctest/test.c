@@ -132,8 +132,40 @@ bool test_event_recording(void) {
     ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_RECORD_W_F32(t, EVENT_A, 1.23f, "my docs");
     ERROR_CHECK(result, passed);
+    result = modality_probe_record_event_with_wide_payload(t, EVENT_A, UINT64_MAX);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_I64(t, EVENT_A, (int64_t) -1, "my docs");
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_U64(t, EVENT_A, (uint64_t) 1);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_F64(t, EVENT_A, 1.23, "my docs");
+    ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_EXPECT(t, EVENT_A, 1 == 0, "my docs", MODALITY_TAGS("SEVERITY_10"));
     ERROR_CHECK(result, passed);
+    result = modality_probe_record_event_with_priority(t, EVENT_A, 2);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_PRIORITY(t, EVENT_A, 2, "my docs");
+    ERROR_CHECK(result, passed);
+    result = modality_probe_record_event_with_payload_with_priority(t, EVENT_A, 1, 2);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_record_event_with_wide_payload_with_time_with_priority(t, EVENT_A, UINT64_MAX, 1, 2);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_I8_W_PRIORITY(t, EVENT_A, (int8_t) -1, 2, "my docs");
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_F64_W_PRIORITY(t, EVENT_A, 1.23, 2);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_set_overwrite_priority_level(t, 0);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_set_repeated_event_compression(t, true);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_record_event(t, EVENT_A);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_record_event(t, EVENT_A);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_set_repeated_event_compression(t, false);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_set_checksums(t, true);
+    ERROR_CHECK(result, passed);
     modality_probe_causal_snapshot snap_b;
     result = modality_probe_produce_snapshot(t, &snap_b);
     ERROR_CHECK(result, passed);
@@ -148,6 +180,14 @@ bool test_event_recording(void) {
     ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_RECORD_W_TIME(t, EVENT_A, 1, MODALITY_TAGS(my-tag));
     ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_SPAN_BEGIN(t, EVENT_A, "span desc");
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_SPAN_END(t, EVENT_A, MODALITY_TAGS(my-tag));
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_SPAN_BEGIN_W_TIME(t, EVENT_A, 1);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_SPAN_END_W_TIME(t, EVENT_A, 2, MODALITY_TAGS(my-tag), "span desc");
+    ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_RECORD_W_I8_W_TIME(t, EVENT_A, (int8_t) 1, 1);
     ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_RECORD_W_U8_W_TIME(t, EVENT_A, (uint8_t) 1, 1, "more docs");
@@ -164,6 +204,14 @@ bool test_event_recording(void) {
     ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_RECORD_W_F32_W_TIME(t, EVENT_A, 1.23f, 1, "my docs");
     ERROR_CHECK(result, passed);
+    result = modality_probe_record_event_with_wide_payload_with_time(t, EVENT_A, UINT64_MAX, 1);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_I64_W_TIME(t, EVENT_A, (int64_t) -1, 1);
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_U64_W_TIME(t, EVENT_A, (uint64_t) 1, 1, MODALITY_TAGS(my tag));
+    ERROR_CHECK(result, passed);
+    result = MODALITY_PROBE_RECORD_W_F64_W_TIME(t, EVENT_A, 1.23, 1, "my docs");
+    ERROR_CHECK(result, passed);
     result = MODALITY_PROBE_EXPECT(t, EVENT_A, 1 == 0, "my docs", MODALITY_TAGS("SEVERITY_10"));
     ERROR_CHECK(result, passed);
     result = modality_probe_produce_snapshot_with_time(t, 1, &snap_b);
@@ -421,6 +469,283 @@ bool test_persistent_restart_sequence_id(void) {
     return passed;
 }
 
+static int g_lock_depth = 0;
+static void lock(void *user_state)
+{
+    assert(user_state == (void*) &g_lock_depth);
+    assert(g_lock_depth == 0);
+    g_lock_depth += 1;
+}
+
+static void unlock(void *user_state)
+{
+    assert(user_state == (void*) &g_lock_depth);
+    assert(g_lock_depth == 1);
+    g_lock_depth -= 1;
+}
+
+bool test_shared_recording(void) {
+    bool passed = true;
+
+    uint8_t * destination = (uint8_t*)malloc(DEFAULT_PROBE_SIZE);
+    uint8_t * log_storage = (uint8_t*)malloc(DEFAULT_LOG_STORAGE);
+    modality_probe * probe;
+    modality_probe_critical_section critical_section = { &lock, &unlock, (void*) &g_lock_depth };
+    size_t bytes_written = 0;
+
+    modality_probe_error result = modality_probe_initialize(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_shared_record_event(probe, &critical_section, EVENT_A);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_shared_record_event_with_wide_payload(probe, &critical_section, EVENT_A, UINT64_MAX);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_shared_record_event(probe, NULL, EVENT_A);
+    if (result != MODALITY_PROBE_ERROR_NULL_POINTER) {
+        passed = false;
+    }
+
+    result = modality_probe_shared_report(probe, &critical_section, log_storage, DEFAULT_LOG_STORAGE, &bytes_written);
+    ERROR_CHECK(result, passed);
+    if (bytes_written == 0 || g_lock_depth != 0) {
+        passed = false;
+    }
+
+    free(destination);
+    free(log_storage);
+    return passed;
+}
+
+bool test_event_filter(void) {
+    bool passed = true;
+
+    uint8_t * destination = (uint8_t*)malloc(DEFAULT_PROBE_SIZE);
+    modality_probe * probe;
+
+    modality_probe_error result = modality_probe_initialize(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_disable_events(probe, EVENT_A, EVENT_A + 10);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_record_event(probe, EVENT_A);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_disable_events(probe, 0, EVENT_A);
+    if (result != MODALITY_PROBE_ERROR_INVALID_EVENT_ID) {
+        passed = false;
+    }
+
+    result = modality_probe_enable_all_events(probe);
+    ERROR_CHECK(result, passed);
+
+    free(destination);
+    return passed;
+}
+
+static const size_t FRAGMENT_SIZE = 64;
+static size_t g_fragments_sent = 0;
+static void send_fragment(const uint8_t *fragment, size_t fragment_size, void *user_state)
+{
+    assert(user_state == (void*) &g_fragments_sent);
+    assert(fragment != NULL);
+    assert(fragment_size <= FRAGMENT_SIZE);
+    g_fragments_sent += 1;
+}
+
+bool test_report_fragmented(void) {
+    bool passed = true;
+
+    uint8_t * destination = (uint8_t*)malloc(DEFAULT_PROBE_SIZE);
+    uint8_t * log_storage = (uint8_t*)malloc(DEFAULT_LOG_STORAGE);
+    uint8_t * fragment = (uint8_t*)malloc(FRAGMENT_SIZE);
+    modality_probe * probe;
+    size_t fragments = 0;
+    int i;
+
+    modality_probe_error result = modality_probe_initialize(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+
+    for (i = 0; i < 20; i++) {
+        result = modality_probe_record_event(probe, EVENT_A);
+        ERROR_CHECK(result, passed);
+    }
+
+    result = modality_probe_report_fragmented(
+            probe,
+            log_storage,
+            DEFAULT_LOG_STORAGE,
+            fragment,
+            FRAGMENT_SIZE,
+            &send_fragment,
+            (void*) &g_fragments_sent,
+            &fragments);
+    ERROR_CHECK(result, passed);
+    if (fragments < 2 || fragments != g_fragments_sent) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    free(destination);
+    free(log_storage);
+    free(fragment);
+    return passed;
+}
+
+bool test_report_acknowledgements(void) {
+    bool passed = true;
+
+    uint8_t * destination = (uint8_t*)malloc(DEFAULT_PROBE_SIZE);
+    uint8_t * log_storage = (uint8_t*)malloc(DEFAULT_LOG_STORAGE);
+    uint8_t * retransmission = (uint8_t*)malloc(DEFAULT_LOG_STORAGE);
+    modality_probe * probe;
+    size_t bytes_written = 0;
+    size_t retransmitted_bytes = 0;
+    bool acknowledged = false;
+    /* Acknowledges report 0 from DEFAULT_PROBE_ID (314) */
+    uint8_t ack[16] = {
+        0x41, 0x50, 0x52, 0x4D,
+        0x3A, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00 };
+
+    modality_probe_error result = modality_probe_initialize(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_set_report_acknowledgements(probe, true);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_record_event(probe, EVENT_A);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_report(probe, log_storage, DEFAULT_LOG_STORAGE, &bytes_written);
+    ERROR_CHECK(result, passed);
+
+    /* Until acknowledged, the same report is produced again */
+    result = modality_probe_report(
+            probe,
+            retransmission,
+            DEFAULT_LOG_STORAGE,
+            &retransmitted_bytes);
+    ERROR_CHECK(result, passed);
+    if (bytes_written == 0
+            || bytes_written != retransmitted_bytes
+            || memcmp(log_storage, retransmission, bytes_written) != 0) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    result = modality_probe_consume_report_ack(probe, ack, sizeof(ack) - 1, &acknowledged);
+    if (result != MODALITY_PROBE_ERROR_INVALID_REPORT_ACK) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    result = modality_probe_consume_report_ack(probe, ack, sizeof(ack), &acknowledged);
+    ERROR_CHECK(result, passed);
+    if (!acknowledged) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    /* Report 0 was already acknowledged */
+    result = modality_probe_acknowledge_report(probe, 0, &acknowledged);
+    ERROR_CHECK(result, passed);
+    if (acknowledged) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    free(destination);
+    free(log_storage);
+    free(retransmission);
+    return passed;
+}
+
+bool test_initialize_or_recover(void) {
+    bool passed = true;
+
+    /* Recovery requires initialized memory */
+    uint8_t * destination = (uint8_t*)calloc(1, DEFAULT_PROBE_SIZE);
+    uint8_t * log_storage = (uint8_t*)malloc(DEFAULT_LOG_STORAGE);
+    modality_probe * probe;
+    size_t fresh_bytes = 0;
+    size_t recovered_bytes = 0;
+
+    /* Nothing to recover yet */
+    modality_probe_error result = modality_probe_initialize_or_recover(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_report(probe, log_storage, DEFAULT_LOG_STORAGE, &fresh_bytes);
+    ERROR_CHECK(result, passed);
+
+    result = modality_probe_record_event(probe, EVENT_A);
+    ERROR_CHECK(result, passed);
+
+    /* Simulate a warm reset, the events recorded before it are kept */
+    probe = NULL;
+    result = modality_probe_initialize_or_recover(
+            destination,
+            DEFAULT_PROBE_SIZE,
+            DEFAULT_PROBE_ID,
+            0,
+            0,
+            NULL,
+            NULL,
+            &probe);
+    ERROR_CHECK(result, passed);
+    result = modality_probe_report(probe, log_storage, DEFAULT_LOG_STORAGE, &recovered_bytes);
+    ERROR_CHECK(result, passed);
+    if (recovered_bytes <= fresh_bytes) {
+        fprintf(stderr, "error check failed at line %d\n", __LINE__);
+        passed = false;
+    }
+
+    free(destination);
+    free(log_storage);
+    return passed;
+}
+
 void run_test(bool (test)(void), const char *name, bool *passed) {
     if (!test()) {
         *passed = false;
@@ -438,6 +763,11 @@ int main(void) {
     run_test(test_merge, "test_merge", &passed);
     run_test(test_now, "test_now", &passed);
     run_test(test_persistent_restart_sequence_id, "test_persistent_restart_sequence_id", &passed);
+    run_test(test_shared_recording, "test_shared_recording", &passed);
+    run_test(test_event_filter, "test_event_filter", &passed);
+    run_test(test_report_fragmented, "test_report_fragmented", &passed);
+    run_test(test_report_acknowledgements, "test_report_acknowledgements", &passed);
+    run_test(test_initialize_or_recover, "test_initialize_or_recover", &passed);
     if (!passed) {
         fprintf(stderr, "FAILED c test suite\n");
         exit(1);
@@ -74,11 +74,15 @@ typedef struct modality_probe_causal_snapshot {
      */
     modality_probe_logical_clock clock;
     /*
-     * Reserved field.
+     * Snapshot format version (first byte) and flags (second byte).
+     *
+     * Snapshots produced before the format was versioned have both
+     * bytes zeroed.
      */
     uint8_t reserved_0[2];
     /*
-     * Reserved field.
+     * Checksum of the rest of the snapshot when the checksum flag
+     * is set, otherwise zero.
      */
     uint8_t reserved_1[2];
 } modality_probe_causal_snapshot;
@@ -109,6 +113,35 @@ typedef size_t (*modality_probe_next_sequence_id_fn)(
         void *user_state,
         uint16_t *out_sequence_id);
 
+/*
+ * Function type for entering or leaving a critical section.
+ */
+typedef void (*modality_probe_critical_section_fn)(void *user_state);
+
+/*
+ * Function type for sending one fragment of a report, see
+ * modality_probe_report_fragmented.
+ */
+typedef void (*modality_probe_report_fragment_fn)(
+        const uint8_t *fragment,
+        size_t fragment_size,
+        void *user_state);
+
+/*
+ * A critical section which provides exclusive access to a probe
+ * between every context (e.g. threads and interrupt handlers) that
+ * uses it, for use with the modality_probe_shared_* functions.
+ *
+ * lock is called before the probe is accessed and unlock after,
+ * both with user_state. A typical implementation masks interrupts
+ * or takes a mutex.
+ */
+typedef struct modality_probe_critical_section {
+    modality_probe_critical_section_fn lock;
+    modality_probe_critical_section_fn unlock;
+    void *user_state;
+} modality_probe_critical_section;
+
 typedef enum {
     /*
      * Everything is okay
@@ -162,6 +195,28 @@ typedef enum {
      * A wall clock time outside of the allowed range was provided.
      */
     MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME = 10,
+    /*
+     * The probe's event filter has no room for another range
+     * of disabled events.
+     */
+    MODALITY_PROBE_ERROR_EVENT_FILTER_FULL = 11,
+    /*
+     * The provided external history was encoded with a newer
+     * snapshot format version that this probe can't merge.
+     * Detected during merging.
+     */
+    MODALITY_PROBE_ERROR_UNSUPPORTED_SNAPSHOT_VERSION = 12,
+    /*
+     * The provided external history's checksum didn't match
+     * its contents.
+     * Detected during merging.
+     */
+    MODALITY_PROBE_ERROR_SNAPSHOT_CHECKSUM_MISMATCH = 13,
+    /*
+     * The provided bytes weren't a valid report acknowledgement.
+     * Detected while consuming an acknowledgement.
+     */
+    MODALITY_PROBE_ERROR_INVALID_REPORT_ACK = 14,
 } modality_probe_error;
 
 /*
@@ -212,6 +267,24 @@ typedef enum {
 #define MODALITY_PROBE_RECORD(probe, event, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event(probe, event) : MODALITY_PROBE_ERROR_OK)
 
+/*
+ * Modality probe event recording with priority macro.
+ *
+ * Used to expose event recording information to the CLI tooling.
+ *
+ * Expands to call `modality_probe_record_event_with_priority(probe, event, priority)`.
+ *
+ * The trailing variadic macro arguments accept (in any order):
+ * - Tags: MODALITY_TAGS(<tag>[,<tag>])
+ * - A string for the event description
+ *
+ */
+#define MODALITY_PROBE_RECORD_W_PRIORITY(probe, event, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_priority(\
+            probe, \
+            event, \
+            priority) : MODALITY_PROBE_ERROR_OK)
+
 /*
  * Modality probe event recording with time macro.
  *
@@ -230,6 +303,77 @@ typedef enum {
             event, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
 
+/*
+ * Modality probe span beginning macro.
+ *
+ * Used to expose span information to the CLI tooling. The event's
+ * name must end in `_BEGIN`; the span is closed by
+ * MODALITY_PROBE_SPAN_END with the event of the same name ending
+ * in `_END` instead.
+ *
+ * Expands to call `modality_probe_record_event(probe, event)`.
+ *
+ * The trailing variadic macro arguments accept (in any order):
+ * - Tags: MODALITY_TAGS(<tag>[,<tag>])
+ * - A string for the event description
+ *
+ */
+#define MODALITY_PROBE_SPAN_BEGIN(probe, event, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event(probe, event) : MODALITY_PROBE_ERROR_OK)
+
+/*
+ * Modality probe span end macro.
+ *
+ * Used to expose span information to the CLI tooling. The event's
+ * name must end in `_END`.
+ *
+ * Expands to call `modality_probe_record_event(probe, event)`.
+ *
+ * The trailing variadic macro arguments accept (in any order):
+ * - Tags: MODALITY_TAGS(<tag>[,<tag>])
+ * - A string for the event description
+ *
+ */
+#define MODALITY_PROBE_SPAN_END(probe, event, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event(probe, event) : MODALITY_PROBE_ERROR_OK)
+
+/*
+ * Modality probe span beginning with time macro.
+ *
+ * Used to expose span information to the CLI tooling. Spans whose
+ * beginning and end both carry a time are shown with their duration.
+ *
+ * Expands to call `modality_probe_record_event_with_time(probe, event, time_ns)`.
+ *
+ * The trailing variadic macro arguments accept (in any order):
+ * - Tags: MODALITY_TAGS(<tag>[,<tag>])
+ * - A string for the event description
+ *
+ */
+#define MODALITY_PROBE_SPAN_BEGIN_W_TIME(probe, event, time_ns, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_time(\
+            probe, \
+            event, \
+            time_ns) : MODALITY_PROBE_ERROR_OK)
+
+/*
+ * Modality probe span end with time macro.
+ *
+ * Used to expose span information to the CLI tooling.
+ *
+ * Expands to call `modality_probe_record_event_with_time(probe, event, time_ns)`.
+ *
+ * The trailing variadic macro arguments accept (in any order):
+ * - Tags: MODALITY_TAGS(<tag>[,<tag>])
+ * - A string for the event description
+ *
+ */
+#define MODALITY_PROBE_SPAN_END_W_TIME(probe, event, time_ns, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_time(\
+            probe, \
+            event, \
+            time_ns) : MODALITY_PROBE_ERROR_OK)
+
 /*
  * Modality probe event recording with payload macro.
  *
@@ -237,6 +381,9 @@ typedef enum {
  *
  * Expands to call `modality_probe_record_event_with_payload_<type>(probe, event)`.
  *
+ * The `_W_PRIORITY` variants take the event's priority after the
+ * payload, see `MODALITY_PROBE_RECORD_W_PRIORITY`.
+ *
  * The trailing variadic macro arguments accept (in any order):
  * - Tags: MODALITY_TAGS(<tag>[,<tag>])
  * - A string for the event description
@@ -257,6 +404,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I8_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i8_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_U8(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u8(\
@@ -269,6 +422,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U8_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u8_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_I16(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i16(\
@@ -281,6 +440,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I16_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i16_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_U16(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u16(\
@@ -293,6 +458,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U16_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u16_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_I32(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i32(\
@@ -305,6 +476,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I32_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i32_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_U32(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u32(\
@@ -317,6 +494,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U32_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u32_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_BOOL(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_bool(\
@@ -329,6 +512,12 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_BOOL_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_bool_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 #define MODALITY_PROBE_RECORD_W_F32(probe, event, payload, ...) \
     ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_f32(\
@@ -341,6 +530,63 @@ typedef enum {
             event, \
             payload, \
             time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_F32_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_f32_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I64(probe, event, payload, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i64(\
+            probe, \
+            event, \
+            payload) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I64_W_TIME(probe, event, payload, time_ns, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i64_with_time(\
+            probe, \
+            event, \
+            payload, \
+            time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_I64_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_i64_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U64(probe, event, payload, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u64(\
+            probe, \
+            event, \
+            payload) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U64_W_TIME(probe, event, payload, time_ns, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u64_with_time(\
+            probe, \
+            event, \
+            payload, \
+            time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_U64_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_u64_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_F64(probe, event, payload, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_f64(\
+            probe, \
+            event, \
+            payload) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_F64_W_TIME(probe, event, payload, time_ns, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_f64_with_time(\
+            probe, \
+            event, \
+            payload, \
+            time_ns) : MODALITY_PROBE_ERROR_OK)
+#define MODALITY_PROBE_RECORD_W_F64_W_PRIORITY(probe, event, payload, priority, ...) \
+    ((MODALITY_PROBE_MACROS_ENABLED) ? modality_probe_record_event_with_payload_f64_with_priority(\
+            probe, \
+            event, \
+            payload, \
+            priority) : MODALITY_PROBE_ERROR_OK)
 
 /*
  * Modality probe expectation expression event recording macro.
@@ -385,6 +631,29 @@ size_t modality_probe_initialize(
         void *next_sequence_id_user_state,
         modality_probe **out);
 
+/*
+ * Create a Modality probe instance like modality_probe_initialize,
+ * unless destination still holds an intact probe with the same
+ * probe_id, time_resolution_ns and wall_clock_id, e.g. in RAM that
+ * isn't cleared at boot after a warm reset.
+ *
+ * A recovered probe keeps its log, bumps its epoch and records
+ * a recovery event. Options such as checksums or the event filter
+ * need to be set again.
+ *
+ * The destination region must be initialized, though its contents
+ * may be arbitrary.
+ */
+size_t modality_probe_initialize_or_recover(
+        uint8_t *destination,
+        size_t destination_size_bytes,
+        uint32_t probe_id,
+        uint32_t time_resolution_ns,
+        uint16_t wall_clock_id,
+        modality_probe_next_sequence_id_fn next_sequence_id_fn,
+        void *next_sequence_id_user_state,
+        modality_probe **out);
+
 /*
  * Record time.
  */
@@ -401,205 +670,547 @@ size_t modality_probe_record_event(
         uint32_t event_id);
 
 /*
- * Record an event.
+ * Record an event with a priority.
  * event_id must be non-zero.
+ *
+ * When the log is full, events whose priority is below the
+ * overwrite priority level are dropped rather than overwriting
+ * older log entries. Events recorded without a priority have
+ * priority 0.
  */
-size_t modality_probe_record_event_with_time(
+size_t modality_probe_record_event_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        uint64_t time_ns);
+        uint32_t priority);
 
 /*
- * Record an event along with a 4-byte payload.
+ * Set the overwrite priority level.
  *
- * event_id must be non-zero.
+ * Once the log is full, events with a priority below this level
+ * are dropped rather than overwriting older log entries.
  */
-size_t modality_probe_record_event_with_payload(
+size_t modality_probe_set_overwrite_priority_level(
         modality_probe *probe,
-        uint32_t event_id,
-        uint32_t payload);
+        uint32_t priority);
 
 /*
- * Record an event along with a 4-byte payload and time.
+ * Enable or disable run-length compression of repeated events.
  *
- * event_id must be non-zero.
+ * While enabled, recording the same event (without payload or time)
+ * several times in a row stores the first occurrence followed by
+ * a single entry carrying the number of additional occurrences.
+ * Disabled by default.
  */
-size_t modality_probe_record_event_with_payload_with_time(
+size_t modality_probe_set_repeated_event_compression(
         modality_probe *probe,
-        uint32_t event_id,
-        uint32_t payload,
-        uint64_t time_ns);
+        bool enabled);
 
 /*
- * Record an event along with a i8 payload.
+ * Enable or disable integrity checksums.
  *
- * event_id must be non-zero.
+ * While enabled, reports are followed by a CRC-32 trailer and
+ * snapshots carry a checksum in their reserved_1 field, so that
+ * collectors and other probes can detect corruption in transit.
+ * Meant to be enabled right after initialization. Disabled by default.
  */
-size_t modality_probe_record_event_with_payload_i8(
+size_t modality_probe_set_checksums(
         modality_probe *probe,
-        uint32_t event_id,
-        int8_t payload);
+        bool enabled);
 
 /*
- * Record an event along with a i8 payload and time.
+ * Enable or disable reliable reporting.
  *
- * event_id must be non-zero.
+ * While enabled, the log entries in a report stay in the log until
+ * a collector acknowledges the report's sequence number, and
+ * reporting again before then produces the same report, so that
+ * a lost report can be retransmitted.
+ * See modality_probe_consume_report_ack. Disabled by default.
  */
-size_t modality_probe_record_event_with_payload_i8_with_time(
+size_t modality_probe_set_report_acknowledgements(
         modality_probe *probe,
-        uint32_t event_id,
-        int8_t payload,
-        uint64_t time_ns);
+        bool enabled);
 
 /*
- * Record an event along with a u8 payload.
+ * Stop recording events with ids from first_event_id through
+ * last_event_id, inclusive.
  *
- * event_id must be non-zero.
+ * The probe's event filter holds a small, fixed number of ranges;
+ * MODALITY_PROBE_ERROR_EVENT_FILTER_FULL is returned once it is full.
+ * The filter can also be changed remotely by the debug collector.
  */
-size_t modality_probe_record_event_with_payload_u8(
+size_t modality_probe_disable_events(
         modality_probe *probe,
-        uint32_t event_id,
-        uint8_t payload);
+        uint32_t first_event_id,
+        uint32_t last_event_id);
 
 /*
- * Record an event along with a u8 payload and time.
- *
+ * Resume recording all events.
+ */
+size_t modality_probe_enable_all_events(
+        modality_probe *probe);
+
+/*
+ * Record an event.
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_u8_with_time(
+size_t modality_probe_record_event_with_time(
         modality_probe *probe,
         uint32_t event_id,
-        uint8_t payload,
         uint64_t time_ns);
 
 /*
- * Record an event along with a i16 payload.
+ * Record an event along with time, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_i16(
+size_t modality_probe_record_event_with_time_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        int16_t payload);
+        uint64_t time_ns,
+        uint32_t priority);
 
 /*
- * Record an event along with a i16 payload and time.
+ * Record an event along with a 4-byte payload.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_i16_with_time(
+size_t modality_probe_record_event_with_payload(
         modality_probe *probe,
         uint32_t event_id,
-        int16_t payload,
-        uint64_t time_ns);
+        uint32_t payload);
 
 /*
- * Record an event along with a u16 payload.
+ * Record an event along with a 4-byte payload, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_u16(
+size_t modality_probe_record_event_with_payload_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        uint16_t payload);
+        uint32_t payload,
+        uint32_t priority);
 
 /*
- * Record an event along with a u16 payload and time.
+ * Record an event along with a 4-byte payload and time.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_u16_with_time(
+size_t modality_probe_record_event_with_payload_with_time(
         modality_probe *probe,
         uint32_t event_id,
-        uint16_t payload,
+        uint32_t payload,
         uint64_t time_ns);
 
 /*
- * Record an event along with a i32 payload.
+ * Record an event along with a 4-byte payload and time, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_i32(
+size_t modality_probe_record_event_with_payload_with_time_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        int32_t payload);
+        uint32_t payload,
+        uint64_t time_ns,
+        uint32_t priority);
 
 /*
- * Record an event along with a i32 payload and time.
+ * Record an event along with an 8-byte payload.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_i32_with_time(
+size_t modality_probe_record_event_with_wide_payload(
         modality_probe *probe,
         uint32_t event_id,
-        int32_t payload,
-        uint64_t time_ns);
+        uint64_t payload);
 
 /*
- * Record an event along with a u32 payload.
+ * Record an event along with an 8-byte payload, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_u32(
+size_t modality_probe_record_event_with_wide_payload_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        uint32_t payload);
+        uint64_t payload,
+        uint32_t priority);
 
 /*
- * Record an event along with a u32 payload and time.
+ * Record an event along with an 8-byte payload and time.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_u32_with_time(
+size_t modality_probe_record_event_with_wide_payload_with_time(
         modality_probe *probe,
         uint32_t event_id,
-        uint32_t payload,
+        uint64_t payload,
         uint64_t time_ns);
 
 /*
- * Record an event along with a bool payload.
+ * Record an event along with an 8-byte payload and time, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_bool(
+size_t modality_probe_record_event_with_wide_payload_with_time_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        bool payload);
+        uint64_t payload,
+        uint64_t time_ns,
+        uint32_t priority);
 
 /*
- * Record an event along with a bool payload and time.
+ * Record an event along with a i8 payload.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_bool_with_time(
+size_t modality_probe_record_event_with_payload_i8(
         modality_probe *probe,
         uint32_t event_id,
-        bool payload,
-        uint64_t time_ns);
+        int8_t payload);
 
 /*
- * Record an event along with a f32 payload.
+ * Record an event along with a i8 payload and time.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_f32(
+size_t modality_probe_record_event_with_payload_i8_with_time(
         modality_probe *probe,
         uint32_t event_id,
-        float payload);
+        int8_t payload,
+        uint64_t time_ns);
 
 /*
- * Record an event along with a f32 payload and time.
+ * Record an event along with a i8 payload, with a priority.
  *
  * event_id must be non-zero.
  */
-size_t modality_probe_record_event_with_payload_f32_with_time(
+size_t modality_probe_record_event_with_payload_i8_with_priority(
         modality_probe *probe,
         uint32_t event_id,
-        float payload,
-        uint64_t time_ns);
+        int8_t payload,
+        uint32_t priority);
 
 /*
- * Conduct necessary background activities, then
+ * Record an event along with a u8 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u8(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint8_t payload);
+
+/*
+ * Record an event along with a u8 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u8_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint8_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a u8 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u8_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint8_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a i16 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i16(
+        modality_probe *probe,
+        uint32_t event_id,
+        int16_t payload);
+
+/*
+ * Record an event along with a i16 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i16_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        int16_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a i16 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i16_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        int16_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a u16 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u16(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint16_t payload);
+
+/*
+ * Record an event along with a u16 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u16_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint16_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a u16 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u16_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint16_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a i32 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i32(
+        modality_probe *probe,
+        uint32_t event_id,
+        int32_t payload);
+
+/*
+ * Record an event along with a i32 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i32_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        int32_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a i32 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i32_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        int32_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a u32 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u32(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint32_t payload);
+
+/*
+ * Record an event along with a u32 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u32_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint32_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a u32 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u32_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint32_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a bool payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_bool(
+        modality_probe *probe,
+        uint32_t event_id,
+        bool payload);
+
+/*
+ * Record an event along with a bool payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_bool_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        bool payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a bool payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_bool_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        bool payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a f32 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f32(
+        modality_probe *probe,
+        uint32_t event_id,
+        float payload);
+
+/*
+ * Record an event along with a f32 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f32_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        float payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a f32 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f32_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        float payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a i64 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i64(
+        modality_probe *probe,
+        uint32_t event_id,
+        int64_t payload);
+
+/*
+ * Record an event along with a i64 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i64_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        int64_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a i64 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_i64_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        int64_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a u64 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u64(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint64_t payload);
+
+/*
+ * Record an event along with a u64 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u64_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint64_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a u64 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_u64_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        uint64_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a f64 payload.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f64(
+        modality_probe *probe,
+        uint32_t event_id,
+        double payload);
+
+/*
+ * Record an event along with a f64 payload and time.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f64_with_time(
+        modality_probe *probe,
+        uint32_t event_id,
+        double payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a f64 payload, with a priority.
+ *
+ * event_id must be non-zero.
+ */
+size_t modality_probe_record_event_with_payload_f64_with_priority(
+        modality_probe *probe,
+        uint32_t event_id,
+        double payload,
+        uint32_t priority);
+
+/*
+ * Conduct necessary background activities, then
  * write a report of recorded events and logical clock
  * data to a supplied destination.
  *
@@ -611,6 +1222,53 @@ size_t modality_probe_report(
         size_t log_report_destination_bytes,
         size_t *out_written_bytes);
 
+/*
+ * Write a report of recorded events and logical clock data
+ * to log_report_destination, then split it into fragments for
+ * MTU-limited transports (e.g. CAN-FD, BLE or 802.15.4 frames).
+ *
+ * Each fragment is written to fragment_destination, at most
+ * fragment_destination_bytes long, and handed to send_fragment
+ * along with user_state. Fragments carry a small header so that
+ * collectors can reassemble the report.
+ *
+ * Populates the number of fragments sent in out_fragments.
+ */
+size_t modality_probe_report_fragmented(
+        modality_probe *probe,
+        uint8_t *log_report_destination,
+        size_t log_report_destination_bytes,
+        uint8_t *fragment_destination,
+        size_t fragment_destination_bytes,
+        modality_probe_report_fragment_fn send_fragment,
+        void *user_state,
+        size_t *out_fragments);
+
+/*
+ * Acknowledge the report with sequence number seq_num, releasing
+ * its log entries when reliable reporting is enabled.
+ *
+ * out_acknowledged is set to whether a report with that sequence
+ * number was awaiting acknowledgement.
+ */
+size_t modality_probe_acknowledge_report(
+        modality_probe *probe,
+        uint64_t seq_num,
+        bool *out_acknowledged);
+
+/*
+ * Consume a report acknowledgement received from a collector.
+ *
+ * out_acknowledged is set to whether it acknowledged the report
+ * awaiting acknowledgement. Acknowledgements meant for other
+ * probes are ignored.
+ */
+size_t modality_probe_consume_report_ack(
+        modality_probe *probe,
+        const uint8_t *ack_source,
+        size_t ack_source_bytes,
+        bool *out_acknowledged);
+
 /*
  * Produce a transmittable summary of this Modality probe's
  * causal history for use by another Modality probe elsewhere
@@ -704,6 +1362,203 @@ size_t modality_probe_merge_snapshot_bytes_with_time(
 modality_probe_instant modality_probe_now(
         modality_probe *probe);
 
+/*
+ * The modality_probe_shared_* functions behave like their
+ * modality_probe_* counterparts, but hold the given critical section
+ * for the duration of the call. They allow a probe to be used from
+ * several contexts, such as threads and interrupt handlers.
+ */
+
+/*
+ * Record time.
+ */
+size_t modality_probe_shared_record_time(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint64_t time_ns);
+
+/*
+ * Record an event.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id);
+
+/*
+ * Record an event with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint32_t priority);
+
+/*
+ * Record an event with time.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_time(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with time, with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_time_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t time_ns,
+        uint32_t priority);
+
+/*
+ * Record an event along with a 4-byte payload.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_payload(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint32_t payload);
+
+/*
+ * Record an event along with a 4-byte payload, with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_payload_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint32_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with a 4-byte payload and time.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_payload_with_time(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint32_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with a 4-byte payload and time, with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_payload_with_time_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint32_t payload,
+        uint64_t time_ns,
+        uint32_t priority);
+
+/*
+ * Record an event along with an 8-byte payload.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_wide_payload(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t payload);
+
+/*
+ * Record an event along with an 8-byte payload, with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_wide_payload_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t payload,
+        uint32_t priority);
+
+/*
+ * Record an event along with an 8-byte payload and time.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_wide_payload_with_time(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t payload,
+        uint64_t time_ns);
+
+/*
+ * Record an event along with an 8-byte payload and time, with a priority.
+ * event_id must be non-zero.
+ */
+size_t modality_probe_shared_record_event_with_wide_payload_with_time_with_priority(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint32_t event_id,
+        uint64_t payload,
+        uint64_t time_ns,
+        uint32_t priority);
+
+/*
+ * Write a report of recorded events and logical clock
+ * data to a supplied destination.
+ *
+ * Populates the number of bytes written in out_written_bytes.
+ */
+size_t modality_probe_shared_report(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint8_t *log_report_destination,
+        size_t log_report_destination_bytes,
+        size_t *out_written_bytes);
+
+/*
+ * Produce a transmittable summary of this Modality probe's
+ * causal history.
+ */
+size_t modality_probe_shared_produce_snapshot(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        modality_probe_causal_snapshot *snapshot);
+
+/*
+ * Produce a transmittable opaque blob of this Modality probe's
+ * causal history.
+ *
+ * Populates the number of bytes written in out_written_bytes.
+ */
+size_t modality_probe_shared_produce_snapshot_bytes(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        uint8_t *history_destination,
+        size_t history_destination_bytes,
+        size_t *out_written_bytes);
+
+/*
+ * Consume a causal history summary structure provided
+ * by some other Modality probe.
+ */
+size_t modality_probe_shared_merge_snapshot(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        const modality_probe_causal_snapshot *snapshot);
+
+/*
+ * Consume a opaque causal history blob provided
+ * by some other Modality probe.
+ */
+size_t modality_probe_shared_merge_snapshot_bytes(
+        modality_probe *probe,
+        const modality_probe_critical_section *critical_section,
+        const uint8_t *history_source,
+        size_t history_source_bytes);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
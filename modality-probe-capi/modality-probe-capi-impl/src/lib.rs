@@ -3,7 +3,8 @@
 use core::mem::MaybeUninit;
 use modality_probe::*;
 pub use modality_probe::{
-    next_sequence_id_fn, CausalSnapshot, ModalityProbe, ModalityProbeInstant,
+    critical_section_fn, next_sequence_id_fn, wire::report_fragment_fn, CCriticalSection,
+    CausalSnapshot, ModalityProbe, ModalityProbeInstant,
 };
 
 pub type ModalityProbeError = usize;
@@ -37,6 +38,19 @@ pub const MODALITY_PROBE_ERROR_INVALID_EXTERNAL_HISTORY_SEMANTICS: ModalityProbe
 pub const MODALITY_PROBE_ERROR_RESTART_PERSISTENCE_SEQUENCE_ID_UNAVAILABLE: ModalityProbeError = 9;
 /// A wall clock time outside of the allowed range was provided.
 pub const MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME: ModalityProbeError = 10;
+/// The probe's event filter has no room for another range
+/// of disabled events.
+pub const MODALITY_PROBE_ERROR_EVENT_FILTER_FULL: ModalityProbeError = 11;
+/// The provided external history was encoded with a newer snapshot
+/// format version that this probe can't merge.
+/// Detected during merging.
+pub const MODALITY_PROBE_ERROR_UNSUPPORTED_SNAPSHOT_VERSION: ModalityProbeError = 12;
+/// The provided external history's checksum didn't match its contents.
+/// Detected during merging.
+pub const MODALITY_PROBE_ERROR_SNAPSHOT_CHECKSUM_MISMATCH: ModalityProbeError = 13;
+/// The provided bytes weren't a valid report acknowledgement.
+/// Detected while consuming an acknowledgement.
+pub const MODALITY_PROBE_ERROR_INVALID_REPORT_ACK: ModalityProbeError = 14;
 
 /// # Safety
 ///
@@ -104,6 +118,70 @@ pub unsafe fn modality_probe_initialize(
     }
 }
 
+/// # Safety
+///
+/// The same requirements as `modality_probe_initialize` apply.
+/// Additionally, the whole `destination` region must be initialized,
+/// though its contents may be arbitrary, e.g. RAM which isn't
+/// cleared at boot.
+///
+/// If `destination` still holds an intact probe with the same probe id,
+/// time resolution and wall clock id, that probe is recovered along
+/// with its log. Otherwise a new probe is initialized.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_initialize_or_recover(
+    destination: *mut MaybeUninit<u8>,
+    destination_size_bytes: usize,
+    probe_id: u32,
+    time_resolution_ns: u32,
+    wall_clock_id: u16,
+    next_sequence_id: Option<next_sequence_id_fn>,
+    next_sequence_id_user_state: *mut core::ffi::c_void,
+    out: *mut *mut ModalityProbe<'static>,
+) -> ModalityProbeError {
+    if destination.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    if out.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    if destination_size_bytes < core::mem::size_of::<ModalityProbe<'static>>() {
+        return MODALITY_PROBE_ERROR_INSUFFICIENT_DESTINATION_BYTES;
+    }
+    let probe_id = match ProbeId::new(probe_id) {
+        Some(id) => id,
+        None => return MODALITY_PROBE_ERROR_INVALID_PROBE_ID,
+    };
+    let restart_counter_provider = if let Some(iface) = next_sequence_id {
+        RestartCounterProvider::C(CRestartCounterProvider {
+            iface,
+            state: next_sequence_id_user_state,
+        })
+    } else {
+        RestartCounterProvider::NoRestartTracking
+    };
+    match ModalityProbe::initialize_or_recover_at(
+        core::slice::from_raw_parts_mut(destination, destination_size_bytes),
+        probe_id,
+        time_resolution_ns.into(),
+        wall_clock_id.into(),
+        restart_counter_provider,
+    ) {
+        Ok(t) => {
+            *out = t;
+            MODALITY_PROBE_ERROR_OK
+        }
+        Err(StorageSetupError::NullDestination) => MODALITY_PROBE_ERROR_NULL_POINTER,
+        Err(StorageSetupError::UnderMinimumAllowedSize) => {
+            MODALITY_PROBE_ERROR_INSUFFICIENT_DESTINATION_BYTES
+        }
+        Err(StorageSetupError::ExceededMaximumAddressableSize) => {
+            MODALITY_PROBE_ERROR_EXCEEDED_MAXIMUM_ADDRESSABLE_SIZE
+        }
+    }
+}
+
 /// # Safety
 ///
 /// The ModalityProbe instance pointer must be non-null and point
@@ -144,6 +222,141 @@ pub unsafe fn modality_probe_record_event(
     }
 }
 
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_priority(event_id, priority) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(modality_probe::InvalidEventId) => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_set_overwrite_priority_level(
+    probe: *mut ModalityProbe<'static>,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    probe.set_overwrite_priority_level(priority);
+    MODALITY_PROBE_ERROR_OK
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_set_repeated_event_compression(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    probe.set_repeated_event_compression(enabled);
+    MODALITY_PROBE_ERROR_OK
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_set_checksums(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    probe.set_checksums(enabled);
+    MODALITY_PROBE_ERROR_OK
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_set_report_acknowledgements(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    probe.set_report_acknowledgements(enabled);
+    MODALITY_PROBE_ERROR_OK
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_disable_events(
+    probe: *mut ModalityProbe<'static>,
+    first_event_id: u32,
+    last_event_id: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    let (first, last) = match (EventId::new(first_event_id), EventId::new(last_event_id)) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    };
+    match probe.disable_events(first, last) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(EventFilterFull) => MODALITY_PROBE_ERROR_EVENT_FILTER_FULL,
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_enable_all_events(
+    probe: *mut ModalityProbe<'static>,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    probe.enable_all_events();
+    MODALITY_PROBE_ERROR_OK
+}
+
 /// # Safety
 ///
 /// The ModalityProbe instance pointer must be non-null and point
@@ -165,6 +378,28 @@ pub unsafe fn modality_probe_record_event_with_time(
     }
 }
 
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_time_with_priority(event_id, time_ns, priority) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(e) => with_time_error_to_modality_probe_error(e),
+    }
+}
+
 /// # Safety
 ///
 /// The ModalityProbe instance pointer must be non-null and point
@@ -186,6 +421,28 @@ pub unsafe fn modality_probe_record_event_with_payload(
     }
 }
 
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_payload_with_priority(event_id, payload, priority) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(modality_probe::InvalidEventId) => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    }
+}
+
 /// # Safety
 ///
 /// The ModalityProbe instance pointer must be non-null and point
@@ -194,60 +451,276 @@ pub unsafe fn modality_probe_record_event_with_payload(
 #[cfg_attr(feature = "no_mangle", no_mangle)]
 pub unsafe fn modality_probe_record_event_with_payload_with_time(
     probe: *mut ModalityProbe<'static>,
-    event_id: u32,
-    payload: u32,
-    time_ns: u64,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_payload_with_time(event_id, payload, time_ns) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(e) => with_time_error_to_modality_probe_error(e),
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe
+        .try_record_event_with_payload_with_time_with_priority(event_id, payload, time_ns, priority)
+    {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(e) => with_time_error_to_modality_probe_error(e),
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_wide_payload(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_wide_payload(event_id, payload) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(modality_probe::InvalidEventId) => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_wide_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_wide_payload_with_priority(event_id, payload, priority) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(modality_probe::InvalidEventId) => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_wide_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_wide_payload_with_time(event_id, payload, time_ns) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(e) => with_time_error_to_modality_probe_error(e),
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_record_event_with_wide_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.try_record_event_with_wide_payload_with_time_with_priority(
+        event_id, payload, time_ns, priority,
+    ) {
+        Ok(_) => MODALITY_PROBE_ERROR_OK,
+        Err(e) => with_time_error_to_modality_probe_error(e),
+    }
+}
+
+fn with_time_error_to_modality_probe_error(with_time_error: WithTimeError) -> ModalityProbeError {
+    match with_time_error {
+        WithTimeError::InvalidWallClockTime => MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME,
+        WithTimeError::InvalidEventId => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    }
+}
+
+/// Write a bulk report to the supplied byte destination.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_report(
+    probe: *mut ModalityProbe<'static>,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    if log_report_destination.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    if out_written_bytes.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    match probe.report(core::slice::from_raw_parts_mut(
+        log_report_destination,
+        log_report_destination_size_bytes,
+    )) {
+        Ok(b) => {
+            *out_written_bytes = b.map(|nonzero| nonzero.get()).unwrap_or(0);
+            MODALITY_PROBE_ERROR_OK
+        }
+        Err(e) => report_error_to_modality_probe_error(e),
+    }
+}
+
+/// Write a bulk report to the supplied byte destination, then split it
+/// into fragments of at most `fragment_destination_size_bytes` each,
+/// handing each fragment to `send_fragment` in turn.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_report_fragmented(
+    probe: *mut ModalityProbe<'static>,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    fragment_destination: *mut u8,
+    fragment_destination_size_bytes: usize,
+    send_fragment: Option<report_fragment_fn>,
+    send_fragment_user_state: *mut core::ffi::c_void,
+    out_fragments: *mut usize,
+) -> ModalityProbeError {
+    let probe = match probe.as_mut() {
+        Some(t) => t,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    if log_report_destination.is_null() || fragment_destination.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    if out_fragments.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
+    }
+    let send_fragment = match send_fragment {
+        Some(f) => f,
+        None => return MODALITY_PROBE_ERROR_NULL_POINTER,
+    };
+    match probe.report_fragmented(
+        core::slice::from_raw_parts_mut(log_report_destination, log_report_destination_size_bytes),
+        core::slice::from_raw_parts_mut(fragment_destination, fragment_destination_size_bytes),
+        |fragment| send_fragment(fragment.as_ptr(), fragment.len(), send_fragment_user_state),
+    ) {
+        Ok(n) => {
+            *out_fragments = n.map(|nonzero| nonzero.get()).unwrap_or(0);
+            MODALITY_PROBE_ERROR_OK
+        }
+        Err(e) => report_error_to_modality_probe_error(e),
+    }
+}
+
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance operating in a single-threaded
+/// fashion.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_acknowledge_report(
+    probe: *mut ModalityProbe<'static>,
+    seq_num: u64,
+    out_acknowledged: *mut bool,
 ) -> ModalityProbeError {
     let probe = match probe.as_mut() {
         Some(t) => t,
         None => return MODALITY_PROBE_ERROR_NULL_POINTER,
     };
-    match probe.try_record_event_with_payload_with_time(event_id, payload, time_ns) {
-        Ok(_) => MODALITY_PROBE_ERROR_OK,
-        Err(e) => with_time_error_to_modality_probe_error(e),
-    }
-}
-
-fn with_time_error_to_modality_probe_error(with_time_error: WithTimeError) -> ModalityProbeError {
-    match with_time_error {
-        WithTimeError::InvalidWallClockTime => MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME,
-        WithTimeError::InvalidEventId => MODALITY_PROBE_ERROR_INVALID_EVENT_ID,
+    if out_acknowledged.is_null() {
+        return MODALITY_PROBE_ERROR_NULL_POINTER;
     }
+    *out_acknowledged = probe.acknowledge_report(seq_num);
+    MODALITY_PROBE_ERROR_OK
 }
 
-/// Write a bulk report to the supplied byte destination.
-///
 /// # Safety
 ///
 /// The ModalityProbe instance pointer must be non-null and point
 /// to an initialized instance operating in a single-threaded
 /// fashion.
 #[cfg_attr(feature = "no_mangle", no_mangle)]
-pub unsafe fn modality_probe_report(
+pub unsafe fn modality_probe_consume_report_ack(
     probe: *mut ModalityProbe<'static>,
-    log_report_destination: *mut u8,
-    log_report_destination_size_bytes: usize,
-    out_written_bytes: *mut usize,
+    ack_source: *const u8,
+    ack_source_bytes: usize,
+    out_acknowledged: *mut bool,
 ) -> ModalityProbeError {
     let probe = match probe.as_mut() {
         Some(t) => t,
         None => return MODALITY_PROBE_ERROR_NULL_POINTER,
     };
-    if log_report_destination.is_null() {
+    if ack_source.is_null() {
         return MODALITY_PROBE_ERROR_NULL_POINTER;
     }
-    if out_written_bytes.is_null() {
+    if out_acknowledged.is_null() {
         return MODALITY_PROBE_ERROR_NULL_POINTER;
     }
-    match probe.report(core::slice::from_raw_parts_mut(
-        log_report_destination,
-        log_report_destination_size_bytes,
-    )) {
-        Ok(b) => {
-            *out_written_bytes = b.map(|nonzero| nonzero.get()).unwrap_or(0);
+    match probe.consume_report_ack(core::slice::from_raw_parts(ack_source, ack_source_bytes)) {
+        Ok(acknowledged) => {
+            *out_acknowledged = acknowledged;
             MODALITY_PROBE_ERROR_OK
         }
-        Err(e) => report_error_to_modality_probe_error(e),
+        Err(_) => MODALITY_PROBE_ERROR_INVALID_REPORT_ACK,
     }
 }
 
@@ -394,6 +867,10 @@ fn merge_error_to_modality_probe_error(merge_error: MergeError) -> ModalityProbe
         MergeError::ExternalHistorySemantics => {
             MODALITY_PROBE_ERROR_INVALID_EXTERNAL_HISTORY_SEMANTICS
         }
+        MergeError::UnsupportedSnapshotVersion(_) => {
+            MODALITY_PROBE_ERROR_UNSUPPORTED_SNAPSHOT_VERSION
+        }
+        MergeError::SnapshotChecksumMismatch => MODALITY_PROBE_ERROR_SNAPSHOT_CHECKSUM_MISMATCH,
     }
 }
 
@@ -517,6 +994,453 @@ pub unsafe fn modality_probe_merge_snapshot_bytes_with_time(
     }
 }
 
+/// Same as `modality_probe_record_time`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    time_ns: u64,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_record_time(probe, time_ns)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_record_event(probe, event_id)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => {
+            cs.with(|| modality_probe_record_event_with_priority(probe, event_id, priority))
+        }
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_time`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    time_ns: u64,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_record_event_with_time(probe, event_id, time_ns)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_time_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_time_with_priority(probe, event_id, time_ns, priority)
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_payload`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_payload(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_record_event_with_payload(probe, event_id, payload)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_payload_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_payload_with_priority(
+                probe, event_id, payload, priority,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_payload_with_time`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_payload_with_time(probe, event_id, payload, time_ns)
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_payload_with_time_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_payload_with_time_with_priority(
+                probe, event_id, payload, time_ns, priority,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_wide_payload`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_wide_payload(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => {
+            cs.with(|| modality_probe_record_event_with_wide_payload(probe, event_id, payload))
+        }
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_wide_payload_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_wide_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_wide_payload_with_priority(
+                probe, event_id, payload, priority,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_wide_payload_with_time`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_wide_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_wide_payload_with_time(
+                probe, event_id, payload, time_ns,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_record_event_with_wide_payload_with_time_with_priority`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_record_event_with_wide_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_record_event_with_wide_payload_with_time_with_priority(
+                probe, event_id, payload, time_ns, priority,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_report`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_report(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_report(
+                probe,
+                log_report_destination,
+                log_report_destination_size_bytes,
+                out_written_bytes,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_produce_snapshot`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_produce_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    destination_snapshot: *mut CausalSnapshot,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_produce_snapshot(probe, destination_snapshot)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_produce_snapshot_bytes`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_produce_snapshot_bytes(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    history_destination: *mut u8,
+    history_destination_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_produce_snapshot_bytes(
+                probe,
+                history_destination,
+                history_destination_bytes,
+                out_written_bytes,
+            )
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_merge_snapshot`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_merge_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    snapshot: *const CausalSnapshot,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| modality_probe_merge_snapshot(probe, snapshot)),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
+/// Same as `modality_probe_merge_snapshot_bytes`, but holds `critical_section`
+/// for the duration of the call.
+///
+/// # Safety
+///
+/// The ModalityProbe instance pointer must be non-null and point
+/// to an initialized instance. The critical section pointer must be
+/// non-null, and its lock function must provide exclusive access to
+/// the probe between every context that records to it.
+#[cfg_attr(feature = "no_mangle", no_mangle)]
+pub unsafe fn modality_probe_shared_merge_snapshot_bytes(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    history_source: *const u8,
+    history_source_bytes: usize,
+) -> ModalityProbeError {
+    match critical_section.as_ref() {
+        Some(cs) => cs.with(|| {
+            modality_probe_merge_snapshot_bytes(probe, history_source, history_source_bytes)
+        }),
+        None => MODALITY_PROBE_ERROR_NULL_POINTER,
+    }
+}
+
 /// Capture the ModalityProbe instance's moment in causal time
 /// for correlation with external systems.
 ///
@@ -1,7 +1,7 @@
 #![deny(warnings)]
 
 use core::{mem::MaybeUninit, ptr};
-use modality_probe::{wire::WireReport, LogicalClock, ProbeId};
+use modality_probe::{wire::WireReport, LogicalClock, ProbeId, EVENT_FILTER_LEN};
 use modality_probe_capi_impl::*;
 use proptest::prelude::*;
 
@@ -62,6 +62,63 @@ fn initialization_errors() {
     }
 }
 
+#[test]
+fn recovery_errors() {
+    let probe_id = 1;
+
+    {
+        let mut probe = MaybeUninit::uninit();
+        let err = unsafe {
+            modality_probe_initialize_or_recover(
+                ptr::null_mut(), // NULL storage pointer
+                1024,
+                probe_id,
+                0,
+                0,
+                None,
+                ptr::null_mut(),
+                probe.as_mut_ptr(),
+            )
+        };
+        assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+    }
+
+    {
+        let mut probe = MaybeUninit::uninit();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let err = unsafe {
+            modality_probe_initialize_or_recover(
+                storage.as_mut_ptr(),
+                storage.len(),
+                0, // Invalid probe id
+                0,
+                0,
+                None,
+                ptr::null_mut(),
+                probe.as_mut_ptr(),
+            )
+        };
+        assert_eq!(MODALITY_PROBE_ERROR_INVALID_PROBE_ID, err);
+    }
+
+    {
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let err = unsafe {
+            modality_probe_initialize_or_recover(
+                storage.as_mut_ptr(),
+                storage.len(),
+                probe_id,
+                0,
+                0,
+                None,
+                ptr::null_mut(),
+                ptr::null_mut(), // NULL probe pointer
+            )
+        };
+        assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+    }
+}
+
 #[test]
 fn event_recording_errors() {
     let err = unsafe { modality_probe_record_event(ptr::null_mut(), 100) };
@@ -70,6 +127,69 @@ fn event_recording_errors() {
     let err = unsafe { modality_probe_record_event_with_payload(ptr::null_mut(), 100, 123) };
     assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
 
+    let err = unsafe { modality_probe_record_event_with_wide_payload(ptr::null_mut(), 100, 123) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_record_event_with_priority(ptr::null_mut(), 100, 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe {
+        modality_probe_record_event_with_payload_with_priority(ptr::null_mut(), 100, 1, 1)
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_set_overwrite_priority_level(ptr::null_mut(), 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_set_repeated_event_compression(ptr::null_mut(), true) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_set_checksums(ptr::null_mut(), true) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let mut report_buffer = [0u8; 256];
+    let mut fragment_buffer = [0u8; 64];
+    let mut fragments: usize = 0;
+    let err = unsafe {
+        modality_probe_report_fragmented(
+            ptr::null_mut(),
+            report_buffer.as_mut_ptr(),
+            report_buffer.len(),
+            fragment_buffer.as_mut_ptr(),
+            fragment_buffer.len(),
+            None,
+            ptr::null_mut(),
+            &mut fragments as *mut usize,
+        )
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_set_report_acknowledgements(ptr::null_mut(), true) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let mut acknowledged = false;
+    let err = unsafe {
+        modality_probe_acknowledge_report(ptr::null_mut(), 0, &mut acknowledged as *mut bool)
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let ack = [0u8; 16];
+    let err = unsafe {
+        modality_probe_consume_report_ack(
+            ptr::null_mut(),
+            ack.as_ptr(),
+            ack.len(),
+            &mut acknowledged as *mut bool,
+        )
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_disable_events(ptr::null_mut(), 1, 2) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_enable_all_events(ptr::null_mut()) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
     let probe_id = 1;
     let mut probe = MaybeUninit::uninit();
     let mut storage = [MaybeUninit::new(0u8); 512];
@@ -94,15 +214,44 @@ fn event_recording_errors() {
     let err = unsafe { modality_probe_record_event_with_payload(probe, 0, 0) };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
 
-    let err = unsafe { modality_probe_record_time(probe, core::u64::MAX) };
+    let err = unsafe { modality_probe_record_event_with_wide_payload(probe, 0, 0) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
+
+    let err = unsafe { modality_probe_record_event_with_priority(probe, 0, 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
+
+    let err =
+        unsafe { modality_probe_record_event_with_wide_payload_with_priority(probe, 0, 0, 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
+
+    let err = unsafe {
+        modality_probe_record_event_with_payload_with_time_with_priority(probe, 1, 2, u64::MAX, 1)
+    };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
 
-    let err = unsafe { modality_probe_record_event_with_time(probe, 1, core::u64::MAX) };
+    let err = unsafe { modality_probe_record_time(probe, u64::MAX) };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
 
-    let err =
-        unsafe { modality_probe_record_event_with_payload_with_time(probe, 1, 2, core::u64::MAX) };
+    let err = unsafe { modality_probe_record_event_with_time(probe, 1, u64::MAX) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
+
+    let err = unsafe { modality_probe_record_event_with_payload_with_time(probe, 1, 2, u64::MAX) };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
+
+    let err = unsafe { modality_probe_disable_events(probe, 0, 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
+
+    for _ in 0..EVENT_FILTER_LEN {
+        let err = unsafe { modality_probe_disable_events(probe, 1, 2) };
+        assert_eq!(MODALITY_PROBE_ERROR_OK, err);
+    }
+    let err = unsafe { modality_probe_disable_events(probe, 1, 2) };
+    assert_eq!(MODALITY_PROBE_ERROR_EVENT_FILTER_FULL, err);
+
+    let err = unsafe { modality_probe_enable_all_events(probe) };
+    assert_eq!(MODALITY_PROBE_ERROR_OK, err);
+    let err = unsafe { modality_probe_disable_events(probe, 1, 2) };
+    assert_eq!(MODALITY_PROBE_ERROR_OK, err);
 }
 
 #[test]
@@ -243,7 +392,7 @@ fn produce_snapshot_errors() {
     let err = unsafe {
         modality_probe_produce_snapshot_with_time(
             probe,
-            core::u64::MAX, // Invalid time
+            u64::MAX, // Invalid time
             &mut snapshot as *mut _,
         )
     };
@@ -282,7 +431,7 @@ fn produce_snapshot_errors() {
     let err = unsafe {
         modality_probe_produce_snapshot_bytes_with_time(
             probe,
-            core::u64::MAX, // Invalid time
+            u64::MAX, // Invalid time
             snapshot_bytes.as_mut_ptr(),
             snapshot_bytes.len(),
             &mut bytes_written as *mut usize,
@@ -351,7 +500,7 @@ fn merge_snapshot_errors() {
         modality_probe_merge_snapshot_with_time(
             probe,
             &snapshot as *const _,
-            core::u64::MAX, // Invalid time
+            u64::MAX, // Invalid time
         )
     };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
@@ -379,7 +528,7 @@ fn merge_snapshot_errors() {
             probe,
             snapshot_bytes.as_ptr(),
             snapshot_bytes.len(),
-            core::u64::MAX, // Invalid time
+            u64::MAX, // Invalid time
         )
     };
     assert_eq!(MODALITY_PROBE_ERROR_INVALID_WALL_CLOCK_TIME, err);
@@ -394,6 +543,71 @@ fn now_errors() {
     assert_eq!(now.event_count, 0);
 }
 
+#[test]
+fn shared_recording_holds_critical_section() {
+    extern "C" fn lock(state: *mut core::ffi::c_void) {
+        let depth = unsafe { &mut *(state as *mut i32) };
+        assert_eq!(*depth, 0);
+        *depth += 1;
+    }
+    extern "C" fn unlock(state: *mut core::ffi::c_void) {
+        let depth = unsafe { &mut *(state as *mut i32) };
+        assert_eq!(*depth, 1);
+        *depth -= 1;
+    }
+
+    let mut depth = 0_i32;
+    let cs = CCriticalSection {
+        lock,
+        unlock,
+        state: &mut depth as *mut i32 as *mut core::ffi::c_void,
+    };
+
+    let mut probe = MaybeUninit::uninit();
+    let mut storage = [MaybeUninit::new(0u8); 512];
+    let err = unsafe {
+        modality_probe_initialize(
+            storage.as_mut_ptr(),
+            storage.len(),
+            1,
+            0,
+            0,
+            None,
+            ptr::null_mut(),
+            probe.as_mut_ptr(),
+        )
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_OK, err);
+    let probe = unsafe { probe.assume_init() };
+
+    let err = unsafe { modality_probe_shared_record_event(probe, ptr::null(), 100) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_shared_record_event(ptr::null_mut(), &cs, 100) };
+    assert_eq!(MODALITY_PROBE_ERROR_NULL_POINTER, err);
+
+    let err = unsafe { modality_probe_shared_record_event(probe, &cs, 0) };
+    assert_eq!(MODALITY_PROBE_ERROR_INVALID_EVENT_ID, err);
+
+    let err = unsafe { modality_probe_shared_record_event_with_payload(probe, &cs, 100, 1) };
+    assert_eq!(MODALITY_PROBE_ERROR_OK, err);
+
+    let mut report_buffer = [0u8; 256];
+    let mut bytes_written = 0;
+    let err = unsafe {
+        modality_probe_shared_report(
+            probe,
+            &cs,
+            report_buffer.as_mut_ptr(),
+            report_buffer.len(),
+            &mut bytes_written,
+        )
+    };
+    assert_eq!(MODALITY_PROBE_ERROR_OK, err);
+    assert_ne!(bytes_written, 0);
+    assert_eq!(depth, 0);
+}
+
 proptest! {
     #[test]
     fn probe_reporting(
@@ -3,7 +3,8 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 use core::mem::MaybeUninit;
 pub use modality_probe_capi_impl::{
-    next_sequence_id_fn, CausalSnapshot, ModalityProbe, ModalityProbeError, ModalityProbeInstant,
+    next_sequence_id_fn, report_fragment_fn, CCriticalSection, CausalSnapshot, ModalityProbe,
+    ModalityProbeError, ModalityProbeInstant,
 };
 
 #[no_mangle]
@@ -31,6 +32,31 @@ pub extern "C" fn modality_probe_initialize(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_initialize_or_recover(
+    destination: *mut MaybeUninit<u8>,
+    destination_size_bytes: usize,
+    probe_id: u32,
+    time_resolution_ns: u32,
+    wall_clock_id: u16,
+    next_sequence_id: Option<next_sequence_id_fn>,
+    next_sequence_id_user_state: *mut core::ffi::c_void,
+    out: *mut *mut ModalityProbe<'static>,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_initialize_or_recover(
+            destination,
+            destination_size_bytes,
+            probe_id,
+            time_resolution_ns,
+            wall_clock_id,
+            next_sequence_id,
+            next_sequence_id_user_state,
+            out,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_time(
     probe: *mut ModalityProbe<'static>,
@@ -47,6 +73,77 @@ pub extern "C" fn modality_probe_record_event(
     unsafe { modality_probe_capi_impl::modality_probe_record_event(probe, event_id) }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_priority(
+            probe, event_id, priority,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_set_overwrite_priority_level(
+    probe: *mut ModalityProbe<'static>,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_set_overwrite_priority_level(probe, priority)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_set_repeated_event_compression(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_set_repeated_event_compression(probe, enabled)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_set_checksums(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_set_checksums(probe, enabled) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_set_report_acknowledgements(
+    probe: *mut ModalityProbe<'static>,
+    enabled: bool,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_set_report_acknowledgements(probe, enabled) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_disable_events(
+    probe: *mut ModalityProbe<'static>,
+    first_event_id: u32,
+    last_event_id: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_disable_events(
+            probe,
+            first_event_id,
+            last_event_id,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_enable_all_events(
+    probe: *mut ModalityProbe<'static>,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_enable_all_events(probe) }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_time(
     probe: *mut ModalityProbe<'static>,
@@ -58,6 +155,16 @@ pub extern "C" fn modality_probe_record_event_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_record_event_with_time_with_priority(probe, event_id, time_ns, priority) }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload(
     probe: *mut ModalityProbe<'static>,
@@ -69,6 +176,16 @@ pub extern "C" fn modality_probe_record_event_with_payload(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(probe, event_id, payload, priority) }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_with_time(
     probe: *mut ModalityProbe<'static>,
@@ -83,6 +200,17 @@ pub extern "C" fn modality_probe_record_event_with_payload_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_record_event_with_payload_with_time_with_priority(probe, event_id, payload, time_ns, priority) }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_i8(
     probe: *mut ModalityProbe<'static>,
@@ -115,6 +243,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_i8_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_i8_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: i8,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_u8(
     probe: *mut ModalityProbe<'static>,
@@ -147,6 +292,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_u8_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_u8_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u8,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_i16(
     probe: *mut ModalityProbe<'static>,
@@ -179,6 +341,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_i16_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_i16_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: i16,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_u16(
     probe: *mut ModalityProbe<'static>,
@@ -211,6 +390,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_u16_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_u16_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u16,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_i32(
     probe: *mut ModalityProbe<'static>,
@@ -243,6 +439,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_i32_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_i32_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: i32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_u32(
     probe: *mut ModalityProbe<'static>,
@@ -268,6 +481,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_u32_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_u32_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_bool(
     probe: *mut ModalityProbe<'static>,
@@ -300,6 +530,23 @@ pub extern "C" fn modality_probe_record_event_with_payload_bool_with_time(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_bool_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: bool,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn modality_probe_record_event_with_payload_f32(
     probe: *mut ModalityProbe<'static>,
@@ -333,96 +580,357 @@ pub extern "C" fn modality_probe_record_event_with_payload_f32_with_time(
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_report(
+pub extern "C" fn modality_probe_record_event_with_payload_f32_with_priority(
     probe: *mut ModalityProbe<'static>,
-    log_report_destination: *mut u8,
-    log_report_destination_size_bytes: usize,
-    out_written_bytes: *mut usize,
+    event_id: u32,
+    payload: f32,
+    priority: u32,
 ) -> ModalityProbeError {
     unsafe {
-        modality_probe_capi_impl::modality_probe_report(
+        modality_probe_capi_impl::modality_probe_record_event_with_payload_with_priority(
             probe,
-            log_report_destination,
-            log_report_destination_size_bytes,
-            out_written_bytes,
+            event_id,
+            payload.to_bits(),
+            priority,
         )
     }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_produce_snapshot(
+pub extern "C" fn modality_probe_record_event_with_wide_payload(
     probe: *mut ModalityProbe<'static>,
-    destination_snapshot: *mut CausalSnapshot,
+    event_id: u32,
+    payload: u64,
 ) -> ModalityProbeError {
     unsafe {
-        modality_probe_capi_impl::modality_probe_produce_snapshot(probe, destination_snapshot)
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload(
+            probe, event_id, payload,
+        )
     }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_produce_snapshot_with_time(
+pub extern "C" fn modality_probe_record_event_with_wide_payload_with_priority(
     probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_priority(probe, event_id, payload, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_wide_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
     time_ns: u64,
-    destination_snapshot: *mut CausalSnapshot,
 ) -> ModalityProbeError {
     unsafe {
-        modality_probe_capi_impl::modality_probe_produce_snapshot_with_time(
-            probe,
-            time_ns,
-            destination_snapshot,
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_time(
+            probe, event_id, payload, time_ns,
         )
     }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_produce_snapshot_bytes(
+pub extern "C" fn modality_probe_record_event_with_wide_payload_with_time_with_priority(
     probe: *mut ModalityProbe<'static>,
-    history_destination: *mut u8,
-    history_destination_bytes: usize,
-    out_written_bytes: *mut usize,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_time_with_priority(probe, event_id, payload, time_ns, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_i64(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: i64,
 ) -> ModalityProbeError {
     unsafe {
-        modality_probe_capi_impl::modality_probe_produce_snapshot_bytes(
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload(
             probe,
-            history_destination,
-            history_destination_bytes,
-            out_written_bytes,
+            event_id,
+            payload as _,
         )
     }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_produce_snapshot_bytes_with_time(
+pub extern "C" fn modality_probe_record_event_with_payload_i64_with_time(
     probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: i64,
     time_ns: u64,
-    history_destination: *mut u8,
-    history_destination_bytes: usize,
-    out_written_bytes: *mut usize,
 ) -> ModalityProbeError {
     unsafe {
-        modality_probe_capi_impl::modality_probe_produce_snapshot_bytes_with_time(
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_time(
             probe,
+            event_id,
+            payload as _,
             time_ns,
-            history_destination,
-            history_destination_bytes,
-            out_written_bytes,
         )
     }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_merge_snapshot(
+pub extern "C" fn modality_probe_record_event_with_payload_i64_with_priority(
     probe: *mut ModalityProbe<'static>,
-    snapshot: *const CausalSnapshot,
+    event_id: u32,
+    payload: i64,
+    priority: u32,
 ) -> ModalityProbeError {
-    unsafe { modality_probe_capi_impl::modality_probe_merge_snapshot(probe, snapshot) }
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_priority(
+            probe,
+            event_id,
+            payload as _,
+            priority,
+        )
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn modality_probe_merge_snapshot_with_time(
+pub extern "C" fn modality_probe_record_event_with_payload_u64(
     probe: *mut ModalityProbe<'static>,
-    snapshot: *const CausalSnapshot,
-    time_ns: u64,
+    event_id: u32,
+    payload: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload(
+            probe, event_id, payload,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_u64_with_time(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_time(
+            probe, event_id, payload, time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_u64_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_priority(
+            probe,
+            event_id,
+            payload,
+            priority,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_f64(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: f64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload(
+            probe,
+            event_id,
+            payload.to_bits(),
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_f64_with_time(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: f64,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_time(
+            probe,
+            event_id,
+            payload.to_bits(),
+            time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_record_event_with_payload_f64_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    event_id: u32,
+    payload: f64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_record_event_with_wide_payload_with_priority(
+            probe,
+            event_id,
+            payload.to_bits(),
+            priority,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_report(
+    probe: *mut ModalityProbe<'static>,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_report(
+            probe,
+            log_report_destination,
+            log_report_destination_size_bytes,
+            out_written_bytes,
+        )
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn modality_probe_report_fragmented(
+    probe: *mut ModalityProbe<'static>,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    fragment_destination: *mut u8,
+    fragment_destination_size_bytes: usize,
+    send_fragment: Option<report_fragment_fn>,
+    send_fragment_user_state: *mut core::ffi::c_void,
+    out_fragments: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_report_fragmented(
+            probe,
+            log_report_destination,
+            log_report_destination_size_bytes,
+            fragment_destination,
+            fragment_destination_size_bytes,
+            send_fragment,
+            send_fragment_user_state,
+            out_fragments,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_acknowledge_report(
+    probe: *mut ModalityProbe<'static>,
+    seq_num: u64,
+    out_acknowledged: *mut bool,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_acknowledge_report(probe, seq_num, out_acknowledged)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_consume_report_ack(
+    probe: *mut ModalityProbe<'static>,
+    ack_source: *const u8,
+    ack_source_bytes: usize,
+    out_acknowledged: *mut bool,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_consume_report_ack(
+            probe,
+            ack_source,
+            ack_source_bytes,
+            out_acknowledged,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_produce_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    destination_snapshot: *mut CausalSnapshot,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_produce_snapshot(probe, destination_snapshot)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_produce_snapshot_with_time(
+    probe: *mut ModalityProbe<'static>,
+    time_ns: u64,
+    destination_snapshot: *mut CausalSnapshot,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_produce_snapshot_with_time(
+            probe,
+            time_ns,
+            destination_snapshot,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_produce_snapshot_bytes(
+    probe: *mut ModalityProbe<'static>,
+    history_destination: *mut u8,
+    history_destination_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_produce_snapshot_bytes(
+            probe,
+            history_destination,
+            history_destination_bytes,
+            out_written_bytes,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_produce_snapshot_bytes_with_time(
+    probe: *mut ModalityProbe<'static>,
+    time_ns: u64,
+    history_destination: *mut u8,
+    history_destination_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_produce_snapshot_bytes_with_time(
+            probe,
+            time_ns,
+            history_destination,
+            history_destination_bytes,
+            out_written_bytes,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_merge_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    snapshot: *const CausalSnapshot,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_merge_snapshot(probe, snapshot) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_merge_snapshot_with_time(
+    probe: *mut ModalityProbe<'static>,
+    snapshot: *const CausalSnapshot,
+    time_ns: u64,
 ) -> ModalityProbeError {
     unsafe {
         modality_probe_capi_impl::modality_probe_merge_snapshot_with_time(probe, snapshot, time_ns)
@@ -466,6 +974,284 @@ pub extern "C" fn modality_probe_now(probe: *mut ModalityProbe<'static>) -> Moda
     unsafe { modality_probe_capi_impl::modality_probe_now(probe) }
 }
 
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_time(
+            probe,
+            critical_section,
+            time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event(
+            probe,
+            critical_section,
+            event_id,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_priority(
+            probe,
+            critical_section,
+            event_id,
+            priority,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_time(
+            probe,
+            critical_section,
+            event_id,
+            time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_shared_record_event_with_time_with_priority(probe, critical_section, event_id, time_ns, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_payload(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_payload(
+            probe,
+            critical_section,
+            event_id,
+            payload,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_shared_record_event_with_payload_with_priority(probe, critical_section, event_id, payload, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_payload_with_time(
+            probe,
+            critical_section,
+            event_id,
+            payload,
+            time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u32,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_shared_record_event_with_payload_with_time_with_priority(probe, critical_section, event_id, payload, time_ns, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_wide_payload(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_wide_payload(
+            probe,
+            critical_section,
+            event_id,
+            payload,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_wide_payload_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_shared_record_event_with_wide_payload_with_priority(probe, critical_section, event_id, payload, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_wide_payload_with_time(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_record_event_with_wide_payload_with_time(
+            probe,
+            critical_section,
+            event_id,
+            payload,
+            time_ns,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_record_event_with_wide_payload_with_time_with_priority(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    event_id: u32,
+    payload: u64,
+    time_ns: u64,
+    priority: u32,
+) -> ModalityProbeError {
+    unsafe { modality_probe_capi_impl::modality_probe_shared_record_event_with_wide_payload_with_time_with_priority(probe, critical_section, event_id, payload, time_ns, priority) }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_report(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    log_report_destination: *mut u8,
+    log_report_destination_size_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_report(
+            probe,
+            critical_section,
+            log_report_destination,
+            log_report_destination_size_bytes,
+            out_written_bytes,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_produce_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    destination_snapshot: *mut CausalSnapshot,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_produce_snapshot(
+            probe,
+            critical_section,
+            destination_snapshot,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_produce_snapshot_bytes(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    history_destination: *mut u8,
+    history_destination_bytes: usize,
+    out_written_bytes: *mut usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_produce_snapshot_bytes(
+            probe,
+            critical_section,
+            history_destination,
+            history_destination_bytes,
+            out_written_bytes,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_merge_snapshot(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    snapshot: *const CausalSnapshot,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_merge_snapshot(
+            probe,
+            critical_section,
+            snapshot,
+        )
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn modality_probe_shared_merge_snapshot_bytes(
+    probe: *mut ModalityProbe<'static>,
+    critical_section: *const CCriticalSection,
+    history_source: *const u8,
+    history_source_bytes: usize,
+) -> ModalityProbeError {
+    unsafe {
+        modality_probe_capi_impl::modality_probe_shared_merge_snapshot_bytes(
+            probe,
+            critical_section,
+            history_source,
+            history_source_bytes,
+        )
+    }
+}
+
 #[cfg(not(test))]
 #[panic_handler]
 pub fn modality_probe_default_panic_abort(_info: &core::panic::PanicInfo) -> ! {
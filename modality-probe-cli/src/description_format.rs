@@ -3,7 +3,7 @@ use dynfmt::{Argument, Format, SimpleCurlyFormat};
 pub trait DescriptionFormat {
     fn contains_formatting(&self) -> bool;
 
-    fn format_payload(&self, payload: Argument) -> Result<String, dynfmt::Error>;
+    fn format_payload(&self, payload: Argument) -> Result<String, dynfmt::Error<'_>>;
 }
 
 impl<T: AsRef<str>> DescriptionFormat for T {
@@ -11,8 +11,8 @@ impl<T: AsRef<str>> DescriptionFormat for T {
         self.as_ref().contains("{}")
     }
 
-    fn format_payload(&self, payload: Argument) -> Result<String, dynfmt::Error> {
-        let cow = SimpleCurlyFormat.format(self.as_ref(), &[payload])?;
+    fn format_payload(&self, payload: Argument) -> Result<String, dynfmt::Error<'_>> {
+        let cow = SimpleCurlyFormat.format(self.as_ref(), [payload])?;
         Ok(cow.to_string())
     }
 }
@@ -22,7 +22,7 @@ mod tests {
     use super::*;
     use proptest::prelude::*;
 
-    const DESC_STR: &'static str = "My event payload = {} other stuff";
+    const DESC_STR: &str = "My event payload = {} other stuff";
 
     fn desc_string() -> String {
         DESC_STR.to_string()
@@ -30,10 +30,10 @@ mod tests {
 
     #[test]
     fn does_not_contain_formatting() {
-        assert_eq!("no formatting here".contains_formatting(), false);
-        assert_eq!("this {   } stuff".contains_formatting(), false);
-        assert_eq!("nothing {{{{".contains_formatting(), false);
-        assert_eq!("}}}} here".contains_formatting(), false);
+        assert!(!"no formatting here".contains_formatting());
+        assert!(!"this {   } stuff".contains_formatting());
+        assert!(!"nothing {{{{".contains_formatting());
+        assert!(!"}}}} here".contains_formatting());
     }
 
     #[test]
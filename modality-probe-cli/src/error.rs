@@ -68,14 +68,14 @@ macro_rules! give_up {
         return Err(Box::new($crate::error::CmdError {
             msg: format!("{}", $msg),
             src: None,
-        }));
+        }))
     };
 
     ($msg:expr, $src:expr) => {
         return Err(Box::new($crate::error::CmdError {
             msg: format!("{}", $msg),
             src: $src,
-        }));
+        }))
     };
 }
 
@@ -27,6 +27,10 @@ pub struct Event {
     pub type_hint: String,
     pub file: String,
     pub line: String,
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
+    pub span: String,
 }
 
 impl Event {
@@ -36,6 +40,13 @@ impl Event {
         state.update(self.name.as_bytes());
         state.update(self.tags.as_bytes());
         state.update(self.type_hint.as_bytes());
+        // Only included when present to keep existing component hashes stable
+        if !self.priority.is_empty() {
+            state.update(self.priority.as_bytes());
+        }
+        if !self.span.is_empty() {
+            state.update(self.span.as_bytes());
+        }
     }
 }
 
@@ -61,6 +72,8 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -74,6 +87,8 @@ impl Events {
                 type_hint: "u32".to_string(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -85,6 +100,8 @@ impl Events {
                 type_hint: "u32".to_string(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -98,6 +115,8 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -112,6 +131,8 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -122,6 +143,8 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -135,6 +158,8 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
             Event {
                 component_id,
@@ -145,6 +170,62 @@ impl Events {
                 type_hint: String::new(),
                 file: String::new(),
                 line: String::new(),
+                priority: String::new(),
+                span: String::new(),
+            },
+            Event {
+                component_id,
+                id: EventId(modality_probe::EventId::EVENT_PREVIOUS_EVENT_REPEATED.get_raw()),
+                name: "MODALITY_PREVIOUS_EVENT_REPEATED".to_string(),
+                description: "The preceding event was recorded again consecutively, the number \
+                    of additional occurrences is stored in the payload"
+                    .to_string(),
+                tags: "INTERNAL".to_string(),
+                type_hint: "u32".to_string(),
+                file: String::new(),
+                line: String::new(),
+                priority: String::new(),
+                span: String::new(),
+            },
+            Event {
+                component_id,
+                id: EventId(modality_probe::EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS.get_raw()),
+                name: "MODALITY_WIDE_PAYLOAD_HIGH_BITS".to_string(),
+                description: "Carries the upper 32 bits of a 64-bit payload, the rest of which \
+                    is stored in the event with payload entry that follows"
+                    .to_string(),
+                tags: "INTERNAL".to_string(),
+                type_hint: "u32".to_string(),
+                file: String::new(),
+                line: String::new(),
+                priority: String::new(),
+                span: String::new(),
+            },
+            Event {
+                component_id,
+                id: EventId(modality_probe::EventId::EVENT_BLOB_DATA.get_raw()),
+                name: "MODALITY_BLOB_DATA".to_string(),
+                description: "Carries up to 4 bytes of a blob payload, the length of which \
+                    is stored in the event with payload entry that follows"
+                    .to_string(),
+                tags: "INTERNAL".to_string(),
+                type_hint: "u32".to_string(),
+                file: String::new(),
+                line: String::new(),
+                priority: String::new(),
+                span: String::new(),
+            },
+            Event {
+                component_id,
+                id: EventId(modality_probe::EventId::EVENT_PROBE_RECOVERED.get_raw()),
+                name: "MODALITY_PROBE_RECOVERED".to_string(),
+                description: "The probe recovered the log it had before a reset".to_string(),
+                tags: "INTERNAL".to_string(),
+                type_hint: String::new(),
+                file: String::new(),
+                line: String::new(),
+                priority: String::new(),
+                span: String::new(),
             },
         ]
     }
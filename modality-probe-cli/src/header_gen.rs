@@ -7,6 +7,7 @@ use crate::{
 };
 use modality_probe::{EventId, ProbeId};
 use sha3::{Digest, Sha3_256};
+use std::fmt;
 use std::fs::File;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -184,9 +185,9 @@ fn pad_nonempty(s: &str) -> String {
 #[derive(Debug)]
 pub struct UnsupportedLang(String);
 
-impl ToString for UnsupportedLang {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
+impl fmt::Display for UnsupportedLang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UnsupportedLang({:?})", self.0)
     }
 }
 
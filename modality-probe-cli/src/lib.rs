@@ -1,3 +1,6 @@
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
+
 pub mod component;
 pub mod description_format;
 pub mod error;
@@ -9,4 +12,5 @@ pub mod manifest_gen;
 pub mod meta;
 pub mod opts;
 pub mod probes;
+pub mod span;
 pub mod visualize;
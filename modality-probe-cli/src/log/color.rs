@@ -27,7 +27,7 @@ pub(crate) fn colorize_info(key: &str, val: &str) -> String {
     if COLORIZE.read().map(|b| *b).unwrap_or(false) {
         format!("{} {}", format!("{}:", key).white(), val.yellow())
     } else {
-        format!("{} {}", format!("{}:", key), val)
+        format!("{}: {}", key, val)
     }
 }
 
@@ -42,6 +42,12 @@ pub(crate) fn format(cfg: &Cfg, row: &ReportLogEntry, fmt: &str) -> String {
             LogEntryData::EventWithPayload(id, _) => meta::get_event_meta(cfg, &row.probe_id, &id)
                 .map(|e| e.description.clone())
                 .unwrap_or_else(|_| String::new()),
+            LogEntryData::EventWithBlob(id, _) => meta::get_event_meta(cfg, &row.probe_id, &id)
+                .map(|e| e.description.clone())
+                .unwrap_or_else(|_| String::new()),
+            LogEntryData::EventRepeated(id, _) => meta::get_event_meta(cfg, &row.probe_id, &id)
+                .map(|e| e.description.clone())
+                .unwrap_or_else(|_| String::new()),
             _ => String::new(),
         };
         format!(
@@ -61,6 +67,8 @@ fn event_id(_: &Cfg, row: &ReportLogEntry) -> String {
     match row.data {
         LogEntryData::Event(id) => format!("{}", id.get_raw()),
         LogEntryData::EventWithPayload(id, _) => format!("{}", id.get_raw()),
+        LogEntryData::EventWithBlob(id, _) => format!("{}", id.get_raw()),
+        LogEntryData::EventRepeated(id, _) => format!("{}", id.get_raw()),
         _ => String::new(),
     }
 }
@@ -69,6 +77,8 @@ fn event_name(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
@@ -80,6 +90,8 @@ fn event_file(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
@@ -91,6 +103,8 @@ fn event_line(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
@@ -102,6 +116,8 @@ fn event_tags(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
@@ -113,6 +129,8 @@ fn event_description(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
@@ -124,37 +142,41 @@ fn event_type_hint(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let eid = match row.data {
         LogEntryData::Event(id) => id,
         LogEntryData::EventWithPayload(id, _) => id,
+        LogEntryData::EventWithBlob(id, _) => id,
+        LogEntryData::EventRepeated(id, _) => id,
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
         .ok()
-        .map(|e| e.type_hint.as_ref().cloned())
-        .flatten()
-        .unwrap_or_else(String::new)
+        .and_then(|e| e.type_hint.as_ref().cloned())
+        .unwrap_or_default()
 }
 
 fn event_payload(cfg: &Cfg, row: &ReportLogEntry) -> String {
     let (eid, pl) = match row.data {
         LogEntryData::Event(id) => (id, None),
         LogEntryData::EventWithPayload(id, pl) => (id, Some(pl)),
+        LogEntryData::EventWithBlob(_, ref blob) => return meta::blob_payload(blob),
         _ => return String::new(),
     };
     meta::get_event_meta(cfg, &row.probe_id, &eid)
         .ok()
-        .map(|e| {
+        .and_then(|e| {
             meta::parsed_payload(e.type_hint.as_ref().map(|s| s.as_ref()), pl)
                 .ok()
                 .flatten()
         })
-        .flatten()
-        .unwrap_or_else(String::new)
+        .unwrap_or_default()
 }
 
 fn raw_event_payload(_: &Cfg, row: &ReportLogEntry) -> String {
-    if let LogEntryData::EventWithPayload(_, pl) = row.data {
-        format!("{}", pl)
-    } else {
-        String::new()
+    match row.data {
+        LogEntryData::EventWithPayload(_, pl) => format!("{}", pl),
+        LogEntryData::EventWithBlob(_, ref blob) => {
+            blob.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+        LogEntryData::EventRepeated(_, count) => format!("{}", count),
+        _ => String::new(),
     }
 }
 
@@ -47,15 +47,24 @@ pub(super) fn print_as_graph<W: WriteIo>(
         for (idx, (probe_id, log)) in probes.iter_mut().enumerate() {
             if let Some(row) = log.pop() {
                 match row.data {
-                    LogEntryData::Event(id) | LogEntryData::EventWithTime(.., id) => {
+                    LogEntryData::Event(id)
+                    | LogEntryData::EventWithTime(.., id)
+                    | LogEntryData::EventWithBlob(id, _)
+                    | LogEntryData::EventRepeated(id, _) => {
                         let blocked = blocked_tls
                             .get(probe_id)
                             .map(|t| !t.is_empty())
                             .unwrap_or(false);
                         if !blocked {
-                            let event_name = meta::get_event_meta(&cfg, &probe_id, &id)
+                            let event_name = meta::get_event_meta(cfg, probe_id, &id)
                                 .map(|em| em.name.clone())
                                 .unwrap_or_else(|_| probe_id.get_raw().to_string());
+                            let event_name = match row.data {
+                                LogEntryData::EventRepeated(_, n) => {
+                                    format!("{} repeated {} more times", event_name, n)
+                                }
+                                _ => event_name,
+                            };
                             let probe_name = cfg
                                 .probes
                                 .get(&probe_id.get_raw())
@@ -104,7 +113,7 @@ pub(super) fn print_as_graph<W: WriteIo>(
                             .map(|t| !t.is_empty())
                             .unwrap_or(false);
                         if !blocked {
-                            let event_meta = meta::get_event_meta(&cfg, &probe_id, &id);
+                            let event_meta = meta::get_event_meta(cfg, probe_id, &id);
                             let event_name = event_meta
                                 .as_ref()
                                 .map(|em| em.name.clone())
@@ -247,9 +256,7 @@ pub(super) fn print_as_graph<W: WriteIo>(
                                             && r.data.trace_clock() == Some(lc.next())
                                     });
                                     if is_present {
-                                        let self_tl = blocked_tls
-                                            .entry(*probe_id)
-                                            .or_insert_with(HashSet::new);
+                                        let self_tl = blocked_tls.entry(*probe_id).or_default();
                                         self_tl.insert((lc.id, lc));
                                     } else {
                                         print_missing_edge_line(
@@ -269,8 +276,7 @@ pub(super) fn print_as_graph<W: WriteIo>(
                                     lc.id == r.probe_id && r.data.trace_clock() == Some(lc.next())
                                 });
                                 if is_present {
-                                    let self_tl =
-                                        blocked_tls.entry(*probe_id).or_insert_with(HashSet::new);
+                                    let self_tl = blocked_tls.entry(*probe_id).or_default();
                                     self_tl.insert((lc.id, lc));
                                 } else {
                                     print_missing_edge_line(
@@ -287,6 +293,33 @@ pub(super) fn print_as_graph<W: WriteIo>(
                             }
                         }
                     }
+                    LogEntryData::ProbeRestarted => {
+                        let blocked = blocked_tls
+                            .get(probe_id)
+                            .map(|t| !t.is_empty())
+                            .unwrap_or(false);
+                        if !blocked {
+                            let probe_name = cfg
+                                .probes
+                                .get(&probe_id.get_raw())
+                                .map(|pm| pm.name.clone())
+                                .unwrap_or_else(|| probe_id.get_raw().to_string());
+                            print_restart_row(
+                                &format!(
+                                    "{} {} {}",
+                                    color::white("restarted"),
+                                    color::colorize_probe(idx, &format!("@ {}", probe_name)),
+                                    color::colorize_coord(&row.coordinate())
+                                ),
+                                idx,
+                                n_probes,
+                                &mut stream,
+                            )?;
+                            print_info_row(n_probes, "", "", "", &mut stream)?;
+                        } else {
+                            log.push(row);
+                        }
+                    }
                     _ => (),
                 }
                 count += 1;
@@ -304,71 +337,75 @@ fn handle_graph_verbosity<W: WriteIo>(
     probe_id: &ProbeId,
     eid: &EventId,
     n_probes: usize,
-    pl: Option<u32>,
+    pl: Option<u64>,
     cfg: &Cfg,
     mut stream: W,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let event_meta = meta::get_event_meta(&cfg, &probe_id, &eid);
+    let event_meta = meta::get_event_meta(cfg, probe_id, eid);
     let probe_meta = cfg.probes.get(&probe_id.get_raw());
-    if l.verbose != 0 && event_meta.is_ok() {
-        let emeta = event_meta.expect("just checked that event meta is_some");
-        print_info_row(
-            n_probes,
-            "description",
-            &emeta.description,
-            "    ",
-            &mut stream,
-        )?;
-        // TODO(dan@auxon.io): Interpolate log-style payload / string
-        // combos here if they're present.
-        // https://github.com/auxoncorp/modality-probe/issues/281
-        if let Some(pp) = meta::parsed_payload(emeta.type_hint.as_ref().map(|s| s.as_ref()), pl)? {
-            print_info_row(n_probes, "payload", &pp, "    ", &mut stream)?;
-        }
-        print_info_row(
-            n_probes,
-            "tags",
-            &emeta.tags.replace(";", ", "),
-            "    ",
-            &mut stream,
-        )?;
-        if !emeta.file.is_empty() && !emeta.line.is_empty() {
+    if l.verbose != 0 {
+        if let Ok(emeta) = event_meta {
+            print_info_row(
+                n_probes,
+                "description",
+                &emeta.description,
+                "    ",
+                &mut stream,
+            )?;
+            // TODO(dan@auxon.io): Interpolate log-style payload / string
+            // combos here if they're present.
+            // https://github.com/auxoncorp/modality-probe/issues/281
+            if let Some(pp) =
+                meta::parsed_payload(emeta.type_hint.as_ref().map(|s| s.as_ref()), pl)?
+            {
+                print_info_row(n_probes, "payload", &pp, "    ", &mut stream)?;
+            }
             print_info_row(
                 n_probes,
-                "source",
-                &format!("\"{}#L{}\"", emeta.file, emeta.line),
+                "tags",
+                &emeta.tags.replace(";", ", "),
                 "    ",
                 &mut stream,
             )?;
+            if !emeta.file.is_empty() && !emeta.line.is_empty() {
+                print_info_row(
+                    n_probes,
+                    "source",
+                    &format!("\"{}#L{}\"", emeta.file, emeta.line),
+                    "    ",
+                    &mut stream,
+                )?;
+            }
         }
     }
-    if l.verbose > 1 && probe_meta.is_some() {
-        let pmeta = probe_meta.expect("just checked that probe meta is_some");
-        print_info_row(
-            n_probes,
-            "probe tags",
-            &pmeta.tags.replace(";", ", "),
-            "    ",
-            &mut stream,
-        )?;
-        print_info_row(
-            n_probes,
-            "probe source",
-            &match (pmeta.file.is_empty(), pmeta.line.is_empty()) {
-                (false, false) => format!("\"{}#L{}\"", pmeta.file, pmeta.line),
-                (false, true) => format!("\"{}\"", pmeta.file),
-                _ => "None".to_string(),
-            },
-            "    ",
-            &mut stream,
-        )?;
-        let comp_name_or_id =
-            if let Some(n) = cfg.component_names.get(&pmeta.component_id.to_string()) {
-                n.to_string()
-            } else {
-                pmeta.component_id.to_string()
-            };
-        print_info_row(n_probes, "component", &comp_name_or_id, "    ", &mut stream)?;
+    if l.verbose > 1 {
+        if let Some(pmeta) = probe_meta {
+            print_info_row(
+                n_probes,
+                "probe tags",
+                &pmeta.tags.replace(";", ", "),
+                "    ",
+                &mut stream,
+            )?;
+            print_info_row(
+                n_probes,
+                "probe source",
+                &match (pmeta.file.is_empty(), pmeta.line.is_empty()) {
+                    (false, false) => format!("\"{}#L{}\"", pmeta.file, pmeta.line),
+                    (false, true) => format!("\"{}\"", pmeta.file),
+                    _ => "None".to_string(),
+                },
+                "    ",
+                &mut stream,
+            )?;
+            let comp_name_or_id =
+                if let Some(n) = cfg.component_names.get(&pmeta.component_id.to_string()) {
+                    n.to_string()
+                } else {
+                    pmeta.component_id.to_string()
+                };
+            print_info_row(n_probes, "component", &comp_name_or_id, "    ", &mut stream)?;
+        }
     }
     Ok(())
 }
@@ -390,7 +427,7 @@ fn handle_source_edge<W: WriteIo>(
             let inner_lc = r.data.trace_clock().unwrap();
             r.probe_id != inner_lc.id && inner_lc == lc.prev()
         });
-        let self_tl = blocked_tls.entry(*probe_id).or_insert_with(HashSet::new);
+        let self_tl = blocked_tls.entry(*probe_id).or_default();
         for t in all_targs {
             self_tl.insert((t.probe_id, t.data.trace_clock().unwrap()));
         }
@@ -412,7 +449,7 @@ fn handle_source_edge<W: WriteIo>(
                     .unwrap_or_else(|| probe_id.get_raw().to_string());
                 print_edge_line(
                     idx,
-                    *indices.get(&pid).unwrap(),
+                    *indices.get(pid).unwrap(),
                     &from_name,
                     &to_name,
                     n_probes,
@@ -431,6 +468,27 @@ fn handle_source_edge<W: WriteIo>(
 }
 
 fn print_event_row<W: WriteIo>(
+    name: &str,
+    idx: usize,
+    n_probe: usize,
+    stream: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    print_marked_row("*", name, idx, n_probe, stream)
+}
+
+/// A restart is marked with `=` on its timeline; the events above it are
+/// from a previous boot of the probe.
+fn print_restart_row<W: WriteIo>(
+    name: &str,
+    idx: usize,
+    n_probe: usize,
+    stream: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    print_marked_row("=", name, idx, n_probe, stream)
+}
+
+fn print_marked_row<W: WriteIo>(
+    marker: &str,
     name: &str,
     idx: usize,
     n_probe: usize,
@@ -440,7 +498,7 @@ fn print_event_row<W: WriteIo>(
     for i in 0..n_probe {
         if i == idx {
             hopefully!(
-                write!(s, "*{}", COL_SPACE),
+                write!(s, "{}{}", marker, COL_SPACE),
                 "Internal error formatting graph"
             )?;
         } else {
@@ -1524,4 +1582,74 @@ pub(crate) mod test {
         print_as_graph(probes, clock_rows, &cfg, &l, &mut out).unwrap();
         assert_eq!(EXPECTED_FANOUT, std::str::from_utf8(&out).unwrap());
     }
+
+    pub fn restart_entry(seq: u64, idx: u32, data: LogEntryData) -> ReportLogEntry {
+        let probe1 = ProbeId::new(1).unwrap();
+        ReportLogEntry {
+            session_id: SessionId(1),
+            sequence_number: SequenceNumber(seq),
+            sequence_index: idx,
+            probe_id: probe1,
+            persistent_epoch_counting: false,
+            time_resolution: NanosecondResolution::UNSPECIFIED,
+            wall_clock_id: WallClockId::default(),
+            clock: LogicalClock {
+                id: probe1,
+                epoch: ProbeEpoch(0),
+                ticks: ProbeTicks(0),
+            },
+            data,
+            receive_time: Utc::now(),
+        }
+    }
+
+    const EXPECTED_RESTART: &str = "\
+*  one @ one (1:1:0:4:1)
+|
+*  one @ one (1:1:0:5:1)
+|
+=  restarted @ one (1:1:0:0:4294967295)
+|
+*  one @ one (1:1:0:0:1)
+|
+*  one @ one (1:1:0:1:1)
+|
+";
+
+    #[test]
+    fn restarts_separate_boots() {
+        let event1 = LogEntryData::Event(EventId::new(1).unwrap());
+        let trace = vec![
+            restart_entry(4, 1, event1.clone()),
+            restart_entry(5, 1, event1.clone()),
+            restart_entry(
+                0,
+                ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX,
+                LogEntryData::ProbeRestarted,
+            ),
+            restart_entry(0, 1, event1.clone()),
+            restart_entry(1, 1, event1),
+        ];
+        let cfg = graph::test::cfg();
+        let l = Log {
+            probe: None,
+            component: None,
+            component_path: vec![],
+            report: PathBuf::default(),
+            graph: true,
+            verbose: 0,
+            format: None,
+            radius: None,
+            from: None,
+            no_color: true,
+        };
+        {
+            let mut b = color::COLORIZE.write().unwrap();
+            *b = false;
+        }
+        let (probes, clock_rows) = log::sort_probes(&cfg, &l, trace).unwrap();
+        let mut out = Vec::new();
+        print_as_graph(probes, clock_rows, &cfg, &l, &mut out).unwrap();
+        assert_eq!(EXPECTED_RESTART, std::str::from_utf8(&out).unwrap());
+    }
 }
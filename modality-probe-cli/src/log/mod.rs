@@ -1,7 +1,6 @@
 use std::{
     collections::{BTreeMap, HashMap},
     convert::TryFrom,
-    fs::File,
     path::PathBuf,
 };
 
@@ -14,6 +13,7 @@ use crate::{
     description_format::DescriptionFormat,
     hopefully, hopefully_ok,
     meta::{self, Cfg},
+    span::{CompletedSpan, SpanTracker},
 };
 
 mod color;
@@ -38,7 +38,8 @@ pub struct Log {
     /// components, provide this switch multiple times.
     #[structopt(short, long, required = true)]
     pub component_path: Vec<PathBuf>,
-    /// The path to the collected trace.
+    /// The path to the collected trace. Segments rotated out of it by
+    /// a collector are read along with it, compressed or not.
     #[structopt(short, long, required = true)]
     pub report: PathBuf,
     /// Print the log as an ASCII-art graph.
@@ -107,12 +108,10 @@ pub struct Log {
 
 pub fn run(mut l: Log) -> Result<(), Box<dyn std::error::Error>> {
     let cfg = meta::assemble_components(&mut l.component_path)?;
-    let mut log_file = hopefully!(
-        File::open(&l.report),
-        format!("Failed to open the report file at {}", l.report.display())
+    let report = hopefully!(
+        json::read_log_entries_from_path(&l.report),
+        format!("Failed to read the report file at {}", l.report.display())
     )?;
-
-    let report = json::read_log_entries(&mut log_file)?;
     let (probes, clock_rows) = sort_probes(&cfg, &l, report)?;
 
     let color_term = std::env::var("COLORTERM").unwrap_or_else(|_| String::new());
@@ -226,13 +225,13 @@ fn sort_probes(
         }
     }
 
-    // Sort the probe-sorted events by sequence and peel off the
-    // clocks. The clock set is used to determine that both ends of an
-    // edge are present in the dataset. This prevents a given timeline
-    // from being blocked and waiting indefinitely.
+    // Sort the probe-sorted events by boot and sequence, and peel off
+    // the clocks. The clock set is used to determine that both ends of
+    // an edge are present in the dataset. This prevents a given
+    // timeline from being blocked and waiting indefinitely.
     let mut clock_rows = Vec::new();
     for plog in probes.values_mut() {
-        plog.sort_by_key(|p| std::cmp::Reverse((p.sequence_number, p.sequence_index)));
+        sort_by_boot(plog);
         for r in plog.iter() {
             match r.data {
                 LogEntryData::TraceClock(_) => clock_rows.push(r.clone()),
@@ -249,12 +248,39 @@ fn sort_probes(
     Ok((probes, clock_rows))
 }
 
+/// Sort a probe's log entries, last first. The collector marks where it
+/// saw the probe restart, and sequence numbers start over after a
+/// restart, so entries are ordered by the boot they're from before their
+/// sequence number.
+pub(crate) fn sort_by_boot(plog: &mut Vec<ReportLogEntry>) {
+    let mut boot = 0;
+    let mut by_boot = plog
+        .drain(..)
+        .map(|e| {
+            if e.is_probe_restart() {
+                boot += 1;
+            }
+            (boot, e)
+        })
+        .collect::<Vec<_>>();
+    by_boot.sort_by_key(|(boot, e)| {
+        std::cmp::Reverse((
+            *boot,
+            !e.is_probe_restart(),
+            e.sequence_number,
+            e.sequence_index,
+        ))
+    });
+    plog.extend(by_boot.into_iter().map(|(_, e)| e));
+}
+
 fn print_as_log(
     mut probes: BTreeMap<ProbeId, Vec<ReportLogEntry>>,
     l: &Log,
     cfg: &Cfg,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut count = 0;
+    let mut spans = SpanTracker::new();
     let indices = probes
         .keys()
         .enumerate()
@@ -267,13 +293,19 @@ fn print_as_log(
             'inner: loop {
                 if let Some(row) = log.pop() {
                     match row.data {
-                        LogEntryData::Event(id) | LogEntryData::EventWithTime(.., id) => {
-                            print_event_info(idx, row, &id, None, l, &cfg)?;
+                        LogEntryData::Event(id)
+                        | LogEntryData::EventWithTime(.., id)
+                        | LogEntryData::EventWithBlob(id, _) => {
+                            let span = spans.observe(cfg, &row);
+                            print_event_info(idx, row, &id, None, l, cfg)?;
+                            print_span_info(idx, span, l, cfg);
                             count += 1;
                         }
                         LogEntryData::EventWithPayload(id, pl)
                         | LogEntryData::EventWithPayloadWithTime(.., id, pl) => {
-                            print_event_info(idx, row, &id, Some(pl), l, &cfg)?;
+                            let span = spans.observe(cfg, &row);
+                            print_event_info(idx, row, &id, Some(pl), l, cfg)?;
+                            print_span_info(idx, span, l, cfg);
                             count += 1;
                         }
                         LogEntryData::TraceClock(lc) | LogEntryData::TraceClockWithTime(.., lc) => {
@@ -326,6 +358,47 @@ fn print_as_log(
                                 println!();
                             }
                         }
+                        LogEntryData::ProbeRestarted => {
+                            spans.observe(cfg, &row);
+                            let probe_name = cfg
+                                .probes
+                                .get(&row.probe_id.get_raw())
+                                .map(|p| p.name.clone())
+                                .unwrap_or_else(|| row.probe_id.get_raw().to_string());
+                            println!();
+                            println!(
+                                "Probe Restarted @ {} {}",
+                                color::colorize_probe(idx, &probe_name),
+                                color::colorize_coord(&row.coordinate()),
+                            );
+                            println!();
+                            count += 1;
+                        }
+                        LogEntryData::EventRepeated(id, n) => {
+                            if let Some(ref fmt) = l.format {
+                                println!("{}", format::format(cfg, &row, fmt));
+                            } else {
+                                let probe_name = cfg
+                                    .probes
+                                    .get(&row.probe_id.get_raw())
+                                    .map(|p| p.name.clone())
+                                    .unwrap_or_else(|| row.probe_id.get_raw().to_string());
+                                let event_name = meta::get_event_meta(cfg, &row.probe_id, &id)
+                                    .map(|em| em.name.clone())
+                                    .unwrap_or_else(|_| id.get_raw().to_string());
+                                println!(
+                                    "{} repeated {} more times {} {}",
+                                    event_name,
+                                    n,
+                                    color::colorize_probe(idx, &probe_name),
+                                    color::colorize_coord(&row.coordinate())
+                                );
+                                if l.verbose != 0 {
+                                    println!();
+                                }
+                            }
+                            count += 1;
+                        }
                         _ => {
                             count += 1;
                         }
@@ -342,18 +415,42 @@ fn print_as_log(
     Ok(())
 }
 
+/// Print the duration of a span once its end has been printed.
+fn print_span_info(idx: usize, span: Option<CompletedSpan>, l: &Log, cfg: &Cfg) {
+    let span = match span {
+        Some(span) if l.format.is_none() => span,
+        _ => return,
+    };
+    let probe_name = cfg
+        .probes
+        .get(&span.probe_id.get_raw())
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| span.probe_id.get_raw().to_string());
+    println!(
+        "Span {} @ {} {}..{} duration={}",
+        span.name,
+        color::colorize_probe(idx, &probe_name),
+        color::colorize_coord(&span.begin.coordinate()),
+        color::colorize_coord(&span.end.coordinate()),
+        span.duration
+    );
+    if l.verbose != 0 {
+        println!();
+    }
+}
+
 fn print_event_info(
     idx: usize,
     ev: ReportLogEntry,
     eid: &EventId,
-    payload: Option<u32>,
+    payload: Option<u64>,
     l: &Log,
     cfg: &Cfg,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(ref fmt) = l.format {
         println!("{}", format::format(cfg, &ev, fmt));
     } else {
-        let event_meta = meta::get_event_meta(&cfg, &ev.probe_id, eid);
+        let event_meta = meta::get_event_meta(cfg, &ev.probe_id, eid);
         let probe_meta = cfg.probes.get(&ev.probe_id.get_raw());
         let pname = probe_meta
             .map(|pm| pm.name.clone())
@@ -393,85 +490,90 @@ fn print_event_info(
             );
         }
 
-        if l.verbose != 0 && event_meta.is_ok() {
-            let emeta = event_meta.expect("just checked that event meta is_ok");
-            println!(
-                "    {}",
-                color::colorize_info(
-                    "description",
-                    if !emeta.description.is_empty() {
-                        &emeta.description
-                    } else {
-                        "None"
-                    }
-                )
-            );
-            println!(
-                "    {}",
-                color::colorize_info(
-                    "payload",
-                    if let Some(ref p) =
-                        meta::parsed_payload(emeta.type_hint.as_ref().map(|s| s.as_ref()), payload)?
-                    {
-                        p
-                    } else {
-                        "None"
-                    }
-                )
-            );
-            println!(
-                "    {}",
-                color::colorize_info("tags", &emeta.tags.replace(";", ", "))
-            );
-            println!(
-                "    {}",
-                match (emeta.file.is_empty(), emeta.line.is_empty()) {
-                    (false, false) => {
-                        color::colorize_info(
-                            "source",
-                            &format!("\"{}#L{}\"", emeta.file, emeta.line),
-                        )
-                    }
-                    (true, false) => {
-                        color::colorize_info("source", &format!("\"{}\"", emeta.file))
-                    }
-                    _ => {
-                        color::colorize_info("source", "None")
+        if l.verbose != 0 {
+            if let Ok(emeta) = event_meta {
+                let parsed_payload = if let LogEntryData::EventWithBlob(_, ref blob) = ev.data {
+                    Some(meta::blob_payload(blob))
+                } else {
+                    meta::parsed_payload(emeta.type_hint.as_ref().map(|s| s.as_ref()), payload)?
+                };
+                println!(
+                    "    {}",
+                    color::colorize_info(
+                        "description",
+                        if !emeta.description.is_empty() {
+                            &emeta.description
+                        } else {
+                            "None"
+                        }
+                    )
+                );
+                println!(
+                    "    {}",
+                    color::colorize_info(
+                        "payload",
+                        if let Some(ref p) = parsed_payload {
+                            p
+                        } else {
+                            "None"
+                        }
+                    )
+                );
+                println!(
+                    "    {}",
+                    color::colorize_info("tags", &emeta.tags.replace(";", ", "))
+                );
+                println!(
+                    "    {}",
+                    match (emeta.file.is_empty(), emeta.line.is_empty()) {
+                        (false, false) => {
+                            color::colorize_info(
+                                "source",
+                                &format!("\"{}#L{}\"", emeta.file, emeta.line),
+                            )
+                        }
+                        (true, false) => {
+                            color::colorize_info("source", &format!("\"{}\"", emeta.file))
+                        }
+                        _ => {
+                            color::colorize_info("source", "None")
+                        }
                     }
-                }
-            );
+                );
+            }
         }
-        if l.verbose > 1 && probe_meta.is_some() {
-            let pmeta = probe_meta.expect("just checked that probe meta is_some");
-            println!(
-                "    {}",
-                color::colorize_info("probe tags", &pmeta.tags.replace(";", ", "))
-            );
-            println!(
-                "    {}",
-                match (pmeta.file.is_empty(), pmeta.line.is_empty()) {
-                    (false, false) => {
-                        color::colorize_info(
-                            "probe source",
-                            &format!("\"{}#L{}\"", pmeta.file, pmeta.line),
-                        )
-                    }
-                    (false, true) => {
-                        color::colorize_info("probe source", &format!("\"{}\"", pmeta.file))
+        if l.verbose > 1 {
+            if let Some(pmeta) = probe_meta {
+                println!(
+                    "    {}",
+                    color::colorize_info("probe tags", &pmeta.tags.replace(";", ", "))
+                );
+                println!(
+                    "    {}",
+                    match (pmeta.file.is_empty(), pmeta.line.is_empty()) {
+                        (false, false) => {
+                            color::colorize_info(
+                                "probe source",
+                                &format!("\"{}#L{}\"", pmeta.file, pmeta.line),
+                            )
+                        }
+                        (false, true) => {
+                            color::colorize_info("probe source", &format!("\"{}\"", pmeta.file))
+                        }
+                        _ => color::colorize_info("probe source", "None"),
                     }
-                    _ => color::colorize_info("probe source", "None"),
-                }
-            );
-            let comp_name_or_id =
-                if let Some(n) = cfg.component_names.get(&pmeta.component_id.to_string()) {
-                    n.to_string()
-                } else {
-                    pmeta.component_id.to_string()
-                };
-            println!(
-                "    {}",
-                color::colorize_info("component", &comp_name_or_id)
-            );
+                );
+                let comp_name_or_id =
+                    if let Some(n) = cfg.component_names.get(&pmeta.component_id.to_string()) {
+                        n.to_string()
+                    } else {
+                        pmeta.component_id.to_string()
+                    };
+                println!(
+                    "    {}",
+                    color::colorize_info("component", &comp_name_or_id)
+                );
+            }
         }
         if l.verbose != 0 {
             println!();
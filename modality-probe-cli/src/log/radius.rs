@@ -103,162 +103,130 @@ pub fn truncate_to_radius(probes: SortedProbes, radius: Radius) -> SortedProbes
         None => return (BTreeMap::new(), vec![]),
     };
 
-    let start = if coord_idx < radius.distance {
-        0
-    } else {
-        coord_idx - radius.distance
-    };
+    let start = coord_idx.saturating_sub(radius.distance);
     let end = if coord_idx + radius.distance >= target_tl_len {
         target_tl_len - 1
     } else {
         coord_idx + radius.distance
     };
 
-    let mut new_probes = BTreeMap::new();
-
     let trunc_tl = target_tl
         .iter()
         .enumerate()
         .skip_while(|(idx, _)| *idx < start)
         .take_while(|(idx, _)| *idx <= end)
-        .map(|(_, row)| row)
-        .collect::<Vec<&ReportLogEntry>>();
+        .collect::<Vec<(usize, &ReportLogEntry)>>();
 
-    let mut clock_rows = Vec::new();
     let mut included_rows = HashSet::new();
     drain_truncated_log(
         trunc_tl,
-        &mut new_probes,
         &mut included_rows,
-        &mut clock_rows,
         &probes,
         radius.distance as i64,
     );
 
-    for row in clock_rows.iter() {
-        let p = new_probes.entry(row.probe_id).or_insert_with(Vec::new);
-        p.push(row.clone());
-    }
-    for plog in new_probes.values_mut() {
-        plog.sort_by_key(|p| std::cmp::Reverse((p.sequence_number, p.sequence_index)));
+    // The timelines are already sorted by boot, and sequence numbers start
+    // over after a restart, so keep the rows in their original order
+    // rather than sorting them again
+    let mut new_probes = BTreeMap::new();
+    let mut clock_rows = Vec::new();
+    for (probe_id, tl) in probes.0.iter() {
+        let plog = tl
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| included_rows.contains(&(*probe_id, *idx)))
+            .map(|(_, row)| row.clone())
+            .collect::<Vec<ReportLogEntry>>();
+        if plog.is_empty() {
+            continue;
+        }
+        clock_rows.extend(
+            plog.iter()
+                .filter(|row| row.data.trace_clock().is_some())
+                .cloned(),
+        );
+        new_probes.insert(*probe_id, plog);
     }
 
     (new_probes, clock_rows)
 }
 
+/// Walk a window of a probe's timeline, given as rows along with their
+/// position in the timeline, marking the rows to keep in `included_rows`
+/// and following the snapshots to the neighboring timelines
 fn drain_truncated_log(
-    mut log: Vec<&ReportLogEntry>,
-    new_probes: &mut BTreeMap<ProbeId, Vec<ReportLogEntry>>,
-    included_rows: &mut HashSet<(ProbeId, SequenceNumber, u32)>,
-    clock_rows: &mut Vec<ReportLogEntry>,
+    mut log: Vec<(usize, &ReportLogEntry)>,
+    included_rows: &mut HashSet<(ProbeId, usize)>,
     probes: &SortedProbes,
     dist: i64,
 ) {
     let mut d = dist;
     let mut idx = 0;
-    while let Some(row) = log.pop() {
+    while let Some((pos, row)) = log.pop() {
         match row.data {
-            LogEntryData::Event(..) | LogEntryData::EventWithTime(..) => {
-                if included_rows.insert((row.probe_id, row.sequence_number, row.sequence_index)) {
-                    let p = new_probes.entry(row.probe_id).or_insert_with(Vec::new);
-                    p.push(row.clone());
-                }
-            }
-            LogEntryData::EventWithPayload(..) | LogEntryData::EventWithPayloadWithTime(..) => {
-                if included_rows.insert((row.probe_id, row.sequence_number, row.sequence_index)) {
-                    let p = new_probes.entry(row.probe_id).or_insert_with(Vec::new);
-                    p.push(row.clone());
-                }
+            LogEntryData::Event(..)
+            | LogEntryData::EventWithTime(..)
+            | LogEntryData::EventWithBlob(..)
+            | LogEntryData::EventRepeated(..)
+            | LogEntryData::EventWithPayload(..)
+            | LogEntryData::EventWithPayloadWithTime(..)
+            | LogEntryData::ProbeRestarted => {
+                included_rows.insert((row.probe_id, pos));
             }
-            LogEntryData::TraceClock(lc) | LogEntryData::TraceClockWithTime(.., lc) => {
-                if included_rows.insert((row.probe_id, row.sequence_number, row.sequence_index)) {
-                    clock_rows.push(row.clone());
-                    if lc.id != row.probe_id {
-                        if let Some(sender) = probes
+            LogEntryData::TraceClock(lc) | LogEntryData::TraceClockWithTime(.., lc)
+                if included_rows.insert((row.probe_id, pos)) =>
+            {
+                if lc.id != row.probe_id {
+                    if let Some(sender) = probes
+                        .1
+                        .iter()
+                        .find(|clock_row| {
+                            clock_row.probe_id == lc.id && clock_row.data.trace_clock() == Some(lc)
+                        })
+                        .map(|row| row.probe_id)
+                    {
+                        handle_snap_merge(lc, d, sender, probes, included_rows);
+                    }
+                } else if lc.pack().1 != 0 {
+                    if let Some(next) = log.pop() {
+                        if let Some(next_lc) = next.1.data.trace_clock() {
+                            if next_lc.id == row.probe_id {
+                                let receivers = probes
+                                    .1
+                                    .iter()
+                                    .filter(|clock_row| {
+                                        clock_row.probe_id != row.probe_id
+                                            && clock_row.data.trace_clock().unwrap() == lc.prev()
+                                    })
+                                    .map(|clock_row| clock_row.probe_id);
+                                for receiver in receivers {
+                                    handle_snap_produce(lc, d, receiver, probes, included_rows);
+                                }
+                            }
+                        }
+                        let receivers = probes
                             .1
                             .iter()
-                            .find(|clock_row| {
-                                clock_row.probe_id == lc.id
-                                    && clock_row.data.trace_clock() == Some(lc)
+                            .filter(|clock_row| {
+                                clock_row.probe_id != row.probe_id
+                                    && clock_row.data.trace_clock().unwrap() == lc.prev()
                             })
-                            .map(|row| row.probe_id)
-                        {
-                            handle_snap_merge(
-                                lc,
-                                d,
-                                sender,
-                                probes,
-                                new_probes,
-                                included_rows,
-                                clock_rows,
-                            );
+                            .map(|clock_row| clock_row.probe_id);
+                        for receiver in receivers {
+                            handle_snap_produce(lc, d, receiver, probes, included_rows);
                         }
-                    } else if lc.pack().1 != 0 {
-                        if let Some(next) = log.pop() {
-                            if let Some(next_lc) = next.data.trace_clock() {
-                                if next_lc.id == row.probe_id {
-                                    let receivers = probes
-                                        .1
-                                        .iter()
-                                        .filter(|clock_row| {
-                                            clock_row.probe_id != row.probe_id
-                                                && clock_row.data.trace_clock().unwrap()
-                                                    == lc.prev()
-                                        })
-                                        .map(|clock_row| clock_row.probe_id);
-                                    for receiver in receivers {
-                                        handle_snap_produce(
-                                            lc,
-                                            d,
-                                            receiver,
-                                            probes,
-                                            new_probes,
-                                            included_rows,
-                                            clock_rows,
-                                        );
-                                    }
-                                }
-                            }
-                            let receivers = probes
-                                .1
-                                .iter()
-                                .filter(|clock_row| {
-                                    clock_row.probe_id != row.probe_id
-                                        && clock_row.data.trace_clock().unwrap() == lc.prev()
-                                })
-                                .map(|clock_row| clock_row.probe_id);
-                            for receiver in receivers {
-                                handle_snap_produce(
-                                    lc,
-                                    d,
-                                    receiver,
-                                    probes,
-                                    new_probes,
-                                    included_rows,
-                                    clock_rows,
-                                );
-                            }
-                            log.push(next);
-                        } else {
-                            let receivers = probes
-                                .1
-                                .iter()
-                                .filter(|clock_row| {
-                                    clock_row.probe_id != row.probe_id
-                                        && clock_row.data.trace_clock().unwrap() == lc.prev()
-                                })
-                                .map(|clock_row| clock_row.probe_id);
-                            for receiver in receivers {
-                                handle_snap_produce(
-                                    lc,
-                                    d,
-                                    receiver,
-                                    probes,
-                                    new_probes,
-                                    included_rows,
-                                    clock_rows,
-                                );
-                            }
+                        log.push(next);
+                    } else {
+                        let receivers = probes
+                            .1
+                            .iter()
+                            .filter(|clock_row| {
+                                clock_row.probe_id != row.probe_id
+                                    && clock_row.data.trace_clock().unwrap() == lc.prev()
+                            })
+                            .map(|clock_row| clock_row.probe_id);
+                        for receiver in receivers {
+                            handle_snap_produce(lc, d, receiver, probes, included_rows);
                         }
                     }
                 }
@@ -275,9 +243,7 @@ fn handle_snap_produce(
     dist: i64,
     receiver: ProbeId,
     probes: &SortedProbes,
-    new_probes: &mut BTreeMap<ProbeId, Vec<ReportLogEntry>>,
-    included_rows: &mut HashSet<(ProbeId, SequenceNumber, u32)>,
-    clock_rows: &mut Vec<ReportLogEntry>,
+    included_rows: &mut HashSet<(ProbeId, usize)>,
 ) {
     if let Some(neighbor) = probes.0.get(&receiver) {
         if let Some(start) = neighbor
@@ -295,16 +261,8 @@ fn handle_snap_produce(
                 .enumerate()
                 .skip_while(|(idx, _)| *idx < start)
                 .take_while(|(idx, _)| *idx < end)
-                .map(|(_, row)| row)
-                .collect::<Vec<&ReportLogEntry>>();
-            drain_truncated_log(
-                trunc_neighbor,
-                new_probes,
-                included_rows,
-                clock_rows,
-                probes,
-                dist,
-            );
+                .collect::<Vec<(usize, &ReportLogEntry)>>();
+            drain_truncated_log(trunc_neighbor, included_rows, probes, dist);
         }
     }
 }
@@ -314,36 +272,22 @@ fn handle_snap_merge(
     dist: i64,
     receiver: ProbeId,
     probes: &SortedProbes,
-    new_probes: &mut BTreeMap<ProbeId, Vec<ReportLogEntry>>,
-    included_rows: &mut HashSet<(ProbeId, SequenceNumber, u32)>,
-    clock_rows: &mut Vec<ReportLogEntry>,
+    included_rows: &mut HashSet<(ProbeId, usize)>,
 ) {
     if let Some(neighbor) = probes.0.get(&receiver) {
         if let Some(end) = neighbor
             .iter()
             .position(|row| row.data.trace_clock() == Some(clock.next()))
         {
-            let start = if end < dist as usize {
-                0
-            } else {
-                end - dist as usize
-            };
+            let start = end.saturating_sub(dist as usize);
 
             let trunc_neighbor = neighbor
                 .iter()
                 .enumerate()
                 .skip_while(|(idx, _)| *idx < start)
                 .take_while(|(idx, _)| *idx <= end)
-                .map(|(_, row)| row)
-                .collect::<Vec<&ReportLogEntry>>();
-            drain_truncated_log(
-                trunc_neighbor,
-                new_probes,
-                included_rows,
-                clock_rows,
-                probes,
-                dist,
-            );
+                .collect::<Vec<(usize, &ReportLogEntry)>>();
+            drain_truncated_log(trunc_neighbor, included_rows, probes, dist);
         }
     }
 }
@@ -352,11 +296,12 @@ fn handle_snap_merge(
 mod test {
     use std::{convert::TryFrom, path::PathBuf};
 
+    use modality_probe::EventId;
     use proptest::prelude::*;
 
     use crate::{
         log,
-        log::{color, Log},
+        log::{color, graph::test::restart_entry, Log},
         visualize::graph,
     };
 
@@ -428,4 +373,52 @@ mod test {
         log::graph::print_as_graph(probes, clock_rows, &cfg, &l, &mut out).unwrap();
         assert_eq!(EXPECTED_GRAPH, std::str::from_utf8(&out).unwrap());
     }
+
+    const EXPECTED_RESTART: &str = "\
+*  one @ one (1:1:0:5:1)
+|
+=  restarted @ one (1:1:0:0:4294967295)
+|
+*  one @ one (1:1:0:0:1)
+|
+*  one @ one (1:1:0:1:1)
+|
+";
+
+    #[test]
+    fn radius_keeps_boot_order() {
+        let event1 = LogEntryData::Event(EventId::new(1).unwrap());
+        let trace = vec![
+            restart_entry(4, 1, event1.clone()),
+            restart_entry(5, 1, event1.clone()),
+            restart_entry(
+                0,
+                ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX,
+                LogEntryData::ProbeRestarted,
+            ),
+            restart_entry(0, 1, event1.clone()),
+            restart_entry(1, 1, event1),
+        ];
+        let cfg = graph::test::cfg();
+        let l = Log {
+            probe: None,
+            component: None,
+            component_path: vec![],
+            report: PathBuf::default(),
+            graph: true,
+            verbose: 0,
+            format: None,
+            radius: Some(2),
+            from: Some("1:1:0:1".to_string()),
+            no_color: true,
+        };
+        {
+            let mut b = color::COLORIZE.write().unwrap();
+            *b = false;
+        }
+        let (probes, clock_rows) = log::sort_probes(&cfg, &l, trace).unwrap();
+        let mut out = Vec::new();
+        log::graph::print_as_graph(probes, clock_rows, &cfg, &l, &mut out).unwrap();
+        assert_eq!(EXPECTED_RESTART, std::str::from_utf8(&out).unwrap());
+    }
 }
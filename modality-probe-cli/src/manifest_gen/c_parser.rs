@@ -1,8 +1,9 @@
 use crate::manifest_gen::{
     event_metadata::EventMetadata,
     parser::{
-        self, event_name_valid, probe_name_valid, remove_double_quotes, tags_or_desc_valid,
-        trimmed_string, trimmed_string_w_space, Parser, ParserConfig, Span,
+        self, event_name_valid, probe_name_valid, remove_double_quotes, span_name,
+        tags_or_desc_valid, trimmed_string, trimmed_string_w_space, Parser, ParserConfig, Span,
+        SPAN_BEGIN_SUFFIX, SPAN_END_SUFFIX,
     },
     probe_metadata::ProbeMetadata,
     source_location::SourceLocation,
@@ -43,6 +44,7 @@ pub enum Error {
     TypeHintNameNotUpperCase(SourceLocation),
     PayloadArgumentSpansManyLines(SourceLocation),
     EmptyTags(SourceLocation),
+    SpanNameMissingSuffix(SourceLocation),
 }
 
 impl Error {
@@ -54,6 +56,7 @@ impl Error {
             Error::TypeHintNameNotUpperCase(l) => l,
             Error::PayloadArgumentSpansManyLines(l) => l,
             Error::EmptyTags(l) => l,
+            Error::SpanNameMissingSuffix(l) => l,
         }
     }
 }
@@ -126,12 +129,22 @@ fn parse_record_event_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     let (input, found_expect) = peek(opt(tag(expect_tag.as_str())))(input)?;
     let with_time_tag = format!("{}_RECORD_W_TIME", prefix);
     let (input, found_with_time) = peek(opt(tag(with_time_tag.as_str())))(input)?;
+    let with_priority_tag = format!("{}_RECORD_W_PRIORITY", prefix);
+    let (input, found_with_priority) = peek(opt(tag(with_priority_tag.as_str())))(input)?;
+    let span_tag = format!("{}_SPAN_", prefix);
+    let (input, found_span) = peek(opt(tag(span_tag.as_str())))(input)?;
     if found_expect.is_some() {
         let (input, metadata) = expect_call_exp(input)?;
         Ok((input, metadata))
+    } else if found_span.is_some() {
+        let (input, metadata) = span_call_exp(input)?;
+        Ok((input, metadata))
     } else if found_with_time.is_some() {
         let (input, metadata) = event_with_time(input)?;
         Ok((input, metadata))
+    } else if found_with_priority.is_some() {
+        let (input, metadata) = event_with_priority(input)?;
+        Ok((input, metadata))
     } else {
         let tag_string = format!("{}_RECORD_W_", prefix);
         let (input, found_with_payload) = peek(opt(tag(tag_string.as_str())))(input)?;
@@ -197,6 +210,155 @@ fn event_with_time(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
+            location: pos.into(),
+        },
+    ))
+}
+
+fn event_with_priority(input: Span) -> ParserResult<Span, EventMetadata> {
+    let prefix = input.extra.as_ref().unwrap().prefix;
+    let tag_string = format!("{}_RECORD_W_PRIORITY", prefix);
+    let (input, pos) = position(input)?;
+    let (input, _) = tag(tag_string.as_str())(input)?;
+    let (input, _) = opt(line_ending)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("(")(input)?;
+    let (input, args) = take_until(");")(input)
+        .map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (input, _) =
+        tag(");")(input).map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (args, probe_instance) = variable_call_exp_arg(args)?;
+    let (args, name) = variable_call_exp_arg(args)?;
+    if !event_name_valid(&name) {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
+    iter.for_each(|s| arg_vec.push(s));
+    let (_args, _) = iter.finish()?;
+    match arg_vec.len() {
+        1..=3 => (), // At least a priority, maybe tags and description
+        _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
+    }
+    let priority = arg_vec.remove(0).trim().to_string();
+    if priority.is_empty() {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    // Check for equal open/close parentheses
+    let open = priority.chars().filter(|&c| c == '(').count();
+    let close = priority.chars().filter(|&c| c == ')').count();
+    if open != close {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut tags_and_desc = arg_vec;
+    for s in tags_and_desc.iter_mut() {
+        *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    }
+    let tags_pos = tags_and_desc.iter().position(|s| s.contains("tags="));
+    let tags = tags_pos
+        .map(|index| tags_and_desc.swap_remove(index))
+        .map(|s| s.replace("tags=", ""));
+    if let Some(t) = &tags {
+        if t.is_empty() {
+            return Err(make_failure(input, Error::EmptyTags(pos.into())));
+        }
+    }
+    let description = tags_and_desc.pop();
+    Ok((
+        input,
+        EventMetadata {
+            name,
+            probe_instance,
+            payload: None,
+            description,
+            tags,
+            priority: Some(priority),
+            span: None,
+            location: pos.into(),
+        },
+    ))
+}
+
+fn span_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
+    let prefix = input.extra.as_ref().unwrap().prefix;
+    let begin_tag = format!("{}_SPAN_BEGIN", prefix);
+    let end_tag = format!("{}_SPAN_END", prefix);
+    let (input, pos) = position(input)?;
+    let (input, found_begin) = opt(tag(begin_tag.as_str()))(input)?;
+    let (input, suffix) = if found_begin.is_some() {
+        (input, SPAN_BEGIN_SUFFIX)
+    } else {
+        let (input, _) = tag(end_tag.as_str())(input)?;
+        (input, SPAN_END_SUFFIX)
+    };
+    let (input, found_with_time) = opt(tag("_W_TIME"))(input)?;
+    let with_time = found_with_time.is_some();
+    let (input, _) = opt(line_ending)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("(")(input)?;
+    let (input, args) = take_until(");")(input)
+        .map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (input, _) =
+        tag(");")(input).map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (args, probe_instance) = variable_call_exp_arg(args)?;
+    let expect_more_args = peek(variable_call_exp_arg)(args).is_ok();
+    let (args, name) = if expect_more_args {
+        variable_call_exp_arg(args)?
+    } else if with_time {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    } else {
+        rest_string(args)?
+    };
+    if !event_name_valid(&name) {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let span = span_name(&name, suffix)
+        .ok_or_else(|| make_failure(input, Error::SpanNameMissingSuffix(pos.into())))?;
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
+    iter.for_each(|s| arg_vec.push(s));
+    let (_args, _) = iter.finish()?;
+    if with_time {
+        if arg_vec.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+        let time = arg_vec.remove(0).trim().to_string();
+        // Check for equal open/close parentheses
+        let open = time.chars().filter(|&c| c == '(').count();
+        let close = time.chars().filter(|&c| c == ')').count();
+        if time.is_empty() || open != close {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+    }
+    let mut tags_and_desc = arg_vec;
+    if tags_and_desc.len() > 2 {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    for s in tags_and_desc.iter_mut() {
+        *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    }
+    let tags_pos = tags_and_desc.iter().position(|s| s.contains("tags="));
+    let tags = tags_pos
+        .map(|index| tags_and_desc.swap_remove(index))
+        .map(|s| s.replace("tags=", ""));
+    if let Some(t) = &tags {
+        if t.is_empty() {
+            return Err(make_failure(input, Error::EmptyTags(pos.into())));
+        }
+    }
+    let description = tags_and_desc.pop();
+    Ok((
+        input,
+        EventMetadata {
+            name,
+            probe_instance,
+            payload: None,
+            description,
+            tags,
+            priority: None,
+            span: Some(span),
             location: pos.into(),
         },
     ))
@@ -256,6 +418,8 @@ fn expect_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: Some((TypeHint::U32, expr).into()),
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -311,6 +475,8 @@ fn event_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -327,6 +493,10 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     if has_time {
         type_hint = type_hint.replace("_W_TIME", "");
     }
+    let has_priority = type_hint.ends_with("_W_PRIORITY");
+    if has_priority {
+        type_hint = type_hint.trim_end_matches("_W_PRIORITY").to_string();
+    }
     if type_hint.to_uppercase().as_str() != type_hint.as_str() {
         return Err(make_failure(
             input,
@@ -351,16 +521,10 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
     iter.for_each(|s| arg_vec.push(s));
     let (_args, _) = iter.finish()?;
-    if has_time {
-        match arg_vec.len() {
-            2..=4 => (), // At least payload and time, maybe tags and description
-            _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
-        }
-    } else {
-        match arg_vec.len() {
-            1..=3 => (), // At least a payload, maybe tags and description
-            _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
-        }
+    // At least a payload, maybe a time or priority, maybe tags and description
+    let required_args = 1 + has_time as usize + has_priority as usize;
+    if arg_vec.len() < required_args || arg_vec.len() > required_args + 2 {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
     }
     // We have a constraint that the payload argument doesn't span
     // multiple lines, trim off leading and trailing space
@@ -382,6 +546,15 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     if has_time {
         let _time = arg_vec.remove(0);
     }
+    let priority = if has_priority {
+        let priority = arg_vec.remove(0).trim().to_string();
+        if priority.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+        Some(priority)
+    } else {
+        None
+    };
     let mut tags_and_desc = arg_vec;
     for s in tags_and_desc.iter_mut() {
         *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
@@ -405,6 +578,8 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
             payload: Some((type_hint, payload).into()),
             description,
             tags,
+            priority,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -626,6 +801,10 @@ impl fmt::Display for Error {
                 f,
                 "Enountered an empty tags statement while parsing a record event call-site",
             ),
+            Error::SpanNameMissingSuffix(_) => write!(
+                f,
+                "Span call-site event names need to end in _BEGIN or _END respectively",
+            ),
         }
     }
 }
@@ -715,7 +894,7 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
-    const MIXED_PROBE_ID_INPUT: &'static str = r#"
+    const MIXED_PROBE_ID_INPUT: &str = r#"
     /* C/C++ style */
     modality_probe_error result = MODALITY_PROBE_INIT(
         destination,
@@ -766,7 +945,7 @@ mod tests {
     MODALITY_PROBE_INIT(storage, size, ID_BAR, 0, 0, NULL, NULL, t, MODALITY_TAGS(my tag));
 "#;
 
-    const MIXED_EVENT_RECORDING_INPUT: &'static str = r#"
+    const MIXED_EVENT_RECORDING_INPUT: &str = r#"
     /* The user writes this line: */
     const size_t err = MODALITY_PROBE_RECORD(g_probe, EVENT_READ1);
 
@@ -848,6 +1027,8 @@ mod tests {
             "Description");
 
     MODALITY_PROBE_RECORD_W_BOOL_W_TIME(g_probe, EVENT_M, true, 1);
+
+    MODALITY_PROBE_RECORD_W_PRIORITY(g_probe, EVENT_N, 3, MODALITY_TAGS(fault), "Description");
 "#;
 
     #[test]
@@ -916,6 +1097,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (61, 3, 24).into(),
                 },
                 EventMetadata {
@@ -924,6 +1107,8 @@ mod tests {
                     payload: None,
                     description: Some("my docs".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (201, 9, 24).into(),
                 },
                 EventMetadata {
@@ -932,6 +1117,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: Some("network".to_string()),
+                    priority: None,
+                    span: None,
                     location: (307, 13, 5).into(),
                 },
                 EventMetadata {
@@ -940,6 +1127,8 @@ mod tests {
                     payload: None,
                     description: Some("docs".to_string()),
                     tags: Some("network;file-system".to_string()),
+                    priority: None,
+                    span: None,
                     location: (449, 18, 5).into(),
                 },
                 EventMetadata {
@@ -948,6 +1137,8 @@ mod tests {
                     payload: Some((TypeHint::U8, "status").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (616, 21, 24).into(),
                 },
                 EventMetadata {
@@ -956,6 +1147,8 @@ mod tests {
                     payload: Some((TypeHint::U8, "status").into()),
                     description: Some("desc text here".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (692, 23, 24).into(),
                 },
                 EventMetadata {
@@ -964,6 +1157,8 @@ mod tests {
                     payload: Some((TypeHint::I16, "(int16_t) data").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (933, 32, 24).into(),
                 },
                 EventMetadata {
@@ -972,6 +1167,8 @@ mod tests {
                     payload: Some((TypeHint::I16, "(int16_t) data").into()),
                     description: Some("docs".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (1018, 34, 24).into(),
                 },
                 EventMetadata {
@@ -980,6 +1177,8 @@ mod tests {
                     payload: Some((TypeHint::I8, "(int8_t) *((uint8_t*) &mydata)").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (1111, 36, 24).into(),
                 },
                 EventMetadata {
@@ -988,6 +1187,8 @@ mod tests {
                     payload: Some((TypeHint::U16, "(uint16_t) *((uint16_t*) &mydata)").into()),
                     description: None,
                     tags: Some("my tag".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1215, 39, 24).into(),
                 },
                 EventMetadata {
@@ -996,6 +1197,8 @@ mod tests {
                     payload: Some((TypeHint::U16, "(uint16_t) *((uint16_t*) &mydata)").into()),
                     description: Some("docs".to_string()),
                     tags: Some("thing1;thing2;my::namespace;tag with spaces".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1372, 46, 24).into(),
                 },
                 EventMetadata {
@@ -1004,6 +1207,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "1 == 0").into()),
                     description: Some("Some description".to_string()),
                     tags: Some("EXPECTATION;SEVERITY_1;another tag".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1624, 54, 11).into(),
                 },
                 EventMetadata {
@@ -1012,6 +1217,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "*foo != (1 + bar)").into()),
                     description: None,
                     tags: Some("EXPECTATION;SEVERITY_2;network".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1909, 62, 5).into(),
                 },
                 EventMetadata {
@@ -1020,6 +1227,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "0 == 0").into()),
                     description: None,
                     tags: Some("EXPECTATION".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2067, 65, 5).into(),
                 },
                 EventMetadata {
@@ -1028,6 +1237,8 @@ mod tests {
                     payload: None,
                     description: Some("Description".to_string()),
                     tags: Some("network;file-system;other-tags".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2125, 67, 11).into(),
                 },
                 EventMetadata {
@@ -1036,6 +1247,8 @@ mod tests {
                     payload: Some((TypeHint::I8, "status").into()),
                     description: Some("Description".to_string()),
                     tags: Some("network;file-system;other-tags".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2308, 74, 5).into(),
                 },
                 EventMetadata {
@@ -1044,8 +1257,20 @@ mod tests {
                     payload: Some((TypeHint::Bool, "true").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (2516, 82, 5).into(),
                 },
+                EventMetadata {
+                    name: "EVENT_N".to_string(),
+                    probe_instance: "g_probe".to_string(),
+                    payload: None,
+                    description: Some("Description".to_string()),
+                    tags: Some("fault".to_string()),
+                    priority: Some("3".to_string()),
+                    span: None,
+                    location: (2585, 84, 5).into(),
+                },
             ])
         );
     }
@@ -1113,6 +1338,124 @@ assert(err == MODALITY_PROBE_ERROR_OK);
         assert_eq!(tokens, Err(Error::UnrecognizedTypeHint((0, 1, 1).into())));
     }
 
+    #[test]
+    fn wide_event_payload_type_hints() {
+        let parser = CParser::default();
+        let input = "MODALITY_PROBE_RECORD_W_I64(probe, EVENT_A, data);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![EventMetadata {
+                name: "EVENT_A".to_string(),
+                probe_instance: "probe".to_string(),
+                payload: Some((TypeHint::I64, "data").into()),
+                description: None,
+                tags: None,
+                priority: None,
+                span: None,
+                location: (0, 1, 1).into(),
+            }])
+        );
+    }
+
+    #[test]
+    fn event_payload_with_priority() {
+        let parser = CParser::default();
+        let input = r#"
+MODALITY_PROBE_RECORD_W_U32_W_PRIORITY(probe, EVENT_A, code, 3, MODALITY_TAGS(fault), "Fault code");
+MODALITY_PROBE_RECORD_W_F64_W_PRIORITY(probe, EVENT_B, 1.5, LOW_PRIORITY);
+"#;
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                EventMetadata {
+                    name: "EVENT_A".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::U32, "code").into()),
+                    description: Some("Fault code".to_string()),
+                    tags: Some("fault".to_string()),
+                    priority: Some("3".to_string()),
+                    span: None,
+                    location: (1, 2, 1).into(),
+                },
+                EventMetadata {
+                    name: "EVENT_B".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::F64, "1.5").into()),
+                    description: None,
+                    tags: None,
+                    priority: Some("LOW_PRIORITY".to_string()),
+                    span: None,
+                    location: (102, 3, 1).into(),
+                },
+            ])
+        );
+        let input = "MODALITY_PROBE_RECORD_W_U8_W_PRIORITY(probe, EVENT_A, 1);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(tokens, Err(Error::Syntax((0, 1, 1).into())));
+    }
+
+    #[test]
+    fn span_events() {
+        let parser = CParser::default();
+        let input = r#"
+MODALITY_PROBE_SPAN_BEGIN(probe, REQUEST_BEGIN, "Handling a request", MODALITY_TAGS(net));
+MODALITY_PROBE_SPAN_END(probe, REQUEST_END);
+MODALITY_PROBE_SPAN_BEGIN_W_TIME(probe, FLUSH_BEGIN, now());
+MODALITY_PROBE_SPAN_END_W_TIME(probe, FLUSH_END, now(), "Flushed");
+"#;
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                EventMetadata {
+                    name: "REQUEST_BEGIN".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: Some("Handling a request".to_string()),
+                    tags: Some("net".to_string()),
+                    priority: None,
+                    span: Some("REQUEST".to_string()),
+                    location: (1, 2, 1).into(),
+                },
+                EventMetadata {
+                    name: "REQUEST_END".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: None,
+                    tags: None,
+                    priority: None,
+                    span: Some("REQUEST".to_string()),
+                    location: (92, 3, 1).into(),
+                },
+                EventMetadata {
+                    name: "FLUSH_BEGIN".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: None,
+                    tags: None,
+                    priority: None,
+                    span: Some("FLUSH".to_string()),
+                    location: (137, 4, 1).into(),
+                },
+                EventMetadata {
+                    name: "FLUSH_END".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: Some("Flushed".to_string()),
+                    tags: None,
+                    priority: None,
+                    span: Some("FLUSH".to_string()),
+                    location: (198, 5, 1).into(),
+                },
+            ])
+        );
+        let input = "MODALITY_PROBE_SPAN_END(probe, REQUEST_BEGIN);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(tokens, Err(Error::SpanNameMissingSuffix((0, 1, 1).into())));
+    }
+
     #[test]
     fn event_payload_casing_errors() {
         let parser = CParser::default();
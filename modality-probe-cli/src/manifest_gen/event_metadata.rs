@@ -8,6 +8,9 @@ pub struct Payload(pub TypeHint, pub String);
 /// Event metadata
 ///
 /// Events with payloads will have a `payload` field.
+/// Events recorded with an explicit priority will have a `priority` field.
+/// Events marking the beginning or end of a span will have a `span`
+/// field holding the span's name.
 /// Events that have already been assigned an identifier will
 /// have `assigned_id` set.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
@@ -17,6 +20,8 @@ pub struct EventMetadata {
     pub payload: Option<Payload>,
     pub description: Option<String>,
     pub tags: Option<String>,
+    pub priority: Option<String>,
+    pub span: Option<String>,
     pub location: SourceLocation,
 }
 
@@ -32,7 +32,7 @@ impl FilePath {
             })
         } else if file.starts_with(search_path) {
             let mut path = PathBuf::new();
-            if let Some(p) = search_path.iter().last() {
+            if let Some(p) = search_path.iter().next_back() {
                 path.push(p)
             }
             let mut sp_iter = search_path.iter();
@@ -49,7 +49,7 @@ impl IdGen {
     }
 
     pub fn hashed_id(&mut self, token: &str) -> NonZeroU32 {
-        let mut max_tries = std::u16::MAX;
+        let mut max_tries = u16::MAX;
         loop {
             let hash = self.token_hash(token);
             if let Some(non_zero_hash) = NonZeroU32::new(hash) {
@@ -139,7 +139,7 @@ mod test {
     use proptest::prelude::*;
 
     prop_compose! {
-        fn gen_non_zero_id()(raw_id in 1..=std::u32::MAX) -> NonZeroU32 {
+        fn gen_non_zero_id()(raw_id in 1..=u32::MAX) -> NonZeroU32 {
             NonZeroU32::new(raw_id).unwrap()
         }
     }
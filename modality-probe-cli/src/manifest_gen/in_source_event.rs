@@ -41,6 +41,16 @@ impl InSourceEvent {
                 .map_or(String::new(), |p| p.0.to_string()),
             file: self.file.path.clone(),
             line: self.metadata.location.line.to_string(),
+            priority: self
+                .metadata
+                .priority
+                .as_ref()
+                .map_or(String::new(), |s| s.clone()),
+            span: self
+                .metadata
+                .span
+                .as_ref()
+                .map_or(String::new(), |s| s.clone()),
         }
     }
 
@@ -62,6 +72,18 @@ impl InSourceEvent {
                 .to_string()
                 .as_str()
                 .eq_ignore_ascii_case(other.line.as_str())
+            && self
+                .metadata
+                .priority
+                .as_deref()
+                .unwrap_or("")
+                .eq(other.priority.as_str())
+            && self
+                .metadata
+                .span
+                .as_deref()
+                .unwrap_or("")
+                .eq(other.span.as_str())
     }
 }
 
@@ -95,6 +117,8 @@ mod tests {
                 payload: Some((TypeHint::U8, "mydata").into()),
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -107,6 +131,8 @@ mod tests {
             tags: String::new(),
             type_hint: String::from("u8"),
             file: "main.c".to_string(),
+            priority: String::new(),
+            span: String::new(),
             line: "2".to_string(),
         };
         assert!(in_src_event.eq(&in_mf_event));
@@ -4,7 +4,7 @@ use crate::manifest_gen::{
     id_gen::IdGen,
     in_source_event::InSourceEvent,
     in_source_probe::InSourceProbe,
-    parser::{self, Parser},
+    parser::{self, Parser, SPAN_BEGIN_SUFFIX, SPAN_END_SUFFIX},
     rust_parser::RustParser,
 };
 use crate::{
@@ -39,9 +39,11 @@ pub enum ProbeCheckError {
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum EventCheckError {
     DuplicateNameInSource(InSourceEvent, InSourceEvent),
     NameNotUpperCase(InSourceEvent),
+    UnpairedSpan(InSourceEvent),
 }
 
 pub struct Invocations {
@@ -98,11 +100,11 @@ impl Invocations {
             .filter_map(Result::ok)
             .filter(|e| !e.file_type().is_dir())
         {
-            let should_consider = config.file_extensions.as_ref().map_or(true, |exts| {
+            let should_consider = config.file_extensions.as_ref().is_none_or(|exts| {
                 exts.iter()
                     .any(|ext| Some(OsStr::new(ext)) == entry.path().extension())
             });
-            let should_exclude = config.exclude_patterns.as_ref().map_or(false, |excludes| {
+            let should_exclude = config.exclude_patterns.as_ref().is_some_and(|excludes| {
                 excludes
                     .iter()
                     .any(|exclude| entry.path().to_string_lossy().contains(exclude.as_str()))
@@ -209,6 +211,7 @@ impl Invocations {
         self.code_hash
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn check_probes(&self) -> Result<(), ProbeCheckError> {
         let mut uniq = HashMap::new();
         for t in self.probes.iter() {
@@ -228,6 +231,7 @@ impl Invocations {
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn check_events(&self) -> Result<(), EventCheckError> {
         let mut uniq = HashMap::new();
         for e in self.events.iter() {
@@ -245,6 +249,24 @@ impl Invocations {
             }
         }
 
+        // Each span's beginning needs an end, and vice versa
+        for e in self.events.iter() {
+            if let Some(span) = &e.metadata.span {
+                let counterpart = if e.canonical_name().ends_with(SPAN_BEGIN_SUFFIX) {
+                    format!("{}{}", span, SPAN_END_SUFFIX)
+                } else {
+                    format!("{}{}", span, SPAN_BEGIN_SUFFIX)
+                };
+                let paired = uniq
+                    .get(&counterpart)
+                    .map(|other| other.metadata.span.is_some())
+                    .unwrap_or(false);
+                if !paired {
+                    return Err(EventCheckError::UnpairedSpan(e.clone()));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -424,6 +446,16 @@ impl Invocations {
                     if !e.tags.as_str().eq(src_tags) {
                         e.tags = String::from(src_tags);
                     }
+
+                    let src_priority = src_event.metadata.priority.as_deref().unwrap_or("");
+                    if !e.priority.as_str().eq(src_priority) {
+                        e.priority = String::from(src_priority);
+                    }
+
+                    let src_span = src_event.metadata.span.as_deref().unwrap_or("");
+                    if !e.span.as_str().eq(src_span) {
+                        e.span = String::from(src_span);
+                    }
                 });
         }
 
@@ -494,7 +526,7 @@ impl Invocations {
         probe_name: &str,
         existing_probe_ids: &mut Vec<u32>,
     ) -> ProbeId {
-        let mut max_tries = std::u16::MAX;
+        let mut max_tries = u16::MAX;
         loop {
             let next_id = gen.hashed_id(probe_name);
 
@@ -584,6 +616,18 @@ impl fmt::Display for EventCheckError {
                     e.file.path, e.metadata.location.line, e.metadata.location.column
                 )
             }
+            EventCheckError::UnpairedSpan(e) => {
+                writeln!(
+                    f,
+                    "The span event '{}' is missing its matching beginning or end",
+                    e.name(),
+                )?;
+                writeln!(
+                    f,
+                    "{}:{}:{}",
+                    e.file.path, e.metadata.location.line, e.metadata.location.column
+                )
+            }
         }
     }
 }
@@ -862,6 +906,8 @@ mod tests {
                 payload: None,
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -876,6 +922,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn event_span_unpaired_error() {
+        let span_event = |name: &str, line| InSourceEvent {
+            file: FilePath {
+                full_path: "main.c".to_string(),
+                path: "main.c".to_string(),
+            },
+            metadata: EventMetadata {
+                name: name.to_string(),
+                probe_instance: "probe".to_string(),
+                payload: None,
+                description: None,
+                tags: None,
+                priority: None,
+                span: Some("REQUEST".to_string()),
+                location: (1, line, 3).into(),
+            },
+        };
+        let begin = span_event("REQUEST_BEGIN", 2);
+        let end = span_event("REQUEST_END", 3);
+        let invcs = Invocations {
+            probes: Vec::new(),
+            events: vec![begin.clone(), end],
+            ..Default::default()
+        };
+        assert_eq!(invcs.check_events(), Ok(()));
+        let invcs = Invocations {
+            probes: Vec::new(),
+            events: vec![begin.clone()],
+            ..Default::default()
+        };
+        assert_eq!(
+            invcs.check_events(),
+            Err(EventCheckError::UnpairedSpan(begin))
+        );
+    }
+
     #[test]
     fn event_name_duplicate_error() {
         let e0 = InSourceEvent {
@@ -889,6 +972,8 @@ mod tests {
                 payload: None,
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -903,6 +988,8 @@ mod tests {
                 payload: None,
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 3, 3).into(),
             },
         };
@@ -930,6 +1017,8 @@ mod tests {
                 payload: None,
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -942,6 +1031,8 @@ mod tests {
             type_hint: String::new(),
             file: "file.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         };
         let invcs = Invocations {
             probes: Vec::new(),
@@ -963,6 +1054,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         });
         assert_eq!(mf_events.events, expected);
     }
@@ -980,6 +1073,8 @@ mod tests {
                 payload: None,
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 8, 3).into(),
             },
         };
@@ -992,6 +1087,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         };
         let invcs = Invocations {
             probes: Vec::new(),
@@ -1013,6 +1110,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "8".to_string(),
+            priority: String::new(),
+            span: String::new(),
         });
         assert_eq!(mf_events.events, expected);
     }
@@ -1030,6 +1129,8 @@ mod tests {
                 payload: Some((TypeHint::U8, "mydata").into()),
                 description: None,
                 tags: None,
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -1042,6 +1143,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         };
         let invcs = Invocations {
             probes: Vec::new(),
@@ -1063,6 +1166,8 @@ mod tests {
             type_hint: "u8".to_string(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         });
         assert_eq!(mf_events.events, expected);
     }
@@ -1080,6 +1185,8 @@ mod tests {
                 payload: None,
                 description: Some("desc".to_string()),
                 tags: Some("my-tag".to_string()),
+                priority: None,
+                span: None,
                 location: (1, 2, 3).into(),
             },
         };
@@ -1092,6 +1199,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         };
         let invcs = Invocations {
             probes: Vec::new(),
@@ -1113,6 +1222,8 @@ mod tests {
             type_hint: String::new(),
             file: "main.c".to_string(),
             line: "2".to_string(),
+            priority: String::new(),
+            span: String::new(),
         });
         assert_eq!(mf_events.events, expected);
     }
@@ -101,3 +101,19 @@ pub(crate) fn event_name_valid(s: &str) -> bool {
 pub(crate) fn probe_name_valid(s: &str) -> bool {
     s.chars().all(|c| c.is_alphanumeric() || c == '_')
 }
+
+/// Event name suffix of a span's beginning
+pub(crate) const SPAN_BEGIN_SUFFIX: &str = "_BEGIN";
+
+/// Event name suffix of a span's end
+pub(crate) const SPAN_END_SUFFIX: &str = "_END";
+
+/// The name of the span marked by an event, which is the event's
+/// name without the given span suffix
+pub(crate) fn span_name(event_name: &str, suffix: &str) -> Option<String> {
+    let name = event_name.to_uppercase();
+    match name.strip_suffix(suffix) {
+        Some(span) if !span.is_empty() => Some(span.to_string()),
+        _ => None,
+    }
+}
@@ -1,8 +1,9 @@
 use crate::manifest_gen::{
     event_metadata::EventMetadata,
     parser::{
-        self, event_name_valid, probe_name_valid, remove_double_quotes, tags_or_desc_valid,
-        trimmed_string, trimmed_string_w_space, Parser, ParserConfig, Span,
+        self, event_name_valid, probe_name_valid, remove_double_quotes, span_name,
+        tags_or_desc_valid, trimmed_string, trimmed_string_w_space, Parser, ParserConfig, Span,
+        SPAN_BEGIN_SUFFIX, SPAN_END_SUFFIX,
     },
     probe_metadata::ProbeMetadata,
     source_location::SourceLocation,
@@ -41,6 +42,7 @@ pub enum Error {
     MissingSemicolon(SourceLocation),
     UnrecognizedTypeHint(SourceLocation),
     EmptyTags(SourceLocation),
+    SpanNameMissingSuffix(SourceLocation),
 }
 
 impl Error {
@@ -50,6 +52,7 @@ impl Error {
             Error::MissingSemicolon(l) => l,
             Error::UnrecognizedTypeHint(l) => l,
             Error::EmptyTags(l) => l,
+            Error::SpanNameMissingSuffix(l) => l,
         }
     }
 }
@@ -126,12 +129,16 @@ fn parse_record_event_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     if found_try.is_some() {
         let (input, found_expect) = peek(opt(tag("try_expect")))(input)?;
         let (input, found_with_time) = peek(opt(tag("try_record_w_time")))(input)?;
+        let (input, found_with_priority) = peek(opt(tag("try_record_w_priority")))(input)?;
         if found_expect.is_some() {
             let (input, metadata) = expect_try_call_exp(input)?;
             Ok((input, metadata))
         } else if found_with_time.is_some() {
             let (input, metadata) = event_try_with_time_call_exp(input)?;
             Ok((input, metadata))
+        } else if found_with_priority.is_some() {
+            let (input, metadata) = event_try_with_priority_call_exp(input)?;
+            Ok((input, metadata))
         } else {
             let (input, found_with_payload) = peek(opt(tag("try_record_w_")))(input)?;
             let (input, metadata) = match found_with_payload {
@@ -143,12 +150,20 @@ fn parse_record_event_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     } else {
         let (input, found_expect) = peek(opt(tag("expect")))(input)?;
         let (input, found_with_time) = peek(opt(tag("record_w_time")))(input)?;
+        let (input, found_with_priority) = peek(opt(tag("record_w_priority")))(input)?;
+        let (input, found_span) = peek(opt(tag("span_")))(input)?;
         if found_expect.is_some() {
             let (input, metadata) = expect_call_exp(input)?;
             Ok((input, metadata))
+        } else if found_span.is_some() {
+            let (input, metadata) = span_call_exp(input)?;
+            Ok((input, metadata))
         } else if found_with_time.is_some() {
             let (input, metadata) = event_with_time_call_exp(input)?;
             Ok((input, metadata))
+        } else if found_with_priority.is_some() {
+            let (input, metadata) = event_with_priority_call_exp(input)?;
+            Ok((input, metadata))
         } else {
             let (input, found_with_payload) = peek(opt(tag("record_w_")))(input)?;
             let (input, metadata) = match found_with_payload {
@@ -220,6 +235,8 @@ fn expect_try_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: Some((TypeHint::U32, expr).into()),
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -300,6 +317,8 @@ fn expect_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: Some((TypeHint::U32, expr).into()),
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -366,6 +385,8 @@ fn event_try_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -437,6 +458,8 @@ fn event_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -499,6 +522,70 @@ fn event_try_with_time_call_exp(input: Span) -> ParserResult<Span, EventMetadata
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
+            location: pos.into(),
+        },
+    ))
+}
+
+fn event_try_with_priority_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
+    let (input, pos) = position(input)?;
+    let (input, _) = tag("try_record_w_priority!")(input)?;
+    let (input, _) = opt(line_ending)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("(")(input)?;
+    let (input, args) = take_until(";")(input)
+        .map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (input, _) =
+        tag(";")(input).map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (args, probe_instance) = variable_call_exp_arg(args)?;
+    let (args, name) = variable_call_exp_arg(args)?;
+    let name =
+        reduce_namespace(&name).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    if !event_name_valid(&name) {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
+    iter.for_each(|s| {
+        if !s.is_empty() {
+            arg_vec.push(s)
+        }
+    });
+    let (_args, _) = iter.finish()?;
+    match arg_vec.len() {
+        1..=3 => (), // At least a priority, maybe tags and description
+        _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
+    }
+    let priority = arg_vec.remove(0).trim().to_string();
+    if priority.is_empty() {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut tags_and_desc = arg_vec;
+    for s in tags_and_desc.iter_mut() {
+        *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    }
+    let tags_pos = tags_and_desc.iter().position(|s| s.contains("tags="));
+    let tags = tags_pos
+        .map(|index| tags_and_desc.remove(index))
+        .map(|s| s.replace("tags=", ""));
+    if let Some(t) = &tags {
+        if t.is_empty() {
+            return Err(make_failure(input, Error::EmptyTags(pos.into())));
+        }
+    }
+    let description = tags_and_desc.pop();
+    Ok((
+        input,
+        EventMetadata {
+            name,
+            probe_instance,
+            payload: None,
+            description,
+            tags,
+            priority: Some(priority),
+            span: None,
             location: pos.into(),
         },
     ))
@@ -514,6 +601,10 @@ fn event_try_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetad
     if has_time {
         type_hint = type_hint.replace("_w_time", "");
     }
+    let has_priority = type_hint.ends_with("_w_priority");
+    if has_priority {
+        type_hint = type_hint.trim_end_matches("_w_priority").to_string();
+    }
     let type_hint = TypeHint::from_str(type_hint.as_str())
         .map_err(|_| make_failure(input, Error::UnrecognizedTypeHint(pos.into())))?;
     let (input, _) = opt(line_ending)(input)?;
@@ -538,21 +629,24 @@ fn event_try_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetad
         }
     });
     let (_args, _) = iter.finish()?;
-    if has_time {
-        match arg_vec.len() {
-            2..=4 => (), // At least payload and time, maybe tags and description
-            _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
-        }
-    } else {
-        match arg_vec.len() {
-            1..=3 => (), // At least a payload, maybe tags and description
-            _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
-        }
+    // At least a payload, maybe a time or priority, maybe tags and description
+    let required_args = 1 + has_time as usize + has_priority as usize;
+    if arg_vec.len() < required_args || arg_vec.len() > required_args + 2 {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
     }
     let payload = arg_vec.remove(0).trim().to_string();
     if has_time {
         let _time = arg_vec.remove(0);
     }
+    let priority = if has_priority {
+        let priority = arg_vec.remove(0).trim().to_string();
+        if priority.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+        Some(priority)
+    } else {
+        None
+    };
     let mut tags_and_desc = arg_vec;
     for s in tags_and_desc.iter_mut() {
         *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
@@ -580,6 +674,8 @@ fn event_try_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetad
             payload: Some((type_hint, payload).into()),
             description,
             tags,
+            priority,
+            span: None,
             location: pos.into(),
         },
     ))
@@ -595,6 +691,10 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     if has_time {
         type_hint = type_hint.replace("_w_time", "");
     }
+    let has_priority = type_hint.ends_with("_w_priority");
+    if has_priority {
+        type_hint = type_hint.trim_end_matches("_w_priority").to_string();
+    }
     let type_hint = TypeHint::from_str(type_hint.as_str())
         .map_err(|_| make_failure(input, Error::UnrecognizedTypeHint(pos.into())))?;
     let (input, _) = tag("(")(input).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
@@ -631,6 +731,19 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
     if payload.is_empty() {
         return Err(make_failure(input, Error::Syntax(pos.into())));
     }
+    let priority = if has_priority {
+        let priority = arg_vec
+            .first()
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        if priority.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+        arg_vec.remove(0);
+        Some(priority)
+    } else {
+        None
+    };
     let mut tags_and_desc: Vec<String> = arg_vec
         .iter()
         .filter(|s| !s.is_empty())
@@ -666,6 +779,82 @@ fn event_with_payload_call_exp(input: Span) -> ParserResult<Span, EventMetadata>
             payload: Some((type_hint, payload).into()),
             description,
             tags,
+            priority,
+            span: None,
+            location: pos.into(),
+        },
+    ))
+}
+
+fn event_with_priority_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
+    let (input, pos) = position(input)?;
+    let (input, _) = tag("record_w_priority!")(input)?;
+    let (input, _) = tag("(")(input).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
+    let (input, _) = opt(line_ending)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, args) = take_until(");")(input)
+        .map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (input, _) =
+        tag(");")(input).map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (args, probe_instance) =
+        variable_call_exp_arg(args).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
+    let (args, full_name) =
+        variable_call_exp_arg(args).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
+    let arg = Span::new_extra(&full_name, input.extra);
+    let (_, name) = alt((
+        reduced_event_id_exp_alt_a,
+        reduced_event_id_exp_alt_b,
+        reduced_event_id_exp_alt_c,
+    ))(arg)
+    .map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    let name =
+        reduce_namespace(&name).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    if !event_name_valid(&name) {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
+    iter.for_each(|s| arg_vec.push(s));
+    let (_args, _) = iter.finish()?;
+    if arg_vec.is_empty() {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let priority = arg_vec.remove(0).trim().to_string();
+    if priority.is_empty() {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let mut tags_and_desc: Vec<String> = arg_vec
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| (*s).to_string())
+        .collect();
+    match tags_and_desc.len() {
+        0..=2 => (), // Maybe tags and description
+        _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
+    }
+    for s in tags_and_desc.iter_mut() {
+        *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    }
+    let tags_pos = tags_and_desc.iter().position(|s| s.contains("tags="));
+    let tags = tags_pos
+        .map(|index| tags_and_desc.swap_remove(index))
+        .map(|s| s.replace("tags=", ""));
+    if let Some(t) = &tags {
+        if t.is_empty() {
+            return Err(make_failure(input, Error::EmptyTags(pos.into())));
+        }
+    }
+    let description = tags_and_desc.pop();
+    Ok((
+        input,
+        EventMetadata {
+            name,
+            probe_instance,
+            payload: None,
+            description,
+            tags,
+            priority: Some(priority),
+            span: None,
             location: pos.into(),
         },
     ))
@@ -743,6 +932,113 @@ fn event_with_time_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
             payload: None,
             description,
             tags,
+            priority: None,
+            span: None,
+            location: pos.into(),
+        },
+    ))
+}
+
+fn span_call_exp(input: Span) -> ParserResult<Span, EventMetadata> {
+    let (input, pos) = position(input)?;
+    let (input, macro_name) = alt((
+        tag("span_begin_w_time!"),
+        tag("span_end_w_time!"),
+        tag("span_begin!"),
+        tag("span_end!"),
+    ))(input)?;
+    let with_time = macro_name.fragment().ends_with("_w_time!");
+    let suffix = if macro_name.fragment().starts_with("span_begin") {
+        SPAN_BEGIN_SUFFIX
+    } else {
+        SPAN_END_SUFFIX
+    };
+    let (input, _) = tag("(")(input).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
+    let (input, _) = opt(line_ending)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, args) = take_until(");")(input)
+        .map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (input, _) =
+        tag(");")(input).map_err(|e| convert_error(e, Error::MissingSemicolon(pos.into())))?;
+    let (args, probe_instance) =
+        variable_call_exp_arg(args).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?;
+    let expect_more_args = peek(variable_call_exp_arg)(args).is_ok();
+    let (args, full_name) = if expect_more_args {
+        variable_call_exp_arg(args).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?
+    } else if with_time {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    } else {
+        rest_string(args).map_err(|e| convert_error(e, Error::Syntax(pos.into())))?
+    };
+    let arg = Span::new_extra(&full_name, input.extra);
+    let (_, name) = alt((
+        reduced_event_id_exp_alt_a,
+        reduced_event_id_exp_alt_b,
+        reduced_event_id_exp_alt_c,
+    ))(arg)
+    .map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    let name =
+        reduce_namespace(&name).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    if !event_name_valid(&name) {
+        return Err(make_failure(input, Error::Syntax(pos.into())));
+    }
+    let span = span_name(&name, suffix)
+        .ok_or_else(|| make_failure(input, Error::SpanNameMissingSuffix(pos.into())))?;
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut iter = iterator(args, multi_variable_call_exp_arg_literal);
+    iter.for_each(|s| arg_vec.push(s));
+    let (_args, _) = iter.finish()?;
+    if with_time {
+        if arg_vec.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+        let arg = arg_vec.remove(0);
+        let arg = Span::new_extra(&arg, input.extra);
+        let (_, time) =
+            rest_literal(arg).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+        if time.is_empty() {
+            return Err(make_failure(input, Error::Syntax(pos.into())));
+        }
+    }
+    let mut tags_and_desc: Vec<String> = arg_vec
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| (*s).to_string())
+        .collect();
+    match tags_and_desc.len() {
+        0..=2 => (), // Maybe tags and description
+        _ => return Err(make_failure(input, Error::Syntax(pos.into()))),
+    }
+    for s in tags_and_desc.iter_mut() {
+        *s = truncate_and_trim(s).map_err(|_| make_failure(input, Error::Syntax(pos.into())))?;
+    }
+    let tags_pos = tags_and_desc.iter().position(|s| s.contains("tags="));
+    let mut tags = tags_pos
+        .map(|index| tags_and_desc.swap_remove(index))
+        .map(|s| s.replace("tags=", ""));
+    if let Some(t) = &tags {
+        if t.is_empty() {
+            return Err(make_failure(input, Error::EmptyTags(pos.into())));
+        }
+    }
+    if with_time {
+        match &mut tags {
+            Some(t) if !t.contains("TIME") => t.insert_str(0, "TIME;"),
+            Some(_) => (),
+            None => tags = Some(String::from("TIME")),
+        }
+    }
+    let description = tags_and_desc.pop();
+    Ok((
+        input,
+        EventMetadata {
+            name,
+            probe_instance,
+            payload: None,
+            description,
+            tags,
+            priority: None,
+            span: Some(span),
             location: pos.into(),
         },
     ))
@@ -1022,7 +1318,7 @@ fn reduce_namespace(s: &str) -> Result<String, ()> {
         match split.last() {
             None => Err(()),
             Some(last) => {
-                if *last == "" {
+                if last.is_empty() {
                     Err(())
                 } else {
                     Ok((*last).to_string())
@@ -1052,6 +1348,10 @@ impl fmt::Display for Error {
                 f,
                 "Enountered an empty tags statement while parsing a record event call-site",
             ),
+            Error::SpanNameMissingSuffix(_) => write!(
+                f,
+                "Span call-site event names need to end in _BEGIN or _END respectively",
+            ),
         }
     }
 }
@@ -1145,7 +1445,7 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
-    const MIXED_PROBE_ID_INPUT: &'static str = r#"
+    const MIXED_PROBE_ID_INPUT: &str = r#"
     /// Docs ModalityProbe::try_initialize_at(a, b, c)
     let probe = try_initialize_at!(&mut storage, PROBE_ID_A, NanosecondResolution::UNSPECIFIED, WallClockId::local_only(), RestartCounterProvider::NoRestartTracking)
         .expect("Could not initialize ModalityProbe");
@@ -1183,7 +1483,7 @@ mod tests {
         tags!("thing1", "thing2", "my::namespace", "tag with spaces"))?; //docs
 "#;
 
-    const MIXED_EVENT_RECORDING_INPUT: &'static str = r#"
+    const MIXED_EVENT_RECORDING_INPUT: &str = r#"
     /* Comments */
     try_record!(probe, EVENT_A, "my text").expect("Could not record event");
 
@@ -1290,6 +1590,10 @@ mod tests {
         tags!("thing1", "thing2"),
         "desc" //docs
     );
+
+    record_w_priority!(probe, EventId::new(EVENT_P).unwrap(), 3, tags!("fault"));
+
+    try_record_w_priority!(probe, EVENT_Q, LOW_PRIORITY, "desc").unwrap();
 "#;
 
     #[test]
@@ -1352,6 +1656,8 @@ mod tests {
                     payload: None,
                     description: Some("my text".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (24, 3, 5).into(),
                 },
                 EventMetadata {
@@ -1360,6 +1666,8 @@ mod tests {
                     payload: None,
                     description: Some("my text".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (102, 5, 5).into(),
                 },
                 EventMetadata {
@@ -1368,6 +1676,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (256, 12, 5).into(),
                 },
                 EventMetadata {
@@ -1376,6 +1686,8 @@ mod tests {
                     payload: None,
                     description: Some("my text".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (323, 14, 5).into(),
                 },
                 EventMetadata {
@@ -1384,6 +1696,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (456, 20, 5).into(),
                 },
                 EventMetadata {
@@ -1392,6 +1706,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (497, 21, 5).into(),
                 },
                 EventMetadata {
@@ -1400,6 +1716,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (550, 23, 5).into(),
                 },
                 EventMetadata {
@@ -1408,6 +1726,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: Some("my tag;tag 2".to_string()),
+                    priority: None,
+                    span: None,
                     location: (619, 25, 5).into(),
                 },
                 EventMetadata {
@@ -1416,6 +1736,8 @@ mod tests {
                     payload: None,
                     description: Some("docs".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (694, 26, 5).into(),
                 },
                 EventMetadata {
@@ -1424,6 +1746,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "1_u32").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (765, 28, 5).into(),
                 },
                 EventMetadata {
@@ -1432,6 +1756,8 @@ mod tests {
                     payload: Some((TypeHint::F32, "1.234_f32").into()),
                     description: Some("desc".to_string()),
                     tags: Some("tag 1".to_string()),
+                    priority: None,
+                    span: None,
                     location: (944, 35, 5).into(),
                 },
                 EventMetadata {
@@ -1440,6 +1766,8 @@ mod tests {
                     payload: Some((TypeHint::I8, "-2_i8").into()),
                     description: Some("desc".to_string()),
                     tags: Some("thing1;thing2;my::namespace;tag with spaces".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1069, 38, 5).into(),
                 },
                 EventMetadata {
@@ -1448,6 +1776,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "14 == (10 + 4)").into()),
                     description: Some("Some description".to_string()),
                     tags: Some("EXPECTATION;SEVERITY_1;another tag".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1270, 46, 5).into(),
                 },
                 EventMetadata {
@@ -1456,6 +1786,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "foo != bar").into()),
                     description: None,
                     tags: Some("EXPECTATION;SEVERITY_2;network".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1447, 54, 5).into(),
                 },
                 EventMetadata {
@@ -1464,6 +1796,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "foo != bar").into()),
                     description: None,
                     tags: Some("EXPECTATION".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1614, 57, 21).into(),
                 },
                 EventMetadata {
@@ -1472,6 +1806,8 @@ mod tests {
                     payload: None,
                     description: Some("At the top of the loop".to_string()),
                     tags: Some("example;my-tag".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1668, 59, 5).into(),
                 },
                 EventMetadata {
@@ -1480,6 +1816,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "loop_counter % 10 == 0").into()),
                     description: Some("Loop counter % 10 event".to_string()),
                     tags: Some("EXPECTATION;example".to_string()),
+                    priority: None,
+                    span: None,
                     location: (1840, 67, 5).into(),
                 },
                 EventMetadata {
@@ -1488,6 +1826,8 @@ mod tests {
                     payload: None,
                     description: Some("Measurement producer thread started".to_string()),
                     tags: Some("producer".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2041, 76, 5).into(),
                 },
                 EventMetadata {
@@ -1496,6 +1836,8 @@ mod tests {
                     payload: None,
                     description: Some("desc".to_string()),
                     tags: Some("TIME;tag1".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2176, 83, 5).into(),
                 },
                 EventMetadata {
@@ -1504,6 +1846,8 @@ mod tests {
                     payload: None,
                     description: None,
                     tags: Some("TIME;tag1".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2289, 91, 5).into(),
                 },
                 EventMetadata {
@@ -1512,6 +1856,8 @@ mod tests {
                     payload: Some((TypeHint::U32, "p").into()),
                     description: None,
                     tags: Some("TIME".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2382, 98, 5).into(),
                 },
                 EventMetadata {
@@ -1520,8 +1866,30 @@ mod tests {
                     payload: Some((TypeHint::I8, "-2_i8").into()),
                     description: Some("desc".to_string()),
                     tags: Some("TIME;thing1;thing2".to_string()),
+                    priority: None,
+                    span: None,
                     location: (2477, 101, 5).into(),
                 },
+                EventMetadata {
+                    name: "EVENT_P".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: None,
+                    tags: Some("fault".to_string()),
+                    priority: Some("3".to_string()),
+                    span: None,
+                    location: (2642, 109, 5).into(),
+                },
+                EventMetadata {
+                    name: "EVENT_Q".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: Some("desc".to_string()),
+                    tags: None,
+                    priority: Some("LOW_PRIORITY".to_string()),
+                    span: None,
+                    location: (2725, 111, 5).into(),
+                },
             ])
         );
     }
@@ -1609,11 +1977,152 @@ record!(probe, EventId::try_from(EVENT_D).unwrap(), "my text");
         let input = "record_w_i12!(t, EVENT, 1);";
         let tokens = parser.parse_event_md(input);
         assert_eq!(tokens, Err(Error::UnrecognizedTypeHint((0, 1, 1).into())));
-        let input = "record_w_f64!(t, EVENT, 1, asdf);";
+        let input = "record_w_f16!(t, EVENT, 1, asdf);";
         let tokens = parser.parse_event_md(input);
         assert_eq!(tokens, Err(Error::UnrecognizedTypeHint((0, 1, 1).into())));
     }
 
+    #[test]
+    fn wide_event_payload_type_hints() {
+        let parser = RustParser::default();
+        let input = r#"
+try_record_w_u64!(probe, EVENT_A, 1_u64).unwrap();
+try_record_w_f64_w_time!(probe, EVENT_B, 1.5_f64, t).unwrap();
+"#;
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                EventMetadata {
+                    name: "EVENT_A".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::U64, "1_u64").into()),
+                    description: None,
+                    tags: None,
+                    priority: None,
+                    span: None,
+                    location: (1, 2, 1).into(),
+                },
+                EventMetadata {
+                    name: "EVENT_B".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::F64, "1.5_f64").into()),
+                    description: None,
+                    tags: Some("TIME".to_string()),
+                    priority: None,
+                    span: None,
+                    location: (52, 3, 1).into(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn event_payload_with_priority() {
+        let parser = RustParser::default();
+        let input = r#"
+record_w_u32_w_priority!(probe, EVENT_A, code, 3, tags!("fault"), "Fault code");
+try_record_w_i64_w_priority!(probe, EVENT_B, -1_i64, LOW_PRIORITY).unwrap();
+"#;
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                EventMetadata {
+                    name: "EVENT_A".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::U32, "code").into()),
+                    description: Some("Fault code".to_string()),
+                    tags: Some("fault".to_string()),
+                    priority: Some("3".to_string()),
+                    span: None,
+                    location: (1, 2, 1).into(),
+                },
+                EventMetadata {
+                    name: "EVENT_B".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: Some((TypeHint::I64, "-1_i64").into()),
+                    description: None,
+                    tags: None,
+                    priority: Some("LOW_PRIORITY".to_string()),
+                    span: None,
+                    location: (82, 3, 1).into(),
+                },
+            ])
+        );
+        let input = "record_w_u8_w_priority!(probe, EVENT_A, 1);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(tokens, Err(Error::Syntax((0, 1, 1).into())));
+    }
+
+    #[test]
+    fn span_events() {
+        let parser = RustParser::default();
+        let input = r#"
+span_begin!(probe, REQUEST_BEGIN, "Handling a request", tags!("net"));
+span_end!(probe, events::REQUEST_END);
+span_begin_w_time!(probe, FLUSH_BEGIN, now);
+span_end_w_time!(probe, FLUSH_END, now, "Flushed");
+"#;
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                EventMetadata {
+                    name: "REQUEST_BEGIN".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: Some("Handling a request".to_string()),
+                    tags: Some("net".to_string()),
+                    priority: None,
+                    span: Some("REQUEST".to_string()),
+                    location: (1, 2, 1).into(),
+                },
+                EventMetadata {
+                    name: "REQUEST_END".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: None,
+                    tags: None,
+                    priority: None,
+                    span: Some("REQUEST".to_string()),
+                    location: (72, 3, 1).into(),
+                },
+                EventMetadata {
+                    name: "FLUSH_BEGIN".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: None,
+                    tags: Some("TIME".to_string()),
+                    priority: None,
+                    span: Some("FLUSH".to_string()),
+                    location: (111, 4, 1).into(),
+                },
+                EventMetadata {
+                    name: "FLUSH_END".to_string(),
+                    probe_instance: "probe".to_string(),
+                    payload: None,
+                    description: Some("Flushed".to_string()),
+                    tags: Some("TIME".to_string()),
+                    priority: None,
+                    span: Some("FLUSH".to_string()),
+                    location: (156, 5, 1).into(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn span_event_name_errors() {
+        let parser = RustParser::default();
+        let input = "span_begin!(probe, REQUEST_START);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(tokens, Err(Error::SpanNameMissingSuffix((0, 1, 1).into())));
+        let input = "span_end_w_time!(probe, REQUEST_BEGIN, now);";
+        let tokens = parser.parse_event_md(input);
+        assert_eq!(tokens, Err(Error::SpanNameMissingSuffix((0, 1, 1).into())));
+    }
+
     #[test]
     fn ignores_include_statements() {
         let parser = RustParser::default();
@@ -1653,6 +2162,8 @@ record_w_i8!(probe, EventId::try_from(events::more::EVENT_D).unwrap(), 1_i8, "de
                     payload: None,
                     description: Some("desc".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (1, 2, 1).into(),
                 },
                 EventMetadata {
@@ -1661,6 +2172,8 @@ record_w_i8!(probe, EventId::try_from(events::more::EVENT_D).unwrap(), 1_i8, "de
                     payload: None,
                     description: Some("my text".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (56, 4, 1).into(),
                 },
                 EventMetadata {
@@ -1669,6 +2182,8 @@ record_w_i8!(probe, EventId::try_from(events::more::EVENT_D).unwrap(), 1_i8, "de
                     payload: Some((TypeHint::U32, "1_u32").into()),
                     description: None,
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (142, 6, 1).into(),
                 },
                 EventMetadata {
@@ -1677,6 +2192,8 @@ record_w_i8!(probe, EventId::try_from(events::more::EVENT_D).unwrap(), 1_i8, "de
                     payload: Some((TypeHint::I8, "1_i8").into()),
                     description: Some("desc".to_string()),
                     tags: None,
+                    priority: None,
+                    span: None,
                     location: (226, 8, 1).into(),
                 },
             ])
@@ -11,6 +11,9 @@ pub enum TypeHint {
     U32,
     F32,
     Bool,
+    I64,
+    U64,
+    F64,
 }
 
 impl Default for TypeHint {
@@ -33,6 +36,9 @@ impl str::FromStr for TypeHint {
             "u32" => TypeHint::U32,
             "f32" => TypeHint::F32,
             "bool" => TypeHint::Bool,
+            "i64" => TypeHint::I64,
+            "u64" => TypeHint::U64,
+            "f64" => TypeHint::F64,
             _ => return Err("Unsupported type hint"),
         })
     }
@@ -49,6 +55,9 @@ impl TypeHint {
             TypeHint::U32 => "u32",
             TypeHint::F32 => "f32",
             TypeHint::Bool => "bool",
+            TypeHint::I64 => "i64",
+            TypeHint::U64 => "u64",
+            TypeHint::F64 => "f64",
         }
     }
 }
@@ -5,7 +5,13 @@ use uuid::Uuid;
 
 use modality_probe::{EventId, ProbeId};
 
-use crate::{component::Component, events::Events, give_up, hopefully, hopefully_ok};
+use crate::{
+    component::Component,
+    events::Events,
+    give_up, hopefully, hopefully_ok,
+    manifest_gen::parser::{SPAN_BEGIN_SUFFIX, SPAN_END_SUFFIX},
+    span::SpanEdge,
+};
 
 /// A row in the events.csv for a component.
 #[derive(PartialEq, Eq, Debug, Clone, Deserialize, Hash, Serialize)]
@@ -18,6 +24,23 @@ pub struct EventMeta {
     pub description: String,
     pub file: String,
     pub line: String,
+    #[serde(default)]
+    pub span: String,
+}
+
+impl EventMeta {
+    /// Which end of a span this event marks, if it marks one.
+    pub fn span_edge(&self) -> Option<SpanEdge> {
+        if self.span.is_empty() {
+            None
+        } else if self.name.ends_with(SPAN_BEGIN_SUFFIX) {
+            Some(SpanEdge::Begin)
+        } else if self.name.ends_with(SPAN_END_SUFFIX) {
+            Some(SpanEdge::End)
+        } else {
+            None
+        }
+    }
 }
 
 /// A row in probes.csv for a component.
@@ -40,9 +63,7 @@ pub struct Cfg {
     pub component_names: HashMap<String, String>,
 }
 
-pub fn assemble_components(
-    comp_dirs: &mut Vec<PathBuf>,
-) -> Result<Cfg, Box<dyn std::error::Error>> {
+pub fn assemble_components(comp_dirs: &mut [PathBuf]) -> Result<Cfg, Box<dyn std::error::Error>> {
     let mut probes = HashMap::new();
     let mut probes_to_components = HashMap::new();
     let mut events = HashMap::new();
@@ -120,7 +141,7 @@ pub fn get_event_meta<'a>(
 
 pub fn parsed_payload(
     th: Option<&str>,
-    pl: Option<u32>,
+    pl: Option<u64>,
 ) -> Result<Option<String>, Box<dyn std::error::Error>> {
     match (th, pl) {
         (Some("i8"), Some(pl)) => Ok(Some(format!("{}", pl as i8))),
@@ -129,8 +150,11 @@ pub fn parsed_payload(
         (Some("u8"), Some(pl)) => Ok(Some(format!("{}", pl as u8))),
         (Some("u16"), Some(pl)) => Ok(Some(format!("{}", pl as u16))),
         (Some("u32"), Some(pl)) => Ok(Some(format!("{}", pl as u32))),
-        (Some("f32"), Some(pl)) => Ok(Some(format!("{}", f32::from_bits(pl)))),
+        (Some("f32"), Some(pl)) => Ok(Some(format!("{}", f32::from_bits(pl as u32)))),
         (Some("bool"), Some(pl)) => Ok(Some(format!("{}", pl != 0))),
+        (Some("i64"), Some(pl)) => Ok(Some(format!("{}", pl as i64))),
+        (Some("u64"), Some(pl)) => Ok(Some(format!("{}", { pl }))),
+        (Some("f64"), Some(pl)) => Ok(Some(format!("{}", f64::from_bits(pl)))),
         (Some(th), Some(_)) => give_up!(format!("{} is not a valid type hint", th)),
         (None, Some(pl)) => Ok(Some(pl.to_string())),
         (Some(_), None) => Ok(None),
@@ -138,6 +162,15 @@ pub fn parsed_payload(
     }
 }
 
+/// Render a blob payload as text when it's valid UTF-8, otherwise as
+/// a hex string.
+pub fn blob_payload(blob: &[u8]) -> String {
+    match std::str::from_utf8(blob) {
+        Ok(s) => s.to_string(),
+        Err(_) => blob.iter().map(|b| format!("{:02x}", b)).collect(),
+    }
+}
+
 fn add_internal_events(events: &mut HashMap<(Uuid, u32), EventMeta>) {
     let nil_uuid = Uuid::nil();
     for ie in Events::internal_events() {
@@ -154,6 +187,7 @@ fn add_internal_events(events: &mut HashMap<(Uuid, u32), EventMeta>) {
             description: ie.description,
             file: ie.file,
             line: ie.line,
+            span: ie.span,
         };
         events.insert((nil_uuid, ie.id.0), ev.clone());
     }
@@ -168,34 +202,34 @@ mod test {
 
     use crate::meta::{EventMeta, ProbeMeta};
 
-    const COMP_ONE_CONTENT: &'static str = r#"
+    const COMP_ONE_CONTENT: &str = r#"
 name = "one"
 id = "bba61171-e4b5-4db4-8cbb-8b4f4a581ca1"
 "#;
-    const COMP_TWO_CONTENT: &'static str = r#"
+    const COMP_TWO_CONTENT: &str = r#"
 name = "two"
 id = "bba61171-e4b5-4db4-8cbb-8b4f4a581ca2"
 "#;
-    const PROBE_ONE_CONTENT: &'static str = "
+    const PROBE_ONE_CONTENT: &str = "
 component_id,id,name,description,tags,file,line
 bba61171-e4b5-4db4-8cbb-8b4f4a581cb1,1,PROBE_ONE,probe one,example,examples/event-recording.rs,26";
-    const PROBE_TWO_CONTENT: &'static str = "
+    const PROBE_TWO_CONTENT: &str = "
 component_id,id,name,description,tags,file,line
 bba61171-e4b5-4db4-8cbb-8b4f4a581cb2,2,PROBE_TWO,probe two,example,examples/event-recording.rs,26";
 
-    const EVENT_ONE_CONTENT: &'static str = "
+    const EVENT_ONE_CONTENT: &str = "
 component_id,id,name,description,tags,type_hint,file,line
 bba61171-e4b5-4db4-8cbb-8b4f4a581cb1,1,TEST_ONE,test event one,,,,26
 ";
-    const EVENT_TWO_CONTENT: &'static str = "
+    const EVENT_TWO_CONTENT: &str = "
 component_id,id,name,description,tags,type_hint,file,line
 bba61171-e4b5-4db4-8cbb-8b4f4a581cb2,2,TEST_TWO,test event two,,,,36
 ";
 
     #[test]
     fn comp_assembly() {
-        let mut tmp_one = tempdir().unwrap().into_path();
-        let mut tmp_two = tempdir().unwrap().into_path();
+        let mut tmp_one = tempdir().unwrap().keep();
+        let mut tmp_two = tempdir().unwrap().keep();
 
         {
             tmp_one.push("Component.toml");
@@ -269,6 +303,7 @@ bba61171-e4b5-4db4-8cbb-8b4f4a581cb2,2,TEST_TWO,test event two,,,,36
                         tags: String::new(),
                         file: String::new(),
                         line: "26".to_string(),
+                        span: String::new(),
                     },
                 ),
                 (
@@ -286,6 +321,7 @@ bba61171-e4b5-4db4-8cbb-8b4f4a581cb2,2,TEST_TWO,test event two,,,,36
                         tags: String::new(),
                         file: String::new(),
                         line: "36".to_string(),
+                        span: String::new(),
                     },
                 ),
             ]
@@ -296,8 +332,7 @@ bba61171-e4b5-4db4-8cbb-8b4f4a581cb2,2,TEST_TWO,test event two,,,,36
 
             tmp_one.pop();
             tmp_two.pop();
-            let cfg =
-                super::assemble_components(&mut vec![tmp_one.clone(), tmp_two.clone()]).unwrap();
+            let cfg = super::assemble_components(&mut [tmp_one.clone(), tmp_two.clone()]).unwrap();
 
             assert_eq!(&cfg.probes, &expected_probes);
             assert_eq!(&cfg.events, &expected_events);
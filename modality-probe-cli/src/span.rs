@@ -0,0 +1,322 @@
+//! Pairing of span beginnings and ends found in a trace.
+
+use std::{collections::HashMap, fmt};
+
+use modality_probe::{Nanoseconds, ProbeId};
+use modality_probe_collector_common::{LogEntryData, ReportLogEntry};
+
+use crate::meta::{self, Cfg};
+
+/// Which end of a span an event marks.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SpanEdge {
+    Begin,
+    End,
+}
+
+/// How long a span took.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SpanDuration {
+    /// Both ends of the span carried a wall clock time.
+    Nanoseconds(u64),
+    /// Without wall clock times, the number of events the probe
+    /// recorded between the span's beginning and end.
+    Events(usize),
+}
+
+impl fmt::Display for SpanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpanDuration::Nanoseconds(ns) => write!(f, "{}ns", ns),
+            SpanDuration::Events(1) => write!(f, "1 event"),
+            SpanDuration::Events(n) => write!(f, "{} events", n),
+        }
+    }
+}
+
+/// A span for which both the beginning and the end were found.
+#[derive(Clone, Debug)]
+pub struct CompletedSpan {
+    pub name: String,
+    pub probe_id: ProbeId,
+    pub begin: ReportLogEntry,
+    pub end: ReportLogEntry,
+    pub duration: SpanDuration,
+}
+
+struct OpenSpan {
+    begin: ReportLogEntry,
+    time: Option<Nanoseconds>,
+    event_index: usize,
+}
+
+/// Tracks the spans open on each probe, pairing up the end of a
+/// span with the most recent unmatched beginning of the same span on
+/// the same probe.
+#[derive(Default)]
+pub struct SpanTracker {
+    open: HashMap<(ProbeId, String), Vec<OpenSpan>>,
+    event_counts: HashMap<ProbeId, usize>,
+}
+
+impl SpanTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the tracker the next entry of a probe's log. Entries
+    /// from each probe must be given in the order they were
+    /// recorded, boot by boot. A `ProbeRestarted` entry drops the
+    /// spans left open on that probe, since their ends will never
+    /// come.
+    ///
+    /// Returns the completed span if the entry ends one.
+    pub fn observe(&mut self, cfg: &Cfg, row: &ReportLogEntry) -> Option<CompletedSpan> {
+        if row.is_probe_restart() {
+            self.open
+                .retain(|(probe_id, _), _| *probe_id != row.probe_id);
+            return None;
+        }
+        if let LogEntryData::EventRepeated(_, n) = row.data {
+            // The repeats were recorded in between too
+            *self.event_counts.entry(row.probe_id).or_insert(0) += n as usize;
+            return None;
+        }
+        let (eid, time) = match row.data {
+            LogEntryData::Event(id)
+            | LogEntryData::EventWithPayload(id, _)
+            | LogEntryData::EventWithBlob(id, _) => (id, None),
+            LogEntryData::EventWithTime(t, id)
+            | LogEntryData::EventWithPayloadWithTime(t, id, _) => (id, Some(t)),
+            _ => return None,
+        };
+        let count = self.event_counts.entry(row.probe_id).or_insert(0);
+        let event_index = *count;
+        *count += 1;
+
+        let emeta = meta::get_event_meta(cfg, &row.probe_id, &eid).ok()?;
+        let edge = emeta.span_edge()?;
+        let key = (row.probe_id, emeta.span.clone());
+        match edge {
+            SpanEdge::Begin => {
+                self.open.entry(key).or_default().push(OpenSpan {
+                    begin: row.clone(),
+                    time,
+                    event_index,
+                });
+                None
+            }
+            SpanEdge::End => {
+                let open = self.open.get_mut(&key)?.pop()?;
+                let duration = match (open.time, time) {
+                    (Some(begin), Some(end)) if end.get() >= begin.get() => {
+                        SpanDuration::Nanoseconds(end.get() - begin.get())
+                    }
+                    _ => SpanDuration::Events(event_index - open.event_index - 1),
+                };
+                Some(CompletedSpan {
+                    name: key.1,
+                    probe_id: row.probe_id,
+                    begin: open.begin,
+                    end: row.clone(),
+                    duration,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use modality_probe::{
+        EventId, LogicalClock, NanosecondResolution, Nanoseconds, ProbeEpoch, ProbeId, ProbeTicks,
+        WallClockId,
+    };
+    use modality_probe_collector_common::{
+        LogEntryData, ReportLogEntry, SequenceNumber, SessionId,
+    };
+
+    use crate::meta::{Cfg, EventMeta, ProbeMeta};
+
+    use super::*;
+
+    fn event_meta(component_id: Uuid, id: u32, name: &str, span: &str) -> EventMeta {
+        EventMeta {
+            component_id,
+            id,
+            name: name.to_string(),
+            type_hint: None,
+            tags: String::new(),
+            description: String::new(),
+            file: String::new(),
+            line: String::new(),
+            span: span.to_string(),
+        }
+    }
+
+    fn cfg() -> Cfg {
+        let comp_id = Uuid::new_v4();
+        let events = vec![
+            event_meta(comp_id, 1, "REQUEST_BEGIN", "REQUEST"),
+            event_meta(comp_id, 2, "REQUEST_END", "REQUEST"),
+            event_meta(comp_id, 3, "WORK", ""),
+        ]
+        .into_iter()
+        .map(|e| ((comp_id, e.id), e))
+        .collect();
+        let mut probes = HashMap::new();
+        probes.insert(
+            1,
+            ProbeMeta {
+                component_id: comp_id,
+                tags: String::new(),
+                id: 1,
+                name: "one".to_string(),
+                description: String::new(),
+                file: String::new(),
+                line: String::new(),
+            },
+        );
+        let mut probes_to_components = HashMap::new();
+        probes_to_components.insert(1, comp_id);
+        Cfg {
+            probes,
+            events,
+            probes_to_components,
+            component_names: HashMap::new(),
+        }
+    }
+
+    fn row(seq_idx: u32, data: LogEntryData) -> ReportLogEntry {
+        let probe_id = ProbeId::new(1).unwrap();
+        ReportLogEntry {
+            session_id: SessionId(1),
+            sequence_number: SequenceNumber(1),
+            sequence_index: seq_idx,
+            probe_id,
+            persistent_epoch_counting: false,
+            data,
+            time_resolution: NanosecondResolution(0),
+            wall_clock_id: WallClockId(0),
+            receive_time: Utc::now(),
+            clock: LogicalClock {
+                id: probe_id,
+                epoch: ProbeEpoch(0),
+                ticks: ProbeTicks(0),
+            },
+        }
+    }
+
+    fn ev(id: u32) -> EventId {
+        EventId::new(id).unwrap()
+    }
+
+    fn ns(t: u64) -> Nanoseconds {
+        Nanoseconds::new(t).unwrap()
+    }
+
+    #[test]
+    fn spans_without_time_count_events() {
+        let cfg = cfg();
+        let mut tracker = SpanTracker::new();
+        assert!(tracker
+            .observe(&cfg, &row(0, LogEntryData::Event(ev(1))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(1, LogEntryData::Event(ev(3))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(2, LogEntryData::EventWithPayload(ev(3), 7)))
+            .is_none());
+        let span = tracker
+            .observe(&cfg, &row(3, LogEntryData::Event(ev(2))))
+            .unwrap();
+        assert_eq!(span.name, "REQUEST");
+        assert_eq!(span.begin.sequence_index, 0);
+        assert_eq!(span.end.sequence_index, 3);
+        assert_eq!(span.duration, SpanDuration::Events(2));
+
+        // An end without a beginning isn't a span
+        assert!(tracker
+            .observe(&cfg, &row(4, LogEntryData::Event(ev(2))))
+            .is_none());
+    }
+
+    #[test]
+    fn spans_without_time_count_repeated_events() {
+        let cfg = cfg();
+        let mut tracker = SpanTracker::new();
+        assert!(tracker
+            .observe(&cfg, &row(0, LogEntryData::Event(ev(1))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(1, LogEntryData::Event(ev(3))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(2, LogEntryData::EventRepeated(ev(3), 4)))
+            .is_none());
+        let span = tracker
+            .observe(&cfg, &row(3, LogEntryData::Event(ev(2))))
+            .unwrap();
+        assert_eq!(span.duration, SpanDuration::Events(5));
+    }
+
+    #[test]
+    fn nested_spans_with_time() {
+        let cfg = cfg();
+        let mut tracker = SpanTracker::new();
+        assert!(tracker
+            .observe(&cfg, &row(0, LogEntryData::EventWithTime(ns(100), ev(1))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(1, LogEntryData::EventWithTime(ns(150), ev(1))))
+            .is_none());
+        let inner = tracker
+            .observe(&cfg, &row(2, LogEntryData::EventWithTime(ns(175), ev(2))))
+            .unwrap();
+        assert_eq!(inner.begin.sequence_index, 1);
+        assert_eq!(inner.duration, SpanDuration::Nanoseconds(25));
+        // Only the end carries a time, so fall back to counting events
+        let outer = tracker
+            .observe(&cfg, &row(3, LogEntryData::Event(ev(2))))
+            .unwrap();
+        assert_eq!(outer.begin.sequence_index, 0);
+        assert_eq!(outer.duration, SpanDuration::Events(2));
+        assert_eq!(outer.duration.to_string(), "2 events");
+        assert_eq!(inner.duration.to_string(), "25ns");
+    }
+
+    #[test]
+    fn restarts_drop_open_spans() {
+        let cfg = cfg();
+        let mut tracker = SpanTracker::new();
+        assert!(tracker
+            .observe(&cfg, &row(0, LogEntryData::Event(ev(1))))
+            .is_none());
+        assert!(tracker
+            .observe(
+                &cfg,
+                &row(
+                    ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX,
+                    LogEntryData::ProbeRestarted
+                )
+            )
+            .is_none());
+        // The end from the new boot doesn't pair with the old beginning
+        assert!(tracker
+            .observe(&cfg, &row(1, LogEntryData::Event(ev(2))))
+            .is_none());
+        assert!(tracker
+            .observe(&cfg, &row(2, LogEntryData::Event(ev(1))))
+            .is_none());
+        let span = tracker
+            .observe(&cfg, &row(3, LogEntryData::Event(ev(2))))
+            .unwrap();
+        assert_eq!(span.begin.sequence_index, 2);
+    }
+}
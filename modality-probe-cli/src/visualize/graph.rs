@@ -2,17 +2,18 @@ use std::{collections::HashSet, hash::Hash, iter::Peekable};
 
 use tinytemplate::TinyTemplate;
 
-use modality_probe_collector_common::{ReportIter, ReportLogEntry};
+use modality_probe_collector_common::ReportLogEntry;
 use modality_probe_graph::{EventDigraph, Graph, GraphEvent};
 
 use crate::{
     description_format::DescriptionFormat,
     hopefully,
     meta::{self, Cfg},
+    span::CompletedSpan,
 };
 
 use super::templates::{
-    self, Component, ComponentSet, Context, Edge, EdgeSet, Event, Probe, ProbeSet,
+    self, Component, ComponentSet, Context, Edge, EdgeSet, Event, Probe, ProbeSet, Span,
 };
 
 pub fn log_to_graph<I>(
@@ -26,13 +27,10 @@ where
         nodes: HashSet::new(),
         edges: HashSet::new(),
     });
-    let report_iter = ReportIter::new(log);
-    for report in report_iter {
-        hopefully!(
-            graph.add_report(&report, include_internals),
-            "Encountered an error reconstructing the graph"
-        )?;
-    }
+    hopefully!(
+        graph.add_log(log, include_internals),
+        "Encountered an error reconstructing the graph"
+    )?;
     Ok(graph)
 }
 
@@ -46,7 +44,7 @@ where
 }
 
 impl NodeAndEdgeLists<GraphEvent> {
-    pub fn as_complete<'a>(&'a self) -> NodeAndEdgeLists<&'a GraphEvent> {
+    pub fn as_complete(&self) -> NodeAndEdgeLists<&GraphEvent> {
         NodeAndEdgeLists {
             nodes: self.nodes.iter().collect(),
             edges: self.edges.iter().map(|(s, t)| (s, t)).collect(),
@@ -55,20 +53,21 @@ impl NodeAndEdgeLists<GraphEvent> {
 
     /// Pare down a complete graph into only trace clocks, which is to
     /// say, only the interactions between probes.
-    pub fn as_interactions<'a>(&'a self) -> NodeAndEdgeLists<&'a GraphEvent> {
+    pub fn as_interactions(&self) -> NodeAndEdgeLists<&GraphEvent> {
         let mut node_set = HashSet::new();
         let mut edge_set = HashSet::new();
         self.filter(
-            |n| node_set.insert((n.probe_id, n.clock)),
+            |n| node_set.insert((n.probe_id, n.boot, n.clock)),
             |s, t| {
                 s.probe_id != t.probe_id
-                    && edge_set.insert(((s.probe_id, s.clock), (t.probe_id, t.clock)))
+                    && edge_set
+                        .insert(((s.probe_id, s.boot, s.clock), (t.probe_id, t.boot, t.clock)))
             },
         )
     }
 
     /// Pare down a complete graph into the event transitions.
-    pub fn as_states<'a>(&'a self) -> NodeAndEdgeLists<&'a GraphEvent> {
+    pub fn as_states(&self) -> NodeAndEdgeLists<&GraphEvent> {
         let mut node_set = HashSet::new();
         let mut edge_set = HashSet::new();
         self.filter(
@@ -79,7 +78,7 @@ impl NodeAndEdgeLists<GraphEvent> {
 
     /// Pare down a complete graph into just the probes and their
     /// communication topology.
-    pub fn as_topology<'a>(&'a self) -> NodeAndEdgeLists<&'a GraphEvent> {
+    pub fn as_topology(&self) -> NodeAndEdgeLists<&GraphEvent> {
         let mut node_set = HashSet::new();
         let mut edge_set = HashSet::new();
         self.filter(
@@ -109,14 +108,18 @@ impl NodeAndEdgeLists<GraphEvent> {
     }
 }
 
-impl<'a> NodeAndEdgeLists<&'a GraphEvent> {
+impl NodeAndEdgeLists<&GraphEvent> {
+    /// Render the graph with the given template. Spans whose
+    /// beginning and end are both nodes in the graph are drawn as
+    /// labeled edges between the two.
     pub fn dot(
         &self,
         cfg: &Cfg,
+        spans: &[CompletedSpan],
         name: &'static str,
         temp: &'static str,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let ctx = graph_to_tree(&self.nodes, &self.edges, cfg);
+        let ctx = graph_to_tree(&self.nodes, &self.edges, spans, cfg);
         let mut tt = TinyTemplate::new();
         tt.add_formatter(
             "discrete_color_formatter",
@@ -144,11 +147,13 @@ impl Graph for NodeAndEdgeLists<GraphEvent> {
 fn graph_to_tree<'a>(
     nodes: &HashSet<&GraphEvent>,
     edges: &HashSet<(&GraphEvent, &GraphEvent)>,
+    spans: &[CompletedSpan],
     cfg: &'a Cfg,
 ) -> Context<'a> {
     let mut ctx = Context {
         components: ComponentSet::new(),
         edges: EdgeSet::new(),
+        spans: Vec::new(),
     };
 
     let mut cluster_idx = 0;
@@ -197,13 +202,9 @@ fn graph_to_tree<'a>(
                 })
             });
             let has_log_str = emeta.description.contains_formatting();
-            let log_str = if has_log_str && payload.is_some() {
-                emeta
-                    .description
-                    .format_payload(payload.as_ref().unwrap())
-                    .ok()
-            } else {
-                None
+            let log_str = match payload.as_ref() {
+                Some(p) if has_log_str => emeta.description.format_payload(p).ok(),
+                _ => None,
             };
 
             probe.events.push(Event {
@@ -219,6 +220,7 @@ fn graph_to_tree<'a>(
                 clock: node.clock.pack().1,
                 seq: node.seq.0,
                 seq_idx: node.seq_idx,
+                boot: node.boot,
             });
         } else {
             probe.events.push(Event {
@@ -234,6 +236,7 @@ fn graph_to_tree<'a>(
                 clock: node.clock.pack().1,
                 seq: node.seq.0,
                 seq_idx: node.seq_idx,
+                boot: node.boot,
             });
         };
     }
@@ -254,6 +257,7 @@ fn graph_to_tree<'a>(
                     raw_probe_id: s.probe_id.get_raw(),
                     seq: s.seq.0,
                     seq_idx: s.seq_idx,
+                    boot: s.boot,
                     clock: s.clock.pack().1,
                     // Payloads aren't needed for edge
                     // enumeration.
@@ -271,6 +275,7 @@ fn graph_to_tree<'a>(
                     raw_probe_id: s.probe_id.get_raw(),
                     seq: s.seq.0,
                     seq_idx: s.seq_idx,
+                    boot: s.boot,
                     clock: s.clock.pack().1,
                     // Payloads aren't needed for edge
                     // enumeration.
@@ -296,6 +301,7 @@ fn graph_to_tree<'a>(
                     raw_probe_id: t.probe_id.get_raw(),
                     seq: t.seq.0,
                     seq_idx: t.seq_idx,
+                    boot: t.boot,
                     clock: t.clock.pack().1,
                     // Payloads aren't needed for edge
                     // enumeration.
@@ -313,6 +319,7 @@ fn graph_to_tree<'a>(
                     raw_probe_id: t.probe_id.get_raw(),
                     seq: t.seq.0,
                     seq_idx: t.seq_idx,
+                    boot: t.boot,
                     clock: t.clock.pack().1,
                     // Payloads aren't needed for edge
                     // enumeration.
@@ -324,16 +331,68 @@ fn graph_to_tree<'a>(
             }
         };
 
-        ctx.edges.insert(Edge { from, to });
+        // The only edge between two boots of a probe is across the
+        // restart
+        let restart = from.boot != to.boot;
+        ctx.edges.insert(Edge { from, to, restart });
+    }
+
+    for span in spans {
+        let find = |entry: &ReportLogEntry| {
+            nodes.iter().find(|n| {
+                n.probe_id == entry.probe_id
+                    && n.seq == entry.sequence_number
+                    && n.seq_idx == entry.sequence_index as usize
+            })
+        };
+        if let (Some(b), Some(e)) = (find(&span.begin), find(&span.end)) {
+            if let (Some(from), Some(to)) = (span_endpoint(cfg, b), span_endpoint(cfg, e)) {
+                ctx.spans.push(Span {
+                    from,
+                    to,
+                    label: format!("{} ({})", span.name, span.duration),
+                });
+            }
+        }
     }
     ctx
 }
 
+fn span_endpoint<'a>(cfg: &'a Cfg, node: &GraphEvent) -> Option<Event<'a>> {
+    let emeta = meta::get_event_meta(cfg, &node.probe_id, &node.id).ok()?;
+    let probe_name = if let Some(pmeta) = cfg.probes.get(&node.probe_id.get_raw()) {
+        pmeta.name.clone()
+    } else {
+        node.probe_id.get_raw().to_string()
+    };
+    Some(Event {
+        is_known: true,
+        probe_name,
+        meta: Some(emeta),
+        raw_id: node.id.get_raw(),
+        raw_probe_id: node.probe_id.get_raw(),
+        seq: node.seq.0,
+        seq_idx: node.seq_idx,
+        boot: node.boot,
+        clock: node.clock.pack().1,
+        payload: None,
+        has_payload: false,
+        log_str: None,
+        has_log_str: false,
+    })
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use uuid::Uuid;
 
-    use crate::meta::{Cfg, EventMeta, ProbeMeta};
+    use modality_probe::{EventId, ProbeId};
+    use modality_probe_collector_common::LogEntryData;
+
+    use crate::{
+        meta::{Cfg, EventMeta, ProbeMeta},
+        span::{CompletedSpan, SpanDuration},
+    };
 
     use super::super::templates;
 
@@ -404,6 +463,7 @@ pub(crate) mod test {
                         description: "one".to_string(),
                         file: "one.c".to_string(),
                         line: "1".to_string(),
+                        span: String::new(),
                     },
                 ),
                 (
@@ -417,6 +477,7 @@ pub(crate) mod test {
                         description: "two".to_string(),
                         file: "two.c".to_string(),
                         line: "2".to_string(),
+                        span: String::new(),
                     },
                 ),
                 (
@@ -430,6 +491,7 @@ pub(crate) mod test {
                         description: "three".to_string(),
                         file: "three.c".to_string(),
                         line: "3".to_string(),
+                        span: String::new(),
                     },
                 ),
                 (
@@ -443,6 +505,7 @@ pub(crate) mod test {
                         description: "four".to_string(),
                         file: "four.c".to_string(),
                         line: "4".to_string(),
+                        span: String::new(),
                     },
                 ),
             ]
@@ -468,9 +531,67 @@ pub(crate) mod test {
         let dot = graph
             .graph
             .as_complete()
-            .dot(&cfg, "complete", templates::COMPLETE)
+            .dot(&cfg, &[], "complete", templates::COMPLETE)
+            .unwrap();
+        assert!(dot.contains("one_one_1_1 ->\n    two_two_1_3"), "{}", dot);
+    }
+
+    #[test]
+    fn complete_dot_with_restart() {
+        let cfg = cfg();
+        let first_boot = modality_probe_graph::test_support::diamond()
+            .into_iter()
+            .filter(|e| e.probe_id == ProbeId::new(1).unwrap())
+            .collect::<Vec<_>>();
+        let mut restart = first_boot[0].clone();
+        restart.data = LogEntryData::ProbeRestarted;
+        let log = first_boot
+            .iter()
+            .cloned()
+            .chain(Some(restart))
+            .chain(first_boot.iter().cloned());
+        let graph = super::log_to_graph(log.peekable(), false).unwrap();
+
+        let dot = graph
+            .graph
+            .as_complete()
+            .dot(&cfg, &[], "complete", templates::COMPLETE)
+            .unwrap();
+        assert!(dot.contains("one_one_1_1_1 ["), "{}", dot);
+        assert!(
+            dot.contains("one_one_1_1 ->\n    one_one_1_1_1 [\n        style = dashed"),
+            "{}",
+            dot
+        );
+    }
+
+    #[test]
+    fn complete_dot_with_spans() {
+        let cfg = cfg();
+        let diamond_log = modality_probe_graph::test_support::diamond();
+        let event = |raw_id: u32| {
+            diamond_log
+                .iter()
+                .find(|e| e.data == LogEntryData::Event(EventId::new(raw_id).unwrap()))
+                .unwrap()
+                .clone()
+        };
+        let spans = vec![CompletedSpan {
+            name: "SPAN".to_string(),
+            probe_id: ProbeId::new(1).unwrap(),
+            begin: event(1),
+            end: event(2),
+            duration: SpanDuration::Events(3),
+        }];
+        let graph = super::log_to_graph(diamond_log.clone().into_iter().peekable(), false).unwrap();
+
+        let dot = graph
+            .graph
+            .as_complete()
+            .dot(&cfg, &spans, "complete", templates::COMPLETE)
             .unwrap();
-        assert!(dot.contains("one_one_1_1 ->\n    two_two_1_3"), dot);
+        assert!(dot.contains("one_one_1_1 ->\n    two_two_1_3 ["), "{}", dot);
+        assert!(dot.contains("label      = \"SPAN (3 events)\""), "{}", dot);
     }
 
     #[test]
@@ -484,9 +605,9 @@ pub(crate) mod test {
         let dot = graph
             .graph
             .as_interactions()
-            .dot(&cfg, "interactions", templates::INTERACTIONS)
+            .dot(&cfg, &[], "interactions", templates::INTERACTIONS)
             .unwrap();
-        assert!(dot.contains("one_0 -> two_1"), dot);
+        assert!(dot.contains("one_0 -> two_1"), "{}", dot);
     }
 
     #[test]
@@ -500,9 +621,9 @@ pub(crate) mod test {
         let dot = graph
             .graph
             .as_states()
-            .dot(&cfg, "states", templates::STATES)
+            .dot(&cfg, &[], "states", templates::STATES)
             .unwrap();
-        assert!(dot.contains("one_AT_one ->\n    two_AT_two"), dot);
+        assert!(dot.contains("one_AT_one ->\n    two_AT_two"), "{}", dot);
     }
 
     #[test]
@@ -516,8 +637,8 @@ pub(crate) mod test {
         let dot = graph
             .graph
             .as_topology()
-            .dot(&cfg, "topo", templates::TOPO)
+            .dot(&cfg, &[], "topo", templates::TOPO)
             .unwrap();
-        assert!(dot.contains("one -> two"), dot);
+        assert!(dot.contains("one -> two"), "{}", dot);
     }
 }
@@ -1,12 +1,13 @@
 //! Visualize a causal graph using the Graphiz / Dot
 
-use std::{fs::File, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 
 use structopt::StructOpt;
 
-use modality_probe_collector_common::json;
+use modality_probe::ProbeId;
+use modality_probe_collector_common::{json, ReportLogEntry};
 
-use crate::{give_up, hopefully, meta};
+use crate::{give_up, hopefully, log, meta, span::SpanTracker};
 
 pub mod graph;
 mod templates;
@@ -26,7 +27,8 @@ pub struct Visualize {
     /// components, provide this switch multiple times.
     #[structopt(short, long, required = true)]
     pub component_path: Vec<PathBuf>,
-    /// The path to the collected trace.
+    /// The path to the collected trace. Segments rotated out of it by
+    /// a collector are read along with it, compressed or not.
     #[structopt(short, long, required = true)]
     pub report: PathBuf,
     /// The type of graph to output.
@@ -58,17 +60,29 @@ impl FromStr for GraphType {
 
 pub fn run(mut viz: Visualize) -> Result<(), Box<dyn std::error::Error>> {
     let cfg = meta::assemble_components(&mut viz.component_path)?;
-    let mut log_file = hopefully!(
-        File::open(&viz.report),
-        format!("Failed to open the report file at {}", viz.report.display(),)
+    let entries = hopefully!(
+        json::read_log_entries_from_path(&viz.report),
+        format!("Failed to read the report file at {}", viz.report.display(),)
     )?;
 
-    let graph = graph::log_to_graph(
-        json::read_log_entries(&mut log_file)?
-            .into_iter()
-            .peekable(),
-        viz.include_internal_events,
-    )?;
+    let mut probes: BTreeMap<ProbeId, Vec<ReportLogEntry>> = BTreeMap::new();
+    for e in entries.iter() {
+        probes.entry(e.probe_id).or_default().push(e.clone());
+    }
+    let mut tracker = SpanTracker::new();
+    let mut spans = Vec::new();
+    for plog in probes.values_mut() {
+        // Sorted last first
+        log::sort_by_boot(plog);
+        spans.extend(
+            plog.iter()
+                .rev()
+                .filter(|e| !e.is_internal_event())
+                .filter_map(|e| tracker.observe(&cfg, e)),
+        );
+    }
+
+    let graph = graph::log_to_graph(entries.into_iter().peekable(), viz.include_internal_events)?;
 
     match (viz.graph_type, viz.interactions_only) {
         (GraphType::Acyclic, false) => println!(
@@ -76,28 +90,30 @@ pub fn run(mut viz: Visualize) -> Result<(), Box<dyn std::error::Error>> {
             graph
                 .graph
                 .as_complete()
-                .dot(&cfg, "complete", templates::COMPLETE)?
+                .dot(&cfg, &spans, "complete", templates::COMPLETE)?
         ),
         (GraphType::Acyclic, true) => println!(
             "{}",
-            graph
-                .graph
-                .as_interactions()
-                .dot(&cfg, "interactions", templates::INTERACTIONS)?
+            graph.graph.as_interactions().dot(
+                &cfg,
+                &spans,
+                "interactions",
+                templates::INTERACTIONS
+            )?
         ),
         (GraphType::Cyclic, false) => println!(
             "{}",
             graph
                 .graph
                 .as_states()
-                .dot(&cfg, "states", templates::STATES)?
+                .dot(&cfg, &spans, "states", templates::STATES)?
         ),
         (GraphType::Cyclic, true) => println!(
             "{}",
             graph
                 .graph
                 .as_topology()
-                .dot(&cfg, "topo", templates::TOPO)?
+                .dot(&cfg, &spans, "topo", templates::TOPO)?
         ),
     }
 
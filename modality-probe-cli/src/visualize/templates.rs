@@ -135,6 +135,7 @@ impl<'a> Serialize for EdgeSet<'a> {
 pub struct Context<'a> {
     pub components: ComponentSet<'a>,
     pub edges: EdgeSet<'a>,
+    pub spans: Vec<Span<'a>>,
 }
 
 #[derive(Hash, PartialEq, Eq, Serialize)]
@@ -151,12 +152,21 @@ pub struct Event<'a> {
     pub clock: u32,
     pub seq: u64,
     pub seq_idx: usize,
+    pub boot: u32,
 }
 
 #[derive(PartialEq, Eq, Hash, Serialize)]
 pub struct Edge<'a> {
     pub from: Event<'a>,
     pub to: Event<'a>,
+    pub restart: bool,
+}
+
+#[derive(Serialize)]
+pub struct Span<'a> {
+    pub from: Event<'a>,
+    pub to: Event<'a>,
+    pub label: String,
 }
 
 pub fn discrete_color_formatter(
@@ -164,15 +174,7 @@ pub fn discrete_color_formatter(
     out: &mut String,
 ) -> Result<(), tinytemplate::error::Error> {
     let idx = match val {
-        Value::Number(n) => {
-            if n.is_u64() {
-                (n.as_u64().unwrap() % 10) as usize
-            } else {
-                return Err(tinytemplate::error::Error::GenericError {
-                    msg: "invalid value given to discrete_color_formatter".to_string(),
-                });
-            }
-        }
+        Value::Number(n) if n.is_u64() => (n.as_u64().unwrap() % 10) as usize,
         Value::String(s) => {
             let mut h = DefaultHasher::new();
             s.hash(&mut h);
@@ -193,15 +195,9 @@ pub fn gradient_color_formatter(
     out: &mut String,
 ) -> Result<(), tinytemplate::error::Error> {
     let idx = match val {
-        Value::Number(n) => {
-            if n.is_u64() {
-                let n_float = n.as_u64().unwrap() as f64;
-                (n_float % 10.0) / 10.0
-            } else {
-                return Err(tinytemplate::error::Error::GenericError {
-                    msg: "invalid value given to discrete_color_formatter".to_string(),
-                });
-            }
+        Value::Number(n) if n.is_u64() => {
+            let n_float = n.as_u64().unwrap() as f64;
+            (n_float % 10.0) / 10.0
         }
         Value::String(s) => {
             let mut h = DefaultHasher::new();
@@ -235,7 +231,7 @@ pub const COMPLETE: &str = "digraph G \\{
             color = \"{ probe.name | discrete_color_formatter }\"
             {{ for event in probe.events }}
             {{ if event.is_known }}
-            { event.meta.name }_{ event.probe_name }_{ event.seq }_{ event.seq_idx } [
+            { event.meta.name }_{ event.probe_name }_{ event.seq }_{ event.seq_idx }{{ if event.boot }}_{ event.boot }{{ endif }} [
                 label        = \"{ event.meta.name }\"
                 {{ if event.has_log_str }}
                 message      = \"{ event.log_str }\"
@@ -250,13 +246,19 @@ pub const COMPLETE: &str = "digraph G \\{
                 {{ endif }}
                 raw_event_id = { event.raw_id }
                 raw_probe_id = { event.raw_probe_id }
+                {{ if event.boot }}
+                boot         = { event.boot }
+                {{ endif }}
             ];
             {{ else }}
-            UNKNOWN_EVENT_{ event.raw_id }_{ event.probe_name }_{ event.seq }_{ event.seq_idx } [
+            UNKNOWN_EVENT_{ event.raw_id }_{ event.probe_name }_{ event.seq }_{ event.seq_idx }{{ if event.boot }}_{ event.boot }{{ endif }} [
                 label        = \"{ event.raw_id }\"
                 probe        = \"{ event.probe_name }\"
                 raw_event_id = { event.raw_id }
                 raw_probe_id = { event.raw_probe_id }
+                {{ if event.boot }}
+                boot         = { event.boot }
+                {{ endif }}
             ];
             {{ endif }}
             {{ endfor }}
@@ -266,8 +268,21 @@ pub const COMPLETE: &str = "digraph G \\{
     {{ endfor }}
 
     {{ for edge in edges }}
-    {{ if edge.from.is_known }}{ edge.from.meta.name }{{ else }}UNKNOWN_EVENT_{ edge.from.raw_id }{{ endif }}_{ edge.from.probe_name }_{ edge.from.seq }_{ edge.from.seq_idx } ->
-    {{ if edge.to.is_known }}{ edge.to.meta.name }{{ else }}UNKNOWN_EVENT_{ edge.to.raw_id }{{ endif }}_{ edge.to.probe_name }_{ edge.to.seq }_{ edge.to.seq_idx };
+    {{ if edge.from.is_known }}{ edge.from.meta.name }{{ else }}UNKNOWN_EVENT_{ edge.from.raw_id }{{ endif }}_{ edge.from.probe_name }_{ edge.from.seq }_{ edge.from.seq_idx }{{ if edge.from.boot }}_{ edge.from.boot }{{ endif }} ->
+    {{ if edge.to.is_known }}{ edge.to.meta.name }{{ else }}UNKNOWN_EVENT_{ edge.to.raw_id }{{ endif }}_{ edge.to.probe_name }_{ edge.to.seq }_{ edge.to.seq_idx }{{ if edge.to.boot }}_{ edge.to.boot }{{ endif }}{{ if edge.restart }} [
+        style = dashed
+        label = \"restarted\"
+    ]{{ endif }};
+    {{ endfor }}
+
+    {{ for span in spans }}
+    { span.from.meta.name }_{ span.from.probe_name }_{ span.from.seq }_{ span.from.seq_idx }{{ if span.from.boot }}_{ span.from.boot }{{ endif }} ->
+    { span.to.meta.name }_{ span.to.probe_name }_{ span.to.seq }_{ span.to.seq_idx }{{ if span.to.boot }}_{ span.to.boot }{{ endif }} [
+        style      = dashed
+        color      = \"#000000\"
+        constraint = false
+        label      = \"{ span.label }\"
+    ];
     {{ endfor }}
 }";
 
@@ -287,7 +302,7 @@ pub const INTERACTIONS: &str = "digraph G \\{
             style = filled
             color = \"{ probe.name | discrete_color_formatter }\"
             {{ for event in probe.events }}
-            {{ if not event.is_known }}UNKNOWN_EVENT_{{ endif }}{ event.probe_name }_{ event.clock } [
+            {{ if not event.is_known }}UNKNOWN_EVENT_{{ endif }}{ event.probe_name }_{ event.clock }{{ if event.boot }}_{ event.boot }{{ endif }} [
                 label        = \"{ event.probe_name }({ event.clock })\"
                 raw_probe_id = { event.raw_probe_id }
             ];
@@ -298,7 +313,7 @@ pub const INTERACTIONS: &str = "digraph G \\{
     {{ endfor }}
 
     {{ for edge in edges }}
-    {{ if not edge.from.is_known }}UNKNOWN_EVENT_{{ endif }}{ edge.from.probe_name }_{ edge.from.clock } -> {{ if not edge.to.is_known }}UNKNOWN_EVENT_{{ endif }}{ edge.to.probe_name }_{ edge.to.clock }
+    {{ if not edge.from.is_known }}UNKNOWN_EVENT_{{ endif }}{ edge.from.probe_name }_{ edge.from.clock }{{ if edge.from.boot }}_{ edge.from.boot }{{ endif }} -> {{ if not edge.to.is_known }}UNKNOWN_EVENT_{{ endif }}{ edge.to.probe_name }_{ edge.to.clock }{{ if edge.to.boot }}_{ edge.to.boot }{{ endif }}
     {{ endfor }}
 }";
 
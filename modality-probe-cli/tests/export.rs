@@ -22,7 +22,7 @@ fn visualize_cli_produces_a_reasonable_dot_file() {
 
         let dot = Command::new("dot")
             .stdin(out.stdout.unwrap())
-            .args(&["-T", "svg"])
+            .args(["-T", "svg"])
             .output()
             .unwrap();
         assert!(dot.status.success(), "{:#?}", dot)
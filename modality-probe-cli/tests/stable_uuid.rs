@@ -103,16 +103,16 @@ fn stable_uuid() {
 
 #[cfg(all(target_endian = "little", target_pointer_width = "64"))]
 mod consts {
-    pub const COMPONENT_TOML: &'static str = r#"name = "my-component"
+    pub const COMPONENT_TOML: &str = r#"name = "my-component"
 id = "fa46ca95-c6fd-4020-b6a7-4323cfa084be"
 code_hash = "af30400eeda5e1f52684e3cfb4c3be1db2c31ba4e46dc8a0156c0fba9a2fcf7a"
 "#;
 
-    pub const COMPONENT_TOML_WO_HASHES: &'static str = r#"name = "my-component"
+    pub const COMPONENT_TOML_WO_HASHES: &str = r#"name = "my-component"
 id = "fa46ca95-c6fd-4020-b6a7-4323cfa084be"
 "#;
 
-    pub const C_SRC: &'static str = r#"
+    pub const C_SRC: &str = r#"
 size_t err = MODALITY_PROBE_INIT(
         &probe_storage[0],
         PROBE_STORAGE_SIZE,
@@ -132,9 +132,9 @@ size_t err = MODALITY_PROBE_RECORD_W_U8(
 assert(err == MODALITY_PROBE_ERROR_OK);
 "#;
 
-    pub const C_EXCLUDE_SRC: &'static str = "#include <stdlib.h>";
+    pub const C_EXCLUDE_SRC: &str = "#include <stdlib.h>";
 
-    pub const RUST_SRC: &'static str = r#"
+    pub const RUST_SRC: &str = r#"
 let probe = try_initialize_at!(
     &mut storage,
     PROBE_ID_B,
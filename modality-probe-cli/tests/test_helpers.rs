@@ -25,9 +25,7 @@ fn built_executable_path(name: &str) -> PathBuf {
             .join("debug")
             .join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
 
-    program_path.canonicalize().expect(&format!(
-        "Cannot resolve {} at {:?}",
-        name,
-        program_path.display()
-    ))
+    program_path
+        .canonicalize()
+        .unwrap_or_else(|_| panic!("Cannot resolve {} at {:?}", name, program_path.display()))
 }
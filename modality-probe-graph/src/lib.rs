@@ -1,12 +1,17 @@
 //! `modality-probe-graph` is a library that builds different types of node
 //! and edge lists from a columnar, unordered collection of log
 //! reports like the one that modality-probe-udp-collector creates.
+
+// The err-derive `Error` derive expands its impls inside anonymous consts
+#![allow(non_local_definitions)]
 use std::{collections::HashMap, hash::Hash};
 
 use err_derive::Error;
 
 use modality_probe::{EventId, LogicalClock, ProbeId};
-use modality_probe_collector_common::{EventLogEntry, Report, SequenceNumber};
+use modality_probe_collector_common::{
+    EventLogEntry, Report, ReportIter, ReportLogEntry, SequenceNumber,
+};
 
 /// A trait for the inner graph type of `EventDiagraph`. This enables
 /// a custom inner graph that can be purpose built for a use-case, but
@@ -23,10 +28,13 @@ pub trait Graph {
 pub struct GraphEvent {
     pub id: EventId,
     pub clock: LogicalClock,
-    pub payload: Option<u32>,
+    pub payload: Option<u64>,
     pub probe_id: ProbeId,
     pub seq: SequenceNumber,
     pub seq_idx: usize,
+    /// How many times the probe had restarted before this event. Events
+    /// from different boots can share sequence numbers and clocks.
+    pub boot: u32,
 }
 
 /// Errors returned by the `EventDigraph` methods.
@@ -70,6 +78,17 @@ pub struct EventDigraph<G: Graph> {
     /// This is the table used to look up the source events when a
     /// foreign clock is encountered in the log.
     last_event_by_probe_and_clock: HashMap<(ProbeId, u32), GraphEvent>,
+
+    /// How many times each probe has restarted so far.
+    boots: HashMap<ProbeId, u32>,
+
+    /// The last event seen from each probe, so that the last event
+    /// before a restart can be joined to the first event after it.
+    last_event_by_probe: HashMap<ProbeId, GraphEvent>,
+
+    /// When a probe restarts, the last event before the restart, to
+    /// be joined to the first event after it.
+    restart_edge_sources: HashMap<ProbeId, GraphEvent>,
 }
 
 impl<G: Graph> EventDigraph<G> {
@@ -80,6 +99,57 @@ impl<G: Graph> EventDigraph<G> {
             tail_pending_edge_sources: HashMap::new(),
             last_event_by_probe_and_seq_num: HashMap::new(),
             last_event_by_probe_and_clock: HashMap::new(),
+            boots: HashMap::new(),
+            last_event_by_probe: HashMap::new(),
+            restart_edge_sources: HashMap::new(),
+        }
+    }
+
+    /// Turn a log, as written by a collector, into nodes and edges on
+    /// the graph, starting a new boot of a probe wherever the
+    /// collector marked that it restarted.
+    pub fn add_log<I>(&mut self, log: I, include_internals: bool) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = ReportLogEntry>,
+    {
+        let mut log = log.into_iter().peekable();
+        while let Some(first) = log.next() {
+            if first.is_probe_restart() {
+                self.add_probe_restart(first.probe_id);
+                continue;
+            }
+            let (probe_id, seq_num) = (first.probe_id, first.sequence_number);
+            let mut entries = vec![first];
+            while let Some(next) = log.peek() {
+                if next.probe_id != probe_id
+                    || next.sequence_number != seq_num
+                    || next.is_probe_restart()
+                {
+                    break;
+                }
+                entries.extend(log.next());
+            }
+            for report in ReportIter::new(entries.into_iter().peekable()) {
+                self.add_report(&report, include_internals)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a new boot of the probe. The sequence numbers and clocks
+    /// of the reports that follow aren't related to the earlier ones,
+    /// so the only edge between the two boots is from the last event
+    /// before the restart to the first event after it.
+    pub fn add_probe_restart(&mut self, probe_id: ProbeId) {
+        *self.boots.entry(probe_id).or_insert(0) += 1;
+        self.tail_pending_edge_sources
+            .retain(|(id, _), _| *id != probe_id);
+        self.last_event_by_probe_and_seq_num
+            .retain(|(id, _), _| *id != probe_id);
+        self.last_event_by_probe_and_clock
+            .retain(|(id, _), _| *id != probe_id);
+        if let Some(last) = self.last_event_by_probe.remove(&probe_id) {
+            self.restart_edge_sources.insert(probe_id, last);
         }
     }
 
@@ -87,10 +157,11 @@ impl<G: Graph> EventDigraph<G> {
     pub fn add_report(&mut self, report: &Report, include_internals: bool) -> Result<(), Error> {
         let probe_id = report.probe_id;
         let seq_num = report.seq_num;
+        let boot = self.boots.get(&probe_id).copied().unwrap_or(0);
         let mut prev_event = None;
         let mut prev_tc = None;
         let mut first_event = true;
-        let mut self_clock = if let Some(sc) = report.frontier_clocks.get(0) {
+        let mut self_clock = if let Some(sc) = report.frontier_clocks.first() {
             sc
         } else {
             return Err(Error::InconsistentData("missing self frontier clock"));
@@ -103,7 +174,9 @@ impl<G: Graph> EventDigraph<G> {
 
         for (idx, ev) in report.event_log.iter().enumerate() {
             match ev {
-                EventLogEntry::Event(id) | EventLogEntry::EventWithTime(.., id) => {
+                EventLogEntry::Event(id)
+                | EventLogEntry::EventWithTime(.., id)
+                | EventLogEntry::EventWithBlob(id, _) => {
                     if include_internals || !id.is_internal() {
                         let node = GraphEvent {
                             probe_id,
@@ -112,6 +185,7 @@ impl<G: Graph> EventDigraph<G> {
                             payload: None,
                             seq: seq_num,
                             seq_idx: idx.saturating_add(num_frontier_clocks),
+                            boot,
                         };
                         self.add_event_to_graph(
                             node,
@@ -134,6 +208,29 @@ impl<G: Graph> EventDigraph<G> {
                             payload: Some(*payload),
                             seq: seq_num,
                             seq_idx: idx,
+                            boot,
+                        };
+                        self.add_event_to_graph(
+                            node,
+                            &mut pending_edges,
+                            &mut prev_event,
+                            &mut prev_tc,
+                            &mut first_event,
+                            probe_id,
+                            seq_num,
+                        );
+                    }
+                }
+                EventLogEntry::EventRepeated(id, count) => {
+                    if include_internals || !id.is_internal() {
+                        let node = GraphEvent {
+                            probe_id,
+                            id: EventId::EVENT_PREVIOUS_EVENT_REPEATED,
+                            clock: *self_clock,
+                            payload: Some(u64::from(*count)),
+                            seq: seq_num,
+                            seq_idx: idx,
+                            boot,
                         };
                         self.add_event_to_graph(
                             node,
@@ -183,6 +280,7 @@ impl<G: Graph> EventDigraph<G> {
         if let Some(pe) = prev_event {
             self.last_event_by_probe_and_seq_num
                 .insert((probe_id, seq_num), pe);
+            self.last_event_by_probe.insert(probe_id, pe);
         }
         if let Some(ptc) = prev_tc {
             if ptc.id != probe_id {
@@ -220,6 +318,9 @@ impl<G: Graph> EventDigraph<G> {
             {
                 self.graph.add_edge(tail, node);
             }
+            if let Some(tail) = self.restart_edge_sources.remove(&probe_id) {
+                self.graph.add_edge(tail, node);
+            }
             *first_event = false;
         }
         if let Some(prev) = prev_event {
@@ -689,6 +790,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(0),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 1,
         };
@@ -701,6 +803,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(1),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 3,
         };
@@ -713,6 +816,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(1),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 3,
         };
@@ -725,6 +829,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(2),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 5,
         };
@@ -860,6 +965,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(0),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 1,
         };
@@ -872,6 +978,7 @@ mod test {
                 epoch: ProbeEpoch(0),
                 ticks: ProbeTicks(0),
             },
+            boot: 0,
             seq: SequenceNumber(1),
             seq_idx: 4,
         };
@@ -882,4 +989,68 @@ mod test {
 
         assert_eq!(graph.graph, expected);
     }
+
+    #[test]
+    fn restarts_start_a_new_boot() {
+        let now = Utc::now();
+        let probe1 = ProbeId::new(1).unwrap();
+        let clock = LogicalClock {
+            id: probe1,
+            epoch: ProbeEpoch(0),
+            ticks: ProbeTicks(0),
+        };
+        let entry = |sequence_index, data| ReportLogEntry {
+            session_id: SessionId(1),
+            sequence_number: SequenceNumber(1),
+            sequence_index,
+            time_resolution: NanosecondResolution(0),
+            wall_clock_id: WallClockId(0),
+            receive_time: now,
+            clock,
+            persistent_epoch_counting: false,
+            probe_id: probe1,
+            data,
+        };
+        // Both boots' first reports have the same sequence number
+        let log = vec![
+            entry(0, LogEntryData::FrontierClock(clock)),
+            entry(1, LogEntryData::Event(EventId::new(1).unwrap())),
+            entry(
+                ReportLogEntry::PROBE_RESTART_SEQUENCE_INDEX,
+                LogEntryData::ProbeRestarted,
+            ),
+            entry(0, LogEntryData::FrontierClock(clock)),
+            entry(1, LogEntryData::Event(EventId::new(2).unwrap())),
+        ];
+
+        let mut graph = EventDigraph::new(NodeAndEdgeList {
+            nodes: HashSet::new(),
+            edges: HashSet::new(),
+        });
+        graph.add_log(log, false).unwrap();
+
+        let before = GraphEvent {
+            id: EventId::new(1).unwrap(),
+            payload: None,
+            probe_id: probe1,
+            clock,
+            boot: 0,
+            seq: SequenceNumber(1),
+            seq_idx: 1,
+        };
+        let after = GraphEvent {
+            id: EventId::new(2).unwrap(),
+            boot: 1,
+            ..before
+        };
+        let mut expected = NodeAndEdgeList {
+            nodes: HashSet::new(),
+            edges: HashSet::new(),
+        };
+        expected.add_node(before);
+        expected.add_node(after);
+        expected.add_edge(before, after);
+
+        assert_eq!(graph.graph, expected);
+    }
 }
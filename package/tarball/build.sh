@@ -49,6 +49,15 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
 
     cargo build --release \
         -p modality-probe-offline-batch-collector --bin modality-probe-offline-batch-collector
+
+    cargo build --release \
+        -p modality-probe-serial-collector --bin modality-probe-serial-collector
+
+    cargo build --release \
+        -p modality-probe-tcp-collector --bin modality-probe-tcp-collector
+
+    cargo build --release \
+        -p modality-probe-unix-collector --bin modality-probe-unix-collector
 )
 
 # Build the binaries for the target
@@ -64,6 +73,15 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
     cross build --release --target "$BINARY_TARGET_TRIPLE" \
         -p modality-probe-offline-batch-collector --bin modality-probe-offline-batch-collector
 
+    cross build --release --target "$BINARY_TARGET_TRIPLE" \
+        -p modality-probe-serial-collector --bin modality-probe-serial-collector
+
+    cross build --release --target "$BINARY_TARGET_TRIPLE" \
+        -p modality-probe-tcp-collector --bin modality-probe-tcp-collector
+
+    cross build --release --target "$BINARY_TARGET_TRIPLE" \
+        -p modality-probe-unix-collector --bin modality-probe-unix-collector
+
     # Requires toolchain of target binary to be installed
     PKG_CONFIG_ALLOW_CROSS=1 cargo build --release --target "$BINARY_TARGET_TRIPLE" \
         -p modality-probe-debug-collector --bin modality-probe-debug-collector
@@ -80,6 +98,15 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
     $LLVM_STRIP --strip-unneeded --strip-debug \
         "target/$BINARY_TARGET_TRIPLE/release/modality-probe-offline-batch-collector"
 
+    $LLVM_STRIP --strip-unneeded --strip-debug \
+        "target/$BINARY_TARGET_TRIPLE/release/modality-probe-serial-collector"
+
+    $LLVM_STRIP --strip-unneeded --strip-debug \
+        "target/$BINARY_TARGET_TRIPLE/release/modality-probe-tcp-collector"
+
+    $LLVM_STRIP --strip-unneeded --strip-debug \
+        "target/$BINARY_TARGET_TRIPLE/release/modality-probe-unix-collector"
+
     mkdir -p "$OUTPUT_DIR/$PACKAGE_NAME/bin"
     cp -a "target/$BINARY_TARGET_TRIPLE/release/modality-probe" \
         "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
@@ -89,6 +116,12 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
         "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
     cp -a "target/$BINARY_TARGET_TRIPLE/release/modality-probe-offline-batch-collector" \
         "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
+    cp -a "target/$BINARY_TARGET_TRIPLE/release/modality-probe-serial-collector" \
+        "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
+    cp -a "target/$BINARY_TARGET_TRIPLE/release/modality-probe-tcp-collector" \
+        "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
+    cp -a "target/$BINARY_TARGET_TRIPLE/release/modality-probe-unix-collector" \
+        "$OUTPUT_DIR/$PACKAGE_NAME/bin/"
 
     chmod 755 "$OUTPUT_DIR/$PACKAGE_NAME/bin/"*
 )
@@ -163,11 +196,20 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
         > "$man_dir/modality-probe-debug-collector.1"
     help2man --no-info "target/release/modality-probe-offline-batch-collector" \
         > "$man_dir/modality-probe-offline-batch-collector.1"
+    help2man --no-info "target/release/modality-probe-serial-collector" \
+        > "$man_dir/modality-probe-serial-collector.1"
+    help2man --no-info "target/release/modality-probe-tcp-collector" \
+        > "$man_dir/modality-probe-tcp-collector.1"
+    help2man --no-info "target/release/modality-probe-unix-collector" \
+        > "$man_dir/modality-probe-unix-collector.1"
 
     gzip --no-name --best "$man_dir/modality-probe.1"
     gzip --no-name --best "$man_dir/modality-probe-udp-collector.1"
     gzip --no-name --best "$man_dir/modality-probe-debug-collector.1"
     gzip --no-name --best "$man_dir/modality-probe-offline-batch-collector.1"
+    gzip --no-name --best "$man_dir/modality-probe-serial-collector.1"
+    gzip --no-name --best "$man_dir/modality-probe-tcp-collector.1"
+    gzip --no-name --best "$man_dir/modality-probe-unix-collector.1"
 
     chmod 644 "$man_dir/"*
 )
@@ -187,11 +229,20 @@ CRATE_VERSION=$(awk -F ' = ' '$1 ~ /version/ { gsub(/[\"]/, "", $2); printf("%s"
         -p modality-probe-debug-collector --bin modality-probe-debug-collector-completions
     cargo run --release \
         -p modality-probe-offline-batch-collector --bin modality-probe-offline-batch-collector-completions
+    cargo run --release \
+        -p modality-probe-serial-collector --bin modality-probe-serial-collector-completions
+    cargo run --release \
+        -p modality-probe-tcp-collector --bin modality-probe-tcp-collector-completions
+    cargo run --release \
+        -p modality-probe-unix-collector --bin modality-probe-unix-collector-completions
 
     mv modality-probe.bash "$comp_dir/"
     mv modality-probe-udp-collector.bash "$comp_dir/"
     mv modality-probe-debug-collector.bash "$comp_dir/"
     mv modality-probe-offline-batch-collector.bash "$comp_dir/"
+    mv modality-probe-serial-collector.bash "$comp_dir/"
+    mv modality-probe-tcp-collector.bash "$comp_dir/"
+    mv modality-probe-unix-collector.bash "$comp_dir/"
 
     chmod 644 "$comp_dir/"*
 )
@@ -53,6 +53,20 @@ impl fmt::Display for InvalidWallClockTime {
     }
 }
 
+/// Error that indicates there is no room left in the probe's
+/// event filter for another range of disabled events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventFilterFull;
+
+#[cfg(feature = "std")]
+impl std::error::Error for EventFilterFull {}
+
+impl fmt::Display for EventFilterFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Event filter is full")
+    }
+}
+
 /// An error relating to the '_with_time' APIs
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WithTimeError {
@@ -196,6 +210,11 @@ pub enum MergeError {
     /// The external history violated a semantic rule of the protocol,
     /// such as by having a probe_id out of the allowed value range.
     ExternalHistorySemantics,
+    /// The external history was encoded with a newer snapshot format
+    /// version that can't safely be merged by this probe.
+    UnsupportedSnapshotVersion(u8),
+    /// The external history's checksum didn't match its contents.
+    SnapshotChecksumMismatch,
 }
 
 #[cfg(feature = "std")]
@@ -209,6 +228,10 @@ impl fmt::Display for MergeError {
             MergeError::ExternalHistorySemantics => {
                 f.write_str("External history semantic violation")
             }
+            MergeError::UnsupportedSnapshotVersion(v) => {
+                write!(f, "Unsupported snapshot version {}", v)
+            }
+            MergeError::SnapshotChecksumMismatch => f.write_str("Snapshot checksum mismatch"),
         }
     }
 }
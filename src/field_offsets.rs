@@ -1,6 +1,12 @@
 //! Exposes offsets of ModalityProbe and DynamicHistory fields for
 //! use in the debug collector
-use crate::{history::DynamicHistory, log::LogEntry, ModalityProbe};
+use crate::{
+    filter::{DisabledEventRange, EventFilter},
+    history::DynamicHistory,
+    log::LogEntry,
+    ModalityProbe,
+};
+use core::mem::size_of;
 use fenced_ring_buffer::{FencedRingBuffer, SeqNum};
 use field_offset::offset_of;
 
@@ -69,3 +75,12 @@ pub fn log_storage_addr_offset() -> u64 {
 pub fn log_storage_cap_offset(n_word_bytes: u8) -> u64 {
     log_storage_addr_offset() + n_word_bytes as u64
 }
+
+/// Offset of the first word (u32), the first disabled event id, of the given
+/// range in DynamicHistory's event filter. The second word holds the last
+/// disabled event id. A range whose first word is 0 is unused
+pub fn event_filter_range_offset(index: usize) -> u64 {
+    debug_assert!(index < crate::EVENT_FILTER_LEN);
+    (offset_of!(DynamicHistory => event_filter: EventFilter => ranges).get_byte_offset()
+        + index * size_of::<DisabledEventRange>()) as u64
+}
@@ -0,0 +1,106 @@
+//! Runtime filtering of recorded events
+use crate::{EventFilterFull, EventId, EVENT_FILTER_LEN};
+
+/// An inclusive range of disabled event ids.
+///
+/// A range whose `first` id is 0 is unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub(crate) struct DisabledEventRange {
+    pub(crate) first: u32,
+    pub(crate) last: u32,
+}
+
+/// Table of event id ranges which are not recorded.
+///
+/// NOTE: the debug-collector writes the ranges of this table
+/// directly, so its layout must not change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub(crate) struct EventFilter {
+    pub(crate) ranges: [DisabledEventRange; EVENT_FILTER_LEN],
+}
+
+impl EventFilter {
+    /// Whether events with the given id should be recorded
+    #[inline]
+    pub(crate) fn is_enabled(&self, event_id: EventId) -> bool {
+        let id = event_id.get_raw();
+        !self
+            .ranges
+            .iter()
+            .any(|r| r.first != 0 && r.first <= id && id <= r.last)
+    }
+
+    /// Disable the events with ids in `first..=last`, using the
+    /// first unused range in the table. The bounds may be given in
+    /// either order.
+    pub(crate) fn disable(&mut self, first: EventId, last: EventId) -> Result<(), EventFilterFull> {
+        let range = self
+            .ranges
+            .iter_mut()
+            .find(|r| r.first == 0)
+            .ok_or(EventFilterFull)?;
+        let (first, last) = (first.get_raw(), last.get_raw());
+        *range = DisabledEventRange {
+            first: first.min(last),
+            last: first.max(last),
+        };
+        Ok(())
+    }
+
+    /// Enable all events
+    pub(crate) fn clear(&mut self) {
+        self.ranges = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(id: u32) -> EventId {
+        EventId::new(id).unwrap()
+    }
+
+    #[test]
+    fn disabled_ranges_are_inclusive() {
+        let mut filter = EventFilter::default();
+        assert!(filter.is_enabled(ev(1)));
+
+        filter.disable(ev(10), ev(20)).unwrap();
+        filter.disable(ev(5), ev(5)).unwrap();
+        assert!(filter.is_enabled(ev(4)));
+        assert!(!filter.is_enabled(ev(5)));
+        assert!(filter.is_enabled(ev(6)));
+        assert!(!filter.is_enabled(ev(10)));
+        assert!(!filter.is_enabled(ev(20)));
+        assert!(filter.is_enabled(ev(21)));
+
+        filter.clear();
+        assert!(filter.is_enabled(ev(5)));
+        assert!(filter.is_enabled(ev(15)));
+    }
+
+    #[test]
+    fn reversed_bounds_are_swapped() {
+        let mut filter = EventFilter::default();
+        filter.disable(ev(20), ev(10)).unwrap();
+        assert!(filter.is_enabled(ev(9)));
+        assert!(!filter.is_enabled(ev(10)));
+        assert!(!filter.is_enabled(ev(15)));
+        assert!(!filter.is_enabled(ev(20)));
+        assert!(filter.is_enabled(ev(21)));
+    }
+
+    #[test]
+    fn table_fills_up() {
+        let mut filter = EventFilter::default();
+        for i in 0..EVENT_FILTER_LEN as u32 {
+            filter.disable(ev(i + 1), ev(i + 1)).unwrap();
+        }
+        assert_eq!(filter.disable(ev(100), ev(200)), Err(EventFilterFull));
+        filter.clear();
+        assert_eq!(filter.disable(ev(100), ev(200)), Ok(()));
+    }
+}
@@ -6,20 +6,22 @@ use core::{
 };
 
 use fixed_slice_vec::{
-    single::{EmbedValueError, SplitUninitError},
+    single::{split_uninit_from_uninit_bytes, EmbedValueError, SplitUninitError},
     FixedSliceVec, StorageError,
 };
 use static_assertions::{assert_eq_align, assert_eq_size, const_assert, const_assert_eq};
 
-use fenced_ring_buffer::{FencedRingBuffer, WholeEntry};
+use fenced_ring_buffer::{FencedRingBuffer, SeqNum, WholeEntry};
 
 use crate::{
+    filter::EventFilter,
     log::{LogBuffer, LogEntry},
     restart_counter::RestartCounterProvider,
     time::{NanosecondResolution, Nanoseconds, WallClockId},
-    wire::{report::WireReport, WireCausalSnapshot},
+    wire::{le_bytes, report::WireReport, WireCausalSnapshot},
     CausalSnapshot, EventId, LogicalClock, MergeError, ModalityProbeInstant, OrdClock, ProbeEpoch,
     ProbeId, ProbeTicks, ProduceError, ReportError, RestartCounter, StorageSetupError,
+    DEFAULT_EVENT_PRIORITY,
 };
 
 pub const MIN_CLOCKS_LEN: usize = 2;
@@ -48,7 +50,7 @@ const_assert_eq!(4, align_of::<CausalSnapshot>());
 const_assert_eq!(12, size_of::<ModalityProbeInstant>());
 const_assert_eq!(4, align_of::<ModalityProbeInstant>());
 
-// 5 bytes of padding required to get the size (99) up to 104, 8-byte aligned
+// 2 bytes of padding required to get the size (174) up to 176, 8-byte aligned
 #[cfg(target_pointer_width = "32")]
 const_assert_eq!(
     size_of::<u32>()
@@ -57,17 +59,24 @@ const_assert_eq!(
         + size_of::<WallClockId>()
         + size_of::<u8>()
         + size_of::<LogBuffer<'_>>()
+        + size_of::<EventFilter>()
         + size_of::<u32>()
         + size_of::<LogicalClock>()
         + size_of::<FixedSliceVec<'_, LogicalClock>>()
         + size_of::<RestartCounterProvider<'_>>()
         + size_of::<u64>()
         + size_of::<u32>()
-        + 5,
+        + size_of::<Option<EventId>>()
+        + size_of::<u32>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + size_of::<Option<UnackedReport>>()
+        + 2,
     size_of::<DynamicHistory>()
 );
 
-// 9 bytes of padding required to get the size (135) up to 144, 8-byte aligned
+// 6 bytes of padding required to get the size (210) up to 216, 8-byte aligned
 #[cfg(target_pointer_width = "64")]
 const_assert_eq!(
     size_of::<u32>()
@@ -76,13 +85,20 @@ const_assert_eq!(
         + size_of::<WallClockId>()
         + size_of::<u8>()
         + size_of::<LogBuffer<'_>>()
+        + size_of::<EventFilter>()
         + size_of::<u32>()
         + size_of::<LogicalClock>()
         + size_of::<FixedSliceVec<'_, LogicalClock>>()
         + size_of::<RestartCounterProvider<'_>>()
         + size_of::<u64>()
         + size_of::<u32>()
-        + 9,
+        + size_of::<Option<EventId>>()
+        + size_of::<u32>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + size_of::<Option<UnackedReport>>()
+        + 6,
     size_of::<DynamicHistory>()
 );
 
@@ -101,6 +117,7 @@ const_assert_eq!(
 /// * wall_clock_id
 /// * persistent_epoch_counting
 /// * log
+/// * event_filter
 #[derive(Debug)]
 #[repr(C)]
 pub struct DynamicHistory<'a> {
@@ -113,6 +130,8 @@ pub struct DynamicHistory<'a> {
     pub(crate) persistent_epoch_counting: u8,
     /// Log used to store events and trace clocks
     pub(crate) log: LogBuffer<'a>,
+    /// Ranges of event ids which are not recorded
+    pub(crate) event_filter: EventFilter,
     /// The number of events seen since the current
     /// probe's logical clock last increased.
     pub(crate) event_count: u32,
@@ -123,10 +142,35 @@ pub struct DynamicHistory<'a> {
     pub(crate) restart_counter: RestartCounterProvider<'a>,
     pub(crate) report_seq_num: u64,
     pub(crate) missed_log_entry_count: u32,
+    /// The most recently recorded event, while it is still the newest
+    /// entry in the log and may be followed by repeats of itself
+    pub(crate) last_event: Option<EventId>,
+    /// The number of consecutive repeats of `last_event` which have
+    /// not yet been written to the log
+    pub(crate) pending_repeat_count: u32,
+    /// Whether consecutive identical events are compressed into
+    /// a single event repeated entry
+    pub(crate) compress_repeated_events: bool,
+    /// Whether produced reports carry a CRC-32 trailer and produced
+    /// snapshots a checksum
+    pub(crate) checksums: bool,
+    /// Whether reported log entries are kept until the collector
+    /// acknowledges the report
+    pub(crate) report_acks: bool,
+    /// The most recent report, while it awaits acknowledgement
+    pub(crate) unacked_report: Option<UnackedReport>,
 }
 
-#[derive(Debug)]
-struct ClocksFullError;
+/// The region of the log covered by a report which has not been
+/// acknowledged yet
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct UnackedReport {
+    seq_num: u64,
+    /// Sequence number of the log entry following the reported ones
+    end_seqn: SeqNum,
+    /// The missed log entry count carried by the report
+    missed_log_entry_count: u32,
+}
 
 impl<'a> DynamicHistory<'a> {
     #[inline]
@@ -141,34 +185,28 @@ impl<'a> DynamicHistory<'a> {
         if remaining_bytes < MIN_HISTORY_SIZE_BYTES {
             return Err(StorageSetupError::UnderMinimumAllowedSize);
         }
-        if destination.as_ptr().is_null() {
-            return Err(StorageSetupError::NullDestination);
-        }
-        let history =
-            match fixed_slice_vec::single::embed_uninit(destination, |dynamic_region_slice| {
-                DynamicHistory::new(
-                    dynamic_region_slice,
-                    probe_id,
-                    time_resolution,
-                    wall_clock_id,
-                    restart_counter,
-                )
-            }) {
-                Ok(v) => Ok(v),
-                Err(EmbedValueError::SplitUninitError(SplitUninitError::InsufficientSpace)) => {
-                    Err(StorageSetupError::UnderMinimumAllowedSize)
-                }
-                Err(EmbedValueError::SplitUninitError(SplitUninitError::Unalignable)) => {
-                    Err(StorageSetupError::UnderMinimumAllowedSize)
-                }
-                Err(EmbedValueError::SplitUninitError(
-                    SplitUninitError::ZeroSizedTypesUnsupported,
-                )) => {
-                    const_assert!(size_of::<DynamicHistory>() > 0);
-                    panic!("Static assertions ensure that this structure is not zero sized")
-                }
-                Err(EmbedValueError::ConstructionError(e)) => Err(e),
-            }?;
+        let history = match crate::embed_uninit(destination, |dynamic_region_slice| {
+            DynamicHistory::new(
+                dynamic_region_slice,
+                probe_id,
+                time_resolution,
+                wall_clock_id,
+                restart_counter,
+            )
+        }) {
+            Ok(v) => Ok(v),
+            Err(EmbedValueError::SplitUninitError(SplitUninitError::InsufficientSpace)) => {
+                Err(StorageSetupError::UnderMinimumAllowedSize)
+            }
+            Err(EmbedValueError::SplitUninitError(SplitUninitError::Unalignable)) => {
+                Err(StorageSetupError::UnderMinimumAllowedSize)
+            }
+            Err(EmbedValueError::SplitUninitError(SplitUninitError::ZeroSizedTypesUnsupported)) => {
+                const_assert!(size_of::<DynamicHistory>() > 0);
+                panic!("Static assertions ensure that this structure is not zero sized")
+            }
+            Err(EmbedValueError::ConstructionError(e)) => Err(e),
+        }?;
         {
             let history_ptr = history as *mut DynamicHistory as usize;
             let clocks_ptr = history.clocks.as_slice().as_ptr() as usize;
@@ -195,6 +233,116 @@ impl<'a> DynamicHistory<'a> {
         wall_clock_id: WallClockId,
         mut restart_counter: RestartCounterProvider<'a>,
     ) -> Result<Self, StorageSetupError> {
+        let (clocks, log) = DynamicHistory::new_clocks_and_log(dynamic_region_slice)?;
+        let (initial_epoch, restart_counter_had_error) =
+            DynamicHistory::calculate_next_epoch(&mut restart_counter, probe_id, None);
+        let mut history = DynamicHistory::from_parts(
+            clocks,
+            log,
+            probe_id,
+            time_resolution,
+            wall_clock_id,
+            restart_counter,
+            initial_epoch,
+        );
+        history.write_clocks_to_log(&[history.self_clock]);
+        history.push_event(EventId::EVENT_PROBE_INITIALIZED);
+        if restart_counter_had_error.0 {
+            history.push_event(EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID);
+        }
+        Ok(history)
+    }
+
+    /// Recover the history left in `destination` before a warm reset
+    /// by a probe with the same id, time resolution and wall clock id,
+    /// keeping its log and bumping its epoch. Hands the restart counter
+    /// back if the history isn't intact.
+    ///
+    /// Only the log and the report bookkeeping are recovered, the
+    /// clocks merged from other probes are still in the log as trace
+    /// clocks until reported, and options are reset to their defaults.
+    ///
+    /// # Safety
+    ///
+    /// The contents of `destination` must be initialized, though they
+    /// may be arbitrary.
+    pub(crate) unsafe fn recover_at(
+        destination: &'a mut [MaybeUninit<u8>],
+        probe_id: ProbeId,
+        time_resolution: NanosecondResolution,
+        wall_clock_id: WallClockId,
+        mut restart_counter: RestartCounterProvider<'a>,
+    ) -> Result<&'a mut DynamicHistory<'a>, RestartCounterProvider<'a>> {
+        if destination.len() < MIN_HISTORY_SIZE_BYTES {
+            return Err(restart_counter);
+        }
+        let (_prefix, uninit_history, dynamic_region_slice) =
+            match split_uninit_from_uninit_bytes::<DynamicHistory>(destination) {
+                Ok(split) => split,
+                Err(_) => return Err(restart_counter),
+            };
+        let stale = uninit_history.as_ptr();
+
+        // The probe id follows overwrite_priority, it is read as a plain
+        // u32 since the stale one may be zero
+        let stale_probe_id = *(stale as *const u32).add(1);
+        if stale_probe_id != probe_id.get_raw()
+            || (*stale).time_resolution != time_resolution
+            || (*stale).wall_clock_id != wall_clock_id
+        {
+            return Err(restart_counter);
+        }
+        let (clocks, mut log) = match DynamicHistory::new_clocks_and_log(dynamic_region_slice) {
+            Ok(parts) => parts,
+            Err(_) => return Err(restart_counter),
+        };
+        if !log.restore_from(&(*stale).log) {
+            return Err(restart_counter);
+        }
+        // Clock entries are trusted to carry a valid probe id when
+        // they're reported, one that doesn't means the log is corrupt
+        let has_invalid_clock = log.iter().any(|entry| match entry {
+            WholeEntry::Double(first, _) if first.has_clock_bit_set() => {
+                ProbeId::new(first.interpret_as_logical_clock_probe_id()).is_none()
+            }
+            _ => false,
+        });
+        if has_invalid_clock {
+            return Err(restart_counter);
+        }
+        let report_seq_num = (*stale).report_seq_num;
+        let missed_log_entry_count = (*stale).missed_log_entry_count;
+
+        let (epoch, restart_counter_had_error) = DynamicHistory::calculate_next_epoch(
+            &mut restart_counter,
+            probe_id,
+            Some((*stale).self_clock.epoch),
+        );
+        let history = uninit_history.write(DynamicHistory::from_parts(
+            clocks,
+            log,
+            probe_id,
+            time_resolution,
+            wall_clock_id,
+            restart_counter,
+            epoch,
+        ));
+        history.report_seq_num = report_seq_num;
+        history.missed_log_entry_count = missed_log_entry_count;
+        history.write_clocks_to_log(&[history.self_clock]);
+        history.push_event(EventId::EVENT_PROBE_RECOVERED);
+        if restart_counter_had_error.0 {
+            history.push_event(EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID);
+        }
+        Ok(history)
+    }
+
+    /// Split the region following the history into the clocks
+    /// and the log
+    #[inline]
+    fn new_clocks_and_log(
+        dynamic_region_slice: &'a mut [MaybeUninit<u8>],
+    ) -> Result<(FixedSliceVec<'a, LogicalClock>, LogBuffer<'a>), StorageSetupError> {
         let max_n_clocks = cmp::max(
             MIN_CLOCKS_LEN,
             dynamic_region_slice.len() / 8 / size_of::<LogicalClock>(),
@@ -204,34 +352,45 @@ impl<'a> DynamicHistory<'a> {
             return Err(StorageSetupError::UnderMinimumAllowedSize);
         }
         let (clocks_region, log_region) = dynamic_region_slice.split_at_mut(clocks_region_bytes);
-        let mut clocks = FixedSliceVec::from_uninit_bytes(clocks_region);
+        let clocks = FixedSliceVec::from_uninit_bytes(clocks_region);
         // Create new FencedRingBuffer, using full log region instead of rounding to power of 2 length for
         // optimized indexing
         // Note: point of future improvement - a heuristic could be used to determine whether or not the memory cost
         // of rounding the log's storage space outweighs the runtime cost of using mod operations for indexing
         let log = FencedRingBuffer::new_from_uninit_bytes(log_region, false)
             .map_err(|_| StorageSetupError::UnderMinimumAllowedSize)?;
-        if clocks.capacity() < MIN_CLOCKS_LEN || (log.capacity() as usize) < MIN_LOG_LEN {
+        if clocks.capacity() < MIN_CLOCKS_LEN || log.capacity() < MIN_LOG_LEN {
             return Err(StorageSetupError::UnderMinimumAllowedSize);
         }
-        let (initial_epoch, restart_counter_had_error) =
-            DynamicHistory::calculate_next_epoch(&mut restart_counter, probe_id, None);
+        Ok((clocks, log))
+    }
+
+    #[inline]
+    fn from_parts(
+        mut clocks: FixedSliceVec<'a, LogicalClock>,
+        log: LogBuffer<'a>,
+        probe_id: ProbeId,
+        time_resolution: NanosecondResolution,
+        wall_clock_id: WallClockId,
+        restart_counter: RestartCounterProvider<'a>,
+        epoch: ProbeEpoch,
+    ) -> Self {
         clocks
             .try_push(LogicalClock {
                 id: probe_id,
-                epoch: initial_epoch,
+                epoch,
                 ticks: ProbeTicks(0),
             })
             .expect(
                 "The History.clocks field should always contain a clock for this probe instance",
             );
-        let mut history = DynamicHistory {
+        DynamicHistory {
             overwrite_priority: 0,
             report_seq_num: 0,
             event_count: 0,
             self_clock: LogicalClock {
                 id: probe_id,
-                epoch: initial_epoch,
+                epoch,
                 ticks: ProbeTicks(0),
             },
             probe_id,
@@ -244,15 +403,16 @@ impl<'a> DynamicHistory<'a> {
             },
             clocks,
             log,
+            event_filter: EventFilter::default(),
             restart_counter,
             missed_log_entry_count: 0,
-        };
-        history.write_clocks_to_log(&[history.self_clock]);
-        history.record_event(EventId::EVENT_PROBE_INITIALIZED);
-        if restart_counter_had_error.0 {
-            history.record_event(EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID);
+            last_event: None,
+            pending_repeat_count: 0,
+            compress_repeated_events: false,
+            checksums: false,
+            report_acks: false,
+            unacked_report: None,
         }
-        Ok(history)
     }
 
     #[inline]
@@ -269,9 +429,10 @@ impl<'a> DynamicHistory<'a> {
     }
 
     /// Merge overwritten logical clock entries as needed, then check
-    /// for overwritten paired wall clock time entries, removing their
-    /// buddy entries as needed, managing
-    /// the missed entry counter along the way
+    /// for overwritten paired wall clock time entries, wide payload high
+    /// bits entries, blob data entries and events followed by event repeated entries,
+    /// removing their buddy entries as needed, managing the missed
+    /// entry counter along the way
     #[inline]
     fn process_overwritten_log_entries(
         &mut self,
@@ -290,51 +451,41 @@ impl<'a> DynamicHistory<'a> {
         // missed counter when the log is actually full and overwriting the tail
         let log_was_full = self.log.is_full();
 
-        if let Some(overwritten) = first_overwritten {
-            if overwritten.is_double()
-                && overwritten
-                    .first_entry()
-                    .has_wall_clock_time_paired_bit_set()
-            {
-                // The buddy entry is either already pop'd in second_overwritten
-                // or the next tail entry in the log
-                if second_overwritten.is_none() {
-                    let buddy_entry = self.log.pop();
-
-                    if let Some(e) = buddy_entry {
-                        if log_was_full {
-                            self.missed_log_entry_count =
-                                self.missed_log_entry_count.saturating_add(e.size().into());
-                        }
-                    }
+        // An overwritten entry which precedes a buddy entry takes the buddy with it.
+        // The buddy is either already pop'd in second_overwritten or the next
+        // tail entry in the log, and may itself precede another buddy entry
+        let mut last_overwritten = second_overwritten.or(first_overwritten);
+        while let Some(overwritten) = last_overwritten {
+            if !precedes_buddy_entry(&overwritten) {
+                break;
+            }
+            let buddy_entry = self.log.pop();
 
-                    self.merge_overwritten_clock(buddy_entry);
+            if let Some(e) = buddy_entry {
+                if log_was_full {
+                    self.missed_log_entry_count =
+                        self.missed_log_entry_count.saturating_add(e.size().into());
                 }
-
-                // All done, don't need to do anything with second_overwritten
-                return;
             }
+
+            self.merge_overwritten_clock(buddy_entry);
+            last_overwritten = buddy_entry;
         }
 
-        // first_overwritten was not a paired wall clock time entry,
-        // need to do the same checks on second_overwritten
-        if let Some(overwritten) = second_overwritten {
-            if overwritten.is_double()
-                && overwritten
-                    .first_entry()
-                    .has_wall_clock_time_paired_bit_set()
-            {
-                // The buddy entry is the next tail entry in the log
-                let buddy_entry = self.log.pop();
+        // An overwritten single event may be followed by an event repeated
+        // entry, which is meaningless without it
+        if let Some(WholeEntry::Single(_)) = last_overwritten {
+            if let Some(WholeEntry::Double(first, _)) = self.log.peek() {
+                if first.is_event_repeated() {
+                    let repeated_entry = self.log.pop();
 
-                if let Some(e) = buddy_entry {
-                    if log_was_full {
-                        self.missed_log_entry_count =
-                            self.missed_log_entry_count.saturating_add(e.size().into());
+                    if let Some(e) = repeated_entry {
+                        if log_was_full {
+                            self.missed_log_entry_count =
+                                self.missed_log_entry_count.saturating_add(e.size().into());
+                        }
                     }
                 }
-
-                self.merge_overwritten_clock(buddy_entry);
             }
         }
     }
@@ -371,40 +522,173 @@ impl<'a> DynamicHistory<'a> {
     }
 
     /// Add an item to the internal log that records this event
-    /// occurred.
+    /// occurred, using the default priority.
     ///
     /// Note: this function overwrites older events in the log if it
-    /// is full.
+    /// is full, unless the default priority is below the current
+    /// overwrite priority, in which case the event is dropped.
     #[inline]
     pub(crate) fn record_event(&mut self, event_id: EventId) {
-        // N.B. point for future improvement - basic compression here
-        let overwritten = self.log.push(LogEntry::event(event_id));
-        self.process_overwritten_log_entries(overwritten, None);
-        self.event_count = self.event_count.saturating_add(1);
+        self.record_event_with_priority(event_id, DEFAULT_EVENT_PRIORITY);
+    }
+
+    /// Add an item to the internal log that records this event
+    /// occurred with the given priority.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless `priority` is below the current overwrite
+    /// priority, in which case the event is dropped.
+    ///
+    /// When repeated event compression is enabled, recording the same
+    /// event again only bumps a pending repeat count, which is written
+    /// to the log as an event repeated entry before the next entry
+    /// or report. The first repeat is admitted like any other entry,
+    /// since it's what makes that event repeated entry necessary.
+    #[inline]
+    pub(crate) fn record_event_with_priority(&mut self, event_id: EventId, priority: u32) {
+        if !self.event_filter.is_enabled(event_id) {
+            return;
+        }
+        if self.last_event == Some(event_id) && self.pending_repeat_count < u32::MAX {
+            if self.pending_repeat_count != 0 || self.admit(priority, 2) {
+                self.pending_repeat_count += 1;
+                self.event_count = self.event_count.saturating_add(1);
+            }
+        } else if self.admit(priority, 1) {
+            self.push_event(event_id);
+            if self.compress_repeated_events {
+                self.last_event = Some(event_id);
+            }
+        }
+    }
+
+    /// Enable or disable compression of consecutive identical events.
+    ///
+    /// Any pending repeats are written to the log first.
+    #[inline]
+    pub(crate) fn set_repeated_event_compression(&mut self, enabled: bool) {
+        self.flush_repeated_events();
+        self.compress_repeated_events = enabled;
+    }
+
+    /// Enable or disable checksums on produced reports and snapshots.
+    #[inline]
+    pub(crate) fn set_checksums(&mut self, enabled: bool) {
+        self.checksums = enabled;
+    }
+
+    /// Enable or disable keeping reported log entries until the report
+    /// is acknowledged.
+    ///
+    /// A report awaiting acknowledgement when this is called is
+    /// forgotten, its log entries are reported again.
+    #[inline]
+    pub(crate) fn set_report_acks(&mut self, enabled: bool) {
+        self.report_acks = enabled;
+        self.unacked_report = None;
+    }
+
+    /// Release the log entries of the report awaiting acknowledgement,
+    /// if its sequence number is `seq_num`.
+    ///
+    /// Returns whether the acknowledgement matched.
+    pub(crate) fn acknowledge_report(&mut self, seq_num: u64) -> bool {
+        let unacked = match self.unacked_report {
+            Some(u) if u.seq_num == seq_num => u,
+            _ => return false,
+        };
+        self.unacked_report = None;
+
+        // Clocks are merged as their log entries leave the log
+        let mut did_clocks_overflow = false;
+        while self.log.tail_seqn() < unacked.end_seqn {
+            match self.log.pop() {
+                Some(WholeEntry::Double(first, second)) if first.has_clock_bit_set() => {
+                    // Safe to unwrap because entry was written into the log as a clock probe id
+                    let id = ProbeId::new(first.interpret_as_logical_clock_probe_id()).unwrap();
+                    let (epoch, ticks) = crate::unpack_clock_word(second.raw());
+                    if Self::merge_clocks(&mut self.clocks, LogicalClock { id, epoch, ticks })
+                        .is_err()
+                    {
+                        did_clocks_overflow = true;
+                    }
+                }
+                Some(_) => (),
+                None => break,
+            }
+        }
+        self.missed_log_entry_count = self
+            .missed_log_entry_count
+            .saturating_sub(unacked.missed_log_entry_count);
+        self.report_seq_num = self.report_seq_num.wrapping_add(1);
+
+        if did_clocks_overflow {
+            self.push_event(EventId::EVENT_NUM_CLOCKS_OVERFLOWED);
+        }
+        true
+    }
+
+    #[inline]
+    fn snapshot_flags(&self) -> u8 {
+        if self.checksums {
+            CausalSnapshot::FLAG_CHECKSUM
+        } else {
+            0
+        }
     }
 
     /// Add the event and its payload to the internal log, recording
-    /// that this event occurred.
+    /// that this event occurred, using the default priority.
     ///
     /// Note: this function overwrites older events in the log if it
-    /// is full.
+    /// is full, unless the default priority is below the current
+    /// overwrite priority, in which case the event is dropped.
     #[inline]
     pub(crate) fn record_event_with_payload(&mut self, event_id: EventId, payload: u32) {
-        let (first, second) = LogEntry::event_with_payload(event_id, payload);
-        let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
-        self.process_overwritten_log_entries(first_overwritten, second_overwritten);
-        self.event_count = self.event_count.saturating_add(1);
+        self.record_event_with_payload_with_priority(event_id, payload, DEFAULT_EVENT_PRIORITY);
+    }
+
+    /// Add the event and its payload to the internal log, recording
+    /// that this event occurred.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless `priority` is below the current overwrite
+    /// priority, in which case the event is dropped.
+    #[inline]
+    pub(crate) fn record_event_with_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        priority: u32,
+    ) {
+        if self.admit_event(event_id, priority, 2) {
+            self.push_event_with_payload(event_id, payload);
+        }
     }
 
     #[inline]
     pub(crate) fn record_time(&mut self, time: Nanoseconds) {
-        self.record_unpaired_wall_clock_time(time);
+        if self.admit(DEFAULT_EVENT_PRIORITY, 2) {
+            self.record_unpaired_wall_clock_time(time);
+        }
     }
 
     #[inline]
     pub fn record_event_with_time(&mut self, event_id: EventId, time: Nanoseconds) {
-        self.record_paired_wall_clock_time(time);
-        self.record_event(event_id);
+        self.record_event_with_time_with_priority(event_id, time, DEFAULT_EVENT_PRIORITY);
+    }
+
+    #[inline]
+    pub fn record_event_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        if self.admit_event(event_id, priority, 3) {
+            self.record_paired_wall_clock_time(time);
+            self.push_event(event_id);
+        }
     }
 
     #[inline]
@@ -414,12 +698,202 @@ impl<'a> DynamicHistory<'a> {
         payload: u32,
         time: Nanoseconds,
     ) {
-        self.record_paired_wall_clock_time(time);
-        self.record_event_with_payload(event_id, payload);
+        self.record_event_with_payload_with_time_with_priority(
+            event_id,
+            payload,
+            time,
+            DEFAULT_EVENT_PRIORITY,
+        );
+    }
+
+    #[inline]
+    pub fn record_event_with_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        if self.admit_event(event_id, priority, 4) {
+            self.record_paired_wall_clock_time(time);
+            self.push_event_with_payload(event_id, payload);
+        }
+    }
+
+    /// Add the event and its 64-bit payload to the internal log,
+    /// recording that this event occurred, using the default priority.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless the default priority is below the current
+    /// overwrite priority, in which case the event is dropped.
+    #[inline]
+    pub(crate) fn record_event_with_wide_payload(&mut self, event_id: EventId, payload: u64) {
+        self.record_event_with_wide_payload_with_priority(
+            event_id,
+            payload,
+            DEFAULT_EVENT_PRIORITY,
+        );
+    }
+
+    /// Add the event and its 64-bit payload to the internal log,
+    /// recording that this event occurred.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless `priority` is below the current overwrite
+    /// priority, in which case the event is dropped.
+    #[inline]
+    pub(crate) fn record_event_with_wide_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        priority: u32,
+    ) {
+        if self.admit_event(event_id, priority, 4) {
+            self.record_wide_payload_high_bits(payload);
+            self.push_event_with_payload(event_id, payload as u32);
+        }
+    }
+
+    #[inline]
+    pub fn record_event_with_wide_payload_with_time(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+    ) {
+        self.record_event_with_wide_payload_with_time_with_priority(
+            event_id,
+            payload,
+            time,
+            DEFAULT_EVENT_PRIORITY,
+        );
+    }
+
+    #[inline]
+    pub fn record_event_with_wide_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        if self.admit_event(event_id, priority, 6) {
+            self.record_wide_payload_high_bits(payload);
+            self.record_paired_wall_clock_time(time);
+            self.push_event_with_payload(event_id, payload as u32);
+        }
+    }
+
+    /// Add the event and a short blob of bytes to the internal log,
+    /// recording that this event occurred, using the default priority.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless the default priority is below the current
+    /// overwrite priority, in which case the event is dropped.
+    #[inline]
+    pub(crate) fn record_event_with_blob(&mut self, event_id: EventId, blob: &[u8]) {
+        self.record_event_with_blob_with_priority(event_id, blob, DEFAULT_EVENT_PRIORITY);
+    }
+
+    /// Add the event and a short blob of bytes to the internal log,
+    /// recording that this event occurred.
+    ///
+    /// Note: this function overwrites older events in the log if it
+    /// is full, unless `priority` is below the current overwrite
+    /// priority, in which case the event is dropped.
+    #[inline]
+    pub(crate) fn record_event_with_blob_with_priority(
+        &mut self,
+        event_id: EventId,
+        blob: &[u8],
+        priority: u32,
+    ) {
+        let blob = &blob[..blob.len().min(crate::ModalityProbe::MAX_BLOB_LEN)];
+        // An empty blob still gets a data entry so it can be told apart
+        // from a plain event with payload
+        let n_chunks = cmp::max(1, blob.len().div_ceil(size_of::<u32>()));
+        if self.admit_event(event_id, priority, 2 * n_chunks + 2) {
+            self.flush_repeated_events();
+            for i in 0..n_chunks {
+                let start = cmp::min(i * size_of::<u32>(), blob.len());
+                let end = cmp::min(start + size_of::<u32>(), blob.len());
+                let (first, second) = LogEntry::blob_data(&blob[start..end]);
+                let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
+                self.process_overwritten_log_entries(first_overwritten, second_overwritten);
+            }
+            self.push_event_with_payload(event_id, blob.len() as u32);
+        }
+    }
+
+    /// Decide whether `n_entries` log entries of the given priority
+    /// may be written to the log.
+    ///
+    /// Entries whose priority is below the overwrite priority are only
+    /// admitted if they fit without overwriting anything, along with
+    /// any pending repeats that get flushed ahead of them; otherwise
+    /// they are dropped and counted as missed.
+    #[inline]
+    fn admit(&mut self, priority: u32, n_entries: usize) -> bool {
+        let n_flushed_entries = if self.pending_repeat_count != 0 { 2 } else { 0 };
+        if priority >= self.overwrite_priority
+            || self.log.capacity() - self.log.len() >= n_entries + n_flushed_entries
+        {
+            true
+        } else {
+            self.missed_log_entry_count =
+                self.missed_log_entry_count.saturating_add(n_entries as u32);
+            false
+        }
+    }
+
+    /// Decide whether `n_entries` log entries recording the given
+    /// event may be written to the log.
+    ///
+    /// Events disabled by the event filter are dropped without being
+    /// counted as missed.
+    #[inline]
+    fn admit_event(&mut self, event_id: EventId, priority: u32, n_entries: usize) -> bool {
+        self.event_filter.is_enabled(event_id) && self.admit(priority, n_entries)
+    }
+
+    /// Write any pending repeats of the most recently recorded event
+    /// into the log as a single event repeated entry.
+    ///
+    /// Must be called before anything else is written to the log.
+    #[inline]
+    fn flush_repeated_events(&mut self) {
+        self.last_event = None;
+        if self.pending_repeat_count != 0 {
+            let (first, second) = LogEntry::event_repeated(self.pending_repeat_count);
+            self.pending_repeat_count = 0;
+            let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
+            self.process_overwritten_log_entries(first_overwritten, second_overwritten);
+            // NOTE: the repeats were already added to the event count when recorded
+        }
+    }
+
+    /// Unconditionally push an event entry into the log
+    #[inline]
+    fn push_event(&mut self, event_id: EventId) {
+        self.flush_repeated_events();
+        let overwritten = self.log.push(LogEntry::event(event_id));
+        self.process_overwritten_log_entries(overwritten, None);
+        self.event_count = self.event_count.saturating_add(1);
+    }
+
+    /// Unconditionally push an event with payload entry into the log
+    #[inline]
+    fn push_event_with_payload(&mut self, event_id: EventId, payload: u32) {
+        self.flush_repeated_events();
+        let (first, second) = LogEntry::event_with_payload(event_id, payload);
+        let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
+        self.process_overwritten_log_entries(first_overwritten, second_overwritten);
+        self.event_count = self.event_count.saturating_add(1);
     }
 
     #[inline]
     fn record_paired_wall_clock_time(&mut self, time: Nanoseconds) {
+        self.flush_repeated_events();
         let (first, second) = LogEntry::paired_wall_clock_time(time);
         let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
         self.process_overwritten_log_entries(first_overwritten, second_overwritten);
@@ -427,8 +901,19 @@ impl<'a> DynamicHistory<'a> {
         // associated with another entry
     }
 
+    #[inline]
+    fn record_wide_payload_high_bits(&mut self, payload: u64) {
+        self.flush_repeated_events();
+        let (first, second) = LogEntry::wide_payload_high_bits(payload);
+        let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
+        self.process_overwritten_log_entries(first_overwritten, second_overwritten);
+        // NOTE: wide payload high bits entries do not increment the event count, they're
+        // associated with another entry
+    }
+
     #[inline]
     fn record_unpaired_wall_clock_time(&mut self, time: Nanoseconds) {
+        self.flush_repeated_events();
         let (first, second) = LogEntry::unpaired_wall_clock_time(time);
         let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
         self.process_overwritten_log_entries(first_overwritten, second_overwritten);
@@ -449,12 +934,12 @@ impl<'a> DynamicHistory<'a> {
                 Some(original_epoch),
             );
             self.self_clock.epoch = fresh_epoch;
-            self.record_event_with_payload(
+            self.push_event_with_payload(
                 EventId::EVENT_LOGICAL_CLOCK_OVERFLOWED,
                 self.self_clock.epoch.0 as u32,
             );
             if restart_counter_had_error.0 {
-                self.record_event(EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID);
+                self.push_event(EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID);
             }
         }
     }
@@ -471,6 +956,8 @@ impl<'a> DynamicHistory<'a> {
         &mut self,
         destination: &mut [u8],
     ) -> Result<Option<NonZeroUsize>, ReportError> {
+        self.flush_repeated_events();
+
         // The log has been drained if there are no events to report
         // (excluding the expected EventId::EVENT_PRODUCED_EXTERNAL_REPORT)
         match self.log.len() {
@@ -487,14 +974,33 @@ impl<'a> DynamicHistory<'a> {
             _ => (),
         }
 
+        // Leave room for the checksum trailer
+        let trailer_len = if self.checksums {
+            WireReport::<&[u8]>::CHECKSUM_LEN
+        } else {
+            0
+        };
+
         // If we can't store at least a header and one event, it's a hard error
-        if destination.len() < WireReport::<&[u8]>::buffer_len(0, 1) {
+        if destination.len() < WireReport::<&[u8]>::buffer_len(0, 1) + trailer_len {
             return Err(ReportError::InsufficientDestinationSize);
         }
 
+        // While awaiting acknowledgement, reported entries stay in the
+        // log and the same region is reported again, under the same
+        // sequence number, until the report is acknowledged
+        let report_start_seqn = self.log.tail_seqn();
+        let report_end_seqn = self.unacked_report.map(|u| u.end_seqn);
+        let mut report_missed_log_entry_count = 0;
+
         let self_clock = self.self_clock;
-        let mut report = WireReport::new_unchecked(destination);
-        report.set_fingerprint();
+        let report_len = destination.len() - trailer_len;
+        let mut report = WireReport::new_unchecked(&mut destination[..report_len]);
+        if self.checksums {
+            report.set_fingerprint_with_checksum();
+        } else {
+            report.set_fingerprint();
+        }
         report.set_probe_id(self.probe_id);
         report.set_clock(crate::pack_clock_word(self_clock.epoch, self_clock.ticks));
         report.set_persistent_epoch_counting(self.restart_counter.is_tracking_restarts());
@@ -540,7 +1046,11 @@ impl<'a> DynamicHistory<'a> {
                     EventId::EVENT_LOG_ITEMS_MISSED,
                     self.missed_log_entry_count,
                 );
-                self.missed_log_entry_count = 0;
+                if self.report_acks {
+                    report_missed_log_entry_count = self.missed_log_entry_count;
+                } else {
+                    self.missed_log_entry_count = 0;
+                }
                 let dest_bytes = &mut log_region[0..2 * size_of::<LogEntry>()];
                 dest_bytes[0..4].copy_from_slice(&first.raw().to_le_bytes());
                 dest_bytes[4..8].copy_from_slice(&second.raw().to_le_bytes());
@@ -551,6 +1061,11 @@ impl<'a> DynamicHistory<'a> {
             // We peek the next entry so that we never throw away an item we don't have space for,
             // since the size of the next entry isn't known until it is peeked
             while let Some(peeked) = self.log.peek() {
+                if let Some(end_seqn) = report_end_seqn {
+                    if self.log.tail_seqn() >= end_seqn {
+                        break;
+                    }
+                }
                 match peeked {
                     WholeEntry::Double(first, second) => {
                         if n_copied > n_entries_possible - 2 {
@@ -559,11 +1074,46 @@ impl<'a> DynamicHistory<'a> {
                             break;
                         }
 
-                        // Ensure we never fragment a paired wall clock entry from its
-                        // associated other entry across reports
-                        if first.has_wall_clock_time_paired_bit_set() {
-                            // Bail out early if we don't have room another double-item entry
-                            if n_copied + 4 > n_entries_possible {
+                        // Ensure we never fragment a paired wall clock time, wide payload
+                        // high bits or blob data entry from its associated entries across reports
+                        if precedes_buddy_entry(&peeked) {
+                            let group_len = buddy_group_len(&self.log);
+                            if group_len > n_entries_possible {
+                                // The group can't fit in any report this size, drop it
+                                // rather than hold up the rest of the log behind it
+                                let mut n_dropped = 0_u32;
+                                while let Some(entry) = self.log.pop() {
+                                    n_dropped += u32::from(entry.size());
+                                    if let WholeEntry::Double(first, second) = entry {
+                                        if first.has_clock_bit_set() && !self.report_acks {
+                                            // Safe to unwrap because entry was written into the log as a clock probe id
+                                            let id = ProbeId::new(
+                                                first.interpret_as_logical_clock_probe_id(),
+                                            )
+                                            .unwrap();
+                                            let (epoch, ticks) =
+                                                crate::unpack_clock_word(second.raw());
+                                            if Self::merge_clocks(
+                                                clocks,
+                                                LogicalClock { id, epoch, ticks },
+                                            )
+                                            .is_err()
+                                            {
+                                                did_clocks_overflow = true;
+                                            }
+                                        }
+                                    }
+                                    if !precedes_buddy_entry(&entry) {
+                                        break;
+                                    }
+                                }
+                                if report_end_seqn.is_none() {
+                                    self.missed_log_entry_count =
+                                        self.missed_log_entry_count.saturating_add(n_dropped);
+                                }
+                                continue;
+                            }
+                            if n_copied + group_len > n_entries_possible {
                                 break;
                             }
                         }
@@ -580,9 +1130,11 @@ impl<'a> DynamicHistory<'a> {
                                 break;
                             }
 
+                            // Awaiting acknowledgement, clocks are merged once acknowledged
                             let (epoch, ticks) = crate::unpack_clock_word(second.raw());
-                            if Self::merge_clocks(clocks, LogicalClock { id, epoch, ticks })
-                                .is_err()
+                            if !self.report_acks
+                                && Self::merge_clocks(clocks, LogicalClock { id, epoch, ticks })
+                                    .is_err()
                             {
                                 did_clocks_overflow = true;
                             }
@@ -600,6 +1152,14 @@ impl<'a> DynamicHistory<'a> {
                             // here, and don't consume the peeked entry
                             break;
                         }
+
+                        // Ensure we never fragment an event from its event repeated
+                        // entry across reports
+                        if let Some(WholeEntry::Double(next, _)) = self.log.iter().nth(1) {
+                            if next.is_event_repeated() && n_copied + 3 > n_entries_possible {
+                                break;
+                            }
+                        }
                         let dest_bytes = &mut log_region[n_copied * size_of::<LogEntry>()
                             ..(n_copied + 1) * size_of::<LogEntry>()];
                         dest_bytes.copy_from_slice(&entry.raw().to_le_bytes());
@@ -614,25 +1174,45 @@ impl<'a> DynamicHistory<'a> {
             report.set_n_log_entries(n_copied as u32);
 
             if did_clocks_overflow {
-                self.record_event(EventId::EVENT_NUM_CLOCKS_OVERFLOWED);
+                self.push_event(EventId::EVENT_NUM_CLOCKS_OVERFLOWED);
             }
         }
 
-        self.report_seq_num = self.report_seq_num.wrapping_add(1);
-        self.record_event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT);
+        if self.report_acks {
+            let end_seqn = self.log.tail_seqn();
+            self.log.rewind_tail(report_start_seqn);
+            let is_retransmission = self.unacked_report.is_some();
+            self.unacked_report = Some(UnackedReport {
+                seq_num: self.report_seq_num,
+                end_seqn,
+                missed_log_entry_count: report_missed_log_entry_count,
+            });
+            if !is_retransmission {
+                self.push_event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT);
+            }
+        } else {
+            self.report_seq_num = self.report_seq_num.wrapping_add(1);
+            self.push_event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT);
+        }
 
-        Ok(NonZeroUsize::new(
-            WireReport::<&[u8]>::header_len() + report.payload_len(),
-        ))
+        let len = WireReport::<&[u8]>::header_len() + report.payload_len();
+        if self.checksums {
+            let checksum = report.compute_checksum();
+            le_bytes::write_u32(&mut destination[len..len + trailer_len], checksum);
+        }
+        Ok(NonZeroUsize::new(len + trailer_len))
     }
 
     #[inline]
     pub(crate) fn produce_snapshot(&mut self) -> CausalSnapshot {
-        let snap = CausalSnapshot {
+        let mut snap = CausalSnapshot {
             clock: self.self_clock,
-            reserved_0: [0, 0],
+            reserved_0: [CausalSnapshot::VERSION, self.snapshot_flags()],
             reserved_1: [0, 0],
         };
+        if self.checksums {
+            snap.reserved_1 = snap.checksum().to_le_bytes();
+        }
         self.increment_local_clock();
         self.write_clocks_to_log(&[self.self_clock]);
         snap
@@ -640,11 +1220,14 @@ impl<'a> DynamicHistory<'a> {
 
     #[inline]
     pub(crate) fn produce_snapshot_with_time(&mut self, time: Nanoseconds) -> CausalSnapshot {
-        let snap = CausalSnapshot {
+        let mut snap = CausalSnapshot {
             clock: self.self_clock,
-            reserved_0: [0, 0],
+            reserved_0: [CausalSnapshot::VERSION, self.snapshot_flags()],
             reserved_1: [0, 0],
         };
+        if self.checksums {
+            snap.reserved_1 = snap.checksum().to_le_bytes();
+        }
         self.increment_local_clock();
         self.record_paired_wall_clock_time(time);
         self.write_clocks_to_log(&[self.self_clock]);
@@ -661,8 +1244,12 @@ impl<'a> DynamicHistory<'a> {
         s.set_probe_id(self.self_clock.id);
         s.set_epoch(self.self_clock.epoch);
         s.set_ticks(self.self_clock.ticks);
-        s.set_reserved_0([0, 0]);
+        s.set_version(CausalSnapshot::VERSION);
+        s.set_flags(self.snapshot_flags());
         s.set_reserved_1([0, 0]);
+        if self.checksums {
+            s.set_checksum();
+        }
         self.increment_local_clock();
         self.write_clocks_to_log(&[self.self_clock]);
         Ok(WireCausalSnapshot::<&[u8]>::min_buffer_len())
@@ -679,8 +1266,12 @@ impl<'a> DynamicHistory<'a> {
         s.set_probe_id(self.self_clock.id);
         s.set_epoch(self.self_clock.epoch);
         s.set_ticks(self.self_clock.ticks);
-        s.set_reserved_0([0, 0]);
+        s.set_version(CausalSnapshot::VERSION);
+        s.set_flags(self.snapshot_flags());
         s.set_reserved_1([0, 0]);
+        if self.checksums {
+            s.set_checksum();
+        }
         self.increment_local_clock();
         self.record_paired_wall_clock_time(time);
         self.write_clocks_to_log(&[self.self_clock]);
@@ -692,6 +1283,8 @@ impl<'a> DynamicHistory<'a> {
         &mut self,
         external_history: &CausalSnapshot,
     ) -> Result<(), MergeError> {
+        external_history.check_version()?;
+        external_history.check_checksum()?;
         self.merge_internal(
             external_history.clock.id,
             external_history.clock.epoch,
@@ -703,6 +1296,8 @@ impl<'a> DynamicHistory<'a> {
     #[inline]
     pub(crate) fn merge_snapshot_bytes(&mut self, source: &[u8]) -> Result<(), MergeError> {
         let external_history = CausalSnapshot::try_from(source)?;
+        external_history.check_version()?;
+        external_history.check_checksum()?;
         self.merge_internal(
             external_history.clock.id,
             external_history.clock.epoch,
@@ -717,6 +1312,8 @@ impl<'a> DynamicHistory<'a> {
         external_history: &CausalSnapshot,
         time: Nanoseconds,
     ) -> Result<(), MergeError> {
+        external_history.check_version()?;
+        external_history.check_checksum()?;
         self.merge_internal(
             external_history.clock.id,
             external_history.clock.epoch,
@@ -732,6 +1329,8 @@ impl<'a> DynamicHistory<'a> {
         time: Nanoseconds,
     ) -> Result<(), MergeError> {
         let external_history = CausalSnapshot::try_from(source)?;
+        external_history.check_version()?;
+        external_history.check_checksum()?;
         self.merge_internal(
             external_history.clock.id,
             external_history.clock.epoch,
@@ -776,6 +1375,7 @@ impl<'a> DynamicHistory<'a> {
     // (via snapshot merge/produce_with_time), it will precede the local logical clocks
     #[inline]
     fn write_clocks_to_log(&mut self, clocks: &[LogicalClock]) {
+        self.flush_repeated_events();
         for c in clocks.iter() {
             let (first, second) = LogEntry::clock(*c);
             let (first_overwritten, second_overwritten) = self.log.push_double(first, second);
@@ -786,7 +1386,7 @@ impl<'a> DynamicHistory<'a> {
     #[inline]
     fn merge_clock(&mut self, ext_clock: LogicalClock) {
         if Self::merge_clocks(&mut self.clocks, ext_clock).is_err() {
-            self.record_event(EventId::EVENT_NUM_CLOCKS_OVERFLOWED);
+            self.push_event(EventId::EVENT_NUM_CLOCKS_OVERFLOWED);
         }
     }
 
@@ -814,6 +1414,32 @@ impl<'a> DynamicHistory<'a> {
 
 struct RestartCounterProvidedInvalidEpochSeqId(bool);
 
+/// Determine if this entry must be kept together with the entry that follows it
+#[inline]
+fn precedes_buddy_entry(entry: &WholeEntry<LogEntry>) -> bool {
+    match entry {
+        WholeEntry::Double(first, _) => {
+            first.has_wall_clock_time_paired_bit_set()
+                || first.is_wide_payload_high_bits()
+                || first.is_blob_data()
+        }
+        WholeEntry::Single(_) => false,
+    }
+}
+
+/// The number of log entries at the tail of the log that must be kept together
+#[inline]
+fn buddy_group_len(log: &LogBuffer) -> usize {
+    let mut len = 0;
+    for entry in log.iter() {
+        len += usize::from(entry.size());
+        if !precedes_buddy_entry(&entry) {
+            break;
+        }
+    }
+    len
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -863,33 +1489,33 @@ mod test {
             .unwrap();
 
             h.merge_clock(lc(probe_b, 1, 1));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 1, 1)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 1, 1)));
 
             // Sanity check just the clock tick
             h.merge_clock(lc(probe_b, 1, 2));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 1, 2)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 1, 2)));
 
             // Sanity check the epoch tick
             h.merge_clock(lc(probe_b, 2, 2));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 2, 2)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 2, 2)));
 
             // Can't roll back the clock
             h.merge_clock(lc(probe_b, 2, 1));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 2, 2)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 2, 2)));
 
             // Can't roll back the epoch
             h.merge_clock(lc(probe_b, 1, 3));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 2, 2)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 2, 2)));
 
             // Go to the very edge of the epoch's range
             let emax = ProbeEpoch::MAX;
             let tmax = ProbeTicks::MAX;
             h.merge_clock(lc(probe_b, emax.0, tmax.0));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, emax.0, tmax.0)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, emax.0, tmax.0)));
 
             // Wraparound to 1 is now allowed
             h.merge_clock(lc(probe_b, 1, 1));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 1, 1)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 1, 1)));
         }
 
         // Wrap around can happen even if a few messages were missed (because of
@@ -907,7 +1533,7 @@ mod test {
 
             h.merge_clock(lc(probe_b, ProbeEpoch::MAX.0 - 2, 1));
             h.merge_clock(lc(probe_b, 2, 1));
-            assert_eq!(find_clock(&h, probe_b), Some(lc(probe_b, 2, 1)));
+            assert_eq!(find_clock(h, probe_b), Some(lc(probe_b, 2, 1)));
         }
 
         // But not outside the threshold
@@ -925,7 +1551,7 @@ mod test {
             h.merge_clock(lc(probe_b, ProbeEpoch::MAX.0 - 2, 1));
             h.merge_clock(lc(probe_b, 5, 1));
             assert_eq!(
-                find_clock(&h, probe_b),
+                find_clock(h, probe_b),
                 Some(lc(probe_b, ProbeEpoch::MAX.0 - 2, 1))
             );
         }
@@ -934,7 +1560,7 @@ mod test {
     #[test]
     fn merged_clocks_overflow_error_event() {
         let probe_id = ProbeId::new(1).unwrap();
-        let mut storage = [MaybeUninit::new(0u8); 512];
+        let mut storage = [MaybeUninit::new(0u8); 544];
         let h = DynamicHistory::new_at(
             &mut storage,
             probe_id,
@@ -950,7 +1576,7 @@ mod test {
             ticks: ProbeTicks(clock),
         };
 
-        assert!(h.clocks.capacity() * 4 <= core::u16::MAX as usize);
+        assert!(h.clocks.capacity() * 4 <= u16::MAX as usize);
         for i in 1..h.clocks.capacity() * 4 {
             let other_probe = ProbeId::new(i as u32).unwrap();
             h.merge_clock(lc(other_probe, 0, i as u16));
@@ -975,7 +1601,7 @@ mod test {
 
         let found_internal_event = log_bytes
             .chunks_exact(size_of::<LogEntry>())
-            .map(|bytes| crate::wire::le_bytes::read_u32(bytes))
+            .map(crate::wire::le_bytes::read_u32)
             .map(|word| unsafe { LogEntry::new_unchecked(word) })
             .find(|log_entry| {
                 if let Some(ev) = log_entry.interpret_as_event_id() {
@@ -996,7 +1622,7 @@ mod test {
     #[test]
     fn drain_report_until_completion() {
         let probe_id = ProbeId::new(1).unwrap();
-        let mut storage = [MaybeUninit::new(0u8); 1036];
+        let mut storage = [MaybeUninit::new(0u8); 1108];
         let h = DynamicHistory::new_at(
             &mut storage,
             probe_id,
@@ -1415,9 +2041,9 @@ mod test {
     fn overwritten_paired_wall_clock_time_drops_buddy_entry() {
         let probe_id = ProbeId::new(1).unwrap();
         #[cfg(target_pointer_width = "64")]
-        let mut storage = [MaybeUninit::new(0u8); 512];
+        let mut storage = [MaybeUninit::new(0u8); 584];
         #[cfg(target_pointer_width = "32")]
-        let mut storage = [MaybeUninit::new(0u8); 478];
+        let mut storage = [MaybeUninit::new(0u8); 550];
         let h = DynamicHistory::new_at(
             &mut storage,
             probe_id,
@@ -1467,6 +2093,498 @@ mod test {
         }
     }
 
+    #[test]
+    fn disabled_events_are_not_recorded() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let quiet = EventId::new(10).unwrap();
+        let loud = EventId::new(21).unwrap();
+        h.event_filter
+            .disable(EventId::new(10).unwrap(), EventId::new(20).unwrap())
+            .unwrap();
+        let len = h.log.len();
+        let event_count = h.event_count;
+
+        h.record_event(quiet);
+        h.record_event_with_priority(quiet, 1);
+        h.record_event_with_payload(quiet, 1);
+        h.record_event_with_time(quiet, Nanoseconds::new(1).unwrap());
+        h.record_event_with_wide_payload(quiet, u64::MAX);
+        h.record_event_with_blob(quiet, b"quiet");
+        assert_eq!(h.log.len(), len);
+        assert_eq!(h.event_count, event_count);
+        assert_eq!(h.missed_log_entry_count, 0);
+
+        h.record_event(loud);
+        assert_eq!(h.log.len(), len + 1);
+
+        h.event_filter.clear();
+        h.record_event(quiet);
+        assert_eq!(h.log.len(), len + 2);
+        assert_eq!(h.event_count, event_count + 2);
+    }
+
+    #[test]
+    fn low_priority_events_do_not_overwrite() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let noisy = EventId::new(8888).unwrap();
+        let fault = EventId::new(9999).unwrap();
+        h.overwrite_priority = 2;
+
+        // Low priority events are recorded while there is room
+        while h.log.len() < h.log.capacity() {
+            h.record_event_with_priority(noisy, 1);
+        }
+        assert_eq!(h.missed_log_entry_count, 0);
+        let event_count = h.event_count;
+
+        // Once full, they're dropped instead of overwriting
+        h.record_event(noisy);
+        h.record_event_with_priority(noisy, 1);
+        h.record_event_with_payload(noisy, 1);
+        h.record_event_with_time(noisy, Nanoseconds::new(1).unwrap());
+        assert_eq!(h.missed_log_entry_count, 1 + 1 + 2 + 3);
+        assert_eq!(h.event_count, event_count);
+        assert_eq!(h.log.len(), h.log.capacity());
+
+        // Events at or above the overwrite priority still overwrite
+        h.record_event_with_priority(fault, 2);
+        h.record_event_with_priority(fault, 3);
+        assert_eq!(h.event_count, event_count + 2);
+        let mut last_entries = h.log.iter().skip(h.log.len() - 2);
+        assert_eq!(
+            last_entries.next(),
+            Some(WholeEntry::Single(LogEntry::event(fault)))
+        );
+        assert_eq!(
+            last_entries.next(),
+            Some(WholeEntry::Single(LogEntry::event(fault)))
+        );
+    }
+
+    #[test]
+    fn events_with_data_take_a_priority() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let noisy = EventId::new(8888).unwrap();
+        let fault = EventId::new(9999).unwrap();
+        let time = Nanoseconds::new(1).unwrap();
+        h.overwrite_priority = 2;
+        while h.log.len() < h.log.capacity() {
+            h.record_event_with_priority(noisy, 1);
+        }
+        let event_count = h.event_count;
+
+        h.record_event_with_payload_with_priority(noisy, 1, 1);
+        h.record_event_with_time_with_priority(noisy, time, 1);
+        h.record_event_with_payload_with_time_with_priority(noisy, 1, time, 1);
+        h.record_event_with_wide_payload_with_priority(noisy, 1, 1);
+        h.record_event_with_wide_payload_with_time_with_priority(noisy, 1, time, 1);
+        h.record_event_with_blob_with_priority(noisy, b"noisy", 1);
+        assert_eq!(h.missed_log_entry_count, 2 + 3 + 4 + 4 + 6 + 6);
+        assert_eq!(h.event_count, event_count);
+
+        h.record_event_with_payload_with_priority(fault, 1, 2);
+        h.record_event_with_time_with_priority(fault, time, 2);
+        h.record_event_with_payload_with_time_with_priority(fault, 1, time, 2);
+        h.record_event_with_wide_payload_with_priority(fault, 1, 2);
+        h.record_event_with_wide_payload_with_time_with_priority(fault, 1, time, 2);
+        h.record_event_with_blob_with_priority(fault, b"fault", 2);
+        assert_eq!(h.event_count, event_count + 6);
+        let (first, second) = LogEntry::event_with_payload(fault, 5);
+        assert_eq!(h.log.iter().last(), Some(WholeEntry::Double(first, second)));
+    }
+
+    #[test]
+    fn low_priority_events_leave_room_for_pending_repeats() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        let b = EventId::new(2).unwrap();
+        h.set_repeated_event_compression(true);
+        h.overwrite_priority = 2;
+
+        // Fill the log up to two free entries, with `a` recorded last
+        if !(h.log.capacity() - h.log.len()).is_multiple_of(2) {
+            h.record_event_with_priority(a, 1);
+        }
+        while h.log.capacity() - h.log.len() > 2 {
+            h.record_event_with_priority(b, 1);
+            h.record_event_with_priority(a, 1);
+        }
+        assert_eq!(h.log.capacity() - h.log.len(), 2);
+        let tail_seqn = h.log.tail_seqn();
+
+        // The repeat of `a` is pending, so there's no room for `b` and
+        // the repeated entry that would be flushed ahead of it
+        h.record_event_with_priority(a, 1);
+        h.record_event_with_priority(b, 1);
+        assert_eq!(h.missed_log_entry_count, 1);
+        assert_eq!(h.pending_repeat_count, 1);
+        assert_eq!(h.log.capacity() - h.log.len(), 2);
+        assert_eq!(h.log.tail_seqn(), tail_seqn);
+    }
+
+    #[test]
+    fn low_priority_repeats_need_room_for_their_entry() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        let b = EventId::new(2).unwrap();
+        let fault = EventId::new(3).unwrap();
+        h.set_repeated_event_compression(true);
+        h.overwrite_priority = 2;
+
+        // Fill the log up to one free entry, with `a` recorded last
+        if (h.log.capacity() - h.log.len()).is_multiple_of(2) {
+            h.record_event_with_priority(a, 1);
+        }
+        while h.log.capacity() - h.log.len() > 1 {
+            h.record_event_with_priority(b, 1);
+            h.record_event_with_priority(a, 1);
+        }
+        assert_eq!(h.log.capacity() - h.log.len(), 1);
+        let tail_seqn = h.log.tail_seqn();
+
+        // Repeating `a` would need an event repeated entry, which
+        // doesn't fit, so the repeat is dropped
+        h.record_event_with_priority(a, 1);
+        assert_eq!(h.pending_repeat_count, 0);
+        assert_eq!(h.missed_log_entry_count, 2);
+
+        // and nothing is left pending to be flushed over older entries
+        h.record_event_with_priority(fault, 2);
+        assert_eq!(h.log.capacity() - h.log.len(), 0);
+        assert_eq!(h.log.tail_seqn(), tail_seqn);
+        let entries: Vec<_> = h.log.iter().collect();
+        assert_eq!(
+            &entries[entries.len() - 2..],
+            &[
+                WholeEntry::Single(LogEntry::event(a)),
+                WholeEntry::Single(LogEntry::event(fault))
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_events_are_compressed() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        let b = EventId::new(2).unwrap();
+        let initial_len = h.log.len();
+
+        // Disabled by default
+        h.record_event(a);
+        h.record_event(a);
+        assert_eq!(h.log.len(), initial_len + 2);
+
+        h.set_repeated_event_compression(true);
+        for _ in 0..5 {
+            h.record_event(a);
+        }
+        assert_eq!(h.event_count, 8);
+        // The repeats are pending until something else is written
+        assert_eq!(h.log.len(), initial_len + 3);
+        h.record_event(b);
+        h.record_event_with_payload(a, 1);
+        h.record_event(a);
+        assert_eq!(h.event_count, 11);
+
+        let entries: Vec<_> = h.log.iter().skip(2).collect();
+        assert_eq!(
+            entries,
+            vec![
+                WholeEntry::Single(LogEntry::event(a)),
+                WholeEntry::Single(LogEntry::event(a)),
+                WholeEntry::Single(LogEntry::event(a)),
+                {
+                    let (first, second) = LogEntry::event_repeated(4);
+                    WholeEntry::Double(first, second)
+                },
+                WholeEntry::Single(LogEntry::event(b)),
+                {
+                    let (first, second) = LogEntry::event_with_payload(a, 1);
+                    WholeEntry::Double(first, second)
+                },
+                WholeEntry::Single(LogEntry::event(a)),
+            ]
+        );
+
+        // Pending repeats are written out before reporting
+        h.record_event(a);
+        let mut report_bytes = [0u8; 512];
+        h.report(&mut report_bytes).unwrap();
+        let report = WireReport::new(&report_bytes[..]).unwrap();
+        let log_bytes = &report.payload()[report.n_clocks() as usize * size_of::<LogicalClock>()..];
+        let n_entries = report.n_log_entries() as usize;
+        let last_entries: Vec<u32> = log_bytes[..n_entries * size_of::<LogEntry>()]
+            .chunks_exact(size_of::<LogEntry>())
+            .skip(n_entries - 3)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let (first, second) = LogEntry::event_repeated(1);
+        assert_eq!(
+            last_entries,
+            vec![LogEntry::event(a).raw(), first.raw(), second.raw()]
+        );
+    }
+
+    #[test]
+    fn overwritten_events_take_their_repeats_with_them() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        h.set_repeated_event_compression(true);
+        let a = EventId::new(1).unwrap();
+        for _ in 0..3 {
+            h.record_event(a);
+        }
+
+        // Record distinct events until everything from before has been overwritten,
+        // an event repeated entry must never be left at the tail of the log
+        for i in 0..h.log.capacity() as u32 {
+            h.record_event(EventId::new(i + 2).unwrap());
+            if let Some(WholeEntry::Double(first, _)) = h.log.peek() {
+                assert!(!first.is_event_repeated());
+            }
+        }
+        assert!(h.log.iter().all(|e| !e.first_entry().is_event_repeated()));
+    }
+
+    #[test]
+    fn wide_payloads_are_split_across_entries() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        let payload = 0x0123_4567_89AB_CDEF;
+        let time = Nanoseconds::new(2).unwrap();
+        let initial_event_count = h.event_count;
+        h.record_event_with_wide_payload(a, payload);
+        h.record_event_with_wide_payload_with_time(a, payload, time);
+        assert_eq!(h.event_count, initial_event_count + 2);
+
+        let entries: Vec<_> = h.log.iter().skip(2).collect();
+        let whole = |(first, second)| WholeEntry::Double(first, second);
+        assert_eq!(
+            entries,
+            vec![
+                whole(LogEntry::wide_payload_high_bits(payload)),
+                whole(LogEntry::event_with_payload(a, 0x89AB_CDEF)),
+                whole(LogEntry::wide_payload_high_bits(payload)),
+                whole(LogEntry::paired_wall_clock_time(time)),
+                whole(LogEntry::event_with_payload(a, 0x89AB_CDEF)),
+            ]
+        );
+    }
+
+    #[test]
+    fn blobs_are_split_across_entries() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        h.record_event_with_blob(a, b"abcdef");
+        h.record_event_with_blob(a, &[]);
+
+        let entries: Vec<_> = h.log.iter().skip(2).collect();
+        let whole = |(first, second)| WholeEntry::Double(first, second);
+        assert_eq!(
+            entries,
+            vec![
+                whole(LogEntry::blob_data(b"abcd")),
+                whole(LogEntry::blob_data(b"ef")),
+                whole(LogEntry::event_with_payload(a, 6)),
+                whole(LogEntry::blob_data(&[])),
+                whole(LogEntry::event_with_payload(a, 0)),
+            ]
+        );
+
+        // Blobs are truncated to the max length
+        let len_before = h.log.len();
+        h.record_event_with_blob(a, &[0xFF; 2 * crate::ModalityProbe::MAX_BLOB_LEN]);
+        let n_chunks = crate::ModalityProbe::MAX_BLOB_LEN / size_of::<u32>();
+        assert_eq!(h.log.len(), len_before + 2 * n_chunks + 2);
+        let max_len = crate::ModalityProbe::MAX_BLOB_LEN as u32;
+        assert_eq!(
+            h.log.iter().last(),
+            Some(whole(LogEntry::event_with_payload(a, max_len)))
+        );
+    }
+
+    #[test]
+    fn blobs_too_large_for_the_report_buffer_are_dropped() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+        let b = EventId::new(2).unwrap();
+        h.record_event_with_blob(a, &[0xFF; crate::ModalityProbe::MAX_BLOB_LEN]);
+        h.record_event(b);
+
+        let report_entries = |report_bytes: &[u8]| -> Vec<LogEntry> {
+            let report = WireReport::new(report_bytes).unwrap();
+            let log_bytes =
+                &report.payload()[report.n_clocks() as usize * size_of::<LogicalClock>()..];
+            log_bytes[..report.n_log_entries() as usize * size_of::<LogEntry>()]
+                .chunks_exact(size_of::<LogEntry>())
+                .map(crate::wire::le_bytes::read_u32)
+                .map(|word| unsafe { LogEntry::new_unchecked(word) })
+                .collect()
+        };
+
+        // A maximum length blob takes 18 entries, which never fit in
+        // a report with room for 16, so it's dropped and counted as
+        // missed instead of holding up the rest of the log
+        let mut report_dest = vec![0_u8; WireReport::<&[u8]>::buffer_len(h.clocks.len(), 16)];
+        let n_bytes = h.report(&mut report_dest).unwrap().unwrap();
+        let entries = report_entries(&report_dest[..n_bytes.get()]);
+        assert!(!entries.iter().any(|e| e.is_blob_data()));
+        assert_eq!(entries.last(), Some(&LogEntry::event(b)));
+
+        h.record_event(b);
+        let n_bytes = h.report(&mut report_dest).unwrap().unwrap();
+        let entries = report_entries(&report_dest[..n_bytes.get()]);
+        let (first, second) = LogEntry::event_with_payload(
+            EventId::EVENT_LOG_ITEMS_MISSED,
+            2 * crate::ModalityProbe::MAX_BLOB_LEN as u32 / 4 + 2,
+        );
+        assert_eq!(
+            entries,
+            vec![
+                first,
+                second,
+                LogEntry::event(EventId::EVENT_PRODUCED_EXTERNAL_REPORT),
+                LogEntry::event(b),
+            ]
+        );
+    }
+
+    #[test]
+    fn overwritten_wide_payload_drops_buddy_entries() {
+        let probe_id = ProbeId::new(1).unwrap();
+        let mut storage = [MaybeUninit::new(0u8); 512];
+        let h = DynamicHistory::new_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let a = EventId::new(1).unwrap();
+
+        // Interleave differently sized entries so the tail lands on every
+        // position within a wide payload group at some point
+        for i in 0..h.log.capacity() as u64 {
+            if i % 3 == 0 {
+                h.record_event(a);
+            } else {
+                h.record_event_with_wide_payload_with_time(
+                    a,
+                    i << 32,
+                    Nanoseconds::new(i).unwrap(),
+                );
+            }
+            if let Some(tail) = h.log.peek() {
+                let first = tail.first_entry();
+                assert!(!first.has_event_with_payload_bit_set() || tail.is_double());
+                assert!(!first.has_wall_clock_time_paired_bit_set());
+            }
+        }
+
+        // Every high bits entry is followed by its paired time and event
+        let entries: Vec<_> = h.log.iter().collect();
+        for (idx, e) in entries.iter().enumerate() {
+            if e.first_entry().is_wide_payload_high_bits() {
+                assert!(entries[idx + 1]
+                    .first_entry()
+                    .has_wall_clock_time_paired_bit_set());
+                assert!(entries[idx + 2]
+                    .first_entry()
+                    .has_event_with_payload_bit_set());
+            }
+        }
+    }
+
     #[cfg(feature = "debug-collector-access")]
     #[test]
     fn debug_collector_offsets() {
@@ -1539,5 +2657,13 @@ mod test {
             log_storage_cap_offset(size_of::<usize>() as _),
             log_storage_cap_addr - history_addr
         );
+
+        for i in 0..crate::EVENT_FILTER_LEN {
+            let range = &probe.history.event_filter.ranges[i];
+            let first_addr = &range.first as *const _ as u64;
+            let last_addr = &range.last as *const _ as u64;
+            assert_eq!(event_filter_range_offset(i), first_addr - history_addr);
+            assert_eq!(event_filter_range_offset(i) + 4, last_addr - history_addr);
+        }
     }
 }
@@ -196,35 +196,52 @@ impl EventId {
     /// The probe produced a log report for transmission to the backend
     /// for external analysis.
     pub const EVENT_PRODUCED_EXTERNAL_REPORT: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 1) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 1).unwrap());
     /// Some log entries were overwritten before getting reported, the number of missed
     /// entries is stored in the payload.
     pub const EVENT_LOG_ITEMS_MISSED: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 2) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 2).unwrap());
     /// A logical clock's count reached the maximum trackable value.
     /// The next sequence id / epoch is stored in the payload.
     /// If this probe is tracking restarts, then the next sequence id is provided
     /// by the user's backing implementation.
     pub const EVENT_LOGICAL_CLOCK_OVERFLOWED: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 3) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 3).unwrap());
     /// The local instance (e.g. Modality probe) did not have enough memory
     /// reserved to store enough logical clocks to track all of the unique
     /// neighbors that attempt to communicate with it.
     pub const EVENT_NUM_CLOCKS_OVERFLOWED: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 4) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 4).unwrap());
     /// The report destination buffer is too small to fit a header and/or the frontier clocks
     pub const EVENT_INSUFFICIENT_REPORT_BUFFER_SIZE: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 5) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 5).unwrap());
     /// The probe successfully initialized itself.
     pub const EVENT_PROBE_INITIALIZED: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 6) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 6).unwrap());
     /// The probe is configured to track restarts, but the user's implementation
     /// returned an invalid zero value or a None option variant.
     pub const EVENT_INVALID_NEXT_EPOCH_SEQ_ID: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 7) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 7).unwrap());
     /// Reserved for indicating wall clock time
     pub const EVENT_WALL_CLOCK_TIME_ONLY: EventId =
-        EventId(unsafe { NonZeroU32::new_unchecked(EventId::MAX_INTERNAL_ID - 8) });
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 8).unwrap());
+    /// The event immediately preceding this one was recorded again, consecutively,
+    /// the number of additional occurrences is stored in the payload.
+    /// Only produced by probes with repeated event compression enabled.
+    pub const EVENT_PREVIOUS_EVENT_REPEATED: EventId =
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 9).unwrap());
+    /// Carries the upper 32 bits of a 64-bit payload, the rest of which is
+    /// stored in the event with payload entry that follows.
+    pub const EVENT_WIDE_PAYLOAD_HIGH_BITS: EventId =
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 10).unwrap());
+    /// Carries up to 4 bytes of a blob payload, the length of which is
+    /// stored in the event with payload entry that follows.
+    pub const EVENT_BLOB_DATA: EventId =
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 11).unwrap());
+    /// The probe recovered the log it had before a reset instead of
+    /// starting afresh.
+    pub const EVENT_PROBE_RECOVERED: EventId =
+        EventId(NonZeroU32::new(EventId::MAX_INTERNAL_ID - 12).unwrap());
 
     /// The events reserved for internal use
     pub const INTERNAL_EVENTS: &'static [EventId] = &[
@@ -236,6 +253,10 @@ impl EventId {
         EventId::EVENT_PROBE_INITIALIZED,
         EventId::EVENT_INVALID_NEXT_EPOCH_SEQ_ID,
         EventId::EVENT_WALL_CLOCK_TIME_ONLY,
+        EventId::EVENT_PREVIOUS_EVENT_REPEATED,
+        EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS,
+        EventId::EVENT_BLOB_DATA,
+        EventId::EVENT_PROBE_RECOVERED,
     ];
 
     /// raw_id must be greater than 0 and less than EventId::MAX_USER_ID
@@ -326,7 +347,7 @@ pub mod prop {
 
         fn current(&self) -> EventId {
             let x = self.0.current();
-            let x1 = core::cmp::max(1, core::cmp::min(x, EventId::MAX_USER_ID));
+            let x1 = x.clamp(1, EventId::MAX_USER_ID);
             EventId(unsafe { NonZeroU32::new_unchecked(x1) })
         }
 
@@ -372,7 +393,7 @@ pub mod prop {
 
         fn current(&self) -> ProbeId {
             let x = self.0.current();
-            let x1 = core::cmp::max(1, core::cmp::min(x, ProbeId::MAX_ID));
+            let x1 = x.clamp(1, ProbeId::MAX_ID);
             ProbeId(unsafe { NonZeroU32::new_unchecked(x1) })
         }
 
@@ -457,7 +478,7 @@ pub(crate) mod id_tests {
     }
 
     prop_compose! {
-        fn gen_raw_invalid_probe_id()(raw_id in (ProbeId::MAX_ID+1)..core::u32::MAX) -> u32 {
+        fn gen_raw_invalid_probe_id()(raw_id in (ProbeId::MAX_ID+1)..u32::MAX) -> u32 {
             raw_id
         }
     }
@@ -487,7 +508,7 @@ pub(crate) mod id_tests {
         }
     }
     prop_compose! {
-        fn gen_raw_invalid_event_id()(raw_id in (EventId::MAX_INTERNAL_ID+1)..core::u32::MAX) -> u32 {
+        fn gen_raw_invalid_event_id()(raw_id in (EventId::MAX_INTERNAL_ID+1)..u32::MAX) -> u32 {
             raw_id
         }
     }
@@ -13,7 +13,7 @@ use core::{
     num::NonZeroUsize,
 };
 
-use fixed_slice_vec::single::{embed_uninit, EmbedValueError, SplitUninitError};
+use fixed_slice_vec::single::{split_uninit_from_uninit_bytes, EmbedValueError, SplitUninitError};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use static_assertions::{assert_cfg, const_assert};
@@ -27,20 +27,36 @@ pub use restart_counter::{
     next_sequence_id_fn, CRestartCounterProvider, RestartCounter, RestartCounterProvider,
     RestartSequenceIdUnavailable, RustRestartCounterProvider,
 };
+#[cfg(feature = "shared-probe")]
+pub use shared::*;
 pub use time::{NanosecondResolution, Nanoseconds, WallClockId};
 
 mod error;
+mod filter;
 mod history;
 mod id;
 pub mod log;
 mod macros;
 mod restart_counter;
+#[cfg(feature = "shared-probe")]
+mod shared;
 pub mod time;
 pub mod wire;
 
 #[cfg(feature = "debug-collector-access")]
 pub mod field_offsets;
 
+/// Priority of events recorded without an explicit priority.
+///
+/// Events whose priority is below the probe's overwrite priority level
+/// are dropped, rather than overwriting older log entries, when the
+/// log is full.
+pub const DEFAULT_EVENT_PRIORITY: u32 = 0;
+
+/// Number of ranges of disabled event ids the probe's event filter
+/// can hold.
+pub const EVENT_FILTER_LEN: usize = 4;
+
 /// Snapshot of causal history for transmission around the system.
 ///
 /// Note the use of bare integer types rather than the safety-oriented
@@ -51,9 +67,14 @@ pub struct CausalSnapshot {
     /// Probe id and tick-count at the probe which this history snapshot
     /// was created from
     pub clock: LogicalClock,
-    /// Reserved field
+    /// Snapshot format version (first byte) and flags (second byte).
+    ///
+    /// Snapshots produced before the format was versioned have both
+    /// bytes zeroed. See `CausalSnapshot::VERSION` and
+    /// `CausalSnapshot::FLAG_COMPATIBLE`.
     pub reserved_0: [u8; 2],
-    /// Reserved field
+    /// Checksum of the rest of the snapshot when the
+    /// `CausalSnapshot::FLAG_CHECKSUM` flag is set, otherwise zero.
     pub reserved_1: [u8; 2],
 }
 
@@ -148,11 +169,30 @@ pub fn pack_clock_word(epoch: ProbeEpoch, ticks: ProbeTicks) -> u32 {
 /// Unpack a probe epoch and clock from a u32
 #[inline]
 pub fn unpack_clock_word(w: u32) -> (ProbeEpoch, ProbeTicks) {
-    let epoch = (w >> 16) & (core::u16::MAX as u32);
-    let ticks = w & (core::u16::MAX as u32);
+    let epoch = (w >> 16) & (u16::MAX as u32);
+    let ticks = w & (u16::MAX as u32);
     (ProbeEpoch(epoch as u16), ProbeTicks(ticks as u16))
 }
 
+/// Embed a value constructed by `f` at an aligned location within
+/// the provided bytes.
+///
+/// Equivalent to `fixed_slice_vec::single::embed_uninit`, except that
+/// the value is written in place rather than assigned, so that the
+/// uninitialized destination is never dropped.
+#[inline]
+pub(crate) fn embed_uninit<'a, T, F, E>(
+    destination: &'a mut [MaybeUninit<u8>],
+    f: F,
+) -> Result<&'a mut T, EmbedValueError<E>>
+where
+    F: FnOnce(&'a mut [MaybeUninit<u8>]) -> Result<T, E>,
+{
+    let (_prefix, uninit_ref, suffix) = split_uninit_from_uninit_bytes(destination)?;
+    let value = f(suffix).map_err(EmbedValueError::ConstructionError)?;
+    Ok(uninit_ref.write(value))
+}
+
 /// A single logical clock, usable as an entry in a vector clock
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -265,11 +305,45 @@ pub trait Probe {
     /// range.
     fn record_event(&mut self, event_id: EventId);
 
+    /// Record that an event occurred with the given priority.
+    ///
+    /// If the log is full and `priority` is below the probe's overwrite
+    /// priority level, the event is dropped instead of overwriting
+    /// older log entries. Events recorded through the other methods
+    /// have priority `DEFAULT_EVENT_PRIORITY`.
+    ///
+    /// Accepts an event_id pre-validated to be within the acceptable
+    /// range.
+    ///
+    /// The default implementation records the event without a
+    /// priority, for implementations that don't drop events.
+    fn record_event_with_priority(&mut self, event_id: EventId, priority: u32) {
+        let _ = priority;
+        self.record_event(event_id);
+    }
+
     /// Record that an event occurred with time.
     ///
     /// See also [ModalityProbe::record_event](struct.ModalityProbe.html#method.record_event).
     fn record_event_with_time(&mut self, event_id: EventId, time: Nanoseconds);
 
+    /// Record that an event occurred with time and the given priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_time(event_id, time);
+    }
+
     /// Record that an event occurred with a `u32`'s width's worth (4
     /// bytes) of context via `payload`. The end user is responsible for
     /// associating meaning with each event_id.
@@ -278,6 +352,24 @@ pub trait Probe {
     /// range.
     fn record_event_with_payload(&mut self, event_id: EventId, payload: u32);
 
+    /// Record that an event occurred with a 4-byte payload and the given
+    /// priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_payload(event_id, payload);
+    }
+
     /// Record that an event occurred with a `u32`'s width's worth (4
     /// bytes) of context via `payload` and time.
     ///
@@ -289,6 +381,129 @@ pub trait Probe {
         time: Nanoseconds,
     );
 
+    /// Record that an event occurred with a 4-byte payload, time and the
+    /// given priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_payload_with_time(event_id, payload, time);
+    }
+
+    /// Record that an event occurred with a `u64`'s width's worth (8
+    /// bytes) of context via `payload`. The end user is responsible for
+    /// associating meaning with each event_id.
+    ///
+    /// Accepts an event_id pre-validated to be within the acceptable
+    /// range.
+    ///
+    /// The default implementation records the event with the low 32
+    /// bits of the payload, for implementations that can't store wide
+    /// payloads.
+    fn record_event_with_wide_payload(&mut self, event_id: EventId, payload: u64) {
+        self.record_event_with_payload(event_id, payload as u32);
+    }
+
+    /// Record that an event occurred with an 8-byte payload and the given
+    /// priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_wide_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_wide_payload(event_id, payload);
+    }
+
+    /// Record that an event occurred with a `u64`'s width's worth (8
+    /// bytes) of context via `payload` and time.
+    ///
+    /// See also [ModalityProbe::record_event_with_wide_payload](struct.ModalityProbe.html#method.record_event_with_wide_payload).
+    ///
+    /// The default implementation records the event with the low 32
+    /// bits of the payload, like `record_event_with_wide_payload`.
+    fn record_event_with_wide_payload_with_time(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+    ) {
+        self.record_event_with_payload_with_time(event_id, payload as u32, time);
+    }
+
+    /// Record that an event occurred with an 8-byte payload, time and the
+    /// given priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_wide_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_wide_payload_with_time(event_id, payload, time);
+    }
+
+    /// Record that an event occurred with a short blob of bytes
+    /// (e.g. a packet header or a task name) as context via `blob`.
+    /// The end user is responsible for associating meaning with each
+    /// event_id.
+    ///
+    /// Blobs longer than [ModalityProbe::MAX_BLOB_LEN](struct.ModalityProbe.html#associatedconstant.MAX_BLOB_LEN)
+    /// bytes are truncated.
+    ///
+    /// Accepts an event_id pre-validated to be within the acceptable
+    /// range.
+    ///
+    /// The default implementation records the event with the blob's
+    /// length as its payload, for implementations that can't store
+    /// blobs.
+    fn record_event_with_blob(&mut self, event_id: EventId, blob: &[u8]) {
+        let len = blob.len().min(ModalityProbe::MAX_BLOB_LEN);
+        self.record_event_with_payload(event_id, len as u32);
+    }
+
+    /// Record that an event occurred with a short blob of bytes and the
+    /// given priority.
+    ///
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority)
+    /// for how the priority is used.
+    ///
+    /// The default implementation records the event without a
+    /// priority, like `record_event_with_priority`.
+    fn record_event_with_blob_with_priority(
+        &mut self,
+        event_id: EventId,
+        blob: &[u8],
+        priority: u32,
+    ) {
+        let _ = priority;
+        self.record_event_with_blob(event_id, blob);
+    }
+
     /// Write a summary of this probe's causal history for use
     /// by another probe elsewhere in the system.
     fn produce_snapshot(&mut self) -> CausalSnapshot;
@@ -376,6 +591,8 @@ impl<'a> ModalityProbe<'a> {
     pub const PADDING_GUARD_BYTE: u8 = 0xbd;
     /// u32 fingerprint at the beginning of probe struct
     pub const STRUCT_FINGERPRINT: u32 = 0x9a9a9a9a;
+    /// Blob payloads longer than this many bytes are truncated when recorded
+    pub const MAX_BLOB_LEN: usize = 32;
 
     /// Initialize a probe for this probe id.
     /// `probe_id` ought to be unique throughout the system,
@@ -474,6 +691,102 @@ impl<'a> ModalityProbe<'a> {
         }
     }
 
+    /// Initialize a probe for this probe id like `initialize_at`,
+    /// unless `memory` still holds an intact probe initialized there
+    /// with the same probe id, time resolution and wall clock id,
+    /// e.g. in RAM which isn't initialized at boot after a warm reset.
+    ///
+    /// A recovered probe keeps its log, so that the events leading up
+    /// to the reset get reported after boot. Its epoch is bumped, using
+    /// the restart counter if it tracks restarts, and it records an
+    /// `EVENT_PROBE_RECOVERED` event rather than
+    /// `EVENT_PROBE_INITIALIZED`. Options such as checksums or the
+    /// event filter are not recovered and need to be set again.
+    ///
+    /// # Safety
+    ///
+    /// The contents of `memory` must be initialized, though they may
+    /// be arbitrary.
+    pub unsafe fn initialize_or_recover_at(
+        memory: &'a mut [MaybeUninit<u8>],
+        probe_id: ProbeId,
+        time_resolution: NanosecondResolution,
+        wall_clock_id: WallClockId,
+        restart_counter: RestartCounterProvider<'a>,
+    ) -> Result<&'a mut ModalityProbe<'a>, StorageSetupError> {
+        // Nothing borrowed from memory outlives a failed recovery
+        let recovery_memory = &mut *(memory as *mut [MaybeUninit<u8>]);
+        match ModalityProbe::recover_at(
+            recovery_memory,
+            probe_id,
+            time_resolution,
+            wall_clock_id,
+            restart_counter,
+        ) {
+            Ok(probe) => Ok(probe),
+            Err(restart_counter) => ModalityProbe::initialize_at(
+                memory,
+                probe_id,
+                time_resolution,
+                wall_clock_id,
+                restart_counter,
+            ),
+        }
+    }
+
+    /// Recover the probe left in `memory` by `initialize_at`, handing
+    /// the restart counter back if it isn't intact.
+    unsafe fn recover_at(
+        memory: &'a mut [MaybeUninit<u8>],
+        probe_id: ProbeId,
+        time_resolution: NanosecondResolution,
+        wall_clock_id: WallClockId,
+        restart_counter: RestartCounterProvider<'a>,
+    ) -> Result<&'a mut ModalityProbe<'a>, RestartCounterProvider<'a>> {
+        let padding_offset = memory.as_ptr().align_offset(align_of::<Self>());
+        if memory.len() < padding_offset {
+            return Err(restart_counter);
+        }
+        let (padding, aligned_memory) = memory.split_at_mut(padding_offset);
+        if padding
+            .iter()
+            .any(|b| b.assume_init() != Self::PADDING_GUARD_BYTE)
+        {
+            return Err(restart_counter);
+        }
+        let (_prefix, uninit_probe, history_memory) =
+            match split_uninit_from_uninit_bytes::<ModalityProbe>(aligned_memory) {
+                Ok(split) => split,
+                Err(_) => return Err(restart_counter),
+            };
+        let stale = uninit_probe.as_ptr();
+
+        // The history reference, which follows the padded fingerprint, is
+        // read as a plain address and must point where `initialize_at`
+        // would have put the history
+        let stale_history_addr = *((stale as *const u32).add(2) as *const usize);
+        let history_addr = history_memory.as_ptr() as usize
+            + history_memory
+                .as_ptr()
+                .align_offset(align_of::<DynamicHistory>());
+        if (*stale).fingerprint != Self::STRUCT_FINGERPRINT || stale_history_addr != history_addr {
+            return Err(restart_counter);
+        }
+
+        let history = DynamicHistory::recover_at(
+            history_memory,
+            probe_id,
+            time_resolution,
+            wall_clock_id,
+            restart_counter,
+        )?;
+        Ok(uninit_probe.write(ModalityProbe {
+            fingerprint: Self::STRUCT_FINGERPRINT,
+            fingerprint_padding: 0,
+            history,
+        }))
+    }
+
     /// Initialize a probe for this probe id,
     /// using `history_memory` for backing storage while
     /// returning a probe instance on the stack.
@@ -539,6 +852,29 @@ impl<'a> ModalityProbe<'a> {
         Ok(())
     }
 
+    /// Record that an event occurred with time and the given priority.
+    ///
+    /// Accepts a primitive event_id/time and returns an error if
+    /// either was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_time_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_time_with_priority(
+        &mut self,
+        event_id: u32,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        let event_id = EventId::try_from(event_id)?;
+        let time = Nanoseconds::new(time_ns).ok_or(InvalidWallClockTime)?;
+        self.history
+            .record_event_with_time_with_priority(event_id, time, priority);
+        Ok(())
+    }
+
     /// Record that an event occurred with a `u32`'s width's worth (4
     /// bytes) of context via `payload` and time.
     ///
@@ -565,6 +901,31 @@ impl<'a> ModalityProbe<'a> {
         Ok(())
     }
 
+    /// Record that an event occurred with a 4-byte payload, time and the
+    /// given priority.
+    ///
+    /// Accepts a primitive event_id/time and returns an error if
+    /// either was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_payload_with_time_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_payload_with_time_with_priority(
+        &mut self,
+        event_id: u32,
+        payload: u32,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        let event_id = EventId::try_from(event_id)?;
+        let time = Nanoseconds::new(time_ns).ok_or(InvalidWallClockTime)?;
+        self.history
+            .record_event_with_payload_with_time_with_priority(event_id, payload, time, priority);
+        Ok(())
+    }
+
     /// Record that an event occurred. The end user is responsible
     /// for associating meaning with each event_id.
     ///
@@ -582,6 +943,26 @@ impl<'a> ModalityProbe<'a> {
         Ok(())
     }
 
+    /// Record that an event occurred with the given priority.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_priority(
+        &mut self,
+        event_id: u32,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history.record_event_with_priority(event_id, priority);
+        Ok(())
+    }
+
     /// Record that an event occurred and associate some context with
     /// via a 4-byte payload, `payload`. The end user is responsible for
     /// associating meaning with each event_id.
@@ -604,6 +985,173 @@ impl<'a> ModalityProbe<'a> {
         Ok(())
     }
 
+    /// Record that an event occurred with a 4-byte payload and the given
+    /// priority.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_payload_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_payload_with_priority(
+        &mut self,
+        event_id: u32,
+        payload: u32,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history
+            .record_event_with_payload_with_priority(event_id, payload, priority);
+        Ok(())
+    }
+
+    /// Record that an event occurred and associate some context with
+    /// via an 8-byte payload, `payload`. The end user is responsible for
+    /// associating meaning with each event_id.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_wide_payload`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_wide_payload(
+        &mut self,
+        event_id: u32,
+        payload: u64,
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history
+            .record_event_with_wide_payload(event_id, payload);
+        Ok(())
+    }
+
+    /// Record that an event occurred with an 8-byte payload and the given
+    /// priority.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_wide_payload_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_priority(
+        &mut self,
+        event_id: u32,
+        payload: u64,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history
+            .record_event_with_wide_payload_with_priority(event_id, payload, priority);
+        Ok(())
+    }
+
+    /// Record that an event occurred with a `u64`'s width's worth (8
+    /// bytes) of context via `payload` and time.
+    ///
+    /// Accepts a primitive event_id/time and
+    /// returns an error if either was discovered
+    /// to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around
+    /// id kinds or want to avoid the performance penalty of id validation
+    /// every call, use `record_event_with_wide_payload_with_time` instead.
+    ///
+    /// See also [ModalityProbe::record_event_with_wide_payload](struct.ModalityProbe.html#method.record_event_with_wide_payload).
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_time(
+        &mut self,
+        event_id: u32,
+        payload: u64,
+        time_ns: u64,
+    ) -> Result<(), WithTimeError> {
+        let event_id = EventId::try_from(event_id)?;
+        let time = Nanoseconds::new(time_ns).ok_or(InvalidWallClockTime)?;
+        self.history
+            .record_event_with_wide_payload_with_time(event_id, payload, time);
+        Ok(())
+    }
+
+    /// Record that an event occurred with an 8-byte payload, time and the
+    /// given priority.
+    ///
+    /// Accepts a primitive event_id/time and returns an error if
+    /// either was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_wide_payload_with_time_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_time_with_priority(
+        &mut self,
+        event_id: u32,
+        payload: u64,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        let event_id = EventId::try_from(event_id)?;
+        let time = Nanoseconds::new(time_ns).ok_or(InvalidWallClockTime)?;
+        self.history
+            .record_event_with_wide_payload_with_time_with_priority(
+                event_id, payload, time, priority,
+            );
+        Ok(())
+    }
+
+    /// Record that an event occurred and associate a short blob of
+    /// bytes with it via `blob`. The end user is responsible for
+    /// associating meaning with each event_id.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_blob`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_blob(
+        &mut self,
+        event_id: u32,
+        blob: &[u8],
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history.record_event_with_blob(event_id, blob);
+        Ok(())
+    }
+
+    /// Record that an event occurred with a short blob of bytes and the
+    /// given priority.
+    ///
+    /// Accepts a primitive event_id and returns an error if the
+    /// event_id was discovered to be invalid.
+    ///
+    /// If you're working in Rust and want type assurances around id
+    /// kinds or want to avoid the performance penalty of id
+    /// validation every call, use `record_event_with_blob_with_priority`
+    /// instead.
+    #[inline]
+    pub fn try_record_event_with_blob_with_priority(
+        &mut self,
+        event_id: u32,
+        blob: &[u8],
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        let event_id = EventId::try_from(event_id)?;
+        self.history
+            .record_event_with_blob_with_priority(event_id, blob, priority);
+        Ok(())
+    }
+
     /// Capture the current instance's moment in causal time
     /// for correlation with external systems.
     pub fn now(&self) -> ModalityProbeInstant {
@@ -611,10 +1159,138 @@ impl<'a> ModalityProbe<'a> {
     }
 
     /// Get current value of overwrite priority
-    #[cfg(feature = "debug-collector-access")]
     pub fn get_overwrite_priority_level(&self) -> u32 {
         self.history.overwrite_priority
     }
+
+    /// Set the overwrite priority level.
+    ///
+    /// Once the log is full, events with a priority below this level
+    /// are dropped rather than overwriting older log entries.
+    pub fn set_overwrite_priority_level(&mut self, priority: u32) {
+        self.history.overwrite_priority = priority;
+    }
+
+    /// Stop recording events with ids in `first..=last`. The bounds
+    /// may be given in either order.
+    ///
+    /// Recording a disabled event has no effect. The probe's event
+    /// filter holds up to `EVENT_FILTER_LEN` ranges; an error is
+    /// returned if it is full. The filter can also be changed
+    /// remotely by the debug collector.
+    pub fn disable_events(&mut self, first: EventId, last: EventId) -> Result<(), EventFilterFull> {
+        self.history.event_filter.disable(first, last)
+    }
+
+    /// Resume recording all events.
+    pub fn enable_all_events(&mut self) {
+        self.history.event_filter.clear();
+    }
+
+    /// Whether events with the given id are currently recorded.
+    pub fn is_event_enabled(&self, event_id: EventId) -> bool {
+        self.history.event_filter.is_enabled(event_id)
+    }
+
+    /// Enable or disable run-length compression of repeated events.
+    ///
+    /// While enabled, recording the same event (without payload or time)
+    /// several times in a row stores the first occurrence followed by a
+    /// single `EVENT_PREVIOUS_EVENT_REPEATED` entry carrying the number
+    /// of additional occurrences, rather than one log entry per occurrence.
+    /// Pending repeats are written to the log ahead of the next entry or
+    /// report. Disabled by default.
+    pub fn set_repeated_event_compression(&mut self, enabled: bool) {
+        self.history.set_repeated_event_compression(enabled);
+    }
+
+    /// Enable or disable integrity checksums.
+    ///
+    /// While enabled, reports are followed by a CRC-32 trailer (and
+    /// use the `WireReport::FINGERPRINT_WITH_CHECKSUM` fingerprint)
+    /// and snapshots carry a checksum in their `reserved_1` field, so
+    /// that collectors and other probes can detect corruption in
+    /// transit. Reports with a trailer need collectors that understand
+    /// it. Meant to be enabled right after initialization. Disabled by
+    /// default.
+    pub fn set_checksums(&mut self, enabled: bool) {
+        self.history.set_checksums(enabled);
+    }
+
+    /// Produce a report split into fragments for MTU-limited transports.
+    ///
+    /// The report is written into `report_buffer`, limited to the
+    /// largest report that fits into `u8::MAX` fragments of
+    /// `fragment_buffer.len()` bytes, then split into numbered
+    /// fragments (see `wire::fragment`). Each fragment is written into
+    /// `fragment_buffer` in turn and handed to `send`. Collectors
+    /// reassemble the fragments into the original report.
+    ///
+    /// Returns the number of fragments sent, or `None` if there was
+    /// nothing to report.
+    pub fn report_fragmented<F: FnMut(&[u8])>(
+        &mut self,
+        report_buffer: &mut [u8],
+        fragment_buffer: &mut [u8],
+        mut send: F,
+    ) -> Result<Option<NonZeroUsize>, ReportError> {
+        let max_report_len = wire::max_fragmented_report_len(fragment_buffer.len());
+        if max_report_len == 0 {
+            return Err(ReportError::InsufficientDestinationSize);
+        }
+        let report_len = report_buffer.len().min(max_report_len);
+        let n_report_bytes = match self.report(&mut report_buffer[..report_len])? {
+            Some(n) => n.get(),
+            None => return Ok(None),
+        };
+        let mut fragmenter =
+            wire::ReportFragmenter::new(&report_buffer[..n_report_bytes], fragment_buffer.len())
+                .map_err(|_| ReportError::InsufficientDestinationSize)?;
+        let mut n_fragments = 0;
+        while let Some(len) = fragmenter.next_fragment(fragment_buffer) {
+            send(&fragment_buffer[..len]);
+            n_fragments += 1;
+        }
+        Ok(NonZeroUsize::new(n_fragments))
+    }
+
+    /// Enable or disable reliable reporting.
+    ///
+    /// While enabled, the log entries in a report stay in the log
+    /// until a collector acknowledges the report's sequence number
+    /// (see `acknowledge_report` and `consume_report_ack`). Until
+    /// then, `report` produces the same entries again, under the same
+    /// sequence number, so that a lost report can be retransmitted;
+    /// entries recorded in the meantime wait for the next report. If
+    /// the log fills up before the report is acknowledged, its oldest
+    /// entries are overwritten and counted as missed, as usual.
+    /// Disabled by default.
+    pub fn set_report_acknowledgements(&mut self, enabled: bool) {
+        self.history.set_report_acks(enabled);
+    }
+
+    /// Acknowledge the report with sequence number `seq_num`, releasing
+    /// its log entries.
+    ///
+    /// Returns whether a report with that sequence number was awaiting
+    /// acknowledgement.
+    pub fn acknowledge_report(&mut self, seq_num: u64) -> bool {
+        self.history.acknowledge_report(seq_num)
+    }
+
+    /// Consume a report acknowledgement sent by a collector (see
+    /// `wire::WireReportAck`).
+    ///
+    /// Returns whether it acknowledged the report awaiting
+    /// acknowledgement. Acknowledgements meant for other probes are
+    /// ignored.
+    pub fn consume_report_ack(&mut self, ack: &[u8]) -> Result<bool, wire::ReportAckWireError> {
+        let ack = wire::WireReportAck::new(ack)?;
+        if ack.probe_id()? != self.history.probe_id {
+            return Ok(false);
+        }
+        Ok(self.acknowledge_report(ack.seq_num()))
+    }
 }
 
 /// A situated moment in causal time.
@@ -651,16 +1327,43 @@ impl<'a> Probe for ModalityProbe<'a> {
         self.history.record_event(event_id);
     }
 
+    #[inline]
+    fn record_event_with_priority(&mut self, event_id: EventId, priority: u32) {
+        self.history.record_event_with_priority(event_id, priority);
+    }
+
     #[inline]
     fn record_event_with_time(&mut self, event_id: EventId, time: Nanoseconds) {
         self.history.record_event_with_time(event_id, time);
     }
 
+    #[inline]
+    fn record_event_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_time_with_priority(event_id, time, priority);
+    }
+
     #[inline]
     fn record_event_with_payload(&mut self, event_id: EventId, payload: u32) {
         self.history.record_event_with_payload(event_id, payload)
     }
 
+    #[inline]
+    fn record_event_with_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_payload_with_priority(event_id, payload, priority);
+    }
+
     #[inline]
     fn record_event_with_payload_with_time(
         &mut self,
@@ -672,11 +1375,81 @@ impl<'a> Probe for ModalityProbe<'a> {
             .record_event_with_payload_with_time(event_id, payload, time);
     }
 
+    #[inline]
+    fn record_event_with_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_payload_with_time_with_priority(event_id, payload, time, priority);
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload(&mut self, event_id: EventId, payload: u64) {
+        self.history
+            .record_event_with_wide_payload(event_id, payload)
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_wide_payload_with_priority(event_id, payload, priority);
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_time(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+    ) {
+        self.history
+            .record_event_with_wide_payload_with_time(event_id, payload, time);
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_wide_payload_with_time_with_priority(
+                event_id, payload, time, priority,
+            );
+    }
+
     #[inline]
     fn produce_snapshot(&mut self) -> CausalSnapshot {
         self.history.produce_snapshot()
     }
 
+    #[inline]
+    fn record_event_with_blob(&mut self, event_id: EventId, blob: &[u8]) {
+        self.history.record_event_with_blob(event_id, blob)
+    }
+
+    #[inline]
+    fn record_event_with_blob_with_priority(
+        &mut self,
+        event_id: EventId,
+        blob: &[u8],
+        priority: u32,
+    ) {
+        self.history
+            .record_event_with_blob_with_priority(event_id, blob, priority);
+    }
+
     #[inline]
     fn produce_snapshot_bytes(&mut self, destination: &mut [u8]) -> Result<usize, ProduceError> {
         self.history.produce_snapshot_bytes(destination)
@@ -914,9 +1687,9 @@ mod tests {
 
         // When one epoch is near the bottom of the range and the other is near the top,
         // we assume that the epoch near the bottom has wrapped around (and is actually ahead)
-        oc_cmp_eq(Greater, (0, 0), (core::u16::MAX, 0));
+        oc_cmp_eq(Greater, (0, 0), (u16::MAX, 0));
         for left in 0..=ProbeEpoch::WRAPAROUND_THRESHOLD_BOTTOM.0 {
-            for right in ProbeEpoch::WRAPAROUND_THRESHOLD_TOP.0..core::u16::MAX {
+            for right in ProbeEpoch::WRAPAROUND_THRESHOLD_TOP.0..u16::MAX {
                 // In this narrow range, even though the underlying numerical epoch value
                 // of the left is less than that of the right, it is considered greater due
                 // to wraparound awareness
@@ -934,7 +1707,7 @@ mod tests {
             epoch: ProbeEpoch(0),
             ticks: ProbeTicks::MAX,
         };
-        let init = l.clone();
+        let init = l;
         assert_eq!(
             l.next(),
             LogicalClock {
@@ -955,7 +1728,7 @@ mod tests {
             epoch: ProbeEpoch(0),
             ticks: ProbeTicks(0),
         };
-        let init = l.clone();
+        let init = l;
         assert_eq!(
             l.prev(),
             LogicalClock {
@@ -976,7 +1749,7 @@ mod tests {
             epoch: ProbeEpoch(2),
             ticks: ProbeTicks(0),
         };
-        let init = l.clone();
+        let init = l;
         assert_eq!(
             l.prev(),
             LogicalClock {
@@ -997,7 +1770,7 @@ mod tests {
             epoch: ProbeEpoch(2),
             ticks: ProbeTicks(2),
         };
-        let init = l.clone();
+        let init = l;
         assert_eq!(
             l.prev(),
             LogicalClock {
@@ -1,6 +1,7 @@
 //! Types and functionality used for the probe's event storage.
 
 use crate::{pack_clock_word, time::Nanoseconds, EventId, LogicalClock};
+use core::mem::size_of;
 use fenced_ring_buffer::FencedRingBuffer;
 
 pub(crate) const CLOCK_MASK: u32 = 0b1000_0000_0000_0000_0000_0000_0000_0000;
@@ -72,6 +73,48 @@ impl LogEntry {
         )
     }
 
+    /// Create a pair of `LogEntry`s representing the upper 32 bits
+    /// of a 64-bit payload.
+    ///
+    /// NOTE: wide payload high bits entries should *always* precede the
+    /// event with payload entry holding the lower 32 bits, with at most
+    /// a paired wall clock time entry in between.
+    #[must_use]
+    #[inline]
+    pub fn wide_payload_high_bits(payload: u64) -> (Self, Self) {
+        Self::event_with_payload(
+            EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS,
+            (payload >> 32) as u32,
+        )
+    }
+
+    /// Create a pair of `LogEntry`s carrying up to 4 bytes of a blob
+    /// payload, stored little endian and padded with zeros.
+    ///
+    /// NOTE: blob data entries should *always* precede the event with
+    /// payload entry holding the blob's length, with any further
+    /// blob data entries in between.
+    #[must_use]
+    #[inline]
+    pub fn blob_data(chunk: &[u8]) -> (Self, Self) {
+        debug_assert!(chunk.len() <= size_of::<u32>());
+        let mut bytes = [0; size_of::<u32>()];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        Self::event_with_payload(EventId::EVENT_BLOB_DATA, u32::from_le_bytes(bytes))
+    }
+
+    /// Create a pair of `LogEntry`s indicating that the event
+    /// immediately preceding them was recorded `count` more times
+    /// in a row.
+    ///
+    /// NOTE: event repeated entries should *always* follow the
+    /// single event entry they're associated with.
+    #[must_use]
+    #[inline]
+    pub fn event_repeated(count: u32) -> (Self, Self) {
+        Self::event_with_payload(EventId::EVENT_PREVIOUS_EVENT_REPEATED, count)
+    }
+
     /// Create a pair of `LogEntry`s representing paired wall clock time.
     ///
     /// The first entry contains the `NanosecondsHighBits` of the
@@ -138,6 +181,24 @@ impl LogEntry {
         (self.0 & mask) == mask
     }
 
+    /// Determine if this entry is the first half of a wide payload high bits pair.
+    #[inline]
+    pub fn is_wide_payload_high_bits(self) -> bool {
+        self.0 == EventId::EVENT_WIDE_PAYLOAD_HIGH_BITS.get_raw() | EVENT_WITH_PAYLOAD_MASK
+    }
+
+    /// Determine if this entry is the first half of a blob data pair.
+    #[inline]
+    pub fn is_blob_data(self) -> bool {
+        self.0 == EventId::EVENT_BLOB_DATA.get_raw() | EVENT_WITH_PAYLOAD_MASK
+    }
+
+    /// Determine if this entry is the first half of an event repeated pair.
+    #[inline]
+    pub fn is_event_repeated(self) -> bool {
+        self.0 == EventId::EVENT_PREVIOUS_EVENT_REPEATED.get_raw() | EVENT_WITH_PAYLOAD_MASK
+    }
+
     /// Get the underlying value as a convenient primitive.
     #[inline]
     pub fn raw(self) -> u32 {
@@ -218,6 +279,46 @@ pub(crate) mod log_tests {
         assert_eq!(payload.0, 777);
     }
 
+    #[test]
+    fn event_repeated_is_well_represented() {
+        let (marker, count) = LogEntry::event_repeated(5);
+        assert!(marker.is_event_repeated());
+        assert!(marker.has_event_with_payload_bit_set());
+        assert_eq!(
+            marker.interpret_as_event_id(),
+            Some(EventId::EVENT_PREVIOUS_EVENT_REPEATED)
+        );
+        assert_eq!(count.0, 5);
+
+        let (ev, _) = LogEntry::event_with_payload(EventId::new(4).unwrap(), 5);
+        assert!(!ev.is_event_repeated());
+        assert!(!LogEntry::event(EventId::EVENT_PREVIOUS_EVENT_REPEATED).is_event_repeated());
+    }
+
+    #[test]
+    fn wide_payload_high_bits_are_well_represented() {
+        let (marker, high) = LogEntry::wide_payload_high_bits(0x1234_5678_9abc_def0);
+        assert!(marker.is_wide_payload_high_bits());
+        assert!(!marker.is_event_repeated());
+        assert!(marker.has_event_with_payload_bit_set());
+        assert_eq!(high.0, 0x1234_5678);
+
+        let (ev, _) = LogEntry::event_with_payload(EventId::new(4).unwrap(), 5);
+        assert!(!ev.is_wide_payload_high_bits());
+    }
+
+    #[test]
+    fn blob_data_is_well_represented() {
+        let (marker, data) = LogEntry::blob_data(&[1, 2, 3]);
+        assert!(marker.is_blob_data());
+        assert!(!marker.is_wide_payload_high_bits());
+        assert!(marker.has_event_with_payload_bit_set());
+        assert_eq!(data.0, 0x0003_0201);
+
+        let (ev, _) = LogEntry::event_with_payload(EventId::new(4).unwrap(), 5);
+        assert!(!ev.is_blob_data());
+    }
+
     #[test]
     fn wall_clock_time_are_well_represented() {
         let (high, low) = LogEntry::paired_wall_clock_time(Nanoseconds::new(1).unwrap());
@@ -228,23 +329,11 @@ pub(crate) mod log_tests {
         );
         assert_eq!(low.0, 1);
 
-        assert_eq!(
-            LogEntry(PAIRED_WALL_CLOCK_TIME_MASK).has_wall_clock_time_paired_bit_set(),
-            false
-        );
-        assert_eq!(
-            LogEntry(PAIRED_WALL_CLOCK_TIME_MASK).has_wall_clock_time_bits_set(),
-            false
-        );
-        assert_eq!(
-            LogEntry(WALL_CLOCK_TIME_MASK | PAIRED_WALL_CLOCK_TIME_MASK)
-                .has_wall_clock_time_paired_bit_set(),
-            true
-        );
-        assert_eq!(
-            LogEntry(WALL_CLOCK_TIME_MASK).has_wall_clock_time_bits_set(),
-            true
-        );
+        assert!(!LogEntry(PAIRED_WALL_CLOCK_TIME_MASK).has_wall_clock_time_paired_bit_set());
+        assert!(!LogEntry(PAIRED_WALL_CLOCK_TIME_MASK).has_wall_clock_time_bits_set());
+        assert!(LogEntry(WALL_CLOCK_TIME_MASK | PAIRED_WALL_CLOCK_TIME_MASK)
+            .has_wall_clock_time_paired_bit_set());
+        assert!(LogEntry(WALL_CLOCK_TIME_MASK).has_wall_clock_time_bits_set());
     }
 
     #[test]
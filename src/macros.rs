@@ -170,6 +170,124 @@ macro_rules! try_record_w_time {
     };
 }
 
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_priority](struct.ModalityProbe.html#method.record_event_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! record_w_priority {
+    ($probe:expr, $event:expr, $priority:expr) => {
+        $probe.record_event_with_priority($event, $priority)
+    };
+    ($probe:expr, $event:expr, $priority:expr, $desc_or_tags:expr) => {
+        $probe.record_event_with_priority($event, $priority)
+    };
+    ($probe:expr, $event:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.record_event_with_priority($event, $priority)
+    };
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_priority](struct.ModalityProbe.html#method.try_record_event_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! try_record_w_priority {
+    ($probe:expr, $event:expr, $priority:expr) => {
+        $probe.try_record_event_with_priority($event, $priority)
+    };
+    ($probe:expr, $event:expr, $priority:expr, $desc_or_tags:expr) => {
+        $probe.try_record_event_with_priority($event, $priority)
+    };
+    ($probe:expr, $event:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.try_record_event_with_priority($event, $priority)
+    };
+}
+
+/// Records the beginning of a span by calling
+/// [ModalityProbe::record_event](struct.ModalityProbe.html#method.record_event).
+///
+/// The event's name must end in `_BEGIN`, and the span is closed by
+/// the [span_end](macro.span_end.html) of the event with the same name
+/// ending in `_END` instead. The CLI pairs the two events up into a
+/// single span.
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! span_begin {
+    ($probe:expr, $event:expr) => {
+        $probe.record_event($event)
+    };
+    ($probe:expr, $event:expr, $desc_or_tags:expr) => {
+        $probe.record_event($event)
+    };
+    ($probe:expr, $event:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.record_event($event)
+    };
+}
+
+/// Records the end of a span by calling
+/// [ModalityProbe::record_event](struct.ModalityProbe.html#method.record_event).
+///
+/// The event's name must end in `_END`, see [span_begin](macro.span_begin.html).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! span_end {
+    ($probe:expr, $event:expr) => {
+        $probe.record_event($event)
+    };
+    ($probe:expr, $event:expr, $desc_or_tags:expr) => {
+        $probe.record_event($event)
+    };
+    ($probe:expr, $event:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.record_event($event)
+    };
+}
+
+/// Records the beginning of a span by calling
+/// [ModalityProbe::record_event_with_time](struct.ModalityProbe.html#method.record_event_with_time).
+///
+/// Spans whose beginning and end both carry a wall clock time are
+/// shown with their duration in nanoseconds by the CLI.
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! span_begin_w_time {
+    ($probe:expr, $event:expr, $time:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+    ($probe:expr, $event:expr, $time:expr, $desc_or_tags:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+    ($probe:expr, $event:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+}
+
+/// Records the end of a span by calling
+/// [ModalityProbe::record_event_with_time](struct.ModalityProbe.html#method.record_event_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export]
+macro_rules! span_end_w_time {
+    ($probe:expr, $event:expr, $time:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+    ($probe:expr, $event:expr, $time:expr, $desc_or_tags:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+    ($probe:expr, $event:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {
+        $probe.record_event_with_time($event, $time)
+    };
+}
+
 /// Convenience macro that calls
 /// [ModalityProbe::record_event_with_payload](struct.ModalityProbe.html#method.record_event_with_payload).
 ///
@@ -490,6 +608,126 @@ macro_rules! record_w_f32_w_time {
     }};
 }
 
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload](struct.ModalityProbe.html#method.record_event_with_wide_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u64 {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u64_w_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload](struct.ModalityProbe.html#method.record_event_with_wide_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i64 {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i64_w_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload](struct.ModalityProbe.html#method.record_event_with_wide_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_f64 {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide!($probe, $event, $payload)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_f64_w_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+}
+
 /// Convenience macro that calls
 /// [ModalityProbe::try_record_event_with_payload](struct.ModalityProbe.html#method.try_record_event_with_payload).
 ///
@@ -811,7 +1049,7 @@ macro_rules! try_record_w_f32_w_time {
 }
 
 /// Convenience macro that calls
-/// [ModalityProbe::record_event_with_payload](struct.ModalityProbe.html#method.record_event_with_payload).
+/// [ModalityProbe::try_record_event_with_wide_payload](struct.ModalityProbe.html#method.try_record_event_with_wide_payload).
 ///
 /// The optional description and tags string arguments are only used
 /// by the CLI and compile away.
@@ -820,20 +1058,38 @@ macro_rules! try_record_w_f32_w_time {
 /// format specifier token (`{}`) to have the payload value formatted
 /// in the description when displayed.
 #[macro_export(local_inner_macros)]
-macro_rules! expect {
-    ($probe:expr, $event:expr, $expression:expr) => {{
-        __record_with!($probe, $event, $expression)
+macro_rules! try_record_w_u64 {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
-    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr) => {{
-        __record_with!($probe, $event, $expression)
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
-    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
-        __record_with!($probe, $event, $expression)
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
 }
 
 /// Convenience macro that calls
-/// [ModalityProbe::try_record_event_with_payload](struct.ModalityProbe.html#method.try_record_event_with_payload).
+/// [ModalityProbe::try_record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_u64_w_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload](struct.ModalityProbe.html#method.try_record_event_with_wide_payload).
 ///
 /// The optional description and tags string arguments are only used
 /// by the CLI and compile away.
@@ -842,47 +1098,633 @@ macro_rules! expect {
 /// format specifier token (`{}`) to have the payload value formatted
 /// in the description when displayed.
 #[macro_export(local_inner_macros)]
-macro_rules! try_expect {
-    ($probe:expr, $event:expr, $expression:expr) => {{
-        __try_record_with!($probe, $event, $expression)
+macro_rules! try_record_w_i64 {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
-    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr) => {{
-        __try_record_with!($probe, $event, $expression)
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
-    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
-        __try_record_with!($probe, $event, $expression)
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
 }
 
-#[doc(hidden)]
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
 #[macro_export(local_inner_macros)]
-macro_rules! __record_with {
-    ($probe:expr, $event:expr, $payload:expr) => {{
-        __payload_as_u32_impls!();
-        $probe.record_event_with_payload($event, $payload.as_u32())
+macro_rules! try_record_w_i64_w_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
     }};
 }
 
-#[doc(hidden)]
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload](struct.ModalityProbe.html#method.try_record_event_with_wide_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
 #[macro_export(local_inner_macros)]
-macro_rules! __try_record_with {
+macro_rules! try_record_w_f64 {
     ($probe:expr, $event:expr, $payload:expr) => {{
-        __payload_as_u32_impls!();
-        $probe.try_record_event_with_payload($event, $payload.as_u32())
+        __try_record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide!($probe, $event, $payload)
     }};
 }
 
-#[doc(hidden)]
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_time).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
 #[macro_export(local_inner_macros)]
-macro_rules! __record_with_time {
+macro_rules! try_record_w_f64_w_time {
     ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
-        __payload_as_u32_impls!();
-        $probe.record_event_with_payload_with_time($event, $payload.as_u32(), $time)
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $time:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_time!($probe, $event, $payload, $time)
     }};
 }
 
-#[doc(hidden)]
-#[macro_export(local_inner_macros)]
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i8_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u8_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i16_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u16_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_bool_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_f32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_u64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_i64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! record_w_f64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_i8_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_u8_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_i16_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_u16_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_i32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_u32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_bool_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_f32_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_u64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_i64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_priority).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_record_w_f64_w_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with_wide_priority!($probe, $event, $payload, $priority)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::record_event_with_payload](struct.ModalityProbe.html#method.record_event_with_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! expect {
+    ($probe:expr, $event:expr, $expression:expr) => {{
+        __record_with!($probe, $event, $expression)
+    }};
+    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr) => {{
+        __record_with!($probe, $event, $expression)
+    }};
+    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __record_with!($probe, $event, $expression)
+    }};
+}
+
+/// Convenience macro that calls
+/// [ModalityProbe::try_record_event_with_payload](struct.ModalityProbe.html#method.try_record_event_with_payload).
+///
+/// The optional description and tags string arguments are only used
+/// by the CLI and compile away.
+///
+/// Event with payload descriptions may additionally use a single
+/// format specifier token (`{}`) to have the payload value formatted
+/// in the description when displayed.
+#[macro_export(local_inner_macros)]
+macro_rules! try_expect {
+    ($probe:expr, $event:expr, $expression:expr) => {{
+        __try_record_with!($probe, $event, $expression)
+    }};
+    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr) => {{
+        __try_record_with!($probe, $event, $expression)
+    }};
+    ($probe:expr, $event:expr, $expression:expr, $desc_or_tags:expr, $tags_or_desc:expr) => {{
+        __try_record_with!($probe, $event, $expression)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __payload_as_u32_impls!();
+        $probe.record_event_with_payload($event, $payload.as_u32())
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __try_record_with {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __payload_as_u32_impls!();
+        $probe.try_record_event_with_payload($event, $payload.as_u32())
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __payload_as_u32_impls!();
+        $probe.record_event_with_payload_with_time($event, $payload.as_u32(), $time)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
 macro_rules! __try_record_with_time {
     ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
         __payload_as_u32_impls!();
@@ -890,6 +1732,82 @@ macro_rules! __try_record_with_time {
     }};
 }
 
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with_wide {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.record_event_with_wide_payload($event, $payload.as_u64())
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __try_record_with_wide {
+    ($probe:expr, $event:expr, $payload:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.try_record_event_with_wide_payload($event, $payload.as_u64())
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with_wide_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.record_event_with_wide_payload_with_time($event, $payload.as_u64(), $time)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __try_record_with_wide_time {
+    ($probe:expr, $event:expr, $payload:expr, $time:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.try_record_event_with_wide_payload_with_time($event, $payload.as_u64(), $time)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __payload_as_u32_impls!();
+        $probe.record_event_with_payload_with_priority($event, $payload.as_u32(), $priority)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __try_record_with_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __payload_as_u32_impls!();
+        $probe.try_record_event_with_payload_with_priority($event, $payload.as_u32(), $priority)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __record_with_wide_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.record_event_with_wide_payload_with_priority($event, $payload.as_u64(), $priority)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __try_record_with_wide_priority {
+    ($probe:expr, $event:expr, $payload:expr, $priority:expr) => {{
+        __payload_as_u64_impls!();
+        $probe.try_record_event_with_wide_payload_with_priority(
+            $event,
+            $payload.as_u64(),
+            $priority,
+        )
+    }};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __payload_as_u32_impls {
@@ -940,6 +1858,31 @@ macro_rules! __payload_as_u32_impls {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __payload_as_u64_impls {
+    () => {
+        trait PayloadAsU64 {
+            fn as_u64(&self) -> u64;
+        }
+        impl PayloadAsU64 for i64 {
+            fn as_u64(&self) -> u64 {
+                *self as u64
+            }
+        }
+        impl PayloadAsU64 for u64 {
+            fn as_u64(&self) -> u64 {
+                *self
+            }
+        }
+        impl PayloadAsU64 for f64 {
+            fn as_u64(&self) -> u64 {
+                self.to_bits()
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -970,6 +1913,40 @@ mod tests {
             "desc"
         );
 
+        record_w_priority!(probe, EventId::new(EVENT_D).unwrap(), 2);
+        record_w_priority!(probe, EventId::new(EVENT_D).unwrap(), 2, "desc");
+        record_w_priority!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            2,
+            tags!("some-tag", "another tag"),
+            "desc"
+        );
+
+        const SPAN_BEGIN: u32 = 2;
+        const SPAN_END: u32 = 3;
+        span_begin!(probe, EventId::new(SPAN_BEGIN).unwrap());
+        span_end!(probe, EventId::new(SPAN_END).unwrap(), "desc");
+        span_begin!(
+            probe,
+            EventId::new(SPAN_BEGIN).unwrap(),
+            tags!("some-tag"),
+            "desc"
+        );
+        span_end!(probe, EventId::new(SPAN_END).unwrap());
+        span_begin_w_time!(
+            probe,
+            EventId::new(SPAN_BEGIN).unwrap(),
+            Nanoseconds::new(1).unwrap()
+        );
+        span_end_w_time!(
+            probe,
+            EventId::new(SPAN_END).unwrap(),
+            Nanoseconds::new(2).unwrap(),
+            tags!("some-tag"),
+            "desc"
+        );
+
         record_time!(probe, Nanoseconds::new(1).unwrap());
         record_time!(probe, Nanoseconds::new(2).unwrap(), "desc");
         record_time!(
@@ -1077,6 +2054,61 @@ mod tests {
             tags!("some-tag"),
             "desc"
         );
+        record_w_u64!(probe, EventId::new(EVENT_D).unwrap(), 1_u64 << 40, "desc");
+        record_w_u64_w_time!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            u64::MAX,
+            Nanoseconds::new(2).unwrap(),
+            tags!("some-tag")
+        );
+        record_w_i64!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            -1_i64,
+            tags!("some-tag"),
+            "desc"
+        );
+        record_w_i64_w_time!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            i64::MIN,
+            Nanoseconds::new(2).unwrap()
+        );
+        record_w_f64!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            0.0_f64,
+            tags!("some-tag"),
+            "desc"
+        );
+        record_w_f64_w_time!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            1.5_f64,
+            Nanoseconds::new(2).unwrap(),
+            tags!("some-tag"),
+            "desc"
+        );
+        record_w_u32_w_priority!(probe, EventId::new(EVENT_D).unwrap(), 1_u32, 2);
+        record_w_i8_w_priority!(probe, EventId::new(EVENT_D).unwrap(), -1_i8, 2, "desc");
+        record_w_f32_w_priority!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            1.5_f32,
+            2,
+            tags!("some-tag"),
+            "desc"
+        );
+        record_w_u64_w_priority!(probe, EventId::new(EVENT_D).unwrap(), u64::MAX, 2);
+        record_w_f64_w_priority!(
+            probe,
+            EventId::new(EVENT_D).unwrap(),
+            1.5_f64,
+            2,
+            tags!("some-tag"),
+            "desc"
+        );
 
         record_w_time!(
             probe,
@@ -1102,6 +2134,9 @@ mod tests {
         try_record!(probe, EVENT_D).unwrap();
         try_record!(probe, EVENT_D, "desc").unwrap();
         try_record!(probe, EVENT_D, tags!("some-tag"), "desc").unwrap();
+        try_record_w_priority!(probe, EVENT_D, 2).unwrap();
+        try_record_w_priority!(probe, EVENT_D, 2, "desc").unwrap();
+        try_record_w_priority!(probe, EVENT_D, 2, tags!("some-tag"), "desc").unwrap();
 
         const TIME: u64 = 1;
         try_record_time!(probe, TIME).unwrap();
@@ -1128,6 +2163,15 @@ mod tests {
         try_record_w_bool_w_time!(probe, EVENT_D, false, TIME, tags!("some-tag"), "desc").unwrap();
         try_record_w_f32!(probe, EVENT_D, 0.0, tags!("some-tag"), "desc").unwrap();
         try_record_w_f32_w_time!(probe, EVENT_D, 0.0, TIME, tags!("some-tag"), "desc").unwrap();
+        try_record_w_u64!(probe, EVENT_D, 0_u64, tags!("some-tag"), "desc").unwrap();
+        try_record_w_u64_w_time!(probe, EVENT_D, u64::MAX, TIME, "desc").unwrap();
+        try_record_w_i64!(probe, EVENT_D, -1_i64).unwrap();
+        try_record_w_i64_w_time!(probe, EVENT_D, 0_i64, TIME, tags!("some-tag"), "desc").unwrap();
+        try_record_w_f64!(probe, EVENT_D, 0.0_f64, tags!("some-tag"), "desc").unwrap();
+        try_record_w_f64_w_time!(probe, EVENT_D, 0.0_f64, TIME, tags!("some-tag"), "desc").unwrap();
+        try_record_w_u32_w_priority!(probe, EVENT_D, 1_u32, 2).unwrap();
+        try_record_w_bool_w_priority!(probe, EVENT_D, true, 2, "desc").unwrap();
+        try_record_w_i64_w_priority!(probe, EVENT_D, -1_i64, 2, tags!("some-tag"), "desc").unwrap();
 
         expect!(probe, EventId::new(EVENT_D).unwrap(), 1 == 0);
         expect!(probe, EventId::new(EVENT_D).unwrap(), 1_i8 == 0_i8, "desc");
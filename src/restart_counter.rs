@@ -19,7 +19,7 @@ pub trait RestartCounter {
 
 /// Either:
 /// * The user-supplied restart persistence counter failed
-/// to produce the next sequence id.
+///   to produce the next sequence id.
 /// * No restart persistence strategy was provided by the user
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct RestartSequenceIdUnavailable;
@@ -48,6 +48,11 @@ pub struct CRestartCounterProvider {
     pub state: *mut core::ffi::c_void,
 }
 
+// The C interface makes no promises about which context it's called
+// from; it's up to the C side to keep `state` usable from any context
+// that records to the probe.
+unsafe impl Send for CRestartCounterProvider {}
+
 impl RestartCounter for CRestartCounterProvider {
     fn next_sequence_id(&mut self, probe_id: ProbeId) -> Result<u16, RestartSequenceIdUnavailable> {
         let mut out_sequence_number: u16 = 0;
@@ -68,7 +73,7 @@ impl RestartCounter for CRestartCounterProvider {
 /// A persistent restart sequence counter provider backed by a Rust implementation
 pub struct RustRestartCounterProvider<'a> {
     /// Rust interface for retrieving the next persistent sequence number
-    pub iface: &'a mut dyn RestartCounter,
+    pub iface: &'a mut (dyn RestartCounter + Send),
 }
 
 /// A persistent restart sequence counter provider
@@ -91,8 +96,8 @@ impl<'a> RestartCounterProvider<'a> {
     }
 }
 
-impl<'a> From<&'a mut dyn RestartCounter> for RestartCounterProvider<'a> {
-    fn from(r: &'a mut dyn RestartCounter) -> Self {
+impl<'a> From<&'a mut (dyn RestartCounter + Send)> for RestartCounterProvider<'a> {
+    fn from(r: &'a mut (dyn RestartCounter + Send)) -> Self {
         RestartCounterProvider::Rust(RustRestartCounterProvider { iface: r })
     }
 }
@@ -127,7 +132,7 @@ mod tests {
     #[test]
     fn no_restart_tracking() {
         let mut rsc = RestartCounterProvider::NoRestartTracking;
-        assert_eq!(rsc.is_tracking_restarts(), false);
+        assert!(!rsc.is_tracking_restarts());
         assert_eq!(
             rsc.next_sequence_id(ProbeId::new(1).unwrap()),
             Err(RestartSequenceIdUnavailable)
@@ -0,0 +1,721 @@
+//! A probe handle which can be recorded to from several execution
+//! contexts (e.g. threads and interrupt handlers) through `&self`.
+//! It is only present if the `"shared-probe"` feature is set.
+use crate::{
+    CausalSnapshot, EventId, InvalidEventId, MergeError, ModalityProbe, Nanoseconds, Probe,
+    ProduceError, ReportError, WithTimeError,
+};
+use core::{
+    cell::{Cell, UnsafeCell},
+    num::NonZeroUsize,
+};
+
+/// A mechanism which provides exclusive access to a probe between
+/// all of the execution contexts that use it, e.g. by masking
+/// interrupts or by taking a lock.
+///
+/// # Safety
+///
+/// While `with` runs `f`, no other execution context may be running
+/// `f` from a call to `with` on the same critical section. `SharedProbe`
+/// relies on this to hand out a `&mut` to its probe. The same context
+/// may enter the critical section again from within `f`, which
+/// `SharedProbe` detects on its own.
+pub unsafe trait CriticalSection {
+    /// Run `f` while no other context which uses this critical
+    /// section can run its own `f`.
+    fn with<R>(&self, f: impl FnOnce() -> R) -> R;
+}
+
+/// C function type for entering or leaving a critical section
+#[allow(non_camel_case_types)]
+pub type critical_section_fn = extern "C" fn(state: *mut core::ffi::c_void);
+
+/// A critical section backed by a pair of C lock/unlock callbacks
+#[repr(C)]
+pub struct CCriticalSection {
+    /// C interface for entering the critical section
+    pub lock: critical_section_fn,
+    /// C interface for leaving the critical section
+    pub unlock: critical_section_fn,
+    /// User's state provided to the lock and unlock function calls
+    pub state: *mut core::ffi::c_void,
+}
+
+// The lock and unlock callbacks are trusted to provide mutual
+// exclusion; a `CCriticalSection` isn't `Sync`, so only C code can
+// share one between contexts.
+unsafe impl CriticalSection for CCriticalSection {
+    fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+        /// Leaves the critical section when dropped, even if `f` panics
+        struct Unlock<'a>(&'a CCriticalSection);
+
+        impl Drop for Unlock<'_> {
+            fn drop(&mut self) {
+                (self.0.unlock)(self.0.state);
+            }
+        }
+
+        (self.lock)(self.state);
+        let _unlock = Unlock(self);
+        f()
+    }
+}
+
+/// A critical section backed by a `std::sync::Mutex`, for use on hosts.
+/// It is only present if the `"std"` feature is set.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct StdCriticalSection(std::sync::Mutex<()>);
+
+#[cfg(feature = "std")]
+unsafe impl CriticalSection for StdCriticalSection {
+    fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+        // The mutex guards no data of its own, so poisoning is ignored
+        let _guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        f()
+    }
+}
+
+/// A `ModalityProbe` which implements `Probe` via `&self`, by entering
+/// a critical section around every operation.
+///
+/// The Rust recording macros can be used with a shared probe by
+/// passing them a reference, e.g. `record!(&shared, EVENT_A)`.
+pub struct SharedProbe<'a, C: CriticalSection> {
+    critical_section: C,
+    probe: UnsafeCell<&'a mut ModalityProbe<'a>>,
+    /// Guards against a probe being accessed re-entrantly from within
+    /// `with`, which the critical section alone can't prevent
+    in_use: Cell<bool>,
+}
+
+// All access to the probe goes through the critical section, which
+// serializes it between contexts. Each access may come from a different
+// context, so the probe itself must be safe to send between them.
+unsafe impl<'a, C: CriticalSection + Sync> Sync for SharedProbe<'a, C> where ModalityProbe<'a>: Send {}
+
+/// Clears a `SharedProbe`'s in use flag when dropped, even if the
+/// function given access to the probe panics
+struct InUse<'a>(&'a Cell<bool>);
+
+impl Drop for InUse<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl<'a, C: CriticalSection> SharedProbe<'a, C> {
+    /// Wrap `probe` so that it's only accessed from within `critical_section`
+    pub fn new(probe: &'a mut ModalityProbe<'a>, critical_section: C) -> Self {
+        SharedProbe {
+            critical_section,
+            probe: UnsafeCell::new(probe),
+            in_use: Cell::new(false),
+        }
+    }
+
+    /// Run `f` with exclusive access to the probe, e.g. to change its
+    /// settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within `f`.
+    pub fn with<R>(&self, f: impl FnOnce(&mut ModalityProbe<'a>) -> R) -> R {
+        self.critical_section.with(|| {
+            assert!(
+                !self.in_use.replace(true),
+                "SharedProbe accessed re-entrantly"
+            );
+            let _in_use = InUse(&self.in_use);
+            // Safe because the critical section excludes other contexts
+            // and `in_use` excludes this one
+            f(unsafe { &mut **self.probe.get() })
+        })
+    }
+
+    /// Unwrap the probe
+    pub fn into_inner(self) -> &'a mut ModalityProbe<'a> {
+        self.probe.into_inner()
+    }
+
+    /// See [Probe::record_time](trait.Probe.html#tymethod.record_time).
+    #[inline]
+    pub fn record_time(&self, time: Nanoseconds) {
+        self.with(|p| p.record_time(time))
+    }
+
+    /// See [Probe::record_event](trait.Probe.html#tymethod.record_event).
+    #[inline]
+    pub fn record_event(&self, event_id: EventId) {
+        self.with(|p| p.record_event(event_id))
+    }
+
+    /// See [Probe::record_event_with_priority](trait.Probe.html#method.record_event_with_priority).
+    #[inline]
+    pub fn record_event_with_priority(&self, event_id: EventId, priority: u32) {
+        self.with(|p| p.record_event_with_priority(event_id, priority))
+    }
+
+    /// See [Probe::record_event_with_time](trait.Probe.html#tymethod.record_event_with_time).
+    #[inline]
+    pub fn record_event_with_time(&self, event_id: EventId, time: Nanoseconds) {
+        self.with(|p| p.record_event_with_time(event_id, time))
+    }
+
+    /// See [Probe::record_event_with_time_with_priority](trait.Probe.html#method.record_event_with_time_with_priority).
+    #[inline]
+    pub fn record_event_with_time_with_priority(
+        &self,
+        event_id: EventId,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.with(|p| p.record_event_with_time_with_priority(event_id, time, priority))
+    }
+
+    /// See [Probe::record_event_with_payload](trait.Probe.html#tymethod.record_event_with_payload).
+    #[inline]
+    pub fn record_event_with_payload(&self, event_id: EventId, payload: u32) {
+        self.with(|p| p.record_event_with_payload(event_id, payload))
+    }
+
+    /// See [Probe::record_event_with_payload_with_priority](trait.Probe.html#method.record_event_with_payload_with_priority).
+    #[inline]
+    pub fn record_event_with_payload_with_priority(
+        &self,
+        event_id: EventId,
+        payload: u32,
+        priority: u32,
+    ) {
+        self.with(|p| p.record_event_with_payload_with_priority(event_id, payload, priority))
+    }
+
+    /// See [Probe::record_event_with_payload_with_time](trait.Probe.html#tymethod.record_event_with_payload_with_time).
+    #[inline]
+    pub fn record_event_with_payload_with_time(
+        &self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+    ) {
+        self.with(|p| p.record_event_with_payload_with_time(event_id, payload, time))
+    }
+
+    /// See [Probe::record_event_with_payload_with_time_with_priority](trait.Probe.html#method.record_event_with_payload_with_time_with_priority).
+    #[inline]
+    pub fn record_event_with_payload_with_time_with_priority(
+        &self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.with(|p| {
+            p.record_event_with_payload_with_time_with_priority(event_id, payload, time, priority)
+        })
+    }
+
+    /// See [Probe::record_event_with_wide_payload](trait.Probe.html#method.record_event_with_wide_payload).
+    #[inline]
+    pub fn record_event_with_wide_payload(&self, event_id: EventId, payload: u64) {
+        self.with(|p| p.record_event_with_wide_payload(event_id, payload))
+    }
+
+    /// See [Probe::record_event_with_wide_payload_with_priority](trait.Probe.html#method.record_event_with_wide_payload_with_priority).
+    #[inline]
+    pub fn record_event_with_wide_payload_with_priority(
+        &self,
+        event_id: EventId,
+        payload: u64,
+        priority: u32,
+    ) {
+        self.with(|p| p.record_event_with_wide_payload_with_priority(event_id, payload, priority))
+    }
+
+    /// See [Probe::record_event_with_wide_payload_with_time](trait.Probe.html#method.record_event_with_wide_payload_with_time).
+    #[inline]
+    pub fn record_event_with_wide_payload_with_time(
+        &self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+    ) {
+        self.with(|p| p.record_event_with_wide_payload_with_time(event_id, payload, time))
+    }
+
+    /// See [Probe::record_event_with_wide_payload_with_time_with_priority](trait.Probe.html#method.record_event_with_wide_payload_with_time_with_priority).
+    #[inline]
+    pub fn record_event_with_wide_payload_with_time_with_priority(
+        &self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        self.with(|p| {
+            p.record_event_with_wide_payload_with_time_with_priority(
+                event_id, payload, time, priority,
+            )
+        })
+    }
+
+    /// See [Probe::record_event_with_blob](trait.Probe.html#tymethod.record_event_with_blob).
+    #[inline]
+    pub fn record_event_with_blob(&self, event_id: EventId, blob: &[u8]) {
+        self.with(|p| p.record_event_with_blob(event_id, blob))
+    }
+
+    /// See [Probe::record_event_with_blob_with_priority](trait.Probe.html#method.record_event_with_blob_with_priority).
+    #[inline]
+    pub fn record_event_with_blob_with_priority(
+        &self,
+        event_id: EventId,
+        blob: &[u8],
+        priority: u32,
+    ) {
+        self.with(|p| p.record_event_with_blob_with_priority(event_id, blob, priority))
+    }
+
+    /// See [ModalityProbe::try_record_time](struct.ModalityProbe.html#method.try_record_time).
+    #[inline]
+    pub fn try_record_time(&self, time_ns: u64) -> Result<(), crate::InvalidWallClockTime> {
+        self.with(|p| p.try_record_time(time_ns))
+    }
+
+    /// See [ModalityProbe::try_record_event](struct.ModalityProbe.html#method.try_record_event).
+    #[inline]
+    pub fn try_record_event(&self, event_id: u32) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event(event_id))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_priority](struct.ModalityProbe.html#method.try_record_event_with_priority).
+    #[inline]
+    pub fn try_record_event_with_priority(
+        &self,
+        event_id: u32,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_priority(event_id, priority))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_time](struct.ModalityProbe.html#method.try_record_event_with_time).
+    #[inline]
+    pub fn try_record_event_with_time(
+        &self,
+        event_id: u32,
+        time_ns: u64,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| p.try_record_event_with_time(event_id, time_ns))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_time_with_priority](struct.ModalityProbe.html#method.try_record_event_with_time_with_priority).
+    #[inline]
+    pub fn try_record_event_with_time_with_priority(
+        &self,
+        event_id: u32,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| p.try_record_event_with_time_with_priority(event_id, time_ns, priority))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_payload](struct.ModalityProbe.html#method.try_record_event_with_payload).
+    #[inline]
+    pub fn try_record_event_with_payload(
+        &self,
+        event_id: u32,
+        payload: u32,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_payload(event_id, payload))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_priority).
+    #[inline]
+    pub fn try_record_event_with_payload_with_priority(
+        &self,
+        event_id: u32,
+        payload: u32,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_payload_with_priority(event_id, payload, priority))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_payload_with_time](struct.ModalityProbe.html#method.try_record_event_with_payload_with_time).
+    #[inline]
+    pub fn try_record_event_with_payload_with_time(
+        &self,
+        event_id: u32,
+        payload: u32,
+        time_ns: u64,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| p.try_record_event_with_payload_with_time(event_id, payload, time_ns))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_payload_with_time_with_priority](struct.ModalityProbe.html#method.try_record_event_with_payload_with_time_with_priority).
+    #[inline]
+    pub fn try_record_event_with_payload_with_time_with_priority(
+        &self,
+        event_id: u32,
+        payload: u32,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| {
+            p.try_record_event_with_payload_with_time_with_priority(
+                event_id, payload, time_ns, priority,
+            )
+        })
+    }
+
+    /// See [ModalityProbe::try_record_event_with_wide_payload](struct.ModalityProbe.html#method.try_record_event_with_wide_payload).
+    #[inline]
+    pub fn try_record_event_with_wide_payload(
+        &self,
+        event_id: u32,
+        payload: u64,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_wide_payload(event_id, payload))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_wide_payload_with_priority](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_priority).
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_priority(
+        &self,
+        event_id: u32,
+        payload: u64,
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| {
+            p.try_record_event_with_wide_payload_with_priority(event_id, payload, priority)
+        })
+    }
+
+    /// See [ModalityProbe::try_record_event_with_wide_payload_with_time](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_time).
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_time(
+        &self,
+        event_id: u32,
+        payload: u64,
+        time_ns: u64,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| p.try_record_event_with_wide_payload_with_time(event_id, payload, time_ns))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_wide_payload_with_time_with_priority](struct.ModalityProbe.html#method.try_record_event_with_wide_payload_with_time_with_priority).
+    #[inline]
+    pub fn try_record_event_with_wide_payload_with_time_with_priority(
+        &self,
+        event_id: u32,
+        payload: u64,
+        time_ns: u64,
+        priority: u32,
+    ) -> Result<(), WithTimeError> {
+        self.with(|p| {
+            p.try_record_event_with_wide_payload_with_time_with_priority(
+                event_id, payload, time_ns, priority,
+            )
+        })
+    }
+
+    /// See [ModalityProbe::try_record_event_with_blob](struct.ModalityProbe.html#method.try_record_event_with_blob).
+    #[inline]
+    pub fn try_record_event_with_blob(
+        &self,
+        event_id: u32,
+        blob: &[u8],
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_blob(event_id, blob))
+    }
+
+    /// See [ModalityProbe::try_record_event_with_blob_with_priority](struct.ModalityProbe.html#method.try_record_event_with_blob_with_priority).
+    #[inline]
+    pub fn try_record_event_with_blob_with_priority(
+        &self,
+        event_id: u32,
+        blob: &[u8],
+        priority: u32,
+    ) -> Result<(), InvalidEventId> {
+        self.with(|p| p.try_record_event_with_blob_with_priority(event_id, blob, priority))
+    }
+}
+
+impl<'a, 'b, C: CriticalSection> Probe for &'b SharedProbe<'a, C> {
+    #[inline]
+    fn record_time(&mut self, time: Nanoseconds) {
+        SharedProbe::record_time(self, time)
+    }
+
+    #[inline]
+    fn record_event(&mut self, event_id: EventId) {
+        SharedProbe::record_event(self, event_id)
+    }
+
+    #[inline]
+    fn record_event_with_priority(&mut self, event_id: EventId, priority: u32) {
+        SharedProbe::record_event_with_priority(self, event_id, priority)
+    }
+
+    #[inline]
+    fn record_event_with_time(&mut self, event_id: EventId, time: Nanoseconds) {
+        SharedProbe::record_event_with_time(self, event_id, time)
+    }
+
+    #[inline]
+    fn record_event_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_time_with_priority(self, event_id, time, priority)
+    }
+
+    #[inline]
+    fn record_event_with_payload(&mut self, event_id: EventId, payload: u32) {
+        SharedProbe::record_event_with_payload(self, event_id, payload)
+    }
+
+    #[inline]
+    fn record_event_with_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_payload_with_priority(self, event_id, payload, priority)
+    }
+
+    #[inline]
+    fn record_event_with_payload_with_time(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+    ) {
+        SharedProbe::record_event_with_payload_with_time(self, event_id, payload, time)
+    }
+
+    #[inline]
+    fn record_event_with_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u32,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_payload_with_time_with_priority(
+            self, event_id, payload, time, priority,
+        )
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload(&mut self, event_id: EventId, payload: u64) {
+        SharedProbe::record_event_with_wide_payload(self, event_id, payload)
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_wide_payload_with_priority(self, event_id, payload, priority)
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_time(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+    ) {
+        SharedProbe::record_event_with_wide_payload_with_time(self, event_id, payload, time)
+    }
+
+    #[inline]
+    fn record_event_with_wide_payload_with_time_with_priority(
+        &mut self,
+        event_id: EventId,
+        payload: u64,
+        time: Nanoseconds,
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_wide_payload_with_time_with_priority(
+            self, event_id, payload, time, priority,
+        )
+    }
+
+    #[inline]
+    fn record_event_with_blob(&mut self, event_id: EventId, blob: &[u8]) {
+        SharedProbe::record_event_with_blob(self, event_id, blob)
+    }
+
+    #[inline]
+    fn record_event_with_blob_with_priority(
+        &mut self,
+        event_id: EventId,
+        blob: &[u8],
+        priority: u32,
+    ) {
+        SharedProbe::record_event_with_blob_with_priority(self, event_id, blob, priority)
+    }
+
+    #[inline]
+    fn produce_snapshot(&mut self) -> CausalSnapshot {
+        self.with(|p| p.produce_snapshot())
+    }
+
+    #[inline]
+    fn produce_snapshot_with_time(&mut self, time: Nanoseconds) -> CausalSnapshot {
+        self.with(|p| p.produce_snapshot_with_time(time))
+    }
+
+    #[inline]
+    fn produce_snapshot_bytes(&mut self, destination: &mut [u8]) -> Result<usize, ProduceError> {
+        self.with(|p| p.produce_snapshot_bytes(destination))
+    }
+
+    #[inline]
+    fn produce_snapshot_bytes_with_time(
+        &mut self,
+        time: Nanoseconds,
+        destination: &mut [u8],
+    ) -> Result<usize, ProduceError> {
+        self.with(|p| p.produce_snapshot_bytes_with_time(time, destination))
+    }
+
+    #[inline]
+    fn merge_snapshot(&mut self, external_history: &CausalSnapshot) -> Result<(), MergeError> {
+        self.with(|p| p.merge_snapshot(external_history))
+    }
+
+    #[inline]
+    fn merge_snapshot_with_time(
+        &mut self,
+        external_history: &CausalSnapshot,
+        time: Nanoseconds,
+    ) -> Result<(), MergeError> {
+        self.with(|p| p.merge_snapshot_with_time(external_history, time))
+    }
+
+    #[inline]
+    fn merge_snapshot_bytes(&mut self, source: &[u8]) -> Result<(), MergeError> {
+        self.with(|p| p.merge_snapshot_bytes(source))
+    }
+
+    #[inline]
+    fn merge_snapshot_bytes_with_time(
+        &mut self,
+        source: &[u8],
+        time: Nanoseconds,
+    ) -> Result<(), MergeError> {
+        self.with(|p| p.merge_snapshot_bytes_with_time(source, time))
+    }
+
+    #[inline]
+    fn report(&mut self, destination: &mut [u8]) -> Result<Option<NonZeroUsize>, ReportError> {
+        self.with(|p| p.report(destination))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{
+        record, try_record, NanosecondResolution, ProbeId, RestartCounterProvider, WallClockId,
+    };
+    use core::mem::MaybeUninit;
+
+    #[test]
+    fn records_from_several_threads() {
+        let mut storage = vec![MaybeUninit::new(0u8); 4096];
+        let mut probe = ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let shared = SharedProbe::new(&mut probe, StdCriticalSection::default());
+        let start = shared.with(|p| p.now().event_count);
+
+        std::thread::scope(|s| {
+            for t in 1..=4 {
+                let shared = &shared;
+                s.spawn(move || {
+                    for _ in 0..10 {
+                        record!(shared, EventId::new(t).unwrap());
+                        try_record!(shared, t).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(shared.with(|p| p.now().event_count), start + 80);
+        let mut report = [0u8; 1024];
+        assert!((&shared).report(&mut report).unwrap().is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "re-entrantly")]
+    fn re_entrant_access_panics() {
+        let mut storage = vec![MaybeUninit::new(0u8); 1024];
+        let mut probe = ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        // Like masking interrupts, this critical section may be nested
+        struct Reentrant;
+        unsafe impl CriticalSection for Reentrant {
+            fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+                f()
+            }
+        }
+        let shared = SharedProbe::new(&mut probe, Reentrant);
+        shared.with(|_| shared.try_record_event(1).unwrap());
+    }
+
+    #[test]
+    fn panics_leave_the_probe_usable() {
+        use std::sync::atomic::{AtomicIsize, Ordering};
+
+        extern "C" fn lock(state: *mut core::ffi::c_void) {
+            unsafe { &*(state as *const AtomicIsize) }.fetch_add(1, Ordering::SeqCst);
+        }
+        extern "C" fn unlock(state: *mut core::ffi::c_void) {
+            unsafe { &*(state as *const AtomicIsize) }.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        let mut storage = vec![MaybeUninit::new(0u8); 1024];
+        let mut probe = ModalityProbe::new_with_storage(
+            &mut storage,
+            ProbeId::new(1).unwrap(),
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )
+        .unwrap();
+        let depth = AtomicIsize::new(0);
+        let cs = CCriticalSection {
+            lock,
+            unlock,
+            state: &depth as *const AtomicIsize as *mut core::ffi::c_void,
+        };
+        let shared = SharedProbe::new(&mut probe, cs);
+
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            shared.with(|_| panic!("recording failed"))
+        }));
+        assert!(r.is_err());
+        assert_eq!(depth.load(Ordering::SeqCst), 0);
+        shared.try_record_event(1).unwrap();
+        assert_eq!(depth.load(Ordering::SeqCst), 0);
+    }
+}
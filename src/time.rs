@@ -236,21 +236,21 @@ mod tests {
 
     #[test]
     fn invalid() {
-        assert_eq!(Nanoseconds::new(core::u64::MAX), None);
-        assert_eq!(Nanoseconds::new(core::u64::MAX >> 1), None);
-        assert_eq!(Nanoseconds::new(core::u64::MAX >> 2), None);
+        assert_eq!(Nanoseconds::new(u64::MAX), None);
+        assert_eq!(Nanoseconds::new(u64::MAX >> 1), None);
+        assert_eq!(Nanoseconds::new(u64::MAX >> 2), None);
 
-        let low_bits = NanosecondsLowBits(core::u32::MAX.to_le_bytes());
-        let high_bits = NanosecondsHighBits(core::u32::MAX.to_le_bytes());
+        let low_bits = NanosecondsLowBits(u32::MAX.to_le_bytes());
+        let high_bits = NanosecondsHighBits(u32::MAX.to_le_bytes());
         assert_eq!(None, Nanoseconds::from_parts(low_bits, high_bits));
     }
 
     #[test]
     fn truncation() {
-        assert_eq!(Nanoseconds::MAX, Nanoseconds::new_truncate(core::u64::MAX));
+        assert_eq!(Nanoseconds::MAX, Nanoseconds::new_truncate(u64::MAX));
 
-        let low_bits = NanosecondsLowBits(core::u32::MAX.to_le_bytes());
-        let high_bits = NanosecondsHighBits(core::u32::MAX.to_le_bytes());
+        let low_bits = NanosecondsLowBits(u32::MAX.to_le_bytes());
+        let high_bits = NanosecondsHighBits(u32::MAX.to_le_bytes());
         assert_eq!(
             Nanoseconds::MAX,
             Nanoseconds::from_parts_truncate(low_bits, high_bits)
@@ -263,10 +263,10 @@ mod tests {
             let t = Nanoseconds::new(raw_time_in).unwrap();
             prop_assert_eq!(t.get(), raw_time_in);
             let (low_bits, high_bits) = t.split();
-            prop_assert_eq!(u32::from_le_bytes(low_bits.0), (raw_time_in & core::u32::MAX as u64) as u32);
+            prop_assert_eq!(u32::from_le_bytes(low_bits.0), (raw_time_in & u32::MAX as u64) as u32);
             prop_assert_eq!(
                 u32::from_le_bytes(high_bits.0),
-                (raw_time_in >> 32 & core::u32::MAX as u64) as u32
+                (raw_time_in >> 32 & u32::MAX as u64) as u32
             );
             let t_out = Nanoseconds::from_parts(low_bits, high_bits).unwrap();
             prop_assert_eq!(t, t_out);
@@ -1,7 +1,8 @@
 //! A wire protocol for representing Modality probe causal snapshots
 
 use crate::{
-    wire::le_bytes, CausalSnapshot, InvalidProbeId, LogicalClock, ProbeEpoch, ProbeId, ProbeTicks,
+    wire::{checksum::crc32, le_bytes},
+    CausalSnapshot, InvalidProbeId, LogicalClock, MergeError, ProbeEpoch, ProbeId, ProbeTicks,
 };
 use core::convert::TryFrom;
 use core::mem::size_of;
@@ -60,10 +61,16 @@ mod field {
     /// LogicalClock.epoch
     pub const EPOCH: Field = 6..8;
 
-    /// Reserved field
+    /// Format version and flags
     pub const RESERVED_0: Field = 8..10;
 
-    /// Reserved field
+    /// Format version, the first byte of `RESERVED_0`
+    pub const VERSION: usize = 8;
+
+    /// Flags, the second byte of `RESERVED_0`
+    pub const FLAGS: usize = 9;
+
+    /// Checksum of the preceding bytes, when the checksum flag is set
     pub const RESERVED_1: Field = 10..12;
 
     /// Remaining bytes
@@ -145,6 +152,28 @@ impl<T: AsRef<[u8]>> WireCausalSnapshot<T> {
         [field[0], field[1]]
     }
 
+    /// Return the snapshot format version, stored in the first byte
+    /// of the `reserved_0` field
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.buffer.as_ref()[field::VERSION]
+    }
+
+    /// Return the snapshot flags, stored in the second byte of the
+    /// `reserved_0` field
+    #[inline]
+    pub fn flags(&self) -> u8 {
+        self.buffer.as_ref()[field::FLAGS]
+    }
+
+    /// Compute the snapshot checksum: the low 16 bits of the CRC-32
+    /// of all the fields preceding `reserved_1`
+    #[inline]
+    pub fn compute_checksum(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        crc32(&data[..field::RESERVED_1.start]) as u16
+    }
+
     /// Return the `reserved_1` field
     #[inline]
     pub fn reserved_1(&self) -> [u8; 2] {
@@ -184,6 +213,18 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> WireCausalSnapshot<T> {
         data[field::RESERVED_0][1] = value[1];
     }
 
+    /// Set the snapshot format version
+    #[inline]
+    pub fn set_version(&mut self, value: u8) {
+        self.buffer.as_mut()[field::VERSION] = value;
+    }
+
+    /// Set the snapshot flags
+    #[inline]
+    pub fn set_flags(&mut self, value: u8) {
+        self.buffer.as_mut()[field::FLAGS] = value;
+    }
+
     /// Set the `reserved_1` field
     #[inline]
     pub fn set_reserved_1(&mut self, value: [u8; 2]) {
@@ -191,6 +232,14 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> WireCausalSnapshot<T> {
         data[field::RESERVED_1][0] = value[0];
         data[field::RESERVED_1][1] = value[1];
     }
+
+    /// Compute the snapshot checksum and store it in the `reserved_1`
+    /// field. The checksum flag must be set separately.
+    #[inline]
+    pub fn set_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        self.set_reserved_1(checksum.to_le_bytes());
+    }
 }
 
 impl<T: AsRef<[u8]>> AsRef<[u8]> for WireCausalSnapshot<T> {
@@ -200,6 +249,70 @@ impl<T: AsRef<[u8]>> AsRef<[u8]> for WireCausalSnapshot<T> {
 }
 
 impl CausalSnapshot {
+    /// Format version of snapshots produced before the format was
+    /// versioned, when the version and flags bytes were always zero.
+    pub const LEGACY_VERSION: u8 = 0;
+
+    /// Format version of the snapshots produced by this probe.
+    pub const VERSION: u8 = 1;
+
+    /// Flag set by producers of a newer snapshot format version to
+    /// signal that the snapshot's logical clock can still be merged by
+    /// probes that don't understand the rest of the format.
+    pub const FLAG_COMPATIBLE: u8 = 0b0000_0001;
+
+    /// Flag set when `reserved_1` holds a checksum of the rest of the
+    /// snapshot, see `CausalSnapshot::checksum`.
+    pub const FLAG_CHECKSUM: u8 = 0b0000_0010;
+
+    /// The snapshot format version
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.reserved_0[0]
+    }
+
+    /// The snapshot flags
+    #[inline]
+    pub fn flags(&self) -> u8 {
+        self.reserved_0[1]
+    }
+
+    /// Check whether this snapshot can be merged by this probe.
+    ///
+    /// Snapshots of a known version are accepted. Snapshots of a newer
+    /// version are merged on a best-effort basis, using only the
+    /// logical clock, if they have the `FLAG_COMPATIBLE` flag set,
+    /// and are otherwise rejected with
+    /// `MergeError::UnsupportedSnapshotVersion`.
+    #[inline]
+    pub fn check_version(&self) -> Result<(), MergeError> {
+        let version = self.version();
+        if version <= Self::VERSION || self.flags() & Self::FLAG_COMPATIBLE != 0 {
+            Ok(())
+        } else {
+            Err(MergeError::UnsupportedSnapshotVersion(version))
+        }
+    }
+
+    /// Compute the snapshot checksum: the low 16 bits of the CRC-32 of
+    /// the snapshot's little endian bytes preceding `reserved_1`.
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        WireCausalSnapshot::new_unchecked(self.to_le_bytes()).compute_checksum()
+    }
+
+    /// Check the snapshot's checksum, if it has one.
+    #[inline]
+    pub fn check_checksum(&self) -> Result<(), MergeError> {
+        if self.flags() & Self::FLAG_CHECKSUM != 0
+            && u16::from_le_bytes(self.reserved_1) != self.checksum()
+        {
+            Err(MergeError::SnapshotChecksumMismatch)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Construct a causal snapshot from a sequence of little endian bytes
     pub fn from_le_bytes(bytes: [u8; 12]) -> Result<Self, InvalidProbeId> {
         let snapshot = WireCausalSnapshot::new_unchecked(bytes);
@@ -405,9 +518,90 @@ mod tests {
         assert_eq!(s.ticks().0, 2);
         assert_eq!(s.epoch().0, 3);
         assert_eq!(s.reserved_0(), [0x04, 0x00]);
+        assert_eq!(s.version(), 0x04);
+        assert_eq!(s.flags(), 0x00);
         assert_eq!(s.reserved_1(), [0x05, 0x00]);
     }
 
+    #[test]
+    fn version_and_flags_share_reserved_0() {
+        let mut bytes = [0u8; 12];
+        let mut s = WireCausalSnapshot::new_unchecked(&mut bytes[..]);
+        s.set_version(CausalSnapshot::VERSION);
+        s.set_flags(CausalSnapshot::FLAG_COMPATIBLE);
+        assert_eq!(
+            s.reserved_0(),
+            [CausalSnapshot::VERSION, CausalSnapshot::FLAG_COMPATIBLE]
+        );
+    }
+
+    fn snapshot_with_reserved_0(reserved_0: [u8; 2]) -> CausalSnapshot {
+        CausalSnapshot {
+            clock: LogicalClock {
+                id: ProbeId::new(1).unwrap(),
+                epoch: ProbeEpoch(0),
+                ticks: ProbeTicks(0),
+            },
+            reserved_0,
+            reserved_1: [0, 0],
+        }
+    }
+
+    #[test]
+    fn check_version() {
+        // Snapshots from probes that predate versioning
+        let legacy = snapshot_with_reserved_0([0, 0]);
+        assert_eq!(legacy.version(), CausalSnapshot::LEGACY_VERSION);
+        assert_eq!(legacy.check_version(), Ok(()));
+
+        let current = snapshot_with_reserved_0([CausalSnapshot::VERSION, 0]);
+        assert_eq!(current.check_version(), Ok(()));
+
+        let newer = snapshot_with_reserved_0([CausalSnapshot::VERSION + 1, 0]);
+        assert_eq!(
+            newer.check_version(),
+            Err(MergeError::UnsupportedSnapshotVersion(
+                CausalSnapshot::VERSION + 1
+            ))
+        );
+
+        let newer_compatible = snapshot_with_reserved_0([
+            CausalSnapshot::VERSION + 1,
+            CausalSnapshot::FLAG_COMPATIBLE,
+        ]);
+        assert_eq!(newer_compatible.check_version(), Ok(()));
+    }
+
+    #[test]
+    fn check_checksum() {
+        let mut snap = snapshot_with_reserved_0([CausalSnapshot::VERSION, 0]);
+        // Without the flag, reserved_1 isn't interpreted
+        snap.reserved_1 = [0xAB, 0xCD];
+        assert_eq!(snap.check_checksum(), Ok(()));
+
+        snap.reserved_0[1] = CausalSnapshot::FLAG_CHECKSUM;
+        assert_eq!(
+            snap.check_checksum(),
+            Err(MergeError::SnapshotChecksumMismatch)
+        );
+        snap.reserved_1 = snap.checksum().to_le_bytes();
+        assert_eq!(snap.check_checksum(), Ok(()));
+
+        // The wire and struct checksums agree
+        let mut bytes = snap.to_le_bytes();
+        let mut wire = WireCausalSnapshot::new_unchecked(&mut bytes);
+        wire.set_reserved_1([0, 0]);
+        wire.set_checksum();
+        assert_eq!(wire.reserved_1(), snap.reserved_1);
+
+        // A flipped bit in the clock is detected
+        snap.clock.ticks.0 ^= 0x4;
+        assert_eq!(
+            snap.check_checksum(),
+            Err(MergeError::SnapshotChecksumMismatch)
+        );
+    }
+
     #[test]
     fn missing_bytes() {
         let bytes = [0xFF; 12 - 1];
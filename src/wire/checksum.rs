@@ -0,0 +1,49 @@
+//! Checksums used to detect corruption of reports and snapshots in transit
+
+/// Nibble-wise lookup table for the reflected CRC-32 (IEEE 802.3)
+/// polynomial `0xEDB88320`, trading a little speed for a table small
+/// enough for constrained targets.
+const CRC32_TABLE: [u32; 16] = [
+    0x0000_0000,
+    0x1DB7_1064,
+    0x3B6E_20C8,
+    0x26D9_30AC,
+    0x76DC_4190,
+    0x6B6B_51F4,
+    0x4DB2_6158,
+    0x5005_713C,
+    0xEDB8_8320,
+    0xF00F_9344,
+    0xD6D6_A3E8,
+    0xCB61_B38C,
+    0x9B64_C2B0,
+    0x86D3_D2D4,
+    0xA00A_E278,
+    0xBDBD_F21C,
+];
+
+/// Compute the CRC-32 (IEEE 802.3, as used by zlib and Ethernet) of `bytes`
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= u32::from(*b);
+        crc = (crc >> 4) ^ CRC32_TABLE[(crc & 0xF) as usize];
+        crc = (crc >> 4) ^ CRC32_TABLE[(crc & 0xF) as usize];
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_values() {
+        assert_eq!(crc32(&[]), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+}
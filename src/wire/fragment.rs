@@ -0,0 +1,503 @@
+//! A wire protocol for splitting a Modality probe report into
+//! fragments small enough for MTU-limited transports.
+//!
+//! Each fragment carries a small header identifying the report it
+//! belongs to (probe id and the low bits of the report's sequence
+//! number) and its position within that report. Collectors
+//! concatenate the fragment payloads, in index order, to rebuild
+//! the original report bytes.
+
+use crate::{wire::le_bytes, ProbeId};
+
+use super::report::{ReportWireError, WireReport};
+
+/// Everything that can go wrong when attempting to interpret a
+/// report fragment from the wire representation, or to split a
+/// report into fragments
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum FragmentWireError {
+    /// The fingerprint didn't match expectations
+    InvalidFingerprint,
+    /// There weren't enough bytes for a full header
+    MissingHeader,
+    /// There weren't enough payload bytes (based on
+    /// expectations from inspecting the header).
+    IncompletePayload,
+    /// The probe id didn't follow the rules for being
+    /// a valid Modality probe-specifying ProbeId
+    InvalidProbeId(u32),
+    /// The fragment's index wasn't less than its fragment count
+    InvalidIndex,
+    /// The report to split wasn't a valid report
+    InvalidReport(ReportWireError),
+    /// The maximum fragment length leaves no room for a payload
+    FragmentLengthTooSmall,
+    /// The report would need more than `u8::MAX` fragments
+    TooManyFragments,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FragmentWireError {}
+
+impl core::fmt::Display for FragmentWireError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FragmentWireError::InvalidFingerprint => f.write_str("Invalid Fingerprint"),
+            FragmentWireError::MissingHeader => f.write_str("Missing Header"),
+            FragmentWireError::IncompletePayload => f.write_str("Incomplete Payload"),
+            FragmentWireError::InvalidProbeId(x) => write!(f, "Invalid Probe Id: 0x{:x}", x),
+            FragmentWireError::InvalidIndex => f.write_str("Invalid Fragment Index"),
+            FragmentWireError::InvalidReport(e) => write!(f, "Invalid Report: {}", e),
+            FragmentWireError::FragmentLengthTooSmall => f.write_str("Fragment Length Too Small"),
+            FragmentWireError::TooManyFragments => f.write_str("Too Many Fragments"),
+        }
+    }
+}
+
+impl From<ReportWireError> for FragmentWireError {
+    fn from(e: ReportWireError) -> Self {
+        FragmentWireError::InvalidReport(e)
+    }
+}
+
+/// A read/write wrapper around a report fragment buffer
+#[derive(Debug, Clone)]
+pub struct WireFragment<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+mod field {
+    type Field = ::core::ops::Range<usize>;
+    type Rest = ::core::ops::RangeFrom<usize>;
+
+    /// A magical (constant) value used as a hint about the data
+    /// encoded in this pile of bytes.
+    pub const FINGERPRINT: Field = 0..4;
+    /// A u32 representing the probe_id of the Modality probe instance
+    /// which produced the fragmented report.
+    pub const PROBE_ID: Field = 4..8;
+    /// The low 16 bits of the fragmented report's sequence number.
+    pub const SEQ_NUM: Field = 8..10;
+    /// The position of this fragment within the report.
+    pub const INDEX: usize = 10;
+    /// The number of fragments the report was split into.
+    pub const COUNT: usize = 11;
+    /// The number of report bytes carried by this fragment.
+    pub const PAYLOAD_LEN: Field = 12..14;
+    /// The fragment of the report's bytes.
+    pub const PAYLOAD: Rest = 14..;
+}
+
+impl<T: AsRef<[u8]>> WireFragment<T> {
+    /// Report fragment fingerprint (MRPF)
+    pub const FINGERPRINT: u32 = 0x4D_52_50_46;
+
+    /// Construct a fragment from a byte buffer
+    pub fn new_unchecked(buffer: T) -> WireFragment<T> {
+        WireFragment { buffer }
+    }
+
+    /// Construct a fragment from a byte buffer, with checks.
+    ///
+    /// A combination of:
+    /// * [new_unchecked](struct.WireFragment.html#method.new_unchecked)
+    /// * [check_len](struct.WireFragment.html#method.check_len)
+    /// * [check_fingerprint](struct.WireFragment.html#method.check_fingerprint)
+    /// * [check_payload_len](struct.WireFragment.html#method.check_payload_len)
+    /// * [check_index](struct.WireFragment.html#method.check_index)
+    pub fn new(buffer: T) -> Result<Self, FragmentWireError> {
+        let f = Self::new_unchecked(buffer);
+        f.check_len()?;
+        f.check_fingerprint()?;
+        f.check_payload_len()?;
+        f.check_index()?;
+        Ok(f)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    ///
+    /// Returns `Err(FragmentWireError::MissingHeader)` if the buffer
+    /// is too short.
+    pub fn check_len(&self) -> Result<(), FragmentWireError> {
+        let len = self.buffer.as_ref().len();
+        if len < field::PAYLOAD.start {
+            Err(FragmentWireError::MissingHeader)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check for the expected fingerprint value.
+    ///
+    /// Returns `Err(FragmentWireError::InvalidFingerprint)` if the fingerprint
+    /// does not match.
+    pub fn check_fingerprint(&self) -> Result<(), FragmentWireError> {
+        if self.fingerprint() != Self::FINGERPRINT {
+            Err(FragmentWireError::InvalidFingerprint)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Ensure the buffer holds as many payload bytes as the header says.
+    ///
+    /// Returns `Err(FragmentWireError::IncompletePayload)` if the buffer
+    /// is too short.
+    pub fn check_payload_len(&self) -> Result<(), FragmentWireError> {
+        let len = self.buffer.as_ref().len();
+        if len < field::PAYLOAD.start + self.payload_len() {
+            Err(FragmentWireError::IncompletePayload)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Ensure the fragment's index is within its fragment count.
+    ///
+    /// Returns `Err(FragmentWireError::InvalidIndex)` if it isn't.
+    pub fn check_index(&self) -> Result<(), FragmentWireError> {
+        if self.index() >= self.count() {
+            Err(FragmentWireError::InvalidIndex)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Consumes the fragment, returning the underlying buffer
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the length of a fragment header
+    pub fn header_len() -> usize {
+        field::PAYLOAD.start
+    }
+
+    /// Return the length of the whole fragment: header and payload
+    pub fn total_len(&self) -> usize {
+        field::PAYLOAD.start + self.payload_len()
+    }
+
+    /// Return the `fingerprint` field
+    #[inline]
+    pub fn fingerprint(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        le_bytes::read_u32(&data[field::FINGERPRINT])
+    }
+
+    /// Return the `probe_id` field
+    #[inline]
+    pub fn probe_id(&self) -> Result<ProbeId, FragmentWireError> {
+        let data = self.buffer.as_ref();
+        let raw_probe_id = le_bytes::read_u32(&data[field::PROBE_ID]);
+        match ProbeId::new(raw_probe_id) {
+            Some(id) => Ok(id),
+            None => Err(FragmentWireError::InvalidProbeId(raw_probe_id)),
+        }
+    }
+
+    /// Return the `seq_num` field, the low 16 bits of the report's
+    /// sequence number
+    #[inline]
+    pub fn seq_num(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        le_bytes::read_u16(&data[field::SEQ_NUM])
+    }
+
+    /// Return the `index` field
+    #[inline]
+    pub fn index(&self) -> u8 {
+        self.buffer.as_ref()[field::INDEX]
+    }
+
+    /// Return the `count` field
+    #[inline]
+    pub fn count(&self) -> u8 {
+        self.buffer.as_ref()[field::COUNT]
+    }
+
+    /// Return the `payload_len` field
+    #[inline]
+    pub fn payload_len(&self) -> usize {
+        let data = self.buffer.as_ref();
+        le_bytes::read_u16(&data[field::PAYLOAD_LEN]) as usize
+    }
+}
+
+impl<'a, T: AsRef<[u8]> + ?Sized> WireFragment<&'a T> {
+    /// Return a pointer to the payload
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        let data = self.buffer.as_ref();
+        &data[field::PAYLOAD.start..field::PAYLOAD.start + self.payload_len()]
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> WireFragment<T> {
+    /// Set the `fingerprint` field to
+    /// [Self::FINGERPRINT](struct.WireFragment.html#associatedconstant.FINGERPRINT)
+    #[inline]
+    pub fn set_fingerprint(&mut self) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(&mut data[field::FINGERPRINT], Self::FINGERPRINT);
+    }
+
+    /// Set the `probe_id` field
+    #[inline]
+    pub fn set_probe_id(&mut self, value: ProbeId) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(&mut data[field::PROBE_ID], value.get_raw());
+    }
+
+    /// Set the `seq_num` field
+    #[inline]
+    pub fn set_seq_num(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u16(&mut data[field::SEQ_NUM], value);
+    }
+
+    /// Set the `index` field
+    #[inline]
+    pub fn set_index(&mut self, value: u8) {
+        self.buffer.as_mut()[field::INDEX] = value;
+    }
+
+    /// Set the `count` field
+    #[inline]
+    pub fn set_count(&mut self, value: u8) {
+        self.buffer.as_mut()[field::COUNT] = value;
+    }
+
+    /// Set the `payload_len` field
+    #[inline]
+    pub fn set_payload_len(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u16(&mut data[field::PAYLOAD_LEN], value);
+    }
+
+    /// Return a mutable pointer to the payload
+    #[inline]
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer.as_mut()[field::PAYLOAD.start..]
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for WireFragment<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// Splits the bytes of a report, as produced by `ModalityProbe::report`,
+/// into fragments no longer than a maximum fragment length.
+///
+/// ```ignore
+/// let n_bytes = probe.report(&mut report_buffer)?;
+/// let mut fragmenter = ReportFragmenter::new(&report_buffer[..n_bytes], MTU)?;
+/// let mut frame = [0u8; MTU];
+/// while let Some(len) = fragmenter.next_fragment(&mut frame) {
+///     transmit(&frame[..len]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReportFragmenter<'a> {
+    report: &'a [u8],
+    probe_id: ProbeId,
+    seq_num: u16,
+    max_payload_len: usize,
+    count: u8,
+    next_index: u8,
+}
+
+impl<'a> ReportFragmenter<'a> {
+    /// Prepare to split `report` into fragments of at most
+    /// `max_fragment_len` bytes each.
+    pub fn new(report: &'a [u8], max_fragment_len: usize) -> Result<Self, FragmentWireError> {
+        let wire_report = WireReport::new(report)?;
+        let report = &report[..wire_report.total_len()];
+        let max_payload_len = max_fragment_len
+            .saturating_sub(field::PAYLOAD.start)
+            .min(u16::MAX as usize);
+        if max_payload_len == 0 {
+            return Err(FragmentWireError::FragmentLengthTooSmall);
+        }
+        let count = report.len().div_ceil(max_payload_len);
+        if count > u8::MAX as usize {
+            return Err(FragmentWireError::TooManyFragments);
+        }
+        Ok(ReportFragmenter {
+            report,
+            probe_id: wire_report.probe_id()?,
+            seq_num: wire_report.seq_num() as u16,
+            max_payload_len,
+            count: count as u8,
+            next_index: 0,
+        })
+    }
+
+    /// The number of fragments the report is split into
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Write the next fragment into `destination`, returning its
+    /// length, or `None` once all fragments have been written.
+    ///
+    /// `destination` must be at least as long as the maximum
+    /// fragment length the fragmenter was created with, or as the
+    /// remaining report bytes plus a fragment header.
+    pub fn next_fragment(&mut self, destination: &mut [u8]) -> Option<usize> {
+        let len = self.fragment(self.next_index, destination)?;
+        self.next_index += 1;
+        Some(len)
+    }
+
+    /// Write the fragment at `index` into `destination`, returning
+    /// its length, or `None` if there is no such fragment or
+    /// `destination` is too short to hold it.
+    pub fn fragment(&self, index: u8, destination: &mut [u8]) -> Option<usize> {
+        if index >= self.count {
+            return None;
+        }
+        let start = index as usize * self.max_payload_len;
+        let end = (start + self.max_payload_len).min(self.report.len());
+        let payload_len = end - start;
+        if destination.len() < field::PAYLOAD.start + payload_len {
+            return None;
+        }
+        let mut f = WireFragment::new_unchecked(destination);
+        f.set_fingerprint();
+        f.set_probe_id(self.probe_id);
+        f.set_seq_num(self.seq_num);
+        f.set_index(index);
+        f.set_count(self.count);
+        f.set_payload_len(payload_len as u16);
+        f.payload_mut()[..payload_len].copy_from_slice(&self.report[start..end]);
+        Some(field::PAYLOAD.start + payload_len)
+    }
+}
+
+/// C function type for sending a report fragment, called with the
+/// fragment bytes and user state
+#[allow(non_camel_case_types)]
+pub type report_fragment_fn =
+    extern "C" fn(fragment: *const u8, fragment_size: usize, state: *mut core::ffi::c_void);
+
+/// Return the largest report, in bytes, that can be split into
+/// fragments of at most `max_fragment_len` bytes each.
+pub fn max_fragmented_report_len(max_fragment_len: usize) -> usize {
+    max_fragment_len
+        .saturating_sub(field::PAYLOAD.start)
+        .min(u16::MAX as usize)
+        * u8::MAX as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    static REPORT_BYTES: [u8; 61] = [
+        // fingerprint
+        0x54, 0x50, 0x52, 0x4D,
+        // probe_id: 1
+        0x01, 0x00, 0x00, 0x00,
+        // clock: 2
+        0x02, 0x00, 0x00, 0x00,
+        // seq_id: 0x1_0008
+        0x08, 0x00, 0x01, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        // persistent_epoch_counting: 0
+        0x00,
+        // time_resolution: 255
+        0xFF, 0x00, 0x00, 0x00,
+        // wall_clock_id: 1
+        0x01, 0x00,
+        // n_clocks: 2
+        0x02, 0x00,
+        // n_log_entries: 3
+        0x03, 0x00, 0x00, 0x00,
+        // payload
+        0x01, 0x00, 0x00, 0x00,
+        0x02, 0x00, 0x00, 0x00,
+        0x03, 0x00, 0x00, 0x00,
+        0x04, 0x00, 0x00, 0x00,
+        0x05, 0x00, 0x00, 0x00,
+        0x06, 0x00, 0x00, 0x00,
+        0x07, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn split_and_rejoin() {
+        const MAX_FRAGMENT_LEN: usize = 30;
+        let mut fragmenter = ReportFragmenter::new(&REPORT_BYTES, MAX_FRAGMENT_LEN).unwrap();
+        assert_eq!(fragmenter.count(), 4);
+
+        let mut frame = [0u8; MAX_FRAGMENT_LEN];
+        let mut rejoined = Vec::new();
+        let mut index = 0;
+        while let Some(len) = fragmenter.next_fragment(&mut frame) {
+            assert!(len <= MAX_FRAGMENT_LEN);
+            let f = WireFragment::new(&frame[..len]).unwrap();
+            assert_eq!(f.probe_id().unwrap().get_raw(), 1);
+            assert_eq!(f.seq_num(), 8);
+            assert_eq!(f.index(), index);
+            assert_eq!(f.count(), 4);
+            assert_eq!(f.total_len(), len);
+            rejoined.extend_from_slice(f.payload());
+            index += 1;
+        }
+        assert_eq!(index, 4);
+        assert_eq!(&rejoined[..], &REPORT_BYTES[..]);
+    }
+
+    #[test]
+    fn fragment_checks() {
+        let mut frame = [0u8; 80];
+        let fragmenter = ReportFragmenter::new(&REPORT_BYTES, frame.len()).unwrap();
+        assert_eq!(fragmenter.count(), 1);
+        let len = fragmenter.fragment(0, &mut frame).unwrap();
+        assert_eq!(
+            len,
+            WireFragment::<&[u8]>::header_len() + REPORT_BYTES.len()
+        );
+        assert!(fragmenter.fragment(1, &mut frame).is_none());
+        assert!(fragmenter.fragment(0, &mut frame[..20]).is_none());
+
+        assert_eq!(
+            WireFragment::new(&frame[..len - 1]).unwrap_err(),
+            FragmentWireError::IncompletePayload
+        );
+        assert_eq!(
+            WireFragment::new(&frame[..10]).unwrap_err(),
+            FragmentWireError::MissingHeader
+        );
+        assert_eq!(
+            WireFragment::new(&REPORT_BYTES[..]).unwrap_err(),
+            FragmentWireError::InvalidFingerprint
+        );
+        WireFragment::new_unchecked(&mut frame[..]).set_index(1);
+        assert_eq!(
+            WireFragment::new(&frame[..len]).unwrap_err(),
+            FragmentWireError::InvalidIndex
+        );
+    }
+
+    #[test]
+    fn fragmenter_limits() {
+        assert_eq!(
+            ReportFragmenter::new(&REPORT_BYTES, 14).unwrap_err(),
+            FragmentWireError::FragmentLengthTooSmall
+        );
+        assert_eq!(
+            ReportFragmenter::new(&REPORT_BYTES[..40], 64).unwrap_err(),
+            FragmentWireError::InvalidReport(ReportWireError::IncompletePayload)
+        );
+        assert_eq!(max_fragmented_report_len(15), 255);
+        assert_eq!(
+            ReportFragmenter::new(&REPORT_BYTES, 15).unwrap().count(),
+            61
+        );
+        assert_eq!(max_fragmented_report_len(14), 0);
+    }
+}
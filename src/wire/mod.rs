@@ -3,10 +3,15 @@
 use crate::{MergeError, ProduceError};
 
 pub mod causal_snapshot;
+pub mod checksum;
+pub mod fragment;
 pub mod report;
+pub mod report_ack;
 
 pub use causal_snapshot::*;
+pub use fragment::*;
 pub use report::*;
+pub use report_ack::*;
 
 impl From<MissingBytes> for ProduceError {
     #[inline]
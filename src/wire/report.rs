@@ -5,7 +5,7 @@
 use crate::{
     log::LogEntry,
     time::{NanosecondResolution, WallClockId},
-    wire::le_bytes,
+    wire::{checksum::crc32, le_bytes},
     LogicalClock, ProbeId,
 };
 use core::mem;
@@ -27,6 +27,8 @@ pub enum ReportWireError {
     /// The probe id didn't follow the rules for being
     /// a valid Modality probe-specifying ProbeId
     InvalidProbeId(u32),
+    /// The report's CRC-32 trailer didn't match its contents
+    ChecksumMismatch,
 }
 
 #[cfg(feature = "std")]
@@ -39,6 +41,7 @@ impl core::fmt::Display for ReportWireError {
             ReportWireError::MissingHeader => f.write_str("Missing Header"),
             ReportWireError::IncompletePayload => f.write_str("Incomplete Payload"),
             ReportWireError::InvalidProbeId(x) => write!(f, "Invalid Probe Id: 0x{:x}", x),
+            ReportWireError::ChecksumMismatch => f.write_str("Checksum Mismatch"),
         }
     }
 }
@@ -80,6 +83,10 @@ mod field {
     /// The payload, consists of (in order):
     /// * Frontier clocks
     /// * Log entries
+    ///
+    /// Reports with the
+    /// [FINGERPRINT_WITH_CHECKSUM](struct.WireReport.html#associatedconstant.FINGERPRINT_WITH_CHECKSUM)
+    /// fingerprint are followed by a u32 CRC-32 of all the preceding bytes.
     pub const PAYLOAD: Rest = 33..;
 }
 
@@ -87,6 +94,12 @@ impl<T: AsRef<[u8]>> WireReport<T> {
     /// Report fingerprint (MRPT)
     pub const FINGERPRINT: u32 = 0x4D_52_50_54;
 
+    /// Fingerprint of a report followed by a CRC-32 trailer (MRPC)
+    pub const FINGERPRINT_WITH_CHECKSUM: u32 = 0x4D_52_50_43;
+
+    /// Length of the CRC-32 trailer
+    pub const CHECKSUM_LEN: usize = mem::size_of::<u32>();
+
     /// Construct a report from a byte buffer
     pub fn new_unchecked(buffer: T) -> WireReport<T> {
         WireReport { buffer }
@@ -99,11 +112,13 @@ impl<T: AsRef<[u8]>> WireReport<T> {
     /// * [check_len](struct.WireReport.html#method.check_len)
     /// * [check_fingerprint](struct.WireReport.html#method.check_fingerprint)
     /// * [check_payload_len](struct.WireReport.html#method.check_payload_len)
+    /// * [check_checksum](struct.WireReport.html#method.check_checksum)
     pub fn new(buffer: T) -> Result<Self, ReportWireError> {
         let r = Self::new_unchecked(buffer);
         r.check_len()?;
         r.check_fingerprint()?;
         r.check_payload_len()?;
+        r.check_checksum()?;
         Ok(r)
     }
 
@@ -125,28 +140,38 @@ impl<T: AsRef<[u8]>> WireReport<T> {
     /// Returns `Err(ReportWireError::InvalidFingerprint)` if the fingerprint
     /// does not match.
     pub fn check_fingerprint(&self) -> Result<(), ReportWireError> {
-        if self.fingerprint() != Self::FINGERPRINT {
-            Err(ReportWireError::InvalidFingerprint)
-        } else {
-            Ok(())
+        match self.fingerprint() {
+            Self::FINGERPRINT | Self::FINGERPRINT_WITH_CHECKSUM => Ok(()),
+            _ => Err(ReportWireError::InvalidFingerprint),
         }
     }
 
     /// Ensure the payload size is sufficient to hold bytes according to the header
-    /// fields `n_clocks` and `n_log_entries`.
+    /// fields `n_clocks` and `n_log_entries`, plus the checksum trailer if present.
     ///
     /// Returns `Err(ReportWireError::IncompletePayload)` if the buffer
     /// is too short.
     pub fn check_payload_len(&self) -> Result<(), ReportWireError> {
-        let payload_len = self.payload_len();
         let len = self.buffer.as_ref().len();
-        if len < (field::PAYLOAD.start + payload_len) {
+        if len < self.total_len() {
             Err(ReportWireError::IncompletePayload)
         } else {
             Ok(())
         }
     }
 
+    /// Verify the CRC-32 trailer, if the report has one.
+    ///
+    /// Returns `Err(ReportWireError::ChecksumMismatch)` if the
+    /// trailer doesn't match the report's contents.
+    pub fn check_checksum(&self) -> Result<(), ReportWireError> {
+        if self.has_checksum() && self.checksum() != self.compute_checksum() {
+            Err(ReportWireError::ChecksumMismatch)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Consumes the report, returning the underlying buffer
     pub fn into_inner(self) -> T {
         self.buffer
@@ -165,6 +190,17 @@ impl<T: AsRef<[u8]>> WireReport<T> {
             + (n_log_entries * mem::size_of::<LogEntry>())
     }
 
+    /// Return the length of the whole report: header, payload and
+    /// checksum trailer if present
+    pub fn total_len(&self) -> usize {
+        let trailer_len = if self.has_checksum() {
+            Self::CHECKSUM_LEN
+        } else {
+            0
+        };
+        field::PAYLOAD.start + self.payload_len() + trailer_len
+    }
+
     /// Return the length of the report payload
     pub fn payload_len(&self) -> usize {
         let n_clock_bytes = self.n_clocks() as usize * mem::size_of::<LogicalClock>();
@@ -179,6 +215,27 @@ impl<T: AsRef<[u8]>> WireReport<T> {
         le_bytes::read_u32(&data[field::FINGERPRINT])
     }
 
+    /// Whether the report is followed by a CRC-32 trailer
+    #[inline]
+    pub fn has_checksum(&self) -> bool {
+        self.fingerprint() == Self::FINGERPRINT_WITH_CHECKSUM
+    }
+
+    /// Return the CRC-32 trailer
+    #[inline]
+    pub fn checksum(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        let start = field::PAYLOAD.start + self.payload_len();
+        le_bytes::read_u32(&data[start..start + Self::CHECKSUM_LEN])
+    }
+
+    /// Compute the CRC-32 of the header and payload
+    #[inline]
+    pub fn compute_checksum(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        crc32(&data[..field::PAYLOAD.start + self.payload_len()])
+    }
+
     /// Return the `probe_id` field
     #[inline]
     pub fn probe_id(&self) -> Result<ProbeId, ReportWireError> {
@@ -265,6 +322,27 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> WireReport<T> {
         le_bytes::write_u32(&mut data[field::FINGERPRINT], Self::FINGERPRINT);
     }
 
+    /// Set the `fingerprint` field to
+    /// [Self::FINGERPRINT_WITH_CHECKSUM](struct.WireReport.html#associatedconstant.FINGERPRINT_WITH_CHECKSUM)
+    #[inline]
+    pub fn set_fingerprint_with_checksum(&mut self) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(
+            &mut data[field::FINGERPRINT],
+            Self::FINGERPRINT_WITH_CHECKSUM,
+        );
+    }
+
+    /// Compute the CRC-32 of the header and payload and write it to
+    /// the trailer. The buffer must have room for the trailer.
+    #[inline]
+    pub fn set_checksum(&mut self) {
+        let value = self.compute_checksum();
+        let start = field::PAYLOAD.start + self.payload_len();
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(&mut data[start..start + Self::CHECKSUM_LEN], value);
+    }
+
     /// Set the `probe_id` field
     #[inline]
     pub fn set_probe_id(&mut self, value: ProbeId) {
@@ -430,7 +508,7 @@ mod tests {
         assert_eq!(r.probe_id().unwrap().get_raw(), 1);
         assert_eq!(r.clock(), 2);
         assert_eq!(r.seq_num(), 8);
-        assert_eq!(r.persistent_epoch_counting(), false);
+        assert!(!r.persistent_epoch_counting());
         assert_eq!(r.time_resolution(), 255.into());
         assert_eq!(r.wall_clock_id(), 1.into());
         assert_eq!(r.n_clocks(), 2);
@@ -472,7 +550,7 @@ mod tests {
             r.set_n_clocks(2);
             r.set_n_log_entries(3);
             let payload_len = r.payload_len();
-            (&mut r.payload_mut()[..payload_len]).copy_from_slice(&PAYLOAD_BYTES[..]);
+            r.payload_mut()[..payload_len].copy_from_slice(&PAYLOAD_BYTES[..]);
             assert_eq!(r.check_fingerprint(), Ok(()));
             assert_eq!(r.check_payload_len(), Ok(()));
             WireReport::<&[u8]>::header_len() + payload_len
@@ -483,7 +561,7 @@ mod tests {
         assert_eq!(r.probe_id().unwrap().get_raw(), 100);
         assert_eq!(r.clock(), 0xAAAA_BBBB);
         assert_eq!(r.seq_num(), 123);
-        assert_eq!(r.persistent_epoch_counting(), true);
+        assert!(r.persistent_epoch_counting());
         assert_eq!(r.time_resolution(), 0xABAB.into());
         assert_eq!(r.wall_clock_id(), 0xFF.into());
         assert_eq!(r.n_clocks(), 2);
@@ -495,6 +573,43 @@ mod tests {
         assert_eq!(r.payload(), &PAYLOAD_BYTES[..]);
     }
 
+    #[test]
+    fn checksum_trailer() {
+        let mut bytes = [0xFF; 61 + 4];
+        bytes[..61].copy_from_slice(&MSG_BYTES[..]);
+        let mut r = WireReport::new_unchecked(&mut bytes[..]);
+        r.set_fingerprint_with_checksum();
+        assert!(r.has_checksum());
+        assert_eq!(r.total_len(), 61 + 4);
+        r.set_checksum();
+        let bytes = r.into_inner();
+
+        let r = WireReport::new(&bytes[..]).unwrap();
+        assert_eq!(
+            r.fingerprint(),
+            WireReport::<&[u8]>::FINGERPRINT_WITH_CHECKSUM
+        );
+        assert_eq!(r.checksum(), crc32(&bytes[..61]));
+        assert_eq!(&r.payload()[..r.payload_len()], &PAYLOAD_BYTES[..]);
+
+        // The trailer is required
+        let r = WireReport::new(&bytes[..61]);
+        assert_eq!(r.unwrap_err(), ReportWireError::IncompletePayload);
+
+        // A single flipped bit anywhere is detected
+        for idx in 0..bytes.len() {
+            let mut corrupted = [0u8; 61 + 4];
+            corrupted.copy_from_slice(&bytes[..]);
+            corrupted[idx] ^= 0x10;
+            assert!(WireReport::new(&corrupted[..]).is_err());
+        }
+        let mut corrupted = [0u8; 61 + 4];
+        corrupted.copy_from_slice(&bytes[..]);
+        corrupted[40] ^= 0x01;
+        let r = WireReport::new(&corrupted[..]);
+        assert_eq!(r.unwrap_err(), ReportWireError::ChecksumMismatch);
+    }
+
     #[test]
     fn invalid_fingerprint() {
         let bytes = [0xFF; 33];
@@ -512,7 +627,7 @@ mod tests {
 
     #[test]
     fn incomplete_payload() {
-        let mut bytes = MSG_BYTES.clone();
+        let mut bytes = MSG_BYTES;
         let mut r = WireReport::new(&mut bytes[..]).unwrap();
         r.set_n_clocks(2 + 1);
         r.set_n_log_entries(3 + 1);
@@ -523,7 +638,7 @@ mod tests {
 
     #[test]
     fn invalid_probe_id() {
-        let mut bytes = MSG_BYTES.clone();
+        let mut bytes = MSG_BYTES;
         le_bytes::write_u32(&mut bytes[field::PROBE_ID], 0);
         let r = WireReport::new(&bytes[..]).unwrap();
         assert_eq!(r.probe_id(), Err(ReportWireError::InvalidProbeId(0)));
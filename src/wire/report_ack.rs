@@ -0,0 +1,202 @@
+//! A wire protocol for collectors to acknowledge receipt of a
+//! Modality probe report
+
+use crate::{wire::le_bytes, ProbeId};
+
+/// Everything that can go wrong when attempting to interpret a report
+/// acknowledgement from the wire representation
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ReportAckWireError {
+    /// The fingerprint didn't match expectations
+    InvalidFingerprint,
+    /// There weren't enough bytes for a full acknowledgement
+    MissingBytes,
+    /// The probe id didn't follow the rules for being
+    /// a valid Modality probe-specifying ProbeId
+    InvalidProbeId(u32),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReportAckWireError {}
+
+impl core::fmt::Display for ReportAckWireError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReportAckWireError::InvalidFingerprint => f.write_str("Invalid Fingerprint"),
+            ReportAckWireError::MissingBytes => f.write_str("Missing Bytes"),
+            ReportAckWireError::InvalidProbeId(x) => write!(f, "Invalid Probe Id: 0x{:x}", x),
+        }
+    }
+}
+
+/// A read/write wrapper around a report acknowledgement buffer
+#[derive(Debug, Clone)]
+pub struct WireReportAck<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+mod field {
+    type Field = ::core::ops::Range<usize>;
+
+    /// A magical (constant) value used as a hint about the data
+    /// encoded in this pile of bytes.
+    pub const FINGERPRINT: Field = 0..4;
+    /// A u32 representing the probe_id of the Modality probe instance
+    /// which produced the acknowledged report.
+    pub const PROBE_ID: Field = 4..8;
+    /// The sequence number of the acknowledged report.
+    pub const SEQ_NUM: Field = 8..16;
+}
+
+impl<T: AsRef<[u8]>> WireReportAck<T> {
+    /// Report acknowledgement fingerprint (MRPA)
+    pub const FINGERPRINT: u32 = 0x4D_52_50_41;
+
+    /// Construct an acknowledgement from a byte buffer
+    pub fn new_unchecked(buffer: T) -> WireReportAck<T> {
+        WireReportAck { buffer }
+    }
+
+    /// Construct an acknowledgement from a byte buffer, with checks.
+    ///
+    /// A combination of:
+    /// * [new_unchecked](struct.WireReportAck.html#method.new_unchecked)
+    /// * [check_len](struct.WireReportAck.html#method.check_len)
+    /// * [check_fingerprint](struct.WireReportAck.html#method.check_fingerprint)
+    pub fn new(buffer: T) -> Result<Self, ReportAckWireError> {
+        let a = Self::new_unchecked(buffer);
+        a.check_len()?;
+        a.check_fingerprint()?;
+        Ok(a)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    ///
+    /// Returns `Err(ReportAckWireError::MissingBytes)` if the buffer
+    /// is too short.
+    pub fn check_len(&self) -> Result<(), ReportAckWireError> {
+        if self.buffer.as_ref().len() < Self::buffer_len() {
+            Err(ReportAckWireError::MissingBytes)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check for the expected fingerprint value.
+    ///
+    /// Returns `Err(ReportAckWireError::InvalidFingerprint)` if the fingerprint
+    /// does not match.
+    pub fn check_fingerprint(&self) -> Result<(), ReportAckWireError> {
+        if self.fingerprint() != Self::FINGERPRINT {
+            Err(ReportAckWireError::InvalidFingerprint)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Consumes the acknowledgement, returning the underlying buffer
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the length of an acknowledgement
+    pub fn buffer_len() -> usize {
+        field::SEQ_NUM.end
+    }
+
+    /// Return the `fingerprint` field
+    #[inline]
+    pub fn fingerprint(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        le_bytes::read_u32(&data[field::FINGERPRINT])
+    }
+
+    /// Return the `probe_id` field
+    #[inline]
+    pub fn probe_id(&self) -> Result<ProbeId, ReportAckWireError> {
+        let data = self.buffer.as_ref();
+        let raw_probe_id = le_bytes::read_u32(&data[field::PROBE_ID]);
+        match ProbeId::new(raw_probe_id) {
+            Some(id) => Ok(id),
+            None => Err(ReportAckWireError::InvalidProbeId(raw_probe_id)),
+        }
+    }
+
+    /// Return the `seq_num` field
+    #[inline]
+    pub fn seq_num(&self) -> u64 {
+        let data = self.buffer.as_ref();
+        le_bytes::read_u64(&data[field::SEQ_NUM])
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> WireReportAck<T> {
+    /// Set the `fingerprint` field to
+    /// [Self::FINGERPRINT](struct.WireReportAck.html#associatedconstant.FINGERPRINT)
+    #[inline]
+    pub fn set_fingerprint(&mut self) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(&mut data[field::FINGERPRINT], Self::FINGERPRINT);
+    }
+
+    /// Set the `probe_id` field
+    #[inline]
+    pub fn set_probe_id(&mut self, value: ProbeId) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u32(&mut data[field::PROBE_ID], value.get_raw());
+    }
+
+    /// Set the `seq_num` field
+    #[inline]
+    pub fn set_seq_num(&mut self, value: u64) {
+        let data = self.buffer.as_mut();
+        le_bytes::write_u64(&mut data[field::SEQ_NUM], value);
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for WireReportAck<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    static ACK_BYTES: [u8; 16] = [
+        // fingerprint
+        0x41, 0x50, 0x52, 0x4D,
+        // probe_id: 1
+        0x01, 0x00, 0x00, 0x00,
+        // seq_num: 8
+        0x08, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn construct() {
+        let mut bytes = [0xFF; 16];
+        let mut a = WireReportAck::new_unchecked(&mut bytes[..]);
+        assert_eq!(
+            a.check_fingerprint(),
+            Err(ReportAckWireError::InvalidFingerprint)
+        );
+        a.set_fingerprint();
+        a.set_probe_id(ProbeId::new(1).unwrap());
+        a.set_seq_num(8);
+        assert_eq!(&a.into_inner()[..], &ACK_BYTES[..]);
+    }
+
+    #[test]
+    fn deconstruct() {
+        let a = WireReportAck::new(&ACK_BYTES[..]).unwrap();
+        assert_eq!(a.probe_id().unwrap().get_raw(), 1);
+        assert_eq!(a.seq_num(), 8);
+        assert_eq!(
+            WireReportAck::new(&ACK_BYTES[..15]).unwrap_err(),
+            ReportAckWireError::MissingBytes
+        );
+    }
+}
@@ -101,6 +101,342 @@ fn round_trip_merge_snapshot() -> Result<(), ModalityProbeError> {
     Ok(())
 }
 
+#[test]
+fn merge_snapshot_versions() -> Result<(), ModalityProbeError> {
+    let mut storage_foo = [MaybeUninit::new(0u8); 1024];
+    let probe_foo = ModalityProbe::initialize_at(
+        &mut storage_foo,
+        1.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    let mut storage_bar = [MaybeUninit::new(0u8); 1024];
+    let probe_bar = ModalityProbe::initialize_at(
+        &mut storage_bar,
+        2.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+
+    // Snapshots carry the current format version
+    let mut bytes = [0u8; 12];
+    probe_foo.produce_snapshot_bytes(&mut bytes)?;
+    let snap = CausalSnapshot::try_from(&bytes[..]).unwrap();
+    assert_eq!(snap.version(), CausalSnapshot::VERSION);
+    assert_eq!(snap.flags(), 0);
+    assert!(probe_bar.merge_snapshot_bytes(&bytes).is_ok());
+
+    // Snapshots from probes that predate versioning are zeroed
+    // in the version and flags bytes
+    let mut legacy = probe_foo.produce_snapshot().to_le_bytes();
+    legacy[8] = 0;
+    legacy[9] = 0;
+    assert_eq!(
+        CausalSnapshot::try_from(&legacy[..]).unwrap().version(),
+        CausalSnapshot::LEGACY_VERSION
+    );
+    assert!(probe_bar.merge_snapshot_bytes(&legacy).is_ok());
+
+    // Newer versions are rejected unless flagged as compatible
+    let mut newer = probe_foo.produce_snapshot().to_le_bytes();
+    newer[8] = CausalSnapshot::VERSION + 1;
+    assert_eq!(
+        probe_bar.merge_snapshot_bytes(&newer),
+        Err(MergeError::UnsupportedSnapshotVersion(
+            CausalSnapshot::VERSION + 1
+        ))
+    );
+    let before = probe_bar.now();
+    newer[9] = CausalSnapshot::FLAG_COMPATIBLE;
+    assert!(probe_bar.merge_snapshot_bytes(&newer).is_ok());
+    assert!(before.clock < probe_bar.now().clock);
+
+    Ok(())
+}
+
+#[test]
+fn checksums() -> Result<(), ModalityProbeError> {
+    let mut storage_foo = [MaybeUninit::new(0u8); 1024];
+    let probe_foo = ModalityProbe::initialize_at(
+        &mut storage_foo,
+        1.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    probe_foo.set_checksums(true);
+    let mut storage_bar = [MaybeUninit::new(0u8); 1024];
+    let probe_bar = ModalityProbe::initialize_at(
+        &mut storage_bar,
+        2.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+
+    let snap = probe_foo.produce_snapshot();
+    assert_ne!(snap.flags() & CausalSnapshot::FLAG_CHECKSUM, 0);
+    assert!(probe_bar.merge_snapshot(&snap).is_ok());
+
+    let mut bytes = [0u8; 12];
+    probe_foo.produce_snapshot_bytes(&mut bytes)?;
+    assert!(probe_bar.merge_snapshot_bytes(&bytes).is_ok());
+    bytes[4] ^= 0x01;
+    assert_eq!(
+        probe_bar.merge_snapshot_bytes(&bytes),
+        Err(MergeError::SnapshotChecksumMismatch)
+    );
+
+    probe_foo.record_event(EventId::new(1).unwrap());
+    let mut report = [0u8; 1024];
+    let n_bytes = probe_foo.report(&mut report)?.unwrap().get();
+    let wire_report = wire::WireReport::new(&report[..n_bytes]).unwrap();
+    assert!(wire_report.has_checksum());
+    assert_eq!(wire_report.total_len(), n_bytes);
+    report[n_bytes - 5] ^= 0x01;
+    assert_eq!(
+        wire::WireReport::new(&report[..n_bytes]).unwrap_err(),
+        wire::ReportWireError::ChecksumMismatch
+    );
+
+    // Reports without checksums are unaffected
+    probe_bar.record_event(EventId::new(1).unwrap());
+    let n_bytes = probe_bar.report(&mut report)?.unwrap().get();
+    let wire_report = wire::WireReport::new(&report[..n_bytes]).unwrap();
+    assert!(!wire_report.has_checksum());
+    assert_eq!(wire_report.total_len(), n_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn reliable_reporting() -> Result<(), ModalityProbeError> {
+    let mut storage = [MaybeUninit::new(0u8); 1024];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        1.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    probe.set_report_acknowledgements(true);
+    for i in 1..=5 {
+        probe.record_event(EventId::new(i).unwrap());
+    }
+
+    let mut first = [0u8; 512];
+    let n_first = probe.report(&mut first)?.unwrap().get();
+    assert_eq!(
+        wire::WireReport::new(&first[..n_first]).unwrap().seq_num(),
+        0
+    );
+
+    // Until acknowledged, the same report is produced again
+    probe.record_event(EventId::new(6).unwrap());
+    let mut retransmitted = [0u8; 512];
+    let n_retransmitted = probe.report(&mut retransmitted)?.unwrap().get();
+    assert_eq!(&first[..n_first], &retransmitted[..n_retransmitted]);
+
+    let mut ack = [0u8; 16];
+    {
+        let mut a = wire::WireReportAck::new_unchecked(&mut ack[..]);
+        a.set_fingerprint();
+        a.set_probe_id(2.try_into()?);
+        a.set_seq_num(0);
+    }
+    // Acknowledgements for other probes or reports are ignored
+    assert_eq!(probe.consume_report_ack(&ack), Ok(false));
+    assert!(!probe.acknowledge_report(1));
+    assert_eq!(
+        probe.consume_report_ack(&ack[..8]),
+        Err(wire::ReportAckWireError::MissingBytes)
+    );
+    wire::WireReportAck::new_unchecked(&mut ack[..]).set_probe_id(1.try_into()?);
+    assert_eq!(probe.consume_report_ack(&ack), Ok(true));
+    assert_eq!(probe.consume_report_ack(&ack), Ok(false));
+
+    // The next report carries what was recorded since
+    let mut second = [0u8; 512];
+    let n_second = probe.report(&mut second)?.unwrap().get();
+    let second_report = wire::WireReport::new(&second[..n_second]).unwrap();
+    assert_eq!(second_report.seq_num(), 1);
+    assert_ne!(&first[..n_first], &second[..n_second]);
+    assert!(probe.acknowledge_report(1));
+
+    // Only the report production event is left
+    assert_eq!(probe.report(&mut second)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn recover_after_warm_reset() -> Result<(), ModalityProbeError> {
+    fn report_log_words(probe: &mut ModalityProbe) -> (u64, Vec<u32>) {
+        let mut report_dest = [0u8; 1024];
+        let bytes_written = probe.report(&mut report_dest).unwrap().unwrap();
+        let report = wire::WireReport::new(&report_dest[..bytes_written.get()]).unwrap();
+        let offset = report.n_clocks() as usize * mem::size_of::<LogicalClock>();
+        let words = report.payload()[offset..]
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        (report.seq_num(), words)
+    }
+
+    let probe_id = 1.try_into()?;
+    let mut storage = [MaybeUninit::new(0u8); 1024];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        probe_id,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    probe.record_event(EventId::new(100).unwrap());
+    assert_eq!(report_log_words(probe).0, 0);
+    for i in 101..=105 {
+        probe.record_event(EventId::new(i).unwrap());
+    }
+
+    // After a warm reset, the unreported events are still there
+    let probe = unsafe {
+        ModalityProbe::initialize_or_recover_at(
+            &mut storage,
+            probe_id,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )?
+    };
+    assert_eq!(probe.now().clock.epoch, ProbeEpoch(1));
+    let (seq_num, words) = report_log_words(probe);
+    assert_eq!(seq_num, 1);
+    for i in 101..=105 {
+        assert!(words.contains(&i));
+    }
+    assert!(words.contains(&EventId::EVENT_PROBE_RECOVERED.get_raw()));
+    assert!(!words.contains(&EventId::EVENT_PROBE_INITIALIZED.get_raw()));
+    assert!(!words.contains(&100));
+
+    // A different probe starts afresh
+    probe.record_event(EventId::new(106).unwrap());
+    let probe = unsafe {
+        ModalityProbe::initialize_or_recover_at(
+            &mut storage,
+            2.try_into()?,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )?
+    };
+    assert_eq!(probe.now().clock.epoch, ProbeEpoch(0));
+    let (seq_num, words) = report_log_words(probe);
+    assert_eq!(seq_num, 0);
+    assert!(words.contains(&EventId::EVENT_PROBE_INITIALIZED.get_raw()));
+    assert!(!words.contains(&106));
+
+    // So does a probe in memory cleared at boot
+    probe.record_event(EventId::new(107).unwrap());
+    for b in storage.iter_mut() {
+        *b = MaybeUninit::new(0);
+    }
+    let probe = unsafe {
+        ModalityProbe::initialize_or_recover_at(
+            &mut storage,
+            2.try_into()?,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )?
+    };
+    let (_, words) = report_log_words(probe);
+    assert!(words.contains(&EventId::EVENT_PROBE_INITIALIZED.get_raw()));
+    assert!(!words.contains(&107));
+
+    // And so does a probe whose log holds a corrupted clock entry
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        2.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    probe.record_event(EventId::new(108).unwrap());
+    let clock_word = (0x8000_0000u32 | 2).to_le_bytes();
+    let clock_word_index = (0..storage.len() - 4)
+        .find(|&i| (0..4).all(|j| unsafe { storage[i + j].assume_init() } == clock_word[j]))
+        .expect("log holds the probe's clock");
+    for (j, b) in 0x8000_0000u32.to_le_bytes().iter().enumerate() {
+        storage[clock_word_index + j] = MaybeUninit::new(*b);
+    }
+    let probe = unsafe {
+        ModalityProbe::initialize_or_recover_at(
+            &mut storage,
+            2.try_into()?,
+            NanosecondResolution::UNSPECIFIED,
+            WallClockId::local_only(),
+            RestartCounterProvider::NoRestartTracking,
+        )?
+    };
+    assert_eq!(probe.now().clock.epoch, ProbeEpoch(0));
+    let (_, words) = report_log_words(probe);
+    assert!(words.contains(&EventId::EVENT_PROBE_INITIALIZED.get_raw()));
+    assert!(!words.contains(&108));
+    assert!(!words.contains(&0x8000_0000));
+
+    Ok(())
+}
+
+#[test]
+fn fragmented_report() -> Result<(), ModalityProbeError> {
+    let mut storage = [MaybeUninit::new(0u8); 1024];
+    let probe = ModalityProbe::initialize_at(
+        &mut storage,
+        1.try_into()?,
+        NanosecondResolution::UNSPECIFIED,
+        WallClockId::local_only(),
+        RestartCounterProvider::NoRestartTracking,
+    )?;
+    for i in 1..=20 {
+        probe.record_event(EventId::new(i).unwrap());
+    }
+
+    let mut report = [0u8; 512];
+    let mut fragment = [0u8; 64];
+    let mut fragments: Vec<Vec<u8>> = Vec::new();
+    let n_fragments = probe
+        .report_fragmented(&mut report, &mut fragment, |f| fragments.push(f.to_vec()))?
+        .unwrap()
+        .get();
+    assert!(n_fragments > 1);
+    assert_eq!(fragments.len(), n_fragments);
+
+    let mut rejoined = Vec::new();
+    for (index, f) in fragments.iter().enumerate() {
+        assert!(f.len() <= fragment.len());
+        let wire_fragment = wire::WireFragment::new(&f[..]).unwrap();
+        assert_eq!(wire_fragment.index() as usize, index);
+        assert_eq!(wire_fragment.count() as usize, n_fragments);
+        rejoined.extend_from_slice(wire_fragment.payload());
+    }
+    let wire_report = wire::WireReport::new(&rejoined[..]).unwrap();
+    assert_eq!(wire_report.total_len(), rejoined.len());
+
+    // Nothing left to report
+    assert_eq!(
+        probe.report_fragmented(&mut report, &mut fragment, |_| panic!())?,
+        None
+    );
+    assert_eq!(
+        probe.report_fragmented(&mut report, &mut fragment[..14], |_| panic!()),
+        Err(ReportError::InsufficientDestinationSize)
+    );
+
+    Ok(())
+}
+
 #[test]
 fn happy_path_backend_service() -> Result<(), ModalityProbeError> {
     let mut storage_foo = [MaybeUninit::new(0u8); 1024];
@@ -176,10 +512,10 @@ fn all_allowed_events() -> Result<(), ModalityProbeError> {
     assert!(EventId::new(EventId::MAX_USER_ID + 1).is_none());
     assert!(EventId::new(EventId::MAX_USER_ID + 2).is_none());
     assert!(EventId::new(EventId::MAX_INTERNAL_ID).is_none());
-    assert_eq!(EventId::MAX_INTERNAL_ID, (core::u32::MAX / 4));
+    assert_eq!(EventId::MAX_INTERNAL_ID, (u32::MAX / 4));
     assert_eq!(
         EventId::MAX_USER_ID,
-        (core::u32::MAX / 4) - EventId::NUM_RESERVED_IDS
+        (u32::MAX / 4) - EventId::NUM_RESERVED_IDS
     );
 
     assert_eq!(
@@ -287,7 +623,7 @@ fn report_buffer_too_small_error() -> Result<(), ModalityProbeError> {
 
 #[test]
 fn report_missed_log_items() -> Result<(), ModalityProbeError> {
-    const NUM_STORAGE_BYTES: usize = 512;
+    const NUM_STORAGE_BYTES: usize = 584;
     let mut storage = [MaybeUninit::new(0u8); NUM_STORAGE_BYTES];
     let probe = ModalityProbe::try_initialize_at(
         &mut storage,
@@ -330,14 +666,14 @@ fn report_missed_log_items() -> Result<(), ModalityProbeError> {
 
         if i == 0 {
             #[cfg(target_pointer_width = "64")]
-            assert_eq!(raw_payload, 949);
+            assert_eq!(raw_payload, 1093);
             #[cfg(target_pointer_width = "32")]
-            assert_eq!(raw_payload, 941);
+            assert_eq!(raw_payload, 1085);
         } else {
             #[cfg(target_pointer_width = "64")]
-            assert_eq!(raw_payload, 947);
+            assert_eq!(raw_payload, 1091);
             #[cfg(target_pointer_width = "32")]
-            assert_eq!(raw_payload, 939);
+            assert_eq!(raw_payload, 1083);
         }
     }
 
@@ -543,7 +879,7 @@ fn persistent_restart_sequence_id() -> Result<(), ModalityProbeError> {
         let mut report_dest = [0u8; 512];
         let bytes_written = probe.report(&mut report_dest)?.unwrap();
         let log_report = wire::WireReport::new(&report_dest[..bytes_written.get()]).unwrap();
-        assert_eq!(log_report.persistent_epoch_counting(), true);
+        assert!(log_report.persistent_epoch_counting());
     }
     assert_eq!(next_id_provider.next_seq_id, 101);
     assert_eq!(next_id_provider.count, 1);
@@ -570,7 +906,7 @@ fn persistent_restart_sequence_id() -> Result<(), ModalityProbeError> {
         let mut report_dest = [0u8; 512];
         let bytes_written = probe.report(&mut report_dest)?.unwrap();
         let log_report = wire::WireReport::new(&report_dest[..bytes_written.get()]).unwrap();
-        assert_eq!(log_report.persistent_epoch_counting(), true);
+        assert!(log_report.persistent_epoch_counting());
     }
     assert_eq!(next_id_provider.next_seq_id, 102);
     assert_eq!(next_id_provider.count, 2);